@@ -38,4 +38,36 @@ pub enum BridgeError {
     /// Used when the `payload` in a dispatch command exceeds the maximum allowed size.
     #[msg("Payload Too Large: The provided payload exceeds the maximum allowed size.")]
     PayloadTooLarge,
+
+    /// Error 6007 (0x1777)
+    /// Used when `gc_inactive_profile` targets a profile that still holds a deposit.
+    #[msg("Deposit Not Empty: Only profiles with a zero deposit can be garbage-collected.")]
+    DepositNotEmpty,
+
+    /// Error 6008 (0x1778)
+    /// Used when `gc_inactive_profile` targets a profile that has not been inactive long enough.
+    #[msg("Profile Still Active: The profile has not been inactive for long enough.")]
+    ProfileStillActive,
+
+    /// Error 6009 (0x1779)
+    /// Used when the admin has disabled garbage collection for their service.
+    #[msg("Garbage Collection Disabled: The admin has disabled profile collection.")]
+    GcDisabled,
+
+    /// Error 6010 (0x177A)
+    /// Used when a non-empty dispatch `payload` does not start with a valid `PayloadHeader`.
+    #[msg("Malformed Payload Header: The payload must start with a valid envelope header.")]
+    MalformedPayloadHeader,
+
+    /// Error 6011 (0x177B)
+    /// Used when a v1 `AdminProfile` that was migrated to the v2 seeds is used instead of its replacement.
+    #[msg(
+        "Profile Migrated: This admin profile was migrated to the v2 seeds; use the v2 profile."
+    )]
+    ProfileMigrated,
+
+    /// Error 6012 (0x177C)
+    /// Used when `admin_migrate_to_v2` is called on a build without the `v2-seeds` feature.
+    #[msg("V2 Seeds Disabled: This program was built without support for the v2 admin seeds.")]
+    V2SeedsDisabled,
 }
@@ -43,6 +43,31 @@ pub struct AdminPricesUpdated {
     pub ts: i64,
 }
 
+/// Emitted when an admin changes the garbage-collection policy for their users' profiles.
+#[event]
+#[derive(Debug, Clone)]
+pub struct AdminGcPolicyUpdated {
+    /// The public key of the `AdminProfile`'s owner (`ChainCard`).
+    pub authority: Pubkey,
+    /// The new number of inactive epochs after which a zero-deposit `UserProfile` can be collected.
+    /// A value of `0` means collection is disabled.
+    pub inactivity_epochs: u64,
+    /// The Unix timestamp of the policy update.
+    pub ts: i64,
+}
+
+/// Emitted when an admin changes the surcharge for high-priority commands.
+#[event]
+#[derive(Debug, Clone)]
+pub struct AdminPrioritySurchargeUpdated {
+    /// The public key of the `AdminProfile`'s owner (`ChainCard`).
+    pub authority: Pubkey,
+    /// The new surcharge in lamports added to the price of a high-priority command.
+    pub surcharge: u64,
+    /// The Unix timestamp of the update.
+    pub ts: i64,
+}
+
 /// Emitted when an admin withdraws earned funds from their profile's internal balance.
 #[event]
 #[derive(Debug, Clone)]
@@ -67,6 +92,22 @@ pub struct AdminProfileClosed {
     pub ts: i64,
 }
 
+/// Emitted when an `AdminProfile` is moved from the v1 to the v2 PDA seed scheme.
+#[event]
+#[derive(Debug, Clone)]
+pub struct AdminProfileMigrated {
+    /// The `ChainCard` public key of the admin who owns the profile.
+    pub authority: Pubkey,
+    /// The address of the closed v1 `AdminProfile` PDA.
+    pub old_profile: Pubkey,
+    /// The address of the new v2 `AdminProfile` PDA.
+    pub new_profile: Pubkey,
+    /// The profile index used in the v2 seeds.
+    pub profile_index: u16,
+    /// The Unix timestamp of the migration.
+    pub ts: i64,
+}
+
 /// Emitted when an admin sends a command (notification) to a user.
 #[event]
 #[derive(Debug, Clone)]
@@ -77,6 +118,8 @@ pub struct AdminCommandDispatched {
     pub target_user_authority: Pubkey,
     /// A `u64` identifier for the specific command or notification being sent.
     pub command_id: u64,
+    /// The lamports moved from the admin's balance into the user's deposit (0 if none).
+    pub rebate: u64,
     /// An opaque byte array containing application-specific data for the command.
     pub payload: Vec<u8>,
     /// The Unix timestamp when the command was dispatched.
@@ -119,8 +162,10 @@ pub struct UserFundsDeposited {
     pub authority: Pubkey,
     /// The amount of lamports deposited into the `UserProfile`.
     pub amount: u64,
-    /// The user's new total `deposit_balance` after this transaction.
+    /// The user's new total `deposit_balance` after this transaction (spendable lamports).
     pub new_deposit_balance: u64,
+    /// The lamports locked in the `UserProfile` for rent, not spendable.
+    pub rent_reserve: u64,
     /// The Unix timestamp of the deposit.
     pub ts: i64,
 }
@@ -135,8 +180,10 @@ pub struct UserFundsWithdrawn {
     pub amount: u64,
     /// The public key of the wallet that received the funds.
     pub destination: Pubkey,
-    /// The user's new total `deposit_balance` after this transaction.
+    /// The user's new total `deposit_balance` after this transaction (spendable lamports).
     pub new_deposit_balance: u64,
+    /// The lamports locked in the `UserProfile` for rent, not spendable.
+    pub rent_reserve: u64,
     /// The Unix timestamp of the withdrawal.
     pub ts: i64,
 }
@@ -164,7 +211,12 @@ pub struct UserCommandDispatched {
     /// A `u64` identifier for the specific command being executed.
     pub command_id: u16,
     /// The amount in lamports deducted from the user's deposit balance for this command (0 if free).
+    /// Includes the `priority_fee`, if any.
     pub price_paid: u64,
+    /// Whether the user flagged this command as high priority.
+    pub high_priority: bool,
+    /// The part of `price_paid` charged as the admin's priority surcharge (0 if not high priority).
+    pub priority_fee: u64,
     /// An opaque byte array containing application-specific data for the command.
     pub payload: Vec<u8>,
     /// The Unix timestamp when the command was dispatched.
@@ -1,12 +1,40 @@
 use super::*;
 use crate::instructions::solana_program::program::invoke;
 use crate::instructions::solana_program::system_instruction;
+use crate::protocols::PayloadHeader;
 use anchor_lang::solana_program;
 // use solana_program::{program::invoke, system_instruction};
 
 /// The maximum size in bytes for the `payload` in dispatch instructions.
 pub const MAX_PAYLOAD_SIZE: usize = 1000;
 
+/// Checks the size of a dispatch `payload` and, if it is non-empty, its envelope header.
+/// Empty payloads are allowed as bare signals.
+fn validate_payload(payload: &[u8]) -> Result<()> {
+    require!(
+        payload.len() <= MAX_PAYLOAD_SIZE,
+        BridgeError::PayloadTooLarge
+    );
+    if !payload.is_empty() {
+        PayloadHeader::parse(payload).map_err(|_| error!(BridgeError::MalformedPayloadHeader))?;
+    }
+    Ok(())
+}
+
+/// Checks that debiting `amount` lamports from a `UserProfile` leaves its `rent_reserve` intact.
+fn require_rent_reserve_kept(user_profile: &Account<UserProfile>, amount: u64) -> Result<()> {
+    let remaining = user_profile
+        .to_account_info()
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(BridgeError::RentExemptViolation)?;
+    require!(
+        remaining >= user_profile.rent_reserve,
+        BridgeError::RentExemptViolation
+    );
+    Ok(())
+}
+
 // --- Admin Instructions ---
 
 /// Initializes a new `AdminProfile` PDA for a service provider.
@@ -20,6 +48,7 @@ pub fn admin_register_profile(
     admin_profile.communication_pubkey = communication_pubkey;
     admin_profile.prices = Vec::new();
     admin_profile.balance = 0;
+    admin_profile.gc_inactivity_epochs = DEFAULT_GC_INACTIVITY_EPOCHS;
 
     emit!(AdminProfileRegistered {
         authority: admin_profile.authority,
@@ -51,6 +80,15 @@ pub fn admin_close_profile(ctx: Context<AdminCloseProfile>) -> Result<()> {
     Ok(())
 }
 
+/// Closes an admin's profile and sweeps all of its lamports to an arbitrary `destination`.
+pub fn admin_close_profile_to(ctx: Context<AdminCloseProfileTo>) -> Result<()> {
+    emit!(AdminProfileClosed {
+        authority: ctx.accounts.authority.key(),
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
 /// Updates the price list for an admin's services.
 /// The associated `AdminProfile` account is automatically resized by Anchor
 /// to accommodate the new list size.
@@ -60,7 +98,9 @@ pub fn admin_update_prices(
 ) -> Result<()> {
     new_prices.sort_unstable_by_key(|k| k.command_id);
     new_prices.dedup_by_key(|k| k.command_id);
-    ctx.accounts.admin_profile.prices = new_prices.clone();
+    let admin_profile = &mut ctx.accounts.admin_profile;
+    admin_profile.record_price_snapshot(&new_prices, Clock::get()?.slot);
+    admin_profile.prices = new_prices.clone();
     emit!(AdminPricesUpdated {
         authority: ctx.accounts.authority.key(),
         new_prices,
@@ -69,6 +109,32 @@ pub fn admin_update_prices(
     Ok(())
 }
 
+/// Sets how many epochs a linked `UserProfile` must stay inactive, with an empty deposit,
+/// before it can be garbage-collected. Passing `0` disables collection for this service.
+pub fn admin_set_gc_policy(ctx: Context<AdminSetGcPolicy>, inactivity_epochs: u64) -> Result<()> {
+    ctx.accounts.admin_profile.gc_inactivity_epochs = inactivity_epochs;
+    emit!(AdminGcPolicyUpdated {
+        authority: ctx.accounts.authority.key(),
+        inactivity_epochs,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Sets the surcharge a user pays on top of the command price for high-priority commands.
+pub fn admin_set_priority_surcharge(
+    ctx: Context<AdminSetPrioritySurcharge>,
+    surcharge: u64,
+) -> Result<()> {
+    ctx.accounts.admin_profile.priority_surcharge = surcharge;
+    emit!(AdminPrioritySurchargeUpdated {
+        authority: ctx.accounts.authority.key(),
+        surcharge,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
 /// Allows an admin to withdraw earned funds from their `AdminProfile`'s internal balance.
 /// It performs checks to ensure the withdrawal does not violate the rent-exemption rule.
 pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
@@ -105,23 +171,81 @@ pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
     Ok(())
 }
 
+/// Moves an `AdminProfile` from the v1 seeds to the v2 seeds, which include a `profile_index`.
+/// All state is copied and the earned `balance` is carried over in lamports. The v1 account
+/// keeps only its rent and is marked with `migrated_to`, which reserves its address and
+/// stops it from serving commands; users linked to it are served by the v2 profile.
+pub fn admin_migrate_to_v2(ctx: Context<AdminMigrateToV2>, profile_index: u16) -> Result<()> {
+    require!(cfg!(feature = "v2-seeds"), BridgeError::V2SeedsDisabled);
+
+    let old_profile = &mut ctx.accounts.admin_profile;
+    let new_profile = &mut ctx.accounts.admin_profile_v2;
+    let balance = old_profile.balance;
+
+    new_profile.set_inner(AdminProfile {
+        profile_index: Some(profile_index),
+        migrated_from: Some(old_profile.key()),
+        ..(**old_profile).clone()
+    });
+
+    // Move the earned balance so the v2 account stays fully backed.
+    **old_profile.to_account_info().try_borrow_mut_lamports()? -= balance;
+    **new_profile.to_account_info().try_borrow_mut_lamports()? += balance;
+    old_profile.balance = 0;
+    old_profile.migrated_to = Some(new_profile.key());
+
+    emit!(AdminProfileMigrated {
+        authority: ctx.accounts.authority.key(),
+        old_profile: old_profile.key(),
+        new_profile: new_profile.key(),
+        profile_index,
+        ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
 /// Allows an admin to send a command or notification to a user.
-/// This is a non-financial transaction; its primary purpose is to emit an event
-/// that an off-chain user `connector` can listen and react to.
+/// Its primary purpose is to emit an event that an off-chain user `connector` can
+/// listen and react to. Optionally, a `rebate` is moved from the admin's balance
+/// into the user's deposit (e.g. as compensation or a usage refund).
 pub fn admin_dispatch_command(
     ctx: Context<AdminDispatchCommand>,
     command_id: u64,
+    rebate: u64,
     payload: Vec<u8>,
 ) -> Result<()> {
-    require!(
-        payload.len() <= MAX_PAYLOAD_SIZE,
-        BridgeError::PayloadTooLarge
-    );
+    validate_payload(&payload)?;
+
+    if rebate > 0 {
+        let admin_profile = &mut ctx.accounts.admin_profile;
+        let user_profile = &mut ctx.accounts.user_profile;
+
+        require!(
+            admin_profile.balance >= rebate,
+            BridgeError::InsufficientAdminBalance
+        );
+
+        let rent = Rent::get()?;
+        let rent_exempt_minimum = rent.minimum_balance(admin_profile.to_account_info().data_len());
+        require!(
+            admin_profile.to_account_info().lamports() - rebate >= rent_exempt_minimum,
+            BridgeError::RentExemptViolation
+        );
+
+        // Transfer lamports from the admin's PDA to the user's PDA.
+        **admin_profile.to_account_info().try_borrow_mut_lamports()? -= rebate;
+        **user_profile.to_account_info().try_borrow_mut_lamports()? += rebate;
+
+        // Update the internal balances of both profiles.
+        admin_profile.balance -= rebate;
+        user_profile.deposit_balance += rebate;
+    }
 
     emit!(AdminCommandDispatched {
         sender: ctx.accounts.admin_authority.key(),
         target_user_authority: ctx.accounts.user_profile.authority,
         command_id,
+        rebate,
         payload,
         ts: Clock::get()?.unix_timestamp,
     });
@@ -144,6 +268,9 @@ pub fn user_create_profile(
     user_profile.deposit_balance = 0;
     user_profile.communication_pubkey = communication_pubkey;
     user_profile.admin_authority_on_creation = target_admin;
+    user_profile.last_active_epoch = Clock::get()?.epoch;
+    user_profile.rent_reserve =
+        Rent::get()?.minimum_balance(user_profile.to_account_info().data_len());
 
     emit!(UserProfileCreated {
         authority: user_profile.authority,
@@ -157,6 +284,7 @@ pub fn user_create_profile(
 /// Updates the off-chain communication public key for a `UserProfile`.
 pub fn user_update_comm_key(ctx: Context<UserUpdateCommKey>, new_key: Pubkey) -> Result<()> {
     ctx.accounts.user_profile.communication_pubkey = new_key;
+    ctx.accounts.user_profile.last_active_epoch = Clock::get()?.epoch;
     emit!(UserCommKeyUpdated {
         authority: ctx.accounts.authority.key(),
         new_comm_pubkey: new_key,
@@ -198,11 +326,13 @@ pub fn user_deposit(ctx: Context<UserDeposit>, amount: u64) -> Result<()> {
 
     // Update the internal deposit balance state.
     user_profile.deposit_balance += amount;
+    user_profile.last_active_epoch = Clock::get()?.epoch;
 
     emit!(UserFundsDeposited {
         authority: user_profile.authority,
         amount,
         new_deposit_balance: user_profile.deposit_balance,
+        rent_reserve: user_profile.rent_reserve,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -219,13 +349,8 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
         BridgeError::InsufficientDepositBalance
     );
 
-    // Check if the on-chain lamport balance will remain above the rent-exempt minimum.
-    let rent = Rent::get()?;
-    let rent_exempt_minimum = rent.minimum_balance(user_profile.to_account_info().data_len());
-    require!(
-        user_profile.to_account_info().lamports() - amount >= rent_exempt_minimum,
-        BridgeError::RentExemptViolation
-    );
+    // The rent reserve must stay in the PDA.
+    require_rent_reserve_kept(user_profile, amount)?;
 
     // Perform the lamport transfer.
     **user_profile.to_account_info().try_borrow_mut_lamports()? -= amount;
@@ -233,12 +358,14 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
 
     // Update the internal deposit balance state.
     user_profile.deposit_balance -= amount;
+    user_profile.last_active_epoch = Clock::get()?.epoch;
 
     emit!(UserFundsWithdrawn {
         authority: user_profile.authority,
         amount,
         destination: destination.key(),
         new_deposit_balance: user_profile.deposit_balance,
+        rent_reserve: user_profile.rent_reserve,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
@@ -252,23 +379,29 @@ pub fn user_withdraw(ctx: Context<UserWithdraw>, amount: u64) -> Result<()> {
 pub fn user_dispatch_command(
     ctx: Context<UserDispatchCommand>,
     command_id: u16,
+    high_priority: bool,
     payload: Vec<u8>,
 ) -> Result<()> {
-    require!(
-        payload.len() <= MAX_PAYLOAD_SIZE,
-        BridgeError::PayloadTooLarge
-    );
+    validate_payload(&payload)?;
 
     let user_profile = &mut ctx.accounts.user_profile;
     let admin_profile = &mut ctx.accounts.admin_profile;
 
-    let command_price = match admin_profile
+    let base_price = match admin_profile
         .prices
         .binary_search_by_key(&command_id, |id| id.command_id)
     {
         Ok(index) => admin_profile.prices[index].price,
         Err(_) => 0,
     };
+    let priority_fee = if high_priority {
+        admin_profile.priority_surcharge
+    } else {
+        0
+    };
+    let command_price = base_price
+        .checked_add(priority_fee)
+        .ok_or(BridgeError::InsufficientDepositBalance)?;
 
     // If the command is not free, process the payment.
     if command_price > 0 {
@@ -277,12 +410,7 @@ pub fn user_dispatch_command(
             BridgeError::InsufficientDepositBalance
         );
 
-        let rent = Rent::get()?;
-        let rent_exempt_minimum = rent.minimum_balance(user_profile.to_account_info().data_len());
-        require!(
-            user_profile.to_account_info().lamports() - command_price >= rent_exempt_minimum,
-            BridgeError::RentExemptViolation
-        );
+        require_rent_reserve_kept(user_profile, command_price)?;
 
         // Transfer lamports from the user's PDA to the admin's PDA.
         **user_profile.to_account_info().try_borrow_mut_lamports()? -= command_price;
@@ -293,17 +421,57 @@ pub fn user_dispatch_command(
         admin_profile.balance += command_price;
     }
 
+    user_profile.last_active_epoch = Clock::get()?.epoch;
+
     emit!(UserCommandDispatched {
         sender: ctx.accounts.authority.key(),
         target_admin_authority: admin_profile.authority,
         command_id,
         price_paid: command_price,
+        high_priority,
+        priority_fee,
         payload,
         ts: Clock::get()?.unix_timestamp,
     });
     Ok(())
 }
 
+/// A permissionless crank that closes an abandoned `UserProfile`.
+/// The profile must have a zero deposit and must not have been touched by its owner for
+/// at least the admin's `gc_inactivity_epochs`. The rent is returned to the user's `authority`
+/// by the `close` directive, so the caller gains nothing but a smaller account set.
+pub fn gc_inactive_profile(ctx: Context<GcInactiveProfile>) -> Result<()> {
+    let user_profile = &ctx.accounts.user_profile;
+
+    require!(
+        user_profile.deposit_balance == 0,
+        BridgeError::DepositNotEmpty
+    );
+
+    // Honor the admin's policy while the service exists. Profiles orphaned by a closed
+    // admin profile fall back to the protocol default.
+    let admin_info = ctx.accounts.admin_profile.to_account_info();
+    let inactivity_epochs = if admin_info.owner == &crate::ID && !admin_info.data_is_empty() {
+        let data = admin_info.try_borrow_data()?;
+        AdminProfile::try_deserialize(&mut &data[..])?.gc_inactivity_epochs
+    } else {
+        DEFAULT_GC_INACTIVITY_EPOCHS
+    };
+    require!(inactivity_epochs > 0, BridgeError::GcDisabled);
+
+    let clock = Clock::get()?;
+    require!(
+        clock.epoch.saturating_sub(user_profile.last_active_epoch) >= inactivity_epochs,
+        BridgeError::ProfileStillActive
+    );
+
+    emit!(UserProfileClosed {
+        authority: user_profile.authority,
+        ts: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
 /// A generic instruction to log a significant off-chain action to the blockchain.
 /// This creates an immutable, auditable record of events that happen outside the chain.
 pub fn log_action(ctx: Context<LogAction>, session_id: u64, action_code: u16) -> Result<()> {
@@ -62,6 +62,15 @@ pub mod w3b2_bridge_program {
         instructions::admin_close_profile(ctx)
     }
 
+    /// Closes an `AdminProfile` account and sweeps its entire balance (earnings plus rent)
+    /// to a `destination` wallet, such as a treasury or cold wallet, in a single transaction.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context containing the `authority`, the `admin_profile` and the `destination`.
+    pub fn admin_close_profile_to(ctx: Context<AdminCloseProfileTo>) -> Result<()> {
+        instructions::admin_close_profile_to(ctx)
+    }
+
     /// Updates the price list for an admin's services. The associated `AdminProfile`
     /// account is automatically resized to fit the new list.
     ///
@@ -75,6 +84,32 @@ pub mod w3b2_bridge_program {
         instructions::admin_update_prices(ctx, args.new_prices)
     }
 
+    /// Sets the garbage-collection policy for `UserProfile`s linked to this admin's service.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts for updating the policy.
+    /// * `inactivity_epochs` - The number of inactive epochs after which a zero-deposit
+    ///   `UserProfile` may be closed by anyone. `0` disables garbage collection.
+    pub fn admin_set_gc_policy(
+        ctx: Context<AdminSetGcPolicy>,
+        inactivity_epochs: u64,
+    ) -> Result<()> {
+        instructions::admin_set_gc_policy(ctx, inactivity_epochs)
+    }
+
+    /// Sets the surcharge, in lamports, that users pay on top of the command price when they
+    /// flag a command as high priority.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context of accounts for updating the surcharge.
+    /// * `surcharge` - The new surcharge in lamports. `0` makes priority free.
+    pub fn admin_set_priority_surcharge(
+        ctx: Context<AdminSetPrioritySurcharge>,
+        surcharge: u64,
+    ) -> Result<()> {
+        instructions::admin_set_priority_surcharge(ctx, surcharge)
+    }
+
     /// Allows an admin to withdraw earned funds from their `AdminProfile`'s internal balance
     /// to a specified destination wallet.
     ///
@@ -85,20 +120,33 @@ pub mod w3b2_bridge_program {
         instructions::admin_withdraw(ctx, amount)
     }
 
-    /// Allows an admin to send a command or notification to a user. This is a non-financial
-    /// transaction; its primary purpose is to emit an `AdminCommandDispatched` event that
-    /// an off-chain user `connector` can listen and react to.
+    /// Migrates an `AdminProfile` to the v2 PDA seed scheme (`[b"admin_v2", authority, profile_index]`),
+    /// copying its state and balance. The v1 account is kept as a marker that reserves its address.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context containing the `authority`, the v1 `admin_profile` and the new `admin_profile_v2`.
+    /// * `profile_index` - The index of this profile among the authority's v2 profiles.
+    pub fn admin_migrate_to_v2(ctx: Context<AdminMigrateToV2>, profile_index: u16) -> Result<()> {
+        instructions::admin_migrate_to_v2(ctx, profile_index)
+    }
+
+    /// Allows an admin to send a command or notification to a user. Its primary purpose is to
+    /// emit an `AdminCommandDispatched` event that an off-chain user `connector` can listen and
+    /// react to. It can optionally credit a rebate from the admin's balance to the user's deposit.
     ///
     /// # Arguments
     /// * `ctx` - The context, including the admin's `authority`, their `admin_profile`, and the target `user_profile`.
     /// * `command_id` - The `u64` identifier of the admin's command.
-    /// * `payload` - An opaque `Vec<u8>` for application-specific data.
+    /// * `rebate` - Lamports to move from the admin's balance into the user's deposit. `0` for none.
+    /// * `payload` - Application-specific data. If non-empty, it must start with a
+    ///   valid `protocols::PayloadHeader`.
     pub fn admin_dispatch_command(
         ctx: Context<AdminDispatchCommand>,
         command_id: u64,
+        rebate: u64,
         payload: Vec<u8>,
     ) -> Result<()> {
-        instructions::admin_dispatch_command(ctx, command_id, payload)
+        instructions::admin_dispatch_command(ctx, command_id, rebate, payload)
     }
 
     // --- User Instructions ---
@@ -162,13 +210,28 @@ pub mod w3b2_bridge_program {
     /// # Arguments
     /// * `ctx` - The context, including the user's `authority`, their `user_profile`, and the target `admin_profile`.
     /// * `command_id` - The `u64` identifier of the service's command to be executed.
-    /// * `payload` - An opaque `Vec<u8>` containing serialized, application-specific data for the off-chain service.
+    /// * `high_priority` - Flags the command as high priority. The admin's `priority_surcharge`
+    ///   is charged on top of the price and the flag is carried in the emitted event.
+    /// * `payload` - Serialized, application-specific data for the off-chain service.
+    ///   If non-empty, it must start with a valid `protocols::PayloadHeader`.
     pub fn user_dispatch_command(
         ctx: Context<UserDispatchCommand>,
         command_id: u16,
+        high_priority: bool,
         payload: Vec<u8>,
     ) -> Result<()> {
-        instructions::user_dispatch_command(ctx, command_id, payload)
+        instructions::user_dispatch_command(ctx, command_id, high_priority, payload)
+    }
+
+    /// A permissionless crank that closes a `UserProfile` which has a zero deposit and has
+    /// been inactive for at least the admin's configured number of epochs. The rent is
+    /// returned to the profile's original `authority`.
+    ///
+    /// # Arguments
+    /// * `ctx` - The context, including the `caller`, the linked `admin_profile`, the user's
+    ///   `authority`, and the `user_profile` to be closed.
+    pub fn gc_inactive_profile(ctx: Context<GcInactiveProfile>) -> Result<()> {
+        instructions::gc_inactive_profile(ctx)
     }
 
     /// A generic instruction to log a significant off-chain action to the blockchain,
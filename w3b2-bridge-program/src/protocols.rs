@@ -6,16 +6,166 @@ use crate::instructions::MAX_PAYLOAD_SIZE;
 
 /*
     This file defines serializable data structures intended for off-chain communication.
-    The on-chain program does not interpret the content of the `payload` in the `dispatch`
-    instructions. It treats it as an opaque byte array (`Vec<u8>`).
+    Apart from a short envelope header, the on-chain program does not interpret the content
+    of the `payload` in the `dispatch` instructions. It treats it as an opaque byte array (`Vec<u8>`).
 
     This design pattern turns the Solana blockchain into a secure, decentralized, and
     auditable message broker. Off-chain components (like the `w3b2-connector`) are
     responsible for serializing these structs into the `payload` and deserializing them
     from the corresponding on-chain events. This keeps the on-chain logic minimal and
     gas-efficient while allowing for arbitrarily complex off-chain protocols.
+
+    The `PayloadHeader` envelope at the start of a payload tells the receiver how to decode
+    the rest (codec, encryption) and is the only part the program validates.
 */
 
+/// Magic bytes that open every non-empty `dispatch` payload.
+pub const PAYLOAD_MAGIC: [u8; 2] = *b"W3";
+
+/// The payload header layout version understood by this program.
+pub const PAYLOAD_HEADER_VERSION: u8 = 1;
+
+/// The serialized size of a `PayloadHeader`: magic (2), version (1), codec (1), flags (1).
+pub const PAYLOAD_HEADER_LEN: usize = 5;
+
+/// Bit in the header's flags byte marking the payload body as encrypted.
+const FLAG_ENCRYPTED: u8 = 0b0000_0001;
+
+/// Bit in the header's flags byte marking the payload as one chunk of a larger message.
+const FLAG_CHUNKED: u8 = 0b0000_0010;
+
+/// Identifies the serialization format of the payload body that follows the header.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadCodec {
+    /// Opaque bytes with no agreed-upon structure.
+    Raw = 0,
+    /// A Borsh-encoded structure, such as `CommandConfig`.
+    Borsh = 1,
+    /// UTF-8 encoded JSON.
+    Json = 2,
+    /// A Protocol Buffers message.
+    Protobuf = 3,
+}
+
+impl PayloadCodec {
+    /// Maps a raw codec id from the header back to a `PayloadCodec`.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(PayloadCodec::Raw),
+            1 => Some(PayloadCodec::Borsh),
+            2 => Some(PayloadCodec::Json),
+            3 => Some(PayloadCodec::Protobuf),
+            _ => None,
+        }
+    }
+}
+
+/// The fixed-size envelope prefixed to every non-empty `dispatch` payload.
+/// The program validates it so that malformed payloads never reach off-chain parties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadHeader {
+    /// The header layout version. Must equal `PAYLOAD_HEADER_VERSION`.
+    pub version: u8,
+    /// The serialization format of the body.
+    pub codec: PayloadCodec,
+    /// Whether the body is encrypted (e.g. with a session key from `CommandConfig`).
+    pub encrypted: bool,
+    /// Whether the body is one chunk of a message too large for a single payload.
+    /// `codec` and `encrypted` then describe the reassembled message.
+    pub chunked: bool,
+}
+
+/// The reasons a payload header can be rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The payload is shorter than `PAYLOAD_HEADER_LEN`.
+    TooShort,
+    /// The payload does not start with `PAYLOAD_MAGIC`.
+    BadMagic,
+    /// The header version is not supported.
+    UnsupportedVersion(u8),
+    /// The codec id does not map to a known `PayloadCodec`.
+    UnknownCodec(u8),
+    /// Reserved bits in the flags byte are set.
+    ReservedFlags(u8),
+}
+
+impl PayloadHeader {
+    /// Creates a header for the current layout version.
+    pub fn new(codec: PayloadCodec, encrypted: bool) -> Self {
+        Self {
+            version: PAYLOAD_HEADER_VERSION,
+            codec,
+            encrypted,
+            chunked: false,
+        }
+    }
+
+    /// Marks the header as that of a chunk of a larger message.
+    pub fn as_chunk(mut self) -> Self {
+        self.chunked = true;
+        self
+    }
+
+    /// Serializes the header into its fixed-size wire form.
+    pub fn to_bytes(&self) -> [u8; PAYLOAD_HEADER_LEN] {
+        let mut flags = 0;
+        if self.encrypted {
+            flags |= FLAG_ENCRYPTED;
+        }
+        if self.chunked {
+            flags |= FLAG_CHUNKED;
+        }
+        [
+            PAYLOAD_MAGIC[0],
+            PAYLOAD_MAGIC[1],
+            self.version,
+            self.codec as u8,
+            flags,
+        ]
+    }
+
+    /// Builds a complete payload by prefixing `body` with this header.
+    pub fn wrap(&self, body: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(PAYLOAD_HEADER_LEN + body.len());
+        payload.extend_from_slice(&self.to_bytes());
+        payload.extend_from_slice(body);
+        payload
+    }
+
+    /// Parses and validates the header at the start of `payload`.
+    ///
+    /// # Returns
+    /// The decoded header and the remaining body bytes.
+    pub fn parse(payload: &[u8]) -> std::result::Result<(Self, &[u8]), HeaderError> {
+        if payload.len() < PAYLOAD_HEADER_LEN {
+            return Err(HeaderError::TooShort);
+        }
+        let (header, body) = payload.split_at(PAYLOAD_HEADER_LEN);
+
+        if header[0..2] != PAYLOAD_MAGIC {
+            return Err(HeaderError::BadMagic);
+        }
+        if header[2] != PAYLOAD_HEADER_VERSION {
+            return Err(HeaderError::UnsupportedVersion(header[2]));
+        }
+        let codec = PayloadCodec::from_id(header[3]).ok_or(HeaderError::UnknownCodec(header[3]))?;
+        if header[4] & !(FLAG_ENCRYPTED | FLAG_CHUNKED) != 0 {
+            return Err(HeaderError::ReservedFlags(header[4]));
+        }
+
+        Ok((
+            Self {
+                version: header[2],
+                codec,
+                encrypted: header[4] & FLAG_ENCRYPTED != 0,
+                chunked: header[4] & FLAG_CHUNKED != 0,
+            },
+            body,
+        ))
+    }
+}
+
 /// Defines the expected communication flow for an off-chain service after
 /// receiving a command via a `dispatch` instruction.
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
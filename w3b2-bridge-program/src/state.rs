@@ -4,6 +4,17 @@ use anchor_lang::prelude::*;
 /// The default number of price entries to allocate space for when creating an AdminProfile.
 const DEFAULT_API_SIZE: usize = 10;
 
+/// The default number of epochs a `UserProfile` may stay inactive (with an empty deposit)
+/// before it becomes eligible for garbage collection. Roughly 60 days on mainnet.
+pub const DEFAULT_GC_INACTIVITY_EPOCHS: u64 = 30;
+
+/// The seed prefix of the v2 `AdminProfile` PDA scheme: `[ADMIN_SEED_V2, authority, profile_index]`.
+/// The extra index lets one authority own several profiles under the same program ID.
+pub const ADMIN_SEED_V2: &[u8] = b"admin_v2";
+
+/// The number of past price-list updates remembered in `AdminProfile::price_history`.
+pub const PRICE_HISTORY_LEN: usize = 8;
+
 // --- Account Data Structs ---
 
 /// Represents the on-chain profile for a Service Provider (Admin).
@@ -23,6 +34,96 @@ pub struct AdminProfile {
     /// The internal balance in lamports where fees from paid user commands are collected.
     /// This balance can be withdrawn by the admin.
     pub balance: u64,
+    /// The number of epochs a linked `UserProfile` must stay inactive, with a zero deposit,
+    /// before anyone may close it via `gc_inactive_profile`. A value of `0` disables collection.
+    pub gc_inactivity_epochs: u64,
+    /// The extra lamports a user pays on top of the command price when dispatching
+    /// a command flagged as high priority.
+    pub priority_surcharge: u64,
+    /// A ring buffer of the last `PRICE_HISTORY_LEN` price-list updates. Lets clients prove,
+    /// at dispute time, which price list was in force when a command was dispatched.
+    pub price_history: [PriceSnapshot; PRICE_HISTORY_LEN],
+    /// Index in `price_history` where the next snapshot will be written.
+    pub price_history_head: u8,
+    /// The index in the v2 seeds (`[ADMIN_SEED_V2, authority, profile_index]`) of a profile
+    /// created by `admin_migrate_to_v2`. `None` for a profile at the v1 address.
+    pub profile_index: Option<u16>,
+    /// On a v2 profile, the v1 profile it was migrated from. `UserProfile`s created for
+    /// that address stay linked to this profile.
+    pub migrated_from: Option<Pubkey>,
+    /// On a v1 profile, the v2 profile it was migrated to. The v1 account is kept so its
+    /// address cannot be registered again, but it no longer serves commands.
+    pub migrated_to: Option<Pubkey>,
+}
+
+impl AdminProfile {
+    /// Stores a snapshot of `prices` taken at `slot`, overwriting the oldest entry.
+    pub fn record_price_snapshot(&mut self, prices: &[PriceEntry], slot: u64) {
+        let head = self.price_history_head as usize % PRICE_HISTORY_LEN;
+        self.price_history[head] = PriceSnapshot {
+            prices_hash: hash_prices(prices),
+            slot,
+        };
+        self.price_history_head = ((head + 1) % PRICE_HISTORY_LEN) as u8;
+    }
+
+    /// Returns the snapshot that was in force at `slot`, i.e. the most recent one
+    /// recorded at or before it, if it is still in the buffer.
+    pub fn price_snapshot_at(&self, slot: u64) -> Option<&PriceSnapshot> {
+        self.price_history
+            .iter()
+            .filter(|s| s.slot != 0 && s.slot <= slot)
+            .max_by_key(|s| s.slot)
+    }
+
+    /// Returns the first seed of this profile's PDA: `b"admin"` for a v1 profile and
+    /// `ADMIN_SEED_V2` for a migrated one.
+    pub fn seed_prefix(&self) -> &'static [u8] {
+        match self.profile_index {
+            Some(_) if cfg!(feature = "v2-seeds") => ADMIN_SEED_V2,
+            _ => b"admin",
+        }
+    }
+
+    /// Returns the seed following the authority: the little-endian `profile_index` of a
+    /// migrated profile, or nothing for a v1 one. An empty seed leaves the derived address
+    /// unchanged, so both schemes fit into one `seeds` list.
+    pub fn index_seed(&self) -> Vec<u8> {
+        match self.profile_index {
+            Some(index) if cfg!(feature = "v2-seeds") => index.to_le_bytes().to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns whether a `UserProfile` created for `link` belongs to this profile, which
+    /// lives at `address`: either it was created for it, or for the v1 profile it replaced.
+    pub fn serves(&self, address: &Pubkey, link: &Pubkey) -> bool {
+        address == link || self.migrated_from.as_ref() == Some(link)
+    }
+}
+
+/// A single entry in `AdminProfile::price_history`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct PriceSnapshot {
+    /// The `hash_prices` digest of the price list that was set.
+    pub prices_hash: [u8; 32],
+    /// The slot in which the price list was set. `0` marks an unused entry.
+    pub slot: u64,
+}
+
+/// Computes the SHA-256 digest of a price list, as stored in `PriceSnapshot::prices_hash`.
+/// Each entry contributes its little-endian `command_id` followed by its little-endian `price`.
+pub fn hash_prices(prices: &[PriceEntry]) -> [u8; 32] {
+    let bytes: Vec<u8> = prices
+        .iter()
+        .flat_map(|p| {
+            p.command_id
+                .to_le_bytes()
+                .into_iter()
+                .chain(p.price.to_le_bytes())
+        })
+        .collect();
+    anchor_lang::solana_program::hash::hash(&bytes).to_bytes()
 }
 
 /// Represents a user's on-chain relationship with and deposit for a specific Admin service.
@@ -41,6 +142,12 @@ pub struct UserProfile {
     /// The user's prepaid balance in lamports for this specific service. This balance
     /// is debited by the `user_dispatch_command` instruction.
     pub deposit_balance: u64,
+    /// The lamports locked in the PDA to keep it rent-exempt. They are never spendable
+    /// and are only returned when the profile is closed.
+    pub rent_reserve: u64,
+    /// The epoch of the last instruction the user signed against this profile.
+    /// Used to decide whether the profile is abandoned and can be garbage-collected.
+    pub last_active_epoch: u64,
 }
 
 // --- Instruction Accounts Structs ---
@@ -79,8 +186,9 @@ pub struct AdminUpdatePrices<'info> {
     /// fit the new price list.
     #[account(
         mut,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
         bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
         realloc = 8 + std::mem::size_of::<AdminProfile>() + (args.new_prices.len() * std::mem::size_of::<(u64, u64)>()),
         realloc::payer = authority,
         realloc::zero = false,
@@ -91,6 +199,24 @@ pub struct AdminUpdatePrices<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Defines the accounts for the `admin_set_gc_policy` instruction.
+#[derive(Accounts)]
+pub struct AdminSetGcPolicy<'info> {
+    /// The admin's `ChainCard`, who must be the `authority` of the `admin_profile`.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` account to be updated. Constraints verify the `authority`
+    /// and the account's PDA seeds.
+    #[account(
+        mut,
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
+        bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
+        constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+}
+
 /// Represents a single entry in an admin's price list.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
 pub struct PriceEntry {
@@ -113,6 +239,23 @@ pub struct UpdatePricesArgs {
     pub new_prices: Vec<PriceEntry>,
 }
 
+/// Defines the accounts for the `admin_set_priority_surcharge` instruction.
+#[derive(Accounts)]
+pub struct AdminSetPrioritySurcharge<'info> {
+    /// The admin's `ChainCard`, who must be the `authority` of the `admin_profile`.
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` account to be updated. Constraints verify the `authority`
+    /// and the account's PDA seeds.
+    #[account(
+        mut,
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
+        bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
+        constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+}
+
 /// Defines the accounts for the `admin_withdraw` instruction.
 #[derive(Accounts)]
 pub struct AdminWithdraw<'info> {
@@ -123,8 +266,9 @@ pub struct AdminWithdraw<'info> {
     /// verify the `authority` and the PDA seeds.
     #[account(
         mut,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
         bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub admin_profile: Account<'info, AdminProfile>,
@@ -148,8 +292,9 @@ pub struct AdminUpdateCommKey<'info> {
     /// and the account's PDA seeds.
     #[account(
         mut,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
         bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub admin_profile: Account<'info, AdminProfile>,
@@ -168,11 +313,63 @@ pub struct AdminCloseProfile<'info> {
     #[account(
         mut,
         close = authority,
-        seeds = [b"admin", authority.key().as_ref()],
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
+        bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
+        constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+}
+
+/// Defines the accounts for the `admin_close_profile_to` instruction.
+#[derive(Accounts)]
+pub struct AdminCloseProfileTo<'info> {
+    /// The admin's `ChainCard`, who must be the `authority` of the `admin_profile`.
+    pub authority: Signer<'info>,
+    /// The `AdminProfile` account to be closed. All of its lamports (earned balance
+    /// plus rent) are swept to the `destination`.
+    #[account(
+        mut,
+        close = destination,
+        seeds = [admin_profile.seed_prefix(), authority.key().as_ref(), admin_profile.index_seed().as_slice()],
         bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated,
         constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub admin_profile: Account<'info, AdminProfile>,
+    /// CHECK: Arbitrary wallet (e.g. a treasury or cold wallet) that receives the swept lamports.
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+}
+
+/// Defines the accounts for the `admin_migrate_to_v2` instruction.
+#[derive(Accounts)]
+#[instruction(profile_index: u16)]
+pub struct AdminMigrateToV2<'info> {
+    /// The admin's `ChainCard`. Pays for the v2 account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// The v1 `AdminProfile` to migrate. It is kept, emptied of its balance, so that the
+    /// v1 address cannot be registered again.
+    #[account(
+        mut,
+        seeds = [b"admin", authority.key().as_ref()],
+        bump,
+        constraint = admin_profile.authority == authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated
+    )]
+    pub admin_profile: Account<'info, AdminProfile>,
+    /// The new `AdminProfile` under the v2 seed scheme, sized for the current price list.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<AdminProfile>() + (admin_profile.prices.len() * std::mem::size_of::<(u64, u64)>()),
+        seeds = [ADMIN_SEED_V2, authority.key().as_ref(), &profile_index.to_le_bytes()],
+        bump
+    )]
+    pub admin_profile_v2: Account<'info, AdminProfile>,
+    /// The System Program, required by Anchor for account creation (`init`).
+    pub system_program: Program<'info, System>,
 }
 
 /// Defines the accounts for the `admin_dispatch_command` instruction.
@@ -181,17 +378,21 @@ pub struct AdminDispatchCommand<'info> {
     /// The `Signer` of the transaction. This must be the `ChainCard` of the admin.
     pub admin_authority: Signer<'info>,
     /// The admin's own profile PDA. Constraints ensure that the `admin_authority`
-    /// is the legitimate owner of this profile.
+    /// is the legitimate owner of this profile. Debited when a rebate is paid.
     #[account(
-        seeds = [b"admin", admin_authority.key().as_ref()],
+        mut,
+        seeds = [admin_profile.seed_prefix(), admin_authority.key().as_ref(), admin_profile.index_seed().as_slice()],
         bump,
-        constraint = admin_profile.authority == admin_authority.key() @ BridgeError::SignerUnauthorized
+        constraint = admin_profile.authority == admin_authority.key() @ BridgeError::SignerUnauthorized,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated
     )]
     pub admin_profile: Account<'info, AdminProfile>,
     /// The target `UserProfile` to which the command is being sent. A constraint
-    /// ensures this profile is associated with this specific `admin_profile`.
+    /// ensures this profile is associated with this specific `admin_profile`, or with
+    /// the v1 profile it was migrated from. Credited when a rebate is paid.
     #[account(
-        constraint = user_profile.admin_authority_on_creation == admin_profile.key() @ BridgeError::AdminMismatch
+        mut,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch
     )]
     pub user_profile: Account<'info, UserProfile>,
 }
@@ -225,15 +426,16 @@ pub struct UserDeposit<'info> {
     /// The user's `ChainCard`, who must be the `authority` of the `user_profile`.
     #[account(mut)]
     pub authority: Signer<'info>,
-    /// The `AdminProfile` associated with the `user_profile`. This is required
-    /// to derive and verify the `user_profile` PDA address.
+    /// The `AdminProfile` associated with the `user_profile`, or the v2 profile
+    /// that replaced it. This is required to verify the `user_profile` link.
     pub admin_profile: Account<'info, AdminProfile>,
     /// The `UserProfile` to receive the deposit. Constraints verify the PDA seeds
-    /// (linking it to the `authority` and `admin_profile`) and ownership.
+    /// (linking it to the `authority` and its admin) and ownership.
     #[account(
         mut,
-        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        seeds = [b"user", authority.key().as_ref(), user_profile.admin_authority_on_creation.as_ref()],
         bump,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch,
         constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
@@ -252,8 +454,9 @@ pub struct UserWithdraw<'info> {
     /// The `UserProfile` from which funds will be withdrawn.
     #[account(
         mut,
-        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        seeds = [b"user", authority.key().as_ref(), user_profile.admin_authority_on_creation.as_ref()],
         bump,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch,
         constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
@@ -276,8 +479,9 @@ pub struct UserUpdateCommKey<'info> {
     /// The `UserProfile` account to be updated.
     #[account(
         mut,
-        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        seeds = [b"user", authority.key().as_ref(), user_profile.admin_authority_on_creation.as_ref()],
         bump,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch,
         constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
@@ -297,8 +501,9 @@ pub struct UserCloseProfile<'info> {
     #[account(
         mut,
         close = authority,
-        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        seeds = [b"user", authority.key().as_ref(), user_profile.admin_authority_on_creation.as_ref()],
         bump,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch,
         constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
@@ -310,20 +515,24 @@ pub struct UserDispatchCommand<'info> {
     /// The `Signer` of the transaction. This is the user's `ChainCard`.
     pub authority: Signer<'info>,
     /// The user's profile PDA. Constraints ensure the `authority` is the owner
-    /// and that this profile is linked to the provided `admin_profile` via its seeds.
+    /// and that this profile is linked to the provided `admin_profile` (or the v1
+    /// profile it was migrated from) via its seeds.
     #[account(
         mut,
-        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        seeds = [b"user", authority.key().as_ref(), user_profile.admin_authority_on_creation.as_ref()],
         bump,
+        constraint = admin_profile.serves(&admin_profile.key(), &user_profile.admin_authority_on_creation) @ BridgeError::AdminMismatch,
         constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
     )]
     pub user_profile: Account<'info, UserProfile>,
     /// The target `AdminProfile` of the service being called. Its seeds are
-    /// checked to ensure it's a valid profile created by this program.
+    /// checked to ensure it's a valid profile created by this program, and a
+    /// migrated v1 profile is refused so that payments reach the v2 one.
     #[account(
         mut,
-        seeds = [b"admin", admin_profile.authority.as_ref()],
-        bump
+        seeds = [admin_profile.seed_prefix(), admin_profile.authority.as_ref(), admin_profile.index_seed().as_slice()],
+        bump,
+        constraint = admin_profile.migrated_to.is_none() @ BridgeError::ProfileMigrated
     )]
     pub admin_profile: Account<'info, AdminProfile>,
     /// The System Program, required for the lamport transfer from the user's PDA
@@ -331,6 +540,34 @@ pub struct UserDispatchCommand<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Defines the accounts for the `gc_inactive_profile` instruction.
+#[derive(Accounts)]
+pub struct GcInactiveProfile<'info> {
+    /// The `Signer` of the transaction. Anyone may act as the crank; they only pay the fee.
+    pub caller: Signer<'info>,
+    /// The `AdminProfile` PDA the `user_profile` is linked to. It is read to obtain the
+    /// service's `gc_inactivity_epochs` policy. If the admin profile has already been
+    /// closed, the program falls back to `DEFAULT_GC_INACTIVITY_EPOCHS`.
+    /// CHECK: The address is bound by the `user_profile` seeds below; its data is only
+    /// deserialized if it is still owned by this program.
+    pub admin_profile: UncheckedAccount<'info>,
+    /// The user's `ChainCard`, which receives the refunded rent lamports.
+    /// CHECK: This is safe because it's only a destination for a lamport transfer and
+    /// is verified against the `authority` stored in the `user_profile`.
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+    /// The abandoned `UserProfile` to be closed. The `close` directive returns all
+    /// its lamports to the original `authority`.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"user", authority.key().as_ref(), admin_profile.key().as_ref()],
+        bump,
+        constraint = user_profile.authority == authority.key() @ BridgeError::SignerUnauthorized
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
 /// Defines the accounts for the `log_action` instruction.
 #[derive(Accounts)]
 pub struct LogAction<'info> {
@@ -12,7 +12,8 @@ use instructions::*;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::sysvar::rent::Rent;
 use solana_sdk::signature::Signer;
-use w3b2_bridge_program::state::{AdminProfile, PriceEntry, UserProfile};
+use w3b2_bridge_program::protocols::{PayloadCodec, PayloadHeader};
+use w3b2_bridge_program::state::{hash_prices, AdminProfile, PriceEntry, UserProfile};
 
 /// Tests the successful creation of an `AdminProfile` PDA.
 ///
@@ -167,6 +168,49 @@ fn test_admin_close_profile_success() {
     );
 }
 
+/// Tests closing an `AdminProfile` while sweeping its balance to a separate wallet.
+///
+/// ### Scenario
+/// An admin shuts down their service and sends the PDA's lamports straight to a cold wallet.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` is created.
+/// 2. A fresh, unfunded `treasury` keypair is created as the sweep destination.
+///
+/// ### Act
+/// The `admin::close_profile_to` helper is called with the treasury as `destination`.
+///
+/// ### Assert
+/// 1. The `AdminProfile` PDA account no longer exists.
+/// 2. The treasury received the PDA's entire lamport balance.
+/// 3. The admin's `ChainCard` only paid the transaction fee.
+#[test]
+fn test_admin_close_profile_to_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &authority, create_keypair().pubkey());
+    let treasury = create_keypair();
+
+    let pda_balance = svm.get_balance(&admin_pda).unwrap();
+    let authority_balance_before = svm.get_balance(&authority.pubkey()).unwrap();
+
+    // === 2. Act ===
+    admin::close_profile_to(&mut svm, &authority, treasury.pubkey());
+
+    // === 3. Assert ===
+    assert!(
+        svm.get_account(&admin_pda).is_none(),
+        "Account was not closed!"
+    );
+    assert_eq!(svm.get_balance(&treasury.pubkey()).unwrap(), pda_balance);
+
+    let authority_balance_after = svm.get_balance(&authority.pubkey()).unwrap();
+    assert_eq!(authority_balance_after, authority_balance_before - 5000);
+
+    println!("✅ Close Profile To Destination Test Passed!");
+}
+
 /// Tests the successful update of an admin's price list and the `realloc` feature.
 ///
 /// ### Scenario
@@ -228,6 +272,54 @@ fn test_admin_update_prices_success() {
     );
 }
 
+/// Tests that every price update is recorded in the `price_history` ring buffer.
+///
+/// ### Scenario
+/// A client needs to know what the price list was at the slot a command was dispatched.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` is created.
+///
+/// ### Act
+/// The price list is updated twice, in two different slots.
+///
+/// ### Assert
+/// 1. `price_history_head` advanced by two.
+/// 2. `price_snapshot_at` resolves each slot to the hash of the list that was in force then.
+#[test]
+fn test_admin_update_prices_records_history() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+    let authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &authority, create_keypair().pubkey());
+
+    let first_prices = vec![PriceEntry::new(1, 1000)];
+    let second_prices = vec![PriceEntry::new(1, 2000), PriceEntry::new(2, 500)];
+
+    // === 2. Act ===
+    svm.warp_to_slot(10);
+    admin::update_prices(&mut svm, &authority, first_prices.clone());
+    svm.warp_to_slot(20);
+    admin::update_prices(&mut svm, &authority, second_prices.clone());
+
+    // === 3. Assert ===
+    let account = svm.get_account(&admin_pda).unwrap();
+    let admin_profile = AdminProfile::try_deserialize(&mut account.data.as_slice()).unwrap();
+
+    assert_eq!(admin_profile.price_history_head, 2);
+    assert!(admin_profile.price_snapshot_at(5).is_none());
+    assert_eq!(
+        admin_profile.price_snapshot_at(15).unwrap().prices_hash,
+        hash_prices(&first_prices)
+    );
+    assert_eq!(
+        admin_profile.price_snapshot_at(25).unwrap().prices_hash,
+        hash_prices(&second_prices)
+    );
+
+    println!("✅ Price History Test Passed!");
+}
+
 /// Tests the successful dispatch of a command *from* an admin *to* a user.
 ///
 /// ### Scenario
@@ -279,7 +371,7 @@ fn test_admin_dispatch_command_success() {
         &admin_authority,
         user_pda,
         101, // Notification command ID
-        vec![4, 5, 6],
+        PayloadHeader::new(PayloadCodec::Raw, false).wrap(&[4, 5, 6]),
     );
     println!("Command dispatched successfully.");
 
@@ -369,7 +461,8 @@ fn test_admin_withdraw_success() {
 
     // User "buys" the service, transferring funds to the Admin
     println!("User pays admin {} lamports...", command_price);
-    user::dispatch_command(&mut svm, &user_authority, admin_pda, 1, vec![1, 2, 3]);
+    let payload = PayloadHeader::new(PayloadCodec::Raw, false).wrap(&[1, 2, 3]);
+    user::dispatch_command(&mut svm, &user_authority, admin_pda, 1, payload);
 
     // Prepare for the withdrawal
     let destination_wallet = create_keypair();
@@ -423,3 +516,214 @@ fn test_admin_withdraw_success() {
         destination_balance_after
     );
 }
+
+/// Tests that an admin can pay a rebate into a user's deposit while dispatching a command.
+///
+/// ### Scenario
+/// An admin compensates a user for a failed off-chain job by refunding part of the price.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` with a priced command and a funded `UserProfile` are created.
+/// 2. The user pays for the command so the admin has an earned balance.
+///
+/// ### Act
+/// The `admin::dispatch_command_with_rebate` helper is called with half of the price.
+///
+/// ### Assert
+/// 1. The admin's `balance` and PDA lamports decreased by the rebate.
+/// 2. The user's `deposit_balance` and PDA lamports increased by the rebate.
+#[test]
+fn test_admin_dispatch_command_with_rebate_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let command_price = LAMPORTS_PER_SOL;
+    admin::update_prices(
+        &mut svm,
+        &admin_authority,
+        vec![PriceEntry::new(1, command_price)],
+    );
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    user::deposit(&mut svm, &user_authority, admin_pda, 2 * LAMPORTS_PER_SOL);
+    user::dispatch_command(&mut svm, &user_authority, admin_pda, 1, vec![]);
+
+    let admin_lamports_before = svm.get_balance(&admin_pda).unwrap();
+    let user_lamports_before = svm.get_balance(&user_pda).unwrap();
+    let user_account_before = svm.get_account(&user_pda).unwrap();
+    let user_profile_before =
+        UserProfile::try_deserialize(&mut user_account_before.data.as_slice()).unwrap();
+
+    let rebate = command_price / 2;
+
+    // === 2. Act ===
+    admin::dispatch_command_with_rebate(&mut svm, &admin_authority, user_pda, 7, rebate, vec![]);
+
+    // === 3. Assert ===
+    let admin_account_after = svm.get_account(&admin_pda).unwrap();
+    let admin_profile_after =
+        AdminProfile::try_deserialize(&mut admin_account_after.data.as_slice()).unwrap();
+    assert_eq!(admin_profile_after.balance, command_price - rebate);
+    assert_eq!(admin_account_after.lamports, admin_lamports_before - rebate);
+
+    let user_account_after = svm.get_account(&user_pda).unwrap();
+    let user_profile_after =
+        UserProfile::try_deserialize(&mut user_account_after.data.as_slice()).unwrap();
+    assert_eq!(
+        user_profile_after.deposit_balance,
+        user_profile_before.deposit_balance + rebate
+    );
+    assert_eq!(user_account_after.lamports, user_lamports_before + rebate);
+
+    println!("✅ Admin Dispatch Command With Rebate Test Passed!");
+}
+
+/// Tests migrating an `AdminProfile` from the v1 to the v2 PDA seed scheme.
+///
+/// ### Scenario
+/// An admin moves their profile to the indexed v2 address ahead of multi-profile support.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` is created with a price list and an earned balance.
+///
+/// ### Act
+/// The `admin::migrate_to_v2` helper is called with profile index 0.
+///
+/// ### Assert
+/// 1. The v1 PDA still exists, holds only its rent and points to the v2 PDA.
+/// 2. The v2 PDA holds the same prices and balance, and its lamports still back the balance.
+#[test]
+fn test_admin_migrate_to_v2_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let command_price = LAMPORTS_PER_SOL;
+    let prices = vec![PriceEntry::new(1, command_price)];
+    admin::update_prices(&mut svm, &admin_authority, prices.clone());
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    user::deposit(&mut svm, &user_authority, admin_pda, 2 * LAMPORTS_PER_SOL);
+    user::dispatch_command(&mut svm, &user_authority, admin_pda, 1, vec![]);
+
+    // === 2. Act ===
+    let admin_pda_v2 = admin::migrate_to_v2(&mut svm, &admin_authority, 0);
+
+    // === 3. Assert ===
+    let account_v1 = svm.get_account(&admin_pda).unwrap();
+    let profile_v1 = AdminProfile::try_deserialize(&mut account_v1.data.as_slice()).unwrap();
+    assert_eq!(profile_v1.balance, 0);
+    assert_eq!(profile_v1.migrated_to, Some(admin_pda_v2));
+    assert_eq!(
+        account_v1.lamports,
+        Rent::default().minimum_balance(account_v1.data.len())
+    );
+
+    let account_v2 = svm.get_account(&admin_pda_v2).unwrap();
+    let profile_v2 = AdminProfile::try_deserialize(&mut account_v2.data.as_slice()).unwrap();
+    assert_eq!(profile_v2.authority, admin_authority.pubkey());
+    assert_eq!(profile_v2.prices, prices);
+    assert_eq!(profile_v2.balance, command_price);
+    assert_eq!(profile_v2.profile_index, Some(0));
+    assert_eq!(profile_v2.migrated_from, Some(admin_pda));
+
+    let rent_exempt_minimum = Rent::default().minimum_balance(account_v2.data.len());
+    assert_eq!(account_v2.lamports, rent_exempt_minimum + command_price);
+
+    println!("✅ Admin Migrate To V2 Test Passed!");
+}
+
+/// Tests that a migrated `AdminProfile` keeps working under its v2 address.
+///
+/// ### Scenario
+/// After migrating, the admin withdraws their earnings and an existing user keeps paying for commands.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` with a price list is created, and a user links to it and deposits.
+/// 2. The profile is migrated to the v2 seeds.
+///
+/// ### Act
+/// 1. The user dispatches a paid command to the v2 profile from their existing `UserProfile`.
+/// 2. The admin withdraws the whole earned balance from the v2 profile.
+///
+/// ### Assert
+/// 1. The command price moved from the user's deposit to the v2 profile.
+/// 2. The withdrawal emptied the v2 balance and credited the destination.
+#[test]
+fn test_admin_migrated_profile_withdraws_and_receives_dispatch() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let command_price = LAMPORTS_PER_SOL;
+    admin::update_prices(
+        &mut svm,
+        &admin_authority,
+        vec![PriceEntry::new(1, command_price)],
+    );
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    user::deposit(&mut svm, &user_authority, admin_pda, 2 * LAMPORTS_PER_SOL);
+
+    let admin_pda_v2 = admin::migrate_to_v2(&mut svm, &admin_authority, 0);
+
+    // === 2. Act ===
+    user::dispatch_command_via(
+        &mut svm,
+        &user_authority,
+        admin_pda,
+        admin_pda_v2,
+        1,
+        vec![],
+    );
+
+    let destination = create_keypair();
+    admin::withdraw_from(
+        &mut svm,
+        &admin_authority,
+        admin_pda_v2,
+        destination.pubkey(),
+        command_price,
+    );
+
+    // === 3. Assert ===
+    let user_account = svm.get_account(&user_pda).unwrap();
+    let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+    assert_eq!(
+        user_profile.deposit_balance,
+        2 * LAMPORTS_PER_SOL - command_price
+    );
+
+    let account_v2 = svm.get_account(&admin_pda_v2).unwrap();
+    let profile_v2 = AdminProfile::try_deserialize(&mut account_v2.data.as_slice()).unwrap();
+    assert_eq!(profile_v2.balance, 0);
+    assert_eq!(
+        account_v2.lamports,
+        Rent::default().minimum_balance(account_v2.data.len())
+    );
+    assert_eq!(svm.get_balance(&destination.pubkey()), Some(command_price));
+
+    println!("✅ Admin Migrated Profile Withdraw And Dispatch Test Passed!");
+}
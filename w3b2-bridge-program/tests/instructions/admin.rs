@@ -1,5 +1,5 @@
 use super::*;
-use w3b2_bridge_program::state::{PriceEntry, UpdatePricesArgs};
+use w3b2_bridge_program::state::{PriceEntry, UpdatePricesArgs, ADMIN_SEED_V2};
 
 // --- High-Level Helper Functions ---
 
@@ -43,6 +43,32 @@ pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair) {
     build_and_send_tx(svm, vec![close_ix], authority, vec![]);
 }
 
+/// A high-level test helper that closes an `AdminProfile` and sweeps its balance elsewhere.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`, who must own the profile.
+/// * `destination` - The `Pubkey` of the wallet that receives the profile's lamports.
+pub fn close_profile_to(svm: &mut LiteSVM, authority: &Keypair, destination: Pubkey) {
+    let close_ix = ix_close_profile_to(authority, destination);
+    build_and_send_tx(svm, vec![close_ix], authority, vec![]);
+}
+
+/// A high-level test helper that migrates an `AdminProfile` to the v2 seed scheme.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`, who must own the profile.
+/// * `profile_index` - The index used in the v2 seeds.
+///
+/// # Returns
+/// The `Pubkey` of the new v2 `AdminProfile` PDA.
+pub fn migrate_to_v2(svm: &mut LiteSVM, authority: &Keypair, profile_index: u16) -> Pubkey {
+    let (migrate_ix, admin_pda_v2) = ix_migrate_to_v2(authority, profile_index);
+    build_and_send_tx(svm, vec![migrate_ix], authority, vec![]);
+    admin_pda_v2
+}
+
 /// A high-level test helper that updates the price list for an `AdminProfile`.
 ///
 /// # Arguments
@@ -54,6 +80,28 @@ pub fn update_prices(svm: &mut LiteSVM, authority: &Keypair, new_prices: Vec<Pri
     build_and_send_tx(svm, vec![update_ix], authority, vec![]);
 }
 
+/// A high-level test helper that sets the inactivity threshold used for garbage collection.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`.
+/// * `inactivity_epochs` - The number of idle epochs after which a user profile may be collected.
+pub fn set_gc_policy(svm: &mut LiteSVM, authority: &Keypair, inactivity_epochs: u64) {
+    let policy_ix = ix_set_gc_policy(authority, inactivity_epochs);
+    build_and_send_tx(svm, vec![policy_ix], authority, vec![]);
+}
+
+/// A high-level test helper that sets the surcharge for high-priority commands.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`.
+/// * `surcharge` - The lamports added to the price of a high-priority command.
+pub fn set_priority_surcharge(svm: &mut LiteSVM, authority: &Keypair, surcharge: u64) {
+    let surcharge_ix = ix_set_priority_surcharge(authority, surcharge);
+    build_and_send_tx(svm, vec![surcharge_ix], authority, vec![]);
+}
+
 /// A high-level test helper that withdraws earned funds from an `AdminProfile`.
 ///
 /// # Arguments
@@ -62,7 +110,30 @@ pub fn update_prices(svm: &mut LiteSVM, authority: &Keypair, new_prices: Vec<Pri
 /// * `destination` - The `Pubkey` of the wallet that will receive the withdrawn lamports.
 /// * `amount` - The amount of lamports to withdraw.
 pub fn withdraw(svm: &mut LiteSVM, authority: &Keypair, destination: Pubkey, amount: u64) {
-    let withdraw_ix = ix_withdraw(authority, destination, amount);
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", authority.pubkey().as_ref()],
+        &w3b2_bridge_program::ID,
+    );
+    withdraw_from(svm, authority, admin_pda, destination, amount);
+}
+
+/// A high-level test helper that withdraws earned funds from a given `AdminProfile`,
+/// e.g. one migrated to the v2 seeds.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`.
+/// * `admin_pda` - The `Pubkey` of the `AdminProfile` to withdraw from.
+/// * `destination` - The `Pubkey` of the wallet that will receive the withdrawn lamports.
+/// * `amount` - The amount of lamports to withdraw.
+pub fn withdraw_from(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) {
+    let withdraw_ix = ix_withdraw(authority, admin_pda, destination, amount);
     build_and_send_tx(svm, vec![withdraw_ix], authority, vec![]);
 }
 
@@ -81,7 +152,29 @@ pub fn dispatch_command(
     command_id: u64,
     payload: Vec<u8>,
 ) {
-    let dispatch_ix = ix_dispatch_command(authority, user_profile_pda, command_id, payload);
+    let dispatch_ix = ix_dispatch_command(authority, user_profile_pda, command_id, 0, payload);
+    build_and_send_tx(svm, vec![dispatch_ix], authority, vec![]);
+}
+
+/// A high-level test helper that sends a command to a user together with a rebate
+/// paid from the admin's balance into the user's deposit.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The admin's `ChainCard` `Keypair`, who is initiating the command.
+/// * `user_profile_pda` - The `Pubkey` of the target `UserProfile` account.
+/// * `command_id` - The `u64` identifier for the command.
+/// * `rebate` - The lamports to move from the admin's balance to the user's deposit.
+/// * `payload` - A `Vec<u8>` containing arbitrary data for the command.
+pub fn dispatch_command_with_rebate(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    user_profile_pda: Pubkey,
+    command_id: u64,
+    rebate: u64,
+    payload: Vec<u8>,
+) {
+    let dispatch_ix = ix_dispatch_command(authority, user_profile_pda, command_id, rebate, payload);
     build_and_send_tx(svm, vec![dispatch_ix], authority, vec![]);
 }
 
@@ -108,7 +201,7 @@ fn ix_create_profile(authority: &Keypair, communication_pubkey: Pubkey) -> (Inst
     let accounts = w3b2_accounts::AdminRegisterProfile {
         authority: authority.pubkey(),
         admin_profile: admin_pda,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -165,6 +258,66 @@ fn ix_close_profile(authority: &Keypair) -> Instruction {
     }
 }
 
+/// A low-level builder for the `admin_close_profile_to` instruction.
+fn ix_close_profile_to(authority: &Keypair, destination: Pubkey) -> Instruction {
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", authority.pubkey().as_ref()],
+        &w3b2_bridge_program::ID,
+    );
+
+    let data = w3b2_instruction::AdminCloseProfileTo {}.data();
+
+    let accounts = w3b2_accounts::AdminCloseProfileTo {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        destination,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts,
+        data,
+    }
+}
+
+/// A low-level builder for the `admin_migrate_to_v2` instruction.
+///
+/// # Returns
+/// A tuple containing the configured `Instruction` and the `Pubkey` of the v2 PDA.
+fn ix_migrate_to_v2(authority: &Keypair, profile_index: u16) -> (Instruction, Pubkey) {
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", authority.pubkey().as_ref()],
+        &w3b2_bridge_program::ID,
+    );
+    let (admin_pda_v2, _) = Pubkey::find_program_address(
+        &[
+            ADMIN_SEED_V2,
+            authority.pubkey().as_ref(),
+            &profile_index.to_le_bytes(),
+        ],
+        &w3b2_bridge_program::ID,
+    );
+
+    let data = w3b2_instruction::AdminMigrateToV2 { profile_index }.data();
+
+    let accounts = w3b2_accounts::AdminMigrateToV2 {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+        admin_profile_v2: admin_pda_v2,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    let ix = Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts,
+        data,
+    };
+
+    (ix, admin_pda_v2)
+}
+
 /// A low-level builder for the `admin_update_prices` instruction.
 fn ix_update_prices(authority: &Keypair, new_prices: Vec<PriceEntry>) -> Instruction {
     let (admin_pda, _) = Pubkey::find_program_address(
@@ -178,7 +331,7 @@ fn ix_update_prices(authority: &Keypair, new_prices: Vec<PriceEntry>) -> Instruc
     let accounts = w3b2_accounts::AdminUpdatePrices {
         authority: authority.pubkey(),
         admin_profile: admin_pda,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -189,20 +342,64 @@ fn ix_update_prices(authority: &Keypair, new_prices: Vec<PriceEntry>) -> Instruc
     }
 }
 
-/// A low-level builder for the `admin_withdraw` instruction.
-fn ix_withdraw(authority: &Keypair, destination: Pubkey, amount: u64) -> Instruction {
+/// A low-level builder for the `admin_set_gc_policy` instruction.
+fn ix_set_gc_policy(authority: &Keypair, inactivity_epochs: u64) -> Instruction {
     let (admin_pda, _) = Pubkey::find_program_address(
         &[b"admin", authority.pubkey().as_ref()],
         &w3b2_bridge_program::ID,
     );
 
+    let data = w3b2_instruction::AdminSetGcPolicy { inactivity_epochs }.data();
+
+    let accounts = w3b2_accounts::AdminSetGcPolicy {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts,
+        data,
+    }
+}
+
+/// A low-level builder for the `admin_set_priority_surcharge` instruction.
+fn ix_set_priority_surcharge(authority: &Keypair, surcharge: u64) -> Instruction {
+    let (admin_pda, _) = Pubkey::find_program_address(
+        &[b"admin", authority.pubkey().as_ref()],
+        &w3b2_bridge_program::ID,
+    );
+
+    let data = w3b2_instruction::AdminSetPrioritySurcharge { surcharge }.data();
+
+    let accounts = w3b2_accounts::AdminSetPrioritySurcharge {
+        authority: authority.pubkey(),
+        admin_profile: admin_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts,
+        data,
+    }
+}
+
+/// A low-level builder for the `admin_withdraw` instruction.
+fn ix_withdraw(
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> Instruction {
     let data = w3b2_instruction::AdminWithdraw { amount }.data();
 
     let accounts = w3b2_accounts::AdminWithdraw {
         authority: authority.pubkey(),
         admin_profile: admin_pda,
         destination,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -218,6 +415,7 @@ fn ix_dispatch_command(
     authority: &Keypair,
     user_profile_pda: Pubkey,
     command_id: u64,
+    rebate: u64,
     payload: Vec<u8>,
 ) -> Instruction {
     let (admin_pda, _) = Pubkey::find_program_address(
@@ -227,6 +425,7 @@ fn ix_dispatch_command(
 
     let data = w3b2_instruction::AdminDispatchCommand {
         command_id,
+        rebate,
         payload,
     }
     .data();
@@ -1,13 +1,16 @@
 // tests/instructions/mod.rs
 
+// Each test binary uses only a subset of these shared helpers.
+#![allow(dead_code)]
+
 /// This module contains high-level test helper functions for Admin-related instructions.
 pub mod admin;
 /// This module contains high-level test helper functions for User-related instructions.
 pub mod user;
 
-use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
 use litesvm::LiteSVM;
-use solana_program::{instruction::Instruction, pubkey::Pubkey, system_program};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
 use solana_sdk::{
     compute_budget::ComputeBudgetInstruction, signature::Keypair, signer::Signer,
     transaction::Transaction,
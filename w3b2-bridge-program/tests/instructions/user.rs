@@ -59,6 +59,23 @@ pub fn close_profile(svm: &mut LiteSVM, authority: &Keypair, admin_pda: Pubkey)
     build_and_send_tx(svm, vec![close_ix], authority, vec![]);
 }
 
+/// A high-level test helper that garbage-collects an inactive `UserProfile`.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `caller` - The `Keypair` of whoever cranks the collection. Pays the fee but receives nothing.
+/// * `user_authority` - The `Pubkey` of the user who owns the profile and receives the rent refund.
+/// * `admin_pda` - The `Pubkey` of the `AdminProfile` the user is associated with.
+pub fn gc_inactive_profile(
+    svm: &mut LiteSVM,
+    caller: &Keypair,
+    user_authority: Pubkey,
+    admin_pda: Pubkey,
+) {
+    let gc_ix = ix_gc_inactive_profile(caller, user_authority, admin_pda);
+    build_and_send_tx(svm, vec![gc_ix], caller, vec![]);
+}
+
 /// A high-level test helper that deposits lamports into a `UserProfile` PDA.
 ///
 /// # Arguments
@@ -105,7 +122,58 @@ pub fn dispatch_command(
     command_id: u16,
     payload: Vec<u8>,
 ) {
-    let dispatch_ix = ix_dispatch_command(authority, admin_pda, command_id, payload);
+    let dispatch_ix =
+        ix_dispatch_command(authority, admin_pda, admin_pda, command_id, false, payload);
+    build_and_send_tx(svm, vec![dispatch_ix], authority, vec![]);
+}
+
+/// A high-level test helper that sends a command flagged as high priority, paying the
+/// admin's priority surcharge on top of the command price.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The user's `ChainCard` `Keypair`, who is initiating the command.
+/// * `admin_pda` - The `Pubkey` of the target `AdminProfile` service.
+/// * `command_id` - The `u16` identifier for the command.
+/// * `payload` - A `Vec<u8>` containing arbitrary data for the command.
+pub fn dispatch_priority_command(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    admin_pda: Pubkey,
+    command_id: u16,
+    payload: Vec<u8>,
+) {
+    let dispatch_ix =
+        ix_dispatch_command(authority, admin_pda, admin_pda, command_id, true, payload);
+    build_and_send_tx(svm, vec![dispatch_ix], authority, vec![]);
+}
+
+/// A high-level test helper that sends a command from a `UserProfile` created for one
+/// `AdminProfile` to the profile that now serves it, e.g. its v2 replacement.
+///
+/// # Arguments
+/// * `svm` - A mutable reference to the `LiteSVM` test environment.
+/// * `authority` - The user's `ChainCard` `Keypair`, who is initiating the command.
+/// * `linked_admin_pda` - The `Pubkey` of the `AdminProfile` the user profile was created for.
+/// * `admin_pda` - The `Pubkey` of the target `AdminProfile` service.
+/// * `command_id` - The `u16` identifier for the command.
+/// * `payload` - A `Vec<u8>` containing arbitrary data for the command.
+pub fn dispatch_command_via(
+    svm: &mut LiteSVM,
+    authority: &Keypair,
+    linked_admin_pda: Pubkey,
+    admin_pda: Pubkey,
+    command_id: u16,
+    payload: Vec<u8>,
+) {
+    let dispatch_ix = ix_dispatch_command(
+        authority,
+        linked_admin_pda,
+        admin_pda,
+        command_id,
+        false,
+        payload,
+    );
     build_and_send_tx(svm, vec![dispatch_ix], authority, vec![]);
 }
 
@@ -136,7 +204,7 @@ fn ix_create_profile(
     let accounts = w3b2_accounts::UserCreateProfile {
         authority: authority.pubkey(),
         user_profile: user_pda,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -173,6 +241,34 @@ fn ix_update_comm_key(authority: &Keypair, admin_pda: Pubkey, new_key: Pubkey) -
     }
 }
 
+/// A low-level builder for the `gc_inactive_profile` instruction.
+fn ix_gc_inactive_profile(
+    caller: &Keypair,
+    user_authority: Pubkey,
+    admin_pda: Pubkey,
+) -> Instruction {
+    let (user_pda, _) = Pubkey::find_program_address(
+        &[b"user", user_authority.as_ref(), admin_pda.as_ref()],
+        &w3b2_bridge_program::ID,
+    );
+
+    let data = w3b2_instruction::GcInactiveProfile {}.data();
+
+    let accounts = w3b2_accounts::GcInactiveProfile {
+        caller: caller.pubkey(),
+        admin_profile: admin_pda,
+        authority: user_authority,
+        user_profile: user_pda,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts,
+        data,
+    }
+}
+
 /// A low-level builder for the `user_close_profile` instruction.
 fn ix_close_profile(authority: &Keypair, admin_pda: Pubkey) -> Instruction {
     let (user_pda, _) = Pubkey::find_program_address(
@@ -209,7 +305,7 @@ fn ix_deposit(authority: &Keypair, admin_pda: Pubkey, amount: u64) -> Instructio
         authority: authority.pubkey(),
         admin_profile: admin_pda,
         user_profile: user_pda,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -239,7 +335,7 @@ fn ix_withdraw(
         admin_profile: admin_pda,
         user_profile: user_pda,
         destination,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
@@ -253,17 +349,24 @@ fn ix_withdraw(
 /// A low-level builder for the `user_dispatch_command` instruction.
 fn ix_dispatch_command(
     authority: &Keypair,
+    linked_admin_pda: Pubkey,
     admin_pda: Pubkey,
     command_id: u16,
+    high_priority: bool,
     payload: Vec<u8>,
 ) -> Instruction {
     let (user_pda, _) = Pubkey::find_program_address(
-        &[b"user", authority.pubkey().as_ref(), admin_pda.as_ref()],
+        &[
+            b"user",
+            authority.pubkey().as_ref(),
+            linked_admin_pda.as_ref(),
+        ],
         &w3b2_bridge_program::ID,
     );
 
     let data = w3b2_instruction::UserDispatchCommand {
         command_id,
+        high_priority,
         payload,
     }
     .data();
@@ -272,7 +375,7 @@ fn ix_dispatch_command(
         authority: authority.pubkey(),
         user_profile: user_pda,
         admin_profile: admin_pda,
-        system_program: system_program::id(),
+        system_program: system_program::ID,
     }
     .to_account_metas(None);
 
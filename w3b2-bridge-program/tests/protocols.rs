@@ -0,0 +1,70 @@
+//! Tests for the off-chain protocol helpers in `w3b2_bridge_program::protocols`.
+//!
+//! These run purely in-process and do not need the compiled program binary.
+
+use w3b2_bridge_program::protocols::{
+    HeaderError, PayloadCodec, PayloadHeader, PAYLOAD_HEADER_LEN, PAYLOAD_HEADER_VERSION,
+};
+
+/// A header written with `wrap` must parse back to the same header and body.
+#[test]
+fn test_payload_header_roundtrip() {
+    let header = PayloadHeader::new(PayloadCodec::Borsh, true);
+    let payload = header.wrap(b"body");
+
+    let (parsed, body) = PayloadHeader::parse(&payload).unwrap();
+
+    assert_eq!(payload.len(), PAYLOAD_HEADER_LEN + 4);
+    assert_eq!(parsed, header);
+    assert_eq!(body, b"body");
+}
+
+/// The chunk flag must survive a roundtrip alongside the encryption flag.
+#[test]
+fn test_payload_header_chunk_flag() {
+    let header = PayloadHeader::new(PayloadCodec::Json, true).as_chunk();
+    let payload = header.wrap(b"part");
+
+    let (parsed, body) = PayloadHeader::parse(&payload).unwrap();
+
+    assert!(parsed.chunked && parsed.encrypted);
+    assert_eq!(payload[4], 0b0000_0011);
+    assert_eq!(body, b"part");
+    assert!(!PayloadHeader::new(PayloadCodec::Json, true).chunked);
+}
+
+/// Every kind of malformed header must be rejected with a specific error.
+#[test]
+fn test_payload_header_rejects_malformed() {
+    let valid = PayloadHeader::new(PayloadCodec::Raw, false).to_bytes();
+
+    assert_eq!(
+        PayloadHeader::parse(&valid[..3]),
+        Err(HeaderError::TooShort)
+    );
+
+    let mut bad_magic = valid;
+    bad_magic[0] = b'X';
+    assert_eq!(PayloadHeader::parse(&bad_magic), Err(HeaderError::BadMagic));
+
+    let mut bad_version = valid;
+    bad_version[2] = PAYLOAD_HEADER_VERSION + 1;
+    assert_eq!(
+        PayloadHeader::parse(&bad_version),
+        Err(HeaderError::UnsupportedVersion(PAYLOAD_HEADER_VERSION + 1))
+    );
+
+    let mut bad_codec = valid;
+    bad_codec[3] = 200;
+    assert_eq!(
+        PayloadHeader::parse(&bad_codec),
+        Err(HeaderError::UnknownCodec(200))
+    );
+
+    let mut bad_flags = valid;
+    bad_flags[4] = 0b1000_0000;
+    assert_eq!(
+        PayloadHeader::parse(&bad_flags),
+        Err(HeaderError::ReservedFlags(0b1000_0000))
+    );
+}
@@ -9,9 +9,11 @@ mod instructions;
 
 use anchor_lang::AccountDeserialize;
 use instructions::*;
+use solana_program::clock::Clock;
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::sysvar::rent::Rent;
 use solana_sdk::signature::Signer;
+use w3b2_bridge_program::protocols::{PayloadCodec, PayloadHeader};
 use w3b2_bridge_program::state::{AdminProfile, PriceEntry, UserProfile};
 
 /// Tests the successful creation of a `UserProfile` PDA.
@@ -74,6 +76,7 @@ fn test_user_create_profile_success() {
     let space = 8 + std::mem::size_of::<UserProfile>();
     let rent_exempt_minimum = rent.minimum_balance(space);
     assert_eq!(user_account_data.lamports, rent_exempt_minimum);
+    assert_eq!(user_profile.rent_reserve, rent_exempt_minimum);
 
     println!("✅ Create User Profile Test Passed!");
     println!("   -> User Authority: {}", user_profile.authority);
@@ -191,6 +194,67 @@ fn test_user_close_profile_success() {
     );
 }
 
+/// Tests that an inactive `UserProfile` can be garbage-collected by a third party.
+///
+/// ### Scenario
+/// A user abandons their profile. Once the admin's inactivity threshold has passed,
+/// anyone may crank the collection and the rent goes back to the user.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` is created and its GC policy set to 2 epochs.
+/// 2. A `UserProfile` is created with an empty deposit balance.
+/// 3. The `Clock` sysvar is warped forward past the threshold.
+///
+/// ### Act
+/// An unrelated `caller` invokes the `user::gc_inactive_profile` helper.
+///
+/// ### Assert
+/// 1. The `UserProfile` PDA account no longer exists.
+/// 2. The user's `ChainCard` balance increased by exactly the PDA's rent; the caller paid the fee.
+#[test]
+fn test_gc_inactive_profile_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    admin::set_gc_policy(&mut svm, &admin_authority, 2);
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+
+    let pda_balance = svm.get_balance(&user_pda).unwrap();
+    let authority_balance_before = svm.get_balance(&user_authority.pubkey()).unwrap();
+
+    let mut clock: Clock = svm.get_sysvar();
+    clock.epoch += 3;
+    svm.set_sysvar(&clock);
+
+    let caller = create_funded_keypair(&mut svm, LAMPORTS_PER_SOL);
+
+    // === 2. Act ===
+    user::gc_inactive_profile(&mut svm, &caller, user_authority.pubkey(), admin_pda);
+
+    // === 3. Assert ===
+    assert!(
+        svm.get_account(&user_pda).is_none(),
+        "Account was not collected!"
+    );
+
+    let authority_balance_after = svm.get_balance(&user_authority.pubkey()).unwrap();
+    assert_eq!(
+        authority_balance_after,
+        authority_balance_before + pda_balance
+    );
+
+    println!("✅ GC Inactive Profile Test Passed!");
+}
+
 /// Tests the successful deposit of funds into a `UserProfile`.
 ///
 /// ### Scenario
@@ -401,7 +465,7 @@ fn test_user_dispatch_command_success() {
         &user_authority,
         admin_pda,
         command_id_to_call,
-        vec![1, 2, 3], // Arbitrary payload
+        PayloadHeader::new(PayloadCodec::Raw, false).wrap(&[1, 2, 3]), // Arbitrary payload
     );
     println!("Command dispatched successfully.");
 
@@ -444,3 +508,97 @@ fn test_user_dispatch_command_success() {
         admin_profile_before.balance, admin_profile_after.balance
     );
 }
+
+/// Tests that a high-priority command is charged the admin's priority surcharge.
+///
+/// ### Scenario
+/// A user jumps the service's queue by paying the surcharge the admin advertised.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` is created with a price for one command and a priority surcharge.
+/// 2. A `UserProfile` is created and funded.
+///
+/// ### Act
+/// The `user::dispatch_priority_command` helper is called.
+///
+/// ### Assert
+/// 1. The user's deposit decreased by the price plus the surcharge.
+/// 2. The admin's balance increased by the same amount.
+#[test]
+fn test_user_dispatch_priority_command_success() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+    let command_price = LAMPORTS_PER_SOL / 10;
+    let surcharge = LAMPORTS_PER_SOL / 100;
+    admin::update_prices(
+        &mut svm,
+        &admin_authority,
+        vec![PriceEntry::new(1, command_price)],
+    );
+    admin::set_priority_surcharge(&mut svm, &admin_authority, surcharge);
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let user_pda = user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+    let deposit_amount = LAMPORTS_PER_SOL;
+    user::deposit(&mut svm, &user_authority, admin_pda, deposit_amount);
+
+    // === 2. Act ===
+    let payload = PayloadHeader::new(PayloadCodec::Raw, false).wrap(&[]);
+    user::dispatch_priority_command(&mut svm, &user_authority, admin_pda, 1, payload);
+
+    // === 3. Assert ===
+    let user_account = svm.get_account(&user_pda).unwrap();
+    let user_profile = UserProfile::try_deserialize(&mut user_account.data.as_slice()).unwrap();
+    assert_eq!(
+        user_profile.deposit_balance,
+        deposit_amount - command_price - surcharge
+    );
+
+    let admin_account = svm.get_account(&admin_pda).unwrap();
+    let admin_profile = AdminProfile::try_deserialize(&mut admin_account.data.as_slice()).unwrap();
+    assert_eq!(admin_profile.balance, command_price + surcharge);
+
+    println!("✅ User Dispatch Priority Command Test Passed!");
+}
+
+/// Tests that a dispatch with a malformed payload header is rejected.
+///
+/// ### Scenario
+/// A client sends a payload that does not start with the `PayloadHeader` envelope.
+///
+/// ### Arrange
+/// 1. An `AdminProfile` and a linked `UserProfile` are created.
+///
+/// ### Act
+/// The `user::dispatch_command` helper is called with a header-less payload.
+///
+/// ### Assert
+/// The transaction fails with `MalformedPayloadHeader`, which the helper surfaces as a panic.
+#[test]
+#[should_panic(expected = "Transaction failed")]
+fn test_user_dispatch_command_rejects_malformed_header() {
+    // === 1. Arrange ===
+    let mut svm = setup_svm();
+
+    let admin_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    let admin_pda = admin::create_profile(&mut svm, &admin_authority, create_keypair().pubkey());
+
+    let user_authority = create_funded_keypair(&mut svm, 10 * LAMPORTS_PER_SOL);
+    user::create_profile(
+        &mut svm,
+        &user_authority,
+        create_keypair().pubkey(),
+        admin_pda,
+    );
+
+    // === 2. Act ===
+    user::dispatch_command(&mut svm, &user_authority, admin_pda, 1, vec![1, 2, 3]);
+}
@@ -0,0 +1,55 @@
+// File: w3b2-connector/src/backoff.rs
+
+//! # Reconnection Backoff
+//!
+//! `Backoff` spaces out attempts to re-establish a lost connection. The delay
+//! doubles after every failed attempt up to a ceiling, and each delay is drawn at
+//! random from its upper half so that many connectors losing the same node do not
+//! all reconnect at the same instant.
+
+use rand::Rng;
+use std::time::Duration;
+
+/// A jittered exponential backoff.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    /// The number of delays handed out since the last reset.
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Creates a backoff whose delays grow from `min` to at most `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            attempts: 0,
+        }
+    }
+
+    /// Returns the delay to wait before the next attempt and backs off further.
+    ///
+    /// The `n`-th delay since the last reset lies between half of and the full
+    /// `min * 2^n`, capped at `max`.
+    pub fn next_delay(&mut self) -> Duration {
+        let ceiling = self
+            .min
+            .checked_mul(2u32.saturating_pow(self.attempts))
+            .map_or(self.max, |delay| delay.min(self.max));
+        self.attempts = self.attempts.saturating_add(1);
+        let half = ceiling / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=ceiling - half)
+    }
+
+    /// Returns the number of delays handed out since the last reset.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Starts over from `min`, after a connection was established.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
@@ -0,0 +1,62 @@
+// File: w3b2-connector/src/blockhash.rs
+
+//! A shared, time-bounded cache for the cluster's latest blockhash.
+//!
+//! A blockhash stays valid for roughly 150 slots, so there is no need to hit the
+//! RPC node for a fresh one on every prepared transaction. One `BlockhashCache`
+//! is meant to be shared (via `Arc`) by every `TransactionBuilder` in the process.
+
+use crate::rpc::SolanaRpc;
+use solana_client::client_error::ClientError;
+use solana_sdk::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Caches the latest blockhash and refreshes it once it is older than `refresh_interval`.
+pub struct BlockhashCache {
+    rpc_client: Arc<dyn SolanaRpc>,
+    refresh_interval: Duration,
+    /// The cached blockhash and the moment it was fetched.
+    cached: Mutex<Option<(Hash, Instant)>>,
+}
+
+impl BlockhashCache {
+    /// Creates a new, empty cache. The first call to `get` fetches from the RPC node.
+    ///
+    /// # Arguments
+    ///
+    /// * `rpc_client` - The client used to fetch blockhashes.
+    /// * `refresh_interval` - How long a fetched blockhash is reused before refreshing.
+    pub fn new(rpc_client: Arc<dyn SolanaRpc>, refresh_interval: Duration) -> Self {
+        Self {
+            rpc_client,
+            refresh_interval,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Returns the cached blockhash, fetching a new one if it is missing or stale.
+    ///
+    /// The lock is held across the fetch, so concurrent callers hitting a stale
+    /// cache trigger a single RPC request.
+    pub async fn get(&self) -> Result<Hash, ClientError> {
+        let mut cached = self.cached.lock().await;
+        if let Some((hash, fetched_at)) = *cached {
+            if fetched_at.elapsed() < self.refresh_interval {
+                return Ok(hash);
+            }
+        }
+
+        let hash = self.rpc_client.get_latest_blockhash().await?;
+        *cached = Some((hash, Instant::now()));
+        Ok(hash)
+    }
+
+    /// Drops the cached blockhash so the next `get` fetches a fresh one.
+    ///
+    /// Useful after a submission fails with `BlockhashNotFound`.
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+}
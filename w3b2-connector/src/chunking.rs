@@ -0,0 +1,202 @@
+// File: w3b2-connector/src/chunking.rs
+
+//! # Chunked Payloads
+//!
+//! A `dispatch` payload may not exceed `MAX_PAYLOAD_SIZE` bytes. Larger messages are
+//! split across several dispatches, each carrying a payload whose header has the
+//! `chunked` flag set, followed by a chunk frame and a slice of the message body:
+//!
+//! ```text
+//! | PayloadHeader (5) | sequence_id: u64 LE | index: u16 LE | total: u16 LE | data |
+//! ```
+//!
+//! `sequence_id` ties the chunks of one message together; it only has to be unique
+//! per sender. The header's codec and encryption flag describe the whole message, so
+//! a reassembled message decodes exactly like one that was sent in one piece.
+//!
+//! `split_payload` produces the chunk payloads, and `TransactionBuilder` has
+//! `prepare_*_dispatch_command_chunked` methods that turn them into transactions. On
+//! the receiving side, a `Reassembler` collects chunks from command events until a
+//! message is complete. Chunks may arrive in any order.
+
+use crate::events::BridgeEvent;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+use w3b2_bridge_program::instructions::MAX_PAYLOAD_SIZE;
+use w3b2_bridge_program::protocols::{PayloadHeader, PAYLOAD_HEADER_LEN};
+
+/// The size of the chunk frame that follows the header of every chunk.
+pub const CHUNK_FRAME_LEN: usize = 12;
+
+/// The most message body bytes a single chunk carries.
+pub const MAX_CHUNK_DATA: usize = MAX_PAYLOAD_SIZE - PAYLOAD_HEADER_LEN - CHUNK_FRAME_LEN;
+
+/// The reasons a payload cannot be split or a chunk cannot be reassembled.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ChunkError {
+    #[error("Invalid payload header: {0}")]
+    InvalidHeader(String),
+
+    #[error("A {0}-byte message needs more than {max} chunks", max = u16::MAX)]
+    TooLarge(usize),
+
+    #[error("Malformed chunk: {0}")]
+    Malformed(&'static str),
+
+    #[error("Chunk {index} of sequence {sequence_id} does not match the earlier chunks")]
+    Mismatch { sequence_id: u64, index: u16 },
+}
+
+/// Splits a complete payload (header and body) into chunk payloads of at most
+/// `MAX_PAYLOAD_SIZE` bytes.
+///
+/// A payload that already fits is returned unchanged, as the only element.
+pub fn split_payload(payload: &[u8], sequence_id: u64) -> Result<Vec<Vec<u8>>, ChunkError> {
+    if payload.len() <= MAX_PAYLOAD_SIZE {
+        return Ok(vec![payload.to_vec()]);
+    }
+    let (header, body) =
+        PayloadHeader::parse(payload).map_err(|e| ChunkError::InvalidHeader(format!("{:?}", e)))?;
+    if header.chunked {
+        return Err(ChunkError::InvalidHeader(
+            "the payload is already a chunk".to_string(),
+        ));
+    }
+    let total = u16::try_from(body.len().div_ceil(MAX_CHUNK_DATA))
+        .map_err(|_| ChunkError::TooLarge(payload.len()))?;
+
+    let header = header.as_chunk().to_bytes();
+    Ok(body
+        .chunks(MAX_CHUNK_DATA)
+        .enumerate()
+        .map(|(index, data)| {
+            let mut chunk = Vec::with_capacity(PAYLOAD_HEADER_LEN + CHUNK_FRAME_LEN + data.len());
+            chunk.extend_from_slice(&header);
+            chunk.extend_from_slice(&sequence_id.to_le_bytes());
+            chunk.extend_from_slice(&(index as u16).to_le_bytes());
+            chunk.extend_from_slice(&total.to_le_bytes());
+            chunk.extend_from_slice(data);
+            chunk
+        })
+        .collect())
+}
+
+/// The chunks received so far for one message.
+struct PartialMessage {
+    header: PayloadHeader,
+    parts: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// Reassembles chunked messages, keyed by sender and sequence id.
+pub struct Reassembler {
+    pending: HashMap<(Pubkey, u64), PartialMessage>,
+    /// The pending messages, oldest first.
+    order: VecDeque<(Pubkey, u64)>,
+    max_pending: usize,
+}
+
+impl Reassembler {
+    /// Creates a reassembler that keeps at most `max_pending` incomplete messages,
+    /// dropping the oldest one when another starts.
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+            max_pending: max_pending.max(1),
+        }
+    }
+
+    /// Returns the number of incomplete messages.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Feeds the payload of a `UserCommandDispatched` or `AdminCommandDispatched`
+    /// event. Other events are ignored.
+    ///
+    /// See `push` for the return value.
+    pub fn push_event(&mut self, event: &BridgeEvent) -> Result<Option<Vec<u8>>, ChunkError> {
+        match event {
+            BridgeEvent::UserCommandDispatched(e) => self.push(e.sender, &e.payload),
+            BridgeEvent::AdminCommandDispatched(e) => self.push(e.sender, &e.payload),
+            _ => Ok(None),
+        }
+    }
+
+    /// Feeds a payload sent by `sender`.
+    ///
+    /// Returns the complete message payload, with a regular header, once its last
+    /// chunk arrives. Payloads that are not chunked are returned as they are.
+    /// Repeated chunks are ignored. A chunk that contradicts the earlier chunks of
+    /// its message is an error, and the message is dropped.
+    pub fn push(&mut self, sender: Pubkey, payload: &[u8]) -> Result<Option<Vec<u8>>, ChunkError> {
+        let Ok((header, body)) = PayloadHeader::parse(payload) else {
+            return Ok(Some(payload.to_vec()));
+        };
+        if !header.chunked {
+            return Ok(Some(payload.to_vec()));
+        }
+        if body.len() < CHUNK_FRAME_LEN {
+            return Err(ChunkError::Malformed("the chunk frame is truncated"));
+        }
+        let (frame, data) = body.split_at(CHUNK_FRAME_LEN);
+        let sequence_id = u64::from_le_bytes(frame[0..8].try_into().unwrap());
+        let index = u16::from_le_bytes(frame[8..10].try_into().unwrap());
+        let total = u16::from_le_bytes(frame[10..12].try_into().unwrap());
+        if index >= total {
+            return Err(ChunkError::Malformed("the chunk index is out of range"));
+        }
+
+        let key = (sender, sequence_id);
+        if !self.pending.contains_key(&key) {
+            self.make_room();
+            self.order.push_back(key);
+        }
+        let message = self.pending.entry(key).or_insert_with(|| PartialMessage {
+            header,
+            parts: vec![None; usize::from(total)],
+            received: 0,
+        });
+        if message.header != header || message.parts.len() != usize::from(total) {
+            self.remove(&key);
+            return Err(ChunkError::Mismatch { sequence_id, index });
+        }
+
+        let part = &mut message.parts[usize::from(index)];
+        if part.is_none() {
+            *part = Some(data.to_vec());
+            message.received += 1;
+        }
+        if message.received < message.parts.len() {
+            return Ok(None);
+        }
+
+        let message = self.remove(&key).expect("message is pending");
+        let mut header = message.header;
+        header.chunked = false;
+        let body: Vec<u8> = message.parts.into_iter().flatten().flatten().collect();
+        Ok(Some(header.wrap(&body)))
+    }
+
+    fn make_room(&mut self) {
+        while self.pending.len() >= self.max_pending {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if self.pending.remove(&oldest).is_some() {
+                tracing::warn!(
+                    "Dropping incomplete chunked message {} from {}",
+                    oldest.1,
+                    oldest.0
+                );
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &(Pubkey, u64)) -> Option<PartialMessage> {
+        self.order.retain(|pending| pending != key);
+        self.pending.remove(key)
+    }
+}
@@ -1,18 +1,316 @@
 // File: w3b2-connector/src/client.rs
 
-use anchor_lang::{InstructionData, ToAccountMetas};
-use solana_client::client_error::ClientError;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::instruction::Instruction;
+use crate::blockhash::BlockhashCache;
+use crate::chunking;
+use crate::events::{parse_logs, BridgeEvent};
+use crate::instructions;
+use crate::prices::{self, PriceCache};
+use crate::rpc::{is_account_not_found, SolanaRpc};
+use crate::signing::{self, SigningError, TransactionSigner};
+use anchor_lang::AccountDeserialize;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_config::{
+    RpcSendTransactionConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_message::{v0, AddressLookupTableAccount, VersionedMessage};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
 use std::sync::Arc;
+use std::time::Duration;
 use w3b2_bridge_program::{
-    accounts, instruction,
-    state::{PriceEntry, UpdatePricesArgs},
+    errors::BridgeError,
+    state::{AdminProfile, PriceEntry, UserProfile},
 };
 
+/// The compute-unit limit requested by default. Comfortably covers every bridge instruction.
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// The highest compute-unit limit a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// The longest memo, in bytes, `with_memo` accepts. Keeps room in the transaction
+/// for the bridge instruction itself.
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Compute budget settings prepended to every prepared transaction.
+///
+/// A `None` field means the corresponding `ComputeBudget` instruction is not added
+/// and the runtime default applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudget {
+    /// The maximum number of compute units the transaction may consume.
+    pub unit_limit: Option<u32>,
+    /// The priority fee, in micro-lamports per compute unit.
+    pub unit_price: Option<u64>,
+}
+
+impl Default for ComputeBudget {
+    fn default() -> Self {
+        Self {
+            unit_limit: Some(DEFAULT_COMPUTE_UNIT_LIMIT),
+            unit_price: None,
+        }
+    }
+}
+
+impl ComputeBudget {
+    /// Returns the `ComputeBudget` program instructions for these settings.
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut ixs = Vec::with_capacity(2);
+        if let Some(limit) = self.unit_limit {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+        if let Some(price) = self.unit_price {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        ixs
+    }
+}
+
+/// The outcome of simulating a prepared transaction against the current cluster state.
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    /// The transaction-level error, if the simulation failed.
+    pub err: Option<TransactionError>,
+    /// The program error, if the failure came from the W3B2 Bridge Program itself.
+    pub bridge_error: Option<BridgeError>,
+    /// The program logs emitted during simulation.
+    pub logs: Vec<String>,
+    /// The number of compute units consumed.
+    pub units_consumed: Option<u64>,
+}
+
+impl SimulationResult {
+    /// Returns `true` if the transaction would succeed if submitted now.
+    pub fn is_ok(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+/// What a `user_dispatch_command` would do if submitted now, see
+/// `TransactionBuilder::preview_dispatch`.
+#[derive(Debug, Clone)]
+pub struct DispatchPreview {
+    /// The lamports the command would be charged, including `priority_fee`.
+    /// `None` if the dispatch would fail.
+    pub price: Option<u64>,
+    /// The priority surcharge included in `price`.
+    pub priority_fee: Option<u64>,
+    /// The user's spendable deposit after paying. `None` if the dispatch would fail.
+    pub remaining_deposit: Option<u64>,
+    /// The full simulation outcome, including the program error if it would fail.
+    pub simulation: SimulationResult,
+}
+
+impl DispatchPreview {
+    /// Returns `true` if the dispatch would succeed if submitted now.
+    pub fn is_ok(&self) -> bool {
+        self.simulation.is_ok()
+    }
+}
+
+/// Controls how `TransactionBuilder::submit_with_policy` delivers a signed transaction.
+#[derive(Debug, Clone)]
+pub struct SubmitPolicy {
+    /// How many times the transaction is re-sent after the initial broadcast.
+    pub max_retries: usize,
+    /// The delay between two broadcasts, during which the signature status is polled.
+    pub rebroadcast_interval: Duration,
+    /// The commitment level the transaction must reach to count as confirmed.
+    pub commitment: CommitmentConfig,
+    /// Skips the preflight simulation on the initial broadcast.
+    pub skip_preflight: bool,
+}
+
+impl Default for SubmitPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            rebroadcast_interval: Duration::from_secs(2),
+            commitment: CommitmentConfig::confirmed(),
+            skip_preflight: false,
+        }
+    }
+}
+
+/// The final state of a transaction submitted with `TransactionBuilder::submit_with_policy`.
+#[derive(Debug, Clone)]
+pub enum SubmitOutcome {
+    /// The transaction landed and reached the requested commitment.
+    Confirmed {
+        signature: Signature,
+        slot: u64,
+        attempts: usize,
+    },
+    /// The transaction was rejected, either by preflight or on-chain.
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+        bridge_error: Option<BridgeError>,
+    },
+    /// The blockhash expired before the transaction landed. It can never land now
+    /// and must be re-prepared and re-signed.
+    Expired {
+        signature: Signature,
+        attempts: usize,
+    },
+    /// All retries were used up while the blockhash was still valid. The transaction
+    /// may still land, so the signature should be checked again before re-submitting.
+    Unconfirmed {
+        signature: Signature,
+        attempts: usize,
+    },
+}
+
+impl SubmitOutcome {
+    /// Returns the signature of the submitted transaction.
+    pub fn signature(&self) -> &Signature {
+        match self {
+            Self::Confirmed { signature, .. }
+            | Self::Failed { signature, .. }
+            | Self::Expired { signature, .. }
+            | Self::Unconfirmed { signature, .. } => signature,
+        }
+    }
+}
+
+/// Returns the signature that identifies a transaction, i.e. the fee payer's.
+/// Unsigned transactions are identified by the default signature.
+fn first_signature(signatures: &[Signature]) -> Signature {
+    signatures.first().copied().unwrap_or_default()
+}
+
+/// Splits `payload` into dispatch payloads under a fresh sequence id.
+fn split_for_dispatch(payload: &[u8]) -> Result<Vec<Vec<u8>>, chunking::ChunkError> {
+    chunking::split_payload(payload, rand::random())
+}
+
+/// Maps a transaction error back to a `BridgeError`, if it carries one of the program's custom codes.
+pub fn decode_bridge_error(err: &TransactionError) -> Option<BridgeError> {
+    let TransactionError::InstructionError(_, InstructionError::Custom(code)) = err else {
+        return None;
+    };
+    let error = match code.checked_sub(anchor_lang::error::ERROR_CODE_OFFSET)? {
+        0 => BridgeError::SignerUnauthorized,
+        1 => BridgeError::AdminMismatch,
+        2 => BridgeError::InsufficientDepositBalance,
+        3 => BridgeError::InsufficientAdminBalance,
+        4 => BridgeError::RentExemptViolation,
+        5 => BridgeError::CommandNotFound,
+        6 => BridgeError::PayloadTooLarge,
+        7 => BridgeError::DepositNotEmpty,
+        8 => BridgeError::ProfileStillActive,
+        9 => BridgeError::GcDisabled,
+        10 => BridgeError::MalformedPayloadHeader,
+        11 => BridgeError::ProfileMigrated,
+        12 => BridgeError::V2SeedsDisabled,
+        _ => return None,
+    };
+    Some(error)
+}
+
+/// The simulation settings used for prepared transactions: signatures are not
+/// verified, the blockhash is replaced, and `addresses` are returned after the run.
+fn simulation_config(addresses: &[Pubkey]) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        accounts: (!addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: addresses.iter().map(Pubkey::to_string).collect(),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Converts the node's simulation result, decoding any `BridgeError`.
+fn simulation_result(result: RpcSimulateTransactionResult) -> SimulationResult {
+    SimulationResult {
+        bridge_error: result.err.as_ref().and_then(decode_bridge_error),
+        err: result.err,
+        logs: result.logs.unwrap_or_default(),
+        units_consumed: result.units_consumed,
+    }
+}
+
+/// Recompiles a prepared legacy transaction into an unsigned v0 transaction with
+/// the same instructions, compute budget included, and the same blockhash.
+///
+/// Accounts found in `lookup_tables` are referenced by index instead of being
+/// inlined, so any `prepare_` method can be offered as a v0 transaction.
+///
+/// # Arguments
+///
+/// * `transaction` - An unsigned transaction from a `prepare_` method.
+/// * `lookup_tables` - Tables resolved with `resolve_lookup_tables`; may be empty.
+#[allow(clippy::result_large_err)]
+pub fn to_versioned_transaction(
+    transaction: &Transaction,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction, ClientError> {
+    let message = &transaction.message;
+    let Some(payer) = message.account_keys.first() else {
+        return Err(ClientErrorKind::Custom("transaction has no fee payer".to_string()).into());
+    };
+    let instructions: Vec<Instruction> = message
+        .instructions
+        .iter()
+        .map(|ix| Instruction {
+            program_id: message.account_keys[ix.program_id_index as usize],
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|&index| {
+                    let index = index as usize;
+                    AccountMeta {
+                        pubkey: message.account_keys[index],
+                        is_signer: message.is_signer(index),
+                        is_writable: message.is_maybe_writable(index, None),
+                    }
+                })
+                .collect(),
+            data: ix.data.clone(),
+        })
+        .collect();
+    compile_v0(
+        payer,
+        &instructions,
+        lookup_tables,
+        message.recent_blockhash,
+    )
+}
+
+/// Compiles `instructions` into an unsigned v0 transaction.
+#[allow(clippy::result_large_err)]
+fn compile_v0(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction, ClientError> {
+    let message =
+        v0::Message::try_compile(payer, instructions, lookup_tables, blockhash).map_err(|e| {
+            ClientError::from(ClientErrorKind::Custom(format!(
+                "failed to compile v0 message: {e}"
+            )))
+        })?;
+
+    let num_signers = message.header.num_required_signatures as usize;
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); num_signers],
+        message: VersionedMessage::V0(message),
+    })
+}
+
 /// A client for preparing on-chain transactions for remote signing.
 ///
 /// This struct provides methods to construct unsigned transactions for every
@@ -21,10 +319,24 @@ use w3b2_bridge_program::{
 /// The server-side component (like a gRPC gateway) uses this builder to create
 /// a transaction, sends it to the client for signing, and then receives the
 /// signed transaction back for submission.
+///
+/// The builder is cheap to clone, so a per-call compute budget override is just
+/// `builder.clone().with_compute_budget(..)`.
 #[derive(Clone)]
 pub struct TransactionBuilder {
     /// A shared, thread-safe reference to the Solana JSON RPC client.
-    rpc_client: Arc<RpcClient>,
+    rpc_client: Arc<dyn SolanaRpc>,
+    /// Compute budget instructions added in front of every prepared transaction.
+    compute_budget: ComputeBudget,
+    /// If set and `compute_budget` has no unit price, the price is estimated from
+    /// this percentile of the recent priority fees.
+    auto_fee_percentile: Option<u8>,
+    /// An SPL Memo appended to every prepared transaction.
+    memo: Option<String>,
+    /// An optional shared blockhash cache. Without it, every transaction fetches a fresh blockhash.
+    blockhash_cache: Option<Arc<BlockhashCache>>,
+    /// An optional shared price cache for budget checks. Without it, prices are fetched on demand.
+    price_cache: Option<Arc<PriceCache>>,
 }
 
 impl TransactionBuilder {
@@ -32,9 +344,175 @@ impl TransactionBuilder {
     ///
     /// # Arguments
     ///
-    /// * `rpc_client` - A shared `Arc<RpcClient>` for communicating with the Solana cluster.
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self { rpc_client }
+    /// * `rpc_client` - A shared RPC client for communicating with the Solana cluster,
+    ///   typically an `Arc<RpcClient>`.
+    pub fn new(rpc_client: Arc<dyn SolanaRpc>) -> Self {
+        Self {
+            rpc_client,
+            compute_budget: ComputeBudget::default(),
+            auto_fee_percentile: None,
+            memo: None,
+            blockhash_cache: None,
+            price_cache: None,
+        }
+    }
+
+    /// Makes this builder take blockhashes from a shared `BlockhashCache`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache, typically shared by every builder in the process.
+    pub fn with_blockhash_cache(mut self, cache: Arc<BlockhashCache>) -> Self {
+        self.blockhash_cache = Some(cache);
+        self
+    }
+
+    /// Makes this builder take command prices for budget checks from a shared `PriceCache`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache, ideally kept up to date with `PriceCache::spawn_watcher`.
+    pub fn with_price_cache(mut self, cache: Arc<PriceCache>) -> Self {
+        self.price_cache = Some(cache);
+        self
+    }
+
+    /// Returns the RPC client the builder prepares and submits transactions with.
+    pub(crate) fn rpc_client(&self) -> &dyn SolanaRpc {
+        self.rpc_client.as_ref()
+    }
+
+    /// Returns a recent blockhash, from the cache if one is configured.
+    async fn latest_blockhash(&self) -> Result<Hash, ClientError> {
+        match &self.blockhash_cache {
+            Some(cache) => cache.get().await,
+            None => self.rpc_client.get_latest_blockhash().await,
+        }
+    }
+
+    /// Replaces the compute budget used for transactions prepared by this builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `compute_budget` - The compute-unit limit and priority fee to request.
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudget) -> Self {
+        self.compute_budget = compute_budget;
+        self
+    }
+
+    /// Makes this builder set the priority fee from recent fees whenever the
+    /// compute budget has no `unit_price`, see `estimate_compute_unit_price`.
+    ///
+    /// # Arguments
+    ///
+    /// * `percentile` - Which percentile of the recent fees to pay, from 0 to 100.
+    ///   Higher values get transactions included faster under congestion.
+    pub fn with_auto_priority_fee(mut self, percentile: u8) -> Self {
+        self.auto_fee_percentile = Some(percentile.min(100));
+        self
+    }
+
+    /// Makes this builder append an SPL Memo instruction carrying `memo` to every
+    /// prepared transaction, e.g. an order or invoice ID shown by explorers.
+    ///
+    /// # Arguments
+    ///
+    /// * `memo` - The memo text, at most `MAX_MEMO_LEN` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `memo` is longer than `MAX_MEMO_LEN` bytes.
+    #[allow(clippy::result_large_err)]
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Result<Self, ClientError> {
+        let memo = memo.into();
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(ClientErrorKind::Custom(format!(
+                "memo is {} bytes, the limit is {MAX_MEMO_LEN}",
+                memo.len()
+            ))
+            .into());
+        }
+        self.memo = Some(memo);
+        Ok(self)
+    }
+
+    /// Appends the memo instruction, if one is configured, to `instructions`.
+    fn push_memo(&self, instructions: &mut Vec<Instruction>) {
+        if let Some(memo) = &self.memo {
+            instructions.push(spl_memo::build_memo(memo.as_bytes(), &[]));
+        }
+    }
+
+    /// Estimates a compute unit price, in micro-lamports, from the priority fees
+    /// paid in recent slots by transactions writing to `writable_accounts`.
+    ///
+    /// Returns 0 if no fees were reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `writable_accounts` - The accounts the transaction will write to.
+    /// * `percentile` - Which percentile of the recent fees to return, from 0 to 100.
+    pub async fn estimate_compute_unit_price(
+        &self,
+        writable_accounts: &[Pubkey],
+        percentile: u8,
+    ) -> Result<u64, ClientError> {
+        let mut fees: Vec<u64> = self
+            .rpc_client
+            .get_recent_prioritization_fees(writable_accounts)
+            .await?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        let index = (fees.len() - 1) * usize::from(percentile.min(100)) / 100;
+        Ok(fees[index])
+    }
+
+    /// Returns the compute budget instructions to prepend to `instructions`,
+    /// estimating the unit price first if the builder is set to.
+    async fn compute_budget_instructions(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let mut compute_budget = self.compute_budget;
+        if let (None, Some(percentile)) = (compute_budget.unit_price, self.auto_fee_percentile) {
+            let mut writable: Vec<Pubkey> = instructions
+                .iter()
+                .flat_map(|ix| ix.accounts.iter())
+                .filter(|meta| meta.is_writable)
+                .map(|meta| meta.pubkey)
+                .collect();
+            writable.sort();
+            writable.dedup();
+            let price = self
+                .estimate_compute_unit_price(&writable, percentile)
+                .await?;
+            compute_budget.unit_price = Some(price);
+        }
+        Ok(compute_budget.instructions())
+    }
+
+    /// Signs a prepared transaction with `signer`, the middle stage between a
+    /// `prepare_` method and `submit_transaction`.
+    ///
+    /// The signer may live outside this process, e.g. a hardware wallet; its
+    /// signature is verified before it is added. Transactions with several signers
+    /// are signed by calling this once per signer.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The prepared transaction. Its blockhash is kept.
+    /// * `signer` - One of the transaction's required signers.
+    pub async fn sign_with(
+        &self,
+        transaction: &mut Transaction,
+        signer: &dyn TransactionSigner,
+    ) -> Result<(), SigningError> {
+        signing::sign_with(transaction, signer).await
     }
 
     /// Submits a fully signed transaction to the Solana network.
@@ -50,6 +528,7 @@ impl TransactionBuilder {
     /// # Returns
     ///
     /// A `Result` containing the `Signature` of the confirmed transaction.
+    #[tracing::instrument(skip_all, err, fields(signature = %first_signature(&transaction.signatures)))]
     pub async fn submit_transaction(
         &self,
         transaction: &Transaction,
@@ -59,19 +538,400 @@ impl TransactionBuilder {
             .await
     }
 
-    /// A private helper function to create a transaction from a single instruction.
+    /// Broadcasts a fully signed transaction once, after a preflight simulation,
+    /// without waiting for it to land.
+    ///
+    /// Pair it with a `TxTracker` to follow the transaction's status afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A `Transaction` object that has already been signed.
+    #[tracing::instrument(skip_all, err, fields(signature = %first_signature(&transaction.signatures)))]
+    pub async fn broadcast_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, ClientError> {
+        self.rpc_client
+            .send_transaction_with_config(transaction, RpcSendTransactionConfig::default())
+            .await
+    }
+
+    /// Broadcasts a fully signed v0 transaction once, after a preflight simulation,
+    /// without waiting for it to land.
+    ///
+    /// The versioned counterpart of `broadcast_transaction`.
+    #[tracing::instrument(skip_all, err, fields(signature = %first_signature(&transaction.signatures)))]
+    pub async fn broadcast_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature, ClientError> {
+        self.rpc_client
+            .send_versioned_transaction_with_config(
+                transaction,
+                RpcSendTransactionConfig::default(),
+            )
+            .await
+    }
+
+    /// Submits a fully signed transaction, re-broadcasting it until it is confirmed,
+    /// rejected, or its blockhash expires.
+    ///
+    /// Unlike `submit_transaction`, transaction-level failures are reported as a
+    /// `SubmitOutcome` rather than an error; `Err` is only returned for transport problems
+    /// on the initial broadcast or while polling.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A `Transaction` object that has already been signed.
+    /// * `policy` - The retry and confirmation policy to apply.
+    #[tracing::instrument(
+        skip_all,
+        err,
+        fields(signature = %first_signature(&transaction.signatures), max_retries = policy.max_retries)
+    )]
+    pub async fn submit_with_policy(
+        &self,
+        transaction: &Transaction,
+        policy: &SubmitPolicy,
+    ) -> Result<SubmitOutcome, ClientError> {
+        let signature = first_signature(&transaction.signatures);
+        let mut attempts = 0;
+
+        while attempts <= policy.max_retries {
+            let config = RpcSendTransactionConfig {
+                // Only the first broadcast is simulated; re-broadcasts are identical.
+                skip_preflight: policy.skip_preflight || attempts > 0,
+                preflight_commitment: Some(policy.commitment.commitment),
+                max_retries: Some(0),
+                ..Default::default()
+            };
+            let sent = self
+                .rpc_client
+                .send_transaction_with_config(transaction, config)
+                .await;
+            attempts += 1;
+            tracing::debug!(attempt = attempts, "Broadcast transaction");
+
+            if let Err(e) = sent {
+                if let Some(error) = e.get_transaction_error() {
+                    return Ok(SubmitOutcome::Failed {
+                        signature,
+                        bridge_error: decode_bridge_error(&error),
+                        error,
+                    });
+                }
+                if attempts == 1 {
+                    return Err(e);
+                }
+                tracing::warn!("Re-broadcast of {} failed: {}", signature, e);
+            }
+
+            let statuses = self
+                .rpc_client
+                .get_signature_statuses(&[signature])
+                .await?
+                .value;
+            if let Some(Some(status)) = statuses.into_iter().next() {
+                if let Some(error) = status.err {
+                    return Ok(SubmitOutcome::Failed {
+                        signature,
+                        bridge_error: decode_bridge_error(&error),
+                        error,
+                    });
+                }
+                if status.satisfies_commitment(policy.commitment) {
+                    return Ok(SubmitOutcome::Confirmed {
+                        signature,
+                        slot: status.slot,
+                        attempts,
+                    });
+                }
+            }
+
+            let blockhash_valid = self
+                .rpc_client
+                .is_blockhash_valid(&transaction.message.recent_blockhash, policy.commitment)
+                .await?;
+            if !blockhash_valid {
+                if let Some(cache) = &self.blockhash_cache {
+                    cache.invalidate().await;
+                }
+                return Ok(SubmitOutcome::Expired {
+                    signature,
+                    attempts,
+                });
+            }
+
+            tokio::time::sleep(policy.rebroadcast_interval).await;
+        }
+
+        Ok(SubmitOutcome::Unconfirmed {
+            signature,
+            attempts,
+        })
+    }
+
+    /// Submits a fully signed v0 transaction to the Solana network.
+    ///
+    /// The versioned counterpart of `submit_transaction`, for transactions prepared
+    /// by `prepare_versioned_transaction`.
+    #[tracing::instrument(skip_all, err, fields(signature = %first_signature(&transaction.signatures)))]
+    pub async fn submit_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature, ClientError> {
+        self.rpc_client
+            .send_and_confirm_versioned_transaction(transaction)
+            .await
+    }
+
+    /// Simulates a prepared transaction without submitting it.
+    ///
+    /// Signatures are not verified and the blockhash is replaced by the node, so an
+    /// unsigned transaction straight from a `prepare_` method can be validated before
+    /// it is handed to the user for signing.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction to simulate, signed or not.
+    pub async fn simulate(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<SimulationResult, ClientError> {
+        Ok(self.simulate_with_accounts(transaction, &[]).await?.0)
+    }
+
+    /// Simulates a prepared v0 transaction without submitting it.
+    ///
+    /// The versioned counterpart of `simulate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction to simulate, signed or not.
+    pub async fn simulate_versioned(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<SimulationResult, ClientError> {
+        let result = self
+            .rpc_client
+            .simulate_versioned_transaction_with_config(transaction, simulation_config(&[]))
+            .await?
+            .value;
+        Ok(simulation_result(result))
+    }
+
+    /// Simulates a prepared transaction and also returns the state of `addresses`
+    /// after it. The states are `None` if the simulation failed.
+    async fn simulate_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<(SimulationResult, Vec<Option<Account>>), ClientError> {
+        let mut result = self
+            .rpc_client
+            .simulate_transaction_with_config(transaction, simulation_config(addresses))
+            .await?
+            .value;
+
+        let accounts = result
+            .accounts
+            .take()
+            .map(|accounts| {
+                accounts
+                    .into_iter()
+                    .map(|account| account.and_then(|account| account.decode()))
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![None; addresses.len()]);
+        Ok((simulation_result(result), accounts))
+    }
+
+    /// Simulates a `user_dispatch_command` and reports what it would cost, so a
+    /// wallet can show the price before the user signs.
+    ///
+    /// The price is taken from the event the program would emit, and the remaining
+    /// deposit from the simulated `UserProfile`, so both reflect exactly what the
+    /// program would do. If the dispatch would fail, only the error is known; use a
+    /// `PriceCache` to show the listed price regardless.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The user's `ChainCard` public key.
+    /// * `admin_profile_pda` - The service to dispatch to.
+    /// * `command_id` - The command to price.
+    /// * `high_priority` - Whether the priority surcharge would be paid.
+    /// * `payload` - The payload to dispatch, which the program validates as well.
+    pub async fn preview_dispatch(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        command_id: u16,
+        high_priority: bool,
+        payload: Vec<u8>,
+    ) -> Result<DispatchPreview, ClientError> {
+        let transaction = self
+            .prepare_user_dispatch_command(
+                authority,
+                admin_profile_pda,
+                command_id,
+                high_priority,
+                payload,
+            )
+            .await?;
+        let user_pda = instructions::user_profile_pda(&authority, &admin_profile_pda);
+        let (simulation, accounts) = self
+            .simulate_with_accounts(&transaction, &[user_pda])
+            .await?;
+
+        let dispatched = parse_logs(&simulation.logs)
+            .0
+            .into_iter()
+            .find_map(|event| match event {
+                BridgeEvent::UserCommandDispatched(e) if simulation.is_ok() => Some(e),
+                _ => None,
+            });
+        let remaining_deposit = accounts
+            .into_iter()
+            .next()
+            .flatten()
+            .filter(|_| simulation.is_ok())
+            .and_then(|account| UserProfile::try_deserialize(&mut account.data.as_slice()).ok())
+            .map(|profile| profile.deposit_balance);
+
+        Ok(DispatchPreview {
+            price: dispatched.as_ref().map(|e| e.price_paid),
+            priority_fee: dispatched.as_ref().map(|e| e.priority_fee),
+            remaining_deposit,
+            simulation,
+        })
+    }
+
+    /// Fetches and decodes Address Lookup Tables so they can be used to compile a v0 message.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_addresses` - The on-chain addresses of the lookup tables.
+    pub async fn resolve_lookup_tables(
+        &self,
+        table_addresses: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>, ClientError> {
+        let mut tables = Vec::with_capacity(table_addresses.len());
+        for key in table_addresses {
+            let account = self.rpc_client.get_account(key).await?;
+            let table = AddressLookupTable::deserialize(&account.data).map_err(|e| {
+                ClientError::from(ClientErrorKind::Custom(format!(
+                    "invalid address lookup table {key}: {e}"
+                )))
+            })?;
+            tables.push(AddressLookupTableAccount {
+                key: *key,
+                addresses: table.addresses.to_vec(),
+            });
+        }
+        Ok(tables)
+    }
+
+    /// Returns the address of the `AdminProfile` that `authority` manages: its v1 PDA,
+    /// or, once that profile was migrated with `admin_migrate_to_v2`, the v2 PDA it
+    /// points to. The `prepare_admin_` methods act on this address.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The admin's `ChainCard` public key.
+    pub async fn resolve_admin_profile(&self, authority: &Pubkey) -> Result<Pubkey, ClientError> {
+        let admin_pda = instructions::admin_profile_pda(authority);
+        let account = match self.rpc_client.get_account(&admin_pda).await {
+            Ok(account) => account,
+            // Not registered (yet); the program reports that when the transaction runs.
+            Err(e) if is_account_not_found(&e) => return Ok(admin_pda),
+            Err(e) => return Err(e),
+        };
+        let migrated_to = AdminProfile::try_deserialize(&mut account.data.as_slice())
+            .ok()
+            .and_then(|profile| profile.migrated_to);
+        Ok(migrated_to.unwrap_or(admin_pda))
+    }
+
+    /// Prepares an unsigned v0 transaction from an arbitrary set of instructions.
+    ///
+    /// Accounts found in `lookup_tables` are referenced by index instead of being
+    /// inlined, which lets multi-instruction flows touching many accounts stay within
+    /// the transaction size limit. The configured compute budget is prepended and the
+    /// memo appended as usual.
+    ///
+    /// # Arguments
+    ///
+    /// * `payer` - The fee payer and first required signer.
+    /// * `instructions` - The instructions to include, in execution order.
+    /// * `lookup_tables` - Tables resolved with `resolve_lookup_tables`; may be empty.
+    pub async fn prepare_versioned_transaction(
+        &self,
+        payer: Pubkey,
+        instructions: Vec<Instruction>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, ClientError> {
+        let latest_blockhash = self.latest_blockhash().await?;
+        let mut all_instructions = self.compute_budget_instructions(&instructions).await?;
+        all_instructions.extend(instructions);
+        self.push_memo(&mut all_instructions);
+        compile_v0(&payer, &all_instructions, lookup_tables, latest_blockhash)
+    }
+
+    /// Prepares an unsigned legacy transaction from an arbitrary set of instructions.
+    ///
+    /// This function encapsulates the boilerplate of fetching the latest blockhash,
+    /// prepending the compute budget instructions, appending the memo, if any, and creating
+    /// a new transaction with a payer.
+    /// All instructions execute atomically: if one fails, the whole transaction is rolled back.
+    ///
+    /// # Arguments
     ///
-    /// This function encapsulates the boilerplate of fetching the latest blockhash
-    /// and creating a new transaction with a payer.
+    /// * `payer` - The fee payer and first required signer.
+    /// * `instructions` - The instructions to include, in execution order.
+    pub async fn prepare_transaction(
+        &self,
+        payer: Pubkey,
+        instructions: Vec<Instruction>,
+    ) -> Result<Transaction, ClientError> {
+        let latest_blockhash = self.latest_blockhash().await?;
+        let mut all_instructions = self.compute_budget_instructions(&instructions).await?;
+        all_instructions.extend(instructions);
+        self.push_memo(&mut all_instructions);
+        let mut tx = Transaction::new_with_payer(&all_instructions, Some(&payer));
+        tx.message.recent_blockhash = latest_blockhash;
+        Ok(tx)
+    }
+
+    /// Starts a `TransactionBatch` that combines several bridge instructions into one
+    /// atomic transaction paid for by `payer`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let tx = builder
+    ///     .batch(user)
+    ///     .user_create_profile(admin_pda, comm_key)
+    ///     .user_deposit(admin_pda, 1_000_000)
+    ///     .user_dispatch_command(admin_pda, 1, false, payload)
+    ///     .build()
+    ///     .await?;
+    /// ```
+    pub fn batch(&self, payer: Pubkey) -> TransactionBatch {
+        TransactionBatch {
+            builder: self.clone(),
+            payer,
+            admin_profile: instructions::admin_profile_pda(&payer),
+            instructions: Vec::new(),
+        }
+    }
+
+    /// A private helper function to create a transaction from a single instruction.
     async fn create_transaction(
         &self,
         payer: &Pubkey,
         instruction: Instruction,
     ) -> Result<Transaction, ClientError> {
-        let latest_blockhash = self.rpc_client.get_latest_blockhash().await?;
-        let mut tx = Transaction::new_with_payer(&[instruction], Some(payer));
-        tx.message.recent_blockhash = latest_blockhash;
-        Ok(tx)
+        self.prepare_transaction(*payer, vec![instruction]).await
     }
 
     // --- Admin Transaction Preparations ---
@@ -87,23 +947,7 @@ impl TransactionBuilder {
         authority: Pubkey,
         communication_pubkey: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminRegisterProfile {
-                authority,
-                admin_profile: admin_pda,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::AdminRegisterProfile {
-                communication_pubkey,
-            }
-            .data(),
-        };
-
+        let ix = instructions::admin_register_profile(authority, communication_pubkey);
         self.create_transaction(&authority, ix).await
     }
 
@@ -113,19 +957,8 @@ impl TransactionBuilder {
         authority: Pubkey,
         new_key: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminUpdateCommKey {
-                authority,
-                admin_profile: admin_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::AdminUpdateCommKey { new_key }.data(),
-        };
-
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_update_comm_key(authority, admin_pda, new_key);
         self.create_transaction(&authority, ix).await
     }
 
@@ -135,23 +968,30 @@ impl TransactionBuilder {
         authority: Pubkey,
         new_prices: Vec<PriceEntry>,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_update_prices(authority, admin_pda, new_prices);
+        self.create_transaction(&authority, ix).await
+    }
 
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminUpdatePrices {
-                authority,
-                admin_profile: admin_pda,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::AdminUpdatePrices {
-                args: UpdatePricesArgs { new_prices },
-            }
-            .data(),
-        };
+    /// Prepares an `admin_set_gc_policy` transaction.
+    pub async fn prepare_admin_set_gc_policy(
+        &self,
+        authority: Pubkey,
+        inactivity_epochs: u64,
+    ) -> Result<Transaction, ClientError> {
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_set_gc_policy(authority, admin_pda, inactivity_epochs);
+        self.create_transaction(&authority, ix).await
+    }
 
+    /// Prepares an `admin_set_priority_surcharge` transaction.
+    pub async fn prepare_admin_set_priority_surcharge(
+        &self,
+        authority: Pubkey,
+        surcharge: u64,
+    ) -> Result<Transaction, ClientError> {
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_set_priority_surcharge(authority, admin_pda, surcharge);
         self.create_transaction(&authority, ix).await
     }
 
@@ -162,21 +1002,8 @@ impl TransactionBuilder {
         amount: u64,
         destination: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminWithdraw {
-                authority,
-                admin_profile: admin_pda,
-                destination,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::AdminWithdraw { amount }.data(),
-        };
-
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_withdraw(authority, admin_pda, amount, destination);
         self.create_transaction(&authority, ix).await
     }
 
@@ -185,19 +1012,29 @@ impl TransactionBuilder {
         &self,
         authority: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_close_profile(authority, admin_pda);
+        self.create_transaction(&authority, ix).await
+    }
 
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminCloseProfile {
-                authority,
-                admin_profile: admin_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::AdminCloseProfile {}.data(),
-        };
+    /// Prepares an `admin_close_profile_to` transaction, sweeping the profile's balance to `destination`.
+    pub async fn prepare_admin_close_profile_to(
+        &self,
+        authority: Pubkey,
+        destination: Pubkey,
+    ) -> Result<Transaction, ClientError> {
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_close_profile_to(authority, admin_pda, destination);
+        self.create_transaction(&authority, ix).await
+    }
 
+    /// Prepares an `admin_migrate_to_v2` transaction, moving the admin's profile to the v2 seeds.
+    pub async fn prepare_admin_migrate_to_v2(
+        &self,
+        authority: Pubkey,
+        profile_index: u16,
+    ) -> Result<Transaction, ClientError> {
+        let ix = instructions::admin_migrate_to_v2(authority, profile_index);
         self.create_transaction(&authority, ix).await
     }
 
@@ -207,29 +1044,53 @@ impl TransactionBuilder {
         authority: Pubkey,
         target_user_profile_pda: Pubkey,
         command_id: u64,
+        rebate: u64,
         payload: Vec<u8>,
     ) -> Result<Transaction, ClientError> {
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::AdminDispatchCommand {
-                admin_authority: authority,
-                admin_profile: admin_pda,
-                user_profile: target_user_profile_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::AdminDispatchCommand {
-                command_id,
-                payload,
-            }
-            .data(),
-        };
-
+        let admin_pda = self.resolve_admin_profile(&authority).await?;
+        let ix = instructions::admin_dispatch_command(
+            authority,
+            admin_pda,
+            target_user_profile_pda,
+            command_id,
+            rebate,
+            payload,
+        );
         self.create_transaction(&authority, ix).await
     }
 
+    /// Prepares the `admin_dispatch_command` transactions that deliver `payload` in
+    /// chunks, see `chunking`. A payload that fits yields a single transaction.
+    ///
+    /// `rebate` is only paid with the first chunk. The transactions can be submitted
+    /// in any order.
+    pub async fn prepare_admin_dispatch_command_chunked(
+        &self,
+        authority: Pubkey,
+        target_user_profile_pda: Pubkey,
+        command_id: u64,
+        rebate: u64,
+        payload: &[u8],
+    ) -> Result<Vec<Transaction>, ClientError> {
+        let chunks = split_for_dispatch(payload)
+            .map_err(|e| ClientError::from(ClientErrorKind::Custom(e.to_string())))?;
+        let mut transactions = Vec::new();
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let rebate = if index == 0 { rebate } else { 0 };
+            transactions.push(
+                self.prepare_admin_dispatch_command(
+                    authority,
+                    target_user_profile_pda,
+                    command_id,
+                    rebate,
+                    chunk,
+                )
+                .await?,
+            );
+        }
+        Ok(transactions)
+    }
+
     // --- User Transaction Preparations ---
 
     /// Prepares a `user_create_profile` transaction.
@@ -239,26 +1100,8 @@ impl TransactionBuilder {
         target_admin_pda: Pubkey,
         communication_pubkey: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), target_admin_pda.as_ref()],
-            &w3b2_bridge_program::ID,
-        );
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserCreateProfile {
-                authority,
-                user_profile: user_pda,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::UserCreateProfile {
-                target_admin: target_admin_pda,
-                communication_pubkey,
-            }
-            .data(),
-        };
-
+        let ix =
+            instructions::user_create_profile(authority, target_admin_pda, communication_pubkey);
         self.create_transaction(&authority, ix).await
     }
 
@@ -269,22 +1112,7 @@ impl TransactionBuilder {
         admin_profile_pda: Pubkey,
         new_key: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_bridge_program::ID,
-        );
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserUpdateCommKey {
-                authority,
-                user_profile: user_pda,
-                admin_profile: admin_profile_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::UserUpdateCommKey { new_key }.data(),
-        };
-
+        let ix = instructions::user_update_comm_key(authority, admin_profile_pda, new_key);
         self.create_transaction(&authority, ix).await
     }
 
@@ -295,23 +1123,7 @@ impl TransactionBuilder {
         admin_profile_pda: Pubkey,
         amount: u64,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_bridge_program::ID,
-        );
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserDeposit {
-                authority,
-                user_profile: user_pda,
-                admin_profile: admin_profile_pda,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::UserDeposit { amount }.data(),
-        };
-
+        let ix = instructions::user_deposit(authority, admin_profile_pda, amount);
         self.create_transaction(&authority, ix).await
     }
 
@@ -323,24 +1135,7 @@ impl TransactionBuilder {
         amount: u64,
         destination: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_bridge_program::ID,
-        );
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserWithdraw {
-                authority,
-                user_profile: user_pda,
-                admin_profile: admin_profile_pda,
-                destination,
-                system_program: solana_sdk::system_program::id(),
-            }
-            .to_account_metas(None),
-            data: instruction::UserWithdraw { amount }.data(),
-        };
-
+        let ix = instructions::user_withdraw(authority, admin_profile_pda, amount, destination);
         self.create_transaction(&authority, ix).await
     }
 
@@ -350,22 +1145,7 @@ impl TransactionBuilder {
         authority: Pubkey,
         admin_profile_pda: Pubkey,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_bridge_program::ID,
-        );
-
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserCloseProfile {
-                authority,
-                user_profile: user_pda,
-                admin_profile: admin_profile_pda,
-            }
-            .to_account_metas(None),
-            data: instruction::UserCloseProfile {}.data(),
-        };
-
+        let ix = instructions::user_close_profile(authority, admin_profile_pda);
         self.create_transaction(&authority, ix).await
     }
 
@@ -377,30 +1157,137 @@ impl TransactionBuilder {
         authority: Pubkey,
         admin_profile_pda: Pubkey,
         command_id: u16,
+        high_priority: bool,
         payload: Vec<u8>,
     ) -> Result<Transaction, ClientError> {
-        let (user_pda, _) = Pubkey::find_program_address(
-            &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
-            &w3b2_bridge_program::ID,
+        let ix = instructions::user_dispatch_command(
+            authority,
+            admin_profile_pda,
+            command_id,
+            high_priority,
+            payload,
         );
+        self.create_transaction(&authority, ix).await
+    }
 
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::UserDispatchCommand {
-                authority,
-                user_profile: user_pda,
-                admin_profile: admin_profile_pda,
-                system_program: solana_sdk::system_program::id(),
+    /// Prepares a `user_dispatch_command` transaction, but only if the command costs
+    /// at most `max_price` lamports, priority surcharge included.
+    ///
+    /// The price comes from the `PriceCache` if one is configured, and from the
+    /// `AdminProfile` otherwise. This protects the user from a price they did not
+    /// expect, but the admin can still change prices before the transaction lands.
+    pub async fn prepare_user_dispatch_command_within_budget(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        command_id: u16,
+        high_priority: bool,
+        payload: Vec<u8>,
+        max_price: u64,
+    ) -> Result<Transaction, ClientError> {
+        let price = self
+            .dispatch_price(&admin_profile_pda, command_id, high_priority)
+            .await?;
+        if price > max_price {
+            return Err(ClientError::from(ClientErrorKind::Custom(format!(
+                "command {} at {} costs {} lamports, over the budget of {}",
+                command_id, admin_profile_pda, price, max_price
+            ))));
+        }
+        self.prepare_user_dispatch_command(
+            authority,
+            admin_profile_pda,
+            command_id,
+            high_priority,
+            payload,
+        )
+        .await
+    }
+
+    /// Returns what the program would charge for a dispatch to `admin_profile_pda`.
+    async fn dispatch_price(
+        &self,
+        admin_profile_pda: &Pubkey,
+        command_id: u16,
+        high_priority: bool,
+    ) -> Result<u64, ClientError> {
+        let price = match &self.price_cache {
+            Some(cache) => {
+                cache
+                    .get_dispatch_price(admin_profile_pda, command_id, high_priority)
+                    .await?
             }
-            .to_account_metas(None),
-            data: instruction::UserDispatchCommand {
-                command_id,
-                payload,
+            None => {
+                let account = self.rpc_client.get_account(admin_profile_pda).await?;
+                let profile =
+                    AdminProfile::try_deserialize(&mut account.data.as_slice()).map_err(|e| {
+                        ClientError::from(ClientErrorKind::Custom(format!(
+                            "failed to deserialize account {}: {}",
+                            admin_profile_pda, e
+                        )))
+                    })?;
+                Some(prices::dispatch_price(
+                    &profile.prices,
+                    profile.priority_surcharge,
+                    command_id,
+                    high_priority,
+                ))
             }
-            .data(),
         };
+        price.ok_or_else(|| {
+            ClientError::from(ClientErrorKind::Custom(format!(
+                "admin profile {} does not exist",
+                admin_profile_pda
+            )))
+        })
+    }
 
-        self.create_transaction(&authority, ix).await
+    /// Prepares the `user_dispatch_command` transactions that deliver `payload` in
+    /// chunks, see `chunking`. A payload that fits yields a single transaction.
+    ///
+    /// The command price is charged for every chunk. The transactions can be
+    /// submitted in any order.
+    pub async fn prepare_user_dispatch_command_chunked(
+        &self,
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+        command_id: u16,
+        high_priority: bool,
+        payload: &[u8],
+    ) -> Result<Vec<Transaction>, ClientError> {
+        let chunks = split_for_dispatch(payload)
+            .map_err(|e| ClientError::from(ClientErrorKind::Custom(e.to_string())))?;
+        let mut transactions = Vec::new();
+        for chunk in chunks {
+            transactions.push(
+                self.prepare_user_dispatch_command(
+                    authority,
+                    admin_profile_pda,
+                    command_id,
+                    high_priority,
+                    chunk,
+                )
+                .await?,
+            );
+        }
+        Ok(transactions)
+    }
+
+    /// Prepares a `gc_inactive_profile` transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `caller` - The crank operator who signs and pays the fee. Can be anyone.
+    /// * `user_authority` - The `ChainCard` that owns the abandoned profile and receives the rent.
+    /// * `admin_profile_pda` - The `AdminProfile` PDA the user profile is linked to.
+    pub async fn prepare_gc_inactive_profile(
+        &self,
+        caller: Pubkey,
+        user_authority: Pubkey,
+        admin_profile_pda: Pubkey,
+    ) -> Result<Transaction, ClientError> {
+        let ix = instructions::gc_inactive_profile(caller, user_authority, admin_profile_pda);
+        self.create_transaction(&caller, ix).await
     }
 
     /// Prepares a `log_action` transaction.
@@ -410,16 +1297,142 @@ impl TransactionBuilder {
         session_id: u64,
         action_code: u16,
     ) -> Result<Transaction, ClientError> {
-        let ix = Instruction {
-            program_id: w3b2_bridge_program::ID,
-            accounts: accounts::LogAction { authority }.to_account_metas(None),
-            data: instruction::LogAction {
-                session_id,
-                action_code,
-            }
-            .data(),
-        };
-
+        let ix = instructions::log_action(authority, session_id, action_code);
         self.create_transaction(&authority, ix).await
     }
 }
+
+/// A fluent builder that accumulates bridge instructions into a single atomic transaction.
+///
+/// Created by `TransactionBuilder::batch`. Every method appends one instruction signed by
+/// the batch's payer, so e.g. a user can create a profile, fund it and dispatch a first
+/// command with a single signature. Instructions signed by other parties can be added
+/// with `instruction`.
+pub struct TransactionBatch {
+    builder: TransactionBuilder,
+    payer: Pubkey,
+    /// The `AdminProfile` the admin instructions act on.
+    admin_profile: Pubkey,
+    instructions: Vec<Instruction>,
+}
+
+impl TransactionBatch {
+    /// Appends an arbitrary instruction.
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Returns the instructions accumulated so far.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Sets the `AdminProfile` the admin instructions act on, e.g. a migrated profile
+    /// found with `TransactionBuilder::resolve_admin_profile`. Defaults to the payer's
+    /// v1 PDA.
+    pub fn with_admin_profile(mut self, admin_profile_pda: Pubkey) -> Self {
+        self.admin_profile = admin_profile_pda;
+        self
+    }
+
+    /// Appends an `admin_update_prices` instruction.
+    pub fn admin_update_prices(self, new_prices: Vec<PriceEntry>) -> Self {
+        let ix = instructions::admin_update_prices(self.payer, self.admin_profile, new_prices);
+        self.instruction(ix)
+    }
+
+    /// Appends an `admin_withdraw` instruction.
+    pub fn admin_withdraw(self, amount: u64, destination: Pubkey) -> Self {
+        let ix = instructions::admin_withdraw(self.payer, self.admin_profile, amount, destination);
+        self.instruction(ix)
+    }
+
+    /// Appends an `admin_dispatch_command` instruction.
+    pub fn admin_dispatch_command(
+        self,
+        target_user_profile_pda: Pubkey,
+        command_id: u64,
+        rebate: u64,
+        payload: Vec<u8>,
+    ) -> Self {
+        let ix = instructions::admin_dispatch_command(
+            self.payer,
+            self.admin_profile,
+            target_user_profile_pda,
+            command_id,
+            rebate,
+            payload,
+        );
+        self.instruction(ix)
+    }
+
+    /// Appends a `user_create_profile` instruction.
+    pub fn user_create_profile(
+        self,
+        target_admin_pda: Pubkey,
+        communication_pubkey: Pubkey,
+    ) -> Self {
+        let ix =
+            instructions::user_create_profile(self.payer, target_admin_pda, communication_pubkey);
+        self.instruction(ix)
+    }
+
+    /// Appends a `user_deposit` instruction.
+    pub fn user_deposit(self, admin_profile_pda: Pubkey, amount: u64) -> Self {
+        let ix = instructions::user_deposit(self.payer, admin_profile_pda, amount);
+        self.instruction(ix)
+    }
+
+    /// Appends a `user_withdraw` instruction.
+    pub fn user_withdraw(
+        self,
+        admin_profile_pda: Pubkey,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Self {
+        let ix = instructions::user_withdraw(self.payer, admin_profile_pda, amount, destination);
+        self.instruction(ix)
+    }
+
+    /// Appends a `user_dispatch_command` instruction.
+    pub fn user_dispatch_command(
+        self,
+        admin_profile_pda: Pubkey,
+        command_id: u16,
+        high_priority: bool,
+        payload: Vec<u8>,
+    ) -> Self {
+        let ix = instructions::user_dispatch_command(
+            self.payer,
+            admin_profile_pda,
+            command_id,
+            high_priority,
+            payload,
+        );
+        self.instruction(ix)
+    }
+
+    /// Appends a `log_action` instruction.
+    pub fn log_action(self, session_id: u64, action_code: u16) -> Self {
+        let ix = instructions::log_action(self.payer, session_id, action_code);
+        self.instruction(ix)
+    }
+
+    /// Compiles the batch into an unsigned legacy transaction.
+    pub async fn build(self) -> Result<Transaction, ClientError> {
+        self.builder
+            .prepare_transaction(self.payer, self.instructions)
+            .await
+    }
+
+    /// Compiles the batch into an unsigned v0 transaction using the given lookup tables.
+    pub async fn build_versioned(
+        self,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, ClientError> {
+        self.builder
+            .prepare_versioned_transaction(self.payer, self.instructions, lookup_tables)
+            .await
+    }
+}
@@ -0,0 +1,59 @@
+//! Identifies the Solana cluster an RPC node belongs to.
+//!
+//! The public clusters are told apart by their genesis hash, so a node can be
+//! identified whatever URL it is reached through. Any other genesis hash is taken
+//! to be a local test validator.
+
+use solana_sdk::hash::Hash;
+use std::fmt;
+use std::str::FromStr;
+
+/// The genesis hash of mainnet-beta.
+pub const MAINNET_BETA_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+/// The genesis hash of testnet.
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+/// The genesis hash of devnet.
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// A Solana cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Testnet,
+    Devnet,
+    /// A local test validator, or any other cluster with an unknown genesis hash.
+    Localnet,
+}
+
+impl Cluster {
+    /// Returns the cluster whose genesis block has the hash `genesis_hash`.
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Self {
+        let is = |known: &str| Hash::from_str(known).is_ok_and(|known| known == *genesis_hash);
+        if is(MAINNET_BETA_GENESIS_HASH) {
+            Cluster::MainnetBeta
+        } else if is(TESTNET_GENESIS_HASH) {
+            Cluster::Testnet
+        } else if is(DEVNET_GENESIS_HASH) {
+            Cluster::Devnet
+        } else {
+            Cluster::Localnet
+        }
+    }
+
+    /// Returns whether this is a development cluster, i.e. devnet or a local
+    /// validator, where SOL has no value and can be airdropped.
+    pub fn is_development(self) -> bool {
+        matches!(self, Cluster::Devnet | Cluster::Localnet)
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Cluster::MainnetBeta => "mainnet-beta",
+            Cluster::Testnet => "testnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Localnet => "localnet",
+        })
+    }
+}
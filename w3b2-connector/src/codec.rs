@@ -0,0 +1,178 @@
+// File: w3b2-connector/src/codec.rs
+
+//! # Payload Codecs
+//!
+//! The `PayloadHeader` of every `dispatch` payload names the format of its body. This
+//! module turns that id into behaviour: a `PayloadCodec` (de)serializes one message
+//! type in one format, and a `CodecRegistry` picks the right codec for an incoming
+//! payload by its header, so services and clients agree on the wire format.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::collections::HashMap;
+use thiserror::Error;
+use w3b2_bridge_program::protocols::{PayloadCodec as CodecId, PayloadHeader};
+
+/// The reasons a payload can fail to encode or decode.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    #[error("Invalid payload header: {0}")]
+    InvalidHeader(String),
+
+    #[error("No codec registered for {0:?}")]
+    NotRegistered(CodecId),
+
+    #[error("Payload body is encrypted and must be decrypted first")]
+    Encrypted,
+
+    #[error("Encoding failed: {0}")]
+    Encode(String),
+
+    #[error("Decoding failed: {0}")]
+    Decode(String),
+}
+
+/// (De)serializes values of type `T` in the format identified by `id`.
+pub trait PayloadCodec<T>: Send + Sync {
+    /// The codec id written to the `PayloadHeader`.
+    fn id(&self) -> CodecId;
+
+    /// Serializes `value` into a payload body.
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError>;
+
+    /// Deserializes a payload body.
+    fn decode(&self, body: &[u8]) -> Result<T, CodecError>;
+}
+
+/// Passes bodies through as opaque bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawCodec;
+
+impl PayloadCodec<Vec<u8>> for RawCodec {
+    fn id(&self) -> CodecId {
+        CodecId::Raw
+    }
+
+    fn encode(&self, value: &Vec<u8>) -> Result<Vec<u8>, CodecError> {
+        Ok(value.clone())
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<Vec<u8>, CodecError> {
+        Ok(body.to_vec())
+    }
+}
+
+/// Encodes bodies with Borsh, the format used for `CommandConfig`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BorshCodec;
+
+impl<T: BorshSerialize + BorshDeserialize> PayloadCodec<T> for BorshCodec {
+    fn id(&self) -> CodecId {
+        CodecId::Borsh
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        borsh::to_vec(value).map_err(|e| CodecError::Encode(e.to_string()))
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<T, CodecError> {
+        T::try_from_slice(body).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+/// Encodes bodies as UTF-8 JSON.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> PayloadCodec<T> for JsonCodec {
+    fn id(&self) -> CodecId {
+        CodecId::Json
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).map_err(|e| CodecError::Encode(e.to_string()))
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<T, CodecError> {
+        serde_json::from_slice(body).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+/// Encodes bodies as Protocol Buffers messages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProtobufCodec;
+
+impl<T: prost::Message + Default> PayloadCodec<T> for ProtobufCodec {
+    fn id(&self) -> CodecId {
+        CodecId::Protobuf
+    }
+
+    fn encode(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        Ok(value.encode_to_vec())
+    }
+
+    fn decode(&self, body: &[u8]) -> Result<T, CodecError> {
+        T::decode(body).map_err(|e| CodecError::Decode(e.to_string()))
+    }
+}
+
+/// The set of codecs a party accepts for messages of type `T`, keyed by codec id.
+pub struct CodecRegistry<T> {
+    codecs: HashMap<u8, Box<dyn PayloadCodec<T>>>,
+}
+
+impl<T> Default for CodecRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> CodecRegistry<T> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            codecs: HashMap::new(),
+        }
+    }
+
+    /// Registers `codec` under its id, replacing any codec with the same id.
+    pub fn register<C: PayloadCodec<T> + 'static>(mut self, codec: C) -> Self {
+        self.codecs.insert(codec.id() as u8, Box::new(codec));
+        self
+    }
+
+    /// Returns whether a codec is registered for `id`.
+    pub fn supports(&self, id: CodecId) -> bool {
+        self.codecs.contains_key(&(id as u8))
+    }
+
+    /// Serializes `value` with the codec for `id` into a complete plaintext payload.
+    pub fn encode(&self, id: CodecId, value: &T) -> Result<Vec<u8>, CodecError> {
+        let body = self.codec(id)?.encode(value)?;
+        Ok(PayloadHeader::new(id, false).wrap(&body))
+    }
+
+    /// Parses the header of `payload` and decodes the body with the codec it names.
+    pub fn decode(&self, payload: &[u8]) -> Result<T, CodecError> {
+        let (header, body) = PayloadHeader::parse(payload)
+            .map_err(|e| CodecError::InvalidHeader(format!("{:?}", e)))?;
+        if header.encrypted {
+            return Err(CodecError::Encrypted);
+        }
+        self.decode_body(header.codec, body)
+    }
+
+    /// Decodes a body that has already been split from (and, if needed, decrypted
+    /// after) its header.
+    pub fn decode_body(&self, id: CodecId, body: &[u8]) -> Result<T, CodecError> {
+        self.codec(id)?.decode(body)
+    }
+
+    fn codec(&self, id: CodecId) -> Result<&dyn PayloadCodec<T>, CodecError> {
+        self.codecs
+            .get(&(id as u8))
+            .map(|codec| codec.as_ref())
+            .ok_or(CodecError::NotRegistered(id))
+    }
+}
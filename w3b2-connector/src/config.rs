@@ -1,11 +1,13 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey, signature::Signature};
+use thiserror::Error;
+use url::Url;
 
 /// Represents the core configuration required by the w3b2-connector library.
 /// This struct should be created by the user of the library and passed to the EventManager.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub struct ConnectorConfig {
@@ -13,10 +15,14 @@ pub struct ConnectorConfig {
     pub solana: Solana,
     #[cfg_attr(feature = "serde", serde(default))]
     pub synchronizer: Synchronizer,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retention: Retention,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filter: IngestionFilter,
 }
 
 /// Solana network connection settings.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub struct Solana {
@@ -24,23 +30,249 @@ pub struct Solana {
     pub ws_url: String,
     #[cfg_attr(feature = "serde", serde(with = "serde_commitment"))]
     pub commitment: CommitmentLevel,
+    /// How long a fetched blockhash is reused by `BlockhashCache` before refreshing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_blockhash_refresh_interval_ms")
+    )]
+    pub blockhash_refresh_interval_ms: u64,
+    /// Further endpoints besides `rpc_url`/`ws_url`. RPC calls are spread over all
+    /// of them (see `RpcPool`); WebSocket subscriptions use the first that connects.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra_endpoints: Vec<Endpoint>,
+    /// How often RPC endpoints are health-checked. A failed endpoint is also
+    /// skipped for this long before being tried again.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_health_check_interval_secs")
+    )]
+    pub health_check_interval_secs: u64,
+    /// A client-side request budget that `RpcPool` applies to each RPC endpoint
+    /// separately. Unlimited when unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rate_limit: Option<RateLimit>,
+    /// The delay before the first attempt to re-establish a dropped WebSocket
+    /// subscription. It doubles, with jitter, after every failed attempt.
+    #[cfg_attr(feature = "serde", serde(default = "default_reconnect_min_backoff_ms"))]
+    pub reconnect_min_backoff_ms: u64,
+    /// The longest delay between two WebSocket reconnection attempts.
+    #[cfg_attr(feature = "serde", serde(default = "default_reconnect_max_backoff_ms"))]
+    pub reconnect_max_backoff_ms: u64,
+}
+
+impl Solana {
+    /// Returns every RPC URL, `rpc_url` first.
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(&self.rpc_url)
+            .chain(self.extra_endpoints.iter().map(|e| &e.rpc_url))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns every WebSocket URL, `ws_url` first.
+    pub fn ws_urls(&self) -> Vec<String> {
+        std::iter::once(&self.ws_url)
+            .chain(self.extra_endpoints.iter().map(|e| &e.ws_url))
+            .cloned()
+            .collect()
+    }
+}
+
+/// An additional RPC node, reachable over HTTP and WebSocket.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct Endpoint {
+    pub rpc_url: String,
+    pub ws_url: String,
+}
+
+/// A token-bucket request budget for one RPC endpoint.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub struct RateLimit {
+    /// The sustained number of requests per second.
+    pub requests_per_second: u32,
+    /// How many requests may go out at once after an idle period.
+    /// Defaults to `requests_per_second`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub burst: Option<u32>,
+}
+
+fn default_blockhash_refresh_interval_ms() -> u64 {
+    2_000
+}
+
+fn default_health_check_interval_secs() -> u64 {
+    30
+}
+
+fn default_reconnect_min_backoff_ms() -> u64 {
+    500
+}
+
+fn default_reconnect_max_backoff_ms() -> u64 {
+    30_000
 }
 
 /// Settings for the event synchronizer.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub struct Synchronizer {
+    /// Where a node without any sync state starts indexing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start_from: StartPoint,
     pub max_catchup_depth: Option<u64>,
     pub poll_interval_secs: u64,
     pub max_signature_fetch: usize,
+    /// How many transactions the catch-up worker fetches in parallel. Events are
+    /// still published in history order.
+    #[cfg_attr(feature = "serde", serde(default = "default_fetch_concurrency"))]
+    pub fetch_concurrency: usize,
+    /// An RPC endpoint with deeper history (e.g. an archive node) used to backfill
+    /// gaps. Falls back to `solana.rpc_url` when unset.
+    pub backfill_rpc_url: Option<String>,
+    /// How often events published below `finalized` commitment are re-checked.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "default_finality_poll_interval_secs")
+    )]
+    pub finality_poll_interval_secs: u64,
+    /// How many slots the finalized slot may move past an event's transaction
+    /// before the transaction is considered dropped and its events revoked.
+    #[cfg_attr(feature = "serde", serde(default = "default_finality_timeout_slots"))]
+    pub finality_timeout_slots: u64,
+    /// How many recently published events are remembered, by signature and index,
+    /// to skip events seen by both the catch-up and live workers. Zero disables it.
+    #[cfg_attr(feature = "serde", serde(default = "default_dedup_window"))]
+    pub dedup_window: usize,
+    /// How many events are held back to broadcast them in slot order. Zero disables it.
+    #[cfg_attr(feature = "serde", serde(default = "default_reorder_window"))]
+    pub reorder_window: usize,
+    /// The longest an event is held back for reordering, in milliseconds.
+    #[cfg_attr(feature = "serde", serde(default = "default_reorder_delay_ms"))]
+    pub reorder_delay_ms: u64,
+}
+
+fn default_fetch_concurrency() -> usize {
+    8
+}
+
+fn default_finality_poll_interval_secs() -> u64 {
+    5
+}
+
+fn default_finality_timeout_slots() -> u64 {
+    150
+}
+
+fn default_dedup_window() -> usize {
+    10_000
 }
 
-impl Default for ConnectorConfig {
+fn default_reorder_window() -> usize {
+    256
+}
+
+fn default_reorder_delay_ms() -> u64 {
+    200
+}
+
+/// The point in the program's history a fresh node starts indexing from.
+///
+/// Only used while the storage holds no sync state; afterwards the synchronizer
+/// always resumes from the last synced signature.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum StartPoint {
+    /// Index the program's full history, bounded only by `max_catchup_depth`.
+    #[default]
+    Genesis,
+    /// Skip the history and index only transactions from now on.
+    Latest,
+    /// Index transactions from this slot onwards.
+    Slot(u64),
+    /// Index transactions after this signature.
+    Signature(String),
+}
+
+/// Retention policy for the stored event history.
+///
+/// Every limit is optional and they combine: an event is pruned as soon as it
+/// violates any of them. With no limit set, history is kept forever.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case", default))]
+pub struct Retention {
+    /// Drop events older than this many seconds, estimated from slots at 400 ms per slot.
+    pub max_age_secs: Option<u64>,
+    /// Drop events more than this many slots behind the last synchronized slot.
+    pub max_slots: Option<u64>,
+    /// Keep at most this many events, dropping the oldest first.
+    pub max_events: Option<usize>,
+    /// How often the pruning task runs.
+    pub prune_interval_secs: u64,
+}
+
+impl Retention {
+    /// Returns whether any limit is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_secs.is_some() || self.max_slots.is_some() || self.max_events.is_some()
+    }
+}
+
+/// Restricts the synchronizer to the transactions a deployment cares about.
+///
+/// Transactions touching none of `accounts` are neither decoded nor broadcast (their
+/// signatures still advance the sync state). Live log notifications carry no account
+/// list, so there a transaction passes if any of its events names a listed pubkey.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case", default))]
+pub struct IngestionFilter {
+    /// The authorities and PDAs of interest. Empty means everything is ingested.
+    #[cfg_attr(feature = "serde", serde(with = "serde_pubkeys"))]
+    pub accounts: Vec<Pubkey>,
+}
+
+impl IngestionFilter {
+    /// Returns whether any account is configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.accounts.is_empty()
+    }
+
+    /// Returns whether a transaction involving `keys` should be ingested.
+    pub fn matches(&self, keys: &[Pubkey]) -> bool {
+        !self.is_enabled() || keys.iter().any(|key| self.accounts.contains(key))
+    }
+}
+
+/// What the `Dispatcher` does with an event for a listener whose channel is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum BackpressurePolicy {
+    /// Wait for room in the channel. A slow listener delays routing for every other one.
+    #[default]
+    Block,
+    /// Discard the oldest buffered event to make room for the new one.
+    DropOldest,
+    /// Discard the new event.
+    DropNewest,
+    /// Unregister the listener and flag it as lagged, see `LagStatus`.
+    Disconnect,
+}
+
+impl Default for Retention {
     fn default() -> Self {
         Self {
-            solana: Solana::default(),
-            synchronizer: Synchronizer::default(),
+            max_age_secs: None,
+            max_slots: None,
+            max_events: None,
+            prune_interval_secs: 300,
         }
     }
 }
@@ -51,6 +283,12 @@ impl Default for Solana {
             rpc_url: "http://127.0.0.1:8899".to_string(),
             ws_url: "ws://127.0.0.1:8900".to_string(),
             commitment: CommitmentLevel::Confirmed,
+            blockhash_refresh_interval_ms: default_blockhash_refresh_interval_ms(),
+            extra_endpoints: Vec::new(),
+            health_check_interval_secs: default_health_check_interval_secs(),
+            rate_limit: None,
+            reconnect_min_backoff_ms: default_reconnect_min_backoff_ms(),
+            reconnect_max_backoff_ms: default_reconnect_max_backoff_ms(),
         }
     }
 }
@@ -58,10 +296,313 @@ impl Default for Solana {
 impl Default for Synchronizer {
     fn default() -> Self {
         Self {
+            start_from: StartPoint::Genesis,
             max_catchup_depth: None,
             poll_interval_secs: 3,
             max_signature_fetch: 1000,
+            fetch_concurrency: default_fetch_concurrency(),
+            backfill_rpc_url: None,
+            finality_poll_interval_secs: default_finality_poll_interval_secs(),
+            finality_timeout_slots: default_finality_timeout_slots(),
+            dedup_window: default_dedup_window(),
+            reorder_window: default_reorder_window(),
+            reorder_delay_ms: default_reorder_delay_ms(),
+        }
+    }
+}
+
+/// The most signatures a single `getSignaturesForAddress` request may return.
+const MAX_SIGNATURE_FETCH: usize = 1000;
+
+/// A setting the connector cannot run with, as reported by `ConnectorConfig::validate`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    #[error("{field}: '{url}' is not a valid {expected} URL")]
+    InvalidUrl {
+        field: String,
+        url: String,
+        expected: &'static str,
+    },
+    #[error("{field} must be greater than zero")]
+    Zero { field: &'static str },
+    #[error("{field} must be at most {max}, got {value}")]
+    TooLarge {
+        field: &'static str,
+        value: u64,
+        max: u64,
+    },
+    #[error("{field}: '{value}' is not a valid transaction signature")]
+    InvalidSignature { field: &'static str, value: String },
+}
+
+impl ConnectorConfig {
+    /// Returns a builder starting from the default configuration.
+    pub fn builder() -> ConnectorConfigBuilder {
+        ConnectorConfigBuilder::default()
+    }
+
+    /// Checks the URLs, intervals and capacities the workers rely on, returning
+    /// the first problem found.
+    ///
+    /// Configurations built with `builder()` are already validated; call this for
+    /// ones deserialized or assembled by hand.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let solana = &self.solana;
+        check_url("solana.rpc_url", &solana.rpc_url, HTTP)?;
+        check_url("solana.ws_url", &solana.ws_url, WS)?;
+        for (i, endpoint) in solana.extra_endpoints.iter().enumerate() {
+            let field = format!("solana.extra_endpoints[{}]", i);
+            check_url(&format!("{}.rpc_url", field), &endpoint.rpc_url, HTTP)?;
+            check_url(&format!("{}.ws_url", field), &endpoint.ws_url, WS)?;
+        }
+        check_positive(
+            "solana.blockhash_refresh_interval_ms",
+            solana.blockhash_refresh_interval_ms,
+        )?;
+        check_positive(
+            "solana.health_check_interval_secs",
+            solana.health_check_interval_secs,
+        )?;
+        if let Some(rate_limit) = &solana.rate_limit {
+            check_positive(
+                "solana.rate_limit.requests_per_second",
+                rate_limit.requests_per_second.into(),
+            )?;
+            if let Some(burst) = rate_limit.burst {
+                check_positive("solana.rate_limit.burst", burst.into())?;
+            }
+        }
+        check_positive(
+            "solana.reconnect_min_backoff_ms",
+            solana.reconnect_min_backoff_ms,
+        )?;
+        if solana.reconnect_min_backoff_ms > solana.reconnect_max_backoff_ms {
+            return Err(ConfigError::TooLarge {
+                field: "solana.reconnect_min_backoff_ms",
+                value: solana.reconnect_min_backoff_ms,
+                max: solana.reconnect_max_backoff_ms,
+            });
         }
+
+        let sync = &self.synchronizer;
+        if let StartPoint::Signature(signature) = &sync.start_from {
+            if signature.parse::<Signature>().is_err() {
+                return Err(ConfigError::InvalidSignature {
+                    field: "synchronizer.start_from",
+                    value: signature.clone(),
+                });
+            }
+        }
+        check_positive("synchronizer.poll_interval_secs", sync.poll_interval_secs)?;
+        check_positive(
+            "synchronizer.max_signature_fetch",
+            sync.max_signature_fetch as u64,
+        )?;
+        if sync.max_signature_fetch > MAX_SIGNATURE_FETCH {
+            return Err(ConfigError::TooLarge {
+                field: "synchronizer.max_signature_fetch",
+                value: sync.max_signature_fetch as u64,
+                max: MAX_SIGNATURE_FETCH as u64,
+            });
+        }
+        check_positive(
+            "synchronizer.fetch_concurrency",
+            sync.fetch_concurrency as u64,
+        )?;
+        if let Some(url) = &sync.backfill_rpc_url {
+            check_url("synchronizer.backfill_rpc_url", url, HTTP)?;
+        }
+        check_positive(
+            "synchronizer.finality_poll_interval_secs",
+            sync.finality_poll_interval_secs,
+        )?;
+        if sync.reorder_window > 0 {
+            check_positive("synchronizer.reorder_delay_ms", sync.reorder_delay_ms)?;
+        }
+
+        check_positive(
+            "retention.prune_interval_secs",
+            self.retention.prune_interval_secs,
+        )?;
+        Ok(())
+    }
+}
+
+const HTTP: (&str, &[&str]) = ("HTTP(S)", &["http", "https"]);
+const WS: (&str, &[&str]) = ("WebSocket", &["ws", "wss"]);
+
+/// Checks that `url` parses and uses one of the `expected` schemes.
+fn check_url(field: &str, url: &str, expected: (&'static str, &[&str])) -> Result<(), ConfigError> {
+    let (kind, schemes) = expected;
+    match Url::parse(url) {
+        Ok(parsed) if schemes.contains(&parsed.scheme()) && parsed.has_host() => Ok(()),
+        _ => Err(ConfigError::InvalidUrl {
+            field: field.to_string(),
+            url: url.to_string(),
+            expected: kind,
+        }),
+    }
+}
+
+fn check_positive(field: &'static str, value: u64) -> Result<(), ConfigError> {
+    if value == 0 {
+        return Err(ConfigError::Zero { field });
+    }
+    Ok(())
+}
+
+/// Builds a `ConnectorConfig` on top of the defaults, validating it in `build`.
+///
+/// ```
+/// # use w3b2_connector::config::ConnectorConfig;
+/// let config = ConnectorConfig::builder()
+///     .rpc_url("https://api.devnet.solana.com")
+///     .ws_url("wss://api.devnet.solana.com")
+///     .poll_interval_secs(5)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.synchronizer.poll_interval_secs, 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConnectorConfigBuilder {
+    config: ConnectorConfig,
+}
+
+impl ConnectorConfigBuilder {
+    /// Sets the primary RPC endpoint.
+    pub fn rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.config.solana.rpc_url = url.into();
+        self
+    }
+
+    /// Sets the primary WebSocket endpoint.
+    pub fn ws_url(mut self, url: impl Into<String>) -> Self {
+        self.config.solana.ws_url = url.into();
+        self
+    }
+
+    /// Sets the commitment level used for reads and subscriptions.
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.config.solana.commitment = commitment;
+        self
+    }
+
+    /// Adds an RPC node besides the primary one.
+    pub fn extra_endpoint(mut self, rpc_url: impl Into<String>, ws_url: impl Into<String>) -> Self {
+        self.config.solana.extra_endpoints.push(Endpoint {
+            rpc_url: rpc_url.into(),
+            ws_url: ws_url.into(),
+        });
+        self
+    }
+
+    /// Sets how long a fetched blockhash is reused.
+    pub fn blockhash_refresh_interval_ms(mut self, interval: u64) -> Self {
+        self.config.solana.blockhash_refresh_interval_ms = interval;
+        self
+    }
+
+    /// Sets how often RPC endpoints are health-checked.
+    pub fn health_check_interval_secs(mut self, interval: u64) -> Self {
+        self.config.solana.health_check_interval_secs = interval;
+        self
+    }
+
+    /// Limits the requests sent to each RPC endpoint.
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.config.solana.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Sets the shortest and longest delay between WebSocket reconnection attempts.
+    pub fn reconnect_backoff_ms(mut self, min: u64, max: u64) -> Self {
+        self.config.solana.reconnect_min_backoff_ms = min;
+        self.config.solana.reconnect_max_backoff_ms = max;
+        self
+    }
+
+    /// Sets where a node without sync state starts indexing.
+    pub fn start_from(mut self, start_from: StartPoint) -> Self {
+        self.config.synchronizer.start_from = start_from;
+        self
+    }
+
+    /// Bounds how many slots back the catch-up worker goes.
+    pub fn max_catchup_depth(mut self, depth: u64) -> Self {
+        self.config.synchronizer.max_catchup_depth = Some(depth);
+        self
+    }
+
+    /// Sets how often the catch-up worker polls for new signatures.
+    pub fn poll_interval_secs(mut self, interval: u64) -> Self {
+        self.config.synchronizer.poll_interval_secs = interval;
+        self
+    }
+
+    /// Sets how many signatures are requested per page, at most 1000.
+    pub fn max_signature_fetch(mut self, count: usize) -> Self {
+        self.config.synchronizer.max_signature_fetch = count;
+        self
+    }
+
+    /// Sets how many transactions the catch-up worker fetches in parallel.
+    pub fn fetch_concurrency(mut self, concurrency: usize) -> Self {
+        self.config.synchronizer.fetch_concurrency = concurrency;
+        self
+    }
+
+    /// Sets the endpoint used to backfill gaps in the history.
+    pub fn backfill_rpc_url(mut self, url: impl Into<String>) -> Self {
+        self.config.synchronizer.backfill_rpc_url = Some(url.into());
+        self
+    }
+
+    /// Sets how often unfinalized events are re-checked.
+    pub fn finality_poll_interval_secs(mut self, interval: u64) -> Self {
+        self.config.synchronizer.finality_poll_interval_secs = interval;
+        self
+    }
+
+    /// Sets after how many slots an unfinalized transaction is considered dropped.
+    pub fn finality_timeout_slots(mut self, slots: u64) -> Self {
+        self.config.synchronizer.finality_timeout_slots = slots;
+        self
+    }
+
+    /// Sets how many recently published events are remembered for deduplication.
+    pub fn dedup_window(mut self, capacity: usize) -> Self {
+        self.config.synchronizer.dedup_window = capacity;
+        self
+    }
+
+    /// Sets how many events are held back to broadcast them in slot order.
+    pub fn reorder_window(mut self, capacity: usize) -> Self {
+        self.config.synchronizer.reorder_window = capacity;
+        self
+    }
+
+    /// Sets the longest an event is held back for reordering.
+    pub fn reorder_delay_ms(mut self, delay: u64) -> Self {
+        self.config.synchronizer.reorder_delay_ms = delay;
+        self
+    }
+
+    /// Sets the retention policy for the stored history.
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.config.retention = retention;
+        self
+    }
+
+    /// Restricts ingestion to transactions touching the filter's accounts.
+    pub fn filter(mut self, filter: IngestionFilter) -> Self {
+        self.config.filter = filter;
+        self
+    }
+
+    /// Validates and returns the configuration.
+    pub fn build(self) -> Result<ConnectorConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
     }
 }
 
@@ -98,3 +639,28 @@ mod serde_commitment {
         Ok(level)
     }
 }
+
+/// (De)serializes pubkeys as base58 strings rather than byte arrays.
+#[cfg(feature = "serde")]
+mod serde_pubkeys {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    pub fn serialize<S>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(pubkeys.iter().map(Pubkey::to_string))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let strings: Vec<String> = Deserialize::deserialize(deserializer)?;
+        strings
+            .iter()
+            .map(|s| s.parse().map_err(D::Error::custom))
+            .collect()
+    }
+}
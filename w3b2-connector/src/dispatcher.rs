@@ -17,73 +17,324 @@
 /// Any other service (e.g. gRPC streaming, audit logging) can hook into the raw broadcast
 /// channel from the `Synchronizer`, bypassing the dispatcher entirely if unfiltered access
 /// is needed.
-use crate::events::BridgeEvent;
+use crate::{
+    config::BackpressurePolicy,
+    events::{BridgeEvent, EventEnvelope, EventKind},
+    prices::PriceCache,
+};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
 
 /// The Dispatcher is responsible for receiving all events from the Synchronizer
 /// and routing them to the appropriate listeners based on the public keys
 /// involved in the event.
 pub struct Dispatcher {
     // This receives all events from the Synchronizer's broadcast channel.
-    event_rx: broadcast::Receiver<BridgeEvent>,
-    // This stores the dedicated channels for listeners who have subscribed.
-    listeners: HashMap<Pubkey, mpsc::Sender<BridgeEvent>>,
+    event_rx: broadcast::Receiver<EventEnvelope>,
+    // This stores the dedicated channels for listeners who have subscribed. Several
+    // listeners may watch the same pubkey, each through its own channel.
+    listeners: HashMap<Pubkey, Vec<Listener>>,
     // This channel now receives commands, not just registrations.
     command_rx: mpsc::Receiver<DispatcherCommand>,
+    // Once cancelled, the dispatcher routes the events already received and stops.
+    shutdown: Option<CancellationToken>,
+    // Updated from every event before it is routed, if set.
+    price_cache: Option<Arc<PriceCache>>,
 }
 
+/// Identifies one listener among those registered for the same public key.
+pub type ListenerId = u64;
+
 /// Defines commands that can be sent to the Dispatcher task.
 #[derive(Debug)]
 pub enum DispatcherCommand {
-    /// Registers a new listener for a given public key.
-    Register(Pubkey, mpsc::Sender<BridgeEvent>),
-    /// Unregisters a listener for a given public key.
+    /// Registers a new listener for a given public key, alongside any existing ones.
+    Register {
+        pubkey: Pubkey,
+        id: ListenerId,
+        tx: mpsc::Sender<EventEnvelope>,
+        options: ListenerOptions,
+        /// Set if the listener is disconnected under `BackpressurePolicy::Disconnect`.
+        lag_status: LagStatus,
+    },
+    /// Unregisters a single listener, leaving the others for the same key in place.
+    UnregisterListener(Pubkey, ListenerId),
+    /// Unregisters every listener for a given public key.
     Unregister(Pubkey),
     /// Signals the dispatcher to shut down gracefully.
     Shutdown,
 }
 
+/// Per-listener settings, given when the listener registers.
+#[derive(Debug, Clone, Default)]
+pub struct ListenerOptions {
+    /// What to do when the listener's channel is full.
+    pub backpressure: BackpressurePolicy,
+    /// Which events to forward. Events that don't match never enter the channel.
+    pub filter: EventFilter,
+}
+
+/// A predicate on events, evaluated by the `Dispatcher` before forwarding.
+///
+/// Every condition is optional and they combine: an event is forwarded only if it
+/// passes all of them. The command conditions only constrain the events they apply
+/// to; other kinds of events pass them.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// The kinds of events to forward. Empty forwards every kind.
+    pub kinds: Vec<EventKind>,
+    /// The command ids to forward, for `UserCommandDispatched` and
+    /// `AdminCommandDispatched`.
+    pub command_ids: Option<RangeInclusive<u64>>,
+    /// The lowest `price_paid` to forward, for `UserCommandDispatched`.
+    pub min_price: Option<u64>,
+}
+
+impl EventFilter {
+    /// Returns whether `event` passes every condition of the filter.
+    pub fn matches(&self, event: &BridgeEvent) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&event.kind()) {
+            return false;
+        }
+        let (command_id, price) = match event {
+            BridgeEvent::UserCommandDispatched(e) => (e.command_id as u64, Some(e.price_paid)),
+            BridgeEvent::AdminCommandDispatched(e) => (e.command_id, None),
+            _ => return true,
+        };
+        let id_matches = self
+            .command_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&command_id));
+        let price_matches = match (self.min_price, price) {
+            (Some(min_price), Some(price)) => price >= min_price,
+            _ => true,
+        };
+        id_matches && price_matches
+    }
+}
+
+/// Tells whether the `Dispatcher` disconnected a listener for falling behind.
+///
+/// Only ever set under `BackpressurePolicy::Disconnect`. It lets a consumer whose
+/// stream ended tell a lag disconnect apart from a normal shutdown.
+#[derive(Debug, Clone, Default)]
+pub struct LagStatus(Arc<AtomicBool>);
+
+impl LagStatus {
+    /// Returns `true` once the listener has been disconnected because its channel was full.
+    pub fn is_lagged(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn mark_lagged(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The dispatcher's side of a registered listener.
+struct Listener {
+    id: ListenerId,
+    tx: mpsc::Sender<EventEnvelope>,
+    backpressure: BackpressurePolicy,
+    filter: EventFilter,
+    /// Under `BackpressurePolicy::DropOldest`, events reach `tx` through a relay task
+    /// reading from this ring buffer, which overwrites its oldest entry when full.
+    relay_tx: Option<broadcast::Sender<EventEnvelope>>,
+    lag_status: LagStatus,
+}
+
+/// The outcome of handing an event to a `Listener`.
+enum Delivery {
+    Delivered,
+    /// The channel was full and the event was discarded.
+    Dropped,
+    /// The channel was full and the listener must be disconnected.
+    Lagged,
+    /// The receiver is gone.
+    Disconnected,
+}
+
+impl Listener {
+    fn new(
+        id: ListenerId,
+        tx: mpsc::Sender<EventEnvelope>,
+        options: ListenerOptions,
+        lag_status: LagStatus,
+    ) -> Self {
+        let relay_tx = (options.backpressure == BackpressurePolicy::DropOldest)
+            .then(|| spawn_relay(id, tx.clone()));
+        Self {
+            id,
+            tx,
+            backpressure: options.backpressure,
+            filter: options.filter,
+            relay_tx,
+            lag_status,
+        }
+    }
+
+    async fn deliver(&self, event: EventEnvelope) -> Delivery {
+        use mpsc::error::TrySendError;
+
+        match self.backpressure {
+            BackpressurePolicy::Block => match self.tx.send(event).await {
+                Ok(()) => Delivery::Delivered,
+                Err(_) => Delivery::Disconnected,
+            },
+            BackpressurePolicy::DropOldest => match &self.relay_tx {
+                Some(relay_tx) if relay_tx.send(event).is_ok() => Delivery::Delivered,
+                _ => Delivery::Disconnected,
+            },
+            BackpressurePolicy::DropNewest => match self.tx.try_send(event) {
+                Ok(()) => Delivery::Delivered,
+                Err(TrySendError::Full(_)) => Delivery::Dropped,
+                Err(TrySendError::Closed(_)) => Delivery::Disconnected,
+            },
+            BackpressurePolicy::Disconnect => match self.tx.try_send(event) {
+                Ok(()) => Delivery::Delivered,
+                Err(TrySendError::Full(_)) => {
+                    self.lag_status.mark_lagged();
+                    Delivery::Lagged
+                }
+                Err(TrySendError::Closed(_)) => Delivery::Disconnected,
+            },
+        }
+    }
+}
+
+/// Spawns the task that forwards a `DropOldest` listener's ring buffer into `tx`.
+fn spawn_relay(
+    id: ListenerId,
+    tx: mpsc::Sender<EventEnvelope>,
+) -> broadcast::Sender<EventEnvelope> {
+    let (relay_tx, mut relay_rx) = broadcast::channel(tx.max_capacity());
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                res = relay_rx.recv() => match res {
+                    Ok(event) => {
+                        if tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::debug!("Dispatcher: Listener {} dropped its {} oldest events.", id, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = tx.closed() => break,
+            }
+        }
+    });
+    relay_tx
+}
+
+/// Keeps a listener registered with the `Dispatcher` for as long as it is alive.
+///
+/// Dropping it sends `DispatcherCommand::UnregisterListener`, so the dispatcher
+/// forgets the listener right away rather than on its next failed delivery.
+#[derive(Debug)]
+pub(crate) struct ListenerRegistration {
+    pubkey: Pubkey,
+    id: ListenerId,
+    lag_status: LagStatus,
+    command_tx: mpsc::Sender<DispatcherCommand>,
+}
+
+impl ListenerRegistration {
+    pub(crate) fn new(
+        pubkey: Pubkey,
+        id: ListenerId,
+        lag_status: LagStatus,
+        command_tx: mpsc::Sender<DispatcherCommand>,
+    ) -> Self {
+        Self {
+            pubkey,
+            id,
+            lag_status,
+            command_tx,
+        }
+    }
+
+    pub(crate) fn lag_status(&self) -> LagStatus {
+        self.lag_status.clone()
+    }
+}
+
+impl Drop for ListenerRegistration {
+    fn drop(&mut self) {
+        // If the command channel is full or closed, the dispatcher still drops the
+        // listener once it finds its channel closed.
+        let _ = self
+            .command_tx
+            .try_send(DispatcherCommand::UnregisterListener(self.pubkey, self.id));
+    }
+}
+
 impl Dispatcher {
     pub fn new(
-        event_rx: broadcast::Receiver<BridgeEvent>,
+        event_rx: broadcast::Receiver<EventEnvelope>,
         command_rx: mpsc::Receiver<DispatcherCommand>,
     ) -> Self {
         Self {
             event_rx,
             listeners: HashMap::new(),
             command_rx,
+            shutdown: None,
+            price_cache: None,
         }
     }
 
+    /// Makes the dispatcher stop once `shutdown` is cancelled, after routing the
+    /// events it has already received. Unlike `DispatcherCommand::Shutdown`, this
+    /// loses nothing that was published before the cancellation.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Keeps `cache` up to date from the events flowing through the dispatcher.
+    ///
+    /// The cache is updated before an event is routed, so a listener woken by an
+    /// `AdminPricesUpdated` already reads the new prices from it.
+    pub fn with_price_cache(mut self, cache: Arc<PriceCache>) -> Self {
+        self.price_cache = Some(cache);
+        self
+    }
+
     /// Starts the main event-loop for the dispatcher.
     pub async fn run(&mut self) {
         tracing::info!("Dispatcher started. Waiting for events and commands...");
+        let shutdown = self.shutdown.clone().unwrap_or_default();
         loop {
             tokio::select! {
                 // An event arrived from the blockchain.
                 Ok(event) = self.event_rx.recv() => {
-                    let relevant_pubkeys = extract_pubkeys_from_event(&event);
-                    for pubkey in relevant_pubkeys {
-                        if let Some(listener_tx) = self.listeners.get(&pubkey) {
-                            if listener_tx.send(event.clone()).await.is_err() {
-                                // The receiver was dropped. The active `unsubscribe` call will clean this up,
-                                // but logging it is still useful.
-                                tracing::warn!("Attempted to send to a disconnected listener for pubkey {}.", pubkey);
-                            }
-                        }
-                    }
+                    self.dispatch(event).await;
                 },
                 // A command to register or unregister a listener arrived.
                 Some(command) = self.command_rx.recv() => {
                     match command {
-                        DispatcherCommand::Register(pubkey, tx) => {
-                            tracing::info!("Dispatcher: Registering new listener for {}", pubkey);
-                            self.listeners.insert(pubkey, tx);
+                        DispatcherCommand::Register { pubkey, id, tx, options, lag_status } => {
+                            tracing::info!("Dispatcher: Registering new listener {} for {}", id, pubkey);
+                            // Listeners of quiet pubkeys are never found closed by `dispatch`.
+                            self.remove_closed_listeners();
+                            self.listeners
+                                .entry(pubkey)
+                                .or_default()
+                                .push(Listener::new(id, tx, options, lag_status));
+                        },
+                        DispatcherCommand::UnregisterListener(pubkey, id) => {
+                            tracing::info!("Dispatcher: Unregistering listener {} for {}", id, pubkey);
+                            self.remove_listeners(&pubkey, |listener_id| listener_id == id);
                         },
                         DispatcherCommand::Unregister(pubkey) => {
-                            tracing::info!("Dispatcher: Unregistering listener for {}", pubkey);
+                            tracing::info!("Dispatcher: Unregistering listeners for {}", pubkey);
                             self.listeners.remove(&pubkey);
                         },
                         DispatcherCommand::Shutdown => {
@@ -92,6 +343,11 @@ impl Dispatcher {
                         }
                     }
                 },
+                _ = shutdown.cancelled(), if self.shutdown.is_some() => {
+                    tracing::info!("Dispatcher: Shutdown requested, routing the remaining events.");
+                    self.drain().await;
+                    break;
+                },
                 else => {
                     tracing::error!("All channels closed. Dispatcher shutting down.");
                     break;
@@ -99,10 +355,95 @@ impl Dispatcher {
             }
         }
     }
+
+    /// Routes every event still buffered in the event channel.
+    async fn drain(&mut self) {
+        loop {
+            match self.event_rx.try_recv() {
+                Ok(event) => self.dispatch(event).await,
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                    tracing::warn!("Dispatcher lagged by {} events while draining.", skipped);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Forwards `event` to the listeners of every pubkey it involves, applying each
+    /// listener's backpressure policy and dropping the listeners whose receivers are gone.
+    #[tracing::instrument(
+        skip_all,
+        fields(slot = event.slot, signature = %event.signature, index = event.index, kind = ?event.event.kind())
+    )]
+    async fn dispatch(&mut self, event: EventEnvelope) {
+        if let Some(cache) = &self.price_cache {
+            cache.observe(&event.event);
+        }
+        for pubkey in event.pubkeys() {
+            let Some(listeners) = self.listeners.get(&pubkey) else {
+                continue;
+            };
+            tracing::debug!(%pubkey, listeners = listeners.len(), "Routing event");
+            let mut disconnected = Vec::new();
+            for listener in listeners {
+                if !listener.filter.matches(&event.event) {
+                    continue;
+                }
+                match listener.deliver(event.clone()).await {
+                    Delivery::Delivered => {
+                        tracing::trace!(%pubkey, listener = listener.id, "Delivered event");
+                    }
+                    Delivery::Dropped => {
+                        tracing::debug!(
+                            "Dispatcher: Listener {} for {} is full, dropping an event.",
+                            listener.id,
+                            pubkey
+                        );
+                    }
+                    Delivery::Lagged => {
+                        tracing::warn!(
+                            "Dispatcher: Disconnecting listener {} for {}: it fell behind.",
+                            listener.id,
+                            pubkey
+                        );
+                        disconnected.push(listener.id);
+                    }
+                    Delivery::Disconnected => disconnected.push(listener.id),
+                }
+            }
+            if !disconnected.is_empty() {
+                tracing::debug!(
+                    "Dispatcher: Dropping {} disconnected listener(s) for {}",
+                    disconnected.len(),
+                    pubkey
+                );
+                self.remove_listeners(&pubkey, |id| disconnected.contains(&id));
+            }
+        }
+    }
+
+    /// Removes the listeners of `pubkey` matching `predicate`, and the map entry once
+    /// none are left.
+    fn remove_listeners(&mut self, pubkey: &Pubkey, predicate: impl Fn(ListenerId) -> bool) {
+        if let Some(listeners) = self.listeners.get_mut(pubkey) {
+            listeners.retain(|listener| !predicate(listener.id));
+            if listeners.is_empty() {
+                self.listeners.remove(pubkey);
+            }
+        }
+    }
+
+    /// Removes every listener whose receiver has been dropped.
+    fn remove_closed_listeners(&mut self) {
+        self.listeners.retain(|_, listeners| {
+            listeners.retain(|listener| !listener.tx.is_closed());
+            !listeners.is_empty()
+        });
+    }
 }
 
 /// Helper function to extract all relevant public keys from an event.
-fn extract_pubkeys_from_event(event: &BridgeEvent) -> Vec<Pubkey> {
+pub(crate) fn extract_pubkeys_from_event(event: &BridgeEvent) -> Vec<Pubkey> {
     use w3b2_bridge_program::events as OnChainEvent;
     match event {
         BridgeEvent::AdminProfileRegistered(OnChainEvent::AdminProfileRegistered {
@@ -115,12 +456,21 @@ fn extract_pubkeys_from_event(event: &BridgeEvent) -> Vec<Pubkey> {
         BridgeEvent::AdminPricesUpdated(OnChainEvent::AdminPricesUpdated { authority, .. }) => {
             vec![*authority]
         }
+        BridgeEvent::AdminGcPolicyUpdated(OnChainEvent::AdminGcPolicyUpdated {
+            authority, ..
+        }) => vec![*authority],
+        BridgeEvent::AdminPrioritySurchargeUpdated(
+            OnChainEvent::AdminPrioritySurchargeUpdated { authority, .. },
+        ) => vec![*authority],
         BridgeEvent::AdminFundsWithdrawn(OnChainEvent::AdminFundsWithdrawn {
             authority, ..
         }) => vec![*authority],
         BridgeEvent::AdminProfileClosed(OnChainEvent::AdminProfileClosed { authority, .. }) => {
             vec![*authority]
         }
+        BridgeEvent::AdminProfileMigrated(OnChainEvent::AdminProfileMigrated {
+            authority, ..
+        }) => vec![*authority],
         BridgeEvent::UserProfileCreated(OnChainEvent::UserProfileCreated {
             authority,
             target_admin,
@@ -151,6 +501,6 @@ fn extract_pubkeys_from_event(event: &BridgeEvent) -> Vec<Pubkey> {
         BridgeEvent::OffChainActionLogged(OnChainEvent::OffChainActionLogged { actor, .. }) => {
             vec![*actor]
         }
-        BridgeEvent::Unknown => vec![],
+        BridgeEvent::Unknown { .. } => vec![],
     }
 }
@@ -1,36 +1,414 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
 use anyhow::Result;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use borsh::BorshDeserialize;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use solana_sdk::{bs58, pubkey::Pubkey};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    UiInnerInstructions, UiInstruction,
+};
+
+use crate::dispatcher::extract_pubkeys_from_event;
+use crate::schema::{self, EVENT_SCHEMA_VERSION};
 
 // Import all the on-chain event structs and give them a clear alias.
 use w3b2_bridge_program::events as OnChainEvent;
 
 /// A connector-side enum that wraps all possible on-chain events.
 /// This provides a single, unified type for the dispatcher to work with.
+///
+/// With the `serde` feature, events serialize as an object tagged with their
+/// `kind`, e.g. `{"kind": "UserFundsDeposited", "authority": "…", …}`. Pubkeys are
+/// base58 strings and byte payloads base64 strings.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind"))]
 pub enum BridgeEvent {
-    AdminProfileRegistered(OnChainEvent::AdminProfileRegistered),
-    AdminCommKeyUpdated(OnChainEvent::AdminCommKeyUpdated),
-    AdminPricesUpdated(OnChainEvent::AdminPricesUpdated),
-    AdminFundsWithdrawn(OnChainEvent::AdminFundsWithdrawn),
-    AdminProfileClosed(OnChainEvent::AdminProfileClosed),
-    AdminCommandDispatched(OnChainEvent::AdminCommandDispatched),
-    UserProfileCreated(OnChainEvent::UserProfileCreated),
-    UserCommKeyUpdated(OnChainEvent::UserCommKeyUpdated),
-    UserFundsDeposited(OnChainEvent::UserFundsDeposited),
-    UserFundsWithdrawn(OnChainEvent::UserFundsWithdrawn),
-    UserProfileClosed(OnChainEvent::UserProfileClosed),
-    UserCommandDispatched(OnChainEvent::UserCommandDispatched),
-    OffChainActionLogged(OnChainEvent::OffChainActionLogged),
+    AdminProfileRegistered(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminProfileRegisteredDef")
+        )]
+        OnChainEvent::AdminProfileRegistered,
+    ),
+    AdminCommKeyUpdated(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminCommKeyUpdatedDef")
+        )]
+        OnChainEvent::AdminCommKeyUpdated,
+    ),
+    AdminPricesUpdated(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::AdminPricesUpdatedDef"))]
+        OnChainEvent::AdminPricesUpdated,
+    ),
+    AdminGcPolicyUpdated(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminGcPolicyUpdatedDef")
+        )]
+        OnChainEvent::AdminGcPolicyUpdated,
+    ),
+    AdminPrioritySurchargeUpdated(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminPrioritySurchargeUpdatedDef")
+        )]
+        OnChainEvent::AdminPrioritySurchargeUpdated,
+    ),
+    AdminFundsWithdrawn(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminFundsWithdrawnDef")
+        )]
+        OnChainEvent::AdminFundsWithdrawn,
+    ),
+    AdminProfileClosed(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::AdminProfileClosedDef"))]
+        OnChainEvent::AdminProfileClosed,
+    ),
+    AdminProfileMigrated(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminProfileMigratedDef")
+        )]
+        OnChainEvent::AdminProfileMigrated,
+    ),
+    AdminCommandDispatched(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::AdminCommandDispatchedDef")
+        )]
+        OnChainEvent::AdminCommandDispatched,
+    ),
+    UserProfileCreated(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::UserProfileCreatedDef"))]
+        OnChainEvent::UserProfileCreated,
+    ),
+    UserCommKeyUpdated(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::UserCommKeyUpdatedDef"))]
+        OnChainEvent::UserCommKeyUpdated,
+    ),
+    UserFundsDeposited(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::UserFundsDepositedDef"))]
+        OnChainEvent::UserFundsDeposited,
+    ),
+    UserFundsWithdrawn(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::UserFundsWithdrawnDef"))]
+        OnChainEvent::UserFundsWithdrawn,
+    ),
+    UserProfileClosed(
+        #[cfg_attr(feature = "serde", serde(with = "serde_events::UserProfileClosedDef"))]
+        OnChainEvent::UserProfileClosed,
+    ),
+    UserCommandDispatched(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::UserCommandDispatchedDef")
+        )]
+        OnChainEvent::UserCommandDispatched,
+    ),
+    OffChainActionLogged(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "serde_events::OffChainActionLoggedDef")
+        )]
+        OnChainEvent::OffChainActionLogged,
+    ),
+    /// An event this version of the connector cannot decode, e.g. one added in a
+    /// newer version of the program. It is kept as logged, so it can be persisted
+    /// and decoded after an upgrade.
+    Unknown { raw: RawEvent },
+}
+
+/// The undecoded data of an event, exactly as the program logged it in a
+/// `Program data: ` line, together with where it was found.
+///
+/// Only `data` is persisted; events restored from storage get their signature back
+/// from the surrounding `EventEnvelope`, but not their logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawEvent {
+    /// The event's discriminator followed by its Borsh-serialized body.
+    #[cfg_attr(feature = "serde", serde(with = "serde_events::base64_bytes"))]
+    pub data: Vec<u8>,
+    /// The signature of the transaction that emitted the event, if known.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub signature: Option<String>,
+    /// The log messages the event was decoded from: the whole transaction's when
+    /// found by the synchronizer, or the single line given to `try_parse_log`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub logs: Vec<String>,
+}
+
+impl RawEvent {
+    /// Returns the 8-byte discriminator identifying the event type, if the data
+    /// is long enough to carry one.
+    pub fn discriminator(&self) -> Option<[u8; 8]> {
+        self.data.get(..8)?.try_into().ok()
+    }
+}
+
+impl BridgeEvent {
+    /// Records the transaction an `Unknown` event was found in; known events are
+    /// returned unchanged.
+    pub(crate) fn with_source(mut self, signature: Option<&str>, logs: &[String]) -> Self {
+        if let BridgeEvent::Unknown { raw } = &mut self {
+            raw.signature = signature.map(str::to_string);
+            raw.logs = logs.to_vec();
+        }
+        self
+    }
+
+    /// Serializes the event back into its on-chain form (discriminator + Borsh body),
+    /// the inverse of `parse_event_data`. `Unknown` serializes to its raw data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        use anchor_lang::Event;
+        match self {
+            BridgeEvent::AdminProfileRegistered(e) => e.data(),
+            BridgeEvent::AdminCommKeyUpdated(e) => e.data(),
+            BridgeEvent::AdminPricesUpdated(e) => e.data(),
+            BridgeEvent::AdminGcPolicyUpdated(e) => e.data(),
+            BridgeEvent::AdminPrioritySurchargeUpdated(e) => e.data(),
+            BridgeEvent::AdminFundsWithdrawn(e) => e.data(),
+            BridgeEvent::AdminProfileClosed(e) => e.data(),
+            BridgeEvent::AdminProfileMigrated(e) => e.data(),
+            BridgeEvent::AdminCommandDispatched(e) => e.data(),
+            BridgeEvent::UserProfileCreated(e) => e.data(),
+            BridgeEvent::UserCommKeyUpdated(e) => e.data(),
+            BridgeEvent::UserFundsDeposited(e) => e.data(),
+            BridgeEvent::UserFundsWithdrawn(e) => e.data(),
+            BridgeEvent::UserProfileClosed(e) => e.data(),
+            BridgeEvent::UserCommandDispatched(e) => e.data(),
+            BridgeEvent::OffChainActionLogged(e) => e.data(),
+            BridgeEvent::Unknown { raw } => raw.data.clone(),
+        }
+    }
+
+    /// Returns which kind of event this is, without its data.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            BridgeEvent::AdminProfileRegistered(_) => EventKind::AdminProfileRegistered,
+            BridgeEvent::AdminCommKeyUpdated(_) => EventKind::AdminCommKeyUpdated,
+            BridgeEvent::AdminPricesUpdated(_) => EventKind::AdminPricesUpdated,
+            BridgeEvent::AdminGcPolicyUpdated(_) => EventKind::AdminGcPolicyUpdated,
+            BridgeEvent::AdminPrioritySurchargeUpdated(_) => {
+                EventKind::AdminPrioritySurchargeUpdated
+            }
+            BridgeEvent::AdminFundsWithdrawn(_) => EventKind::AdminFundsWithdrawn,
+            BridgeEvent::AdminProfileClosed(_) => EventKind::AdminProfileClosed,
+            BridgeEvent::AdminProfileMigrated(_) => EventKind::AdminProfileMigrated,
+            BridgeEvent::AdminCommandDispatched(_) => EventKind::AdminCommandDispatched,
+            BridgeEvent::UserProfileCreated(_) => EventKind::UserProfileCreated,
+            BridgeEvent::UserCommKeyUpdated(_) => EventKind::UserCommKeyUpdated,
+            BridgeEvent::UserFundsDeposited(_) => EventKind::UserFundsDeposited,
+            BridgeEvent::UserFundsWithdrawn(_) => EventKind::UserFundsWithdrawn,
+            BridgeEvent::UserProfileClosed(_) => EventKind::UserProfileClosed,
+            BridgeEvent::UserCommandDispatched(_) => EventKind::UserCommandDispatched,
+            BridgeEvent::OffChainActionLogged(_) => EventKind::OffChainActionLogged,
+            BridgeEvent::Unknown { .. } => EventKind::Unknown,
+        }
+    }
+}
+
+/// The kind of a `BridgeEvent`, one per variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EventKind {
+    AdminProfileRegistered,
+    AdminCommKeyUpdated,
+    AdminPricesUpdated,
+    AdminGcPolicyUpdated,
+    AdminPrioritySurchargeUpdated,
+    AdminFundsWithdrawn,
+    AdminProfileClosed,
+    AdminProfileMigrated,
+    AdminCommandDispatched,
+    UserProfileCreated,
+    UserCommKeyUpdated,
+    UserFundsDeposited,
+    UserFundsWithdrawn,
+    UserProfileClosed,
+    UserCommandDispatched,
+    OffChainActionLogged,
     Unknown,
 }
 
+/// A decoded event together with its position on-chain.
+///
+/// `(slot, signature, index)` uniquely identifies an event and orders events
+/// chronologically, which is what storage backends key their history on. The
+/// signature also links the event to its transaction in a block explorer.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventEnvelope {
+    /// The slot of the transaction that emitted the event.
+    pub slot: u64,
+    /// The signature of the transaction that emitted the event.
+    pub signature: String,
+    /// The position of the event among the bridge events of its transaction.
+    pub index: u32,
+    /// The position of the transaction within its block, if known.
+    ///
+    /// Only the catch-up worker fills it in, from the block's signature list; log
+    /// notifications don't say where in the block a transaction landed.
+    pub tx_index: Option<u32>,
+    /// The estimated production time of the slot, as a Unix timestamp, if known.
+    ///
+    /// Events seen by the live worker carry no block time, since log
+    /// notifications don't include one.
+    pub block_time: Option<i64>,
+    pub event: BridgeEvent,
+}
+
+/// Why the synchronizer could not process a stretch of history in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapReason {
+    /// The RPC node no longer serves signatures back to the last synced one.
+    HistoryTruncated,
+    /// Transactions were older than `max_catchup_depth` and skipped on purpose.
+    CatchupDepthExceeded,
+}
+
+/// A notification that events between two points in history may be missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncGap {
+    pub reason: GapReason,
+    /// The first slot of the gap.
+    pub from_slot: u64,
+    /// The last slot of the gap.
+    pub to_slot: u64,
+    /// The last signature processed before the gap, if known.
+    pub after_signature: Option<String>,
+    /// The first signature processed after the gap, if known.
+    pub before_signature: Option<String>,
+    /// How many transactions a targeted backfill recovered from within the gap.
+    pub backfilled: usize,
+}
+
+/// A notice that a published event belongs to a transaction that never finalized,
+/// i.e. its fork was dropped. The event has also been removed from the history.
+#[derive(Debug, Clone)]
+pub struct Revocation {
+    pub envelope: EventEnvelope,
+    /// The finalized slot at which the transaction was given up on.
+    pub finalized_slot: u64,
+}
+
+/// The position of an event in the history, as stored by durable subscribers.
+///
+/// Cursors order the same way storage backends key their history: by slot, then
+/// signature, then index.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventCursor {
+    pub slot: u64,
+    pub signature: String,
+    pub index: u32,
+}
+
+impl EventCursor {
+    /// Serializes the cursor for storage.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        borsh::to_vec(&(self.slot, &self.signature, self.index))
+            .expect("serializing into a Vec cannot fail")
+    }
+
+    /// Restores a cursor serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (slot, signature, index) = <(u64, String, u32)>::try_from_slice(bytes)?;
+        Ok(Self {
+            slot,
+            signature,
+            index,
+        })
+    }
+}
+
+impl EventEnvelope {
+    /// Returns the position of this event in the history.
+    pub fn cursor(&self) -> EventCursor {
+        EventCursor {
+            slot: self.slot,
+            signature: self.signature.clone(),
+            index: self.index,
+        }
+    }
+
+    /// Returns the public keys the event involves, as used for routing to listeners.
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        extract_pubkeys_from_event(&self.event)
+    }
+
+    /// Serializes the envelope for storage.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        borsh::to_vec(&(
+            self.slot,
+            &self.signature,
+            self.index,
+            self.event.to_bytes(),
+            self.block_time,
+            self.tx_index,
+        ))
+        .expect("serializing into a Vec cannot fail")
+    }
+
+    /// Restores an envelope serialized with `to_bytes`.
+    ///
+    /// Envelopes stored before the block time or the transaction index were
+    /// recorded are restored without them.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (slot, signature, index, data, block_time, tx_index) =
+            <(u64, String, u32, Vec<u8>, Option<i64>, Option<u32>)>::try_from_slice(bytes)
+                .or_else(|_| {
+                    <(u64, String, u32, Vec<u8>, Option<i64>)>::try_from_slice(bytes).map(
+                        |(slot, signature, index, data, block_time)| {
+                            (slot, signature, index, data, block_time, None)
+                        },
+                    )
+                })
+                .or_else(|_| {
+                    <(u64, String, u32, Vec<u8>)>::try_from_slice(bytes).map(
+                        |(slot, signature, index, data)| (slot, signature, index, data, None, None),
+                    )
+                })?;
+        let event = parse_event_data(&data)?.with_source(Some(&signature), &[]);
+        Ok(Self {
+            slot,
+            signature,
+            index,
+            tx_index,
+            block_time,
+            event,
+        })
+    }
+}
+
 /// Parses the raw event data from a log message.
 /// It identifies the event type by its 8-byte discriminator and deserializes
-/// the rest of the data into the corresponding struct.
+/// the rest of the data into the corresponding struct. Data with an unknown
+/// discriminator is returned as `BridgeEvent::Unknown`.
+///
+/// Events emitted in an older layout are upgraded to the current one, see `schema`.
 pub fn parse_event_data(data: &[u8]) -> Result<BridgeEvent> {
+    Ok(parse_event_data_with_version(data)?.0)
+}
+
+/// Like `parse_event_data`, but also returns the schema version the event was
+/// emitted in. Unknown events report the current version.
+pub fn parse_event_data_with_version(data: &[u8]) -> Result<(BridgeEvent, u32)> {
+    let unknown = || {
+        let event = BridgeEvent::Unknown {
+            raw: RawEvent {
+                data: data.to_vec(),
+                ..Default::default()
+            },
+        };
+        (event, EVENT_SCHEMA_VERSION)
+    };
     if data.len() < 8 {
-        return Ok(BridgeEvent::Unknown);
+        return Ok(unknown());
     }
 
     let discriminator = &data[0..8];
@@ -45,63 +423,477 @@ pub fn parse_event_data(data: &[u8]) -> Result<BridgeEvent> {
         };
     }
 
+    // Decodes the current layout of an event, falling back to its older layouts.
+    macro_rules! decode {
+        ($name:ident) => {
+            match OnChainEvent::$name::try_from_slice(event_data) {
+                Ok(event) => Ok((BridgeEvent::$name(event), EVENT_SCHEMA_VERSION)),
+                Err(e) => {
+                    schema::decode_legacy(stringify!($name), event_data).ok_or_else(|| e.into())
+                }
+            }
+        };
+    }
+
     // Compare the discriminator from the log with the known discriminators.
     if discriminator == get_disc!("AdminProfileRegistered").as_slice() {
-        let event = OnChainEvent::AdminProfileRegistered::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminProfileRegistered(event))
+        decode!(AdminProfileRegistered)
     } else if discriminator == get_disc!("AdminCommKeyUpdated").as_slice() {
-        let event = OnChainEvent::AdminCommKeyUpdated::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminCommKeyUpdated(event))
+        decode!(AdminCommKeyUpdated)
     } else if discriminator == get_disc!("AdminPricesUpdated").as_slice() {
-        let event = OnChainEvent::AdminPricesUpdated::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminPricesUpdated(event))
+        decode!(AdminPricesUpdated)
+    } else if discriminator == get_disc!("AdminGcPolicyUpdated").as_slice() {
+        decode!(AdminGcPolicyUpdated)
+    } else if discriminator == get_disc!("AdminPrioritySurchargeUpdated").as_slice() {
+        decode!(AdminPrioritySurchargeUpdated)
     } else if discriminator == get_disc!("AdminFundsWithdrawn").as_slice() {
-        let event = OnChainEvent::AdminFundsWithdrawn::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminFundsWithdrawn(event))
+        decode!(AdminFundsWithdrawn)
     } else if discriminator == get_disc!("AdminProfileClosed").as_slice() {
-        let event = OnChainEvent::AdminProfileClosed::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminProfileClosed(event))
+        decode!(AdminProfileClosed)
+    } else if discriminator == get_disc!("AdminProfileMigrated").as_slice() {
+        decode!(AdminProfileMigrated)
     } else if discriminator == get_disc!("AdminCommandDispatched").as_slice() {
-        let event = OnChainEvent::AdminCommandDispatched::try_from_slice(event_data)?;
-        Ok(BridgeEvent::AdminCommandDispatched(event))
+        decode!(AdminCommandDispatched)
     } else if discriminator == get_disc!("UserProfileCreated").as_slice() {
-        let event = OnChainEvent::UserProfileCreated::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserProfileCreated(event))
+        decode!(UserProfileCreated)
     } else if discriminator == get_disc!("UserCommKeyUpdated").as_slice() {
-        let event = OnChainEvent::UserCommKeyUpdated::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserCommKeyUpdated(event))
+        decode!(UserCommKeyUpdated)
     } else if discriminator == get_disc!("UserFundsDeposited").as_slice() {
-        let event = OnChainEvent::UserFundsDeposited::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserFundsDeposited(event))
+        decode!(UserFundsDeposited)
     } else if discriminator == get_disc!("UserFundsWithdrawn").as_slice() {
-        let event = OnChainEvent::UserFundsWithdrawn::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserFundsWithdrawn(event))
+        decode!(UserFundsWithdrawn)
     } else if discriminator == get_disc!("UserProfileClosed").as_slice() {
-        let event = OnChainEvent::UserProfileClosed::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserProfileClosed(event))
+        decode!(UserProfileClosed)
     } else if discriminator == get_disc!("UserCommandDispatched").as_slice() {
-        let event = OnChainEvent::UserCommandDispatched::try_from_slice(event_data)?;
-        Ok(BridgeEvent::UserCommandDispatched(event))
+        decode!(UserCommandDispatched)
     } else if discriminator == get_disc!("OffChainActionLogged").as_slice() {
-        let event = OnChainEvent::OffChainActionLogged::try_from_slice(event_data)?;
-        Ok(BridgeEvent::OffChainActionLogged(event))
+        decode!(OffChainActionLogged)
     } else {
-        Ok(BridgeEvent::Unknown)
+        Ok(unknown())
+    }
+}
+
+/// Decodes every event in a transaction's logs, in log order.
+///
+/// Events the connector doesn't know are returned as `BridgeEvent::Unknown`,
+/// unless the logs show they were emitted by another program invoked in the same
+/// transaction. Also returns how many `Program data: ` payloads failed to decode,
+/// which `try_parse_log` silently treats as unknown.
+pub(crate) fn parse_logs(logs: &[String]) -> (Vec<BridgeEvent>, usize) {
+    let bridge_program = w3b2_bridge_program::ID.to_string();
+    // The programs currently executing, innermost last.
+    let mut invoked: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    let mut failures = 0;
+    for log in logs {
+        let Some(data_str) = log.strip_prefix("Program data: ") else {
+            track_invocation(&mut invoked, log);
+            continue;
+        };
+        let parsed = BASE64
+            .decode(data_str.trim())
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| parse_event_data(&bytes));
+        match parsed {
+            Ok(BridgeEvent::Unknown { .. })
+                if invoked
+                    .last()
+                    .is_some_and(|program| *program != bridge_program) => {}
+            Ok(event) => events.push(event),
+            Err(_) => failures += 1,
+        }
+    }
+    (events, failures)
+}
+
+/// Keeps `invoked`, the stack of executing programs, up to date with the
+/// `Program <id> invoke [n]` and `Program <id> success`/`failed` log lines.
+/// Returns the program `log` invoked, if any.
+fn track_invocation<'a>(invoked: &mut Vec<&'a str>, log: &'a str) -> Option<&'a str> {
+    let (program, status) = log.strip_prefix("Program ")?.split_once(' ')?;
+    if status.starts_with("invoke [") {
+        invoked.push(program);
+        return Some(program);
+    }
+    if status == "success" || status.starts_with("failed") {
+        invoked.pop();
+    }
+    None
+}
+
+/// Returns whether a transaction's logs may not carry all of its events: either
+/// the node truncated them, or the bridge program invoked itself, which is how
+/// `emit_cpi!` emits events outside the logs.
+pub(crate) fn logs_incomplete(logs: &[String]) -> bool {
+    let bridge_program = w3b2_bridge_program::ID.to_string();
+    let mut invoked: Vec<&str> = Vec::new();
+    for log in logs {
+        if log.starts_with("Log truncated") {
+            return true;
+        }
+        let caller = invoked.last().copied();
+        if track_invocation(&mut invoked, log) == Some(bridge_program.as_str())
+            && caller == Some(bridge_program.as_str())
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Decodes every event of a fetched transaction: those logged with `emit!`,
+/// followed by those emitted with `emit_cpi!` in its inner instructions.
+///
+/// `emit_cpi!` events don't depend on the logs, so they are recovered even when
+/// the logs were truncated. Also returns how many payloads failed to decode.
+pub(crate) fn parse_transaction(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> (Vec<BridgeEvent>, usize) {
+    let Some(meta) = &tx.transaction.meta else {
+        return (Vec::new(), 0);
+    };
+    let (mut events, mut failures) = match &meta.log_messages {
+        OptionSerializer::Some(logs) => parse_logs(logs),
+        _ => (Vec::new(), 0),
+    };
+    if let (OptionSerializer::Some(inner), Some(keys)) =
+        (&meta.inner_instructions, transaction_account_keys(tx))
+    {
+        let (cpi_events, cpi_failures) = parse_cpi_events(&keys, inner);
+        events.extend(cpi_events);
+        failures += cpi_failures;
     }
+    (events, failures)
+}
+
+/// Returns the log messages of a fetched transaction, or none if the node didn't
+/// return them.
+pub(crate) fn transaction_logs(tx: &EncodedConfirmedTransactionWithStatusMeta) -> &[String] {
+    match tx.transaction.meta.as_ref().map(|meta| &meta.log_messages) {
+        Some(OptionSerializer::Some(logs)) => logs,
+        _ => &[],
+    }
+}
+
+/// Returns every account key of `tx`: the static ones followed by those loaded
+/// from lookup tables. Returns `None` if the transaction cannot be decoded.
+pub(crate) fn transaction_account_keys(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<Vec<Pubkey>> {
+    let decoded = tx.transaction.transaction.decode()?;
+    let mut keys = decoded.message.static_account_keys().to_vec();
+    if let Some(meta) = &tx.transaction.meta {
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            keys.extend(
+                loaded
+                    .writable
+                    .iter()
+                    .chain(&loaded.readonly)
+                    .filter_map(|key| key.parse::<Pubkey>().ok()),
+            );
+        }
+    }
+    Some(keys)
+}
+
+/// Decodes the events emitted with `emit_cpi!`, in instruction order.
+///
+/// `emit_cpi!` invokes the bridge program itself with the event data, prefixed
+/// by Anchor's event instruction tag, as instruction data. `account_keys` resolves
+/// the inner instructions' program indices. Also returns how many payloads failed
+/// to decode.
+pub fn parse_cpi_events(
+    account_keys: &[Pubkey],
+    inner_instructions: &[UiInnerInstructions],
+) -> (Vec<BridgeEvent>, usize) {
+    let mut events = Vec::new();
+    let mut failures = 0;
+    for instruction in inner_instructions
+        .iter()
+        .flat_map(|inner| &inner.instructions)
+    {
+        let UiInstruction::Compiled(instruction) = instruction else {
+            continue;
+        };
+        let program = account_keys.get(instruction.program_id_index as usize);
+        if program != Some(&w3b2_bridge_program::ID) {
+            continue;
+        }
+        let Ok(data) = bs58::decode(&instruction.data).into_vec() else {
+            failures += 1;
+            continue;
+        };
+        let Some(event_data) = data.strip_prefix(EVENT_IX_TAG_LE) else {
+            continue;
+        };
+        match parse_event_data(event_data) {
+            Ok(event) => events.push(event),
+            Err(_) => failures += 1,
+        }
+    }
+    (events, failures)
 }
 
 /// Attempts to extract a base64 payload from a log line and parse it into an event.
 /// This function looks for the "Program data: " prefix added by `emit!`.
+///
+/// Anything else yields an `Unknown` event carrying the log line, and the
+/// payload if it could be extracted, so an event with an unknown discriminator
+/// keeps its data.
 pub fn try_parse_log(log: &str) -> Result<BridgeEvent> {
-    if let Some(data_str) = log.strip_prefix("Program data: ") {
-        if let Ok(bytes) = BASE64.decode(data_str.trim()) {
-            if let Ok(event) = parse_event_data(&bytes) {
-                // Only return successfully parsed, known events.
-                if !matches!(event, BridgeEvent::Unknown) {
-                    return Ok(event);
-                }
-            }
+    let data = log
+        .strip_prefix("Program data: ")
+        .and_then(|data_str| BASE64.decode(data_str.trim()).ok())
+        .unwrap_or_default();
+    let event = parse_event_data(&data).unwrap_or_else(|_| BridgeEvent::Unknown {
+        raw: RawEvent {
+            data,
+            ..Default::default()
+        },
+    });
+    Ok(event.with_source(None, &[log.to_string()]))
+}
+
+/// Remote definitions deriving serde for the on-chain event structs, which the
+/// program crate doesn't implement itself.
+#[cfg(feature = "serde")]
+mod serde_events {
+    use super::OnChainEvent;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use w3b2_bridge_program::state::PriceEntry;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminProfileRegistered")]
+    pub struct AdminProfileRegisteredDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "pubkey")]
+        pub communication_pubkey: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminCommKeyUpdated")]
+    pub struct AdminCommKeyUpdatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "pubkey")]
+        pub new_comm_pubkey: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminPricesUpdated")]
+    pub struct AdminPricesUpdatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "price_entries")]
+        pub new_prices: Vec<PriceEntry>,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminGcPolicyUpdated")]
+    pub struct AdminGcPolicyUpdatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub inactivity_epochs: u64,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminPrioritySurchargeUpdated")]
+    pub struct AdminPrioritySurchargeUpdatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub surcharge: u64,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminFundsWithdrawn")]
+    pub struct AdminFundsWithdrawnDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub amount: u64,
+        #[serde(with = "pubkey")]
+        pub destination: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminProfileClosed")]
+    pub struct AdminProfileClosedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminProfileMigrated")]
+    pub struct AdminProfileMigratedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "pubkey")]
+        pub old_profile: Pubkey,
+        #[serde(with = "pubkey")]
+        pub new_profile: Pubkey,
+        pub profile_index: u16,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::AdminCommandDispatched")]
+    pub struct AdminCommandDispatchedDef {
+        #[serde(with = "pubkey")]
+        pub sender: Pubkey,
+        #[serde(with = "pubkey")]
+        pub target_user_authority: Pubkey,
+        pub command_id: u64,
+        pub rebate: u64,
+        #[serde(with = "base64_bytes")]
+        pub payload: Vec<u8>,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserProfileCreated")]
+    pub struct UserProfileCreatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "pubkey")]
+        pub target_admin: Pubkey,
+        #[serde(with = "pubkey")]
+        pub communication_pubkey: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserCommKeyUpdated")]
+    pub struct UserCommKeyUpdatedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        #[serde(with = "pubkey")]
+        pub new_comm_pubkey: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserFundsDeposited")]
+    pub struct UserFundsDepositedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub amount: u64,
+        pub new_deposit_balance: u64,
+        pub rent_reserve: u64,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserFundsWithdrawn")]
+    pub struct UserFundsWithdrawnDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub amount: u64,
+        #[serde(with = "pubkey")]
+        pub destination: Pubkey,
+        pub new_deposit_balance: u64,
+        pub rent_reserve: u64,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserProfileClosed")]
+    pub struct UserProfileClosedDef {
+        #[serde(with = "pubkey")]
+        pub authority: Pubkey,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::UserCommandDispatched")]
+    pub struct UserCommandDispatchedDef {
+        #[serde(with = "pubkey")]
+        pub sender: Pubkey,
+        #[serde(with = "pubkey")]
+        pub target_admin_authority: Pubkey,
+        pub command_id: u16,
+        pub price_paid: u64,
+        pub high_priority: bool,
+        pub priority_fee: u64,
+        #[serde(with = "base64_bytes")]
+        pub payload: Vec<u8>,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "OnChainEvent::OffChainActionLogged")]
+    pub struct OffChainActionLoggedDef {
+        #[serde(with = "pubkey")]
+        pub actor: Pubkey,
+        pub session_id: u64,
+        pub action_code: u16,
+        pub ts: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PriceEntry")]
+    struct PriceEntryDef {
+        command_id: u16,
+        price: u64,
+    }
+
+    /// (De)serializes a pubkey as a base58 string rather than a byte array.
+    pub mod pubkey {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(pubkey)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+            let s: String = Deserialize::deserialize(deserializer)?;
+            s.parse().map_err(D::Error::custom)
+        }
+    }
+
+    /// (De)serializes bytes as a base64 string rather than an array of numbers.
+    pub mod base64_bytes {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&BASE64.encode(bytes))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            let s: String = Deserialize::deserialize(deserializer)?;
+            BASE64.decode(s).map_err(D::Error::custom)
+        }
+    }
+
+    /// (De)serializes a price list through `PriceEntryDef`.
+    mod price_entries {
+        use super::*;
+
+        #[derive(Serialize, Deserialize)]
+        struct Entry(#[serde(with = "PriceEntryDef")] PriceEntry);
+
+        pub fn serialize<S: Serializer>(
+            entries: &[PriceEntry],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(entries.iter().map(|entry| Entry(entry.clone())))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<PriceEntry>, D::Error> {
+            let entries: Vec<Entry> = Deserialize::deserialize(deserializer)?;
+            Ok(entries.into_iter().map(|Entry(entry)| entry).collect())
         }
     }
-    Ok(BridgeEvent::Unknown)
 }
@@ -0,0 +1,379 @@
+// File: w3b2-connector/src/instructions.rs
+
+//! Raw `Instruction` constructors for every W3B2 Bridge Program instruction.
+//!
+//! These are the building blocks used by `TransactionBuilder`. They derive the
+//! PDAs from the given authorities, so callers only supply the business arguments.
+//! The exception is the admin's own `AdminProfile`, which may have been migrated
+//! to the v2 seeds: admin instructions take its address, see
+//! `TransactionBuilder::resolve_admin_profile`.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::{
+    accounts, instruction,
+    state::{PriceEntry, UpdatePricesArgs, ADMIN_SEED_V2},
+};
+
+/// Derives the `AdminProfile` PDA owned by `authority`.
+pub fn admin_profile_pda(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID).0
+}
+
+/// Derives the v2 `AdminProfile` PDA owned by `authority`, created by `admin_migrate_to_v2`.
+pub fn admin_profile_pda_v2(authority: &Pubkey, profile_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            ADMIN_SEED_V2,
+            authority.as_ref(),
+            &profile_index.to_le_bytes(),
+        ],
+        &w3b2_bridge_program::ID,
+    )
+    .0
+}
+
+/// Derives the `UserProfile` PDA owned by `authority` and linked to `admin_profile_pda`.
+pub fn user_profile_pda(authority: &Pubkey, admin_profile_pda: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user", authority.as_ref(), admin_profile_pda.as_ref()],
+        &w3b2_bridge_program::ID,
+    )
+    .0
+}
+
+// --- Admin Instructions ---
+
+/// Builds an `admin_register_profile` instruction.
+pub fn admin_register_profile(authority: Pubkey, communication_pubkey: Pubkey) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminRegisterProfile {
+            authority,
+            admin_profile: admin_profile_pda(&authority),
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::AdminRegisterProfile {
+            communication_pubkey,
+        }
+        .data(),
+    }
+}
+
+/// Builds an `admin_update_comm_key` instruction.
+pub fn admin_update_comm_key(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    new_key: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminUpdateCommKey {
+            authority,
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminUpdateCommKey { new_key }.data(),
+    }
+}
+
+/// Builds an `admin_update_prices` instruction.
+pub fn admin_update_prices(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    new_prices: Vec<PriceEntry>,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminUpdatePrices {
+            authority,
+            admin_profile: admin_profile_pda,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::AdminUpdatePrices {
+            args: UpdatePricesArgs { new_prices },
+        }
+        .data(),
+    }
+}
+
+/// Builds an `admin_set_gc_policy` instruction.
+pub fn admin_set_gc_policy(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    inactivity_epochs: u64,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminSetGcPolicy {
+            authority,
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminSetGcPolicy { inactivity_epochs }.data(),
+    }
+}
+
+/// Builds an `admin_set_priority_surcharge` instruction.
+pub fn admin_set_priority_surcharge(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    surcharge: u64,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminSetPrioritySurcharge {
+            authority,
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminSetPrioritySurcharge { surcharge }.data(),
+    }
+}
+
+/// Builds an `admin_withdraw` instruction.
+pub fn admin_withdraw(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    amount: u64,
+    destination: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminWithdraw {
+            authority,
+            admin_profile: admin_profile_pda,
+            destination,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::AdminWithdraw { amount }.data(),
+    }
+}
+
+/// Builds an `admin_close_profile` instruction.
+pub fn admin_close_profile(authority: Pubkey, admin_profile_pda: Pubkey) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminCloseProfile {
+            authority,
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminCloseProfile {}.data(),
+    }
+}
+
+/// Builds an `admin_close_profile_to` instruction.
+pub fn admin_close_profile_to(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminCloseProfileTo {
+            authority,
+            admin_profile: admin_profile_pda,
+            destination,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminCloseProfileTo {}.data(),
+    }
+}
+
+/// Builds an `admin_migrate_to_v2` instruction.
+pub fn admin_migrate_to_v2(authority: Pubkey, profile_index: u16) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminMigrateToV2 {
+            authority,
+            admin_profile: admin_profile_pda(&authority),
+            admin_profile_v2: admin_profile_pda_v2(&authority, profile_index),
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::AdminMigrateToV2 { profile_index }.data(),
+    }
+}
+
+/// Builds an `admin_dispatch_command` instruction.
+pub fn admin_dispatch_command(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    target_user_profile_pda: Pubkey,
+    command_id: u64,
+    rebate: u64,
+    payload: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::AdminDispatchCommand {
+            admin_authority: authority,
+            admin_profile: admin_profile_pda,
+            user_profile: target_user_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::AdminDispatchCommand {
+            command_id,
+            rebate,
+            payload,
+        }
+        .data(),
+    }
+}
+
+// --- User Instructions ---
+
+/// Builds a `user_create_profile` instruction.
+pub fn user_create_profile(
+    authority: Pubkey,
+    target_admin_pda: Pubkey,
+    communication_pubkey: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserCreateProfile {
+            authority,
+            user_profile: user_profile_pda(&authority, &target_admin_pda),
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::UserCreateProfile {
+            target_admin: target_admin_pda,
+            communication_pubkey,
+        }
+        .data(),
+    }
+}
+
+/// Builds a `user_update_comm_key` instruction.
+pub fn user_update_comm_key(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    new_key: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserUpdateCommKey {
+            authority,
+            user_profile: user_profile_pda(&authority, &admin_profile_pda),
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::UserUpdateCommKey { new_key }.data(),
+    }
+}
+
+/// Builds a `user_deposit` instruction.
+pub fn user_deposit(authority: Pubkey, admin_profile_pda: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserDeposit {
+            authority,
+            user_profile: user_profile_pda(&authority, &admin_profile_pda),
+            admin_profile: admin_profile_pda,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::UserDeposit { amount }.data(),
+    }
+}
+
+/// Builds a `user_withdraw` instruction.
+pub fn user_withdraw(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    amount: u64,
+    destination: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserWithdraw {
+            authority,
+            user_profile: user_profile_pda(&authority, &admin_profile_pda),
+            admin_profile: admin_profile_pda,
+            destination,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::UserWithdraw { amount }.data(),
+    }
+}
+
+/// Builds a `user_close_profile` instruction.
+pub fn user_close_profile(authority: Pubkey, admin_profile_pda: Pubkey) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserCloseProfile {
+            authority,
+            user_profile: user_profile_pda(&authority, &admin_profile_pda),
+            admin_profile: admin_profile_pda,
+        }
+        .to_account_metas(None),
+        data: instruction::UserCloseProfile {}.data(),
+    }
+}
+
+// --- Operational Instructions ---
+
+/// Builds a `user_dispatch_command` instruction.
+pub fn user_dispatch_command(
+    authority: Pubkey,
+    admin_profile_pda: Pubkey,
+    command_id: u16,
+    high_priority: bool,
+    payload: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::UserDispatchCommand {
+            authority,
+            user_profile: user_profile_pda(&authority, &admin_profile_pda),
+            admin_profile: admin_profile_pda,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: instruction::UserDispatchCommand {
+            command_id,
+            high_priority,
+            payload,
+        }
+        .data(),
+    }
+}
+
+/// Builds a `gc_inactive_profile` instruction.
+pub fn gc_inactive_profile(
+    caller: Pubkey,
+    user_authority: Pubkey,
+    admin_profile_pda: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::GcInactiveProfile {
+            caller,
+            admin_profile: admin_profile_pda,
+            authority: user_authority,
+            user_profile: user_profile_pda(&user_authority, &admin_profile_pda),
+        }
+        .to_account_metas(None),
+        data: instruction::GcInactiveProfile {}.data(),
+    }
+}
+
+/// Builds a `log_action` instruction.
+pub fn log_action(authority: Pubkey, session_id: u64, action_code: u16) -> Instruction {
+    Instruction {
+        program_id: w3b2_bridge_program::ID,
+        accounts: accounts::LogAction { authority }.to_account_metas(None),
+        data: instruction::LogAction {
+            session_id,
+            action_code,
+        }
+        .data(),
+    }
+}
@@ -1,9 +1,30 @@
+pub mod backoff;
+pub mod blockhash;
+pub mod chunking;
 pub mod client;
+pub mod cluster;
+pub mod codec;
 pub mod config;
 pub mod dispatcher;
 pub mod events;
+pub mod instructions;
 pub mod listener;
+pub mod metrics;
+pub mod prices;
+pub mod protocol;
+pub mod reader;
+pub mod rotation;
+pub mod rpc;
+pub mod rpc_pool;
+pub mod runtime;
+pub mod schema;
+pub mod signing;
+pub mod sink;
 pub mod storage;
+pub mod subscription;
+pub mod topup;
+pub mod tracker;
 pub mod workers;
 
 pub use w3b2_bridge_program::state as Accounts;
+pub use w3b2_bridge_program::ID as PROGRAM_ID;
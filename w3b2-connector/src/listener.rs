@@ -37,7 +37,7 @@
 //! streams tailored to the operational needs of a service.
 //!
 //! - **`personal_events`**: A stream for actions the admin performs on their own `AdminProfile`.
-//!   - Contains: `AdminProfileRegistered`, `AdminPricesUpdated`, `AdminFundsWithdrawn`, `AdminCommKeyUpdated`, `AdminProfileClosed`, `AdminCommandDispatched`, `OffChainActionLogged`.
+//!   - Contains: `AdminProfileRegistered`, `AdminPricesUpdated`, `AdminGcPolicyUpdated`, `AdminPrioritySurchargeUpdated`, `AdminFundsWithdrawn`, `AdminCommKeyUpdated`, `AdminProfileClosed`, `AdminProfileMigrated`, `AdminCommandDispatched`, `OffChainActionLogged`.
 //!
 //! - **`new_user_profiles`**: The "discovery" stream for an admin. It emits an event only when a new
 //!   user creates a `UserProfile` for this admin's service. This acts as a "doorbell" for new customers.
@@ -46,14 +46,30 @@
 //! - **`incoming_user_commands`**: The primary operational stream for a service, delivering all
 //!   commands sent by users to this specific admin.
 //!   - Contains: `UserCommandDispatched`.
+//!
+//! Every stream yields `EventEnvelope`s, so consumers also get the slot, signature and
+//! block time of each event, e.g. to deduplicate events or link them to an explorer.
+//!
+//! Besides the raw channels, both listeners can hand out their streams as an
+//! `EventStream`, a `futures::Stream`, either per category or merged into one, so
+//! consumers can use the standard stream combinators instead of a `tokio::select!` loop.
+//!
+//! `UserEventKind` and `AdminEventKind` tell which channel a listener routes an event
+//! to, e.g. to sort events read from storage the same way as live ones.
 
-pub use crate::events::BridgeEvent;
+use crate::dispatcher::{LagStatus, ListenerRegistration};
+pub use crate::events::{BridgeEvent, EventEnvelope};
 use dashmap::DashMap;
+use futures::stream::{self, BoxStream, StreamExt};
 use solana_sdk::pubkey::Pubkey;
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
 use w3b2_bridge_program::ID as PROGRAM_ID;
 
+/// A boxed stream of events, as handed out by the listeners' stream adapters.
+pub type EventStream = BoxStream<'static, EventEnvelope>;
+
 // --- User Listener ---
 
 /// Manages event streams from a user's perspective.
@@ -68,11 +84,13 @@ use w3b2_bridge_program::ID as PROGRAM_ID;
 #[derive(Debug)]
 pub struct UserListener {
     /// Channel for personal user events.
-    personal_events_rx: broadcast::Receiver<BridgeEvent>,
+    personal_events_rx: broadcast::Receiver<EventEnvelope>,
     /// Channel for all service-related interactions.
-    all_interactions_rx: broadcast::Receiver<BridgeEvent>,
+    all_interactions_rx: broadcast::Receiver<EventEnvelope>,
     /// Map of service-specific listeners keyed by `Admin PDA`.
-    service_listeners: Arc<DashMap<Pubkey, mpsc::Sender<BridgeEvent>>>,
+    service_listeners: Arc<DashMap<Pubkey, mpsc::Sender<EventEnvelope>>>,
+    /// Whether the dispatcher disconnected this listener for falling behind.
+    lag_status: LagStatus,
 }
 
 impl UserListener {
@@ -85,48 +103,51 @@ impl UserListener {
     /// Spawns a background task that routes events into the categorized channels.
     pub fn new(
         pubkey: Pubkey,
-        mut raw_event_rx: mpsc::Receiver<BridgeEvent>,
+        raw_event_rx: mpsc::Receiver<EventEnvelope>,
+        channel_capacity: usize,
+    ) -> Self {
+        Self::start(pubkey, raw_event_rx, None, channel_capacity)
+    }
+
+    /// Like `new`, but keeps `registration` alive for as long as the routing task
+    /// runs. The task stops once the listener and every receiver obtained from it
+    /// have been dropped, which unregisters it from the dispatcher.
+    pub(crate) fn start(
+        pubkey: Pubkey,
+        mut raw_event_rx: mpsc::Receiver<EventEnvelope>,
+        registration: Option<ListenerRegistration>,
         channel_capacity: usize,
     ) -> Self {
         let (personal_tx, personal_rx) = broadcast::channel(channel_capacity);
         let (all_interactions_tx, all_interactions_rx) = broadcast::channel(channel_capacity);
         let service_listeners = Arc::new(DashMap::new());
         let service_listeners_clone = service_listeners.clone();
+        let lag_status = registration
+            .as_ref()
+            .map(ListenerRegistration::lag_status)
+            .unwrap_or_default();
 
         tokio::spawn(async move {
+            let _registration = registration;
             while let Some(event) = raw_event_rx.recv().await {
-                match &event {
-                    // --- Personal Events ---
-                    BridgeEvent::UserFundsDeposited(e) if e.authority == pubkey => {
-                        let _ = personal_tx.send(event.clone());
-                    }
-                    BridgeEvent::UserFundsWithdrawn(e) if e.authority == pubkey => {
-                        let _ = personal_tx.send(event.clone());
-                    }
-                    BridgeEvent::UserCommKeyUpdated(e) if e.authority == pubkey => {
+                match UserEventKind::of(&pubkey, &event.event) {
+                    Some(UserEventKind::Personal) => {
                         let _ = personal_tx.send(event.clone());
                     }
-                    BridgeEvent::UserProfileClosed(e) if e.authority == pubkey => {
-                        let _ = personal_tx.send(event.clone());
-                    }
-                    BridgeEvent::OffChainActionLogged(e) if e.actor == pubkey => {
-                        let _ = personal_tx.send(event.clone());
-                    }
-
-                    // --- Interaction Events ---
-                    BridgeEvent::UserProfileCreated(e) if e.authority == pubkey => {
-                        handle_interaction(event, &all_interactions_tx, &service_listeners_clone)
-                            .await;
-                    }
-                    BridgeEvent::UserCommandDispatched(e) if e.sender == pubkey => {
-                        handle_interaction(event, &all_interactions_tx, &service_listeners_clone)
-                            .await;
-                    }
-                    BridgeEvent::AdminCommandDispatched(e) if e.target_user_authority == pubkey => {
+                    Some(UserEventKind::ServiceInteraction) => {
                         handle_interaction(event, &all_interactions_tx, &service_listeners_clone)
                             .await;
                     }
-                    _ => {}
+                    None => {}
+                }
+
+                // Nobody is left to consume the categorized streams.
+                if Arc::strong_count(&service_listeners_clone) == 1
+                    && personal_tx.receiver_count() == 0
+                    && all_interactions_tx.receiver_count() == 0
+                    && service_listeners_clone.iter().all(|tx| tx.is_closed())
+                {
+                    break;
                 }
             }
         });
@@ -135,14 +156,21 @@ impl UserListener {
             personal_events_rx: personal_rx,
             all_interactions_rx,
             service_listeners,
+            lag_status,
         }
     }
 
+    /// Returns a handle telling whether the dispatcher disconnected this listener
+    /// for falling behind. Check it once the streams have ended.
+    pub fn lag_status(&self) -> LagStatus {
+        self.lag_status.clone()
+    }
+
     /// Get a receiver for the channel of **personal user events**.
     ///
     /// Events include deposits, withdrawals, comm key updates, and profile closure.
     /// This clones the underlying broadcast receiver.
-    pub fn personal_events(&self) -> broadcast::Receiver<BridgeEvent> {
+    pub fn personal_events(&self) -> broadcast::Receiver<EventEnvelope> {
         self.personal_events_rx.resubscribe()
     }
 
@@ -150,10 +178,34 @@ impl UserListener {
     ///
     /// Events include any user ↔ admin relationship creation or command dispatch.
     /// This clones the underlying broadcast receiver.
-    pub fn all_service_interactions(&self) -> broadcast::Receiver<BridgeEvent> {
+    pub fn all_service_interactions(&self) -> broadcast::Receiver<EventEnvelope> {
         self.all_interactions_rx.resubscribe()
     }
 
+    /// Returns the **personal user events** as a `Stream`.
+    ///
+    /// Events the stream fell too far behind on are skipped with a warning.
+    pub fn personal_events_stream(&self) -> EventStream {
+        broadcast_stream(self.personal_events())
+    }
+
+    /// Returns **all service interactions** as a `Stream`.
+    ///
+    /// Events the stream fell too far behind on are skipped with a warning.
+    pub fn all_service_interactions_stream(&self) -> EventStream {
+        broadcast_stream(self.all_service_interactions())
+    }
+
+    /// Consumes the listener and merges its personal events and service
+    /// interactions into one `Stream`, in arrival order per category.
+    pub fn into_stream(self) -> EventStream {
+        stream::select(
+            broadcast_stream(self.personal_events_rx),
+            broadcast_stream(self.all_interactions_rx),
+        )
+        .boxed()
+    }
+
     /// Create a new channel for events tied to a **specific service/admin**.
     ///
     /// - `target_admin_pda`: The PDA of the target service/admin.
@@ -164,7 +216,7 @@ impl UserListener {
         &self,
         target_admin_pda: Pubkey,
         capacity: usize,
-    ) -> mpsc::Receiver<BridgeEvent> {
+    ) -> mpsc::Receiver<EventEnvelope> {
         let (tx, rx) = mpsc::channel(capacity);
         self.service_listeners.insert(target_admin_pda, tx);
         rx
@@ -177,7 +229,7 @@ impl UserListener {
     pub fn stop_listening_for_service(
         &self,
         target_admin_pda: Pubkey,
-    ) -> Option<(Pubkey, mpsc::Sender<BridgeEvent>)> {
+    ) -> Option<(Pubkey, mpsc::Sender<EventEnvelope>)> {
         self.service_listeners.remove(&target_admin_pda)
     }
 }
@@ -195,11 +247,13 @@ impl UserListener {
 #[derive(Debug)]
 pub struct AdminListener {
     /// Channel for admin-only events.
-    personal_events_rx: mpsc::Receiver<BridgeEvent>,
+    personal_events_rx: mpsc::Receiver<EventEnvelope>,
     /// Channel for incoming user commands targeted to this admin.
-    incoming_user_commands_rx: mpsc::Receiver<BridgeEvent>,
+    incoming_user_commands_rx: mpsc::Receiver<EventEnvelope>,
     /// Channel for new user profile creation events.
-    new_user_profiles_rx: mpsc::Receiver<BridgeEvent>,
+    new_user_profiles_rx: mpsc::Receiver<EventEnvelope>,
+    /// Whether the dispatcher disconnected this listener for falling behind.
+    lag_status: LagStatus,
 }
 
 impl AdminListener {
@@ -212,67 +266,52 @@ impl AdminListener {
     /// Spawns a background task that routes events into the categorized channels.
     pub fn new(
         admin_authority_pubkey: Pubkey,
-        mut raw_event_rx: mpsc::Receiver<BridgeEvent>,
+        raw_event_rx: mpsc::Receiver<EventEnvelope>,
+        channel_capacity: usize,
+    ) -> Self {
+        Self::start(admin_authority_pubkey, raw_event_rx, None, channel_capacity)
+    }
+
+    /// Like `new`, but keeps `registration` alive for as long as the routing task
+    /// runs. The task stops once all three receivers have been dropped, which
+    /// unregisters it from the dispatcher.
+    pub(crate) fn start(
+        admin_authority_pubkey: Pubkey,
+        mut raw_event_rx: mpsc::Receiver<EventEnvelope>,
+        registration: Option<ListenerRegistration>,
         channel_capacity: usize,
     ) -> Self {
         let (personal_tx, personal_rx) = mpsc::channel(channel_capacity);
         let (commands_tx, commands_rx) = mpsc::channel(channel_capacity);
         let (new_users_tx, new_users_rx) = mpsc::channel(channel_capacity);
-
-        let (admin_pda, _) =
-            Pubkey::find_program_address(&[b"admin", admin_authority_pubkey.as_ref()], &PROGRAM_ID);
+        let lag_status = registration
+            .as_ref()
+            .map(ListenerRegistration::lag_status)
+            .unwrap_or_default();
 
         tokio::spawn(async move {
-            while let Some(event) = raw_event_rx.recv().await {
-                match &event {
-                    // --- Personal Admin Events ---
-                    BridgeEvent::AdminProfileRegistered(e)
-                        if e.authority == admin_authority_pubkey =>
-                    {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::AdminPricesUpdated(e) if e.authority == admin_authority_pubkey => {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::AdminFundsWithdrawn(e)
-                        if e.authority == admin_authority_pubkey =>
-                    {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::AdminCommKeyUpdated(e)
-                        if e.authority == admin_authority_pubkey =>
-                    {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::AdminProfileClosed(e) if e.authority == admin_authority_pubkey => {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::AdminCommandDispatched(e)
-                        if e.sender == admin_authority_pubkey =>
-                    {
-                        let _ = personal_tx.send(event).await;
-                    }
-                    BridgeEvent::OffChainActionLogged(e) if e.actor == admin_authority_pubkey => {
-                        let _ = personal_tx.send(event).await;
-                    }
-
-                    // --- User → Admin Events ---
-                    BridgeEvent::UserCommandDispatched(e) => {
-                        // Derive the target admin's PDA from the event data
-                        let target_pda = Pubkey::find_program_address(
-                            &[b"admin", e.target_admin_authority.as_ref()],
-                            &PROGRAM_ID,
+            let _registration = registration;
+            loop {
+                let event = tokio::select! {
+                    event = raw_event_rx.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = async {
+                        tokio::join!(
+                            personal_tx.closed(),
+                            commands_tx.closed(),
+                            new_users_tx.closed()
                         )
-                        .0;
-                        if target_pda == admin_pda {
-                            let _ = commands_tx.send(event).await;
-                        }
-                    }
-                    BridgeEvent::UserProfileCreated(e) if e.target_admin == admin_pda => {
-                        let _ = new_users_tx.send(event).await;
-                    }
-                    _ => {}
-                }
+                    } => break,
+                };
+                let tx = match AdminEventKind::of(&admin_authority_pubkey, &event.event) {
+                    Some(AdminEventKind::Personal) => &personal_tx,
+                    Some(AdminEventKind::IncomingUserCommand) => &commands_tx,
+                    Some(AdminEventKind::NewUserProfile) => &new_users_tx,
+                    None => continue,
+                };
+                let _ = tx.send(event).await;
             }
         });
 
@@ -280,39 +319,63 @@ impl AdminListener {
             personal_events_rx: personal_rx,
             incoming_user_commands_rx: commands_rx,
             new_user_profiles_rx: new_users_rx,
+            lag_status,
         }
     }
 
+    /// Returns a handle telling whether the dispatcher disconnected this listener
+    /// for falling behind. Check it once the streams have ended; `into_parts`
+    /// does not carry it.
+    pub fn lag_status(&self) -> LagStatus {
+        self.lag_status.clone()
+    }
+
     /// Access the channel of **personal admin events**.
     ///
     /// Includes profile registration, price updates, withdrawals,
     /// comm key updates, and profile closure.
-    pub fn personal_events(&mut self) -> &mut mpsc::Receiver<BridgeEvent> {
+    pub fn personal_events(&mut self) -> &mut mpsc::Receiver<EventEnvelope> {
         &mut self.personal_events_rx
     }
 
     /// Access the channel of **incoming user commands**.
     ///
     /// Provides the operational command stream for this admin's service.
-    pub fn incoming_user_commands(&mut self) -> &mut mpsc::Receiver<BridgeEvent> {
+    pub fn incoming_user_commands(&mut self) -> &mut mpsc::Receiver<EventEnvelope> {
         &mut self.incoming_user_commands_rx
     }
 
     /// Access the channel of **new user profiles**.
     ///
     /// Emits events when a new user creates a profile for this admin.
-    pub fn new_user_profiles(&mut self) -> &mut mpsc::Receiver<BridgeEvent> {
+    pub fn new_user_profiles(&mut self) -> &mut mpsc::Receiver<EventEnvelope> {
         &mut self.new_user_profiles_rx
     }
 
+    /// Consumes the listener and returns its personal events, incoming user
+    /// commands and new user profiles as separate `Stream`s, in that order.
+    pub fn into_streams(self) -> (EventStream, EventStream, EventStream) {
+        (
+            ReceiverStream::new(self.personal_events_rx).boxed(),
+            ReceiverStream::new(self.incoming_user_commands_rx).boxed(),
+            ReceiverStream::new(self.new_user_profiles_rx).boxed(),
+        )
+    }
+
+    /// Consumes the listener and merges all of its categories into one `Stream`.
+    pub fn into_stream(self) -> EventStream {
+        let (personal, commands, new_users) = self.into_streams();
+        stream::select_all([personal, commands, new_users]).boxed()
+    }
+
     /// Consumes the listener and returns its underlying receiver channels.
     /// This is useful for moving the channels into separate tasks, like in `tokio::select!`.
     pub fn into_parts(
         self,
     ) -> (
-        mpsc::Receiver<BridgeEvent>,
-        mpsc::Receiver<BridgeEvent>,
-        mpsc::Receiver<BridgeEvent>,
+        mpsc::Receiver<EventEnvelope>,
+        mpsc::Receiver<EventEnvelope>,
+        mpsc::Receiver<EventEnvelope>,
     ) {
         (
             self.personal_events_rx,
@@ -322,17 +385,138 @@ impl AdminListener {
     }
 }
 
+// --- Event Kinds ---
+
+/// The `UserListener` channel an event is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserEventKind {
+    /// The **personal events** channel.
+    Personal,
+    /// The **all service interactions** channel, and the matching service-specific
+    /// channel if there is one.
+    ServiceInteraction,
+}
+
+impl UserEventKind {
+    /// Returns the channel a `UserListener` for `user` routes `event` to, or
+    /// `None` if it drops the event.
+    pub fn of(user: &Pubkey, event: &BridgeEvent) -> Option<Self> {
+        match event {
+            BridgeEvent::UserFundsDeposited(e) if e.authority == *user => Some(Self::Personal),
+            BridgeEvent::UserFundsWithdrawn(e) if e.authority == *user => Some(Self::Personal),
+            BridgeEvent::UserCommKeyUpdated(e) if e.authority == *user => Some(Self::Personal),
+            BridgeEvent::UserProfileClosed(e) if e.authority == *user => Some(Self::Personal),
+            BridgeEvent::OffChainActionLogged(e) if e.actor == *user => Some(Self::Personal),
+
+            BridgeEvent::UserProfileCreated(e) if e.authority == *user => {
+                Some(Self::ServiceInteraction)
+            }
+            BridgeEvent::UserCommandDispatched(e) if e.sender == *user => {
+                Some(Self::ServiceInteraction)
+            }
+            BridgeEvent::AdminCommandDispatched(e) if e.target_user_authority == *user => {
+                Some(Self::ServiceInteraction)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The `AdminListener` channel an event is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminEventKind {
+    /// The **personal events** channel.
+    Personal,
+    /// The **incoming user commands** channel.
+    IncomingUserCommand,
+    /// The **new user profiles** channel.
+    NewUserProfile,
+}
+
+impl AdminEventKind {
+    /// Returns the channel an `AdminListener` for `admin_authority` routes
+    /// `event` to, or `None` if it drops the event.
+    pub fn of(admin_authority: &Pubkey, event: &BridgeEvent) -> Option<Self> {
+        match event {
+            BridgeEvent::AdminProfileRegistered(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminPricesUpdated(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminGcPolicyUpdated(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminPrioritySurchargeUpdated(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminFundsWithdrawn(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminCommKeyUpdated(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminProfileClosed(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminProfileMigrated(e) if e.authority == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::AdminCommandDispatched(e) if e.sender == *admin_authority => {
+                Some(Self::Personal)
+            }
+            BridgeEvent::OffChainActionLogged(e) if e.actor == *admin_authority => {
+                Some(Self::Personal)
+            }
+
+            BridgeEvent::UserCommandDispatched(e)
+                if e.target_admin_authority == *admin_authority =>
+            {
+                Some(Self::IncomingUserCommand)
+            }
+            // User profiles name the admin by its PDA.
+            BridgeEvent::UserProfileCreated(e)
+                if e.target_admin
+                    == Pubkey::find_program_address(
+                        &[b"admin", admin_authority.as_ref()],
+                        &PROGRAM_ID,
+                    )
+                    .0 =>
+            {
+                Some(Self::NewUserProfile)
+            }
+            _ => None,
+        }
+    }
+}
+
 // --- Helper functions ---
 
+/// Adapts a broadcast receiver into an `EventStream` that ends once the channel closes.
+fn broadcast_stream(rx: broadcast::Receiver<EventEnvelope>) -> EventStream {
+    stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some((event, rx)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Listener stream lagged, skipped {} events.", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .boxed()
+}
+
 /// Process a user interaction event for a `UserListener`.
 ///
 /// Routes the event into the **all service interactions** channel,
 /// and, if a matching admin-specific listener exists,
 /// into the appropriate service-specific channel as well.
 async fn handle_interaction(
-    event: BridgeEvent,
-    all_interactions_tx: &broadcast::Sender<BridgeEvent>,
-    service_listeners: &Arc<DashMap<Pubkey, mpsc::Sender<BridgeEvent>>>,
+    event: EventEnvelope,
+    all_interactions_tx: &broadcast::Sender<EventEnvelope>,
+    service_listeners: &Arc<DashMap<Pubkey, mpsc::Sender<EventEnvelope>>>,
 ) {
     if all_interactions_tx.send(event.clone()).is_err() {
         // This can happen if no one is listening to the `all_service_interactions` stream.
@@ -340,7 +524,7 @@ async fn handle_interaction(
         tracing::debug!("No active receivers for 'all_service_interactions' broadcast channel.");
     }
 
-    if let Some(admin_pubkey) = get_admin_pubkey_from_interaction(&event) {
+    if let Some(admin_pubkey) = get_admin_pubkey_from_interaction(&event.event) {
         if let Some(specific_tx) = service_listeners.get(&admin_pubkey) {
             if specific_tx.send(event).await.is_err() {
                 tracing::warn!(
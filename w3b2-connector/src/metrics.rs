@@ -0,0 +1,230 @@
+// File: w3b2-connector/src/metrics.rs
+
+//! # Synchronizer Metrics
+//!
+//! The workers record what they observe in a shared `SyncMetrics`, and
+//! `EventManagerHandle::sync_status` turns it into a `SyncStatus` snapshot. The
+//! snapshot can also be rendered in the Prometheus text format, for operators who
+//! want to alert when the connector falls behind the chain.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// The window over which `SyncStatus::events_per_sec` is averaged.
+const RATE_WINDOW_SECS: u64 = 60;
+
+/// A point-in-time view of the synchronizer's progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncStatus {
+    /// The latest slot observed on the chain.
+    pub chain_slot: u64,
+    /// The last slot the synchronizer has fully processed.
+    pub processed_slot: u64,
+    /// The number of events published since start.
+    pub events_total: u64,
+    /// The publishing rate averaged over the last minute.
+    pub events_per_sec: f64,
+    /// The number of event payloads of the program that failed to decode.
+    pub decode_failures: u64,
+    /// The number of events skipped because they had already been published.
+    pub duplicates_skipped: u64,
+    /// The number of signatures left in the current catch-up batch.
+    pub catchup_remaining: usize,
+    /// The number of times the live WebSocket subscription was re-established.
+    pub ws_reconnects: u64,
+}
+
+impl SyncStatus {
+    /// Returns how many slots the synchronizer is behind the chain.
+    pub fn slot_lag(&self) -> u64 {
+        self.chain_slot.saturating_sub(self.processed_slot)
+    }
+
+    /// Renders the status in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (name, kind, help, value) in self.samples() {
+            let _ = writeln!(out, "# HELP w3b2_sync_{name} {help}");
+            let _ = writeln!(out, "# TYPE w3b2_sync_{name} {kind}");
+            let _ = writeln!(out, "w3b2_sync_{name} {value}");
+        }
+        out
+    }
+
+    /// Renders the status of several synchronizers in the Prometheus text
+    /// exposition format, one series per synchronizer, labeled with
+    /// `label="<name>"`.
+    pub fn to_prometheus_labeled(label: &str, statuses: &[(String, SyncStatus)]) -> String {
+        let samples: Vec<_> = statuses
+            .iter()
+            .map(|(name, status)| (name, status.samples()))
+            .collect();
+        let mut out = String::new();
+        let Some((_, first)) = samples.first() else {
+            return out;
+        };
+        for (i, (name, kind, help, _)) in first.iter().enumerate() {
+            let _ = writeln!(out, "# HELP w3b2_sync_{name} {help}");
+            let _ = writeln!(out, "# TYPE w3b2_sync_{name} {kind}");
+            for (label_value, samples) in &samples {
+                let value = &samples[i].3;
+                let _ = writeln!(out, "w3b2_sync_{name}{{{label}=\"{label_value}\"}} {value}");
+            }
+        }
+        out
+    }
+
+    /// Returns the name, type, help text and value of every exported metric.
+    fn samples(&self) -> [(&'static str, &'static str, &'static str, String); 9] {
+        [
+            (
+                "chain_slot",
+                "gauge",
+                "Latest slot observed on the chain.",
+                self.chain_slot.to_string(),
+            ),
+            (
+                "processed_slot",
+                "gauge",
+                "Last slot fully processed by the synchronizer.",
+                self.processed_slot.to_string(),
+            ),
+            (
+                "slot_lag",
+                "gauge",
+                "Slots the synchronizer is behind the chain.",
+                self.slot_lag().to_string(),
+            ),
+            (
+                "events_total",
+                "counter",
+                "Events published since start.",
+                self.events_total.to_string(),
+            ),
+            (
+                "events_per_second",
+                "gauge",
+                "Events published per second over the last minute.",
+                self.events_per_sec.to_string(),
+            ),
+            (
+                "decode_failures_total",
+                "counter",
+                "Program event payloads that failed to decode.",
+                self.decode_failures.to_string(),
+            ),
+            (
+                "duplicates_skipped_total",
+                "counter",
+                "Events skipped because they had already been published.",
+                self.duplicates_skipped.to_string(),
+            ),
+            (
+                "catchup_remaining",
+                "gauge",
+                "Signatures left in the current catch-up batch.",
+                self.catchup_remaining.to_string(),
+            ),
+            (
+                "ws_reconnects_total",
+                "counter",
+                "Times the live WebSocket subscription was re-established.",
+                self.ws_reconnects.to_string(),
+            ),
+        ]
+    }
+}
+
+/// Counters shared by the synchronizer's workers.
+pub(crate) struct SyncMetrics {
+    started_at: Instant,
+    chain_slot: AtomicU64,
+    events_total: AtomicU64,
+    decode_failures: AtomicU64,
+    duplicates_skipped: AtomicU64,
+    catchup_remaining: AtomicUsize,
+    ws_reconnects: AtomicU64,
+    /// Events published per second since start, for the last `RATE_WINDOW_SECS` seconds.
+    recent: Mutex<VecDeque<(u64, u64)>>,
+}
+
+impl SyncMetrics {
+    pub(crate) fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            chain_slot: AtomicU64::new(0),
+            events_total: AtomicU64::new(0),
+            decode_failures: AtomicU64::new(0),
+            duplicates_skipped: AtomicU64::new(0),
+            catchup_remaining: AtomicUsize::new(0),
+            ws_reconnects: AtomicU64::new(0),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub(crate) fn observe_chain_slot(&self, slot: u64) {
+        self.chain_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_event(&self) {
+        self.events_total.fetch_add(1, Ordering::Relaxed);
+
+        let second = self.started_at.elapsed().as_secs();
+        let mut recent = self.recent.lock().unwrap();
+        match recent.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => recent.push_back((second, 1)),
+        }
+        while recent
+            .front()
+            .is_some_and(|(s, _)| s + RATE_WINDOW_SECS <= second)
+        {
+            recent.pop_front();
+        }
+    }
+
+    pub(crate) fn record_decode_failures(&self, failures: usize) {
+        self.decode_failures
+            .fetch_add(failures as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_duplicate(&self) {
+        self.duplicates_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_catchup_remaining(&self, remaining: usize) {
+        self.catchup_remaining.store(remaining, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_reconnect(&self) {
+        self.ws_reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Builds a snapshot, given the last processed slot from storage.
+    pub(crate) fn status(&self, processed_slot: u64) -> SyncStatus {
+        let elapsed = self.started_at.elapsed().as_secs();
+        let recent_events: u64 = self
+            .recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(s, _)| s + RATE_WINDOW_SECS > elapsed)
+            .map(|(_, count)| count)
+            .sum();
+        let window = elapsed.clamp(1, RATE_WINDOW_SECS);
+
+        SyncStatus {
+            chain_slot: self.chain_slot.load(Ordering::Relaxed).max(processed_slot),
+            processed_slot,
+            events_total: self.events_total.load(Ordering::Relaxed),
+            events_per_sec: recent_events as f64 / window as f64,
+            decode_failures: self.decode_failures.load(Ordering::Relaxed),
+            duplicates_skipped: self.duplicates_skipped.load(Ordering::Relaxed),
+            catchup_remaining: self.catchup_remaining.load(Ordering::Relaxed),
+            ws_reconnects: self.ws_reconnects.load(Ordering::Relaxed),
+        }
+    }
+}
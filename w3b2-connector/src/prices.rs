@@ -0,0 +1,204 @@
+// File: w3b2-connector/src/prices.rs
+
+//! An in-memory cache of admin price lists.
+//!
+//! Services check the price of a command on every paid request. `PriceCache` reads
+//! each admin's price list from chain once and then keeps it up to date from the
+//! event stream, so those checks don't hit the RPC node.
+//!
+//! The cache is fed either by `spawn_watcher` or, when attached with
+//! `EventManager::with_price_cache`, by the `Dispatcher` itself, which updates it
+//! before routing each event to the listeners.
+
+use crate::events::{BridgeEvent, EventEnvelope};
+use crate::instructions::admin_profile_pda;
+use crate::reader::AccountReader;
+use dashmap::DashMap;
+use solana_client::client_error::ClientError;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use w3b2_bridge_program::state::PriceEntry;
+
+/// The pricing of one service.
+struct ServicePrices {
+    prices: Vec<PriceEntry>,
+    priority_surcharge: u64,
+}
+
+/// Caches `AdminProfile` price lists, keyed by `AdminProfile` PDA.
+pub struct PriceCache {
+    reader: AccountReader,
+    prices: DashMap<Pubkey, ServicePrices>,
+}
+
+impl PriceCache {
+    /// Creates a new, empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader used to load price lists that are not cached yet.
+    pub fn new(reader: AccountReader) -> Self {
+        Self {
+            reader,
+            prices: DashMap::new(),
+        }
+    }
+
+    /// Returns the price in lamports of `command_id` at the service `admin_pda`.
+    ///
+    /// Returns `Ok(None)` if the command is not in the price list or the admin
+    /// profile does not exist. Only the first lookup per admin hits the RPC node.
+    pub async fn get_command_price(
+        &self,
+        admin_pda: &Pubkey,
+        command_id: u16,
+    ) -> Result<Option<u64>, ClientError> {
+        Ok(self
+            .with_service(admin_pda, |service| find_price(&service.prices, command_id))
+            .await?
+            .flatten())
+    }
+
+    /// Returns what the program would charge for dispatching `command_id` to the
+    /// service `admin_pda`: the listed price, or nothing for an unlisted command,
+    /// plus the priority surcharge if `high_priority` is set.
+    ///
+    /// Returns `Ok(None)` if the admin profile does not exist.
+    pub async fn get_dispatch_price(
+        &self,
+        admin_pda: &Pubkey,
+        command_id: u16,
+        high_priority: bool,
+    ) -> Result<Option<u64>, ClientError> {
+        self.with_service(admin_pda, |service| {
+            dispatch_price(
+                &service.prices,
+                service.priority_surcharge,
+                command_id,
+                high_priority,
+            )
+        })
+        .await
+    }
+
+    /// Returns the cached price in lamports of `command_id` at the service `admin_pda`,
+    /// without ever hitting the RPC node.
+    ///
+    /// Returns `None` if the command is not in the price list or the admin is not
+    /// watched yet; `watch` or any of the async lookups start watching it.
+    pub fn price_of(&self, admin_pda: &Pubkey, command_id: u16) -> Option<u64> {
+        find_price(&self.prices.get(admin_pda)?.prices, command_id)
+    }
+
+    /// Loads the price list of `admin_pda`, if not cached yet, so that events keep it
+    /// up to date from now on.
+    ///
+    /// Returns `Ok(false)` if the admin profile does not exist.
+    pub async fn watch(&self, admin_pda: &Pubkey) -> Result<bool, ClientError> {
+        Ok(self.with_service(admin_pda, |_| ()).await?.is_some())
+    }
+
+    /// Applies `f` to the cached pricing of `admin_pda`, loading it first if needed.
+    async fn with_service<R>(
+        &self,
+        admin_pda: &Pubkey,
+        f: impl FnOnce(&ServicePrices) -> R,
+    ) -> Result<Option<R>, ClientError> {
+        if let Some(service) = self.prices.get(admin_pda) {
+            return Ok(Some(f(&service)));
+        }
+
+        let Some(profile) = self.reader.fetch_admin_profile_at(admin_pda).await? else {
+            return Ok(None);
+        };
+        let service = ServicePrices {
+            prices: profile.prices,
+            priority_surcharge: profile.priority_surcharge,
+        };
+        let result = f(&service);
+        self.prices.insert(*admin_pda, service);
+        Ok(Some(result))
+    }
+
+    /// Drops the cached price list for `admin_pda`; the next lookup reloads it from chain.
+    pub fn invalidate(&self, admin_pda: &Pubkey) {
+        self.prices.remove(admin_pda);
+    }
+
+    /// Updates the cache from an on-chain event.
+    ///
+    /// `AdminPricesUpdated` and `AdminPrioritySurchargeUpdated` update the cached
+    /// pricing, while `AdminProfileClosed` and `AdminProfileMigrated` evict it. All
+    /// other events are ignored.
+    pub fn observe(&self, event: &BridgeEvent) {
+        match event {
+            BridgeEvent::AdminPricesUpdated(e) => {
+                let admin_pda = admin_profile_pda(&e.authority);
+                // Only refresh admins somebody has asked about; others load lazily.
+                if let Some(mut service) = self.prices.get_mut(&admin_pda) {
+                    service.prices = e.new_prices.clone();
+                }
+            }
+            BridgeEvent::AdminPrioritySurchargeUpdated(e) => {
+                let admin_pda = admin_profile_pda(&e.authority);
+                if let Some(mut service) = self.prices.get_mut(&admin_pda) {
+                    service.priority_surcharge = e.surcharge;
+                }
+            }
+            BridgeEvent::AdminProfileClosed(e) => {
+                self.invalidate(&admin_profile_pda(&e.authority));
+            }
+            BridgeEvent::AdminProfileMigrated(e) => {
+                self.invalidate(&e.old_profile);
+            }
+            _ => {}
+        }
+    }
+
+    /// Spawns a background task that keeps the cache in sync with `events`.
+    ///
+    /// The task ends when the event channel is closed. If it lags behind, the whole
+    /// cache is cleared, since a price update may have been missed.
+    pub fn spawn_watcher(
+        self: &Arc<Self>,
+        mut events: broadcast::Receiver<EventEnvelope>,
+    ) -> tokio::task::JoinHandle<()> {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(envelope) => cache.observe(&envelope.event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "PriceCache lagged behind by {} events, clearing cache.",
+                            skipped
+                        );
+                        cache.prices.clear();
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+fn find_price(prices: &[PriceEntry], command_id: u16) -> Option<u64> {
+    prices
+        .iter()
+        .find(|entry| entry.command_id == command_id)
+        .map(|entry| entry.price)
+}
+
+/// Computes the price of a dispatch the way `user_dispatch_command` does.
+pub(crate) fn dispatch_price(
+    prices: &[PriceEntry],
+    priority_surcharge: u64,
+    command_id: u16,
+    high_priority: bool,
+) -> u64 {
+    let surcharge = if high_priority { priority_surcharge } else { 0 };
+    find_price(prices, command_id)
+        .unwrap_or(0)
+        .saturating_add(surcharge)
+}
@@ -0,0 +1,372 @@
+// File: w3b2-connector/src/protocol.rs
+
+//! # Off-chain Protocol Helpers
+//!
+//! Client-side tooling for the `CommandConfig` handshake defined in
+//! `w3b2_bridge_program::protocols`.
+//!
+//! A party opening a session generates a random AES-256 session key and encrypts it
+//! for the peer's `communication_pubkey`, which is an X25519 public key. The encryption
+//! uses a fresh ephemeral X25519 keypair per message:
+//!
+//! 1. `shared = X25519(ephemeral_secret, peer_comm_pubkey)`
+//! 2. `key = HKDF-SHA256(shared, info = ephemeral_pubkey || peer_comm_pubkey)`
+//! 3. `ciphertext = AES-256-GCM-SIV(key, nonce, session_key)`
+//!
+//! The resulting `encrypted_session_key` is `ephemeral_pubkey || nonce || ciphertext`.
+//! Only the holder of the secret behind `peer_comm_pubkey` can recover the session key.
+//!
+//! The same construction is exposed as `encrypt_for` / `decrypt` for arbitrary bytes,
+//! and as `encrypt_payload` / `decrypt_payload` for complete `dispatch` payloads.
+
+use aes_gcm_siv::aead::{Aead, KeyInit};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce};
+use borsh::BorshDeserialize;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+use w3b2_bridge_program::instructions::MAX_PAYLOAD_SIZE;
+use w3b2_bridge_program::protocols::{
+    CommandConfig, ConfigError, Destination, PayloadCodec, PayloadHeader, PAYLOAD_HEADER_LEN,
+};
+
+/// The length of an AES-256 session key, in bytes.
+pub const SESSION_KEY_LEN: usize = 32;
+
+/// The length of the AES-GCM-SIV nonce, in bytes.
+const NONCE_LEN: usize = 12;
+
+/// The length of the AES-GCM-SIV authentication tag, in bytes.
+const TAG_LEN: usize = 16;
+
+/// The length of an `encrypted_session_key`: ephemeral pubkey, nonce, key and tag.
+pub const ENCRYPTED_SESSION_KEY_LEN: usize = 32 + NONCE_LEN + SESSION_KEY_LEN + TAG_LEN;
+
+/// Domain separation for the HKDF salt.
+const KDF_SALT: &[u8] = b"w3b2-bridge/x25519-aes256-gcm-siv/v1";
+
+/// The reasons a protocol operation can fail.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ProtocolError {
+    #[error("Key exchange with a low-order public key")]
+    WeakPublicKey,
+
+    #[error("Encrypted data is {0} bytes, which is too short")]
+    TooShort(usize),
+
+    #[error("Decryption failed: wrong key or tampered ciphertext")]
+    DecryptionFailed,
+
+    #[error("Invalid session key length: {0}")]
+    InvalidSessionKey(usize),
+
+    #[error("Invalid payload header: {0}")]
+    InvalidHeader(String),
+
+    #[error("Unexpected payload: {0}")]
+    UnexpectedPayload(String),
+
+    #[error("Payload is {calculated_size} bytes, exceeding the {max_size} byte limit")]
+    PayloadTooLarge {
+        calculated_size: usize,
+        max_size: usize,
+    },
+
+    #[error("Borsh (de)serialization failed: {0}")]
+    Borsh(String),
+}
+
+/// An X25519 keypair used for off-chain key exchange.
+///
+/// Its public half is what gets registered on-chain as `communication_pubkey`.
+#[derive(Clone)]
+pub struct X25519Keypair {
+    secret: [u8; 32],
+    public: MontgomeryPoint,
+}
+
+impl X25519Keypair {
+    /// Generates a new random keypair.
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        Self::from_secret_bytes(secret)
+    }
+
+    /// Restores a keypair from its 32-byte secret. The secret is clamped on use.
+    pub fn from_secret_bytes(secret: [u8; 32]) -> Self {
+        Self {
+            secret,
+            public: MontgomeryPoint::mul_base_clamped(secret),
+        }
+    }
+
+    /// Returns the secret bytes, e.g. for storing the keypair.
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.secret
+    }
+
+    /// Returns the public key in the form stored on-chain as `communication_pubkey`.
+    pub fn public_key(&self) -> Pubkey {
+        Pubkey::new_from_array(self.public.to_bytes())
+    }
+
+    /// Computes the X25519 shared secret with `peer`.
+    pub fn diffie_hellman(&self, peer: &Pubkey) -> Result<[u8; 32], ProtocolError> {
+        let shared = MontgomeryPoint(peer.to_bytes()).mul_clamped(self.secret);
+        // A low-order peer key yields an all-zero secret that any attacker can compute.
+        if shared.to_bytes() == [0u8; 32] {
+            return Err(ProtocolError::WeakPublicKey);
+        }
+        Ok(shared.to_bytes())
+    }
+}
+
+impl std::fmt::Debug for X25519Keypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("X25519Keypair")
+            .field("public", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A symmetric AES-256 key protecting one off-chain session.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionKey([u8; SESSION_KEY_LEN]);
+
+impl SessionKey {
+    /// Generates a new random session key.
+    pub fn generate() -> Self {
+        let mut key = [0u8; SESSION_KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn from_bytes(bytes: [u8; SESSION_KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; SESSION_KEY_LEN] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionKey(..)")
+    }
+}
+
+/// Encrypts `session_key` so that only the owner of `peer_comm_pubkey` can read it.
+///
+/// # Returns
+/// The bytes to put into `CommandConfig::encrypted_session_key`.
+pub fn encrypt_session_key(
+    peer_comm_pubkey: &Pubkey,
+    session_key: &SessionKey,
+) -> Result<Vec<u8>, ProtocolError> {
+    encrypt_for(peer_comm_pubkey, session_key.as_bytes())
+}
+
+/// Recovers a session key encrypted for `keypair` by `encrypt_session_key`.
+pub fn decrypt_session_key(
+    keypair: &X25519Keypair,
+    encrypted_session_key: &[u8],
+) -> Result<SessionKey, ProtocolError> {
+    let key = decrypt(keypair, encrypted_session_key)?;
+    let key: [u8; SESSION_KEY_LEN] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| ProtocolError::InvalidSessionKey(key.len()))?;
+    Ok(SessionKey(key))
+}
+
+/// Builds a `CommandConfig` that opens a new session with a peer.
+#[derive(Debug, Clone)]
+pub struct CommandConfigBuilder {
+    session_id: u64,
+    destination: Destination,
+    meta: Vec<u8>,
+}
+
+impl CommandConfigBuilder {
+    /// * `session_id` - The identifier both parties will use for the session.
+    /// * `destination` - Where the peer should connect to.
+    pub fn new(session_id: u64, destination: Destination) -> Self {
+        Self {
+            session_id,
+            destination,
+            meta: Vec::new(),
+        }
+    }
+
+    /// Attaches free-form metadata to the config.
+    pub fn with_meta(mut self, meta: Vec<u8>) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    /// Generates a fresh session key, encrypts it for `peer_comm_pubkey` and assembles
+    /// the config.
+    ///
+    /// # Returns
+    /// The config to send and the plaintext session key to keep.
+    pub fn build(
+        self,
+        peer_comm_pubkey: &Pubkey,
+    ) -> Result<(CommandConfig, SessionKey), ProtocolError> {
+        let session_key = SessionKey::generate();
+        let encrypted_session_key = encrypt_session_key(peer_comm_pubkey, &session_key)?;
+        let config = CommandConfig::new(
+            self.session_id,
+            encrypted_session_key,
+            self.destination,
+            self.meta,
+        )
+        .map_err(|e| match e {
+            ConfigError::PayloadTooLarge {
+                calculated_size,
+                max_size,
+            } => ProtocolError::PayloadTooLarge {
+                calculated_size,
+                max_size,
+            },
+        })?;
+        Ok((config, session_key))
+    }
+}
+
+/// Serializes `config` into a complete `dispatch` payload, header included.
+pub fn encode_command_config(config: &CommandConfig) -> Result<Vec<u8>, ProtocolError> {
+    let body = borsh::to_vec(config).map_err(|e| ProtocolError::Borsh(e.to_string()))?;
+    let calculated_size = PAYLOAD_HEADER_LEN + body.len();
+    if calculated_size > MAX_PAYLOAD_SIZE {
+        return Err(ProtocolError::PayloadTooLarge {
+            calculated_size,
+            max_size: MAX_PAYLOAD_SIZE,
+        });
+    }
+    Ok(PayloadHeader::new(PayloadCodec::Borsh, false).wrap(&body))
+}
+
+/// Builds a `dispatch` payload whose `codec`-encoded `body` is encrypted for `peer_comm_pubkey`.
+///
+/// The header stays in the clear, with its `encrypted` flag set, so the program can
+/// still validate it.
+pub fn encrypt_payload(
+    peer_comm_pubkey: &Pubkey,
+    codec: PayloadCodec,
+    body: &[u8],
+) -> Result<Vec<u8>, ProtocolError> {
+    let sealed = encrypt_for(peer_comm_pubkey, body)?;
+    let calculated_size = PAYLOAD_HEADER_LEN + sealed.len();
+    if calculated_size > MAX_PAYLOAD_SIZE {
+        return Err(ProtocolError::PayloadTooLarge {
+            calculated_size,
+            max_size: MAX_PAYLOAD_SIZE,
+        });
+    }
+    Ok(PayloadHeader::new(codec, true).wrap(&sealed))
+}
+
+/// Parses a `dispatch` payload and decrypts its body if the header says it is encrypted.
+///
+/// # Returns
+/// The header and the plaintext body.
+pub fn decrypt_payload(
+    own_keypair: &X25519Keypair,
+    payload: &[u8],
+) -> Result<(PayloadHeader, Vec<u8>), ProtocolError> {
+    let (header, body) = PayloadHeader::parse(payload)
+        .map_err(|e| ProtocolError::InvalidHeader(format!("{:?}", e)))?;
+    let body = if header.encrypted {
+        decrypt(own_keypair, body)?
+    } else {
+        body.to_vec()
+    };
+    Ok((header, body))
+}
+
+/// Parses a `dispatch` payload produced by `encode_command_config`.
+pub fn decode_command_config(payload: &[u8]) -> Result<CommandConfig, ProtocolError> {
+    let (header, mut body) = PayloadHeader::parse(payload)
+        .map_err(|e| ProtocolError::InvalidHeader(format!("{:?}", e)))?;
+    if header.codec != PayloadCodec::Borsh || header.encrypted {
+        return Err(ProtocolError::UnexpectedPayload(format!(
+            "expected a plaintext Borsh body, got {:?} (encrypted: {})",
+            header.codec, header.encrypted
+        )));
+    }
+    let config =
+        CommandConfig::deserialize(&mut body).map_err(|e| ProtocolError::Borsh(e.to_string()))?;
+    if !body.is_empty() {
+        return Err(ProtocolError::UnexpectedPayload(format!(
+            "{} trailing bytes after CommandConfig",
+            body.len()
+        )));
+    }
+    Ok(config)
+}
+
+/// The number of bytes `encrypt_for` adds to a plaintext: ephemeral pubkey, nonce and tag.
+pub const SEALED_OVERHEAD: usize = 32 + NONCE_LEN + TAG_LEN;
+
+/// Encrypts `plaintext` so that only the owner of `peer_comm_pubkey` can read it
+/// (sealed-box style).
+///
+/// A fresh ephemeral keypair is used for every call, so the sender needs no key of
+/// its own and the output is `SEALED_OVERHEAD` bytes longer than `plaintext`.
+pub fn encrypt_for(peer_comm_pubkey: &Pubkey, plaintext: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+    let ephemeral = X25519Keypair::generate();
+    let shared = ephemeral.diffie_hellman(peer_comm_pubkey)?;
+    let cipher = cipher(&shared, &ephemeral.public_key(), peer_comm_pubkey);
+
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .expect("AES-GCM-SIV encryption of an in-memory buffer cannot fail");
+
+    let mut sealed = Vec::with_capacity(32 + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&ephemeral.public_key().to_bytes());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts data produced by `encrypt_for` for `own_keypair`'s public key.
+pub fn decrypt(own_keypair: &X25519Keypair, sealed: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+    if sealed.len() < SEALED_OVERHEAD {
+        return Err(ProtocolError::TooShort(sealed.len()));
+    }
+    let (ephemeral, rest) = sealed.split_at(32);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let ephemeral = Pubkey::try_from(ephemeral).expect("slice is 32 bytes");
+
+    let shared = own_keypair.diffie_hellman(&ephemeral)?;
+    cipher(&shared, &ephemeral, &own_keypair.public_key())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| ProtocolError::DecryptionFailed)
+}
+
+/// Derives the AES-256 key for one sealed message with HKDF-SHA256 (RFC 5869).
+///
+/// Binding both public keys into `info` ties the key to this exact sender/recipient pair.
+fn cipher(shared: &[u8; 32], ephemeral: &Pubkey, recipient: &Pubkey) -> Aes256GcmSiv {
+    let mut extract =
+        <Hmac<Sha256> as Mac>::new_from_slice(KDF_SALT).expect("HMAC takes any key size");
+    extract.update(shared);
+    let prk = extract.finalize().into_bytes();
+
+    let mut expand = <Hmac<Sha256> as Mac>::new_from_slice(&prk).expect("HMAC takes any key size");
+    expand.update(ephemeral.as_ref());
+    expand.update(recipient.as_ref());
+    expand.update(&[1]);
+    let key = expand.finalize().into_bytes();
+
+    Aes256GcmSiv::new(&key)
+}
@@ -0,0 +1,399 @@
+// File: w3b2-connector/src/reader.rs
+
+//! Typed readers for the W3B2 Bridge Program's on-chain accounts.
+//!
+//! `AccountReader` derives the PDA, fetches the raw account and Anchor-deserializes
+//! it, so consumers never have to hand-roll `get_account_data` + `try_deserialize`.
+
+use crate::instructions::{admin_profile_pda, user_profile_pda};
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::sync::Arc;
+use w3b2_bridge_program::state::{AdminProfile, UserProfile};
+
+/// The fixed-size head of an `AdminProfile`, as returned by `list_admin_profiles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdminProfileSummary {
+    /// The address of the `AdminProfile` PDA.
+    pub admin_pda: Pubkey,
+    /// The admin's `ChainCard` public key.
+    pub authority: Pubkey,
+    /// The admin's public key for off-chain key exchange.
+    pub communication_pubkey: Pubkey,
+}
+
+/// The maximum number of accounts the RPC node returns from one `getMultipleAccounts` call.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// The maximum number of profiles returned in one page, bounded by `getMultipleAccounts`.
+pub const MAX_PAGE_SIZE: usize = MAX_MULTIPLE_ACCOUNTS;
+
+/// A decoded `UserProfile` together with its address.
+#[derive(Debug)]
+pub struct UserProfileEntry {
+    /// The address of the `UserProfile` PDA.
+    pub user_pda: Pubkey,
+    /// The decoded account state.
+    pub profile: UserProfile,
+}
+
+/// One page of the user profiles linked to a service.
+#[derive(Debug)]
+pub struct UserProfilePage {
+    /// The profiles on this page, ordered by PDA.
+    pub profiles: Vec<UserProfileEntry>,
+    /// The total number of profiles linked to the service.
+    pub total: usize,
+    /// The offset of the next page, or `None` if this is the last one.
+    pub next_offset: Option<usize>,
+}
+
+/// A read-only client for fetching and decoding bridge program accounts.
+#[derive(Clone)]
+pub struct AccountReader {
+    /// A shared, thread-safe reference to the Solana JSON RPC client.
+    rpc_client: Arc<RpcClient>,
+}
+
+impl AccountReader {
+    /// Creates a new AccountReader.
+    ///
+    /// # Arguments
+    ///
+    /// * `rpc_client` - A shared `Arc<RpcClient>` for communicating with the Solana cluster.
+    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Fetches the `AdminProfile` owned by `authority`. If it was migrated to the v2
+    /// seeds, the v2 profile is returned.
+    ///
+    /// Returns `Ok(None)` if the admin has not registered a profile (or has closed it).
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The admin's `ChainCard` public key.
+    pub async fn fetch_admin_profile(
+        &self,
+        authority: &Pubkey,
+    ) -> Result<Option<AdminProfile>, ClientError> {
+        let profile = self
+            .fetch_admin_profile_at(&admin_profile_pda(authority))
+            .await?;
+        match profile.as_ref().and_then(|profile| profile.migrated_to) {
+            Some(admin_pda_v2) => self.fetch_admin_profile_at(&admin_pda_v2).await,
+            None => Ok(profile),
+        }
+    }
+
+    /// Fetches an `AdminProfile` by its PDA.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_pda` - The address of the `AdminProfile` account.
+    pub async fn fetch_admin_profile_at(
+        &self,
+        admin_pda: &Pubkey,
+    ) -> Result<Option<AdminProfile>, ClientError> {
+        self.fetch_account(admin_pda).await
+    }
+
+    /// Fetches the `UserProfile` owned by `user_authority` for the service at `admin_pda`.
+    ///
+    /// Returns `Ok(None)` if the user has no profile with that service.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_authority` - The user's `ChainCard` public key.
+    /// * `admin_pda` - The `AdminProfile` PDA the user profile is linked to.
+    pub async fn fetch_user_profile(
+        &self,
+        user_authority: &Pubkey,
+        admin_pda: &Pubkey,
+    ) -> Result<Option<UserProfile>, ClientError> {
+        self.fetch_account(&user_profile_pda(user_authority, admin_pda))
+            .await
+    }
+
+    /// Fetches many `AdminProfile`s by PDA, `MAX_MULTIPLE_ACCOUNTS` per RPC call.
+    ///
+    /// The result lines up with `admin_pdas`, with `None` for profiles that do not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_pdas` - The addresses of the `AdminProfile` accounts.
+    pub async fn fetch_admin_profiles_at(
+        &self,
+        admin_pdas: &[Pubkey],
+    ) -> Result<Vec<Option<AdminProfile>>, ClientError> {
+        self.fetch_accounts(admin_pdas).await
+    }
+
+    /// Fetches many `UserProfile`s by PDA, `MAX_MULTIPLE_ACCOUNTS` per RPC call.
+    ///
+    /// The result lines up with `user_pdas`, with `None` for profiles that do not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_pdas` - The addresses of the `UserProfile` accounts.
+    pub async fn fetch_user_profiles_at(
+        &self,
+        user_pdas: &[Pubkey],
+    ) -> Result<Vec<Option<UserProfile>>, ClientError> {
+        self.fetch_accounts(user_pdas).await
+    }
+
+    /// Returns the user's spendable deposit with the service at `admin_pda`, in lamports.
+    ///
+    /// The rent reserve is not included. A missing profile has a balance of `0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_authority` - The user's `ChainCard` public key.
+    /// * `admin_pda` - The `AdminProfile` PDA the user profile is linked to.
+    pub async fn get_deposit_balance(
+        &self,
+        user_authority: &Pubkey,
+        admin_pda: &Pubkey,
+    ) -> Result<u64, ClientError> {
+        Ok(self
+            .fetch_user_profile(user_authority, admin_pda)
+            .await?
+            .map_or(0, |profile| profile.deposit_balance))
+    }
+
+    /// Lists every registered `AdminProfile`, i.e. the directory of available services.
+    ///
+    /// Only the fixed-size `authority` and `communication_pubkey` fields are downloaded,
+    /// so this stays cheap however long the individual price lists are. Use
+    /// `fetch_admin_profile_at` for the full state of a single service.
+    pub async fn list_admin_profiles(&self) -> Result<Vec<AdminProfileSummary>, ClientError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                AdminProfile::DISCRIMINATOR.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                // Skip the discriminator and keep `authority` + `communication_pubkey`.
+                data_slice: Some(UiDataSliceConfig {
+                    offset: AdminProfile::DISCRIMINATOR.len(),
+                    length: 64,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = self
+            .rpc_client
+            .get_program_accounts_with_config(&w3b2_bridge_program::ID, config)
+            .await?;
+
+        let mut summaries = Vec::with_capacity(accounts.len());
+        for (admin_pda, account) in accounts {
+            let Some(head) = account.data.get(..64) else {
+                return Err(ClientError::from(ClientErrorKind::Custom(format!(
+                    "truncated admin profile {admin_pda}"
+                ))));
+            };
+            let (authority, communication_pubkey) = head.split_at(32);
+            summaries.push(AdminProfileSummary {
+                admin_pda,
+                authority: Pubkey::try_from(authority).expect("slice is 32 bytes"),
+                communication_pubkey: Pubkey::try_from(communication_pubkey)
+                    .expect("slice is 32 bytes"),
+            });
+        }
+        Ok(summaries)
+    }
+
+    /// Lists the `UserProfile`s linked to the service at `admin_pda`, one page at a time.
+    ///
+    /// The matching addresses are fetched first (without data) and sorted, so pages are
+    /// stable between calls as long as no profile is created or closed in between. Only
+    /// the requested page is then downloaded and decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin_pda` - The `AdminProfile` PDA the user profiles are linked to.
+    /// * `offset` - The number of profiles to skip.
+    /// * `limit` - The page size, capped at `MAX_PAGE_SIZE`.
+    pub async fn list_user_profiles_for_admin(
+        &self,
+        admin_pda: &Pubkey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<UserProfilePage, ClientError> {
+        // `admin_authority_on_creation` follows the discriminator, `authority` and `communication_pubkey`.
+        let admin_offset = UserProfile::DISCRIMINATOR.len() + 32 + 32;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    0,
+                    UserProfile::DISCRIMINATOR.to_vec(),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    admin_offset,
+                    admin_pda.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut addresses: Vec<Pubkey> = self
+            .rpc_client
+            .get_program_accounts_with_config(&w3b2_bridge_program::ID, config)
+            .await?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect();
+        addresses.sort();
+
+        let total = addresses.len();
+        let start = offset.min(total);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total);
+        let page = &addresses[start..end];
+
+        let profiles = page
+            .iter()
+            .zip(self.fetch_user_profiles_at(page).await?)
+            // The profile may have been closed since the address list was fetched.
+            .filter_map(|(user_pda, profile)| {
+                Some(UserProfileEntry {
+                    user_pda: *user_pda,
+                    profile: profile?,
+                })
+            })
+            .collect();
+
+        Ok(UserProfilePage {
+            profiles,
+            total,
+            next_offset: (end < total).then_some(end),
+        })
+    }
+
+    /// Lists every `UserProfile` owned by `user_authority`, across all services,
+    /// ordered by PDA.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_authority` - The user's `ChainCard` public key.
+    pub async fn list_user_profiles_of(
+        &self,
+        user_authority: &Pubkey,
+    ) -> Result<Vec<UserProfileEntry>, ClientError> {
+        // `authority` directly follows the discriminator.
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    0,
+                    UserProfile::DISCRIMINATOR.to_vec(),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    UserProfile::DISCRIMINATOR.len(),
+                    user_authority.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut profiles = Vec::new();
+        for (user_pda, account) in self
+            .rpc_client
+            .get_program_accounts_with_config(&w3b2_bridge_program::ID, config)
+            .await?
+        {
+            profiles.push(UserProfileEntry {
+                user_pda,
+                profile: decode_account(&user_pda, &account)?,
+            });
+        }
+        profiles.sort_by_key(|entry| entry.user_pda);
+        Ok(profiles)
+    }
+
+    /// Fetches and deserializes an Anchor account, returning `None` if it does not exist.
+    async fn fetch_account<T: AccountDeserialize>(
+        &self,
+        address: &Pubkey,
+    ) -> Result<Option<T>, ClientError> {
+        let account = self
+            .rpc_client
+            .get_account_with_commitment(address, self.rpc_client.commitment())
+            .await?
+            .value;
+
+        match account {
+            Some(account) => decode_account(address, &account).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches and deserializes many Anchor accounts with `getMultipleAccounts`,
+    /// chunked at `MAX_MULTIPLE_ACCOUNTS`, returning `None` for those that do not exist.
+    async fn fetch_accounts<T: AccountDeserialize>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, ClientError> {
+        let mut decoded = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self
+                .rpc_client
+                .get_multiple_accounts_with_commitment(chunk, self.rpc_client.commitment())
+                .await?
+                .value;
+            if accounts.len() != chunk.len() {
+                return Err(ClientError::from(ClientErrorKind::Custom(format!(
+                    "requested {} accounts, received {}",
+                    chunk.len(),
+                    accounts.len()
+                ))));
+            }
+            for (address, account) in chunk.iter().zip(accounts) {
+                decoded.push(match account {
+                    Some(account) => Some(decode_account(address, &account)?),
+                    None => None,
+                });
+            }
+        }
+        Ok(decoded)
+    }
+}
+
+/// Checks that `account` belongs to the bridge program and deserializes it.
+#[allow(clippy::result_large_err)]
+fn decode_account<T: AccountDeserialize>(
+    address: &Pubkey,
+    account: &Account,
+) -> Result<T, ClientError> {
+    if account.owner != w3b2_bridge_program::ID {
+        return Err(ClientError::from(ClientErrorKind::Custom(format!(
+            "account {address} is not owned by the bridge program"
+        ))));
+    }
+
+    T::try_deserialize(&mut account.data.as_slice()).map_err(|e| {
+        ClientError::from(ClientErrorKind::Custom(format!(
+            "failed to deserialize account {address}: {e}"
+        )))
+    })
+}
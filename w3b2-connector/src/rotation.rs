@@ -0,0 +1,337 @@
+// File: w3b2-connector/src/rotation.rs
+
+//! # Communication Key Rotation
+//!
+//! `rotate_comm_key` replaces the X25519 `communication_pubkey` of an admin or user
+//! profile without letting the chain and the local key store disagree:
+//!
+//! 1. A new keypair is generated and staged in the `CommKeyStore`, next to the
+//!    active one, so it survives a crash from here on.
+//! 2. `admin_update_comm_key` or `user_update_comm_key` is submitted and confirmed.
+//! 3. Only then is the staged key promoted and the old one retired.
+//!
+//! If the update fails, the staged key is discarded and the old key stays active.
+//! A submission error does not prove the update did not land, so before rolling
+//! back the profile is read from the chain and, if it already carries the new key,
+//! the rotation completes instead. When even that is unknown, the staged key is
+//! kept and `resolve_rotation` settles the rotation later.
+
+use crate::client::TransactionBuilder;
+use crate::instructions::{admin_profile_pda, user_profile_pda};
+use crate::protocol::X25519Keypair;
+use crate::signing::{SigningError, TransactionSigner};
+use crate::storage::StorageError;
+use anchor_lang::AccountDeserialize;
+use async_trait::async_trait;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use thiserror::Error;
+use w3b2_bridge_program::state::{AdminProfile, UserProfile};
+
+/// The profile whose communication key is rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommKeyOwner {
+    /// The `AdminProfile` of `authority`.
+    Admin { authority: Pubkey },
+    /// The `UserProfile` of `authority` with the service at `admin_profile_pda`.
+    User {
+        authority: Pubkey,
+        admin_profile_pda: Pubkey,
+    },
+}
+
+impl CommKeyOwner {
+    /// Returns the `ChainCard` that signs the update.
+    pub fn authority(&self) -> Pubkey {
+        match self {
+            Self::Admin { authority } | Self::User { authority, .. } => *authority,
+        }
+    }
+
+    /// Returns the address of the profile.
+    pub fn profile_pda(&self) -> Pubkey {
+        match self {
+            Self::Admin { authority } => admin_profile_pda(authority),
+            Self::User {
+                authority,
+                admin_profile_pda,
+            } => user_profile_pda(authority, admin_profile_pda),
+        }
+    }
+}
+
+/// Where the communication keys of one or more profiles are kept.
+///
+/// Every owner has an active key and at most one staged key awaiting promotion.
+#[async_trait]
+pub trait CommKeyStore: Send + Sync {
+    /// Stores `keypair` as the staged key of `owner`, replacing any staged before.
+    /// It must be durable once this returns.
+    async fn stage(
+        &self,
+        owner: &CommKeyOwner,
+        keypair: &X25519Keypair,
+    ) -> Result<(), StorageError>;
+
+    /// Makes the staged key of `owner` the active one and deletes the old key.
+    async fn promote(&self, owner: &CommKeyOwner) -> Result<(), StorageError>;
+
+    /// Deletes the staged key of `owner`, leaving the active one in place.
+    async fn discard(&self, owner: &CommKeyOwner) -> Result<(), StorageError>;
+}
+
+/// The reasons a rotation fails. Each says where the keys stand afterwards.
+#[derive(Error, Debug)]
+pub enum RotationError {
+    /// The new key could not be staged. Nothing changed.
+    #[error("Failed to stage the new key: {0}")]
+    Stage(#[source] StorageError),
+
+    /// The update could not be signed. The new key was discarded.
+    #[error("Failed to sign the key update: {0}")]
+    Signing(#[from] SigningError),
+
+    /// The update was not applied on-chain. The new key was discarded.
+    #[error("The key update failed: {0}")]
+    Update(#[source] Box<ClientError>),
+
+    /// Whether the update was applied is unknown. The new key stays staged until
+    /// `resolve_rotation` is called.
+    #[error("The key update to {new_key} may not have landed: {source}")]
+    Unconfirmed {
+        new_key: Pubkey,
+        #[source]
+        source: Box<ClientError>,
+    },
+
+    /// The update was applied on-chain, but the new key could not be promoted. It
+    /// stays staged; promoting it again completes the rotation.
+    #[error("The key update to {new_key} landed, but promoting it failed: {source}")]
+    Promote {
+        new_key: Pubkey,
+        #[source]
+        source: StorageError,
+    },
+}
+
+/// Rotates the communication key of `owner` and returns the new keypair.
+///
+/// * `builder` - Used to prepare and submit the update.
+/// * `signer` - The `ChainCard` of `owner`, possibly outside this process.
+/// * `store` - Holds the keys of `owner`.
+pub async fn rotate_comm_key(
+    builder: &TransactionBuilder,
+    signer: &dyn TransactionSigner,
+    owner: CommKeyOwner,
+    store: &dyn CommKeyStore,
+) -> Result<X25519Keypair, RotationError> {
+    let keypair = X25519Keypair::generate();
+    let new_key = keypair.public_key();
+    store
+        .stage(&owner, &keypair)
+        .await
+        .map_err(RotationError::Stage)?;
+
+    let mut tx = match prepare_update(builder, owner, new_key).await {
+        Ok(tx) => tx,
+        Err(e) => {
+            roll_back(store, &owner).await;
+            return Err(RotationError::Update(Box::new(e)));
+        }
+    };
+    if let Err(e) = builder.sign_with(&mut tx, signer).await {
+        roll_back(store, &owner).await;
+        return Err(e.into());
+    }
+
+    if let Err(e) = builder.submit_transaction(&tx).await {
+        tracing::warn!(
+            "Submitting the key update for {} failed, checking the chain: {}",
+            owner.profile_pda(),
+            e
+        );
+        match resolve_rotation(builder, owner, &new_key, store).await {
+            Ok(true) => return Ok(keypair),
+            Ok(false) => return Err(RotationError::Update(Box::new(e))),
+            Err(RotationError::Unconfirmed { .. }) => {
+                return Err(RotationError::Unconfirmed {
+                    new_key,
+                    source: Box::new(e),
+                })
+            }
+            Err(other) => return Err(other),
+        }
+    }
+
+    store
+        .promote(&owner)
+        .await
+        .map_err(|source| RotationError::Promote { new_key, source })?;
+    tracing::info!(
+        "Rotated the communication key of {} to {}",
+        owner.profile_pda(),
+        new_key
+    );
+    Ok(keypair)
+}
+
+/// Settles a rotation to `new_key` whose outcome is unknown, e.g. after
+/// `RotationError::Unconfirmed` or a crash, by reading the key from the chain.
+///
+/// Promotes the staged key and returns `true` if the profile carries `new_key`;
+/// otherwise discards it and returns `false`.
+pub async fn resolve_rotation(
+    builder: &TransactionBuilder,
+    owner: CommKeyOwner,
+    new_key: &Pubkey,
+    store: &dyn CommKeyStore,
+) -> Result<bool, RotationError> {
+    let on_chain =
+        fetch_comm_key(builder, owner)
+            .await
+            .map_err(|e| RotationError::Unconfirmed {
+                new_key: *new_key,
+                source: Box::new(e),
+            })?;
+    if on_chain == *new_key {
+        store
+            .promote(&owner)
+            .await
+            .map_err(|source| RotationError::Promote {
+                new_key: *new_key,
+                source,
+            })?;
+        Ok(true)
+    } else {
+        roll_back(store, &owner).await;
+        Ok(false)
+    }
+}
+
+/// Reads the `communication_pubkey` of the profile of `owner` from the chain.
+pub async fn fetch_comm_key(
+    builder: &TransactionBuilder,
+    owner: CommKeyOwner,
+) -> Result<Pubkey, ClientError> {
+    let address = match owner {
+        CommKeyOwner::Admin { authority } => builder.resolve_admin_profile(&authority).await?,
+        CommKeyOwner::User { .. } => owner.profile_pda(),
+    };
+    let account = builder.rpc_client().get_account(&address).await?;
+    let data = &mut account.data.as_slice();
+    let key = match owner {
+        CommKeyOwner::Admin { .. } => {
+            AdminProfile::try_deserialize(data).map(|p| p.communication_pubkey)
+        }
+        CommKeyOwner::User { .. } => {
+            UserProfile::try_deserialize(data).map(|p| p.communication_pubkey)
+        }
+    };
+    key.map_err(|e| {
+        ClientError::from(ClientErrorKind::Custom(format!(
+            "failed to deserialize account {}: {}",
+            address, e
+        )))
+    })
+}
+
+async fn prepare_update(
+    builder: &TransactionBuilder,
+    owner: CommKeyOwner,
+    new_key: Pubkey,
+) -> Result<Transaction, ClientError> {
+    match owner {
+        CommKeyOwner::Admin { authority } => {
+            builder
+                .prepare_admin_update_comm_key(authority, new_key)
+                .await
+        }
+        CommKeyOwner::User {
+            authority,
+            admin_profile_pda,
+        } => {
+            builder
+                .prepare_user_update_comm_key(authority, admin_profile_pda, new_key)
+                .await
+        }
+    }
+}
+
+/// Discards the staged key. A failure only leaves an unused key behind, so it is
+/// logged rather than hiding the error that caused the rollback.
+async fn roll_back(store: &dyn CommKeyStore, owner: &CommKeyOwner) {
+    if let Err(e) = store.discard(owner).await {
+        tracing::warn!(
+            "Failed to discard the staged key of {}: {}",
+            owner.profile_pda(),
+            e
+        );
+    }
+}
+
+/// The keys `MemoryCommKeyStore` holds for one owner.
+#[derive(Debug, Default)]
+struct OwnerKeys {
+    active: Option<X25519Keypair>,
+    staged: Option<X25519Keypair>,
+}
+
+/// A volatile, in-memory `CommKeyStore`, for tests and short-lived tools.
+#[derive(Debug, Default)]
+pub struct MemoryCommKeyStore {
+    keys: Mutex<HashMap<CommKeyOwner, OwnerKeys>>,
+}
+
+impl MemoryCommKeyStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the active key of `owner`, e.g. the one registered with the profile.
+    pub fn insert(&self, owner: CommKeyOwner, keypair: X25519Keypair) {
+        self.keys.lock().unwrap().entry(owner).or_default().active = Some(keypair);
+    }
+
+    /// Returns the active key of `owner`.
+    pub fn active(&self, owner: &CommKeyOwner) -> Option<X25519Keypair> {
+        self.keys.lock().unwrap().get(owner)?.active.clone()
+    }
+
+    /// Returns the staged key of `owner`.
+    pub fn staged(&self, owner: &CommKeyOwner) -> Option<X25519Keypair> {
+        self.keys.lock().unwrap().get(owner)?.staged.clone()
+    }
+}
+
+#[async_trait]
+impl CommKeyStore for MemoryCommKeyStore {
+    async fn stage(
+        &self,
+        owner: &CommKeyOwner,
+        keypair: &X25519Keypair,
+    ) -> Result<(), StorageError> {
+        self.keys.lock().unwrap().entry(*owner).or_default().staged = Some(keypair.clone());
+        Ok(())
+    }
+
+    async fn promote(&self, owner: &CommKeyOwner) -> Result<(), StorageError> {
+        let mut keys = self.keys.lock().unwrap();
+        let keys = keys.entry(*owner).or_default();
+        let staged = keys.staged.take().ok_or_else(|| {
+            StorageError::Backend(format!("no staged key for {}", owner.profile_pda()))
+        })?;
+        keys.active = Some(staged);
+        Ok(())
+    }
+
+    async fn discard(&self, owner: &CommKeyOwner) -> Result<(), StorageError> {
+        if let Some(keys) = self.keys.lock().unwrap().get_mut(owner) {
+            keys.staged = None;
+        }
+        Ok(())
+    }
+}
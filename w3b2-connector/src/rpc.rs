@@ -1,62 +1,717 @@
-//! Defines a generic RPC client trait to abstract over different client implementations.
+//! Abstracts the Solana JSON RPC calls made by the connector behind a trait, so
+//! `TransactionBuilder`, `BlockhashCache` and the synchronizer's workers can run
+//! against `MockSolanaRpc` in tests instead of a live cluster.
+//!
+//! Every `Arc<RpcClient>` coerces to `Arc<dyn SolanaRpc>`, so callers holding a
+//! real client need no changes. The live worker's WebSocket subscription is not
+//! covered: it goes through `PubsubClient`, not the JSON RPC API.
 
 use async_trait::async_trait;
-use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
-use solana_program_test::BanksClient;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{
+        RpcBlockConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+        RpcTransactionConfig,
+    },
+    rpc_request::RpcError,
+    rpc_response::{
+        Response, RpcConfirmedTransactionStatusWithSignature, RpcPrioritizationFee,
+        RpcResponseContext, RpcResult, RpcSimulateTransactionResult,
+    },
+};
 use solana_sdk::{
-    hash::Hash, signature::Signature, transaction::Transaction, transport::TransportError,
+    account::Account,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    EncodedTransactionWithStatusMeta, TransactionStatus, TransactionStatusMeta, UiConfirmedBlock,
 };
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
-/// A generic trait for a Solana RPC client.
+/// The subset of the Solana JSON RPC API used by the connector.
 ///
-/// This trait abstracts over the specific client implementation, allowing for the use of
-/// both a real `RpcClient` for live environments and a `BanksClient` for testing with
-/// `solana-program-test`.
+/// The methods mirror their namesakes on `RpcClient`; see its documentation for
+/// the exact semantics.
 #[async_trait]
-pub trait GenericRpcClient: Send + Sync {
-    /// Gets the latest blockhash.
-    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError>;
+pub trait SolanaRpc: Send + Sync {
+    /// Returns the slot that has reached the client's default commitment.
+    async fn get_slot(&self) -> ClientResult<Slot>;
+
+    /// Returns the slot that has reached the given commitment.
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot>;
+
+    /// Returns the latest blockhash.
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash>;
+
+    /// Returns whether a blockhash is still valid for new transactions.
+    async fn is_blockhash_valid(
+        &self,
+        blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<bool>;
 
-    /// Sends and confirms a transaction.
+    /// Returns the account stored at `pubkey`.
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account>;
+
+    /// Returns the signatures of transactions involving `address`, newest first.
+    async fn get_signatures_for_address_with_config(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
+    /// Returns a confirmed transaction with its status metadata.
+    async fn get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta>;
+
+    /// Returns a confirmed block, with as much transaction detail as `config` asks for.
+    async fn get_block_with_config(
+        &self,
+        slot: Slot,
+        config: RpcBlockConfig,
+    ) -> ClientResult<UiConfirmedBlock>;
+
+    /// Returns the statuses of recently processed transactions.
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    /// Returns the statuses of transactions, searching the ledger's full history.
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>>;
+
+    /// Broadcasts a signed transaction without waiting for confirmation.
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    /// Broadcasts a signed v0 transaction without waiting for confirmation.
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    /// Broadcasts a signed transaction and waits until it is confirmed.
     async fn send_and_confirm_transaction(
         &self,
         transaction: &Transaction,
-    ) -> Result<Signature, ClientError>;
+    ) -> ClientResult<Signature>;
+
+    /// Broadcasts a signed v0 transaction and waits until it is confirmed.
+    async fn send_and_confirm_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature>;
+
+    /// Simulates a transaction without submitting it.
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult>;
+
+    /// Simulates a v0 transaction without submitting it.
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult>;
+
+    /// Returns the priority fees paid in recent slots by transactions writing to
+    /// all of `addresses`.
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>>;
 }
 
 #[async_trait]
-impl GenericRpcClient for RpcClient {
-    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
+impl SolanaRpc for RpcClient {
+    async fn get_slot(&self) -> ClientResult<Slot> {
+        self.get_slot().await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        self.get_slot_with_commitment(commitment).await
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
         self.get_latest_blockhash().await
     }
 
+    async fn is_blockhash_valid(
+        &self,
+        blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<bool> {
+        self.is_blockhash_valid(blockhash, commitment).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.get_account(pubkey).await
+    }
+
+    async fn get_signatures_for_address_with_config(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.get_signatures_for_address_with_config(address, config)
+            .await
+    }
+
+    async fn get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta> {
+        self.get_transaction_with_config(signature, config).await
+    }
+
+    async fn get_block_with_config(
+        &self,
+        slot: Slot,
+        config: RpcBlockConfig,
+    ) -> ClientResult<UiConfirmedBlock> {
+        self.get_block_with_config(slot, config).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.get_signature_statuses(signatures).await
+    }
+
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.get_signature_statuses_with_history(signatures).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.send_transaction_with_config(transaction, config).await
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.send_transaction_with_config(transaction, config).await
+    }
+
     async fn send_and_confirm_transaction(
         &self,
         transaction: &Transaction,
-    ) -> Result<Signature, ClientError> {
+    ) -> ClientResult<Signature> {
         self.send_and_confirm_transaction(transaction).await
     }
+
+    async fn send_and_confirm_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_transaction(transaction).await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(transaction, config)
+            .await
+    }
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(transaction, config)
+            .await
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.get_recent_prioritization_fees(addresses).await
+    }
+}
+
+/// An in-memory `SolanaRpc` whose responses are set up by the test.
+///
+/// Unlike `RpcClient::new_mock`, it keeps state: transactions added with
+/// `add_transaction` show up in the program's signature history, statuses can be
+/// changed between calls, and send failures can be queued to exercise retry and
+/// error-mapping paths. Every call is counted, see `calls`.
+pub struct MockSolanaRpc {
+    state: Mutex<MockState>,
+}
+
+struct MockState {
+    slot: Slot,
+    /// How long `get_slot` takes to answer.
+    slot_latency: Duration,
+    finalized_slot: Slot,
+    blockhash: Hash,
+    blockhash_valid: bool,
+    accounts: HashMap<Pubkey, Account>,
+    /// The signature history, newest first. The address is not tracked.
+    history: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    /// The log messages of each transaction in `history`.
+    logs: HashMap<Signature, Vec<String>>,
+    statuses: HashMap<Signature, TransactionStatus>,
+    /// How many more times fetching each transaction fails.
+    fetch_failures: HashMap<Signature, usize>,
+    send_errors: VecDeque<ClientError>,
+    simulation: Option<RpcSimulateTransactionResult>,
+    prioritization_fees: Vec<RpcPrioritizationFee>,
+    sent: Vec<Signature>,
+    calls: HashMap<&'static str, usize>,
+}
+
+impl Default for MockSolanaRpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockSolanaRpc {
+    /// Creates a mock at slot 0 with no accounts or transactions, and a latest
+    /// blockhash that stays valid.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                slot: 0,
+                slot_latency: Duration::ZERO,
+                finalized_slot: 0,
+                blockhash: Hash::new_unique(),
+                blockhash_valid: true,
+                accounts: HashMap::new(),
+                history: Vec::new(),
+                logs: HashMap::new(),
+                statuses: HashMap::new(),
+                fetch_failures: HashMap::new(),
+                send_errors: VecDeque::new(),
+                simulation: None,
+                prioritization_fees: Vec::new(),
+                sent: Vec::new(),
+                calls: HashMap::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sets the slot returned by `get_slot`.
+    pub fn set_slot(&self, slot: Slot) {
+        self.state().slot = slot;
+    }
+
+    /// Delays every `get_slot` answer by `latency`, e.g. to hold a catch-up poll
+    /// back while the live worker goes ahead.
+    pub fn set_slot_latency(&self, latency: Duration) {
+        self.state().slot_latency = latency;
+    }
+
+    /// Sets the slot returned by `get_slot_with_commitment` for `finalized`.
+    pub fn set_finalized_slot(&self, slot: Slot) {
+        self.state().finalized_slot = slot;
+    }
+
+    /// Sets the blockhash returned by `get_latest_blockhash`.
+    pub fn set_latest_blockhash(&self, blockhash: Hash) {
+        self.state().blockhash = blockhash;
+    }
+
+    /// Sets what `is_blockhash_valid` answers for any blockhash.
+    pub fn set_blockhash_valid(&self, valid: bool) {
+        self.state().blockhash_valid = valid;
+    }
+
+    /// Stores `account` at `pubkey`.
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.state().accounts.insert(pubkey, account);
+    }
+
+    /// Appends a successful transaction to the signature history, as the newest
+    /// one, with the given log messages.
+    ///
+    /// Its encoded transaction body is empty, so ingestion filters let it through.
+    /// The transactions added at the same slot make up its block, in the order they
+    /// were added.
+    pub fn add_transaction(&self, signature: Signature, slot: Slot, logs: Vec<String>) {
+        let mut state = self.state();
+        state.history.insert(
+            0,
+            RpcConfirmedTransactionStatusWithSignature {
+                signature: signature.to_string(),
+                slot,
+                err: None,
+                memo: None,
+                block_time: None,
+                confirmation_status: None,
+            },
+        );
+        state.logs.insert(signature, logs);
+    }
+
+    /// Sets the status reported for `signature`; `None` makes it unknown again.
+    pub fn set_signature_status(&self, signature: Signature, status: Option<TransactionStatus>) {
+        let mut state = self.state();
+        match status {
+            Some(status) => state.statuses.insert(signature, status),
+            None => state.statuses.remove(&signature),
+        };
+    }
+
+    /// Makes the next `count` fetches of transaction `signature` fail, as if the
+    /// node could not serve it.
+    pub fn fail_transaction_fetches(&self, signature: Signature, count: usize) {
+        self.state().fetch_failures.insert(signature, count);
+    }
+
+    /// Makes the next send fail with `error`. Queued errors are used in order,
+    /// one per send.
+    pub fn fail_next_send(&self, error: ClientError) {
+        self.state().send_errors.push_back(error);
+    }
+
+    /// Sets the result returned by `simulate_transaction_with_config`. Without
+    /// one, simulations succeed with no logs.
+    pub fn set_simulation_result(&self, result: RpcSimulateTransactionResult) {
+        self.state().simulation = Some(result);
+    }
+
+    /// Sets the fees returned by `get_recent_prioritization_fees`, whatever the
+    /// addresses asked for.
+    pub fn set_prioritization_fees(&self, fees: Vec<RpcPrioritizationFee>) {
+        self.state().prioritization_fees = fees;
+    }
+
+    /// Returns the signatures of every transaction sent successfully, in order.
+    pub fn sent_signatures(&self) -> Vec<Signature> {
+        self.state().sent.clone()
+    }
+
+    /// Returns how many times the `SolanaRpc` method named `method` was called.
+    pub fn calls(&self, method: &str) -> usize {
+        self.state().calls.get(method).copied().unwrap_or(0)
+    }
+
+    /// Counts a call to `method` and returns the state.
+    fn call(&self, method: &'static str) -> MutexGuard<'_, MockState> {
+        let mut state = self.state();
+        *state.calls.entry(method).or_default() += 1;
+        state
+    }
+
+    // The error type is `RpcClient`'s, which the trait has to mirror.
+    #[allow(clippy::result_large_err)]
+    fn send(&self, method: &'static str, signature: Signature) -> ClientResult<Signature> {
+        let mut state = self.call(method);
+        if let Some(error) = state.send_errors.pop_front() {
+            return Err(error);
+        }
+        state.sent.push(signature);
+        Ok(signature)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn simulation(&self, method: &'static str) -> RpcResult<RpcSimulateTransactionResult> {
+        let state = self.call(method);
+        let value = state
+            .simulation
+            .clone()
+            .unwrap_or(RpcSimulateTransactionResult {
+                err: None,
+                logs: Some(Vec::new()),
+                accounts: None,
+                units_consumed: None,
+                loaded_accounts_data_size: None,
+                return_data: None,
+                inner_instructions: None,
+                replacement_blockhash: None,
+            });
+        Ok(response(state.slot, value))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn statuses(
+        &self,
+        method: &'static str,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        let state = self.call(method);
+        let value = signatures
+            .iter()
+            .map(|signature| state.statuses.get(signature).cloned())
+            .collect();
+        Ok(response(state.slot, value))
+    }
+}
+
+fn response<T>(slot: Slot, value: T) -> Response<T> {
+    Response {
+        context: RpcResponseContext {
+            slot,
+            api_version: None,
+        },
+        value,
+    }
+}
+
+/// Returns whether `error` is the node's answer to `get_account` for an address
+/// that holds no account.
+pub fn is_account_not_found(error: &ClientError) -> bool {
+    matches!(
+        error.kind(),
+        ClientErrorKind::RpcError(RpcError::ForUser(message)) if message.starts_with("AccountNotFound")
+    )
+}
+
+fn not_found(what: impl std::fmt::Display) -> ClientError {
+    ClientErrorKind::Custom(format!("{} not found", what)).into()
 }
 
 #[async_trait]
-impl GenericRpcClient for BanksClient {
-    async fn get_latest_blockhash(&self) -> Result<Hash, ClientError> {
-        let mut client = self.clone();
-        client
-            .get_latest_blockhash()
-            .await
-            .map_err(|e| ClientError::from(TransportError::Custom(e.to_string())))
+impl SolanaRpc for MockSolanaRpc {
+    async fn get_slot(&self) -> ClientResult<Slot> {
+        let latency = self.state().slot_latency;
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        Ok(self.call("get_slot").slot)
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        let state = self.call("get_slot_with_commitment");
+        Ok(if commitment.is_finalized() {
+            state.finalized_slot
+        } else {
+            state.slot
+        })
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        Ok(self.call("get_latest_blockhash").blockhash)
+    }
+
+    async fn is_blockhash_valid(
+        &self,
+        _blockhash: &Hash,
+        _commitment: CommitmentConfig,
+    ) -> ClientResult<bool> {
+        Ok(self.call("is_blockhash_valid").blockhash_valid)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        let state = self.call("get_account");
+        state
+            .accounts
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(|| RpcError::ForUser(format!("AccountNotFound: pubkey={pubkey}")).into())
+    }
+
+    async fn get_signatures_for_address_with_config(
+        &self,
+        _address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let state = self.call("get_signatures_for_address_with_config");
+        let position = |signature: Signature| {
+            state
+                .history
+                .iter()
+                .position(|s| s.signature == signature.to_string())
+        };
+        // Like the real node, an unknown `before` signature yields nothing.
+        let start = match config.before {
+            Some(before) => position(before).map_or(state.history.len(), |i| i + 1),
+            None => 0,
+        };
+        let end = config
+            .until
+            .and_then(position)
+            .unwrap_or(state.history.len())
+            .max(start);
+        let limit = config.limit.unwrap_or(1000);
+        Ok(state.history[start..end]
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        _config: RpcTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta> {
+        let mut state = self.call("get_transaction_with_config");
+        if let Some(failures @ 1..) = state.fetch_failures.get_mut(signature) {
+            *failures -= 1;
+            return Err(ClientErrorKind::Custom(format!(
+                "transaction {} is temporarily unavailable",
+                signature
+            ))
+            .into());
+        }
+        let logs = state
+            .logs
+            .get(signature)
+            .cloned()
+            .ok_or_else(|| not_found(format!("transaction {}", signature)))?;
+        let slot = state
+            .history
+            .iter()
+            .find(|s| s.signature == signature.to_string())
+            .map_or(state.slot, |s| s.slot);
+        let meta = TransactionStatusMeta {
+            log_messages: Some(logs),
+            ..Default::default()
+        };
+        Ok(EncodedConfirmedTransactionWithStatusMeta {
+            slot,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::LegacyBinary(String::new()),
+                meta: Some(meta.into()),
+                version: None,
+            },
+            block_time: None,
+        })
+    }
+
+    async fn get_block_with_config(
+        &self,
+        slot: Slot,
+        _config: RpcBlockConfig,
+    ) -> ClientResult<UiConfirmedBlock> {
+        let state = self.call("get_block_with_config");
+        let signatures: Vec<String> = state
+            .history
+            .iter()
+            .rev()
+            .filter(|s| s.slot == slot)
+            .map(|s| s.signature.clone())
+            .collect();
+        if signatures.is_empty() {
+            return Err(not_found(format!("block {}", slot)));
+        }
+        Ok(UiConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::default().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: None,
+            signatures: Some(signatures),
+            rewards: None,
+            num_reward_partitions: None,
+            block_time: None,
+            block_height: None,
+        })
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.statuses("get_signature_statuses", signatures)
+    }
+
+    async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        self.statuses("get_signature_statuses_with_history", signatures)
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        _config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.send("send_transaction_with_config", signature)
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        _config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.send("send_versioned_transaction_with_config", signature)
     }
 
     async fn send_and_confirm_transaction(
         &self,
         transaction: &Transaction,
-    ) -> Result<Signature, ClientError> {
-        let mut client = self.clone();
-        client
-            .process_transaction(transaction.clone())
-            .await
-            .unwrap();
-        Ok(transaction.signatures[0])
+    ) -> ClientResult<Signature> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.send("send_and_confirm_transaction", signature)
+    }
+
+    async fn send_and_confirm_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.send("send_and_confirm_versioned_transaction", signature)
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        _transaction: &Transaction,
+        _config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulation("simulate_transaction_with_config")
+    }
+
+    async fn simulate_versioned_transaction_with_config(
+        &self,
+        _transaction: &VersionedTransaction,
+        _config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulation("simulate_versioned_transaction_with_config")
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        _addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        Ok(self
+            .call("get_recent_prioritization_fees")
+            .prioritization_fees
+            .clone())
     }
 }
@@ -0,0 +1,319 @@
+// File: w3b2-connector/src/rpc_pool.rs
+
+//! # Multi-Endpoint RPC
+//!
+//! An `RpcPool` spreads RPC calls over several nodes. It plugs into a regular
+//! `RpcClient` as its transport, so the workers and `TransactionBuilder` keep using
+//! `Arc<RpcClient>` unchanged:
+//!
+//! - read calls are load-balanced round-robin over the healthy endpoints;
+//! - transactions are sent through the first healthy endpoint, in configured order;
+//! - a call that fails at the transport level (or hits an unhealthy node) is retried
+//!   on the next endpoint, and the failing endpoint is skipped until it recovers;
+//! - with a `RateLimit`, each endpoint only receives as many requests as its
+//!   budget allows, and callers wait for their turn instead of being rejected.
+//!   The limit can be changed while the pool is in use, see `set_rate_limit`.
+
+use crate::config::{RateLimit, Solana};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::RpcClientConfig,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    rpc_request::{RpcError, RpcRequest},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// A token bucket limiting how fast requests are sent.
+pub struct RateLimiter {
+    /// Tokens added per second.
+    rate: f64,
+    /// The bucket capacity.
+    burst: f64,
+    /// The available tokens and when they were last topped up.
+    bucket: tokio::sync::Mutex<(f64, Instant)>,
+    /// The total time callers spent waiting, in microseconds.
+    throttled_micros: AtomicU64,
+}
+
+impl RateLimiter {
+    /// Creates a limiter with a full bucket.
+    pub fn new(limit: &RateLimit) -> Self {
+        let rate = f64::from(limit.requests_per_second.max(1));
+        let burst = f64::from(limit.burst.unwrap_or(limit.requests_per_second).max(1));
+        Self {
+            rate,
+            burst,
+            bucket: tokio::sync::Mutex::new((burst, Instant::now())),
+            throttled_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// Waits until a request may be sent and takes a token for it.
+    ///
+    /// Waiting callers are served in arrival order.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        let (tokens, refilled_at) = &mut *bucket;
+        *tokens = (*tokens + refilled_at.elapsed().as_secs_f64() * self.rate).min(self.burst);
+        *refilled_at = Instant::now();
+
+        if *tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - *tokens) / self.rate);
+            tokio::time::sleep(wait).await;
+            self.throttled_micros
+                .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
+            *tokens = 1.0;
+            *refilled_at = Instant::now();
+        }
+        *tokens -= 1.0;
+    }
+
+    /// Returns the total time callers have spent waiting for a token.
+    pub fn throttled_time(&self) -> Duration {
+        Duration::from_micros(self.throttled_micros.load(Ordering::Relaxed))
+    }
+}
+
+/// A single RPC node of the pool.
+struct Endpoint {
+    url: String,
+    client: RpcClient,
+    limiter: RwLock<Option<Arc<RateLimiter>>>,
+    /// When the endpoint last failed, or `None` while it is healthy.
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, retry_after: Duration) -> bool {
+        match *self.failed_at.lock().unwrap() {
+            Some(failed_at) => failed_at.elapsed() >= retry_after,
+            None => true,
+        }
+    }
+
+    fn set_healthy(&self, healthy: bool) {
+        *self.failed_at.lock().unwrap() = (!healthy).then(Instant::now);
+    }
+
+    /// Waits for the endpoint's rate limiter, if it has one.
+    async fn acquire(&self) {
+        let limiter = self.limiter.read().unwrap().clone();
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+    }
+}
+
+struct PoolInner {
+    endpoints: Vec<Endpoint>,
+    /// The round-robin position for read calls.
+    next: AtomicUsize,
+    /// How long a failed endpoint is skipped unless a health check clears it sooner.
+    retry_after: Duration,
+}
+
+/// A set of interchangeable RPC endpoints with health tracking and failover.
+///
+/// Cloning is cheap; every clone shares the same health state.
+#[derive(Clone)]
+pub struct RpcPool {
+    inner: Arc<PoolInner>,
+}
+
+impl RpcPool {
+    /// Creates a pool over `urls`. The first URL is the preferred endpoint for
+    /// sending transactions. `rate_limit`, if any, applies to each endpoint separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `urls` is empty.
+    pub fn new(urls: Vec<String>, retry_after: Duration, rate_limit: Option<&RateLimit>) -> Self {
+        assert!(!urls.is_empty(), "an RpcPool needs at least one endpoint");
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                client: RpcClient::new(url.clone()),
+                limiter: RwLock::new(rate_limit.map(|limit| Arc::new(RateLimiter::new(limit)))),
+                url,
+                failed_at: Mutex::new(None),
+            })
+            .collect();
+        Self {
+            inner: Arc::new(PoolInner {
+                endpoints,
+                next: AtomicUsize::new(0),
+                retry_after,
+            }),
+        }
+    }
+
+    /// Creates a pool over every RPC endpoint in `solana`.
+    pub fn from_config(solana: &Solana) -> Self {
+        Self::new(
+            solana.rpc_urls(),
+            Duration::from_secs(solana.health_check_interval_secs),
+            solana.rate_limit.as_ref(),
+        )
+    }
+
+    /// Returns an `RpcClient` whose calls go through this pool.
+    pub fn rpc_client(&self, commitment: CommitmentConfig) -> RpcClient {
+        RpcClient::new_sender(self.clone(), RpcClientConfig::with_commitment(commitment))
+    }
+
+    /// Replaces the rate limit of every endpoint, or lifts it with `None`. Requests
+    /// already waiting for a token finish under the old limit; the new limiters
+    /// start with a full bucket.
+    pub fn set_rate_limit(&self, rate_limit: Option<&RateLimit>) {
+        for endpoint in &self.inner.endpoints {
+            *endpoint.limiter.write().unwrap() =
+                rate_limit.map(|limit| Arc::new(RateLimiter::new(limit)));
+        }
+    }
+
+    /// Returns the URLs of the endpoints currently considered healthy.
+    pub fn healthy_urls(&self) -> Vec<String> {
+        self.inner
+            .endpoints
+            .iter()
+            .filter(|e| e.is_healthy(self.inner.retry_after))
+            .map(|e| e.url.clone())
+            .collect()
+    }
+
+    /// Calls `getHealth` on every endpoint and records the outcome.
+    /// Returns the number of healthy endpoints.
+    pub async fn check_health(&self) -> usize {
+        let mut healthy = 0;
+        for endpoint in &self.inner.endpoints {
+            endpoint.acquire().await;
+            match endpoint.client.get_health().await {
+                Ok(()) => {
+                    endpoint.set_healthy(true);
+                    healthy += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "RPC endpoint {} failed its health check: {}",
+                        endpoint.url,
+                        e
+                    );
+                    endpoint.set_healthy(false);
+                }
+            }
+        }
+        healthy
+    }
+
+    /// Runs `check_health` every `interval`, forever. Meant to be spawned.
+    pub async fn run_health_checks(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if self.check_health().await == 0 {
+                tracing::error!("No healthy RPC endpoint left.");
+            }
+        }
+    }
+
+    /// Returns the order in which endpoints are tried for `request`: healthy ones
+    /// first, then the others as a last resort.
+    fn order(&self, request: RpcRequest) -> Vec<usize> {
+        let count = self.inner.endpoints.len();
+        let start = if request == RpcRequest::SendTransaction {
+            0
+        } else {
+            self.inner.next.fetch_add(1, Ordering::Relaxed) % count
+        };
+        let (healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|i| (start + i) % count)
+            .partition(|&i| self.inner.endpoints[i].is_healthy(self.inner.retry_after));
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+}
+
+/// Returns whether `err` says something about the endpoint rather than the request.
+fn is_endpoint_failure(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) | ClientErrorKind::Middleware(_) => {
+            true
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl RpcSender for RpcPool {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ClientError> {
+        let mut last_err = None;
+        for i in self.order(request) {
+            let endpoint = &self.inner.endpoints[i];
+            endpoint.acquire().await;
+            match endpoint
+                .client
+                .send::<serde_json::Value>(request, params.clone())
+                .await
+            {
+                Ok(value) => {
+                    endpoint.set_healthy(true);
+                    return Ok(value);
+                }
+                Err(e) if is_endpoint_failure(&e) => {
+                    tracing::warn!("RPC endpoint {} failed, failing over: {}", endpoint.url, e);
+                    endpoint.set_healthy(false);
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err.expect("the pool has at least one endpoint"))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        let mut stats = RpcTransportStats::default();
+        for endpoint in &self.inner.endpoints {
+            let endpoint_stats = endpoint.client.get_transport_stats();
+            stats.request_count += endpoint_stats.request_count;
+            stats.elapsed_time += endpoint_stats.elapsed_time;
+            stats.rate_limited_time += endpoint_stats.rate_limited_time;
+            if let Some(limiter) = &*endpoint.limiter.read().unwrap() {
+                stats.elapsed_time += limiter.throttled_time();
+                stats.rate_limited_time += limiter.throttled_time();
+            }
+        }
+        stats
+    }
+
+    fn url(&self) -> String {
+        self.inner.endpoints[0].url.clone()
+    }
+}
+
+/// Connects to the first WebSocket endpoint in `solana` that accepts a connection.
+pub(crate) async fn connect_pubsub(solana: &Solana) -> Result<PubsubClient> {
+    let mut last_err = None;
+    for url in solana.ws_urls() {
+        match PubsubClient::new(&url).await {
+            Ok(client) => return Ok(client),
+            Err(e) => {
+                tracing::warn!("WebSocket endpoint {} is unavailable: {}", url, e);
+                last_err = Some(e);
+            }
+        }
+    }
+    Err(last_err.map_or_else(|| anyhow!("no WebSocket endpoint configured"), Into::into))
+}
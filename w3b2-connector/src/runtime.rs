@@ -0,0 +1,231 @@
+// File: w3b2-connector/src/runtime.rs
+
+//! # Service Runtime
+//!
+//! A high-level harness for building a service on top of the bridge. The
+//! `ServiceRuntime` listens for `UserCommandDispatched` events addressed to the
+//! service's `AdminProfile`, decodes their payload envelope, and hands them to the
+//! `CommandHandler` registered for the command's `command_id`.
+//!
+//! Whatever the handler asks for in its `CommandResponse` — a reply via
+//! `admin_dispatch_command`, an audit entry via `log_action`, or both — is signed
+//! with the service's key and submitted as a single atomic transaction.
+
+use crate::client::TransactionBuilder;
+use crate::codec::{CodecError, CodecRegistry};
+use crate::instructions::{admin_profile_pda, user_profile_pda};
+use crate::listener::BridgeEvent;
+use crate::prices::PriceCache;
+use crate::workers::EventManagerHandle;
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use std::collections::HashMap;
+use std::sync::Arc;
+use w3b2_bridge_program::events::UserCommandDispatched;
+use w3b2_bridge_program::protocols::PayloadHeader;
+
+/// Everything a handler needs to process one incoming command.
+#[derive(Debug, Clone)]
+pub struct CommandContext {
+    /// The on-chain event, including the raw payload and the price paid.
+    pub command: UserCommandDispatched,
+    /// The `UserProfile` PDA of the sender, used to address a reply.
+    pub user_profile_pda: Pubkey,
+    /// The payload envelope header, or `None` for an empty payload.
+    pub header: Option<PayloadHeader>,
+    /// The payload with the envelope header stripped.
+    pub body: Vec<u8>,
+    /// The price the command is listed at now, if the runtime has a `PriceCache`
+    /// and the command is listed. It differs from `command.price_paid` if the
+    /// admin changed prices since the user dispatched the command.
+    pub listed_price: Option<u64>,
+}
+
+impl CommandContext {
+    /// Decodes the body with the codec named in its header.
+    ///
+    /// Fails for empty payloads and for encrypted bodies, which the handler has to
+    /// decrypt itself before calling `CodecRegistry::decode_body`.
+    pub fn decode<T>(&self, registry: &CodecRegistry<T>) -> Result<T, CodecError> {
+        let Some(header) = self.header else {
+            return Err(CodecError::Decode("empty payload".to_string()));
+        };
+        if header.encrypted {
+            return Err(CodecError::Encrypted);
+        }
+        registry.decode_body(header.codec, &self.body)
+    }
+}
+
+/// A reply sent back to the user via `admin_dispatch_command`.
+#[derive(Debug, Clone)]
+pub struct CommandReply {
+    pub command_id: u64,
+    /// Lamports refunded from the admin's balance into the user's deposit.
+    pub rebate: u64,
+    /// The reply payload. Must carry a valid `PayloadHeader` unless empty.
+    pub payload: Vec<u8>,
+}
+
+/// An audit entry recorded via `log_action`.
+#[derive(Debug, Clone, Copy)]
+pub struct ActionLog {
+    pub session_id: u64,
+    pub action_code: u16,
+}
+
+/// What the runtime should do on-chain after a handler has run.
+#[derive(Debug, Clone, Default)]
+pub struct CommandResponse {
+    pub reply: Option<CommandReply>,
+    pub log: Option<ActionLog>,
+}
+
+/// Business logic for a single `command_id`.
+#[async_trait]
+pub trait CommandHandler: Send + Sync {
+    /// Processes one command. An `Err` is logged and nothing is submitted on-chain.
+    async fn handle(&self, ctx: CommandContext) -> Result<CommandResponse>;
+}
+
+/// Routes a service's incoming commands to their `CommandHandler`s.
+pub struct ServiceRuntime {
+    builder: TransactionBuilder,
+    signer: Arc<dyn Signer + Send + Sync>,
+    handlers: HashMap<u16, Arc<dyn CommandHandler>>,
+    channel_capacity: usize,
+    price_cache: Option<Arc<PriceCache>>,
+}
+
+impl ServiceRuntime {
+    /// Creates a runtime for the service owned by `signer`.
+    ///
+    /// * `builder` - Used to prepare and submit reply transactions.
+    /// * `signer` - The admin's `ChainCard`, which signs every reply.
+    pub fn new(builder: TransactionBuilder, signer: Arc<dyn Signer + Send + Sync>) -> Self {
+        Self {
+            builder,
+            signer,
+            handlers: HashMap::new(),
+            channel_capacity: 100,
+            price_cache: None,
+        }
+    }
+
+    /// Sets the buffer capacity of the underlying `AdminListener` channels.
+    pub fn with_channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity;
+        self
+    }
+
+    /// Fills `CommandContext::listed_price` from `cache`, which should be kept up to
+    /// date with `EventManager::with_price_cache` or `PriceCache::spawn_watcher`.
+    pub fn with_price_cache(mut self, cache: Arc<PriceCache>) -> Self {
+        self.price_cache = Some(cache);
+        self
+    }
+
+    /// Registers `handler` for `command_id`, replacing any previous one.
+    pub fn register<H: CommandHandler + 'static>(mut self, command_id: u16, handler: H) -> Self {
+        self.handlers.insert(command_id, Arc::new(handler));
+        self
+    }
+
+    /// Listens for commands on `handle` until the event stream ends.
+    ///
+    /// Commands are processed one at a time, in the order they were observed.
+    pub async fn run(self, handle: EventManagerHandle) -> Result<()> {
+        let authority = self.signer.pubkey();
+        if let Some(cache) = &self.price_cache {
+            if let Err(e) = cache.watch(&admin_profile_pda(&authority)).await {
+                tracing::warn!("ServiceRuntime could not load its price list: {}", e);
+            }
+        }
+        let mut listener = handle
+            .listen_as_admin(authority, self.channel_capacity)
+            .await?;
+        tracing::info!("ServiceRuntime started for admin {}.", authority);
+
+        while let Some(envelope) = listener.incoming_user_commands().recv().await {
+            let BridgeEvent::UserCommandDispatched(command) = envelope.event else {
+                continue;
+            };
+            if let Err(e) = self.handle_command(command).await {
+                tracing::error!("ServiceRuntime failed to process a command: {}", e);
+            }
+        }
+
+        tracing::info!("ServiceRuntime for admin {} stopped.", authority);
+        Ok(())
+    }
+
+    /// Processes one command and returns the signature of the response transaction,
+    /// if one was submitted.
+    pub async fn handle_command(
+        &self,
+        command: UserCommandDispatched,
+    ) -> Result<Option<Signature>> {
+        let Some(handler) = self.handlers.get(&command.command_id) else {
+            tracing::warn!(
+                "No handler registered for command {} from {}.",
+                command.command_id,
+                command.sender
+            );
+            return Ok(None);
+        };
+
+        let (header, body) = if command.payload.is_empty() {
+            (None, Vec::new())
+        } else {
+            let (header, body) = PayloadHeader::parse(&command.payload)
+                .map_err(|e| anyhow::anyhow!("malformed payload header: {:?}", e))?;
+            (Some(header), body.to_vec())
+        };
+
+        let authority = self.signer.pubkey();
+        let admin_pda = admin_profile_pda(&authority);
+        let listed_price = self
+            .price_cache
+            .as_ref()
+            .and_then(|cache| cache.price_of(&admin_pda, command.command_id));
+        let ctx = CommandContext {
+            user_profile_pda: user_profile_pda(&command.sender, &admin_pda),
+            command,
+            header,
+            body,
+            listed_price,
+        };
+        let response = handler.handle(ctx.clone()).await?;
+
+        let mut batch = self.builder.batch(authority);
+        if let Some(reply) = response.reply {
+            batch = batch.admin_dispatch_command(
+                ctx.user_profile_pda,
+                reply.command_id,
+                reply.rebate,
+                reply.payload,
+            );
+        }
+        if let Some(log) = response.log {
+            batch = batch.log_action(log.session_id, log.action_code);
+        }
+        if batch.instructions().is_empty() {
+            return Ok(None);
+        }
+
+        let mut tx = batch.build().await?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_sign(&[self.signer.as_ref()], blockhash)?;
+        let signature = self.builder.submit_transaction(&tx).await?;
+        tracing::info!(
+            "ServiceRuntime responded to command {} from {}: {}",
+            ctx.command.command_id,
+            ctx.command.sender,
+            signature
+        );
+        Ok(Some(signature))
+    }
+}
@@ -0,0 +1,126 @@
+// File: w3b2-connector/src/schema.rs
+
+//! # Event Schema Versions
+//!
+//! `BridgeEvent` follows the event layouts of the current program. Earlier program
+//! versions emitted some events with fewer fields, and their transactions stay in
+//! the history forever. The shims here decode those older layouts and upgrade them
+//! to the current one, filling in the value the missing fields had before they
+//! existed, so a connector can read old history without a re-index.
+//!
+//! | Version | Changes                                                              |
+//! |---------|----------------------------------------------------------------------|
+//! | 1       | The original layouts.                                                |
+//! | 2       | `UserCommandDispatched` gains `high_priority` and `priority_fee`,    |
+//! |         | `AdminCommandDispatched` gains `rebate`, and `UserFundsDeposited`    |
+//! |         | and `UserFundsWithdrawn` gain `rent_reserve`.                        |
+//!
+//! Each event is upgraded on its own, so history from a deployment that picked up
+//! only some of these changes decodes as well.
+
+use crate::events::BridgeEvent;
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::events as OnChainEvent;
+
+/// The schema version `BridgeEvent` follows.
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+/// The version 1 layout of `UserCommandDispatched`.
+#[derive(BorshDeserialize)]
+struct UserCommandDispatchedV1 {
+    sender: Pubkey,
+    target_admin_authority: Pubkey,
+    command_id: u16,
+    price_paid: u64,
+    payload: Vec<u8>,
+    ts: i64,
+}
+
+/// The version 1 layout of `AdminCommandDispatched`.
+#[derive(BorshDeserialize)]
+struct AdminCommandDispatchedV1 {
+    sender: Pubkey,
+    target_user_authority: Pubkey,
+    command_id: u64,
+    payload: Vec<u8>,
+    ts: i64,
+}
+
+/// The version 1 layout of `UserFundsDeposited`.
+#[derive(BorshDeserialize)]
+struct UserFundsDepositedV1 {
+    authority: Pubkey,
+    amount: u64,
+    new_deposit_balance: u64,
+    ts: i64,
+}
+
+/// The version 1 layout of `UserFundsWithdrawn`.
+#[derive(BorshDeserialize)]
+struct UserFundsWithdrawnV1 {
+    authority: Pubkey,
+    amount: u64,
+    destination: Pubkey,
+    new_deposit_balance: u64,
+    ts: i64,
+}
+
+/// Decodes the body of the event `name` in an older layout and upgrades it.
+///
+/// Returns the event and the schema version it was decoded as, or `None` if the
+/// body matches no older layout of the event.
+pub(crate) fn decode_legacy(name: &str, body: &[u8]) -> Option<(BridgeEvent, u32)> {
+    let event = match name {
+        "UserCommandDispatched" => {
+            let e = UserCommandDispatchedV1::try_from_slice(body).ok()?;
+            // Before the surcharge existed, every command was a normal-priority one.
+            BridgeEvent::UserCommandDispatched(OnChainEvent::UserCommandDispatched {
+                sender: e.sender,
+                target_admin_authority: e.target_admin_authority,
+                command_id: e.command_id,
+                price_paid: e.price_paid,
+                high_priority: false,
+                priority_fee: 0,
+                payload: e.payload,
+                ts: e.ts,
+            })
+        }
+        "AdminCommandDispatched" => {
+            let e = AdminCommandDispatchedV1::try_from_slice(body).ok()?;
+            BridgeEvent::AdminCommandDispatched(OnChainEvent::AdminCommandDispatched {
+                sender: e.sender,
+                target_user_authority: e.target_user_authority,
+                command_id: e.command_id,
+                rebate: 0,
+                payload: e.payload,
+                ts: e.ts,
+            })
+        }
+        // The rent reserve was part of the deposit balance before it was tracked,
+        // so nothing was reserved separately.
+        "UserFundsDeposited" => {
+            let e = UserFundsDepositedV1::try_from_slice(body).ok()?;
+            BridgeEvent::UserFundsDeposited(OnChainEvent::UserFundsDeposited {
+                authority: e.authority,
+                amount: e.amount,
+                new_deposit_balance: e.new_deposit_balance,
+                rent_reserve: 0,
+                ts: e.ts,
+            })
+        }
+        "UserFundsWithdrawn" => {
+            let e = UserFundsWithdrawnV1::try_from_slice(body).ok()?;
+            BridgeEvent::UserFundsWithdrawn(OnChainEvent::UserFundsWithdrawn {
+                authority: e.authority,
+                amount: e.amount,
+                destination: e.destination,
+                new_deposit_balance: e.new_deposit_balance,
+                rent_reserve: 0,
+                ts: e.ts,
+            })
+        }
+        _ => return None,
+    };
+    Some((event, 1))
+}
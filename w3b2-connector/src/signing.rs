@@ -0,0 +1,163 @@
+// File: w3b2-connector/src/signing.rs
+
+//! # Multi-Signer Transactions
+//!
+//! Helpers for transactions that are signed by more than one party, each on their
+//! own device. A typical flow is sponsor-pays-fees: the service prepares a
+//! transaction with itself as fee payer (e.g. with `TransactionBuilder::batch`)
+//! and signs it with `partial_sign`, the user's wallet adds its signature, and the
+//! results are combined with `merge_signatures` or `add_signature` before
+//! submission.
+//!
+//! Every signature taken from outside is verified against the message, so a
+//! mismatched or tampered transaction is rejected before it reaches the network.
+//!
+//! Keys that cannot sign in-process, such as hardware wallets or air-gapped
+//! machines, implement `TransactionSigner`. `TransactionBuilder::sign_with` signs
+//! with one between preparing and submitting a transaction.
+
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::Transaction;
+use thiserror::Error;
+
+/// The reasons a signature cannot be added to a transaction.
+#[derive(Error, Debug)]
+pub enum SigningError {
+    #[error("{0} is not a required signer of the transaction")]
+    NotASigner(Pubkey),
+
+    #[error("The signature of {0} does not match the transaction")]
+    InvalidSignature(Pubkey),
+
+    #[error("The transactions have different messages")]
+    MessageMismatch,
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+}
+
+/// A key that signs transaction messages, possibly outside this process.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Returns the public key the signatures are made with.
+    fn signer_pubkey(&self) -> Pubkey;
+
+    /// Signs the serialized transaction `message`, e.g. by handing it to a hardware
+    /// wallet and waiting for the user to confirm.
+    async fn sign(&self, message: &[u8]) -> Result<Signature, SigningError>;
+}
+
+/// Every in-process `Signer`, such as a `Keypair`, is a `TransactionSigner`.
+#[async_trait]
+impl<T: Signer + Send + Sync> TransactionSigner for T {
+    fn signer_pubkey(&self) -> Pubkey {
+        self.pubkey()
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        Ok(self.try_sign_message(message)?)
+    }
+}
+
+/// Signs `transaction` with `signer` and adds the verified signature.
+pub async fn sign_with(
+    transaction: &mut Transaction,
+    signer: &dyn TransactionSigner,
+) -> Result<(), SigningError> {
+    let signature = signer.sign(&transaction.message_data()).await?;
+    add_signature(transaction, &signer.signer_pubkey(), signature)
+}
+
+/// Returns the accounts that must sign `transaction`, fee payer first.
+pub fn required_signers(transaction: &Transaction) -> &[Pubkey] {
+    let count = usize::from(transaction.message.header.num_required_signatures);
+    &transaction.message.account_keys[..count.min(transaction.message.account_keys.len())]
+}
+
+/// Returns the required signers that have not signed `transaction` yet.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    required_signers(transaction)
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+/// Returns `true` if every required signer has signed `transaction`.
+pub fn is_fully_signed(transaction: &Transaction) -> bool {
+    missing_signers(transaction).is_empty()
+}
+
+/// Signs `transaction` with `signers`, leaving the other signatures as they are.
+///
+/// The transaction keeps its blockhash, so signatures made elsewhere stay valid.
+pub fn partial_sign(
+    transaction: &mut Transaction,
+    signers: &[&dyn Signer],
+) -> Result<(), SigningError> {
+    let blockhash = transaction.message.recent_blockhash;
+    transaction.try_partial_sign(&signers.to_vec(), blockhash)?;
+    Ok(())
+}
+
+/// Adds a signature made elsewhere, e.g. by a browser wallet, after checking that
+/// `pubkey` is a required signer and that the signature matches the message.
+pub fn add_signature(
+    transaction: &mut Transaction,
+    pubkey: &Pubkey,
+    signature: Signature,
+) -> Result<(), SigningError> {
+    let position = required_signers(transaction)
+        .iter()
+        .position(|signer| signer == pubkey)
+        .ok_or(SigningError::NotASigner(*pubkey))?;
+    if !signature.verify(pubkey.as_ref(), &transaction.message_data()) {
+        return Err(SigningError::InvalidSignature(*pubkey));
+    }
+    transaction.signatures[position] = signature;
+    Ok(())
+}
+
+/// Copies the signatures of `other`, a copy of `transaction` signed by other
+/// parties, into `transaction`. Returns the number of signatures added.
+///
+/// Both must carry the same message. Signatures already in `transaction` are kept.
+pub fn merge_signatures(
+    transaction: &mut Transaction,
+    other: &Transaction,
+) -> Result<usize, SigningError> {
+    if transaction.message != other.message {
+        return Err(SigningError::MessageMismatch);
+    }
+    let signers = required_signers(other).to_vec();
+    let mut added = 0;
+    for (position, (pubkey, signature)) in signers.iter().zip(&other.signatures).enumerate() {
+        if *signature == Signature::default()
+            || transaction.signatures[position] != Signature::default()
+        {
+            continue;
+        }
+        add_signature(transaction, pubkey, *signature)?;
+        added += 1;
+    }
+    Ok(added)
+}
+
+/// Checks every signature present on `transaction`. Missing signatures are not
+/// an error; see `is_fully_signed`.
+pub fn verify_signatures(transaction: &Transaction) -> Result<(), SigningError> {
+    let message = transaction.message_data();
+    for (pubkey, signature) in required_signers(transaction)
+        .iter()
+        .zip(&transaction.signatures)
+    {
+        if *signature != Signature::default() && !signature.verify(pubkey.as_ref(), &message) {
+            return Err(SigningError::InvalidSignature(*pubkey));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,393 @@
+// File: w3b2-connector/src/sink.rs
+
+//! # Event Sinks
+//!
+//! An `EventSink` receives every published event, to hand it on to external
+//! infrastructure such as a message broker. `forward` drives a sink from the
+//! synchronizer's event stream, e.g. `EventManagerHandle::subscribe_all`.
+//!
+//! Brokers built around topics and keyed records can use `SinkRecord` for a
+//! common layout: one topic per `EventCategory` under a configurable prefix,
+//! keyed by the authority that initiated the event, so all events of one
+//! authority stay in order within a partition.
+//!
+//! Brokers built around subject hierarchies, such as NATS, can use `subject`,
+//! which puts the admin ahead of the event type so that consumers can subscribe
+//! to one service with a wildcard. `message_id` identifies an event for brokers
+//! that discard duplicate publishes, e.g. through JetStream's `Nats-Msg-Id`;
+//! together with `RetryingSink` that gives at-least-once delivery without
+//! duplicates on the consuming side.
+//!
+//! With the `kafka` feature, `KafkaSink` publishes `SinkRecord`s to Kafka. With
+//! the `nats` feature, `NatsSink` publishes to NATS JetStream by `subject`.
+
+use crate::backoff::Backoff;
+use crate::events::{BridgeEvent, EventEnvelope, EventKind};
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+/// The reasons an event cannot be delivered to a sink.
+#[derive(Error, Debug)]
+pub enum SinkError {
+    #[error("Failed to encode event: {0}")]
+    Encode(String),
+
+    #[error("Failed to publish event: {0}")]
+    Publish(String),
+}
+
+/// A destination for published events.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Delivers one event. Events are delivered in publishing order, one at a time.
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), SinkError>;
+
+    /// Waits until every delivered event has been handed off, e.g. before shutdown.
+    async fn flush(&self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+/// A coarse grouping of event kinds, one topic each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    /// Changes to an `AdminProfile`.
+    Admin,
+    /// Changes to a `UserProfile`.
+    User,
+    /// Commands dispatched in either direction.
+    Command,
+    /// Off-chain actions logged on-chain.
+    Action,
+    /// Events this connector cannot decode.
+    Unknown,
+}
+
+impl EventCategory {
+    /// Returns the category of `kind`.
+    pub fn of(kind: EventKind) -> Self {
+        match kind {
+            EventKind::AdminCommandDispatched | EventKind::UserCommandDispatched => Self::Command,
+            EventKind::AdminProfileRegistered
+            | EventKind::AdminCommKeyUpdated
+            | EventKind::AdminPricesUpdated
+            | EventKind::AdminGcPolicyUpdated
+            | EventKind::AdminPrioritySurchargeUpdated
+            | EventKind::AdminFundsWithdrawn
+            | EventKind::AdminProfileClosed
+            | EventKind::AdminProfileMigrated => Self::Admin,
+            EventKind::UserProfileCreated
+            | EventKind::UserCommKeyUpdated
+            | EventKind::UserFundsDeposited
+            | EventKind::UserFundsWithdrawn
+            | EventKind::UserProfileClosed => Self::User,
+            EventKind::OffChainActionLogged => Self::Action,
+            EventKind::Unknown => Self::Unknown,
+        }
+    }
+
+    /// Returns the lowercase name used in topic names.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::User => "user",
+            Self::Command => "command",
+            Self::Action => "action",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// An event laid out as a keyed record on a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkRecord {
+    /// `<prefix>.<category>`, e.g. `w3b2.events.command`.
+    pub topic: String,
+    /// The authority that initiated the event; `None` for unknown events.
+    pub key: Option<Pubkey>,
+    /// The encoded envelope.
+    pub payload: Vec<u8>,
+}
+
+impl SinkRecord {
+    /// Lays out `envelope` with an already encoded `payload`.
+    pub fn new(topic_prefix: &str, envelope: &EventEnvelope, payload: Vec<u8>) -> Self {
+        let category = EventCategory::of(envelope.event.kind());
+        Self {
+            topic: format!("{}.{}", topic_prefix, category.as_str()),
+            key: envelope.pubkeys().first().copied(),
+            payload,
+        }
+    }
+
+    /// Lays out `envelope` with the envelope encoded as JSON.
+    #[cfg(feature = "serde")]
+    pub fn json(topic_prefix: &str, envelope: &EventEnvelope) -> Result<Self, SinkError> {
+        let payload = serde_json::to_vec(envelope).map_err(|e| SinkError::Encode(e.to_string()))?;
+        Ok(Self::new(topic_prefix, envelope, payload))
+    }
+}
+
+/// Returns the subject for `envelope`: `<prefix>.<admin>.<event_type>`, e.g.
+/// `w3b2.events.<admin pubkey>.UserCommandDispatched`.
+///
+/// The admin is the service the event concerns. Events that do not name one,
+/// such as user deposits or off-chain actions, use `_` in its place.
+pub fn subject(prefix: &str, envelope: &EventEnvelope) -> String {
+    let admin = event_admin(&envelope.event).map_or_else(|| "_".to_string(), |a| a.to_string());
+    format!("{}.{}.{:?}", prefix, admin, envelope.event.kind())
+}
+
+/// Returns an id unique to `envelope`, `<signature>:<index>`.
+///
+/// The signature alone is not enough, as one transaction may emit several events.
+pub fn message_id(envelope: &EventEnvelope) -> String {
+    format!("{}:{}", envelope.signature, envelope.index)
+}
+
+fn event_admin(event: &BridgeEvent) -> Option<Pubkey> {
+    match event {
+        BridgeEvent::AdminProfileRegistered(e) => Some(e.authority),
+        BridgeEvent::AdminCommKeyUpdated(e) => Some(e.authority),
+        BridgeEvent::AdminPricesUpdated(e) => Some(e.authority),
+        BridgeEvent::AdminGcPolicyUpdated(e) => Some(e.authority),
+        BridgeEvent::AdminPrioritySurchargeUpdated(e) => Some(e.authority),
+        BridgeEvent::AdminFundsWithdrawn(e) => Some(e.authority),
+        BridgeEvent::AdminProfileClosed(e) => Some(e.authority),
+        BridgeEvent::AdminProfileMigrated(e) => Some(e.authority),
+        BridgeEvent::AdminCommandDispatched(e) => Some(e.sender),
+        BridgeEvent::UserProfileCreated(e) => Some(e.target_admin),
+        BridgeEvent::UserCommandDispatched(e) => Some(e.target_admin_authority),
+        BridgeEvent::UserCommKeyUpdated(_)
+        | BridgeEvent::UserFundsDeposited(_)
+        | BridgeEvent::UserFundsWithdrawn(_)
+        | BridgeEvent::UserProfileClosed(_)
+        | BridgeEvent::OffChainActionLogged(_)
+        | BridgeEvent::Unknown { .. } => None,
+    }
+}
+
+/// Wraps a sink to retry failed publishes with a jittered backoff.
+///
+/// Only `SinkError::Publish` is retried; an event that cannot be encoded will not
+/// encode on the next attempt either.
+pub struct RetryingSink<S> {
+    inner: S,
+    max_attempts: u32,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<S: EventSink> RetryingSink<S> {
+    /// Wraps `inner`, trying every publish at most `max_attempts` times with a
+    /// backoff from 100 ms up to 5 s.
+    pub fn new(inner: S, max_attempts: u32) -> Self {
+        Self {
+            inner,
+            max_attempts: max_attempts.max(1),
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the bounds of the backoff between attempts.
+    pub fn with_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Returns the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<S: EventSink> EventSink for RetryingSink<S> {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), SinkError> {
+        let mut backoff = Backoff::new(self.min_backoff, self.max_backoff);
+        loop {
+            match self.inner.publish(envelope).await {
+                Err(SinkError::Publish(e)) if backoff.attempts() + 1 < self.max_attempts => {
+                    let delay = backoff.next_delay();
+                    tracing::warn!(
+                        "Failed to publish event {}, retrying in {:?}: {}",
+                        message_id(envelope),
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn flush(&self) -> Result<(), SinkError> {
+        self.inner.flush().await
+    }
+}
+
+/// Publishes events to Kafka as JSON `SinkRecord`s: one topic per `EventCategory`,
+/// keyed by the authority that initiated the event.
+///
+/// `publish` returns once the broker has acknowledged the record, so events reach
+/// Kafka in publishing order.
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    topic_prefix: String,
+    timeout: Duration,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    /// Creates a producer for `brokers`, a comma-separated list of `host:port`,
+    /// publishing under `topic_prefix`, e.g. `w3b2.events`.
+    ///
+    /// Idempotence is enabled, so retries inside the producer do not duplicate
+    /// records.
+    pub fn new(brokers: &str, topic_prefix: &str) -> Result<Self, SinkError> {
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("enable.idempotence", "true")
+            .create()
+            .map_err(|e| SinkError::Publish(e.to_string()))?;
+        Ok(Self::with_producer(producer, topic_prefix))
+    }
+
+    /// Publishes through a producer configured by the caller, e.g. with
+    /// authentication or a custom delivery timeout.
+    pub fn with_producer(producer: rdkafka::producer::FutureProducer, topic_prefix: &str) -> Self {
+        Self {
+            producer,
+            topic_prefix: topic_prefix.to_string(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets how long a publish may wait for room in the producer's queue, and how
+    /// long `flush` waits for outstanding records. Defaults to 30 s.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl EventSink for KafkaSink {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), SinkError> {
+        let record = SinkRecord::json(&self.topic_prefix, envelope)?;
+        let key = record.key.map(|key| key.to_string());
+        let kafka_record = rdkafka::producer::FutureRecord {
+            topic: &record.topic,
+            partition: None,
+            payload: Some(&record.payload),
+            key: key.as_deref(),
+            timestamp: None,
+            headers: None,
+        };
+        self.producer
+            .send(kafka_record, self.timeout)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| SinkError::Publish(e.to_string()))
+    }
+
+    async fn flush(&self) -> Result<(), SinkError> {
+        use rdkafka::producer::Producer;
+
+        // Flushing blocks the calling thread until the queue drains.
+        let producer = self.producer.clone();
+        let timeout = self.timeout;
+        tokio::task::spawn_blocking(move || producer.flush(timeout))
+            .await
+            .map_err(|e| SinkError::Publish(e.to_string()))?
+            .map_err(|e| SinkError::Publish(e.to_string()))
+    }
+}
+
+/// Publishes events to NATS JetStream as JSON, on the subject given by `subject`.
+///
+/// Every message carries `message_id` as its `Nats-Msg-Id`, so the stream discards
+/// a publish repeated within its duplicate window, and `publish` waits for the
+/// stream's acknowledgement. Wrapped in a `RetryingSink`, that delivers each event
+/// at least once while storing it once. A stream must capture the subjects, e.g.
+/// `w3b2.events.>`.
+#[cfg(feature = "nats")]
+pub struct NatsSink {
+    jetstream: async_nats::jetstream::Context,
+    subject_prefix: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    /// Connects to the NATS server at `url`, publishing under `subject_prefix`,
+    /// e.g. `w3b2.events`.
+    pub async fn connect(url: &str, subject_prefix: &str) -> Result<Self, SinkError> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| SinkError::Publish(e.to_string()))?;
+        Ok(Self::with_client(client, subject_prefix))
+    }
+
+    /// Publishes through a client configured by the caller, e.g. with credentials.
+    pub fn with_client(client: async_nats::Client, subject_prefix: &str) -> Self {
+        Self {
+            jetstream: async_nats::jetstream::new(client),
+            subject_prefix: subject_prefix.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+#[async_trait]
+impl EventSink for NatsSink {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), SinkError> {
+        let payload = serde_json::to_vec(envelope).map_err(|e| SinkError::Encode(e.to_string()))?;
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert(
+            async_nats::header::NATS_MESSAGE_ID,
+            message_id(envelope).as_str(),
+        );
+        let ack = self
+            .jetstream
+            .publish_with_headers(
+                subject(&self.subject_prefix, envelope),
+                headers,
+                payload.into(),
+            )
+            .await
+            .map_err(|e| SinkError::Publish(e.to_string()))?;
+        ack.await
+            .map(|_| ())
+            .map_err(|e| SinkError::Publish(e.to_string()))
+    }
+}
+
+/// Delivers every event received on `events` to `sink` until the channel closes,
+/// then flushes the sink.
+///
+/// Events missed because the sink fell too far behind are logged and skipped. A
+/// delivery failure stops forwarding and is returned, so the caller can decide
+/// whether to resubscribe or resume from storage.
+pub async fn forward(
+    mut events: broadcast::Receiver<EventEnvelope>,
+    sink: &dyn EventSink,
+) -> Result<(), SinkError> {
+    loop {
+        match events.recv().await {
+            Ok(envelope) => sink.publish(&envelope).await?,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("Event sink lagged, skipped {} events.", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+    sink.flush().await
+}
@@ -1,17 +1,360 @@
-use anyhow::Result;
+use crate::events::{EventCursor, EventEnvelope};
 use async_trait::async_trait;
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// The reasons a storage operation fails.
+#[derive(Error, Debug)]
+pub enum StorageError {
+    /// Reading from or writing to disk failed.
+    #[error("Storage I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Stored data, or a snapshot being imported, cannot be decoded.
+    #[error("Corrupted data: {0}")]
+    Corrupted(String),
+
+    /// A snapshot was written by a newer, unsupported format version.
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedVersion(u8),
+
+    /// Any other failure of the backend, e.g. an aborted transaction.
+    #[error("Storage backend failed: {0}")]
+    Backend(String),
+}
+
+impl StorageError {
+    /// Wraps a decoding error of stored data.
+    pub fn corrupted(e: impl std::fmt::Display) -> Self {
+        Self::Corrupted(e.to_string())
+    }
+}
 
 /// A trait defining the required functionality for a persistent storage backend.
 /// This allows for different database implementations.
 #[async_trait]
 pub trait Storage: Send + Sync {
     /// Retrieves the last synchronized slot number from the storage.
-    async fn get_last_slot(&self) -> Result<u64>;
+    async fn get_last_slot(&self) -> Result<u64, StorageError>;
 
     /// Retrieves the last synchronized signature from the storage.
-    async fn get_last_sig(&self) -> Result<Option<String>>;
+    async fn get_last_sig(&self) -> Result<Option<String>, StorageError>;
 
     /// Atomically sets the last synchronized slot and signature.
     /// This should be a transactional operation to ensure data consistency.
-    async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<()>;
+    async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<(), StorageError>;
+
+    /// Persists a decoded event in the history.
+    /// Appending an event with the same `(slot, signature, index)` again must not duplicate it.
+    async fn append_event(&self, envelope: &EventEnvelope) -> Result<(), StorageError>;
+
+    /// Returns the stored events from `from_slot` to `to_slot` (inclusive), oldest first.
+    async fn events_by_slot_range(
+        &self,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError>;
+
+    /// Returns the stored events emitted by the transaction `signature`, in log order.
+    async fn events_by_signature(
+        &self,
+        signature: &str,
+    ) -> Result<Vec<EventEnvelope>, StorageError>;
+
+    /// Returns the stored events involving `pubkey` from `from_slot` to `to_slot`
+    /// (inclusive), oldest first.
+    async fn events_by_pubkey(
+        &self,
+        pubkey: &Pubkey,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError>;
+
+    /// Returns the latest `limit` stored events involving `pubkey`, oldest first.
+    ///
+    /// The default implementation reads the whole history of `pubkey`; backends
+    /// with an ordered index should override it.
+    async fn recent_events_by_pubkey(
+        &self,
+        pubkey: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let mut events = self.events_by_pubkey(pubkey, 0, u64::MAX).await?;
+        events.drain(..events.len().saturating_sub(limit));
+        Ok(events)
+    }
+
+    /// Deletes every stored event from a slot before `before_slot`.
+    /// Returns the number of events removed.
+    async fn prune_events_before(&self, before_slot: u64) -> Result<usize, StorageError>;
+
+    /// Deletes the events emitted by the transaction `signature`, e.g. because it
+    /// was on a dropped fork. Returns the number of events removed.
+    async fn remove_events_by_signature(&self, signature: &str) -> Result<usize, StorageError>;
+
+    /// Deletes the oldest stored events until at most `max_events` remain.
+    /// Returns the number of events removed.
+    async fn prune_events_to_count(&self, max_events: usize) -> Result<usize, StorageError>;
+
+    /// Retrieves the last event acknowledged by the durable subscriber `subscriber_id`.
+    async fn get_cursor(&self, subscriber_id: &str) -> Result<Option<EventCursor>, StorageError>;
+
+    /// Records `cursor` as the last event acknowledged by `subscriber_id`.
+    async fn set_cursor(
+        &self,
+        subscriber_id: &str,
+        cursor: &EventCursor,
+    ) -> Result<(), StorageError>;
+
+    /// Returns every durable subscriber cursor, ordered by subscriber id.
+    async fn cursors(&self) -> Result<Vec<(String, EventCursor)>, StorageError>;
+
+    /// Captures the sync state and subscriber cursors, plus the whole event history
+    /// if `include_events` is set, in a form any backend can import.
+    async fn export_snapshot(&self, include_events: bool) -> Result<Snapshot, StorageError> {
+        let events = if include_events {
+            self.events_by_slot_range(0, u64::MAX).await?
+        } else {
+            Vec::new()
+        };
+        Ok(Snapshot {
+            last_slot: self.get_last_slot().await?,
+            last_sig: self.get_last_sig().await?,
+            cursors: self.cursors().await?,
+            events,
+        })
+    }
+
+    /// Loads a snapshot, e.g. to seed a new node. The snapshot's events are added
+    /// to the history, and its sync state and cursors replace the current ones.
+    async fn import_snapshot(&self, snapshot: &Snapshot) -> Result<(), StorageError> {
+        for envelope in &snapshot.events {
+            self.append_event(envelope).await?;
+        }
+        for (subscriber_id, cursor) in &snapshot.cursors {
+            self.set_cursor(subscriber_id, cursor).await?;
+        }
+        if let Some(sig) = &snapshot.last_sig {
+            self.set_sync_state(snapshot.last_slot, sig).await?;
+        }
+        self.flush().await
+    }
+
+    /// Makes every previous write durable. Called once the synchronizer has shut
+    /// down, so the sync state survives the process exiting right after.
+    async fn flush(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+/// Marks a serialized `Snapshot`, followed by the format version.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"W3B2SNAP";
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// A portable copy of a storage backend's contents, made with
+/// `Storage::export_snapshot`.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    /// The last synchronized slot.
+    pub last_slot: u64,
+    /// The last synchronized signature, if anything was synchronized.
+    pub last_sig: Option<String>,
+    /// The durable subscriber cursors, by subscriber id.
+    pub cursors: Vec<(String, EventCursor)>,
+    /// The event history, oldest first. Empty unless exported with events.
+    pub events: Vec<EventEnvelope>,
+}
+
+impl Snapshot {
+    /// Serializes the snapshot, e.g. to write it to a file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cursors: Vec<(&String, Vec<u8>)> = self
+            .cursors
+            .iter()
+            .map(|(id, cursor)| (id, cursor.to_bytes()))
+            .collect();
+        let events: Vec<Vec<u8>> = self.events.iter().map(EventEnvelope::to_bytes).collect();
+
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.push(SNAPSHOT_VERSION);
+        borsh::to_writer(
+            &mut bytes,
+            &(self.last_slot, &self.last_sig, cursors, events),
+        )
+        .expect("serializing into a Vec cannot fail");
+        bytes
+    }
+
+    /// Restores a snapshot serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StorageError> {
+        let Some(body) = bytes.strip_prefix(SNAPSHOT_MAGIC.as_slice()) else {
+            return Err(StorageError::corrupted("not a W3B2 snapshot"));
+        };
+        let Some((&version, body)) = body.split_first() else {
+            return Err(StorageError::corrupted("truncated snapshot"));
+        };
+        if version != SNAPSHOT_VERSION {
+            return Err(StorageError::UnsupportedVersion(version));
+        }
+        let (last_slot, last_sig, cursors, events) =
+            <(u64, Option<String>, Vec<(String, Vec<u8>)>, Vec<Vec<u8>>)>::try_from_slice(body)
+                .map_err(StorageError::corrupted)?;
+        Ok(Self {
+            last_slot,
+            last_sig,
+            cursors: cursors
+                .into_iter()
+                .map(|(id, cursor)| Ok((id, EventCursor::from_bytes(&cursor)?)))
+                .collect::<anyhow::Result<_>>()
+                .map_err(StorageError::corrupted)?,
+            events: events
+                .iter()
+                .map(|event| EventEnvelope::from_bytes(event))
+                .collect::<anyhow::Result<_>>()
+                .map_err(StorageError::corrupted)?,
+        })
+    }
+}
+
+/// A volatile, in-memory `Storage` backend.
+///
+/// Nothing is persisted, so a restarted synchronizer starts over from slot 0. Meant
+/// for tests and short-lived tools that should not touch disk.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    /// The last synchronized slot and signature, updated together.
+    state: Mutex<(u64, Option<String>)>,
+    /// The event history, keyed by `(slot, signature, index)`.
+    events: Mutex<BTreeMap<(u64, String, u32), EventEnvelope>>,
+    /// Durable subscriber cursors, keyed by subscriber id.
+    cursors: Mutex<HashMap<String, EventCursor>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty storage, as if nothing had been synchronized yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn get_last_slot(&self) -> Result<u64, StorageError> {
+        Ok(self.state.lock().unwrap().0)
+    }
+
+    async fn get_last_sig(&self) -> Result<Option<String>, StorageError> {
+        Ok(self.state.lock().unwrap().1.clone())
+    }
+
+    async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<(), StorageError> {
+        *self.state.lock().unwrap() = (slot, Some(sig.to_string()));
+        Ok(())
+    }
+
+    async fn append_event(&self, envelope: &EventEnvelope) -> Result<(), StorageError> {
+        let key = (envelope.slot, envelope.signature.clone(), envelope.index);
+        self.events.lock().unwrap().insert(key, envelope.clone());
+        Ok(())
+    }
+
+    async fn events_by_slot_range(
+        &self,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| (from_slot..=to_slot).contains(&e.slot))
+            .cloned()
+            .collect())
+    }
+
+    async fn events_by_signature(
+        &self,
+        signature: &str,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| e.signature == signature)
+            .cloned()
+            .collect())
+    }
+
+    async fn events_by_pubkey(
+        &self,
+        pubkey: &Pubkey,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| (from_slot..=to_slot).contains(&e.slot) && e.pubkeys().contains(pubkey))
+            .cloned()
+            .collect())
+    }
+
+    async fn prune_events_before(&self, before_slot: u64) -> Result<usize, StorageError> {
+        let mut events = self.events.lock().unwrap();
+        let kept = events.split_off(&(before_slot, String::new(), 0));
+        let removed = events.len();
+        *events = kept;
+        Ok(removed)
+    }
+
+    async fn remove_events_by_signature(&self, signature: &str) -> Result<usize, StorageError> {
+        let mut events = self.events.lock().unwrap();
+        let before = events.len();
+        events.retain(|(_, sig, _), _| sig != signature);
+        Ok(before - events.len())
+    }
+
+    async fn prune_events_to_count(&self, max_events: usize) -> Result<usize, StorageError> {
+        let mut events = self.events.lock().unwrap();
+        let removed = events.len().saturating_sub(max_events);
+        for _ in 0..removed {
+            events.pop_first();
+        }
+        Ok(removed)
+    }
+
+    async fn get_cursor(&self, subscriber_id: &str) -> Result<Option<EventCursor>, StorageError> {
+        Ok(self.cursors.lock().unwrap().get(subscriber_id).cloned())
+    }
+
+    async fn set_cursor(
+        &self,
+        subscriber_id: &str,
+        cursor: &EventCursor,
+    ) -> Result<(), StorageError> {
+        self.cursors
+            .lock()
+            .unwrap()
+            .insert(subscriber_id.to_string(), cursor.clone());
+        Ok(())
+    }
+
+    async fn cursors(&self) -> Result<Vec<(String, EventCursor)>, StorageError> {
+        let mut cursors: Vec<_> = self
+            .cursors
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, cursor)| (id.clone(), cursor.clone()))
+            .collect();
+        cursors.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(cursors)
+    }
 }
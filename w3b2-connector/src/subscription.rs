@@ -0,0 +1,173 @@
+// File: w3b2-connector/src/subscription.rs
+
+//! # Durable Subscriptions
+//!
+//! A regular listener only sees events that happen while it is connected. A
+//! `DurableSubscription` is tied to a stable subscriber id instead: every event it
+//! acknowledges moves a cursor kept in `Storage`, and the next subscription with the
+//! same id resumes from that cursor, replaying the stored history first.
+
+use crate::events::{EventCursor, EventEnvelope};
+use crate::storage::{Storage, StorageError};
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+/// A resumable stream of the events involving one pubkey.
+pub struct DurableSubscription {
+    subscriber_id: String,
+    storage: Arc<dyn Storage>,
+    rx: mpsc::Receiver<EventEnvelope>,
+}
+
+impl DurableSubscription {
+    /// (Internal) Starts delivery from the subscriber's stored cursor.
+    ///
+    /// `live_rx` must be subscribed before this is called, so that no event falls
+    /// between the history read here and the live stream.
+    pub(crate) async fn start(
+        subscriber_id: String,
+        pubkey: Pubkey,
+        storage: Arc<dyn Storage>,
+        live_rx: broadcast::Receiver<EventEnvelope>,
+        channel_capacity: usize,
+    ) -> Result<Self> {
+        let cursor = storage.get_cursor(&subscriber_id).await?;
+        let (tx, rx) = mpsc::channel(channel_capacity);
+        tokio::spawn(forward(pubkey, storage.clone(), cursor, live_rx, tx));
+
+        Ok(Self {
+            subscriber_id,
+            storage,
+            rx,
+        })
+    }
+
+    /// Receives the next event, or `None` once the event stream has ended.
+    pub async fn recv(&mut self) -> Option<EventEnvelope> {
+        self.rx.recv().await
+    }
+
+    /// Marks `envelope` and everything delivered before it as processed.
+    ///
+    /// A subscription reopened with the same id resumes from this event's slot,
+    /// skipping the event itself. Other events of that slot may be delivered again,
+    /// so handlers should be idempotent per `EventCursor`.
+    pub async fn ack(&self, envelope: &EventEnvelope) -> Result<(), StorageError> {
+        self.storage
+            .set_cursor(&self.subscriber_id, &envelope.cursor())
+            .await
+    }
+
+    /// Returns the last acknowledged position, if any.
+    pub async fn cursor(&self) -> Result<Option<EventCursor>, StorageError> {
+        self.storage.get_cursor(&self.subscriber_id).await
+    }
+}
+
+/// How many slots back a subscription remembers what it has delivered, to drop
+/// duplicates between the stored history and the live stream.
+const DEDUP_WINDOW_SLOTS: u64 = 300;
+
+/// Delivers events to the subscriber, dropping those it has already seen.
+struct Delivery {
+    tx: mpsc::Sender<EventEnvelope>,
+    /// The acknowledged cursor the subscription resumed from.
+    resumed_from: Option<EventCursor>,
+    /// Cursors delivered within the last `DEDUP_WINDOW_SLOTS` slots.
+    delivered: HashSet<EventCursor>,
+    highest_slot: u64,
+}
+
+impl Delivery {
+    /// The first slot that still has to be read from storage.
+    fn first_unread_slot(&self) -> u64 {
+        match &self.resumed_from {
+            Some(cursor) if self.delivered.is_empty() => cursor.slot,
+            _ => self.highest_slot.saturating_sub(DEDUP_WINDOW_SLOTS),
+        }
+    }
+
+    /// Sends `envelope` unless it is a duplicate. Returns `false` if the subscriber is gone.
+    async fn send(&mut self, envelope: EventEnvelope) -> bool {
+        let cursor = envelope.cursor();
+        if self.resumed_from.as_ref() == Some(&cursor)
+            || self.delivered.contains(&cursor)
+            || cursor.slot + DEDUP_WINDOW_SLOTS < self.highest_slot
+        {
+            return true;
+        }
+
+        self.highest_slot = self.highest_slot.max(cursor.slot);
+        self.delivered.insert(cursor);
+        if self.delivered.len() > 4096 {
+            let floor = self.highest_slot.saturating_sub(DEDUP_WINDOW_SLOTS);
+            self.delivered.retain(|cursor| cursor.slot >= floor);
+        }
+        self.tx.send(envelope).await.is_ok()
+    }
+
+    /// Sends every stored event for `pubkey` from `first_unread_slot` on.
+    /// Returns `false` if the subscriber is gone or storage failed.
+    async fn catch_up(&mut self, pubkey: &Pubkey, storage: &dyn Storage) -> bool {
+        let history = match storage
+            .events_by_pubkey(pubkey, self.first_unread_slot(), u64::MAX)
+            .await
+        {
+            Ok(history) => history,
+            Err(e) => {
+                tracing::error!("Failed to read event history for {}: {}", pubkey, e);
+                return false;
+            }
+        };
+        for envelope in history {
+            if !self.send(envelope).await {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Feeds `tx` with the stored events from the subscriber's cursor on, then with
+/// live events. If the live stream lags, the missed events are re-read from storage.
+async fn forward(
+    pubkey: Pubkey,
+    storage: Arc<dyn Storage>,
+    cursor: Option<EventCursor>,
+    mut live_rx: broadcast::Receiver<EventEnvelope>,
+    tx: mpsc::Sender<EventEnvelope>,
+) {
+    let mut delivery = Delivery {
+        tx,
+        resumed_from: cursor,
+        delivered: HashSet::new(),
+        highest_slot: 0,
+    };
+    if !delivery.catch_up(&pubkey, storage.as_ref()).await {
+        return;
+    }
+
+    loop {
+        match live_rx.recv().await {
+            Ok(envelope) => {
+                if envelope.pubkeys().contains(&pubkey) && !delivery.send(envelope).await {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "Durable subscription for {} lagged by {} events, re-reading from storage.",
+                    pubkey,
+                    skipped
+                );
+                if !delivery.catch_up(&pubkey, storage.as_ref()).await {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// File: w3b2-connector/src/topup.rs
+
+//! # Automatic Deposit Top-Up
+//!
+//! `AutoTopUp` keeps a user's deposit with one service funded. It follows the
+//! `UserProfile` through the updates of an `AccountWatcher` and, whenever the
+//! spendable deposit drops below a threshold, deposits a fixed amount from the
+//! user's `ChainCard` via `user_deposit`.
+//!
+//! A `TopUpPolicy` bounds what the engine may spend: top-ups are spaced at least
+//! `cooldown` apart, so a deposit that has not landed yet is not repeated, and the
+//! total deposited never exceeds `max_total`.
+
+use crate::client::TransactionBuilder;
+use crate::workers::ProfileUpdate;
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// When and how much `AutoTopUp` deposits.
+#[derive(Debug, Clone, Copy)]
+pub struct TopUpPolicy {
+    /// Top up once the spendable deposit drops below this many lamports.
+    pub threshold: u64,
+    /// The lamports deposited per top-up.
+    pub amount: u64,
+    /// The minimum time between two top-ups, successful or not.
+    pub cooldown: Duration,
+    /// The most lamports deposited over the engine's lifetime, or `None` for no cap.
+    pub max_total: Option<u64>,
+}
+
+/// Tops up a `UserProfile` deposit according to a `TopUpPolicy`.
+pub struct AutoTopUp {
+    builder: TransactionBuilder,
+    signer: Arc<dyn Signer + Send + Sync>,
+    admin_profile_pda: Pubkey,
+    policy: TopUpPolicy,
+    /// The lamports deposited so far.
+    deposited: u64,
+    /// When the last top-up was attempted.
+    last_attempt: Option<Instant>,
+}
+
+impl AutoTopUp {
+    /// Creates an engine for the deposit of `signer` with the service at
+    /// `admin_profile_pda`.
+    ///
+    /// * `builder` - Used to prepare and submit deposit transactions.
+    /// * `signer` - The user's `ChainCard`, which funds and signs every deposit.
+    pub fn new(
+        builder: TransactionBuilder,
+        signer: Arc<dyn Signer + Send + Sync>,
+        admin_profile_pda: Pubkey,
+        policy: TopUpPolicy,
+    ) -> Self {
+        Self {
+            builder,
+            signer,
+            admin_profile_pda,
+            policy,
+            deposited: 0,
+            last_attempt: None,
+        }
+    }
+
+    /// Returns the lamports deposited so far.
+    pub fn deposited(&self) -> u64 {
+        self.deposited
+    }
+
+    /// Returns the amount to deposit if the spendable deposit is `deposit_balance`
+    /// at `now`, or `None` if the policy does not allow a top-up.
+    ///
+    /// The last top-up before the cap is reduced so the cap is not exceeded.
+    pub fn plan(&self, deposit_balance: u64, now: Instant) -> Option<u64> {
+        if deposit_balance >= self.policy.threshold {
+            return None;
+        }
+        if self
+            .last_attempt
+            .is_some_and(|last| now.duration_since(last) < self.policy.cooldown)
+        {
+            return None;
+        }
+        let remaining = self
+            .policy
+            .max_total
+            .map_or(u64::MAX, |max| max.saturating_sub(self.deposited));
+        Some(self.policy.amount.min(remaining)).filter(|&amount| amount > 0)
+    }
+
+    /// Tops up if the policy calls for it at a spendable deposit of `deposit_balance`.
+    ///
+    /// Returns the signature of the deposit transaction, if one was submitted.
+    pub async fn observe(&mut self, deposit_balance: u64) -> Result<Option<Signature>> {
+        let now = Instant::now();
+        let Some(amount) = self.plan(deposit_balance, now) else {
+            return Ok(None);
+        };
+        self.last_attempt = Some(now);
+
+        let authority = self.signer.pubkey();
+        let mut tx = self
+            .builder
+            .prepare_user_deposit(authority, self.admin_profile_pda, amount)
+            .await?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_sign(&[self.signer.as_ref()], blockhash)?;
+        let signature = self.builder.submit_transaction(&tx).await?;
+        self.deposited += amount;
+
+        tracing::info!(
+            "Topped up the deposit of {} with {} by {} lamports ({} deposited so far): {}",
+            authority,
+            self.admin_profile_pda,
+            amount,
+            self.deposited,
+            signature
+        );
+        Ok(Some(signature))
+    }
+
+    /// Follows the profile's updates, e.g. from
+    /// `AccountWatcher::watch_user_profile`, until the channel closes or the
+    /// profile is closed.
+    ///
+    /// A failed top-up is logged and retried after the cooldown, on a later update.
+    pub async fn run(mut self, mut updates: mpsc::Receiver<ProfileUpdate>) -> Result<()> {
+        while let Some(update) = updates.recv().await {
+            match update {
+                ProfileUpdate::User { profile, .. } => {
+                    if let Err(e) = self.observe(profile.deposit_balance).await {
+                        tracing::error!("Failed to top up the deposit: {}", e);
+                    }
+                }
+                ProfileUpdate::Closed { address, .. } => {
+                    tracing::info!("Profile {} was closed, stopping top-ups.", address);
+                    break;
+                }
+                ProfileUpdate::Admin { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
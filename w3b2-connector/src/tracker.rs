@@ -0,0 +1,225 @@
+// File: w3b2-connector/src/tracker.rs
+
+//! # Pending Transaction Tracking
+//!
+//! A `TxTracker` follows a transaction after it was broadcast, e.g. with
+//! `TransactionBuilder::broadcast_transaction`, by polling its signature status.
+//! Every change of status is reported over a channel until the transaction
+//! reaches the requested commitment, fails, or its blockhash expires.
+
+use crate::client::decode_bridge_error;
+use crate::rpc::SolanaRpc;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use w3b2_bridge_program::errors::BridgeError;
+
+/// The status of a tracked transaction.
+#[derive(Debug, Clone)]
+pub enum TxStatus {
+    /// The cluster does not know the transaction (yet).
+    Pending,
+    /// The transaction landed in a block that is not confirmed yet.
+    Processed { slot: u64 },
+    /// The block was voted on by a supermajority of the cluster.
+    Confirmed { slot: u64 },
+    /// The block was finalized.
+    Finalized { slot: u64 },
+    /// The transaction landed but failed.
+    Failed {
+        slot: u64,
+        error: TransactionError,
+        bridge_error: Option<BridgeError>,
+    },
+    /// The blockhash expired before the transaction landed. It can never land now
+    /// and must be re-prepared and re-signed.
+    Expired,
+}
+
+impl TxStatus {
+    /// Returns `true` if the transaction failed or can no longer land.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Failed { .. } | Self::Expired)
+    }
+
+    /// Returns the slot the transaction landed in, if it did.
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            Self::Processed { slot }
+            | Self::Confirmed { slot }
+            | Self::Finalized { slot }
+            | Self::Failed { slot, .. } => Some(*slot),
+            Self::Pending | Self::Expired => None,
+        }
+    }
+
+    fn from_rpc(status: TransactionStatus) -> Self {
+        let slot = status.slot;
+        if let Some(error) = status.err.clone() {
+            return Self::Failed {
+                slot,
+                bridge_error: decode_bridge_error(&error),
+                error,
+            };
+        }
+        match status.confirmation_status() {
+            TransactionConfirmationStatus::Processed => Self::Processed { slot },
+            TransactionConfirmationStatus::Confirmed => Self::Confirmed { slot },
+            TransactionConfirmationStatus::Finalized => Self::Finalized { slot },
+        }
+    }
+
+    /// Compares statuses by kind and slot; errors are not comparable.
+    fn same_as(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other) && self.slot() == other.slot()
+    }
+}
+
+/// A status change of a tracked transaction.
+#[derive(Debug, Clone)]
+pub struct TxStatusUpdate {
+    pub signature: Signature,
+    pub status: TxStatus,
+    /// Set on the last update sent for the transaction.
+    pub is_final: bool,
+}
+
+/// Follows submitted transactions until they settle.
+///
+/// The tracker is cheap to clone; every tracked transaction is polled separately.
+#[derive(Clone)]
+pub struct TxTracker {
+    rpc_client: Arc<dyn SolanaRpc>,
+    poll_interval: Duration,
+    commitment: CommitmentConfig,
+}
+
+impl TxTracker {
+    /// Creates a tracker that polls every 500 ms until a transaction is `confirmed`.
+    pub fn new(rpc_client: Arc<dyn SolanaRpc>) -> Self {
+        Self {
+            rpc_client,
+            poll_interval: Duration::from_millis(500),
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+
+    /// Sets how often the signature status is polled.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the commitment at which a transaction counts as settled.
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Starts tracking a transaction in the background and returns its status
+    /// updates. The first update is the current status; the channel closes after
+    /// the final one.
+    ///
+    /// Tracking stops early once the receiver is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - The transaction's signature.
+    /// * `recent_blockhash` - The blockhash the transaction was signed with, used to
+    ///   detect that it expired.
+    pub fn watch(
+        &self,
+        signature: Signature,
+        recent_blockhash: Hash,
+    ) -> mpsc::Receiver<TxStatusUpdate> {
+        let (tx, rx) = mpsc::channel(8);
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            tracker.track(signature, recent_blockhash, Some(&tx)).await;
+        });
+        rx
+    }
+
+    /// Waits until the transaction settles and returns its final status.
+    pub async fn wait(&self, signature: Signature, recent_blockhash: Hash) -> TxStatus {
+        self.track(signature, recent_blockhash, None).await
+    }
+
+    #[tracing::instrument(skip_all, fields(%signature))]
+    async fn track(
+        &self,
+        signature: Signature,
+        recent_blockhash: Hash,
+        updates: Option<&mpsc::Sender<TxStatusUpdate>>,
+    ) -> TxStatus {
+        let mut last: Option<TxStatus> = None;
+        loop {
+            let Some(status) = self.poll(&signature, &recent_blockhash).await else {
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            };
+            let is_final = self.is_settled(&status);
+
+            if last.as_ref().is_none_or(|last| !last.same_as(&status)) {
+                tracing::debug!(?status, "Transaction status changed");
+                if let Some(updates) = updates {
+                    let update = TxStatusUpdate {
+                        signature,
+                        status: status.clone(),
+                        is_final,
+                    };
+                    if updates.send(update).await.is_err() {
+                        return status;
+                    }
+                }
+            }
+            if is_final {
+                return status;
+            }
+            last = Some(status);
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Fetches the current status. Returns `None` if it could not be determined,
+    /// e.g. because of a transport error.
+    async fn poll(&self, signature: &Signature, recent_blockhash: &Hash) -> Option<TxStatus> {
+        let statuses = match self.rpc_client.get_signature_statuses(&[*signature]).await {
+            Ok(response) => response.value,
+            Err(e) => {
+                tracing::warn!("Failed to fetch the status of {}: {}", signature, e);
+                return None;
+            }
+        };
+        if let Some(Some(status)) = statuses.into_iter().next() {
+            return Some(TxStatus::from_rpc(status));
+        }
+
+        match self
+            .rpc_client
+            .is_blockhash_valid(recent_blockhash, self.commitment)
+            .await
+        {
+            Ok(true) => Some(TxStatus::Pending),
+            Ok(false) => Some(TxStatus::Expired),
+            Err(e) => {
+                tracing::warn!("Failed to check the blockhash of {}: {}", signature, e);
+                None
+            }
+        }
+    }
+
+    fn is_settled(&self, status: &TxStatus) -> bool {
+        match status {
+            TxStatus::Pending => false,
+            TxStatus::Processed { .. } => self.commitment.is_processed(),
+            TxStatus::Confirmed { .. } => !self.commitment.is_finalized(),
+            TxStatus::Finalized { .. } | TxStatus::Failed { .. } | TxStatus::Expired => true,
+        }
+    }
+}
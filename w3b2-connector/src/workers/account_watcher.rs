@@ -0,0 +1,172 @@
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use solana_account_decoder_client_types::{UiAccount, UiAccountEncoding};
+use solana_client::{rpc_config::RpcAccountInfoConfig, rpc_response::Response};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::sync::Arc;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::StreamExt;
+use w3b2_bridge_program::state::{AdminProfile, UserProfile};
+
+use crate::config::ConnectorConfig;
+use crate::rpc_pool::connect_pubsub;
+
+/// The kind of profile account a watcher decodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKind {
+    Admin,
+    User,
+}
+
+/// A typed snapshot of a watched profile account after a change.
+#[derive(Debug)]
+pub enum ProfileUpdate {
+    /// The new state of an `AdminProfile` (balance, prices, comm key, ...).
+    Admin {
+        address: Pubkey,
+        slot: u64,
+        profile: Box<AdminProfile>,
+    },
+    /// The new state of a `UserProfile` (deposit, comm key, ...).
+    User {
+        address: Pubkey,
+        slot: u64,
+        profile: UserProfile,
+    },
+    /// The account was closed or is no longer owned by the bridge program.
+    Closed { address: Pubkey, slot: u64 },
+}
+
+impl ProfileUpdate {
+    /// Decodes an account notification into a `ProfileUpdate`.
+    pub fn decode(
+        kind: ProfileKind,
+        address: Pubkey,
+        slot: u64,
+        account: &UiAccount,
+    ) -> Result<Self> {
+        if account.lamports == 0 || account.owner != w3b2_bridge_program::ID.to_string() {
+            return Ok(Self::Closed { address, slot });
+        }
+
+        let data = account
+            .data
+            .decode()
+            .ok_or_else(|| anyhow!("unsupported account encoding for {address}"))?;
+        let update = match kind {
+            ProfileKind::Admin => Self::Admin {
+                address,
+                slot,
+                profile: Box::new(AdminProfile::try_deserialize(&mut data.as_slice())?),
+            },
+            ProfileKind::User => Self::User {
+                address,
+                slot,
+                profile: UserProfile::try_deserialize(&mut data.as_slice())?,
+            },
+        };
+        Ok(update)
+    }
+}
+
+/// Streams typed state changes of individual profile accounts via `accountSubscribe`.
+///
+/// This complements the event stream: events say *what happened*, while the watcher
+/// delivers the resulting account state, so consumers can reconcile without replaying
+/// history.
+#[derive(Clone)]
+pub struct AccountWatcher {
+    config: Arc<ConnectorConfig>,
+}
+
+impl AccountWatcher {
+    pub fn new(config: Arc<ConnectorConfig>) -> Self {
+        Self { config }
+    }
+
+    /// Watches an `AdminProfile` PDA.
+    ///
+    /// * `admin_pda` - The address of the `AdminProfile` to watch.
+    /// * `channel_capacity` - The buffer capacity of the returned channel.
+    pub fn watch_admin_profile(
+        &self,
+        admin_pda: Pubkey,
+        channel_capacity: usize,
+    ) -> (mpsc::Receiver<ProfileUpdate>, JoinHandle<Result<()>>) {
+        self.watch(ProfileKind::Admin, admin_pda, channel_capacity)
+    }
+
+    /// Watches a `UserProfile` PDA.
+    ///
+    /// * `user_pda` - The address of the `UserProfile` to watch.
+    /// * `channel_capacity` - The buffer capacity of the returned channel.
+    pub fn watch_user_profile(
+        &self,
+        user_pda: Pubkey,
+        channel_capacity: usize,
+    ) -> (mpsc::Receiver<ProfileUpdate>, JoinHandle<Result<()>>) {
+        self.watch(ProfileKind::User, user_pda, channel_capacity)
+    }
+
+    /// Spawns the subscription task. It ends when the receiver is dropped or the
+    /// WebSocket subscription closes.
+    fn watch(
+        &self,
+        kind: ProfileKind,
+        address: Pubkey,
+        channel_capacity: usize,
+    ) -> (mpsc::Receiver<ProfileUpdate>, JoinHandle<Result<()>>) {
+        let (tx, rx) = mpsc::channel(channel_capacity);
+        let config = self.config.clone();
+        let handle = tokio::spawn(async move { run(config, kind, address, tx).await });
+        (rx, handle)
+    }
+}
+
+async fn run(
+    config: Arc<ConnectorConfig>,
+    kind: ProfileKind,
+    address: Pubkey,
+    tx: mpsc::Sender<ProfileUpdate>,
+) -> Result<()> {
+    let client = connect_pubsub(&config.solana).await?;
+    let (mut stream, _unsubscribe) = client
+        .account_subscribe(
+            &address,
+            Some(RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig {
+                    commitment: config.solana.commitment,
+                }),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    tracing::info!(
+        "Account watcher subscribed to {:?} profile {}.",
+        kind,
+        address
+    );
+
+    loop {
+        tokio::select! {
+            Some(msg) = stream.next() => {
+                let Response { context, value } = msg;
+                match ProfileUpdate::decode(kind, address, context.slot, &value) {
+                    Ok(update) => {
+                        if tx.send(update).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to decode account update for {}: {}", address, e),
+                }
+            },
+            _ = tx.closed() => break,
+            else => break,
+        }
+    }
+
+    tracing::info!("Account watcher for {} stopped.", address);
+    Ok(())
+}
@@ -1,25 +1,87 @@
 use crate::{
-    events::{try_parse_log, BridgeEvent},
+    backoff::Backoff,
+    config::{IngestionFilter, StartPoint},
+    events::{
+        parse_transaction, transaction_account_keys, transaction_logs, BridgeEvent, EventEnvelope,
+        GapReason, SyncGap,
+    },
+    rpc::SolanaRpc,
+    rpc_pool::RpcPool,
     workers::WorkerContext,
 };
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
-use solana_client::{
-    rpc_config::RpcTransactionConfig, rpc_response::RpcConfirmedTransactionStatusWithSignature,
-};
-use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
-use solana_transaction_status::UiTransactionEncoding;
+use solana_client::rpc_config::RpcBlockConfig;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, TransactionDetails};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
+/// How many times a transaction is requested before catch-up stops short of it
+/// until the next poll.
+const FETCH_ATTEMPTS: u32 = 3;
+
 pub struct CatchupWorker {
     ctx: WorkerContext,
-    program_id: solana_sdk::pubkey::Pubkey,
+    program_id: Pubkey,
+    /// The client used to backfill gaps; the main client unless an archive node is configured.
+    backfill_rpc_client: Arc<dyn SolanaRpc>,
+}
+
+/// A transaction fetched during catch-up, decoded but not yet published.
+struct FetchedTransaction {
+    slot: u64,
+    signature: String,
+    /// The position of the transaction within its block, if known.
+    tx_index: Option<u32>,
+    block_time: Option<i64>,
+    events: Vec<BridgeEvent>,
+}
+
+/// The signature list of the block fetched last, to locate transactions within it.
+///
+/// Catch-up goes through transactions oldest first, so the program's transactions
+/// in one slot follow each other and a single block is enough to remember.
+#[derive(Default)]
+struct BlockSignatures {
+    slot: Option<u64>,
+    /// `None` if the block could not be fetched.
+    signatures: Option<Vec<String>>,
+}
+
+/// The signatures found since the last synced one.
+struct NewSignatures {
+    /// Ordered from oldest to newest.
+    signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    /// Set if the last synced signature was not found in the RPC node's history.
+    gap: Option<SyncGap>,
 }
 
 impl CatchupWorker {
     pub fn new(ctx: WorkerContext) -> Self {
         let program_id = w3b2_bridge_program::ID;
-        Self { ctx, program_id }
+        let backfill_rpc_client: Arc<dyn SolanaRpc> =
+            match &ctx.config.synchronizer.backfill_rpc_url {
+                Some(url) => Arc::new(
+                    RpcPool::new(
+                        vec![url.clone()],
+                        Duration::from_secs(ctx.config.solana.health_check_interval_secs),
+                        ctx.config.solana.rate_limit.as_ref(),
+                    )
+                    .rpc_client(CommitmentConfig {
+                        commitment: ctx.config.solana.commitment,
+                    }),
+                ),
+                None => ctx.rpc_client.clone(),
+            };
+        Self {
+            ctx,
+            program_id,
+            backfill_rpc_client,
+        }
     }
 
     /// Runs the main catch-up loop.
@@ -29,30 +91,78 @@ impl CatchupWorker {
             let poll_interval = self.ctx.config.synchronizer.poll_interval_secs;
 
             tokio::select! {
-                _ = sleep(Duration::from_secs(poll_interval)) => {
-                    let signatures = self.fetch_new_signatures().await?;
-                    if !signatures.is_empty() {
-                        tracing::info!("Found {} new signatures to process.", signatures.len());
-                        self.process_signatures(signatures).await?;
-                    }
+                _ = sleep(Duration::from_secs(poll_interval)) => self.poll().await?,
+                _ = self.ctx.catchup_trigger.notified() => {
+                    tracing::info!("CatchupWorker: catch-up requested, polling now.");
+                    self.poll().await?;
                 }
                 // If the broadcast channel is closed, it means we are shutting down.
                 _ = self.ctx.event_sender.closed() => {
                     tracing::info!("CatchupWorker: event channel closed, shutting down.");
                     return Ok(());
                 }
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("CatchupWorker: shutdown requested, stopping.");
+                    return Ok(());
+                }
             }
         }
     }
 
+    /// Processes every transaction since the last synced one.
+    ///
+    /// A poll that gets through all of them completes the catch-up requests made
+    /// before it started, letting the live worker move the sync state again.
+    async fn poll(&self) -> Result<()> {
+        let request = self.ctx.catchup_requested.load(Ordering::SeqCst);
+        match self.ctx.rpc_client.get_slot().await {
+            Ok(slot) => self.ctx.metrics.observe_chain_slot(slot),
+            Err(e) => tracing::warn!("Failed to get the current slot: {}", e),
+        }
+        let NewSignatures { signatures, gap } = self.fetch_new_signatures().await?;
+        // Heal the gap first so events are still published oldest first.
+        if let Some(gap) = gap {
+            self.heal_gap(gap).await?;
+        }
+        let complete = if signatures.is_empty() {
+            true
+        } else {
+            tracing::info!("Found {} new signatures to process.", signatures.len());
+            self.process_signatures(signatures).await?
+        };
+        if complete {
+            self.ctx
+                .catchup_completed
+                .fetch_max(request, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
     /// Fetches signatures in pages until it finds the last one we processed.
-    async fn fetch_new_signatures(
-        &self,
-    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    ///
+    /// On a fresh node, the configured `StartPoint` decides how far back to go.
+    async fn fetch_new_signatures(&self) -> Result<NewSignatures> {
         let mut before_sig: Option<Signature> = None;
-        let last_known_sig = self.ctx.storage.get_last_sig().await?;
+        let stored_sig = self.ctx.storage.get_last_sig().await?;
+        let start_from = &self.ctx.config.synchronizer.start_from;
         let mut signatures_to_process = Vec::new();
 
+        if stored_sig.is_none() && *start_from == StartPoint::Latest {
+            self.skip_history().await?;
+            return Ok(NewSignatures {
+                signatures: Vec::new(),
+                gap: None,
+            });
+        }
+        let last_known_sig = stored_sig.or_else(|| match start_from {
+            StartPoint::Signature(sig) => Some(sig.clone()),
+            _ => None,
+        });
+        let min_slot = match start_from {
+            StartPoint::Slot(slot) if last_known_sig.is_none() => Some(*slot),
+            _ => None,
+        };
+
         tracing::info!(
             "Starting catch-up from last known signature: {:?}",
             last_known_sig
@@ -82,88 +192,385 @@ impl CatchupWorker {
             if let Some(ref last_sig) = last_known_sig {
                 if let Some(pos) = sigs.iter().position(|s| &s.signature == last_sig) {
                     signatures_to_process.extend_from_slice(&sigs[..pos]);
-                    break 'fetch_loop;
+                    return Ok(NewSignatures {
+                        signatures: oldest_first(signatures_to_process),
+                        gap: None,
+                    });
+                }
+            }
+            if let Some(min_slot) = min_slot {
+                if let Some(pos) = sigs.iter().position(|s| s.slot < min_slot) {
+                    signatures_to_process.extend_from_slice(&sigs[..pos]);
+                    return Ok(NewSignatures {
+                        signatures: oldest_first(signatures_to_process),
+                        gap: None,
+                    });
                 }
             }
             signatures_to_process.extend(sigs);
         }
 
-        // Process from oldest to newest.
-        signatures_to_process.reverse();
-        Ok(signatures_to_process)
+        // The history ran out before reaching the last synced signature, so everything
+        // between it and the oldest signature still served is unaccounted for.
+        let gap = match (last_known_sig, signatures_to_process.last()) {
+            (Some(last_sig), Some(oldest)) => Some(SyncGap {
+                reason: GapReason::HistoryTruncated,
+                from_slot: self.ctx.storage.get_last_slot().await?,
+                to_slot: oldest.slot,
+                after_signature: Some(last_sig),
+                before_signature: Some(oldest.signature.clone()),
+                backfilled: 0,
+            }),
+            _ => None,
+        };
+        Ok(NewSignatures {
+            signatures: oldest_first(signatures_to_process),
+            gap,
+        })
+    }
+
+    /// Records the program's newest transaction as the sync checkpoint without
+    /// processing anything, so indexing starts from the current tip.
+    async fn skip_history(&self) -> Result<()> {
+        let sig_config = GetConfirmedSignaturesForAddress2Config {
+            before: None,
+            until: None,
+            limit: Some(1),
+            commitment: Some(CommitmentConfig {
+                commitment: self.ctx.config.solana.commitment,
+            }),
+        };
+        let newest = self
+            .ctx
+            .rpc_client
+            .get_signatures_for_address_with_config(&self.program_id, sig_config)
+            .await?;
+        if let Some(newest) = newest.first() {
+            tracing::info!(
+                "Skipping program history, indexing from slot {} onwards.",
+                newest.slot
+            );
+            self.ctx
+                .storage
+                .set_sync_state(newest.slot, &newest.signature)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Tries to recover the transactions inside a `HistoryTruncated` gap from the
+    /// backfill RPC node, then reports the gap with the number of transactions recovered.
+    ///
+    /// Backfilled transactions don't move the sync state, which already points past them.
+    async fn heal_gap(&self, mut gap: SyncGap) -> Result<()> {
+        let mut before_sig = gap
+            .before_signature
+            .as_deref()
+            .and_then(|s| s.parse::<Signature>().ok());
+        let until_sig = gap
+            .after_signature
+            .as_deref()
+            .and_then(|s| s.parse::<Signature>().ok());
+        let mut missing = Vec::new();
+
+        loop {
+            let sig_config = GetConfirmedSignaturesForAddress2Config {
+                before: before_sig,
+                until: until_sig,
+                limit: Some(self.ctx.config.synchronizer.max_signature_fetch),
+                commitment: Some(CommitmentConfig {
+                    commitment: self.ctx.config.solana.commitment,
+                }),
+            };
+            let sigs = match self
+                .backfill_rpc_client
+                .get_signatures_for_address_with_config(&self.program_id, sig_config)
+                .await
+            {
+                Ok(sigs) => sigs,
+                Err(e) => {
+                    tracing::error!("Backfill of sync gap failed: {}", e);
+                    break;
+                }
+            };
+            if sigs.is_empty() {
+                break;
+            }
+            before_sig = sigs.last().and_then(|s| s.signature.parse().ok());
+            missing.extend(sigs);
+        }
+
+        gap.backfilled = self
+            .fetch_and_publish(
+                self.backfill_rpc_client.as_ref(),
+                oldest_first(missing),
+                false,
+            )
+            .await?;
+
+        tracing::warn!(
+            "Sync gap ({:?}) between slots {} and {}: backfilled {} transactions.",
+            gap.reason,
+            gap.from_slot,
+            gap.to_slot,
+            gap.backfilled
+        );
+        let _ = self.ctx.gap_sender.send(gap);
+        Ok(())
     }
 
-    /// Iterates through a batch of signatures and processes each one individually.
+    /// Processes the new signatures, skipping those older than `max_catchup_depth`.
+    /// Returns whether every kept signature was published.
     async fn process_signatures(
         &self,
+        mut signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    ) -> Result<bool> {
+        if let Some(max_depth) = self.ctx.config.synchronizer.max_catchup_depth {
+            let current_slot = self.ctx.rpc_client.get_slot().await?;
+            let min_slot = current_slot.saturating_sub(max_depth);
+            // Signatures are ordered oldest first, so the skipped ones form a prefix.
+            let kept = signatures.split_off(signatures.partition_point(|s| s.slot < min_slot));
+            if let (Some(first), Some(last)) = (signatures.first(), signatures.last()) {
+                tracing::debug!(
+                    "Skipping {} signatures due to max_catchup_depth",
+                    signatures.len()
+                );
+                self.report_skipped(SyncGap {
+                    reason: GapReason::CatchupDepthExceeded,
+                    from_slot: first.slot,
+                    to_slot: last.slot,
+                    after_signature: None,
+                    before_signature: kept.first().map(|s| s.signature.clone()),
+                    backfilled: 0,
+                });
+            }
+            signatures = kept;
+        }
+
+        let total = signatures.len();
+        let published = self
+            .fetch_and_publish(self.ctx.rpc_client.as_ref(), signatures, true)
+            .await?;
+        Ok(published == total)
+    }
+
+    /// Reports transactions skipped because of `max_catchup_depth`.
+    fn report_skipped(&self, gap: SyncGap) {
+        tracing::warn!(
+            "Skipped transactions between slots {} and {} due to max_catchup_depth.",
+            gap.from_slot,
+            gap.to_slot
+        );
+        let _ = self.ctx.gap_sender.send(gap);
+    }
+
+    /// Fetches the transactions of `signatures` with up to `fetch_concurrency`
+    /// requests in flight, and publishes their events strictly in the given order.
+    ///
+    /// With `advance_sync_state`, each published transaction becomes the new sync
+    /// checkpoint. A transaction that cannot be fetched stops the run, so the
+    /// checkpoint never moves past it and the next poll starts over from there.
+    /// Returns the number of transactions published.
+    async fn fetch_and_publish(
+        &self,
+        rpc_client: &dyn SolanaRpc,
         signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
-    ) -> Result<()> {
-        let current_slot = self.ctx.rpc_client.get_slot().await?;
+        advance_sync_state: bool,
+    ) -> Result<usize> {
+        let concurrency = self.ctx.config.synchronizer.fetch_concurrency.max(1);
+        // Collected up front: futures are lazy, and mapping inside the stream trips
+        // rustc's `Send` inference for the spawned synchronizer task.
+        let fetches: Vec<_> = signatures
+            .iter()
+            .map(|sig_info| self.fetch_transaction(rpc_client, sig_info))
+            .collect();
+        let mut fetched = stream::iter(fetches).buffered(concurrency);
 
-        for sig_info in signatures {
-            if let Some(max_depth) = self.ctx.config.synchronizer.max_catchup_depth {
-                if sig_info.slot < current_slot.saturating_sub(max_depth) {
-                    tracing::debug!(
-                        "Skipping {} from slot {} due to max_catchup_depth",
+        let mut count = 0;
+        let mut remaining = signatures.len();
+        let mut block = BlockSignatures::default();
+        self.ctx.metrics.set_catchup_remaining(remaining);
+        for sig_info in &signatures {
+            let Some(fetch) = fetched.next().await else {
+                break;
+            };
+            let mut tx = match fetch {
+                Ok(tx) => tx,
+                Err(e) => {
+                    tracing::warn!(
+                        "Stopping catch-up before transaction {}, which could not be fetched: {}",
                         sig_info.signature,
-                        sig_info.slot
+                        e
                     );
-                    continue;
+                    return Ok(count);
                 }
+            };
+            if !tx.events.is_empty() {
+                tx.tx_index = self
+                    .tx_index(rpc_client, &mut block, tx.slot, &tx.signature)
+                    .await;
             }
+            self.publish_transaction(tx, advance_sync_state).await?;
+            count += 1;
+            remaining -= 1;
+            self.ctx.metrics.set_catchup_remaining(remaining);
+        }
+        Ok(count)
+    }
 
-            self.process_one_transaction(&sig_info).await?;
+    /// Returns the position of transaction `signature` within block `slot`, fetching
+    /// the block's signature list unless `block` already holds it.
+    ///
+    /// Returns `None` if the block is not available, e.g. at `processed` commitment
+    /// or on a node that no longer serves it.
+    async fn tx_index(
+        &self,
+        rpc_client: &dyn SolanaRpc,
+        block: &mut BlockSignatures,
+        slot: u64,
+        signature: &str,
+    ) -> Option<u32> {
+        if block.slot != Some(slot) {
+            let config = RpcBlockConfig {
+                transaction_details: Some(TransactionDetails::Signatures),
+                rewards: Some(false),
+                commitment: Some(CommitmentConfig {
+                    commitment: self.ctx.config.solana.commitment,
+                }),
+                max_supported_transaction_version: Some(0),
+                ..Default::default()
+            };
+            block.slot = Some(slot);
+            block.signatures = match rpc_client.get_block_with_config(slot, config).await {
+                Ok(fetched) => fetched.signatures,
+                Err(e) => {
+                    tracing::debug!("Failed to get block {}: {}", slot, e);
+                    None
+                }
+            };
         }
-        Ok(())
+        let position = block
+            .signatures
+            .as_ref()?
+            .iter()
+            .position(|s| s == signature)?;
+        u32::try_from(position).ok()
     }
 
-    /// Fetches a single transaction, parses its logs for events, and sends them.
-    async fn process_one_transaction(
+    /// Fetches a single transaction and parses its logs for events.
+    ///
+    /// Failed requests are retried with backoff, up to `FETCH_ATTEMPTS` in total.
+    #[tracing::instrument(skip_all, fields(signature = %sig_info.signature, slot = sig_info.slot))]
+    async fn fetch_transaction(
         &self,
+        rpc_client: &dyn SolanaRpc,
         sig_info: &RpcConfirmedTransactionStatusWithSignature,
-    ) -> Result<()> {
+    ) -> Result<FetchedTransaction> {
         let sig = sig_info.signature.parse::<Signature>()?;
-        let tx_config = RpcTransactionConfig {
-            encoding: Some(UiTransactionEncoding::Base64),
-            commitment: Some(CommitmentConfig {
-                commitment: self.ctx.config.solana.commitment,
-            }),
-            max_supported_transaction_version: Some(0),
+        let solana = &self.ctx.config.solana;
+        let mut backoff = Backoff::new(
+            Duration::from_millis(solana.reconnect_min_backoff_ms),
+            Duration::from_millis(solana.reconnect_max_backoff_ms),
+        );
+        let tx = loop {
+            match rpc_client
+                .get_transaction_with_config(&sig, self.ctx.transaction_config())
+                .await
+            {
+                Ok(tx) => break tx,
+                Err(e) if backoff.attempts() + 1 < FETCH_ATTEMPTS => {
+                    let delay = backoff.next_delay();
+                    tracing::warn!(
+                        "Failed to get transaction {}, retrying in {:?}: {}",
+                        sig,
+                        delay,
+                        e
+                    );
+                    sleep(delay).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
         };
 
-        match self
-            .ctx
-            .rpc_client
-            .get_transaction_with_config(&sig, tx_config)
-            .await
-        {
-            Ok(tx) => {
-                if let Some(meta) = tx.transaction.meta {
-                    if let solana_transaction_status::option_serializer::OptionSerializer::Some(
-                        logs,
-                    ) = meta.log_messages
-                    {
-                        for log in logs {
-                            if let Ok(event) = try_parse_log(&log) {
-                                if !matches!(event, BridgeEvent::Unknown) {
-                                    if self.ctx.event_sender.send(event).is_err() {
-                                        tracing::warn!(
-                                            "No active receivers for broadcast channel."
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        if !touches_filtered_accounts(&self.ctx.config.filter, &tx) {
+            return Ok(FetchedTransaction {
+                slot: tx.slot,
+                signature: sig_info.signature.clone(),
+                tx_index: None,
+                block_time: tx.block_time,
+                events: Vec::new(),
+            });
+        }
+        let (events, failures) = parse_transaction(&tx);
+        self.ctx.metrics.record_decode_failures(failures);
+        let logs = transaction_logs(&tx);
+        let events = events
+            .into_iter()
+            .map(|event| event.with_source(Some(&sig_info.signature), logs))
+            .collect();
+        Ok(FetchedTransaction {
+            slot: tx.slot,
+            signature: sig_info.signature.clone(),
+            tx_index: None,
+            block_time: tx.block_time,
+            events,
+        })
+    }
 
-                self.ctx
-                    .storage
-                    .set_sync_state(tx.slot, &sig_info.signature)
-                    .await?;
+    /// Publishes the events of a fetched transaction.
+    #[tracing::instrument(
+        name = "catchup_transaction",
+        skip_all,
+        fields(slot = tx.slot, signature = %tx.signature, events = tx.events.len())
+    )]
+    async fn publish_transaction(
+        &self,
+        tx: FetchedTransaction,
+        advance_sync_state: bool,
+    ) -> Result<()> {
+        for (index, event) in tx.events.into_iter().enumerate() {
+            let envelope = EventEnvelope {
+                slot: tx.slot,
+                signature: tx.signature.clone(),
+                index: index as u32,
+                tx_index: tx.tx_index,
+                block_time: tx.block_time,
+                event,
+            };
+            if !self.ctx.publish(envelope).await? {
+                tracing::warn!("No active receivers for broadcast channel.");
             }
-            Err(e) => tracing::error!("Failed to get transaction {}: {}", sig, e),
+        }
+
+        if advance_sync_state {
+            self.ctx
+                .storage
+                .set_sync_state(tx.slot, &tx.signature)
+                .await?;
         }
         Ok(())
     }
 }
+
+/// Returns whether `tx` involves any account of `filter`, counting accounts loaded
+/// from lookup tables. Transactions that cannot be decoded are let through.
+fn touches_filtered_accounts(
+    filter: &IngestionFilter,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> bool {
+    if !filter.is_enabled() {
+        return true;
+    }
+    match transaction_account_keys(tx) {
+        Some(keys) => filter.matches(&keys),
+        None => true,
+    }
+}
+
+/// Reverses a newest-first signature list, as returned by the RPC node.
+fn oldest_first(
+    mut signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+) -> Vec<RpcConfirmedTransactionStatusWithSignature> {
+    signatures.reverse();
+    signatures
+}
@@ -0,0 +1,65 @@
+use std::collections::{HashSet, VecDeque};
+
+/// The most recently published events, identified by transaction signature and
+/// their index within it.
+///
+/// The catch-up and live workers cover overlapping slots around the point where
+/// one hands over to the other, so both may decode the same transaction. The
+/// synchronizer publishes an event only the first time it enters the window, so
+/// downstream services don't act twice on it (e.g. execute a paid command twice).
+pub struct DedupWindow {
+    seen: HashSet<(String, u32)>,
+    /// The keys of `seen`, oldest first.
+    order: VecDeque<(String, u32)>,
+    capacity: usize,
+}
+
+impl DedupWindow {
+    /// Creates an empty window remembering up to `capacity` events. A capacity of
+    /// zero disables deduplication.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns whether the window holds the event at `index` in transaction
+    /// `signature`, without recording it.
+    pub fn contains(&self, signature: &str, index: u32) -> bool {
+        self.seen.contains(&(signature.to_string(), index))
+    }
+
+    /// Records the event at `index` in transaction `signature`. Returns `false` if
+    /// the window already holds it, i.e. the event is a duplicate.
+    ///
+    /// Once full, the window forgets its oldest event to make room.
+    pub fn insert(&mut self, signature: &str, index: u32) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        let key = (signature.to_string(), index);
+        if self.seen.contains(&key) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+
+    /// Returns the number of events currently remembered.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns whether the window remembers no events.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
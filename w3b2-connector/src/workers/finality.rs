@@ -0,0 +1,186 @@
+use anyhow::Result;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    signature::Signature,
+};
+use std::collections::BTreeMap;
+use tokio::sync::broadcast;
+use tokio::time::{interval, Duration};
+
+use crate::{
+    events::{EventEnvelope, Revocation},
+    rpc::SolanaRpc,
+    storage::Storage,
+    workers::WorkerContext,
+};
+
+/// The most signatures a single `getSignatureStatuses` request may carry.
+const MAX_STATUSES_PER_REQUEST: usize = 256;
+
+/// Re-validates published events at `finalized` commitment and revokes those whose
+/// transaction never finalizes.
+///
+/// Only runs when the connector publishes at a lower commitment than `Finalized`.
+pub struct FinalityWorker {
+    ctx: WorkerContext,
+    envelope_rx: broadcast::Receiver<EventEnvelope>,
+}
+
+impl FinalityWorker {
+    pub fn new(ctx: WorkerContext) -> Self {
+        // Subscribe right away so nothing published before `run` is missed.
+        let envelope_rx = ctx.event_sender.subscribe();
+        Self { ctx, envelope_rx }
+    }
+
+    pub async fn run(mut self) -> Result<()> {
+        if self.ctx.config.solana.commitment == CommitmentLevel::Finalized {
+            return Ok(());
+        }
+
+        let sync_config = &self.ctx.config.synchronizer;
+        let mut tracker = FinalityTracker::new(sync_config.finality_timeout_slots);
+        let mut poll = interval(Duration::from_secs(sync_config.finality_poll_interval_secs));
+        // Events stored before a restart may not have finalized yet either.
+        self.track_recent(&mut tracker).await?;
+
+        loop {
+            tokio::select! {
+                res = self.envelope_rx.recv() => match res {
+                    Ok(envelope) => tracker.track(envelope),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "FinalityWorker lagged by {} events, re-reading them from storage.",
+                            skipped
+                        );
+                        self.track_recent(&mut tracker).await?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+                _ = poll.tick() => {
+                    match tracker.check(self.ctx.rpc_client.as_ref(), self.ctx.storage.as_ref()).await {
+                        Ok(revocations) => {
+                            for revocation in revocations {
+                                tracing::warn!(
+                                    "Revoking event from {} at slot {}: transaction did not finalize.",
+                                    revocation.envelope.signature,
+                                    revocation.envelope.slot
+                                );
+                                let _ = self.ctx.revocation_sender.send(revocation);
+                            }
+                        }
+                        Err(e) => tracing::error!("Failed to check event finality: {}", e),
+                    }
+                }
+                _ = self.ctx.event_sender.closed() => {
+                    tracing::info!("FinalityWorker: event channel closed, shutting down.");
+                    return Ok(());
+                }
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("FinalityWorker: shutdown requested, stopping.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Tracks the stored events within `finality_timeout_slots` of the last synced slot.
+    async fn track_recent(&self, tracker: &mut FinalityTracker) -> Result<()> {
+        let last_slot = self.ctx.storage.get_last_slot().await?;
+        let from_slot = last_slot.saturating_sub(tracker.timeout_slots);
+        for envelope in self
+            .ctx
+            .storage
+            .events_by_slot_range(from_slot, u64::MAX)
+            .await?
+        {
+            tracker.track(envelope);
+        }
+        Ok(())
+    }
+}
+
+/// The set of published events whose transactions have not been seen finalized yet.
+pub struct FinalityTracker {
+    pending: BTreeMap<Signature, Vec<EventEnvelope>>,
+    timeout_slots: u64,
+}
+
+impl FinalityTracker {
+    /// Creates an empty tracker. A transaction is given up on once the finalized
+    /// slot is more than `timeout_slots` past the slot it was seen in.
+    pub fn new(timeout_slots: u64) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            timeout_slots,
+        }
+    }
+
+    /// Starts tracking `envelope` until its transaction finalizes or is dropped.
+    pub fn track(&mut self, envelope: EventEnvelope) {
+        let Ok(signature) = envelope.signature.parse::<Signature>() else {
+            tracing::warn!(
+                "Not tracking event with invalid signature {}",
+                envelope.signature
+            );
+            return;
+        };
+        let envelopes = self.pending.entry(signature).or_default();
+        if !envelopes.iter().any(|e| e.index == envelope.index) {
+            envelopes.push(envelope);
+        }
+    }
+
+    /// Returns the number of transactions still awaiting finalization.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Queries the status of every pending transaction once.
+    ///
+    /// Finalized transactions stop being tracked. Transactions still not finalized
+    /// once the finalized slot has moved `timeout_slots` past them are considered
+    /// dropped: their events are removed from `storage` and returned as revocations.
+    pub async fn check(
+        &mut self,
+        rpc_client: &dyn SolanaRpc,
+        storage: &dyn Storage,
+    ) -> Result<Vec<Revocation>> {
+        if self.pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let finalized_slot = rpc_client
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .await?;
+        let signatures: Vec<Signature> = self.pending.keys().copied().collect();
+        let mut revocations = Vec::new();
+
+        for chunk in signatures.chunks(MAX_STATUSES_PER_REQUEST) {
+            let statuses = rpc_client
+                .get_signature_statuses_with_history(chunk)
+                .await?
+                .value;
+            for (signature, status) in chunk.iter().zip(statuses) {
+                if status.is_some_and(|s| s.satisfies_commitment(CommitmentConfig::finalized())) {
+                    self.pending.remove(signature);
+                    continue;
+                }
+
+                let seen_slot = self.pending[signature][0].slot;
+                if seen_slot.saturating_add(self.timeout_slots) >= finalized_slot {
+                    continue;
+                }
+                let envelopes = self.pending.remove(signature).unwrap_or_default();
+                storage
+                    .remove_events_by_signature(&signature.to_string())
+                    .await?;
+                revocations.extend(envelopes.into_iter().map(|envelope| Revocation {
+                    envelope,
+                    finalized_slot,
+                }));
+            }
+        }
+        Ok(revocations)
+    }
+}
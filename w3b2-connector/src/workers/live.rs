@@ -1,28 +1,88 @@
 use anyhow::Result;
 use solana_client::{
-    nonblocking::pubsub_client::PubsubClient,
     rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
-    rpc_response::Response,
+    rpc_response::{Response, RpcLogsResponse},
 };
-use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use tokio::time::{sleep, Duration};
 use tokio_stream::StreamExt;
 
+use crate::backoff::Backoff;
+use crate::dispatcher::extract_pubkeys_from_event;
+use crate::events::{logs_incomplete, parse_logs, parse_transaction, BridgeEvent, EventEnvelope};
+use crate::rpc_pool::connect_pubsub;
 use crate::workers::WorkerContext;
 
 pub struct LiveWorker {
     ctx: WorkerContext,
 }
 
+/// How a WebSocket session of the live worker ended.
+enum Session {
+    /// The subscription dropped or could not be established.
+    Disconnected,
+    /// The worker should stop.
+    Stopped,
+}
+
 impl LiveWorker {
     pub fn new(ctx: WorkerContext) -> Self {
         Self { ctx }
     }
 
     /// Subscribes to new logs via WebSocket and processes them in real-time.
+    ///
+    /// When the subscription drops, it is re-established after a jittered
+    /// exponential backoff, and the catch-up worker is woken to fetch whatever was
+    /// missed in between. Events keep flowing meanwhile, but the sync state only
+    /// moves once that catch-up is done.
     pub async fn run(self) -> Result<()> {
-        let client = PubsubClient::new(&self.ctx.config.solana.ws_url).await?;
+        let solana = &self.ctx.config.solana;
+        let mut backoff = Backoff::new(
+            Duration::from_millis(solana.reconnect_min_backoff_ms),
+            Duration::from_millis(solana.reconnect_max_backoff_ms),
+        );
+        let mut reconnecting = false;
+
+        loop {
+            if let Session::Stopped = self.listen(&mut backoff, reconnecting).await? {
+                return Ok(());
+            }
+            reconnecting = true;
 
-        let (mut stream, _) = client
+            let delay = backoff.next_delay();
+            tracing::warn!(
+                attempt = backoff.attempts(),
+                "LiveWorker: WebSocket subscription lost, reconnecting in {:?}.",
+                delay
+            );
+            tokio::select! {
+                _ = sleep(delay) => {},
+                _ = self.ctx.event_sender.closed() => {
+                    tracing::info!("LiveWorker: event channel closed, shutting down.");
+                    return Ok(());
+                },
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("LiveWorker: shutdown requested, stopping.");
+                    return Ok(());
+                },
+            }
+        }
+    }
+
+    /// Connects, subscribes and processes logs until the subscription drops or the
+    /// worker should stop. Connection failures end the session; only failures to
+    /// process an event are returned as errors.
+    async fn listen(&self, backoff: &mut Backoff, reconnecting: bool) -> Result<Session> {
+        let client = match connect_pubsub(&self.ctx.config.solana).await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!("LiveWorker: failed to connect to WebSocket: {}", e);
+                return Ok(Session::Disconnected);
+            }
+        };
+
+        let subscription = client
             .logs_subscribe(
                 RpcTransactionLogsFilter::Mentions(vec![w3b2_bridge_program::ID.to_string()]),
                 RpcTransactionLogsConfig {
@@ -31,43 +91,121 @@ impl LiveWorker {
                     }),
                 },
             )
-            .await?;
+            .await;
+        let (mut stream, _) = match subscription {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                tracing::warn!("LiveWorker: failed to subscribe to logs: {}", e);
+                return Ok(Session::Disconnected);
+            }
+        };
 
-        tracing::info!("Live worker connected to WebSocket and listening for logs.");
+        backoff.reset();
+        if reconnecting {
+            tracing::info!("Live worker reconnected; catching up on the missed window.");
+            self.ctx.metrics.record_reconnect();
+            self.ctx.request_catchup();
+        } else {
+            tracing::info!("Live worker connected to WebSocket and listening for logs.");
+        }
 
         loop {
             tokio::select! {
-                Some(msg) = stream.next() => {
-                    let Response { context, value } = msg;
-                    let slot = context.slot;
-
-                    if slot <= self.ctx.storage.get_last_slot().await? {
-                        continue;
+                msg = stream.next() => {
+                    let Some(Response { context, value }) = msg else {
+                        return Ok(Session::Disconnected);
+                    };
+                    self.ctx.metrics.observe_chain_slot(context.slot);
+                    if !self.process_logs(context.slot, value).await? {
+                        tracing::warn!("No active receivers for broadcast channel. Shutting down LiveWorker.");
+                        return Ok(Session::Stopped);
                     }
-
-                    for log in value.logs {
-                        if let Ok(event) = crate::events::try_parse_log(&log) {
-                            if !matches!(event, crate::events::BridgeEvent::Unknown) {
-                                tracing::info!("[LIVE] slot={} event={:?}", slot, event);
-                                if self.ctx.event_sender.send(event).is_err() {
-                                    tracing::warn!("No active receivers for broadcast channel. Shutting down LiveWorker.");
-                                    return Ok(());
-                                }
-                            }
-                        }
-                    }
-                    self.ctx
-                        .storage
-                        .set_sync_state(slot, &value.signature)
-                        .await?;
                 },
                 _ = self.ctx.event_sender.closed() => {
                     tracing::info!("LiveWorker: event channel closed, shutting down.");
-                    return Ok(());
+                    return Ok(Session::Stopped);
                 },
-                else => break,
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("LiveWorker: shutdown requested, stopping.");
+                    return Ok(Session::Stopped);
+                },
+            }
+        }
+    }
+
+    /// Decodes and publishes the events of one transaction's logs, then advances
+    /// the sync state past it once catch-up is complete. Returns `false` if the event
+    /// channel has no receivers.
+    #[tracing::instrument(name = "live_transaction", skip_all, fields(slot, signature = %logs.signature))]
+    async fn process_logs(&self, slot: u64, logs: RpcLogsResponse) -> Result<bool> {
+        if slot <= self.ctx.storage.get_last_slot().await? {
+            return Ok(true);
+        }
+
+        let (events, failures) = if logs_incomplete(&logs.logs) {
+            self.decode_full_transaction(&logs).await
+        } else {
+            parse_logs(&logs.logs)
+        };
+        self.ctx.metrics.record_decode_failures(failures);
+        let events: Vec<BridgeEvent> = events
+            .into_iter()
+            .map(|event| event.with_source(Some(&logs.signature), &logs.logs))
+            .collect();
+        let filter = &self.ctx.config.filter;
+        let wanted = !filter.is_enabled()
+            || events
+                .iter()
+                .any(|event| filter.matches(&extract_pubkeys_from_event(event)));
+
+        for (index, event) in events.into_iter().enumerate().filter(|_| wanted) {
+            tracing::info!("[LIVE] slot={} event={:?}", slot, event);
+            let envelope = EventEnvelope {
+                slot,
+                signature: logs.signature.clone(),
+                index: index as u32,
+                tx_index: None,
+                block_time: None,
+                event,
+            };
+            if !self.ctx.publish(envelope).await? {
+                return Ok(false);
+            }
+        }
+        // Until catch-up has covered the time before this subscription, moving the
+        // sync state here would make it skip whatever was missed in between.
+        if self.ctx.caught_up() {
+            self.ctx
+                .storage
+                .set_sync_state(slot, &logs.signature)
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Decodes the events of a transaction whose logs may not carry all of them,
+    /// from the full transaction fetched over RPC. Falls back to the logs if the
+    /// transaction cannot be fetched, e.g. at `processed` commitment.
+    async fn decode_full_transaction(&self, logs: &RpcLogsResponse) -> (Vec<BridgeEvent>, usize) {
+        let fetched = match logs.signature.parse::<Signature>() {
+            Ok(signature) => self
+                .ctx
+                .rpc_client
+                .get_transaction_with_config(&signature, self.ctx.transaction_config())
+                .await
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(e.into()),
+        };
+        match fetched {
+            Ok(tx) => parse_transaction(&tx),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to fetch transaction {} with incomplete logs: {}",
+                    logs.signature,
+                    e
+                );
+                parse_logs(&logs.logs)
             }
         }
-        Ok(())
     }
 }
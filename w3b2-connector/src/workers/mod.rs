@@ -1,41 +1,185 @@
+mod account_watcher;
 mod catchup;
+mod dedup;
+mod finality;
 mod live;
+mod pruner;
+mod reorder;
 mod synchronizer;
 
+pub use account_watcher::{AccountWatcher, ProfileKind, ProfileUpdate};
+pub use dedup::DedupWindow;
+pub use finality::FinalityTracker;
+pub use pruner::prune;
+pub use reorder::ReorderBuffer;
+
 use crate::{
     config::ConnectorConfig,
-    dispatcher::{Dispatcher, DispatcherCommand},
-    events::BridgeEvent,
+    dispatcher::{Dispatcher, DispatcherCommand, LagStatus, ListenerOptions, ListenerRegistration},
+    events::{EventEnvelope, Revocation, SyncGap},
     listener::{AdminListener, UserListener},
-    storage::Storage,
+    metrics::{SyncMetrics, SyncStatus},
+    prices::PriceCache,
+    rpc::SolanaRpc,
+    storage::{Storage, StorageError},
+    subscription::DurableSubscription,
     workers::synchronizer::Synchronizer,
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
-use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch, Notify};
+use tokio_util::sync::CancellationToken;
 
 /// A shared context containing all dependencies required by the workers.
 #[derive(Clone)]
 struct WorkerContext {
     pub config: Arc<ConnectorConfig>,
     pub storage: Arc<dyn Storage>,
-    pub rpc_client: Arc<RpcClient>,
-    pub event_sender: broadcast::Sender<BridgeEvent>,
+    pub rpc_client: Arc<dyn SolanaRpc>,
+    pub event_sender: broadcast::Sender<EventEnvelope>,
+    /// Notifies about stretches of history the synchronizer could not process.
+    pub gap_sender: broadcast::Sender<SyncGap>,
+    /// Notifies about published events whose transaction never finalized.
+    pub revocation_sender: broadcast::Sender<Revocation>,
+    pub metrics: Arc<SyncMetrics>,
+    /// The events published most recently, shared by the catch-up and live workers.
+    pub dedup: Arc<Mutex<DedupWindow>>,
+    /// Puts the events of both workers back in chain order before they are broadcast.
+    pub reorder: Arc<Mutex<ReorderBuffer>>,
+    /// Cancelled when the workers should finish their current step and return.
+    pub shutdown: CancellationToken,
+    /// Wakes the catch-up worker before its next poll, e.g. to fetch what the live
+    /// worker missed while its subscription was down.
+    pub catchup_trigger: Arc<Notify>,
+    /// Counts the catch-ups the live worker waits for: one at startup and one per
+    /// reconnect.
+    pub catchup_requested: Arc<AtomicU64>,
+    /// The latest catch-up request covered by a complete catch-up poll.
+    pub catchup_completed: Arc<AtomicU64>,
 }
 
 impl WorkerContext {
     fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<dyn SolanaRpc>,
         storage: Arc<dyn Storage>,
-        event_sender: broadcast::Sender<BridgeEvent>,
+        event_sender: broadcast::Sender<EventEnvelope>,
+        gap_sender: broadcast::Sender<SyncGap>,
+        revocation_sender: broadcast::Sender<Revocation>,
     ) -> Self {
+        let dedup = DedupWindow::new(config.synchronizer.dedup_window);
+        let reorder = ReorderBuffer::new(
+            config.synchronizer.reorder_window,
+            Duration::from_millis(config.synchronizer.reorder_delay_ms),
+        );
         Self {
             config,
             storage,
             rpc_client,
             event_sender,
+            gap_sender,
+            revocation_sender,
+            metrics: Arc::new(SyncMetrics::new()),
+            dedup: Arc::new(Mutex::new(dedup)),
+            reorder: Arc::new(Mutex::new(reorder)),
+            shutdown: CancellationToken::new(),
+            catchup_trigger: Arc::new(Notify::new()),
+            catchup_requested: Arc::new(AtomicU64::new(1)),
+            catchup_completed: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Wakes the catch-up worker to fetch what the live worker may have missed, and
+    /// holds back the live worker's sync state updates until it has.
+    fn request_catchup(&self) {
+        self.catchup_requested.fetch_add(1, Ordering::SeqCst);
+        self.catchup_trigger.notify_one();
+    }
+
+    /// Returns whether every catch-up requested so far has completed, i.e. whether
+    /// the live worker may move the sync state without skipping missed transactions.
+    fn caught_up(&self) -> bool {
+        self.catchup_completed.load(Ordering::SeqCst)
+            >= self.catchup_requested.load(Ordering::SeqCst)
+    }
+
+    /// Returns the configuration for fetching a full transaction at the configured
+    /// commitment.
+    fn transaction_config(&self) -> RpcTransactionConfig {
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig {
+                commitment: self.config.solana.commitment,
+            }),
+            max_supported_transaction_version: Some(0),
+        }
+    }
+
+    /// Persists an event and then publishes it to all subscribers, through the
+    /// reorder buffer.
+    ///
+    /// Persisting first guarantees that anything a subscriber receives can also be
+    /// found in the history. Events still in the dedup window, i.e. already published
+    /// by the other worker, are skipped. An event only enters the window once it is
+    /// stored, so a publish retried after a storage failure is not mistaken for a
+    /// duplicate. Returns `false` if the main event channel has no receivers.
+    #[tracing::instrument(
+        skip_all,
+        fields(index = envelope.index, kind = ?envelope.event.kind(), pubkeys = ?envelope.pubkeys())
+    )]
+    async fn publish(&self, envelope: EventEnvelope) -> anyhow::Result<bool> {
+        let seen = self
+            .dedup
+            .lock()
+            .unwrap()
+            .contains(&envelope.signature, envelope.index);
+        if seen {
+            return Ok(self.skip_duplicate(&envelope));
+        }
+
+        self.storage.append_event(&envelope).await?;
+        // The other worker may have stored the same event meanwhile; storage is keyed
+        // by the event, so only the broadcast has to be skipped.
+        let is_new = self
+            .dedup
+            .lock()
+            .unwrap()
+            .insert(&envelope.signature, envelope.index);
+        if !is_new {
+            return Ok(self.skip_duplicate(&envelope));
+        }
+        self.metrics.record_event();
+        let ready = self
+            .reorder
+            .lock()
+            .unwrap()
+            .push(envelope, tokio::time::Instant::now());
+        self.broadcast(ready);
+        Ok(self.event_sender.receiver_count() > 0)
+    }
+
+    /// Counts an event already published by the other worker. Returns `false` if the
+    /// main event channel has no receivers.
+    fn skip_duplicate(&self, envelope: &EventEnvelope) -> bool {
+        tracing::debug!(
+            "Skipping duplicate event {}#{}",
+            envelope.signature,
+            envelope.index
+        );
+        self.metrics.record_duplicate();
+        self.event_sender.receiver_count() > 0
+    }
+
+    /// Sends events released by the reorder buffer to all subscribers.
+    fn broadcast(&self, events: Vec<EventEnvelope>) {
+        for envelope in events {
+            // Without receivers the workers stop once `publish` reports it.
+            let _ = self.event_sender.send(envelope);
         }
     }
 }
@@ -45,27 +189,124 @@ impl WorkerContext {
 #[derive(Clone)]
 pub struct EventManagerHandle {
     command_tx: mpsc::Sender<DispatcherCommand>,
+    next_listener_id: Arc<AtomicU64>,
+    event_tx: broadcast::Sender<EventEnvelope>,
+    gap_tx: broadcast::Sender<SyncGap>,
+    revocation_tx: broadcast::Sender<Revocation>,
+    storage: Arc<dyn Storage>,
+    metrics: Arc<SyncMetrics>,
+    shutdown: CancellationToken,
+    running: watch::Receiver<bool>,
 }
 
 impl EventManagerHandle {
     /// (Internal) Creates a raw, un-filtered subscription for a pubkey.
     /// This is the low-level building block for the high-level listeners.
+    ///
+    /// The subscription stays registered until the returned `ListenerRegistration`
+    /// is dropped or the receiver is found closed. Fails once the dispatcher has
+    /// stopped, e.g. after `stop`.
     async fn subscribe_raw(
         &self,
         pubkey: Pubkey,
         channel_capacity: usize,
-    ) -> mpsc::Receiver<BridgeEvent> {
+        options: ListenerOptions,
+    ) -> anyhow::Result<(ListenerRegistration, mpsc::Receiver<EventEnvelope>)> {
+        let id = self.next_listener_id.fetch_add(1, Ordering::Relaxed);
+        let lag_status = LagStatus::default();
         let (tx, rx) = mpsc::channel(channel_capacity);
         self.command_tx
-            .send(DispatcherCommand::Register(pubkey, tx))
+            .send(DispatcherCommand::Register {
+                pubkey,
+                id,
+                tx,
+                options,
+                lag_status: lag_status.clone(),
+            })
             .await
-            .expect("Dispatcher should always be running");
-        rx
+            .map_err(|_| anyhow::anyhow!("the event manager is stopped"))?;
+        Ok((
+            ListenerRegistration::new(pubkey, id, lag_status, self.command_tx.clone()),
+            rx,
+        ))
+    }
+
+    /// (Internal) Creates a subscription that first replays the stored history of
+    /// `pubkey` from `from_slot` onwards and then switches to live events.
+    ///
+    /// The live subscription is registered before the history is read, so no event
+    /// falls between the two. Events that show up in both are delivered once.
+    async fn subscribe_with_replay(
+        &self,
+        pubkey: Pubkey,
+        from_slot: u64,
+        channel_capacity: usize,
+    ) -> anyhow::Result<mpsc::Receiver<EventEnvelope>> {
+        // Events at or after the last synced slot may be stored and broadcast
+        // concurrently with the registration below.
+        let synced_slot = self.storage.get_last_slot().await?;
+        let (registration, mut live_rx) = self
+            .subscribe_raw(pubkey, channel_capacity, ListenerOptions::default())
+            .await?;
+        let history = self
+            .storage
+            .events_by_pubkey(&pubkey, from_slot, u64::MAX)
+            .await?;
+
+        let (tx, rx) = mpsc::channel(channel_capacity);
+        tokio::spawn(async move {
+            // Unregisters the live subscription once the receiver below is dropped.
+            let _registration = registration;
+            let mut recent = HashSet::new();
+            for envelope in history {
+                if envelope.slot >= synced_slot {
+                    recent.insert(envelope.cursor());
+                }
+                if tx.send(envelope).await.is_err() {
+                    return;
+                }
+            }
+            while let Some(envelope) = live_rx.recv().await {
+                if !recent.is_empty() && recent.remove(&envelope.cursor()) {
+                    continue;
+                }
+                if tx.send(envelope).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    /// Opens a durable subscription to the events involving `pubkey`.
+    ///
+    /// Delivery resumes from the last event `subscriber_id` acknowledged (or from
+    /// the start of the stored history for a new subscriber), replaying whatever
+    /// happened while it was disconnected before switching to live events.
+    ///
+    /// * `subscriber_id` - A stable identity for the subscriber, used as the cursor key.
+    /// * `pubkey` - The `ChainCard` public key to follow.
+    /// * `channel_capacity` - The buffer capacity of the subscription channel.
+    pub async fn subscribe_durable(
+        &self,
+        subscriber_id: &str,
+        pubkey: Pubkey,
+        channel_capacity: usize,
+    ) -> anyhow::Result<DurableSubscription> {
+        DurableSubscription::start(
+            subscriber_id.to_string(),
+            pubkey,
+            self.storage.clone(),
+            self.event_tx.subscribe(),
+            channel_capacity,
+        )
+        .await
     }
 
-    /// Unregisters a listener for a specific pubkey from the dispatcher.
+    /// Unregisters every listener for a specific pubkey from the dispatcher.
     ///
-    /// This should be called when a listener is no longer needed to prevent resource leaks.
+    /// This closes the streams of all listeners watching `pubkey`, including those
+    /// opened by other callers. To stop a single listener, drop it instead.
     pub async fn unsubscribe(&self, pubkey: Pubkey) {
         if self
             .command_tx
@@ -80,22 +321,64 @@ impl EventManagerHandle {
         }
     }
 
-    /// Sends a shutdown signal to the `EventManager`'s background services.
+    /// Returns an unfiltered receiver for every event the `Synchronizer` produces.
     ///
-    /// This will cause the `Dispatcher` and `Synchronizer` to gracefully terminate.
+    /// Intended for process-wide consumers such as caches or audit logs, which would
+    /// otherwise need a listener per pubkey.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.event_tx.subscribe()
+    }
+
+    /// Returns a receiver for `SyncGap` notifications.
+    ///
+    /// A gap means events between two points in history may be missing, even after
+    /// the synchronizer's own backfill attempt (see `SyncGap::backfilled`).
+    pub fn subscribe_gaps(&self) -> broadcast::Receiver<SyncGap> {
+        self.gap_tx.subscribe()
+    }
+
+    /// Returns a receiver for `Revocation` notices.
+    ///
+    /// Events are published at the configured commitment, which may be below
+    /// `Finalized`. A revocation means the transaction behind an already published
+    /// event was dropped with its fork, so any action taken on it should be undone.
+    pub fn subscribe_revocations(&self) -> broadcast::Receiver<Revocation> {
+        self.revocation_tx.subscribe()
+    }
+
+    /// Returns the latest `limit` published events involving `pubkey`, oldest
+    /// first, e.g. for a client to fill its view before it starts listening.
+    pub async fn recent_events(
+        &self,
+        pubkey: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        self.storage.recent_events_by_pubkey(pubkey, limit).await
+    }
+
+    /// Returns a snapshot of the synchronizer's progress, e.g. to alert when it
+    /// falls behind the chain. See `SyncStatus::to_prometheus` for exporting it.
+    pub async fn sync_status(&self) -> anyhow::Result<SyncStatus> {
+        let processed_slot = self.storage.get_last_slot().await?;
+        Ok(self.metrics.status(processed_slot))
+    }
+
+    /// Shuts down the `EventManager`'s background services and waits for them to finish.
+    ///
+    /// The `Synchronizer`'s workers complete the step they are in, so no event is
+    /// published without its sync state being recorded, and the storage is flushed.
+    /// The `Dispatcher` then routes the events already published and closes every
+    /// listener's channel. Returns right away if the runner is not running.
     pub async fn stop(&self) {
-        if self
-            .command_tx
-            .send(DispatcherCommand::Shutdown)
-            .await
-            .is_err()
-        {
-            tracing::warn!("Failed to send shutdown command. EventManager may already be down.");
-        }
+        self.shutdown.cancel();
+        let mut running = self.running.clone();
+        // Fails only if the runner was dropped, which means it is not running either.
+        let _ = running.wait_for(|running| !running).await;
     }
 
     /// Creates and returns a contextual listener for a User `ChainCard`.
     /// This is the primary method for users of the library to listen to their events.
+    /// Fails if the `EventManager` has been stopped.
     ///
     /// * `user_pubkey` - The public key of the user's `ChainCard` to monitor.
     /// * `channel_capacity` - The buffer capacity for the internal event channels.
@@ -103,14 +386,38 @@ impl EventManagerHandle {
         &self,
         user_pubkey: Pubkey,
         channel_capacity: usize,
-    ) -> UserListener {
+    ) -> anyhow::Result<UserListener> {
+        self.listen_as_user_with(user_pubkey, channel_capacity, ListenerOptions::default())
+            .await
+    }
+
+    /// Like `listen_as_user`, with explicit `ListenerOptions`, e.g. a backpressure
+    /// policy other than blocking.
+    ///
+    /// * `user_pubkey` - The public key of the user's `ChainCard` to monitor.
+    /// * `channel_capacity` - The buffer capacity for the internal event channels.
+    /// * `options` - How the dispatcher treats this listener.
+    pub async fn listen_as_user_with(
+        &self,
+        user_pubkey: Pubkey,
+        channel_capacity: usize,
+        options: ListenerOptions,
+    ) -> anyhow::Result<UserListener> {
         // 1. Get the raw event stream for the user's pubkey.
-        let raw_rx = self.subscribe_raw(user_pubkey, channel_capacity).await;
+        let (registration, raw_rx) = self
+            .subscribe_raw(user_pubkey, channel_capacity, options)
+            .await?;
         // 2. Construct the high-level listener that will consume and categorize the raw stream.
-        UserListener::new(user_pubkey, raw_rx, channel_capacity)
+        Ok(UserListener::start(
+            user_pubkey,
+            raw_rx,
+            Some(registration),
+            channel_capacity,
+        ))
     }
 
     /// Creates and returns a contextual listener for an Admin `ChainCard`.
+    /// Fails if the `EventManager` has been stopped.
     ///
     /// * `admin_pubkey` - The public key of the admin's `ChainCard` to monitor.
     /// * `channel_capacity` - The buffer capacity for the internal event channels.
@@ -118,65 +425,189 @@ impl EventManagerHandle {
         &self,
         admin_pubkey: Pubkey,
         channel_capacity: usize,
-    ) -> AdminListener {
+    ) -> anyhow::Result<AdminListener> {
+        self.listen_as_admin_with(admin_pubkey, channel_capacity, ListenerOptions::default())
+            .await
+    }
+
+    /// Like `listen_as_admin`, with explicit `ListenerOptions`, e.g. a backpressure
+    /// policy other than blocking.
+    ///
+    /// * `admin_pubkey` - The public key of the admin's `ChainCard` to monitor.
+    /// * `channel_capacity` - The buffer capacity for the internal event channels.
+    /// * `options` - How the dispatcher treats this listener.
+    pub async fn listen_as_admin_with(
+        &self,
+        admin_pubkey: Pubkey,
+        channel_capacity: usize,
+        options: ListenerOptions,
+    ) -> anyhow::Result<AdminListener> {
         // 1. Get the raw event stream for the admin's pubkey.
-        let raw_rx = self.subscribe_raw(admin_pubkey, channel_capacity).await;
+        let (registration, raw_rx) = self
+            .subscribe_raw(admin_pubkey, channel_capacity, options)
+            .await?;
         // 2. Construct the high-level listener.
-        AdminListener::new(admin_pubkey, raw_rx, channel_capacity)
+        Ok(AdminListener::start(
+            admin_pubkey,
+            raw_rx,
+            Some(registration),
+            channel_capacity,
+        ))
+    }
+
+    /// Like `listen_as_user`, but first replays every stored event for the user
+    /// from `from_slot` onwards, so a client that was offline misses nothing.
+    ///
+    /// * `user_pubkey` - The public key of the user's `ChainCard` to monitor.
+    /// * `from_slot` - The first slot to replay.
+    /// * `channel_capacity` - The buffer capacity for the internal event channels.
+    pub async fn replay_as_user(
+        &self,
+        user_pubkey: Pubkey,
+        from_slot: u64,
+        channel_capacity: usize,
+    ) -> anyhow::Result<UserListener> {
+        let raw_rx = self
+            .subscribe_with_replay(user_pubkey, from_slot, channel_capacity)
+            .await?;
+        Ok(UserListener::new(user_pubkey, raw_rx, channel_capacity))
+    }
+
+    /// Like `listen_as_admin`, but first replays every stored event for the admin
+    /// from `from_slot` onwards, so a service that was offline can process every
+    /// command it missed.
+    ///
+    /// * `admin_pubkey` - The public key of the admin's `ChainCard` to monitor.
+    /// * `from_slot` - The first slot to replay.
+    /// * `channel_capacity` - The buffer capacity for the internal event channels.
+    pub async fn replay_as_admin(
+        &self,
+        admin_pubkey: Pubkey,
+        from_slot: u64,
+        channel_capacity: usize,
+    ) -> anyhow::Result<AdminListener> {
+        let raw_rx = self
+            .subscribe_with_replay(admin_pubkey, from_slot, channel_capacity)
+            .await?;
+        Ok(AdminListener::new(admin_pubkey, raw_rx, channel_capacity))
     }
 }
 
-// The main background service runner.
-/// This struct is created once, its `run` method is spawned, and then it's consumed.
+/// The main background service runner.
+///
+/// Every consumer follows the same lifecycle:
+///
+/// 1. `EventManager::new` returns the runner together with an `EventManagerHandle`.
+/// 2. The runner's `run` method is spawned once, which consumes it.
+/// 3. The handle is cloned wherever events are needed (`listen_as_user`,
+///    `listen_as_admin`, `unsubscribe`, ...), and `stop` ends the background services.
 pub struct EventManager {
     synchronizer: Synchronizer,
     dispatcher: Dispatcher,
+    /// Stops the synchronizer's workers.
+    shutdown: CancellationToken,
+    /// Stops the dispatcher, once the workers are done publishing.
+    dispatcher_shutdown: CancellationToken,
+    running: watch::Sender<bool>,
 }
 
 impl EventManager {
+    /// Creates the runner and the handle controlling it. Nothing runs until the
+    /// runner's `run` method is spawned.
+    ///
+    /// * `broadcast_capacity` - The buffer capacity of the synchronizer's event channels.
+    /// * `command_capacity` - The buffer capacity of the dispatcher's command channel.
     pub fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<dyn SolanaRpc>,
         storage: Arc<dyn Storage>,
         broadcast_capacity: usize,
         command_capacity: usize,
     ) -> (Self, EventManagerHandle) {
         let (event_tx, event_rx) = broadcast::channel(broadcast_capacity);
+        let (gap_tx, _) = broadcast::channel(command_capacity);
+        let (revocation_tx, _) = broadcast::channel(broadcast_capacity);
         let (cmd_tx, cmd_rx) = mpsc::channel(command_capacity);
 
         let synchronizer = Synchronizer::new(
             config.clone(),
             rpc_client.clone(),
             storage.clone(),
-            event_tx,
+            event_tx.clone(),
+            gap_tx.clone(),
+            revocation_tx.clone(),
         );
 
-        let dispatcher = Dispatcher::new(event_rx, cmd_rx);
+        let metrics = synchronizer.metrics();
+        let shutdown = synchronizer.shutdown_token();
+        let dispatcher_shutdown = CancellationToken::new();
+        let dispatcher =
+            Dispatcher::new(event_rx, cmd_rx).with_shutdown(dispatcher_shutdown.clone());
+        let (running_tx, running_rx) = watch::channel(false);
 
         let runner = Self {
             synchronizer,
             dispatcher,
+            shutdown: shutdown.clone(),
+            dispatcher_shutdown,
+            running: running_tx,
         };
 
-        let handle = EventManagerHandle { command_tx: cmd_tx };
+        let handle = EventManagerHandle {
+            command_tx: cmd_tx,
+            next_listener_id: Arc::new(AtomicU64::new(0)),
+            event_tx,
+            gap_tx,
+            revocation_tx,
+            storage,
+            metrics,
+            shutdown,
+            running: running_rx,
+        };
 
         (runner, handle)
     }
 
+    /// Keeps `cache` up to date from the events this connector synchronizes.
+    pub fn with_price_cache(mut self, cache: Arc<PriceCache>) -> Self {
+        self.dispatcher = self.dispatcher.with_price_cache(cache);
+        self
+    }
+
     /// Runs all background services of the connector.
     /// This method should be spawned as a background task by the application.
-    pub async fn run(mut self) {
+    ///
+    /// Returns once both the synchronizer and the dispatcher have stopped, either
+    /// through `EventManagerHandle::stop` or because one of them exited on its own.
+    pub async fn run(self) {
+        let Self {
+            synchronizer,
+            mut dispatcher,
+            shutdown,
+            dispatcher_shutdown,
+            running,
+        } = self;
+        running.send_replace(true);
         tracing::info!("Connector is running all background services.");
-        // Run both workers concurrently. The select loop will exit when either
-        // of the workers finishes, which is the desired behavior for graceful shutdown.
-        tokio::select! {
-            res = self.synchronizer.run() => {
-                if let Err(e) = res { tracing::error!("Synchronizer exited with an error: {}", e); }
-                else { tracing::info!("Synchronizer has shut down."); }
-            },
-            _ = self.dispatcher.run() => {
-                tracing::info!("Dispatcher has shut down.");
+
+        let synchronize = async {
+            match synchronizer.run().await {
+                Ok(()) => tracing::info!("Synchronizer has shut down."),
+                Err(e) => tracing::error!("Synchronizer exited with an error: {}", e),
             }
-        }
+            // Route what was published so far, then close the listeners.
+            dispatcher_shutdown.cancel();
+        };
+        let dispatch = async {
+            dispatcher.run().await;
+            tracing::info!("Dispatcher has shut down.");
+            // Nothing published from here on could reach a listener.
+            shutdown.cancel();
+        };
+        tokio::join!(synchronize, dispatch);
+
+        // Dropping the dispatcher closes the listeners' channels.
+        drop(dispatcher);
+        running.send_replace(false);
     }
 }
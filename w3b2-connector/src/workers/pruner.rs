@@ -0,0 +1,74 @@
+use anyhow::Result;
+use solana_sdk::clock::DEFAULT_MS_PER_SLOT;
+use tokio::time::{sleep, Duration};
+
+use crate::{config::Retention, storage::Storage, workers::WorkerContext};
+
+/// Periodically deletes stored events that fall outside the configured `Retention`.
+pub struct PrunerWorker {
+    ctx: WorkerContext,
+}
+
+impl PrunerWorker {
+    pub fn new(ctx: WorkerContext) -> Self {
+        Self { ctx }
+    }
+
+    /// Runs the pruning loop. Returns immediately if no retention limit is configured.
+    pub async fn run(self) -> Result<()> {
+        let retention = self.ctx.config.retention.clone();
+        if !retention.is_enabled() {
+            return Ok(());
+        }
+
+        loop {
+            tokio::select! {
+                _ = sleep(Duration::from_secs(retention.prune_interval_secs)) => {
+                    // Retention is housekeeping: a failed cycle must not stop the
+                    // synchronization, so it is retried on the next tick.
+                    match prune(self.ctx.storage.as_ref(), &retention).await {
+                        Ok(0) => {}
+                        Ok(removed) => {
+                            tracing::info!("Pruned {} events from the event history.", removed);
+                        }
+                        Err(e) => tracing::error!("Failed to prune the event history: {}", e),
+                    }
+                }
+                _ = self.ctx.event_sender.closed() => {
+                    tracing::info!("PrunerWorker: event channel closed, shutting down.");
+                    return Ok(());
+                }
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("PrunerWorker: shutdown requested, stopping.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Applies every configured limit of `retention` to `storage` once.
+///
+/// Slot-based limits are measured back from the last synchronized slot.
+/// Returns the total number of events removed.
+pub async fn prune(storage: &dyn Storage, retention: &Retention) -> Result<usize> {
+    let last_slot = storage.get_last_slot().await?;
+    let age_in_slots = retention
+        .max_age_secs
+        .map(|secs| secs.saturating_mul(1000) / DEFAULT_MS_PER_SLOT);
+
+    let mut removed = 0;
+    if let Some(max_slots) = [retention.max_slots, age_in_slots]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        removed += storage
+            .prune_events_before(last_slot.saturating_sub(max_slots))
+            .await?;
+    }
+    if let Some(max_events) = retention.max_events {
+        removed += storage.prune_events_to_count(max_events).await?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::events::EventEnvelope;
+use crate::workers::WorkerContext;
+
+/// Holds published events briefly so they are broadcast in chain order.
+///
+/// The catch-up and live workers publish concurrently, and notifications from the
+/// WebSocket may arrive slightly out of order, so events can reach the synchronizer
+/// a few slots apart from where they belong. The buffer releases them sorted by
+/// slot, transaction position in the block and index in the transaction, once it
+/// holds more than `capacity` events or the oldest arrival has waited `max_delay`.
+///
+/// Only the catch-up worker knows a transaction's position in its block. Within a
+/// slot, transactions without one come after those with one, in the order they
+/// arrived.
+///
+/// An event arriving after later slots were already released is released with the
+/// next batch; it can no longer be put in order.
+pub struct ReorderBuffer {
+    /// Pending events keyed by their position on-chain, with their arrival time.
+    pending: BTreeMap<Position, (Instant, EventEnvelope)>,
+    /// The slot and arrival number of each pending transaction whose position in
+    /// the block is unknown, by signature.
+    unplaced: HashMap<String, (u64, u64)>,
+    next_arrival: u64,
+    capacity: usize,
+    max_delay: Duration,
+}
+
+/// Where a pending event sorts in the `ReorderBuffer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Position {
+    slot: u64,
+    transaction: TransactionOrder,
+    index: u32,
+}
+
+/// How transactions sort within a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TransactionOrder {
+    /// By position in the block.
+    InBlock(u32),
+    /// By arrival, after every transaction with a known position.
+    Arrival(u64),
+}
+
+impl ReorderBuffer {
+    /// Creates an empty buffer holding up to `capacity` events for at most
+    /// `max_delay`. A capacity of zero disables reordering.
+    pub fn new(capacity: usize, max_delay: Duration) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            unplaced: HashMap::new(),
+            next_arrival: 0,
+            capacity,
+            max_delay,
+        }
+    }
+
+    /// Returns whether the buffer reorders events at all.
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Adds an event that arrived at `now` and returns the events ready to be
+    /// broadcast, in chain order.
+    pub fn push(&mut self, envelope: EventEnvelope, now: Instant) -> Vec<EventEnvelope> {
+        if !self.is_enabled() {
+            return vec![envelope];
+        }
+        let position = self.position(&envelope);
+        self.pending.insert(position, (now, envelope));
+
+        let mut ready = Vec::new();
+        while self.pending.len() > self.capacity {
+            if let Some((_, (_, envelope))) = self.pending.pop_first() {
+                ready.push(envelope);
+            }
+        }
+        ready.extend(self.pop_expired(now));
+        self.forget_released();
+        ready
+    }
+
+    /// Returns, in chain order, every event that has waited `max_delay` at `now`
+    /// together with the events sorted before it.
+    pub fn pop_expired(&mut self, now: Instant) -> Vec<EventEnvelope> {
+        let cutoff = self
+            .pending
+            .iter()
+            .filter(|(_, (arrived, _))| now.duration_since(*arrived) >= self.max_delay)
+            .map(|(position, _)| *position)
+            .max();
+        let Some(cutoff) = cutoff else {
+            return Vec::new();
+        };
+        let mut rest = self.pending.split_off(&cutoff);
+        let last = rest.pop_first();
+        let mut ready = std::mem::replace(&mut self.pending, rest);
+        ready.extend(last);
+        self.forget_released();
+        ready.into_values().map(|(_, envelope)| envelope).collect()
+    }
+
+    /// Returns every pending event, in chain order.
+    pub fn drain(&mut self) -> Vec<EventEnvelope> {
+        self.unplaced.clear();
+        std::mem::take(&mut self.pending)
+            .into_values()
+            .map(|(_, envelope)| envelope)
+            .collect()
+    }
+
+    /// Returns the number of events currently held.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns whether the buffer holds no events.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns where `envelope` sorts. Events of a transaction with an unknown
+    /// position share the arrival number of its first event, so they stay together.
+    fn position(&mut self, envelope: &EventEnvelope) -> Position {
+        let transaction = match envelope.tx_index {
+            Some(tx_index) => TransactionOrder::InBlock(tx_index),
+            None => {
+                let next_arrival = &mut self.next_arrival;
+                let (_, arrival) = *self
+                    .unplaced
+                    .entry(envelope.signature.clone())
+                    .or_insert_with(|| {
+                        *next_arrival += 1;
+                        (envelope.slot, *next_arrival)
+                    });
+                TransactionOrder::Arrival(arrival)
+            }
+        };
+        Position {
+            slot: envelope.slot,
+            transaction,
+            index: envelope.index,
+        }
+    }
+
+    /// Forgets the arrival numbers of transactions in slots already released.
+    fn forget_released(&mut self) {
+        match self.pending.first_key_value() {
+            Some((first, _)) => {
+                let first_slot = first.slot;
+                self.unplaced.retain(|_, (slot, _)| *slot >= first_slot);
+            }
+            None => self.unplaced.clear(),
+        }
+    }
+}
+
+/// Releases the events that have waited long enough in the `ReorderBuffer`.
+pub struct ReorderWorker {
+    ctx: WorkerContext,
+}
+
+impl ReorderWorker {
+    pub fn new(ctx: WorkerContext) -> Self {
+        Self { ctx }
+    }
+
+    /// Runs the release loop. Returns immediately if reordering is disabled.
+    pub async fn run(&self) -> Result<()> {
+        if !self.ctx.reorder.lock().unwrap().is_enabled() {
+            return Ok(());
+        }
+        let interval = Duration::from_millis(self.ctx.config.synchronizer.reorder_delay_ms);
+
+        loop {
+            tokio::select! {
+                _ = sleep(interval) => {
+                    let ready = self.ctx.reorder.lock().unwrap().pop_expired(Instant::now());
+                    self.ctx.broadcast(ready);
+                }
+                _ = self.ctx.event_sender.closed() => {
+                    tracing::info!("ReorderWorker: event channel closed, shutting down.");
+                    return Ok(());
+                }
+                _ = self.ctx.shutdown.cancelled() => {
+                    tracing::info!("ReorderWorker: shutdown requested, stopping.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Broadcasts every event still held, once the other workers have stopped.
+    pub fn flush(&self) {
+        let ready = self.ctx.reorder.lock().unwrap().drain();
+        self.ctx.broadcast(ready);
+    }
+}
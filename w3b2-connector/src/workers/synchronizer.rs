@@ -1,47 +1,98 @@
 use crate::{
     config::ConnectorConfig,
-    events::BridgeEvent,
+    events::{EventEnvelope, Revocation, SyncGap},
+    metrics::SyncMetrics,
+    rpc::SolanaRpc,
     storage::Storage,
-    workers::{catchup::CatchupWorker, live::LiveWorker, WorkerContext},
+    workers::{
+        catchup::CatchupWorker, finality::FinalityWorker, live::LiveWorker, pruner::PrunerWorker,
+        reorder::ReorderWorker, WorkerContext,
+    },
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
 use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 
 pub struct Synchronizer {
     catchup_worker: CatchupWorker,
     live_worker: LiveWorker,
+    pruner_worker: PrunerWorker,
+    finality_worker: FinalityWorker,
+    reorder_worker: ReorderWorker,
+    metrics: Arc<SyncMetrics>,
+    shutdown: CancellationToken,
+    storage: Arc<dyn Storage>,
 }
 
 impl Synchronizer {
     /// Creates a new `Synchronizer` instance, preparing the workers but not starting them.
     pub fn new(
         config: Arc<ConnectorConfig>,
-        rpc_client: Arc<RpcClient>,
+        rpc_client: Arc<dyn SolanaRpc>,
         storage: Arc<dyn Storage>,
-        event_tx: broadcast::Sender<BridgeEvent>,
+        event_tx: broadcast::Sender<EventEnvelope>,
+        gap_tx: broadcast::Sender<SyncGap>,
+        revocation_tx: broadcast::Sender<Revocation>,
     ) -> Self {
-        let context = WorkerContext::new(config, rpc_client, storage, event_tx);
+        let context = WorkerContext::new(
+            config,
+            rpc_client,
+            storage.clone(),
+            event_tx,
+            gap_tx,
+            revocation_tx,
+        );
+        let metrics = context.metrics.clone();
+        let shutdown = context.shutdown.clone();
         let catchup_worker = CatchupWorker::new(context.clone());
-        let live_worker = LiveWorker::new(context);
+        let live_worker = LiveWorker::new(context.clone());
+        let pruner_worker = PrunerWorker::new(context.clone());
+        let finality_worker = FinalityWorker::new(context.clone());
+        let reorder_worker = ReorderWorker::new(context);
 
         Self {
             catchup_worker,
             live_worker,
+            pruner_worker,
+            finality_worker,
+            reorder_worker,
+            metrics,
+            shutdown,
+            storage,
         }
     }
 
-    /// Runs both the catch-up and live workers concurrently.
+    /// Returns the metrics the workers record into.
+    pub(crate) fn metrics(&self) -> Arc<SyncMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Returns the token that, once cancelled, makes `run` wind down.
+    pub(crate) fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Runs the catch-up, live, pruning, finality and reorder workers concurrently.
     ///
-    /// This method will run indefinitely until one of the workers fails or the parent task is cancelled.
+    /// This method will run until one of the workers fails or the shutdown token is
+    /// cancelled. Either way, the events still held for reordering are broadcast and
+    /// the storage is flushed before returning.
     /// This should be called and awaited by the application's main runtime.
     pub async fn run(self) -> anyhow::Result<()> {
         tracing::info!("Starting synchronizer workers...");
 
-        // Run both workers concurrently. `tokio::try_join!` will return
+        // Run all workers concurrently. `tokio::try_join!` will return
         // immediately if any of the workers returns an error.
-        tokio::try_join!(self.catchup_worker.run(), self.live_worker.run())?;
+        let result = tokio::try_join!(
+            self.catchup_worker.run(),
+            self.live_worker.run(),
+            self.pruner_worker.run(),
+            self.finality_worker.run(),
+            self.reorder_worker.run()
+        );
 
-        Ok(())
+        self.reorder_worker.flush();
+        self.storage.flush().await?;
+        result.map(|_| ())
     }
 }
@@ -0,0 +1,300 @@
+use anchor_lang::AccountSerialize;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, sync::Arc};
+use w3b2_bridge_program::state::{
+    AdminProfile, PriceEntry, PriceSnapshot, UserProfile, PRICE_HISTORY_LEN,
+};
+use w3b2_connector::reader::{AccountReader, AdminProfileSummary};
+
+/// Builds a mocked `getAccountInfo` response holding `data`, owned by `owner`.
+fn account_info_response(data: &[u8], owner: &Pubkey) -> Value {
+    json!({
+        "context": { "slot": 1 },
+        "value": {
+            "lamports": 1_000_000,
+            "data": [BASE64_STANDARD.encode(data), "base64"],
+            "owner": owner.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": data.len(),
+        }
+    })
+}
+
+fn reader_with_account(response: Value) -> AccountReader {
+    let mocks = HashMap::from([(RpcRequest::GetAccountInfo, response)]);
+    AccountReader::new(Arc::new(RpcClient::new_mock_with_mocks(
+        "succeeds".to_string(),
+        mocks,
+    )))
+}
+
+fn sample_admin_profile(authority: Pubkey) -> AdminProfile {
+    AdminProfile {
+        authority,
+        communication_pubkey: Pubkey::new_unique(),
+        prices: vec![PriceEntry::new(1, 100), PriceEntry::new(2, 250)],
+        balance: 42,
+        gc_inactivity_epochs: 0,
+        priority_surcharge: 0,
+        price_history: [PriceSnapshot::default(); PRICE_HISTORY_LEN],
+        price_history_head: 0,
+        profile_index: None,
+        migrated_from: None,
+        migrated_to: None,
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_admin_profile_decodes_account() {
+    let authority = Pubkey::new_unique();
+    let profile = sample_admin_profile(authority);
+    let mut data = Vec::new();
+    profile.try_serialize(&mut data).unwrap();
+
+    let reader = reader_with_account(account_info_response(&data, &w3b2_bridge_program::ID));
+    let fetched = reader
+        .fetch_admin_profile(&authority)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(fetched.authority, authority);
+    assert_eq!(fetched.communication_pubkey, profile.communication_pubkey);
+    assert_eq!(fetched.prices, profile.prices);
+    assert_eq!(fetched.balance, 42);
+}
+
+#[tokio::test]
+async fn test_fetch_admin_profile_returns_none_when_missing() {
+    let reader = reader_with_account(json!({ "context": { "slot": 1 }, "value": null }));
+
+    let fetched = reader
+        .fetch_admin_profile(&Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert!(fetched.is_none());
+}
+
+#[tokio::test]
+async fn test_fetch_admin_profile_rejects_foreign_owner() {
+    let mut data = Vec::new();
+    sample_admin_profile(Pubkey::new_unique())
+        .try_serialize(&mut data)
+        .unwrap();
+
+    let reader = reader_with_account(account_info_response(&data, &Pubkey::new_unique()));
+
+    assert!(reader
+        .fetch_admin_profile(&Pubkey::new_unique())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_fetch_user_profile_and_deposit_balance() {
+    let user = Pubkey::new_unique();
+    let admin_pda = Pubkey::new_unique();
+    let profile = UserProfile {
+        authority: user,
+        communication_pubkey: Pubkey::new_unique(),
+        admin_authority_on_creation: admin_pda,
+        deposit_balance: 5_000,
+        rent_reserve: 1_500_000,
+        last_active_epoch: 3,
+    };
+    let mut data = Vec::new();
+    profile.try_serialize(&mut data).unwrap();
+
+    // Mocked responses are consumed by the first matching request.
+    let response = account_info_response(&data, &w3b2_bridge_program::ID);
+    let fetched = reader_with_account(response.clone())
+        .fetch_user_profile(&user, &admin_pda)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(fetched.authority, user);
+    assert_eq!(fetched.admin_authority_on_creation, admin_pda);
+    assert_eq!(
+        reader_with_account(response)
+            .get_deposit_balance(&user, &admin_pda)
+            .await
+            .unwrap(),
+        5_000
+    );
+}
+
+#[tokio::test]
+async fn test_get_deposit_balance_is_zero_without_profile() {
+    let reader = reader_with_account(json!({ "context": { "slot": 1 }, "value": null }));
+
+    let balance = reader
+        .get_deposit_balance(&Pubkey::new_unique(), &Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(balance, 0);
+}
+
+#[tokio::test]
+async fn test_list_admin_profiles_decodes_sliced_accounts() {
+    let admin_pda = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let communication_pubkey = Pubkey::new_unique();
+    // The RPC node returns only the requested 64-byte slice.
+    let head = [authority.to_bytes(), communication_pubkey.to_bytes()].concat();
+
+    let response = json!([{
+        "pubkey": admin_pda.to_string(),
+        "account": account_info_response(&head, &w3b2_bridge_program::ID)["value"],
+    }]);
+    let mocks = HashMap::from([(RpcRequest::GetProgramAccounts, response)]);
+    let reader = AccountReader::new(Arc::new(RpcClient::new_mock_with_mocks(
+        "succeeds".to_string(),
+        mocks,
+    )));
+
+    let admins = reader.list_admin_profiles().await.unwrap();
+
+    assert_eq!(
+        admins,
+        vec![AdminProfileSummary {
+            admin_pda,
+            authority,
+            communication_pubkey,
+        }]
+    );
+}
+
+fn sample_user_profile(admin_pda: Pubkey, deposit_balance: u64) -> UserProfile {
+    UserProfile {
+        authority: Pubkey::new_unique(),
+        communication_pubkey: Pubkey::new_unique(),
+        admin_authority_on_creation: admin_pda,
+        deposit_balance,
+        rent_reserve: 1_500_000,
+        last_active_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_list_user_profiles_for_admin_paginates() {
+    let admin_pda = Pubkey::new_unique();
+    let mut user_pdas: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    user_pdas.sort();
+
+    let keyed_accounts: Vec<Value> = user_pdas
+        .iter()
+        .rev()
+        .map(|pda| {
+            json!({
+                "pubkey": pda.to_string(),
+                "account": account_info_response(&[], &w3b2_bridge_program::ID)["value"],
+            })
+        })
+        .collect();
+    // Only the second page (the last profile) is downloaded.
+    let mut data = Vec::new();
+    sample_user_profile(admin_pda, 77)
+        .try_serialize(&mut data)
+        .unwrap();
+    let page_accounts = json!({
+        "context": { "slot": 1 },
+        "value": [account_info_response(&data, &w3b2_bridge_program::ID)["value"]],
+    });
+
+    let mocks = HashMap::from([
+        (RpcRequest::GetProgramAccounts, Value::Array(keyed_accounts)),
+        (RpcRequest::GetMultipleAccounts, page_accounts),
+    ]);
+    let reader = AccountReader::new(Arc::new(RpcClient::new_mock_with_mocks(
+        "succeeds".to_string(),
+        mocks,
+    )));
+
+    let page = reader
+        .list_user_profiles_for_admin(&admin_pda, 2, 2)
+        .await
+        .unwrap();
+
+    assert_eq!(page.total, 3);
+    assert_eq!(page.next_offset, None);
+    assert_eq!(page.profiles.len(), 1);
+    assert_eq!(page.profiles[0].user_pda, user_pdas[2]);
+    assert_eq!(page.profiles[0].profile.deposit_balance, 77);
+}
+
+#[tokio::test]
+async fn test_fetch_admin_profiles_at_lines_up_with_addresses() {
+    let authority = Pubkey::new_unique();
+    let mut data = Vec::new();
+    sample_admin_profile(authority)
+        .try_serialize(&mut data)
+        .unwrap();
+    let accounts = json!({
+        "context": { "slot": 1 },
+        "value": [
+            Value::Null,
+            account_info_response(&data, &w3b2_bridge_program::ID)["value"],
+        ],
+    });
+    let mocks = HashMap::from([(RpcRequest::GetMultipleAccounts, accounts)]);
+    let reader = AccountReader::new(Arc::new(RpcClient::new_mock_with_mocks(
+        "succeeds".to_string(),
+        mocks,
+    )));
+
+    let profiles = reader
+        .fetch_admin_profiles_at(&[Pubkey::new_unique(), Pubkey::new_unique()])
+        .await
+        .unwrap();
+
+    assert_eq!(profiles.len(), 2);
+    assert!(profiles[0].is_none());
+    assert_eq!(profiles[1].as_ref().unwrap().authority, authority);
+    // Nothing is requested for an empty batch.
+    assert!(reader.fetch_user_profiles_at(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_list_user_profiles_of_decodes_every_service() {
+    let mut entries: Vec<(Pubkey, u64)> = (0..2)
+        .map(|deposit| (Pubkey::new_unique(), deposit * 100))
+        .collect();
+    let keyed_accounts: Vec<Value> = entries
+        .iter()
+        .rev()
+        .map(|(pda, deposit)| {
+            let mut data = Vec::new();
+            sample_user_profile(Pubkey::new_unique(), *deposit)
+                .try_serialize(&mut data)
+                .unwrap();
+            json!({
+                "pubkey": pda.to_string(),
+                "account": account_info_response(&data, &w3b2_bridge_program::ID)["value"],
+            })
+        })
+        .collect();
+    let mocks = HashMap::from([(RpcRequest::GetProgramAccounts, Value::Array(keyed_accounts))]);
+    let reader = AccountReader::new(Arc::new(RpcClient::new_mock_with_mocks(
+        "succeeds".to_string(),
+        mocks,
+    )));
+
+    let profiles = reader
+        .list_user_profiles_of(&Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    entries.sort();
+    let listed: Vec<(Pubkey, u64)> = profiles
+        .iter()
+        .map(|entry| (entry.user_pda, entry.profile.deposit_balance))
+        .collect();
+    assert_eq!(listed, entries);
+}
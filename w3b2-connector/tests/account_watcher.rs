@@ -0,0 +1,71 @@
+use anchor_lang::AccountSerialize;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use solana_account_decoder_client_types::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::state::UserProfile;
+use w3b2_connector::workers::{ProfileKind, ProfileUpdate};
+
+fn ui_account(lamports: u64, data: &[u8], owner: &Pubkey) -> UiAccount {
+    UiAccount {
+        lamports,
+        data: UiAccountData::Binary(BASE64_STANDARD.encode(data), UiAccountEncoding::Base64),
+        owner: owner.to_string(),
+        executable: false,
+        rent_epoch: 0,
+        space: Some(data.len() as u64),
+    }
+}
+
+#[test]
+fn test_decode_user_profile_update() {
+    let address = Pubkey::new_unique();
+    let profile = UserProfile {
+        authority: Pubkey::new_unique(),
+        communication_pubkey: Pubkey::new_unique(),
+        admin_authority_on_creation: Pubkey::new_unique(),
+        deposit_balance: 1_234,
+        rent_reserve: 1_500_000,
+        last_active_epoch: 9,
+    };
+    let mut data = Vec::new();
+    profile.try_serialize(&mut data).unwrap();
+
+    let update = ProfileUpdate::decode(
+        ProfileKind::User,
+        address,
+        42,
+        &ui_account(2_000_000, &data, &w3b2_bridge_program::ID),
+    )
+    .unwrap();
+
+    let ProfileUpdate::User {
+        address: a,
+        slot,
+        profile: decoded,
+    } = update
+    else {
+        panic!("expected a user profile update");
+    };
+    assert_eq!(a, address);
+    assert_eq!(slot, 42);
+    assert_eq!(decoded.deposit_balance, 1_234);
+    assert_eq!(decoded.communication_pubkey, profile.communication_pubkey);
+}
+
+#[test]
+fn test_decode_closed_account() {
+    let address = Pubkey::new_unique();
+
+    let update = ProfileUpdate::decode(
+        ProfileKind::Admin,
+        address,
+        7,
+        &ui_account(0, &[], &solana_sdk::system_program::id()),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        update,
+        ProfileUpdate::Closed { address: a, slot: 7 } if a == address
+    ));
+}
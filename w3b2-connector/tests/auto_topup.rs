@@ -0,0 +1,100 @@
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use w3b2_bridge_program::state::UserProfile;
+use w3b2_connector::{
+    client::TransactionBuilder,
+    rpc::MockSolanaRpc,
+    topup::{AutoTopUp, TopUpPolicy},
+    workers::ProfileUpdate,
+};
+
+fn policy(cooldown: Duration, max_total: Option<u64>) -> TopUpPolicy {
+    TopUpPolicy {
+        threshold: 1_000,
+        amount: 5_000,
+        cooldown,
+        max_total,
+    }
+}
+
+fn user_update(user: &Keypair, deposit_balance: u64) -> ProfileUpdate {
+    ProfileUpdate::User {
+        address: Pubkey::new_unique(),
+        slot: 1,
+        profile: UserProfile {
+            authority: user.pubkey(),
+            communication_pubkey: Pubkey::new_unique(),
+            admin_authority_on_creation: Pubkey::new_unique(),
+            deposit_balance,
+            rent_reserve: 0,
+            last_active_epoch: 0,
+        },
+    }
+}
+
+#[test]
+fn test_plan_respects_threshold_cooldown_and_cap() {
+    let builder = TransactionBuilder::new(Arc::new(MockSolanaRpc::new()));
+    let topup = AutoTopUp::new(
+        builder,
+        Arc::new(Keypair::new()),
+        Pubkey::new_unique(),
+        policy(Duration::from_secs(60), Some(3_000)),
+    );
+    let now = Instant::now();
+
+    assert_eq!(topup.plan(1_000, now), None);
+    assert_eq!(topup.plan(999, now), Some(3_000));
+}
+
+#[tokio::test]
+async fn test_run_tops_up_until_the_cap() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let user = Arc::new(Keypair::new());
+    let mut topup = AutoTopUp::new(
+        builder,
+        user.clone(),
+        Pubkey::new_unique(),
+        policy(Duration::ZERO, Some(8_000)),
+    );
+
+    assert!(topup.observe(500).await.unwrap().is_some());
+    assert!(topup.observe(2_000).await.unwrap().is_none());
+    assert!(topup.observe(500).await.unwrap().is_some());
+    assert_eq!(topup.deposited(), 8_000);
+    assert!(topup.observe(0).await.unwrap().is_none());
+    assert_eq!(rpc.sent_signatures().len(), 2);
+
+    let (tx, rx) = mpsc::channel(4);
+    tx.send(user_update(&user, 0)).await.unwrap();
+    tx.send(ProfileUpdate::Closed {
+        address: Pubkey::new_unique(),
+        slot: 2,
+    })
+    .await
+    .unwrap();
+    topup.run(rx).await.unwrap();
+    assert_eq!(rpc.sent_signatures().len(), 2);
+}
+
+#[tokio::test]
+async fn test_cooldown_spaces_out_top_ups() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let mut topup = AutoTopUp::new(
+        builder,
+        Arc::new(Keypair::new()),
+        Pubkey::new_unique(),
+        policy(Duration::from_secs(60), None),
+    );
+
+    assert!(topup.observe(0).await.unwrap().is_some());
+    assert!(topup.observe(0).await.unwrap().is_none());
+    assert!(topup
+        .plan(0, Instant::now() + Duration::from_secs(61))
+        .is_some());
+    assert_eq!(rpc.sent_signatures().len(), 1);
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+use w3b2_connector::backoff::Backoff;
+
+#[test]
+fn test_backoff_doubles_with_jitter_up_to_the_maximum() {
+    let min = Duration::from_millis(100);
+    let max = Duration::from_millis(1_000);
+    let mut backoff = Backoff::new(min, max);
+
+    for ceiling in [100, 200, 400, 800, 1_000, 1_000] {
+        let ceiling = Duration::from_millis(ceiling);
+        let delay = backoff.next_delay();
+        assert!(
+            delay >= ceiling / 2 && delay <= ceiling,
+            "{:?} is outside {:?}..={:?}",
+            delay,
+            ceiling / 2,
+            ceiling
+        );
+    }
+    assert_eq!(backoff.attempts(), 6);
+}
+
+#[test]
+fn test_backoff_reset_starts_over() {
+    let min = Duration::from_millis(100);
+    let mut backoff = Backoff::new(min, Duration::from_secs(30));
+    for _ in 0..40 {
+        backoff.next_delay();
+    }
+    assert!(backoff.next_delay() <= Duration::from_secs(30));
+
+    backoff.reset();
+    assert_eq!(backoff.attempts(), 0);
+    assert!(backoff.next_delay() <= min);
+}
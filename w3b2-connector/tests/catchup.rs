@@ -0,0 +1,105 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use common::action_log;
+use solana_sdk::signature::Signature;
+use std::{sync::Arc, time::Duration};
+use w3b2_connector::{
+    config::ConnectorConfig,
+    events::{BridgeEvent, EventEnvelope},
+    rpc::MockSolanaRpc,
+    storage::MemoryStorage,
+    workers::{EventManager, EventManagerHandle},
+};
+
+/// Starts an event manager that polls for new transactions right away and
+/// retries failed requests quickly.
+fn start(rpc: Arc<MockSolanaRpc>) -> EventManagerHandle {
+    let mut config = ConnectorConfig::default();
+    config.synchronizer.poll_interval_secs = 0;
+    config.solana.reconnect_min_backoff_ms = 10;
+    config.solana.reconnect_max_backoff_ms = 100;
+    let (manager, handle) = EventManager::new(
+        Arc::new(config),
+        rpc,
+        Arc::new(MemoryStorage::new()),
+        16,
+        16,
+    );
+    tokio::spawn(manager.run());
+    handle
+}
+
+async fn next(events: &mut tokio::sync::broadcast::Receiver<EventEnvelope>) -> EventEnvelope {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("the event should be published")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_catchup_records_transaction_position_in_block() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let (second, third) = (Signature::new_unique(), Signature::new_unique());
+    rpc.add_transaction(Signature::new_unique(), 10, Vec::new());
+    rpc.add_transaction(second, 10, vec![action_log(1)]);
+    rpc.add_transaction(third, 11, vec![action_log(2)]);
+
+    let handle = start(rpc.clone());
+    let mut events = handle.subscribe_all();
+
+    let first_event = next(&mut events).await;
+    assert_eq!(first_event.signature, second.to_string());
+    assert_eq!(first_event.tx_index, Some(1));
+    let second_event = next(&mut events).await;
+    assert_eq!(second_event.signature, third.to_string());
+    assert_eq!(second_event.tx_index, Some(0));
+
+    handle.stop().await;
+    // Only blocks holding bridge events are fetched.
+    assert_eq!(rpc.calls("get_block_with_config"), 2);
+}
+
+#[tokio::test]
+async fn test_catchup_keeps_the_source_of_unknown_events() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let signature = Signature::new_unique();
+    let data = [[7u8; 8].as_slice(), &[1, 2, 3]].concat();
+    let logs = vec![
+        format!("Program {} invoke [1]", w3b2_bridge_program::ID),
+        format!("Program data: {}", BASE64_STANDARD.encode(&data)),
+        format!("Program {} success", w3b2_bridge_program::ID),
+    ];
+    rpc.add_transaction(signature, 10, logs.clone());
+
+    let handle = start(rpc);
+    let mut events = handle.subscribe_all();
+
+    let BridgeEvent::Unknown { raw } = next(&mut events).await.event else {
+        panic!("expected an unknown event");
+    };
+    assert_eq!(raw.data, data);
+    assert_eq!(raw.signature, Some(signature.to_string()));
+    assert_eq!(raw.logs, logs);
+    handle.stop().await;
+}
+
+#[tokio::test]
+async fn test_catchup_does_not_skip_a_transaction_it_failed_to_fetch() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let signatures: Vec<Signature> = (0..3).map(|_| Signature::new_unique()).collect();
+    for (slot, signature) in signatures.iter().enumerate() {
+        rpc.add_transaction(*signature, 10 + slot as u64, vec![action_log(slot as u64)]);
+    }
+    // More failures than a single poll retries.
+    rpc.fail_transaction_fetches(signatures[1], 4);
+
+    let handle = start(rpc.clone());
+    let mut events = handle.subscribe_all();
+
+    for signature in &signatures {
+        assert_eq!(next(&mut events).await.signature, signature.to_string());
+    }
+    handle.stop().await;
+    assert!(rpc.calls("get_transaction_with_config") >= 7);
+}
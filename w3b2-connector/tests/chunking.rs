@@ -0,0 +1,128 @@
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use w3b2_bridge_program::{
+    events::UserCommandDispatched,
+    instructions::MAX_PAYLOAD_SIZE,
+    protocols::{PayloadCodec, PayloadHeader},
+};
+use w3b2_connector::{
+    chunking::{split_payload, ChunkError, Reassembler, MAX_CHUNK_DATA},
+    client::TransactionBuilder,
+    events::BridgeEvent,
+    rpc::MockSolanaRpc,
+};
+
+fn message(len: usize) -> Vec<u8> {
+    let body: Vec<u8> = (0..len).map(|i| i as u8).collect();
+    PayloadHeader::new(PayloadCodec::Json, true).wrap(&body)
+}
+
+#[test]
+fn test_split_and_reassemble_out_of_order() {
+    let payload = message(2 * MAX_CHUNK_DATA + 10);
+    let chunks = split_payload(&payload, 42).unwrap();
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_PAYLOAD_SIZE));
+    let (header, _) = PayloadHeader::parse(&chunks[0]).unwrap();
+    assert!(header.chunked && header.encrypted);
+
+    let sender = Pubkey::new_unique();
+    let mut reassembler = Reassembler::new(8);
+    assert_eq!(reassembler.push(sender, &chunks[2]), Ok(None));
+    assert_eq!(reassembler.push(sender, &chunks[0]), Ok(None));
+    // A repeated chunk, e.g. seen by both the catch-up and live workers.
+    assert_eq!(reassembler.push(sender, &chunks[0]), Ok(None));
+    // The same sequence id from another sender is another message.
+    assert_eq!(reassembler.push(Pubkey::new_unique(), &chunks[1]), Ok(None));
+    assert_eq!(reassembler.pending(), 2);
+
+    assert_eq!(reassembler.push(sender, &chunks[1]), Ok(Some(payload)));
+    assert_eq!(reassembler.pending(), 1);
+}
+
+#[test]
+fn test_small_payloads_pass_through() {
+    let payload = message(10);
+    assert_eq!(split_payload(&payload, 1).unwrap(), vec![payload.clone()]);
+
+    let event = BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+        sender: Pubkey::new_unique(),
+        target_admin_authority: Pubkey::new_unique(),
+        command_id: 1,
+        price_paid: 0,
+        high_priority: false,
+        priority_fee: 0,
+        payload: payload.clone(),
+        ts: 0,
+    });
+    let mut reassembler = Reassembler::new(8);
+    assert_eq!(reassembler.push_event(&event), Ok(Some(payload)));
+}
+
+#[test]
+fn test_reassembler_rejects_inconsistent_chunks() {
+    let sender = Pubkey::new_unique();
+    let first = split_payload(&message(2 * MAX_CHUNK_DATA), 7).unwrap();
+    let other = split_payload(&message(3 * MAX_CHUNK_DATA), 7).unwrap();
+
+    let mut reassembler = Reassembler::new(8);
+    reassembler.push(sender, &first[0]).unwrap();
+    assert_eq!(
+        reassembler.push(sender, &other[1]),
+        Err(ChunkError::Mismatch {
+            sequence_id: 7,
+            index: 1
+        })
+    );
+    assert_eq!(reassembler.pending(), 0);
+
+    let mut truncated = first[0].clone();
+    truncated.truncate(10);
+    assert!(matches!(
+        reassembler.push(sender, &truncated),
+        Err(ChunkError::Malformed(_))
+    ));
+}
+
+#[test]
+fn test_reassembler_drops_the_oldest_incomplete_message() {
+    let sender = Pubkey::new_unique();
+    let mut reassembler = Reassembler::new(2);
+    for sequence_id in 0..3 {
+        let chunks = split_payload(&message(2 * MAX_CHUNK_DATA), sequence_id).unwrap();
+        reassembler.push(sender, &chunks[0]).unwrap();
+    }
+    assert_eq!(reassembler.pending(), 2);
+
+    // The first message was evicted, so its last chunk starts it over.
+    let chunks = split_payload(&message(2 * MAX_CHUNK_DATA), 0).unwrap();
+    assert_eq!(reassembler.push(sender, &chunks[1]), Ok(None));
+}
+
+#[tokio::test]
+async fn test_prepare_chunked_dispatch() {
+    let builder = TransactionBuilder::new(Arc::new(MockSolanaRpc::new()));
+    let transactions = builder
+        .prepare_user_dispatch_command_chunked(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            3,
+            false,
+            &message(3 * MAX_CHUNK_DATA),
+        )
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 3);
+
+    let unframed = vec![0; MAX_PAYLOAD_SIZE + 1];
+    assert!(builder
+        .prepare_admin_dispatch_command_chunked(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            3,
+            0,
+            &unframed,
+        )
+        .await
+        .is_err());
+}
@@ -0,0 +1,23 @@
+use solana_sdk::hash::Hash;
+use std::str::FromStr;
+use w3b2_connector::cluster::{Cluster, DEVNET_GENESIS_HASH, MAINNET_BETA_GENESIS_HASH};
+
+#[test]
+fn test_public_clusters_are_identified_by_genesis_hash() {
+    let mainnet = Cluster::from_genesis_hash(&Hash::from_str(MAINNET_BETA_GENESIS_HASH).unwrap());
+    let devnet = Cluster::from_genesis_hash(&Hash::from_str(DEVNET_GENESIS_HASH).unwrap());
+
+    assert_eq!(mainnet, Cluster::MainnetBeta);
+    assert!(!mainnet.is_development());
+    assert_eq!(devnet, Cluster::Devnet);
+    assert!(devnet.is_development());
+}
+
+#[test]
+fn test_unknown_genesis_hash_is_localnet() {
+    let cluster = Cluster::from_genesis_hash(&Hash::new_unique());
+
+    assert_eq!(cluster, Cluster::Localnet);
+    assert!(cluster.is_development());
+    assert_eq!(cluster.to_string(), "localnet");
+}
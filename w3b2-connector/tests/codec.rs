@@ -0,0 +1,123 @@
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::events::UserCommandDispatched;
+use w3b2_bridge_program::protocols::{
+    CommandConfig, Destination, PayloadCodec as CodecId, PayloadHeader,
+};
+use w3b2_connector::codec::{BorshCodec, CodecError, CodecRegistry, ProtobufCodec, RawCodec};
+use w3b2_connector::runtime::CommandContext;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Ping {
+    #[prost(uint64, tag = "1")]
+    nonce: u64,
+    #[prost(string, tag = "2")]
+    note: String,
+}
+
+fn config() -> CommandConfig {
+    CommandConfig::new(
+        7,
+        vec![1, 2, 3],
+        Destination::IpV4([10, 0, 0, 1], 443),
+        vec![],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_borsh_round_trip() {
+    let registry = CodecRegistry::new().register(BorshCodec);
+
+    let payload = registry.encode(CodecId::Borsh, &config()).unwrap();
+    let (header, _) = PayloadHeader::parse(&payload).unwrap();
+    assert_eq!(header, PayloadHeader::new(CodecId::Borsh, false));
+    assert_eq!(registry.decode(&payload).unwrap(), config());
+}
+
+#[test]
+fn test_protobuf_round_trip() {
+    let registry = CodecRegistry::new().register(ProtobufCodec);
+    let ping = Ping {
+        nonce: 99,
+        note: "hi".to_string(),
+    };
+
+    let payload = registry.encode(CodecId::Protobuf, &ping).unwrap();
+    assert_eq!(registry.decode(&payload).unwrap(), ping);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+    use w3b2_connector::codec::JsonCodec;
+
+    let registry = CodecRegistry::new().register(JsonCodec);
+    let value = serde_json::json!({ "op": "ping", "nonce": 1 });
+
+    let payload = registry.encode(CodecId::Json, &value).unwrap();
+    assert_eq!(&payload[5..], br#"{"nonce":1,"op":"ping"}"#);
+    assert_eq!(registry.decode(&payload).unwrap(), value);
+}
+
+#[test]
+fn test_codec_selected_by_header() {
+    let registry = CodecRegistry::new().register(RawCodec);
+    assert!(registry.supports(CodecId::Raw));
+    assert!(!registry.supports(CodecId::Borsh));
+
+    let raw = PayloadHeader::new(CodecId::Raw, false).wrap(b"bytes");
+    assert_eq!(registry.decode(&raw).unwrap(), b"bytes");
+
+    let borsh = PayloadHeader::new(CodecId::Borsh, false).wrap(b"bytes");
+    assert_eq!(
+        registry.decode(&borsh),
+        Err(CodecError::NotRegistered(CodecId::Borsh))
+    );
+}
+
+#[test]
+fn test_decode_errors() {
+    let registry: CodecRegistry<CommandConfig> = CodecRegistry::new().register(BorshCodec);
+
+    let encrypted = PayloadHeader::new(CodecId::Borsh, true).wrap(b"sealed");
+    assert_eq!(registry.decode(&encrypted), Err(CodecError::Encrypted));
+
+    assert!(matches!(
+        registry.decode(b"??"),
+        Err(CodecError::InvalidHeader(_))
+    ));
+
+    let garbage = PayloadHeader::new(CodecId::Borsh, false).wrap(&[0xff]);
+    assert!(matches!(
+        registry.decode(&garbage),
+        Err(CodecError::Decode(_))
+    ));
+}
+
+#[test]
+fn test_command_context_decode() {
+    let registry = CodecRegistry::new().register(BorshCodec);
+    let payload = registry.encode(CodecId::Borsh, &config()).unwrap();
+    let (header, body) = PayloadHeader::parse(&payload).unwrap();
+
+    let mut ctx = CommandContext {
+        command: UserCommandDispatched {
+            sender: Pubkey::new_unique(),
+            target_admin_authority: Pubkey::new_unique(),
+            command_id: 1,
+            price_paid: 0,
+            high_priority: false,
+            priority_fee: 0,
+            payload: payload.clone(),
+            ts: 0,
+        },
+        user_profile_pda: Pubkey::new_unique(),
+        header: Some(header),
+        body: body.to_vec(),
+        listed_price: None,
+    };
+    assert_eq!(ctx.decode(&registry).unwrap(), config());
+
+    ctx.header = None;
+    assert!(matches!(ctx.decode(&registry), Err(CodecError::Decode(_))));
+}
@@ -0,0 +1,165 @@
+use anchor_lang::AccountSerialize;
+use solana_client::client_error::ClientErrorKind;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::sync::Arc;
+use w3b2_bridge_program::state::{AdminProfile, PriceSnapshot, UserProfile, PRICE_HISTORY_LEN};
+use w3b2_connector::{
+    client::TransactionBuilder,
+    protocol::X25519Keypair,
+    rotation::{
+        fetch_comm_key, resolve_rotation, rotate_comm_key, CommKeyOwner, CommKeyStore,
+        MemoryCommKeyStore, RotationError,
+    },
+    rpc::MockSolanaRpc,
+};
+
+fn set_admin_profile(rpc: &MockSolanaRpc, authority: Pubkey, communication_pubkey: Pubkey) {
+    let mut data = Vec::new();
+    AdminProfile {
+        authority,
+        communication_pubkey,
+        prices: Vec::new(),
+        balance: 0,
+        gc_inactivity_epochs: 0,
+        priority_surcharge: 0,
+        price_history: [PriceSnapshot::default(); PRICE_HISTORY_LEN],
+        price_history_head: 0,
+        profile_index: None,
+        migrated_from: None,
+        migrated_to: None,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    let owner = CommKeyOwner::Admin { authority };
+    rpc.set_account(
+        owner.profile_pda(),
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: w3b2_bridge_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+fn connection_reset() -> solana_client::client_error::ClientError {
+    ClientErrorKind::Custom("connection reset".to_string()).into()
+}
+
+#[tokio::test]
+async fn test_rotation_promotes_key_after_confirmation() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let authority = Keypair::new();
+    let owner = CommKeyOwner::Admin {
+        authority: authority.pubkey(),
+    };
+    let old = X25519Keypair::generate();
+    let store = MemoryCommKeyStore::new();
+    store.insert(owner, old);
+
+    let new = rotate_comm_key(&builder, &authority, owner, &store)
+        .await
+        .unwrap();
+
+    assert_eq!(rpc.sent_signatures().len(), 1);
+    assert_eq!(store.active(&owner).unwrap().public_key(), new.public_key());
+    assert!(store.staged(&owner).is_none());
+}
+
+#[tokio::test]
+async fn test_rotation_rolls_back_when_update_did_not_land() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let authority = Keypair::new();
+    let owner = CommKeyOwner::Admin {
+        authority: authority.pubkey(),
+    };
+    let old = X25519Keypair::generate();
+    set_admin_profile(&rpc, authority.pubkey(), old.public_key());
+    let store = MemoryCommKeyStore::new();
+    store.insert(owner, old.clone());
+    rpc.fail_next_send(connection_reset());
+
+    let result = rotate_comm_key(&builder, &authority, owner, &store).await;
+
+    assert!(matches!(result, Err(RotationError::Update(_))));
+    assert_eq!(store.active(&owner).unwrap().public_key(), old.public_key());
+    assert!(store.staged(&owner).is_none());
+}
+
+#[tokio::test]
+async fn test_unconfirmed_rotation_is_resolved_from_chain() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let authority = Keypair::new();
+    let admin_profile_pda = Pubkey::new_unique();
+    let owner = CommKeyOwner::User {
+        authority: authority.pubkey(),
+        admin_profile_pda,
+    };
+    let store = MemoryCommKeyStore::new();
+    store.insert(owner, X25519Keypair::generate());
+    // Neither the send nor the profile lookup succeeds, so the outcome is unknown.
+    rpc.fail_next_send(connection_reset());
+
+    let result = rotate_comm_key(&builder, &authority, owner, &store).await;
+
+    let Err(RotationError::Unconfirmed { new_key, .. }) = result else {
+        panic!("expected an unconfirmed rotation, got {:?}", result);
+    };
+    assert_eq!(store.staged(&owner).unwrap().public_key(), new_key);
+
+    // The update turns out to have landed.
+    let mut data = Vec::new();
+    UserProfile {
+        authority: authority.pubkey(),
+        communication_pubkey: new_key,
+        admin_authority_on_creation: admin_profile_pda,
+        deposit_balance: 0,
+        rent_reserve: 0,
+        last_active_epoch: 0,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    rpc.set_account(
+        owner.profile_pda(),
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: w3b2_bridge_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    assert_eq!(fetch_comm_key(&builder, owner).await.unwrap(), new_key);
+
+    assert!(resolve_rotation(&builder, owner, &new_key, &store)
+        .await
+        .unwrap());
+    assert_eq!(store.active(&owner).unwrap().public_key(), new_key);
+    assert!(store.staged(&owner).is_none());
+}
+
+#[tokio::test]
+async fn test_resolve_discards_key_that_did_not_land() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let authority = Pubkey::new_unique();
+    let owner = CommKeyOwner::Admin { authority };
+    let old = X25519Keypair::generate();
+    set_admin_profile(&rpc, authority, old.public_key());
+    let store = MemoryCommKeyStore::new();
+    store.insert(owner, old.clone());
+    let new = X25519Keypair::generate();
+    store.stage(&owner, &new).await.unwrap();
+
+    assert!(
+        !resolve_rotation(&builder, owner, &new.public_key(), &store)
+            .await
+            .unwrap()
+    );
+    assert_eq!(store.active(&owner).unwrap().public_key(), old.public_key());
+    assert!(store.staged(&owner).is_none());
+}
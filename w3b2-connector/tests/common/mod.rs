@@ -0,0 +1,61 @@
+// tests/common/mod.rs
+
+//! Event fixtures shared by the connector's tests.
+//!
+//! Most tests only need some valid event; an `OffChainActionLogged` is the
+//! simplest one to build.
+
+// Each test binary uses only a subset of these shared helpers.
+#![allow(dead_code)]
+
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use w3b2_bridge_program::events::OffChainActionLogged;
+use w3b2_connector::events::{BridgeEvent, EventEnvelope};
+
+/// Returns an off-chain action logged by `actor`.
+pub fn action_event(actor: Pubkey, session_id: u64) -> BridgeEvent {
+    BridgeEvent::OffChainActionLogged(OffChainActionLogged {
+        actor,
+        session_id,
+        action_code: 200,
+        ts: 0,
+    })
+}
+
+/// Returns the log line with which the program emits an off-chain action.
+pub fn action_log(session_id: u64) -> String {
+    let event = action_event(Pubkey::new_unique(), session_id);
+    format!("Program data: {}", BASE64_STANDARD.encode(event.to_bytes()))
+}
+
+/// Wraps `event` as event `index` of transaction `signature`, at slot 1.
+pub fn envelope(signature: &str, index: u32, event: BridgeEvent) -> EventEnvelope {
+    EventEnvelope {
+        slot: 1,
+        signature: signature.to_string(),
+        index,
+        tx_index: None,
+        block_time: None,
+        event,
+    }
+}
+
+/// Returns an off-chain action by `actor` as event `index` of transaction
+/// `signature`, at `slot`.
+pub fn action(actor: Pubkey, slot: u64, signature: &str, index: u32) -> EventEnvelope {
+    EventEnvelope {
+        slot,
+        ..envelope(signature, index, action_event(actor, slot))
+    }
+}
+
+/// Returns an off-chain action by a new actor, alone in a new transaction at `slot`.
+pub fn action_at(slot: u64) -> EventEnvelope {
+    action(
+        Pubkey::new_unique(),
+        slot,
+        &Signature::new_unique().to_string(),
+        0,
+    )
+}
@@ -0,0 +1,106 @@
+use solana_sdk::{commitment_config::CommitmentLevel, signature::Signature};
+use w3b2_connector::config::{ConfigError, ConnectorConfig, RateLimit, StartPoint};
+
+#[test]
+fn test_builder_applies_settings_over_defaults() {
+    let config = ConnectorConfig::builder()
+        .rpc_url("https://api.devnet.solana.com")
+        .ws_url("wss://api.devnet.solana.com")
+        .commitment(CommitmentLevel::Finalized)
+        .extra_endpoint("http://10.0.0.2:8899", "ws://10.0.0.2:8900")
+        .poll_interval_secs(10)
+        .fetch_concurrency(2)
+        .start_from(StartPoint::Signature(Signature::default().to_string()))
+        .build()
+        .unwrap();
+
+    assert_eq!(config.solana.rpc_url, "https://api.devnet.solana.com");
+    assert_eq!(config.solana.commitment, CommitmentLevel::Finalized);
+    assert_eq!(config.solana.extra_endpoints.len(), 1);
+    assert_eq!(config.synchronizer.poll_interval_secs, 10);
+    assert_eq!(config.synchronizer.fetch_concurrency, 2);
+    // Untouched settings keep their defaults.
+    assert_eq!(config.synchronizer.max_signature_fetch, 1000);
+    assert!(ConnectorConfig::default().validate().is_ok());
+}
+
+#[test]
+fn test_builder_rejects_invalid_urls() {
+    let err = ConnectorConfig::builder()
+        .rpc_url("not a url")
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidUrl { ref field, .. } if field == "solana.rpc_url"));
+
+    // A WebSocket endpoint where an HTTP one is expected.
+    let err = ConnectorConfig::builder()
+        .extra_endpoint("ws://10.0.0.2:8900", "ws://10.0.0.2:8900")
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "solana.extra_endpoints[0].rpc_url: 'ws://10.0.0.2:8900' is not a valid HTTP(S) URL"
+    );
+
+    let err = ConnectorConfig::builder()
+        .backfill_rpc_url("ftp://archive")
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidUrl { .. }));
+}
+
+#[test]
+fn test_builder_rejects_zero_intervals_and_capacities() {
+    let cases = [
+        (
+            ConnectorConfig::builder().poll_interval_secs(0),
+            "synchronizer.poll_interval_secs",
+        ),
+        (
+            ConnectorConfig::builder().health_check_interval_secs(0),
+            "solana.health_check_interval_secs",
+        ),
+        (
+            ConnectorConfig::builder().fetch_concurrency(0),
+            "synchronizer.fetch_concurrency",
+        ),
+        (
+            ConnectorConfig::builder().rate_limit(RateLimit {
+                requests_per_second: 0,
+                burst: None,
+            }),
+            "solana.rate_limit.requests_per_second",
+        ),
+    ];
+    for (builder, field) in cases {
+        assert_eq!(builder.build().unwrap_err(), ConfigError::Zero { field });
+    }
+
+    let err = ConnectorConfig::builder()
+        .max_signature_fetch(5000)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "synchronizer.max_signature_fetch must be at most 1000, got 5000"
+    );
+
+    let err = ConnectorConfig::builder()
+        .reconnect_backoff_ms(5_000, 1_000)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::TooLarge {
+            field: "solana.reconnect_min_backoff_ms",
+            value: 5_000,
+            max: 1_000,
+        }
+    );
+
+    let err = ConnectorConfig::builder()
+        .start_from(StartPoint::Signature("nope".to_string()))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidSignature { .. }));
+}
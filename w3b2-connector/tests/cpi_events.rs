@@ -0,0 +1,74 @@
+mod common;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use common::action_event;
+use solana_sdk::{bs58, pubkey::Pubkey};
+use solana_transaction_status::{UiCompiledInstruction, UiInnerInstructions, UiInstruction};
+use w3b2_connector::events::{parse_cpi_events, BridgeEvent};
+
+fn instruction(program_id_index: u8, data: &[u8]) -> UiInstruction {
+    UiInstruction::Compiled(UiCompiledInstruction {
+        program_id_index,
+        accounts: vec![],
+        data: bs58::encode(data).into_string(),
+        stack_height: Some(2),
+    })
+}
+
+fn emit_cpi_data(event: &BridgeEvent) -> Vec<u8> {
+    [EVENT_IX_TAG_LE, &event.to_bytes()].concat()
+}
+
+#[test]
+fn test_parse_cpi_events_decodes_self_invocations() {
+    // Index 0 is another program, index 1 the bridge program.
+    let keys = vec![Pubkey::new_unique(), w3b2_bridge_program::ID];
+    let inner = vec![
+        UiInnerInstructions {
+            index: 0,
+            instructions: vec![
+                instruction(1, &emit_cpi_data(&action_event(Pubkey::new_unique(), 1))),
+                // Event data sent to another program is not a bridge event.
+                instruction(0, &emit_cpi_data(&action_event(Pubkey::new_unique(), 2))),
+                // A regular bridge instruction carries no event.
+                instruction(1, &[1, 2, 3]),
+            ],
+        },
+        UiInnerInstructions {
+            index: 1,
+            instructions: vec![instruction(
+                1,
+                &emit_cpi_data(&action_event(Pubkey::new_unique(), 3)),
+            )],
+        },
+    ];
+
+    let (events, failures) = parse_cpi_events(&keys, &inner);
+
+    let sessions: Vec<u64> = events
+        .iter()
+        .map(|event| match event {
+            BridgeEvent::OffChainActionLogged(e) => e.session_id,
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    assert_eq!(sessions, vec![1, 3]);
+    assert_eq!(failures, 0);
+}
+
+#[test]
+fn test_parse_cpi_events_counts_malformed_payloads() {
+    let keys = vec![w3b2_bridge_program::ID];
+    let mut truncated = emit_cpi_data(&action_event(Pubkey::new_unique(), 1));
+    truncated.truncate(truncated.len() - 4);
+    let unknown = [EVENT_IX_TAG_LE, &[9; 12]].concat();
+    let inner = vec![UiInnerInstructions {
+        index: 0,
+        instructions: vec![instruction(0, &truncated), instruction(0, &unknown)],
+    }];
+
+    let (events, failures) = parse_cpi_events(&keys, &inner);
+
+    assert_eq!(failures, 1);
+    assert!(matches!(&events[..], [BridgeEvent::Unknown { raw }] if raw.data == [9; 12]));
+}
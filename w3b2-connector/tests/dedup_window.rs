@@ -0,0 +1,47 @@
+use w3b2_connector::workers::DedupWindow;
+
+#[test]
+fn test_dedup_window_skips_repeated_events() {
+    let mut window = DedupWindow::new(8);
+    assert!(window.insert("sig-a", 0));
+    assert!(window.insert("sig-a", 1));
+    assert!(window.insert("sig-b", 0));
+
+    // The same transaction seen by the other worker.
+    assert!(!window.insert("sig-a", 0));
+    assert!(!window.insert("sig-a", 1));
+    assert_eq!(window.len(), 3);
+}
+
+#[test]
+fn test_dedup_window_forgets_oldest_events() {
+    let mut window = DedupWindow::new(2);
+    assert!(window.insert("sig-a", 0));
+    assert!(window.insert("sig-b", 0));
+    assert!(window.insert("sig-c", 0));
+
+    assert_eq!(window.len(), 2);
+    assert!(!window.insert("sig-c", 0));
+    assert!(window.insert("sig-a", 0));
+}
+
+#[test]
+fn test_empty_dedup_window_is_disabled() {
+    let mut window = DedupWindow::new(0);
+    assert!(window.insert("sig-a", 0));
+    assert!(window.insert("sig-a", 0));
+    assert!(window.is_empty());
+}
+
+#[test]
+fn test_dedup_window_lookup_does_not_record() {
+    let mut window = DedupWindow::new(8);
+    // Looked up before the event is stored; a failed store must not mark it.
+    assert!(!window.contains("sig-a", 0));
+    assert!(!window.contains("sig-a", 0));
+    assert!(window.is_empty());
+
+    assert!(window.insert("sig-a", 0));
+    assert!(window.contains("sig-a", 0));
+    assert!(!window.contains("sig-a", 1));
+}
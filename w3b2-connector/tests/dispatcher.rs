@@ -0,0 +1,301 @@
+mod common;
+
+use common::{action_event, envelope};
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+use w3b2_bridge_program::events::UserCommandDispatched;
+use w3b2_connector::{
+    config::BackpressurePolicy,
+    dispatcher::{
+        Dispatcher, DispatcherCommand, EventFilter, LagStatus, ListenerId, ListenerOptions,
+    },
+    events::{BridgeEvent, EventEnvelope, EventKind, RawEvent},
+};
+
+fn command(admin: Pubkey, command_id: u16, price_paid: u64) -> BridgeEvent {
+    BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+        sender: Pubkey::new_unique(),
+        target_admin_authority: admin,
+        command_id,
+        price_paid,
+        high_priority: false,
+        priority_fee: 0,
+        payload: Vec::new(),
+        ts: 0,
+    })
+}
+
+fn session_id(envelope: EventEnvelope) -> u64 {
+    match envelope.event {
+        BridgeEvent::OffChainActionLogged(e) => e.session_id,
+        BridgeEvent::UserCommandDispatched(e) => e.command_id as u64,
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+/// Spawns a dispatcher and returns its event and command channels.
+fn spawn_dispatcher() -> (
+    broadcast::Sender<EventEnvelope>,
+    mpsc::Sender<DispatcherCommand>,
+) {
+    let (event_tx, event_rx) = broadcast::channel(16);
+    let (command_tx, command_rx) = mpsc::channel(16);
+    tokio::spawn(async move { Dispatcher::new(event_rx, command_rx).run().await });
+    (event_tx, command_tx)
+}
+
+async fn register(
+    command_tx: &mpsc::Sender<DispatcherCommand>,
+    pubkey: Pubkey,
+    id: ListenerId,
+) -> mpsc::Receiver<EventEnvelope> {
+    register_with(command_tx, pubkey, id, 16, BackpressurePolicy::Block)
+        .await
+        .1
+}
+
+async fn register_with(
+    command_tx: &mpsc::Sender<DispatcherCommand>,
+    pubkey: Pubkey,
+    id: ListenerId,
+    capacity: usize,
+    backpressure: BackpressurePolicy,
+) -> (LagStatus, mpsc::Receiver<EventEnvelope>) {
+    let options = ListenerOptions {
+        backpressure,
+        ..Default::default()
+    };
+    register_with_options(command_tx, pubkey, id, capacity, options).await
+}
+
+async fn register_with_options(
+    command_tx: &mpsc::Sender<DispatcherCommand>,
+    pubkey: Pubkey,
+    id: ListenerId,
+    capacity: usize,
+    options: ListenerOptions,
+) -> (LagStatus, mpsc::Receiver<EventEnvelope>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let lag_status = LagStatus::default();
+    command_tx
+        .send(DispatcherCommand::Register {
+            pubkey,
+            id,
+            tx,
+            options,
+            lag_status: lag_status.clone(),
+        })
+        .await
+        .unwrap();
+    (lag_status, rx)
+}
+
+/// Publishes `count` events for `pubkey` while nobody reads them.
+async fn flood(event_tx: &broadcast::Sender<EventEnvelope>, pubkey: Pubkey, count: u64) {
+    // Let the dispatcher process pending registrations first.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    for session in 1..=count {
+        event_tx
+            .send(envelope("sig", 0, action_event(pubkey, session)))
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+async fn drain(rx: &mut mpsc::Receiver<EventEnvelope>) -> Vec<u64> {
+    let mut sessions = Vec::new();
+    while let Some(event) = recv(rx).await {
+        sessions.push(session_id(event));
+    }
+    sessions
+}
+
+async fn recv(rx: &mut mpsc::Receiver<EventEnvelope>) -> Option<EventEnvelope> {
+    tokio::time::timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+#[tokio::test]
+async fn test_dispatcher_delivers_to_every_listener_of_a_pubkey() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let mut first = register(&command_tx, admin, 1).await;
+    let mut second = register(&command_tx, admin, 2).await;
+    let mut third = register(&command_tx, admin, 3).await;
+    // Let the dispatcher process the registrations before publishing.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    event_tx
+        .send(envelope("sig", 0, action_event(admin, 1)))
+        .unwrap();
+    assert_eq!(recv(&mut first).await.map(session_id), Some(1));
+    assert_eq!(recv(&mut second).await.map(session_id), Some(1));
+    assert_eq!(recv(&mut third).await.map(session_id), Some(1));
+
+    // A listener going away does not affect the others.
+    drop(second);
+    event_tx
+        .send(envelope("sig", 0, action_event(admin, 2)))
+        .unwrap();
+    assert_eq!(recv(&mut first).await.map(session_id), Some(2));
+    assert_eq!(recv(&mut third).await.map(session_id), Some(2));
+
+    // Unregistering the pubkey closes all of its listeners.
+    command_tx
+        .send(DispatcherCommand::Unregister(admin))
+        .await
+        .unwrap();
+    assert!(recv(&mut first).await.is_none());
+    assert!(recv(&mut third).await.is_none());
+}
+
+#[tokio::test]
+async fn test_dispatcher_unregisters_a_single_listener() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let mut kept = register(&command_tx, admin, 1).await;
+    let mut removed = register(&command_tx, admin, 2).await;
+
+    command_tx
+        .send(DispatcherCommand::UnregisterListener(admin, 2))
+        .await
+        .unwrap();
+    assert!(recv(&mut removed).await.is_none());
+
+    event_tx
+        .send(envelope("sig", 0, action_event(admin, 1)))
+        .unwrap();
+    assert_eq!(recv(&mut kept).await.map(session_id), Some(1));
+}
+
+#[tokio::test]
+async fn test_dispatcher_drop_newest_keeps_buffered_events() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let (_, mut slow) =
+        register_with(&command_tx, admin, 1, 2, BackpressurePolicy::DropNewest).await;
+    let mut fast = register(&command_tx, admin, 2).await;
+
+    flood(&event_tx, admin, 5).await;
+
+    assert_eq!(drain(&mut slow).await, vec![1, 2]);
+    // The slow listener does not hold up the others.
+    assert_eq!(drain(&mut fast).await, vec![1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_dispatcher_drop_oldest_keeps_latest_events() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let (_, mut slow) =
+        register_with(&command_tx, admin, 1, 1, BackpressurePolicy::DropOldest).await;
+
+    flood(&event_tx, admin, 6).await;
+
+    let sessions = drain(&mut slow).await;
+    assert!(sessions.len() < 6, "nothing was dropped: {:?}", sessions);
+    assert_eq!(sessions.first(), Some(&1));
+    assert_eq!(sessions.last(), Some(&6));
+}
+
+#[tokio::test]
+async fn test_dispatcher_disconnects_lagging_listener() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let (lag_status, mut slow) =
+        register_with(&command_tx, admin, 1, 2, BackpressurePolicy::Disconnect).await;
+    let (kept_status, mut kept) =
+        register_with(&command_tx, admin, 2, 16, BackpressurePolicy::Disconnect).await;
+
+    flood(&event_tx, admin, 3).await;
+
+    // The buffered events are still delivered before the stream ends.
+    assert_eq!(drain(&mut slow).await, vec![1, 2]);
+    assert!(slow.recv().await.is_none());
+    assert!(lag_status.is_lagged());
+
+    assert_eq!(drain(&mut kept).await, vec![1, 2, 3]);
+    assert!(!kept_status.is_lagged());
+}
+
+#[test]
+fn test_event_filter_conditions() {
+    let admin = Pubkey::new_unique();
+    let filter = EventFilter {
+        kinds: vec![
+            EventKind::UserCommandDispatched,
+            EventKind::OffChainActionLogged,
+        ],
+        command_ids: Some(10..=20),
+        min_price: Some(1_000),
+    };
+
+    assert!(filter.matches(&command(admin, 10, 1_000)));
+    assert!(!filter.matches(&command(admin, 21, 1_000)));
+    assert!(!filter.matches(&command(admin, 15, 999)));
+    // The command conditions don't apply to other kinds.
+    assert!(filter.matches(&action_event(admin, 1)));
+    let unknown = BridgeEvent::Unknown {
+        raw: RawEvent::default(),
+    };
+    assert!(!filter.matches(&unknown));
+    assert!(EventFilter::default().matches(&unknown));
+}
+
+#[tokio::test]
+async fn test_dispatcher_applies_listener_filters() {
+    let (event_tx, command_tx) = spawn_dispatcher();
+    let admin = Pubkey::new_unique();
+    let options = ListenerOptions {
+        filter: EventFilter {
+            kinds: vec![EventKind::UserCommandDispatched],
+            min_price: Some(500),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (_, mut filtered) = register_with_options(&command_tx, admin, 1, 16, options).await;
+    let mut unfiltered = register(&command_tx, admin, 2).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    for event in [
+        command(admin, 1, 100),
+        action_event(admin, 2),
+        command(admin, 3, 500),
+    ] {
+        event_tx.send(envelope("sig", 0, event)).unwrap();
+    }
+
+    assert_eq!(drain(&mut filtered).await, vec![3]);
+    assert_eq!(drain(&mut unfiltered).await, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_dispatcher_routes_buffered_events_before_shutting_down() {
+    let (event_tx, event_rx) = broadcast::channel(16);
+    let (command_tx, command_rx) = mpsc::channel(16);
+    let shutdown = CancellationToken::new();
+    let dispatcher = tokio::spawn({
+        let mut dispatcher = Dispatcher::new(event_rx, command_rx).with_shutdown(shutdown.clone());
+        async move { dispatcher.run().await }
+    });
+    let admin = Pubkey::new_unique();
+    let mut listener = register(&command_tx, admin, 1).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // The dispatcher does not get to run before the shutdown is requested.
+    for session in 1..=3 {
+        event_tx
+            .send(envelope("sig", 0, action_event(admin, session)))
+            .unwrap();
+    }
+    shutdown.cancel();
+    dispatcher.await.unwrap();
+
+    assert_eq!(drain(&mut listener).await, vec![1, 2, 3]);
+    assert!(listener.recv().await.is_none());
+}
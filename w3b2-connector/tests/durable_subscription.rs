@@ -0,0 +1,75 @@
+mod common;
+
+use common::action;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::{sync::Arc, time::Duration};
+use w3b2_connector::{
+    config::ConnectorConfig,
+    storage::{MemoryStorage, Storage},
+    workers::{EventManager, EventManagerHandle},
+};
+
+fn handle(storage: Arc<MemoryStorage>) -> EventManagerHandle {
+    let (_manager, handle) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(RpcClient::new_mock("succeeds".to_string())),
+        storage,
+        16,
+        16,
+    );
+    handle
+}
+
+#[tokio::test]
+async fn test_durable_subscription_resumes_after_ack() {
+    let actor = Pubkey::new_unique();
+    let storage = Arc::new(MemoryStorage::new());
+    for slot in [5, 10, 15] {
+        storage
+            .append_event(&action(actor, slot, &format!("sig-{slot}"), 0))
+            .await
+            .unwrap();
+    }
+    storage
+        .append_event(&action(Pubkey::new_unique(), 12, "sig-12", 0))
+        .await
+        .unwrap();
+    let handle = handle(storage.clone());
+
+    // First session: process slot 5, receive slot 10 but crash before acking it.
+    let mut subscription = handle.subscribe_durable("svc", actor, 16).await.unwrap();
+    let first = subscription.recv().await.unwrap();
+    assert_eq!(first.slot, 5);
+    subscription.ack(&first).await.unwrap();
+    assert_eq!(subscription.recv().await.unwrap().slot, 10);
+    drop(subscription);
+
+    assert_eq!(
+        storage.get_cursor("svc").await.unwrap().map(|c| c.slot),
+        Some(5)
+    );
+
+    // Second session: the unacknowledged event is delivered again.
+    let mut subscription = handle.subscribe_durable("svc", actor, 16).await.unwrap();
+    let mut slots = Vec::new();
+    for _ in 0..2 {
+        let envelope = subscription.recv().await.unwrap();
+        slots.push(envelope.slot);
+        subscription.ack(&envelope).await.unwrap();
+    }
+    assert_eq!(slots, vec![10, 15]);
+    drop(subscription);
+
+    // Third session: everything was acknowledged, so nothing is replayed.
+    let mut subscription = handle.subscribe_durable("svc", actor, 16).await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), subscription.recv())
+            .await
+            .is_err()
+    );
+
+    // Other subscribers keep their own cursor.
+    let mut other = handle.subscribe_durable("audit", actor, 16).await.unwrap();
+    assert_eq!(other.recv().await.unwrap().slot, 5);
+}
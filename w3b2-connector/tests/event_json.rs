@@ -0,0 +1,86 @@
+#![cfg(feature = "serde")]
+
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::{
+    events::{AdminPricesUpdated, UserCommandDispatched},
+    state::PriceEntry,
+};
+use w3b2_connector::events::{BridgeEvent, EventEnvelope, EventKind, RawEvent};
+
+fn round_trip(event: &BridgeEvent) -> BridgeEvent {
+    let json = serde_json::to_string(event).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_envelope_json_shape() {
+    let sender = Pubkey::new_unique();
+    let envelope = EventEnvelope {
+        slot: 42,
+        signature: "sig".to_string(),
+        index: 1,
+        tx_index: None,
+        block_time: Some(1_700_000_000),
+        event: BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+            sender,
+            target_admin_authority: sender,
+            command_id: 7,
+            price_paid: 1_000,
+            high_priority: false,
+            priority_fee: 0,
+            payload: vec![1, 2, 3],
+            ts: 5,
+        }),
+    };
+
+    let value = serde_json::to_value(&envelope).unwrap();
+    assert_eq!(value["slot"], 42);
+    assert_eq!(value["block_time"], 1_700_000_000);
+    assert_eq!(value["event"]["kind"], "UserCommandDispatched");
+    assert_eq!(value["event"]["sender"], sender.to_string());
+    assert_eq!(value["event"]["payload"], "AQID");
+
+    let restored: EventEnvelope = serde_json::from_value(value).unwrap();
+    assert_eq!(restored.cursor(), envelope.cursor());
+    assert_eq!(restored.block_time, envelope.block_time);
+    assert_eq!(restored.event.to_bytes(), envelope.event.to_bytes());
+}
+
+#[test]
+fn test_event_json_round_trip() {
+    let prices = BridgeEvent::AdminPricesUpdated(AdminPricesUpdated {
+        authority: Pubkey::new_unique(),
+        new_prices: vec![PriceEntry::new(1, 100), PriceEntry::new(2, 200)],
+        ts: 9,
+    });
+    let value = serde_json::to_value(&prices).unwrap();
+    assert_eq!(
+        value["new_prices"][1],
+        json!({ "command_id": 2, "price": 200 })
+    );
+    assert_eq!(round_trip(&prices).to_bytes(), prices.to_bytes());
+
+    let unknown = BridgeEvent::Unknown {
+        raw: RawEvent {
+            data: vec![9; 10],
+            signature: Some("sig".to_string()),
+            logs: vec!["Program data: CQkJCQkJCQkJCQ==".to_string()],
+        },
+    };
+    assert!(matches!(
+        round_trip(&unknown),
+        BridgeEvent::Unknown { raw } if raw.data == [9; 10] && raw.logs.len() == 1
+    ));
+
+    assert_eq!(
+        serde_json::to_value(EventKind::UserFundsDeposited).unwrap(),
+        "UserFundsDeposited"
+    );
+}
+
+#[test]
+fn test_event_json_rejects_invalid_pubkey() {
+    let json = json!({ "kind": "AdminProfileClosed", "authority": "not-a-pubkey", "ts": 0 });
+    assert!(serde_json::from_value::<BridgeEvent>(json).is_err());
+}
@@ -0,0 +1,61 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::{sync::Arc, time::Duration};
+use w3b2_connector::{config::ConnectorConfig, storage::MemoryStorage, workers::EventManager};
+
+#[tokio::test]
+async fn test_event_manager_stops_through_its_handle() {
+    let (manager, handle) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(RpcClient::new_mock("succeeds".to_string())),
+        Arc::new(MemoryStorage::new()),
+        16,
+        16,
+    );
+    let runner = tokio::spawn(manager.run());
+
+    let mut admin = handle
+        .listen_as_admin(Pubkey::new_unique(), 16)
+        .await
+        .unwrap();
+    let user = handle
+        .clone()
+        .listen_as_user(Pubkey::new_unique(), 16)
+        .await
+        .unwrap();
+    let mut user_events = user.personal_events();
+
+    handle.stop().await;
+    tokio::time::timeout(Duration::from_secs(5), runner)
+        .await
+        .expect("the runner should return after stop")
+        .unwrap();
+
+    // Stopping closes the streams of every listener.
+    assert!(admin.personal_events().recv().await.is_none());
+    drop(user);
+    assert!(user_events.recv().await.is_err());
+}
+
+#[tokio::test]
+async fn test_listening_after_stop_fails() {
+    let (manager, handle) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(RpcClient::new_mock("succeeds".to_string())),
+        Arc::new(MemoryStorage::new()),
+        16,
+        16,
+    );
+    let runner = tokio::spawn(manager.run());
+    handle.stop().await;
+    runner.await.unwrap();
+
+    assert!(handle
+        .listen_as_admin(Pubkey::new_unique(), 16)
+        .await
+        .is_err());
+    assert!(handle
+        .listen_as_user(Pubkey::new_unique(), 16)
+        .await
+        .is_err());
+}
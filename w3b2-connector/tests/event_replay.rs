@@ -0,0 +1,68 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use w3b2_bridge_program::events::UserCommandDispatched;
+use w3b2_connector::{
+    config::ConnectorConfig,
+    events::{BridgeEvent, EventEnvelope},
+    storage::{MemoryStorage, Storage},
+    workers::EventManager,
+};
+
+fn command(admin: Pubkey, command_id: u16, slot: u64) -> EventEnvelope {
+    EventEnvelope {
+        slot,
+        signature: format!("sig-{slot}"),
+        index: 0,
+        tx_index: None,
+        block_time: None,
+        event: BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+            sender: Pubkey::new_unique(),
+            target_admin_authority: admin,
+            command_id,
+            price_paid: 0,
+            high_priority: false,
+            priority_fee: 0,
+            payload: Vec::new(),
+            ts: 0,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_replay_as_admin_delivers_missed_commands() {
+    let admin = Pubkey::new_unique();
+    let storage = Arc::new(MemoryStorage::new());
+    storage.append_event(&command(admin, 1, 5)).await.unwrap();
+    storage.append_event(&command(admin, 2, 10)).await.unwrap();
+    storage
+        .append_event(&command(Pubkey::new_unique(), 3, 11))
+        .await
+        .unwrap();
+    storage.append_event(&command(admin, 4, 12)).await.unwrap();
+
+    let (_manager, handle) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(RpcClient::new_mock("succeeds".to_string())),
+        storage,
+        16,
+        16,
+    );
+
+    let mut listener = handle.replay_as_admin(admin, 10, 16).await.unwrap();
+    let mut replayed = Vec::new();
+    for _ in 0..2 {
+        match listener
+            .incoming_user_commands()
+            .recv()
+            .await
+            .map(|e| e.event)
+        {
+            Some(BridgeEvent::UserCommandDispatched(e)) => replayed.push(e.command_id),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    assert_eq!(replayed, vec![2, 4]);
+    assert!(listener.incoming_user_commands().try_recv().is_err());
+}
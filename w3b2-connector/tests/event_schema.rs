@@ -0,0 +1,91 @@
+use anchor_lang::Discriminator;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::events::{
+    AdminCommandDispatched, UserCommandDispatched, UserFundsDeposited, UserFundsWithdrawn,
+};
+use w3b2_connector::events::{parse_event_data, parse_event_data_with_version, BridgeEvent};
+use w3b2_connector::schema::EVENT_SCHEMA_VERSION;
+
+/// Encodes an event body the way an older program version emitted it.
+fn legacy<T: borsh::BorshSerialize>(discriminator: &[u8], body: T) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend(borsh::to_vec(&body).unwrap());
+    data
+}
+
+#[test]
+fn test_v1_command_events_are_upgraded() {
+    let sender = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let data = legacy(
+        UserCommandDispatched::DISCRIMINATOR,
+        (sender, admin, 7u16, 1_000u64, vec![1u8, 2], 42i64),
+    );
+
+    let (event, version) = parse_event_data_with_version(&data).unwrap();
+    assert_eq!(version, 1);
+    let BridgeEvent::UserCommandDispatched(e) = event else {
+        panic!("expected UserCommandDispatched, got {:?}", event);
+    };
+    assert_eq!(e.sender, sender);
+    assert_eq!(e.command_id, 7);
+    assert_eq!(e.price_paid, 1_000);
+    assert!(!e.high_priority);
+    assert_eq!(e.priority_fee, 0);
+    assert_eq!(e.payload, vec![1, 2]);
+    assert_eq!(e.ts, 42);
+
+    let data = legacy(
+        AdminCommandDispatched::DISCRIMINATOR,
+        (admin, sender, 9u64, vec![3u8], 43i64),
+    );
+    let BridgeEvent::AdminCommandDispatched(e) = parse_event_data(&data).unwrap() else {
+        panic!("expected AdminCommandDispatched");
+    };
+    assert_eq!(e.command_id, 9);
+    assert_eq!(e.rebate, 0);
+    assert_eq!(e.payload, vec![3]);
+}
+
+#[test]
+fn test_v1_funds_events_are_upgraded() {
+    let user = Pubkey::new_unique();
+    let data = legacy(
+        UserFundsDeposited::DISCRIMINATOR,
+        (user, 500u64, 1_500u64, 1i64),
+    );
+    let BridgeEvent::UserFundsDeposited(e) = parse_event_data(&data).unwrap() else {
+        panic!("expected UserFundsDeposited");
+    };
+    assert_eq!(e.new_deposit_balance, 1_500);
+    assert_eq!(e.rent_reserve, 0);
+
+    let destination = Pubkey::new_unique();
+    let data = legacy(
+        UserFundsWithdrawn::DISCRIMINATOR,
+        (user, 500u64, destination, 1_000u64, 2i64),
+    );
+    let BridgeEvent::UserFundsWithdrawn(e) = parse_event_data(&data).unwrap() else {
+        panic!("expected UserFundsWithdrawn");
+    };
+    assert_eq!(e.destination, destination);
+    assert_eq!(e.rent_reserve, 0);
+}
+
+#[test]
+fn test_current_layout_reports_current_version() {
+    let event = BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+        authority: Pubkey::new_unique(),
+        amount: 1,
+        new_deposit_balance: 2,
+        rent_reserve: 3,
+        ts: 4,
+    });
+    let (decoded, version) = parse_event_data_with_version(&event.to_bytes()).unwrap();
+    assert_eq!(version, EVENT_SCHEMA_VERSION);
+    assert!(matches!(decoded, BridgeEvent::UserFundsDeposited(e) if e.rent_reserve == 3));
+
+    // A body that matches no layout is still an error.
+    let data = legacy(UserFundsDeposited::DISCRIMINATOR, (1u8,));
+    assert!(parse_event_data(&data).is_err());
+}
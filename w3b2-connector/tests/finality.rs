@@ -0,0 +1,57 @@
+mod common;
+
+use common::action_at;
+use serde_json::json;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest};
+use std::collections::HashMap;
+use w3b2_connector::{
+    storage::{MemoryStorage, Storage},
+    workers::FinalityTracker,
+};
+
+#[tokio::test]
+async fn test_finalized_events_stop_being_tracked() {
+    let storage = MemoryStorage::new();
+    let envelope = action_at(10);
+    storage.append_event(&envelope).await.unwrap();
+
+    let mut tracker = FinalityTracker::new(150);
+    tracker.track(envelope.clone());
+    tracker.track(envelope);
+    assert_eq!(tracker.pending(), 1);
+
+    // The mock reports every signature as finalized.
+    let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    let revocations = tracker.check(&rpc_client, &storage).await.unwrap();
+
+    assert!(revocations.is_empty());
+    assert_eq!(tracker.pending(), 0);
+    assert_eq!(storage.events_by_slot_range(0, 100).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_dropped_transactions_are_revoked() {
+    let storage = MemoryStorage::new();
+    let dropped = action_at(10);
+    let recent = action_at(900);
+    storage.append_event(&dropped).await.unwrap();
+    storage.append_event(&recent).await.unwrap();
+
+    let mut tracker = FinalityTracker::new(150);
+    tracker.track(dropped.clone());
+    tracker.track(recent.clone());
+
+    // No signature is known to the cluster, and the finalized slot is 1000.
+    let mocks = HashMap::from([(RpcRequest::GetSlot, json!(1000))]);
+    let rpc_client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+    let revocations = tracker.check(&rpc_client, &storage).await.unwrap();
+
+    assert_eq!(revocations.len(), 1);
+    assert_eq!(revocations[0].envelope.signature, dropped.signature);
+    assert_eq!(revocations[0].finalized_slot, 1000);
+    // The recent transaction may still finalize.
+    assert_eq!(tracker.pending(), 1);
+    let remaining = storage.events_by_slot_range(0, u64::MAX).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].signature, recent.signature);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use common::envelope;
+use futures::StreamExt;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+use w3b2_bridge_program::events::{
+    AdminCommKeyUpdated, UserCommandDispatched, UserFundsDeposited, UserProfileCreated,
+};
+use w3b2_connector::{
+    events::{BridgeEvent, EventKind},
+    instructions::admin_profile_pda,
+    listener::{AdminEventKind, AdminListener, UserEventKind, UserListener},
+};
+
+fn command(sender: Pubkey, admin: Pubkey) -> BridgeEvent {
+    BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+        sender,
+        target_admin_authority: admin,
+        command_id: 1,
+        price_paid: 0,
+        high_priority: false,
+        priority_fee: 0,
+        payload: Vec::new(),
+        ts: 0,
+    })
+}
+
+#[tokio::test]
+async fn test_admin_listener_streams() {
+    let admin = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let (tx, rx) = mpsc::channel(16);
+    let (personal, commands, new_users) = AdminListener::new(admin, rx, 16).into_streams();
+
+    let events = [
+        BridgeEvent::AdminCommKeyUpdated(AdminCommKeyUpdated {
+            authority: admin,
+            new_comm_pubkey: Pubkey::new_unique(),
+            ts: 0,
+        }),
+        command(user, admin),
+        // Addressed to another admin.
+        command(user, Pubkey::new_unique()),
+        BridgeEvent::UserProfileCreated(UserProfileCreated {
+            authority: user,
+            target_admin: admin_profile_pda(&admin),
+            communication_pubkey: Pubkey::new_unique(),
+            ts: 0,
+        }),
+    ];
+    for (index, event) in events.into_iter().enumerate() {
+        tx.send(envelope("sig", index as u32, event)).await.unwrap();
+    }
+    drop(tx);
+
+    let kinds = |stream: w3b2_connector::listener::EventStream| async move {
+        stream
+            .map(|envelope| envelope.event.kind())
+            .collect::<Vec<_>>()
+            .await
+    };
+    assert_eq!(kinds(personal).await, vec![EventKind::AdminCommKeyUpdated]);
+    assert_eq!(
+        kinds(commands).await,
+        vec![EventKind::UserCommandDispatched]
+    );
+    assert_eq!(kinds(new_users).await, vec![EventKind::UserProfileCreated]);
+}
+
+#[tokio::test]
+async fn test_merged_listener_streams_work_with_combinators() {
+    let admin = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+
+    let (tx, rx) = mpsc::channel(16);
+    let merged = AdminListener::new(admin, rx, 16).into_stream();
+    tx.send(envelope("sig", 0, command(user, admin)))
+        .await
+        .unwrap();
+    tx.send(envelope("sig", 1, command(Pubkey::new_unique(), admin)))
+        .await
+        .unwrap();
+    drop(tx);
+    let from_user: Vec<u32> = merged
+        .filter(|envelope| {
+            let wanted = matches!(&envelope.event, BridgeEvent::UserCommandDispatched(e) if e.sender == user);
+            async move { wanted }
+        })
+        .map(|envelope| envelope.index)
+        .collect()
+        .await;
+    assert_eq!(from_user, vec![0]);
+
+    let (tx, rx) = mpsc::channel(16);
+    let mut merged = UserListener::new(user, rx, 16).into_stream();
+    tx.send(envelope(
+        "sig",
+        0,
+        BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+            authority: user,
+            amount: 1,
+            new_deposit_balance: 1,
+            rent_reserve: 0,
+            ts: 0,
+        }),
+    ))
+    .await
+    .unwrap();
+    tx.send(envelope("sig", 1, command(user, admin)))
+        .await
+        .unwrap();
+
+    // The categories are merged without a cross-category order.
+    let kinds: HashSet<EventKind> = [
+        merged.next().await.unwrap().event.kind(),
+        merged.next().await.unwrap().event.kind(),
+    ]
+    .into();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::UserCommandDispatched,
+            EventKind::UserFundsDeposited
+        ]
+        .into()
+    );
+
+    drop(tx);
+    assert!(merged.next().await.is_none());
+}
+
+#[test]
+fn test_event_kinds_follow_the_listener_routing() {
+    let admin = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let profile_created = BridgeEvent::UserProfileCreated(UserProfileCreated {
+        authority: user,
+        target_admin: admin_profile_pda(&admin),
+        communication_pubkey: Pubkey::new_unique(),
+        ts: 0,
+    });
+    let deposit = BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+        authority: user,
+        amount: 1,
+        new_deposit_balance: 1,
+        rent_reserve: 0,
+        ts: 0,
+    });
+
+    assert_eq!(
+        UserEventKind::of(&user, &deposit),
+        Some(UserEventKind::Personal)
+    );
+    assert_eq!(
+        UserEventKind::of(&user, &command(user, admin)),
+        Some(UserEventKind::ServiceInteraction)
+    );
+    assert_eq!(
+        UserEventKind::of(&user, &profile_created),
+        Some(UserEventKind::ServiceInteraction)
+    );
+    assert_eq!(UserEventKind::of(&admin, &deposit), None);
+
+    assert_eq!(
+        AdminEventKind::of(&admin, &command(user, admin)),
+        Some(AdminEventKind::IncomingUserCommand)
+    );
+    assert_eq!(
+        AdminEventKind::of(&admin, &profile_created),
+        Some(AdminEventKind::NewUserProfile)
+    );
+    assert_eq!(
+        AdminEventKind::of(&Pubkey::new_unique(), &command(user, admin)),
+        None
+    );
+    assert_eq!(AdminEventKind::of(&admin, &deposit), None);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::action_log;
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use solana_sdk::signature::Signature;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+use w3b2_connector::{
+    config::ConnectorConfig,
+    rpc::MockSolanaRpc,
+    storage::{MemoryStorage, Storage},
+    workers::EventManager,
+};
+
+/// Accepts the live worker's WebSocket connection and confirms its logs subscription.
+async fn accept_subscription(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+    let request = loop {
+        if let Message::Text(text) = ws.next().await.unwrap().unwrap() {
+            break serde_json::from_str::<serde_json::Value>(&text).unwrap();
+        }
+    };
+    assert_eq!(request["method"], "logsSubscribe");
+    let reply = json!({ "jsonrpc": "2.0", "result": 1, "id": request["id"] });
+    ws.send(Message::Text(reply.to_string())).await.unwrap();
+    ws
+}
+
+fn logs_notification(slot: u64, signature: Signature, logs: Vec<String>) -> Message {
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "logsNotification",
+        "params": {
+            "result": {
+                "context": { "slot": slot },
+                "value": { "signature": signature.to_string(), "err": null, "logs": logs },
+            },
+            "subscription": 1,
+        },
+    });
+    Message::Text(notification.to_string())
+}
+
+#[tokio::test]
+async fn test_reconnect_does_not_skip_the_missed_window() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let storage = Arc::new(MemoryStorage::new());
+    let synced = Signature::new_unique();
+    rpc.add_transaction(synced, 10, Vec::new());
+    storage
+        .set_sync_state(10, &synced.to_string())
+        .await
+        .unwrap();
+
+    let mut config = ConnectorConfig::default();
+    config.solana.ws_url = format!("ws://{}", listener.local_addr().unwrap());
+    config.solana.reconnect_min_backoff_ms = 10;
+    config.solana.reconnect_max_backoff_ms = 100;
+    // Only the reconnect wakes the catch-up worker.
+    config.synchronizer.poll_interval_secs = 3600;
+    let (manager, handle) =
+        EventManager::new(Arc::new(config), rpc.clone(), storage.clone(), 16, 16);
+    tokio::spawn(manager.run());
+    let mut events = handle.subscribe_all();
+
+    let first = accept_subscription(&listener).await;
+    drop(first);
+    let (missed, live) = (Signature::new_unique(), Signature::new_unique());
+    rpc.add_transaction(missed, 11, vec![action_log(1)]);
+    rpc.add_transaction(live, 12, vec![action_log(2)]);
+
+    // The catch-up requested on reconnect is held back, so the live message is
+    // handled before it runs.
+    rpc.set_slot_latency(Duration::from_millis(300));
+    let mut second = accept_subscription(&listener).await;
+    second
+        .send(logs_notification(12, live, vec![action_log(2)]))
+        .await
+        .unwrap();
+
+    let mut published = HashSet::new();
+    while published.len() < 2 {
+        let envelope = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("both transactions should be published")
+            .unwrap();
+        published.insert(envelope.signature);
+    }
+    assert!(published.contains(&missed.to_string()));
+    assert!(published.contains(&live.to_string()));
+    handle.stop().await;
+}
@@ -0,0 +1,519 @@
+mod common;
+
+use anchor_lang::AccountSerialize;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use common::action_at;
+use solana_account_decoder_client_types::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::sync::Arc;
+use std::time::Duration;
+use w3b2_bridge_program::{
+    errors::BridgeError,
+    events::UserCommandDispatched,
+    state::{AdminProfile, PriceEntry, PriceSnapshot, UserProfile, PRICE_HISTORY_LEN},
+};
+use w3b2_connector::{
+    blockhash::BlockhashCache,
+    client::{to_versioned_transaction, SubmitOutcome, SubmitPolicy, TransactionBuilder},
+    events::BridgeEvent,
+    instructions::{admin_profile_pda, admin_profile_pda_v2},
+    rpc::{MockSolanaRpc, SolanaRpc},
+    storage::{MemoryStorage, Storage},
+    workers::FinalityTracker,
+};
+
+fn fast_policy(max_retries: usize) -> SubmitPolicy {
+    SubmitPolicy {
+        max_retries,
+        rebroadcast_interval: Duration::from_millis(1),
+        ..Default::default()
+    }
+}
+
+async fn prepared(builder: &TransactionBuilder) -> Transaction {
+    builder
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap()
+}
+
+fn status(slot: u64, confirmation_status: TransactionConfirmationStatus) -> TransactionStatus {
+    TransactionStatus {
+        slot,
+        confirmations: match confirmation_status {
+            TransactionConfirmationStatus::Finalized => None,
+            _ => Some(1),
+        },
+        status: Ok(()),
+        err: None,
+        confirmation_status: Some(confirmation_status),
+    }
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_rebroadcasts_until_retries_run_out() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = prepared(&builder).await;
+
+    // The mock never reports a status, so every retry is spent.
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(2)).await;
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Unconfirmed { attempts: 3, .. })
+    ));
+    assert_eq!(rpc.calls("send_transaction_with_config"), 3);
+    assert_eq!(rpc.calls("get_signature_statuses"), 3);
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_fails_fast_on_initial_transport_error() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = prepared(&builder).await;
+    rpc.fail_next_send(ClientErrorKind::Custom("connection reset".to_string()).into());
+
+    let result = builder.submit_with_policy(&tx, &fast_policy(5)).await;
+
+    assert!(result.is_err());
+    assert_eq!(rpc.calls("send_transaction_with_config"), 1);
+    assert!(rpc.sent_signatures().is_empty());
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_waits_for_requested_commitment() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = prepared(&builder).await;
+    rpc.set_signature_status(
+        tx.signatures[0],
+        Some(status(42, TransactionConfirmationStatus::Processed)),
+    );
+
+    // Processed does not satisfy `confirmed`, so the transaction is re-sent.
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(1)).await;
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Unconfirmed { attempts: 2, .. })
+    ));
+
+    rpc.set_signature_status(
+        tx.signatures[0],
+        Some(status(42, TransactionConfirmationStatus::Confirmed)),
+    );
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(1)).await;
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Confirmed {
+            slot: 42,
+            attempts: 1,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_reports_expired_blockhash() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let cache = Arc::new(BlockhashCache::new(rpc.clone(), Duration::from_secs(60)));
+    let builder = TransactionBuilder::new(rpc.clone()).with_blockhash_cache(cache.clone());
+    let tx = prepared(&builder).await;
+    rpc.set_blockhash_valid(false);
+
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(5)).await;
+
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Expired { attempts: 1, .. })
+    ));
+    // The stale blockhash is dropped from the cache.
+    let fresh = Hash::new_unique();
+    rpc.set_latest_blockhash(fresh);
+    assert_eq!(cache.get().await.unwrap(), fresh);
+    assert_eq!(rpc.calls("get_latest_blockhash"), 2);
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_maps_program_errors() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = prepared(&builder).await;
+
+    // Rejected by the preflight simulation.
+    let rejected = TransactionError::InstructionError(1, InstructionError::Custom(6005));
+    rpc.fail_next_send(ClientError::from(rejected.clone()));
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(5)).await;
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Failed {
+            ref error,
+            bridge_error: Some(BridgeError::CommandNotFound),
+            ..
+        }) if *error == rejected
+    ));
+
+    // Landed, but failed on chain.
+    let mut failed = status(7, TransactionConfirmationStatus::Confirmed);
+    failed.err = Some(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(6005),
+    ));
+    rpc.set_signature_status(tx.signatures[0], Some(failed));
+    let outcome = builder.submit_with_policy(&tx, &fast_policy(5)).await;
+    assert!(matches!(
+        outcome,
+        Ok(SubmitOutcome::Failed {
+            bridge_error: Some(BridgeError::CommandNotFound),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_simulate_decodes_program_error() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = prepared(&builder).await;
+    rpc.set_simulation_result(RpcSimulateTransactionResult {
+        err: Some(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(6005),
+        )),
+        logs: Some(vec!["Program log: boom".to_string()]),
+        accounts: None,
+        units_consumed: Some(1_234),
+        loaded_accounts_data_size: None,
+        return_data: None,
+        inner_instructions: None,
+        replacement_blockhash: None,
+    });
+
+    let result = builder.simulate(&tx).await.unwrap();
+
+    assert!(!result.is_ok());
+    assert!(matches!(
+        result.bridge_error,
+        Some(BridgeError::CommandNotFound)
+    ));
+    assert_eq!(result.logs, vec!["Program log: boom".to_string()]);
+    assert_eq!(result.units_consumed, Some(1_234));
+}
+
+#[tokio::test]
+async fn test_v0_transaction_can_be_simulated_and_broadcast() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = to_versioned_transaction(&prepared(&builder).await, &[]).unwrap();
+
+    let simulation = builder.simulate_versioned(&tx).await.unwrap();
+    let signature = builder.broadcast_versioned_transaction(&tx).await.unwrap();
+
+    assert!(simulation.is_ok());
+    assert_eq!(signature, tx.signatures[0]);
+    assert_eq!(rpc.calls("simulate_versioned_transaction_with_config"), 1);
+    assert_eq!(rpc.calls("send_versioned_transaction_with_config"), 1);
+    assert_eq!(rpc.sent_signatures(), vec![signature]);
+}
+
+#[tokio::test]
+async fn test_finality_tracker_with_mock_rpc() {
+    let rpc = MockSolanaRpc::new();
+    let storage = MemoryStorage::new();
+    let finalized = action_at(10);
+    let dropped = action_at(20);
+    let recent = action_at(900);
+    for envelope in [&finalized, &dropped, &recent] {
+        storage.append_event(envelope).await.unwrap();
+    }
+
+    let mut tracker = FinalityTracker::new(150);
+    tracker.track(finalized.clone());
+    tracker.track(dropped.clone());
+    tracker.track(recent.clone());
+    rpc.set_finalized_slot(1000);
+    rpc.set_signature_status(
+        finalized.signature.parse().unwrap(),
+        Some(status(10, TransactionConfirmationStatus::Finalized)),
+    );
+
+    let revocations = tracker.check(&rpc, &storage).await.unwrap();
+
+    assert_eq!(revocations.len(), 1);
+    assert_eq!(revocations[0].envelope.signature, dropped.signature);
+    assert_eq!(revocations[0].finalized_slot, 1000);
+    assert_eq!(tracker.pending(), 1);
+    assert_eq!(rpc.calls("get_signature_statuses_with_history"), 1);
+}
+
+#[tokio::test]
+async fn test_mock_signature_history_pages_like_the_node() {
+    let rpc = MockSolanaRpc::new();
+    let signatures: Vec<Signature> = (0..5).map(|_| Signature::new_unique()).collect();
+    for (slot, signature) in signatures.iter().enumerate() {
+        rpc.add_transaction(*signature, slot as u64, vec![]);
+    }
+    let page = |before, until, limit| GetConfirmedSignaturesForAddress2Config {
+        before,
+        until,
+        limit,
+        commitment: Some(CommitmentConfig::confirmed()),
+    };
+    let program = w3b2_bridge_program::ID;
+
+    // Newest first, bounded by `limit`.
+    let first = rpc
+        .get_signatures_for_address_with_config(&program, page(None, None, Some(2)))
+        .await
+        .unwrap();
+    let slots: Vec<u64> = first.iter().map(|s| s.slot).collect();
+    assert_eq!(slots, vec![4, 3]);
+
+    // Continues before the last one seen, and stops at `until`.
+    let rest = rpc
+        .get_signatures_for_address_with_config(
+            &program,
+            page(Some(signatures[3]), Some(signatures[0]), None),
+        )
+        .await
+        .unwrap();
+    let slots: Vec<u64> = rest.iter().map(|s| s.slot).collect();
+    assert_eq!(slots, vec![2, 1]);
+
+    let tx = rpc
+        .get_transaction_with_config(&signatures[2], Default::default())
+        .await
+        .unwrap();
+    assert_eq!(tx.slot, 2);
+    assert!(rpc
+        .get_transaction_with_config(&Signature::new_unique(), Default::default())
+        .await
+        .is_err());
+}
+
+fn simulation(
+    err: Option<TransactionError>,
+    logs: Vec<String>,
+    accounts: Option<Vec<Option<UiAccount>>>,
+) -> RpcSimulateTransactionResult {
+    RpcSimulateTransactionResult {
+        err,
+        logs: Some(logs),
+        accounts,
+        units_consumed: None,
+        loaded_accounts_data_size: None,
+        return_data: None,
+        inner_instructions: None,
+        replacement_blockhash: None,
+    }
+}
+
+#[tokio::test]
+async fn test_preview_dispatch_reports_price_and_remaining_deposit() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let user = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+
+    let event = BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+        sender: user,
+        target_admin_authority: admin,
+        command_id: 3,
+        price_paid: 1_500,
+        high_priority: true,
+        priority_fee: 500,
+        payload: vec![1],
+        ts: 0,
+    });
+    let mut data = Vec::new();
+    UserProfile {
+        authority: user,
+        communication_pubkey: Pubkey::new_unique(),
+        admin_authority_on_creation: admin_profile_pda(&admin),
+        deposit_balance: 8_500,
+        rent_reserve: 0,
+        last_active_epoch: 0,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    let profile = UiAccount {
+        lamports: 10_000,
+        data: UiAccountData::Binary(BASE64_STANDARD.encode(data), UiAccountEncoding::Base64),
+        owner: w3b2_bridge_program::ID.to_string(),
+        executable: false,
+        rent_epoch: 0,
+        space: None,
+    };
+    rpc.set_simulation_result(simulation(
+        None,
+        vec![format!(
+            "Program data: {}",
+            BASE64_STANDARD.encode(event.to_bytes())
+        )],
+        Some(vec![Some(profile)]),
+    ));
+
+    let preview = builder
+        .preview_dispatch(user, admin_profile_pda(&admin), 3, true, vec![1])
+        .await
+        .unwrap();
+
+    assert!(preview.is_ok());
+    assert_eq!(preview.price, Some(1_500));
+    assert_eq!(preview.priority_fee, Some(500));
+    assert_eq!(preview.remaining_deposit, Some(8_500));
+}
+
+#[tokio::test]
+async fn test_preview_dispatch_reports_program_error() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    rpc.set_simulation_result(simulation(
+        Some(TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(6000 + BridgeError::InsufficientDepositBalance as u32),
+        )),
+        Vec::new(),
+        None,
+    ));
+
+    let preview = builder
+        .preview_dispatch(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            3,
+            false,
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(!preview.is_ok());
+    assert!(matches!(
+        preview.simulation.bridge_error,
+        Some(BridgeError::InsufficientDepositBalance)
+    ));
+    assert_eq!(preview.price, None);
+    assert_eq!(preview.remaining_deposit, None);
+}
+
+#[tokio::test]
+async fn test_dispatch_within_budget_checks_the_price() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let admin = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&admin);
+    let mut data = Vec::new();
+    AdminProfile {
+        authority: admin,
+        communication_pubkey: Pubkey::new_unique(),
+        prices: vec![PriceEntry::new(3, 1_000)],
+        balance: 0,
+        gc_inactivity_epochs: 0,
+        priority_surcharge: 500,
+        price_history: [PriceSnapshot::default(); PRICE_HISTORY_LEN],
+        price_history_head: 0,
+        profile_index: None,
+        migrated_from: None,
+        migrated_to: None,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    rpc.set_account(
+        admin_pda,
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: w3b2_bridge_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let user = Pubkey::new_unique();
+
+    assert!(builder
+        .prepare_user_dispatch_command_within_budget(user, admin_pda, 3, false, Vec::new(), 1_000)
+        .await
+        .is_ok());
+    let err = builder
+        .prepare_user_dispatch_command_within_budget(user, admin_pda, 3, true, Vec::new(), 1_000)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("over the budget"));
+    assert!(builder
+        .prepare_user_dispatch_command_within_budget(
+            user,
+            Pubkey::new_unique(),
+            3,
+            false,
+            Vec::new(),
+            1_000
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_admin_transactions_follow_a_migrated_profile() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let admin = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&admin);
+    let admin_pda_v2 = admin_profile_pda_v2(&admin, 0);
+
+    assert_eq!(
+        builder.resolve_admin_profile(&admin).await.unwrap(),
+        admin_pda
+    );
+
+    let mut data = Vec::new();
+    AdminProfile {
+        authority: admin,
+        communication_pubkey: Pubkey::new_unique(),
+        prices: Vec::new(),
+        balance: 0,
+        gc_inactivity_epochs: 0,
+        priority_surcharge: 0,
+        price_history: [PriceSnapshot::default(); PRICE_HISTORY_LEN],
+        price_history_head: 0,
+        profile_index: None,
+        migrated_from: None,
+        migrated_to: Some(admin_pda_v2),
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    rpc.set_account(
+        admin_pda,
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: w3b2_bridge_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    assert_eq!(
+        builder.resolve_admin_profile(&admin).await.unwrap(),
+        admin_pda_v2
+    );
+    let tx = builder
+        .prepare_admin_withdraw(admin, 1_000, Pubkey::new_unique())
+        .await
+        .unwrap();
+    assert!(tx.message.account_keys.contains(&admin_pda_v2));
+    assert!(!tx.message.account_keys.contains(&admin_pda));
+}
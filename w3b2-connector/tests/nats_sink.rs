@@ -0,0 +1,105 @@
+#![cfg(feature = "nats")]
+
+mod common;
+
+use common::envelope;
+use solana_sdk::pubkey::Pubkey;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use w3b2_bridge_program::events::UserCommandDispatched;
+use w3b2_connector::{
+    events::{BridgeEvent, EventEnvelope},
+    sink::{EventSink, NatsSink, SinkError},
+};
+
+/// A message published to the fake server: its subject, headers and payload.
+struct Published {
+    subject: String,
+    headers: String,
+    payload: Vec<u8>,
+}
+
+/// Serves one NATS client until it publishes a message, acknowledges it as a
+/// JetStream stream would, and returns the message.
+async fn serve_one_publish(listener: TcpListener) -> Published {
+    let (stream, _) = listener.accept().await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let info = r#"{"server_id":"test","server_name":"test","version":"2.10.0","go":"go1.22","host":"127.0.0.1","port":4222,"headers":true,"max_payload":1048576,"proto":1}"#;
+    writer
+        .write_all(format!("INFO {}\r\n", info).as_bytes())
+        .await
+        .unwrap();
+
+    let mut inbox_sid = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["PING"] => writer.write_all(b"PONG\r\n").await.unwrap(),
+            ["SUB", _, sid] => inbox_sid = sid.to_string(),
+            ["HPUB", subject, reply, header_len, total_len] => {
+                let header_len: usize = header_len.parse().unwrap();
+                let mut message = vec![0; total_len.parse::<usize>().unwrap() + 2];
+                reader.read_exact(&mut message).await.unwrap();
+                let ack = r#"{"stream":"events","seq":1}"#;
+                writer
+                    .write_all(
+                        format!("MSG {} {} {}\r\n{}\r\n", reply, inbox_sid, ack.len(), ack)
+                            .as_bytes(),
+                    )
+                    .await
+                    .unwrap();
+                message.truncate(message.len() - 2);
+                return Published {
+                    subject: subject.to_string(),
+                    headers: String::from_utf8(message[..header_len].to_vec()).unwrap(),
+                    payload: message[header_len..].to_vec(),
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_nats_sink_publishes_with_message_id() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("nats://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_one_publish(listener));
+    let admin = Pubkey::new_unique();
+    let envelope = envelope(
+        "sig",
+        2,
+        BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+            sender: Pubkey::new_unique(),
+            target_admin_authority: admin,
+            command_id: 1,
+            price_paid: 0,
+            high_priority: false,
+            priority_fee: 0,
+            payload: Vec::new(),
+            ts: 0,
+        }),
+    );
+
+    let sink = NatsSink::connect(&url, "w3b2.events").await.unwrap();
+    sink.publish(&envelope).await.unwrap();
+
+    let published = server.await.unwrap();
+    assert_eq!(
+        published.subject,
+        format!("w3b2.events.{}.UserCommandDispatched", admin)
+    );
+    assert!(published.headers.contains("Nats-Msg-Id: sig:2"));
+    let decoded: EventEnvelope = serde_json::from_slice(&published.payload).unwrap();
+    assert_eq!(decoded.signature, "sig");
+}
+
+#[tokio::test]
+async fn test_nats_sink_reports_unreachable_servers() {
+    let port = portpicker::pick_unused_port().unwrap();
+    let sink = NatsSink::connect(&format!("nats://127.0.0.1:{}", port), "w3b2.events").await;
+    assert!(matches!(sink, Err(SinkError::Publish(_))));
+}
@@ -0,0 +1,198 @@
+use anchor_lang::AccountSerialize;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use serde_json::json;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{broadcast, mpsc};
+use w3b2_bridge_program::{
+    events::{AdminPricesUpdated, AdminPrioritySurchargeUpdated},
+    state::{AdminProfile, PriceEntry, PriceSnapshot, PRICE_HISTORY_LEN},
+};
+use w3b2_connector::{
+    dispatcher::{Dispatcher, DispatcherCommand, LagStatus},
+    events::{BridgeEvent, EventEnvelope},
+    instructions::admin_profile_pda,
+    prices::PriceCache,
+    reader::AccountReader,
+};
+
+/// Creates a cache whose RPC returns the given admin profile exactly once.
+fn cache_with_admin_profile(authority: Pubkey, prices: Vec<PriceEntry>) -> PriceCache {
+    let profile = AdminProfile {
+        authority,
+        communication_pubkey: Pubkey::new_unique(),
+        prices,
+        balance: 0,
+        gc_inactivity_epochs: 0,
+        priority_surcharge: 0,
+        price_history: [PriceSnapshot::default(); PRICE_HISTORY_LEN],
+        price_history_head: 0,
+        profile_index: None,
+        migrated_from: None,
+        migrated_to: None,
+    };
+    let mut data = Vec::new();
+    profile.try_serialize(&mut data).unwrap();
+
+    let response = json!({
+        "context": { "slot": 1 },
+        "value": {
+            "lamports": 1_000_000,
+            "data": [BASE64_STANDARD.encode(&data), "base64"],
+            "owner": w3b2_bridge_program::ID.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": data.len(),
+        }
+    });
+    let mocks = HashMap::from([(RpcRequest::GetAccountInfo, response)]);
+    let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+    PriceCache::new(AccountReader::new(Arc::new(rpc_client)))
+}
+
+#[tokio::test]
+async fn test_get_command_price_is_served_from_cache() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = cache_with_admin_profile(authority, vec![PriceEntry::new(7, 500)]);
+
+    assert_eq!(
+        cache.get_command_price(&admin_pda, 7).await.unwrap(),
+        Some(500)
+    );
+    // The mocked account is gone now, so these can only be answered from the cache.
+    assert_eq!(
+        cache.get_command_price(&admin_pda, 7).await.unwrap(),
+        Some(500)
+    );
+    assert_eq!(cache.get_command_price(&admin_pda, 8).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_prices_updated_event_refreshes_cache() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = cache_with_admin_profile(authority, vec![PriceEntry::new(7, 500)]);
+    cache.get_command_price(&admin_pda, 7).await.unwrap();
+
+    cache.observe(&BridgeEvent::AdminPricesUpdated(AdminPricesUpdated {
+        authority,
+        new_prices: vec![PriceEntry::new(7, 900)],
+        ts: 0,
+    }));
+
+    assert_eq!(
+        cache.get_command_price(&admin_pda, 7).await.unwrap(),
+        Some(900)
+    );
+}
+
+#[tokio::test]
+async fn test_invalidate_forces_reload() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = cache_with_admin_profile(authority, vec![PriceEntry::new(7, 500)]);
+    cache.get_command_price(&admin_pda, 7).await.unwrap();
+
+    cache.invalidate(&admin_pda);
+
+    // The reload finds no account anymore.
+    assert_eq!(cache.get_command_price(&admin_pda, 7).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_dispatch_price_includes_priority_surcharge() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = cache_with_admin_profile(authority, vec![PriceEntry::new(7, 500)]);
+
+    assert_eq!(
+        cache.get_dispatch_price(&admin_pda, 7, true).await.unwrap(),
+        Some(500)
+    );
+    cache.observe(&BridgeEvent::AdminPrioritySurchargeUpdated(
+        AdminPrioritySurchargeUpdated {
+            authority,
+            surcharge: 200,
+            ts: 0,
+        },
+    ));
+
+    assert_eq!(
+        cache.get_dispatch_price(&admin_pda, 7, true).await.unwrap(),
+        Some(700)
+    );
+    assert_eq!(
+        cache
+            .get_dispatch_price(&admin_pda, 7, false)
+            .await
+            .unwrap(),
+        Some(500)
+    );
+    // Unlisted commands are free, as on-chain.
+    assert_eq!(
+        cache.get_dispatch_price(&admin_pda, 8, true).await.unwrap(),
+        Some(200)
+    );
+}
+
+#[tokio::test]
+async fn test_price_of_only_answers_for_watched_admins() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = cache_with_admin_profile(authority, vec![PriceEntry::new(7, 500)]);
+
+    assert_eq!(cache.price_of(&admin_pda, 7), None);
+    assert!(cache.watch(&admin_pda).await.unwrap());
+
+    assert_eq!(cache.price_of(&admin_pda, 7), Some(500));
+    assert_eq!(cache.price_of(&admin_pda, 8), None);
+}
+
+#[tokio::test]
+async fn test_dispatcher_updates_cache_before_routing() {
+    let authority = Pubkey::new_unique();
+    let admin_pda = admin_profile_pda(&authority);
+    let cache = Arc::new(cache_with_admin_profile(
+        authority,
+        vec![PriceEntry::new(7, 500)],
+    ));
+    cache.watch(&admin_pda).await.unwrap();
+
+    let (event_tx, event_rx) = broadcast::channel(16);
+    let (command_tx, command_rx) = mpsc::channel(16);
+    let mut dispatcher = Dispatcher::new(event_rx, command_rx).with_price_cache(cache.clone());
+    tokio::spawn(async move { dispatcher.run().await });
+    let (tx, mut rx) = mpsc::channel(16);
+    command_tx
+        .send(DispatcherCommand::Register {
+            pubkey: authority,
+            id: 0,
+            tx,
+            options: Default::default(),
+            lag_status: LagStatus::default(),
+        })
+        .await
+        .unwrap();
+    // Let the dispatcher process the registration first.
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    event_tx
+        .send(EventEnvelope {
+            slot: 1,
+            signature: "sig".to_string(),
+            index: 0,
+            tx_index: None,
+            block_time: None,
+            event: BridgeEvent::AdminPricesUpdated(AdminPricesUpdated {
+                authority,
+                new_prices: vec![PriceEntry::new(7, 900)],
+                ts: 0,
+            }),
+        })
+        .unwrap();
+
+    rx.recv().await.unwrap();
+    assert_eq!(cache.price_of(&admin_pda, 7), Some(900));
+}
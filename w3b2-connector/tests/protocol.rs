@@ -0,0 +1,166 @@
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::protocols::{Destination, PayloadCodec, PayloadHeader};
+use w3b2_connector::protocol::{
+    decode_command_config, decrypt, decrypt_payload, decrypt_session_key, encode_command_config,
+    encrypt_for, encrypt_payload, encrypt_session_key, CommandConfigBuilder, ProtocolError,
+    SessionKey, X25519Keypair, ENCRYPTED_SESSION_KEY_LEN, SEALED_OVERHEAD,
+};
+
+#[test]
+fn test_key_exchange_is_symmetric() {
+    let alice = X25519Keypair::generate();
+    let bob = X25519Keypair::generate();
+
+    assert_eq!(
+        alice.diffie_hellman(&bob.public_key()).unwrap(),
+        bob.diffie_hellman(&alice.public_key()).unwrap()
+    );
+
+    let restored = X25519Keypair::from_secret_bytes(alice.secret_bytes());
+    assert_eq!(restored.public_key(), alice.public_key());
+}
+
+#[test]
+fn test_low_order_public_key_is_rejected() {
+    let keypair = X25519Keypair::generate();
+    assert_eq!(
+        keypair.diffie_hellman(&Pubkey::default()),
+        Err(ProtocolError::WeakPublicKey)
+    );
+}
+
+#[test]
+fn test_session_key_round_trip() {
+    let service = X25519Keypair::generate();
+    let session_key = SessionKey::generate();
+
+    let encrypted = encrypt_session_key(&service.public_key(), &session_key).unwrap();
+    assert_eq!(encrypted.len(), ENCRYPTED_SESSION_KEY_LEN);
+    assert_eq!(
+        decrypt_session_key(&service, &encrypted).unwrap(),
+        session_key
+    );
+
+    // Each encryption uses a fresh ephemeral key.
+    let again = encrypt_session_key(&service.public_key(), &session_key).unwrap();
+    assert_ne!(encrypted, again);
+}
+
+#[test]
+fn test_session_key_cannot_be_decrypted_by_others() {
+    let service = X25519Keypair::generate();
+    let eavesdropper = X25519Keypair::generate();
+    let mut encrypted =
+        encrypt_session_key(&service.public_key(), &SessionKey::generate()).unwrap();
+
+    assert_eq!(
+        decrypt_session_key(&eavesdropper, &encrypted),
+        Err(ProtocolError::DecryptionFailed)
+    );
+
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+    assert_eq!(
+        decrypt_session_key(&service, &encrypted),
+        Err(ProtocolError::DecryptionFailed)
+    );
+    assert_eq!(
+        decrypt_session_key(&service, &encrypted[..10]),
+        Err(ProtocolError::TooShort(10))
+    );
+}
+
+#[test]
+fn test_command_config_payload_round_trip() {
+    let service = X25519Keypair::generate();
+    let (config, session_key) =
+        CommandConfigBuilder::new(42, Destination::Url("wss://example.com".to_string()))
+            .with_meta(b"hello".to_vec())
+            .build(&service.public_key())
+            .unwrap();
+
+    let payload = encode_command_config(&config).unwrap();
+    let (header, _) = PayloadHeader::parse(&payload).unwrap();
+    assert_eq!(header, PayloadHeader::new(PayloadCodec::Borsh, false));
+
+    let decoded = decode_command_config(&payload).unwrap();
+    assert_eq!(decoded, config);
+    assert_eq!(decoded.session_id, 42);
+    assert_eq!(decoded.meta, b"hello");
+    assert_eq!(
+        decrypt_session_key(&service, &decoded.encrypted_session_key).unwrap(),
+        session_key
+    );
+}
+
+#[test]
+fn test_decode_rejects_other_payloads() {
+    let json = PayloadHeader::new(PayloadCodec::Json, false).wrap(b"{}");
+    assert!(matches!(
+        decode_command_config(&json),
+        Err(ProtocolError::UnexpectedPayload(_))
+    ));
+    assert!(matches!(
+        decode_command_config(b"not a payload"),
+        Err(ProtocolError::InvalidHeader(_))
+    ));
+}
+
+#[test]
+fn test_oversized_config_is_rejected() {
+    let service = X25519Keypair::generate();
+    let result = CommandConfigBuilder::new(1, Destination::IpV4([127, 0, 0, 1], 8080))
+        .with_meta(vec![0; 2000])
+        .build(&service.public_key());
+    assert!(matches!(result, Err(ProtocolError::PayloadTooLarge { .. })));
+}
+
+#[test]
+fn test_encrypt_for_round_trip() {
+    let user = X25519Keypair::generate();
+    let other = X25519Keypair::generate();
+    let message = b"top secret command body";
+
+    let sealed = encrypt_for(&user.public_key(), message).unwrap();
+    assert_eq!(sealed.len(), message.len() + SEALED_OVERHEAD);
+    assert_eq!(decrypt(&user, &sealed).unwrap(), message);
+    assert_eq!(
+        decrypt(&other, &sealed),
+        Err(ProtocolError::DecryptionFailed)
+    );
+
+    // Empty messages are still authenticated.
+    let sealed = encrypt_for(&user.public_key(), &[]).unwrap();
+    assert_eq!(decrypt(&user, &sealed).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_encrypted_payload_round_trip() {
+    let service = X25519Keypair::generate();
+
+    let payload =
+        encrypt_payload(&service.public_key(), PayloadCodec::Json, b"{\"op\":1}").unwrap();
+    // The header stays readable for the on-chain validation.
+    let (header, body) = PayloadHeader::parse(&payload).unwrap();
+    assert_eq!(header, PayloadHeader::new(PayloadCodec::Json, true));
+    assert_ne!(body, b"{\"op\":1}");
+
+    let (header, body) = decrypt_payload(&service, &payload).unwrap();
+    assert_eq!(header.codec, PayloadCodec::Json);
+    assert_eq!(body, b"{\"op\":1}");
+
+    // Plaintext payloads pass through untouched.
+    let plain = PayloadHeader::new(PayloadCodec::Raw, false).wrap(b"abc");
+    let (header, body) = decrypt_payload(&service, &plain).unwrap();
+    assert!(!header.encrypted);
+    assert_eq!(body, b"abc");
+}
+
+#[test]
+fn test_encrypted_payload_respects_size_limit() {
+    let service = X25519Keypair::generate();
+    assert!(matches!(
+        encrypt_payload(&service.public_key(), PayloadCodec::Raw, &[0; 1000]),
+        Err(ProtocolError::PayloadTooLarge { .. })
+    ));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::action_at;
+use std::time::Duration;
+use tokio::time::Instant;
+use w3b2_connector::{events::EventEnvelope, workers::ReorderBuffer};
+
+fn slots(events: Vec<EventEnvelope>) -> Vec<u64> {
+    events.into_iter().map(|envelope| envelope.slot).collect()
+}
+
+#[test]
+fn test_reorder_buffer_releases_lowest_slot_when_full() {
+    let mut buffer = ReorderBuffer::new(2, Duration::from_secs(60));
+    let now = Instant::now();
+
+    assert!(buffer.push(action_at(12), now).is_empty());
+    assert!(buffer.push(action_at(10), now).is_empty());
+    assert_eq!(slots(buffer.push(action_at(11), now)), vec![10]);
+    assert_eq!(slots(buffer.drain()), vec![11, 12]);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_reorder_buffer_releases_expired_events_in_order() {
+    let delay = Duration::from_millis(100);
+    let mut buffer = ReorderBuffer::new(16, delay);
+    let start = Instant::now();
+
+    buffer.push(action_at(7), start);
+    buffer.push(action_at(5), start + delay / 2);
+    buffer.push(action_at(9), start + delay / 2);
+
+    // Slot 7 has waited long enough; slot 5 goes out with it to stay in order.
+    assert_eq!(slots(buffer.pop_expired(start + delay)), vec![5, 7]);
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(slots(buffer.pop_expired(start + delay * 2)), vec![9]);
+}
+
+/// An event of transaction `signature` in slot 5.
+fn event_in(signature: &str, tx_index: Option<u32>, index: u32) -> EventEnvelope {
+    EventEnvelope {
+        signature: signature.to_string(),
+        index,
+        tx_index,
+        ..action_at(5)
+    }
+}
+
+fn positions(events: Vec<EventEnvelope>) -> Vec<(String, u32)> {
+    events
+        .into_iter()
+        .map(|envelope| (envelope.signature, envelope.index))
+        .collect()
+}
+
+#[test]
+fn test_reorder_buffer_orders_transactions_within_a_slot() {
+    let mut buffer = ReorderBuffer::new(16, Duration::from_secs(60));
+    let now = Instant::now();
+
+    buffer.push(event_in("sig-b", Some(2), 0), now);
+    buffer.push(event_in("sig-a", Some(0), 0), now);
+    buffer.push(event_in("sig-a", Some(0), 1), now);
+
+    assert_eq!(
+        positions(buffer.drain()),
+        vec![
+            ("sig-a".to_string(), 0),
+            ("sig-a".to_string(), 1),
+            ("sig-b".to_string(), 0),
+        ]
+    );
+}
+
+#[test]
+fn test_reorder_buffer_keeps_unplaced_transactions_together() {
+    let mut buffer = ReorderBuffer::new(16, Duration::from_secs(60));
+    let now = Instant::now();
+
+    // Live events carry no block position; they follow the placed ones in arrival
+    // order, without interleaving two transactions.
+    buffer.push(event_in("sig-live-a", None, 0), now);
+    buffer.push(event_in("sig-live-b", None, 0), now);
+    buffer.push(event_in("sig-live-a", None, 1), now);
+    buffer.push(event_in("sig-placed", Some(7), 0), now);
+
+    assert_eq!(
+        positions(buffer.drain()),
+        vec![
+            ("sig-placed".to_string(), 0),
+            ("sig-live-a".to_string(), 0),
+            ("sig-live-a".to_string(), 1),
+            ("sig-live-b".to_string(), 0),
+        ]
+    );
+}
+
+#[test]
+fn test_empty_reorder_buffer_is_disabled() {
+    let mut buffer = ReorderBuffer::new(0, Duration::from_secs(60));
+
+    assert_eq!(slots(buffer.push(action_at(3), Instant::now())), vec![3]);
+    assert!(buffer.is_empty());
+}
@@ -0,0 +1,138 @@
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use w3b2_connector::{
+    config::RateLimit,
+    rpc_pool::{RateLimiter, RpcPool},
+};
+
+/// Answers every JSON-RPC request with `result`.
+async fn serve(result: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(respond(stream, result));
+        }
+    });
+    url
+}
+
+async fn respond(mut stream: TcpStream, result: &str) {
+    // Read the whole request before answering.
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        if n == 0 {
+            return;
+        }
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request).to_lowercase();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length: usize = text
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .map(|v| v.trim().parse().unwrap())
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                break;
+            }
+        }
+    }
+
+    let body = format!(r#"{{"jsonrpc":"2.0","result":{result},"id":1}}"#);
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+}
+
+/// An address nothing listens on.
+async fn dead_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn test_rpc_pool_fails_over_to_healthy_endpoint() {
+    let dead = dead_url().await;
+    let live = serve("42").await;
+    let pool = RpcPool::new(
+        vec![dead.clone(), live.clone()],
+        Duration::from_secs(60),
+        None,
+    );
+    let client = pool.rpc_client(CommitmentConfig::confirmed());
+
+    for _ in 0..3 {
+        assert_eq!(client.get_slot().await.unwrap(), 42);
+    }
+    assert_eq!(pool.healthy_urls(), vec![live]);
+}
+
+#[tokio::test]
+async fn test_rpc_pool_health_check() {
+    let dead = dead_url().await;
+    let live = serve("\"ok\"").await;
+    let pool = RpcPool::new(vec![live.clone(), dead], Duration::from_secs(60), None);
+
+    assert_eq!(pool.check_health().await, 1);
+    assert_eq!(pool.healthy_urls(), vec![live]);
+}
+
+#[tokio::test]
+async fn test_rpc_pool_fails_when_every_endpoint_is_down() {
+    let pool = RpcPool::new(
+        vec![dead_url().await, dead_url().await],
+        Duration::from_secs(60),
+        None,
+    );
+    let client = pool.rpc_client(CommitmentConfig::confirmed());
+
+    assert!(client.get_slot().await.is_err());
+    assert!(pool.healthy_urls().is_empty());
+}
+
+#[tokio::test]
+async fn test_rate_limiter_spaces_requests_after_burst() {
+    let limiter = RateLimiter::new(&RateLimit {
+        requests_per_second: 20,
+        burst: Some(2),
+    });
+
+    let start = Instant::now();
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert!(start.elapsed() < Duration::from_millis(40));
+    assert_eq!(limiter.throttled_time(), Duration::ZERO);
+
+    // The bucket is empty: the third request waits for a token (50 ms at 20/s).
+    limiter.acquire().await;
+    assert!(start.elapsed() >= Duration::from_millis(45));
+    assert!(limiter.throttled_time() >= Duration::from_millis(45));
+}
+
+#[tokio::test]
+async fn test_rpc_pool_applies_rate_limit_per_endpoint() {
+    let live = serve("42").await;
+    let limit = RateLimit {
+        requests_per_second: 20,
+        burst: Some(1),
+    };
+    let pool = RpcPool::new(vec![live], Duration::from_secs(60), Some(&limit));
+    let client = pool.rpc_client(CommitmentConfig::confirmed());
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert_eq!(client.get_slot().await.unwrap(), 42);
+    }
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    // Time spent on slow responses refills the bucket, so only part of the wait
+    // is necessarily spent throttled.
+    assert!(client.get_transport_stats().rate_limited_time > Duration::ZERO);
+}
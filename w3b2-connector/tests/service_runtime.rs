@@ -0,0 +1,99 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::sync::{Arc, Mutex};
+use w3b2_bridge_program::{
+    events::UserCommandDispatched,
+    protocols::{PayloadCodec, PayloadHeader},
+};
+use w3b2_connector::{
+    client::TransactionBuilder,
+    runtime::{
+        ActionLog, CommandContext, CommandHandler, CommandReply, CommandResponse, ServiceRuntime,
+    },
+};
+
+/// Records every body it sees and answers with a reply plus an audit log entry.
+struct EchoHandler {
+    seen: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+#[async_trait]
+impl CommandHandler for EchoHandler {
+    async fn handle(&self, ctx: CommandContext) -> Result<CommandResponse> {
+        self.seen.lock().unwrap().push(ctx.body.clone());
+        Ok(CommandResponse {
+            reply: Some(CommandReply {
+                command_id: ctx.command.command_id as u64,
+                rebate: 0,
+                payload: PayloadHeader::new(PayloadCodec::Raw, false).wrap(&ctx.body),
+            }),
+            log: Some(ActionLog {
+                session_id: 1,
+                action_code: 200,
+            }),
+        })
+    }
+}
+
+fn command(admin: &Keypair, command_id: u16, payload: Vec<u8>) -> UserCommandDispatched {
+    UserCommandDispatched {
+        sender: Pubkey::new_unique(),
+        target_admin_authority: admin.pubkey(),
+        command_id,
+        price_paid: 0,
+        high_priority: false,
+        priority_fee: 0,
+        payload,
+        ts: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_runtime_routes_command_to_handler_and_responds() {
+    let admin = Arc::new(Keypair::new());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let builder = TransactionBuilder::new(Arc::new(RpcClient::new_mock("succeeds".to_string())));
+    let runtime =
+        ServiceRuntime::new(builder, admin.clone()).register(7, EchoHandler { seen: seen.clone() });
+
+    let payload = PayloadHeader::new(PayloadCodec::Json, false).wrap(b"{}");
+    let signature = runtime
+        .handle_command(command(&admin, 7, payload))
+        .await
+        .unwrap();
+
+    assert!(signature.is_some());
+    assert_eq!(*seen.lock().unwrap(), vec![b"{}".to_vec()]);
+}
+
+#[tokio::test]
+async fn test_runtime_ignores_unregistered_commands() {
+    let admin = Arc::new(Keypair::new());
+    let builder = TransactionBuilder::new(Arc::new(RpcClient::new_mock("succeeds".to_string())));
+    let runtime = ServiceRuntime::new(builder, admin.clone());
+
+    let signature = runtime
+        .handle_command(command(&admin, 9, Vec::new()))
+        .await
+        .unwrap();
+
+    assert!(signature.is_none());
+}
+
+#[tokio::test]
+async fn test_runtime_rejects_malformed_payload() {
+    let admin = Arc::new(Keypair::new());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let builder = TransactionBuilder::new(Arc::new(RpcClient::new_mock("succeeds".to_string())));
+    let runtime =
+        ServiceRuntime::new(builder, admin.clone()).register(7, EchoHandler { seen: seen.clone() });
+
+    let result = runtime
+        .handle_command(command(&admin, 7, b"no header".to_vec()))
+        .await;
+
+    assert!(result.is_err());
+    assert!(seen.lock().unwrap().is_empty());
+}
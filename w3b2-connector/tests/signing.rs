@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use std::sync::Arc;
+use w3b2_connector::{
+    client::TransactionBuilder,
+    instructions,
+    rpc::MockSolanaRpc,
+    signing::{
+        add_signature, is_fully_signed, merge_signatures, missing_signers, partial_sign,
+        required_signers, verify_signatures, SigningError, TransactionSigner,
+    },
+};
+
+/// A deposit by `user` whose fees are paid by `sponsor`.
+async fn sponsored_deposit(sponsor: &Keypair, user: &Keypair) -> Transaction {
+    let builder = TransactionBuilder::new(Arc::new(MockSolanaRpc::new()));
+    builder
+        .batch(sponsor.pubkey())
+        .instruction(instructions::user_deposit(
+            user.pubkey(),
+            Pubkey::new_unique(),
+            1_000,
+        ))
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_sponsor_and_user_sign_separately() {
+    let sponsor = Keypair::new();
+    let user = Keypair::new();
+    let mut tx = sponsored_deposit(&sponsor, &user).await;
+    assert_eq!(required_signers(&tx), [sponsor.pubkey(), user.pubkey()]);
+
+    partial_sign(&mut tx, &[&sponsor]).unwrap();
+    assert_eq!(missing_signers(&tx), vec![user.pubkey()]);
+
+    // The user's wallet signs its own copy.
+    let mut wallet_copy = tx.clone();
+    wallet_copy.signatures[0] = Signature::default();
+    partial_sign(&mut wallet_copy, &[&user]).unwrap();
+
+    assert_eq!(merge_signatures(&mut tx, &wallet_copy).unwrap(), 1);
+    assert!(is_fully_signed(&tx));
+    verify_signatures(&tx).unwrap();
+    tx.verify().unwrap();
+}
+
+#[tokio::test]
+async fn test_external_signatures_are_verified() {
+    let sponsor = Keypair::new();
+    let user = Keypair::new();
+    let mut tx = sponsored_deposit(&sponsor, &user).await;
+
+    let signature = user.sign_message(&tx.message_data());
+    assert!(matches!(
+        add_signature(&mut tx, &Keypair::new().pubkey(), signature),
+        Err(SigningError::NotASigner(_))
+    ));
+    assert!(matches!(
+        add_signature(&mut tx, &sponsor.pubkey(), signature),
+        Err(SigningError::InvalidSignature(_))
+    ));
+    add_signature(&mut tx, &user.pubkey(), signature).unwrap();
+    assert_eq!(missing_signers(&tx), vec![sponsor.pubkey()]);
+
+    let mut other = tx.clone();
+    other.message.recent_blockhash = Hash::new_unique();
+    assert!(matches!(
+        merge_signatures(&mut tx, &other),
+        Err(SigningError::MessageMismatch)
+    ));
+}
+
+/// Signs out of process, like a hardware wallet would.
+struct HardwareWallet {
+    key: Keypair,
+}
+
+#[async_trait]
+impl TransactionSigner for HardwareWallet {
+    fn signer_pubkey(&self) -> Pubkey {
+        self.key.pubkey()
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        tokio::task::yield_now().await;
+        Ok(self.key.sign_message(message))
+    }
+}
+
+#[tokio::test]
+async fn test_prepare_sign_with_submit() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let wallet = HardwareWallet {
+        key: Keypair::new(),
+    };
+
+    let mut tx = builder
+        .prepare_user_deposit(wallet.signer_pubkey(), Pubkey::new_unique(), 1_000)
+        .await
+        .unwrap();
+    builder.sign_with(&mut tx, &wallet).await.unwrap();
+    assert!(is_fully_signed(&tx));
+    let signature = builder.submit_transaction(&tx).await.unwrap();
+    assert_eq!(rpc.sent_signatures(), vec![signature]);
+
+    // A keypair works the same way, but only for transactions it has to sign.
+    let stranger = Keypair::new();
+    assert!(matches!(
+        builder.sign_with(&mut tx, &stranger).await,
+        Err(SigningError::NotASigner(_))
+    ));
+}
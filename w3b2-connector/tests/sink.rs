@@ -0,0 +1,248 @@
+mod common;
+
+use async_trait::async_trait;
+use common::envelope;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use w3b2_bridge_program::events::{UserCommandDispatched, UserFundsDeposited};
+use w3b2_connector::{
+    events::{BridgeEvent, EventEnvelope, EventKind, RawEvent},
+    sink::{
+        forward, message_id, subject, EventCategory, EventSink, RetryingSink, SinkError, SinkRecord,
+    },
+};
+
+/// Lays out every event as a record, as a broker-backed sink would.
+#[derive(Default)]
+struct CollectingSink {
+    records: Mutex<Vec<SinkRecord>>,
+    flushed: Mutex<bool>,
+}
+
+#[async_trait]
+impl EventSink for CollectingSink {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), SinkError> {
+        let record = SinkRecord::new("w3b2.events", envelope, envelope.signature.clone().into());
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), SinkError> {
+        *self.flushed.lock().unwrap() = true;
+        Ok(())
+    }
+}
+
+/// Fails the first `failures` publishes.
+struct FlakySink {
+    failures: u32,
+    calls: AtomicU32,
+}
+
+#[async_trait]
+impl EventSink for FlakySink {
+    async fn publish(&self, _envelope: &EventEnvelope) -> Result<(), SinkError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(SinkError::Publish("unavailable".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_event_categories() {
+    assert_eq!(
+        EventCategory::of(EventKind::UserCommandDispatched),
+        EventCategory::Command
+    );
+    assert_eq!(
+        EventCategory::of(EventKind::AdminCommandDispatched),
+        EventCategory::Command
+    );
+    assert_eq!(
+        EventCategory::of(EventKind::AdminPricesUpdated),
+        EventCategory::Admin
+    );
+    assert_eq!(
+        EventCategory::of(EventKind::UserFundsDeposited),
+        EventCategory::User
+    );
+    assert_eq!(
+        EventCategory::of(EventKind::OffChainActionLogged),
+        EventCategory::Action
+    );
+    assert_eq!(EventCategory::of(EventKind::Unknown).as_str(), "unknown");
+}
+
+#[tokio::test]
+async fn test_forward_lays_out_records() {
+    let user = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let (tx, rx) = broadcast::channel(16);
+    tx.send(envelope(
+        "a",
+        0,
+        BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+            sender: user,
+            target_admin_authority: admin,
+            command_id: 1,
+            price_paid: 0,
+            high_priority: false,
+            priority_fee: 0,
+            payload: Vec::new(),
+            ts: 0,
+        }),
+    ))
+    .unwrap();
+    tx.send(envelope(
+        "b",
+        0,
+        BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+            authority: user,
+            amount: 10,
+            new_deposit_balance: 10,
+            rent_reserve: 0,
+            ts: 0,
+        }),
+    ))
+    .unwrap();
+    drop(tx);
+
+    let sink = CollectingSink::default();
+    forward(rx, &sink).await.unwrap();
+
+    let records = sink.records.lock().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].topic, "w3b2.events.command");
+    assert_eq!(records[0].key, Some(user));
+    assert_eq!(records[0].payload, b"a");
+    assert_eq!(records[1].topic, "w3b2.events.user");
+    assert_eq!(records[1].key, Some(user));
+    assert!(*sink.flushed.lock().unwrap());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_record_payload() {
+    let user = Pubkey::new_unique();
+    let envelope = envelope(
+        "c",
+        0,
+        BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+            authority: user,
+            amount: 10,
+            new_deposit_balance: 10,
+            rent_reserve: 0,
+            ts: 0,
+        }),
+    );
+
+    let record = SinkRecord::json("events", &envelope).unwrap();
+    assert_eq!(record.topic, "events.user");
+    let decoded: EventEnvelope = serde_json::from_slice(&record.payload).unwrap();
+    assert_eq!(decoded.signature, "c");
+}
+
+#[cfg(feature = "kafka")]
+#[tokio::test]
+async fn test_kafka_sink_reports_undelivered_events() {
+    let port = portpicker::pick_unused_port().unwrap();
+    let producer = rdkafka::ClientConfig::new()
+        .set("bootstrap.servers", format!("127.0.0.1:{}", port))
+        .set("message.timeout.ms", "200")
+        .create()
+        .unwrap();
+    let sink = w3b2_connector::sink::KafkaSink::with_producer(producer, "w3b2.events");
+    let event = envelope(
+        "sig",
+        0,
+        BridgeEvent::Unknown {
+            raw: RawEvent::default(),
+        },
+    );
+
+    let result = sink.publish(&event).await;
+    assert!(matches!(result, Err(SinkError::Publish(_))));
+}
+
+#[test]
+fn test_subject_and_message_id() {
+    let user = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mut command = envelope(
+        "sig",
+        0,
+        BridgeEvent::UserCommandDispatched(UserCommandDispatched {
+            sender: user,
+            target_admin_authority: admin,
+            command_id: 1,
+            price_paid: 0,
+            high_priority: false,
+            priority_fee: 0,
+            payload: Vec::new(),
+            ts: 0,
+        }),
+    );
+    command.index = 3;
+    assert_eq!(
+        subject("w3b2.events", &command),
+        format!("w3b2.events.{}.UserCommandDispatched", admin)
+    );
+    assert_eq!(message_id(&command), "sig:3");
+
+    let deposit = envelope(
+        "sig",
+        0,
+        BridgeEvent::UserFundsDeposited(UserFundsDeposited {
+            authority: user,
+            amount: 10,
+            new_deposit_balance: 10,
+            rent_reserve: 0,
+            ts: 0,
+        }),
+    );
+    assert_eq!(
+        subject("w3b2.events", &deposit),
+        "w3b2.events._.UserFundsDeposited"
+    );
+}
+
+#[tokio::test]
+async fn test_retrying_sink() {
+    let event = envelope(
+        "sig",
+        0,
+        BridgeEvent::Unknown {
+            raw: RawEvent::default(),
+        },
+    );
+    let backoff = Duration::from_millis(1);
+
+    let sink = RetryingSink::new(
+        FlakySink {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        },
+        3,
+    )
+    .with_backoff(backoff, backoff);
+    sink.publish(&event).await.unwrap();
+    assert_eq!(sink.into_inner().calls.load(Ordering::SeqCst), 3);
+
+    let sink = RetryingSink::new(
+        FlakySink {
+            failures: 5,
+            calls: AtomicU32::new(0),
+        },
+        3,
+    )
+    .with_backoff(backoff, backoff);
+    assert!(matches!(
+        sink.publish(&event).await,
+        Err(SinkError::Publish(_))
+    ));
+    assert_eq!(sink.into_inner().calls.load(Ordering::SeqCst), 3);
+}
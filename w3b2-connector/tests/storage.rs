@@ -0,0 +1,309 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use common::action;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::events::OffChainActionLogged;
+use w3b2_connector::config::Retention;
+use w3b2_connector::events::{
+    parse_event_data, try_parse_log, BridgeEvent, EventEnvelope, RawEvent,
+};
+use w3b2_connector::storage::{MemoryStorage, Snapshot, Storage, StorageError};
+use w3b2_connector::workers::prune;
+
+#[tokio::test]
+async fn test_memory_storage_sync_state() {
+    let storage = MemoryStorage::new();
+    assert_eq!(storage.get_last_slot().await.unwrap(), 0);
+    assert_eq!(storage.get_last_sig().await.unwrap(), None);
+
+    storage.set_sync_state(42, "sig-a").await.unwrap();
+    storage.set_sync_state(43, "sig-b").await.unwrap();
+
+    assert_eq!(storage.get_last_slot().await.unwrap(), 43);
+    assert_eq!(
+        storage.get_last_sig().await.unwrap().as_deref(),
+        Some("sig-b")
+    );
+}
+
+#[test]
+fn test_event_envelope_round_trip() {
+    let actor = Pubkey::new_unique();
+    let envelope = action(actor, 7, "sig", 2);
+
+    let restored = EventEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+    assert_eq!(restored.slot, 7);
+    assert_eq!(restored.signature, "sig");
+    assert_eq!(restored.index, 2);
+    assert_eq!(restored.pubkeys(), vec![actor]);
+    assert!(matches!(
+        restored.event,
+        BridgeEvent::OffChainActionLogged(OffChainActionLogged { session_id: 7, .. })
+    ));
+}
+
+#[test]
+fn test_event_envelope_block_time() {
+    let mut envelope = action(Pubkey::new_unique(), 7, "sig", 2);
+    envelope.block_time = Some(1_700_000_000);
+    let restored = EventEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+    assert_eq!(restored.block_time, Some(1_700_000_000));
+
+    // Envelopes stored before the block time was recorded still decode.
+    let legacy = borsh::to_vec(&(7u64, "sig", 2u32, envelope.event.to_bytes())).unwrap();
+    let restored = EventEnvelope::from_bytes(&legacy).unwrap();
+    assert_eq!(restored.cursor(), envelope.cursor());
+    assert_eq!(restored.block_time, None);
+}
+
+#[test]
+fn test_event_envelope_tx_index() {
+    let mut envelope = action(Pubkey::new_unique(), 7, "sig", 2);
+    envelope.tx_index = Some(3);
+    envelope.block_time = Some(1_700_000_000);
+    let restored = EventEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+    assert_eq!(restored.tx_index, Some(3));
+
+    // Envelopes stored before the transaction index was recorded still decode.
+    let legacy = borsh::to_vec(&(
+        7u64,
+        "sig",
+        2u32,
+        envelope.event.to_bytes(),
+        Some(1_700_000_000i64),
+    ))
+    .unwrap();
+    let restored = EventEnvelope::from_bytes(&legacy).unwrap();
+    assert_eq!(restored.block_time, Some(1_700_000_000));
+    assert_eq!(restored.tx_index, None);
+}
+
+#[test]
+fn test_unknown_event_keeps_raw_data() {
+    let data = [[7u8; 8].as_slice(), &[1, 2, 3]].concat();
+    let BridgeEvent::Unknown { raw } = parse_event_data(&data).unwrap() else {
+        panic!("expected an unknown event");
+    };
+    assert_eq!(raw.discriminator(), Some([7; 8]));
+    assert_eq!(raw.data, data);
+
+    let mut envelope = action(Pubkey::new_unique(), 7, "sig", 0);
+    envelope.event = BridgeEvent::Unknown { raw };
+    let restored = EventEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+    assert!(matches!(
+        restored.event,
+        BridgeEvent::Unknown { raw } if raw.data == data && raw.signature.as_deref() == Some("sig")
+    ));
+}
+
+#[test]
+fn test_unknown_log_round_trips() {
+    let data = [[7u8; 8].as_slice(), &[1, 2, 3]].concat();
+    let log = format!("Program data: {}", BASE64_STANDARD.encode(&data));
+
+    let event = try_parse_log(&log).unwrap();
+    let BridgeEvent::Unknown { raw } = &event else {
+        panic!("expected an unknown event");
+    };
+    assert_eq!(raw.discriminator(), Some([7; 8]));
+    assert_eq!(raw.logs, vec![log.clone()]);
+    assert_eq!(raw.signature, None);
+
+    // Logging it again yields the same event.
+    let relogged = format!("Program data: {}", BASE64_STANDARD.encode(event.to_bytes()));
+    assert_eq!(relogged, log);
+    let BridgeEvent::Unknown { raw: reparsed } = try_parse_log(&relogged).unwrap() else {
+        panic!("expected an unknown event");
+    };
+    assert_eq!(&reparsed, raw);
+}
+
+#[test]
+fn test_stored_unknown_event_decodes_once_known() {
+    // An event stored by a connector that did not know its type yet.
+    let known = action(Pubkey::new_unique(), 7, "sig", 0);
+    let mut stored = known.clone();
+    stored.event = BridgeEvent::Unknown {
+        raw: RawEvent {
+            data: known.event.to_bytes(),
+            ..Default::default()
+        },
+    };
+
+    let restored = EventEnvelope::from_bytes(&stored.to_bytes()).unwrap();
+    assert!(matches!(
+        restored.event,
+        BridgeEvent::OffChainActionLogged(e) if e.session_id == 7
+    ));
+    assert_eq!(RawEvent::default().discriminator(), None);
+}
+
+#[tokio::test]
+async fn test_memory_storage_event_history() {
+    let storage = MemoryStorage::new();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    storage
+        .append_event(&action(alice, 20, "b", 0))
+        .await
+        .unwrap();
+    storage
+        .append_event(&action(bob, 10, "a", 0))
+        .await
+        .unwrap();
+    storage
+        .append_event(&action(alice, 10, "a", 1))
+        .await
+        .unwrap();
+    // Re-appending the same event does not duplicate it.
+    storage
+        .append_event(&action(alice, 10, "a", 1))
+        .await
+        .unwrap();
+
+    let slots =
+        |events: Vec<EventEnvelope>| events.iter().map(|e| (e.slot, e.index)).collect::<Vec<_>>();
+
+    assert_eq!(
+        slots(storage.events_by_slot_range(0, 100).await.unwrap()),
+        vec![(10, 0), (10, 1), (20, 0)]
+    );
+    assert_eq!(
+        slots(storage.events_by_slot_range(11, 20).await.unwrap()),
+        vec![(20, 0)]
+    );
+    assert_eq!(
+        slots(storage.events_by_signature("a").await.unwrap()),
+        vec![(10, 0), (10, 1)]
+    );
+    assert_eq!(
+        slots(storage.events_by_pubkey(&alice, 0, 100).await.unwrap()),
+        vec![(10, 1), (20, 0)]
+    );
+    assert_eq!(
+        slots(storage.events_by_pubkey(&alice, 15, 100).await.unwrap()),
+        vec![(20, 0)]
+    );
+}
+
+#[tokio::test]
+async fn test_memory_storage_pruning() {
+    let storage = MemoryStorage::new();
+    let actor = Pubkey::new_unique();
+    for slot in [10, 20, 30, 40] {
+        storage
+            .append_event(&action(actor, slot, "s", 0))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(storage.prune_events_before(20).await.unwrap(), 1);
+    assert_eq!(storage.prune_events_to_count(2).await.unwrap(), 1);
+    assert_eq!(storage.prune_events_to_count(2).await.unwrap(), 0);
+
+    let remaining = storage.events_by_slot_range(0, u64::MAX).await.unwrap();
+    assert_eq!(
+        remaining.iter().map(|e| e.slot).collect::<Vec<_>>(),
+        vec![30, 40]
+    );
+}
+
+#[tokio::test]
+async fn test_prune_applies_retention_limits() {
+    let storage = MemoryStorage::new();
+    let actor = Pubkey::new_unique();
+    for slot in [100, 200, 300, 400, 500] {
+        storage
+            .append_event(&action(actor, slot, "s", 0))
+            .await
+            .unwrap();
+    }
+    storage.set_sync_state(500, "s").await.unwrap();
+
+    // Nothing configured, nothing pruned.
+    assert_eq!(prune(&storage, &Retention::default()).await.unwrap(), 0);
+
+    // 100 seconds is 250 slots, which is tighter than `max_slots`.
+    let retention = Retention {
+        max_age_secs: Some(100),
+        max_slots: Some(350),
+        ..Default::default()
+    };
+    assert_eq!(prune(&storage, &retention).await.unwrap(), 2);
+
+    let retention = Retention {
+        max_events: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(prune(&storage, &retention).await.unwrap(), 2);
+    assert_eq!(
+        storage.events_by_slot_range(0, u64::MAX).await.unwrap()[0].slot,
+        500
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_round_trip() {
+    let source = MemoryStorage::new();
+    let actor = Pubkey::new_unique();
+    source.set_sync_state(30, "sig-30").await.unwrap();
+    source
+        .append_event(&action(actor, 10, "sig-10", 0))
+        .await
+        .unwrap();
+    source
+        .append_event(&action(actor, 30, "sig-30", 0))
+        .await
+        .unwrap();
+    let cursor = action(actor, 10, "sig-10", 0).cursor();
+    source.set_cursor("svc", &cursor).await.unwrap();
+
+    let bytes = source.export_snapshot(true).await.unwrap().to_bytes();
+    let snapshot = Snapshot::from_bytes(&bytes).unwrap();
+    let target = MemoryStorage::new();
+    target.import_snapshot(&snapshot).await.unwrap();
+
+    assert_eq!(target.get_last_slot().await.unwrap(), 30);
+    assert_eq!(
+        target.get_last_sig().await.unwrap().as_deref(),
+        Some("sig-30")
+    );
+    assert_eq!(target.get_cursor("svc").await.unwrap(), Some(cursor));
+    assert_eq!(
+        target
+            .events_by_slot_range(0, u64::MAX)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let without_events = source.export_snapshot(false).await.unwrap();
+    assert!(without_events.events.is_empty());
+    assert_eq!(without_events.cursors.len(), 1);
+
+    assert!(Snapshot::from_bytes(b"not a snapshot").is_err());
+}
+
+#[test]
+fn test_snapshot_decoding_errors() {
+    let mut bytes = Snapshot::default().to_bytes();
+    assert!(Snapshot::from_bytes(&bytes).is_ok());
+
+    assert!(matches!(
+        Snapshot::from_bytes(&bytes[..8]),
+        Err(StorageError::Corrupted(_))
+    ));
+    assert!(matches!(
+        Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(StorageError::Corrupted(_))
+    ));
+
+    bytes[8] = 2;
+    assert!(matches!(
+        Snapshot::from_bytes(&bytes),
+        Err(StorageError::UnsupportedVersion(2))
+    ));
+}
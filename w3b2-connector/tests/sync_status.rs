@@ -0,0 +1,67 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::sync::Arc;
+use w3b2_connector::{
+    config::ConnectorConfig,
+    metrics::SyncStatus,
+    storage::{MemoryStorage, Storage},
+    workers::EventManager,
+};
+
+#[tokio::test]
+async fn test_sync_status_reports_processed_slot() {
+    let storage = Arc::new(MemoryStorage::new());
+    storage.set_sync_state(1234, "sig").await.unwrap();
+    let (_manager, handle) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(RpcClient::new_mock("succeeds".to_string())),
+        storage,
+        16,
+        16,
+    );
+
+    let status = handle.sync_status().await.unwrap();
+    assert_eq!(status.processed_slot, 1234);
+    assert_eq!(status.chain_slot, 1234);
+    assert_eq!(status.slot_lag(), 0);
+    assert_eq!(status.events_total, 0);
+    assert_eq!(status.events_per_sec, 0.0);
+    assert_eq!(status.duplicates_skipped, 0);
+    assert_eq!(status.ws_reconnects, 0);
+
+    let exposition = status.to_prometheus();
+    assert!(exposition.contains("# TYPE w3b2_sync_events_total counter\n"));
+    assert!(exposition.contains("w3b2_sync_processed_slot 1234\n"));
+    assert!(exposition.contains("w3b2_sync_slot_lag 0\n"));
+    assert!(exposition.contains("w3b2_sync_ws_reconnects_total 0\n"));
+}
+
+#[test]
+fn test_labeled_exposition_has_one_series_per_synchronizer() {
+    let status = |processed_slot| SyncStatus {
+        chain_slot: 100,
+        processed_slot,
+        events_total: 0,
+        events_per_sec: 0.0,
+        decode_failures: 0,
+        duplicates_skipped: 0,
+        catchup_remaining: 0,
+        ws_reconnects: 0,
+    };
+    let exposition = SyncStatus::to_prometheus_labeled(
+        "cluster",
+        &[
+            ("default".to_string(), status(100)),
+            ("devnet".to_string(), status(40)),
+        ],
+    );
+
+    assert_eq!(
+        exposition
+            .matches("# TYPE w3b2_sync_slot_lag gauge\n")
+            .count(),
+        1
+    );
+    assert!(exposition.contains("w3b2_sync_slot_lag{cluster=\"default\"} 0\n"));
+    assert!(exposition.contains("w3b2_sync_slot_lag{cluster=\"devnet\"} 60\n"));
+    assert!(SyncStatus::to_prometheus_labeled("cluster", &[]).is_empty());
+}
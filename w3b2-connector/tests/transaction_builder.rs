@@ -0,0 +1,367 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_response::RpcPrioritizationFee;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use std::sync::Arc;
+use std::time::Duration;
+use w3b2_bridge_program::errors::BridgeError;
+use w3b2_connector::blockhash::BlockhashCache;
+use w3b2_connector::client::{
+    decode_bridge_error, to_versioned_transaction, ComputeBudget, SubmitOutcome, SubmitPolicy,
+    TransactionBuilder, MAX_MEMO_LEN,
+};
+use w3b2_connector::instructions;
+use w3b2_connector::rpc::MockSolanaRpc;
+
+fn mock_builder() -> TransactionBuilder {
+    TransactionBuilder::new(Arc::new(RpcClient::new_mock("succeeds".to_string())))
+}
+
+fn program_ids(tx: &solana_sdk::transaction::Transaction) -> Vec<Pubkey> {
+    tx.message
+        .instructions
+        .iter()
+        .map(|ix| tx.message.account_keys[ix.program_id_index as usize])
+        .collect()
+}
+
+#[tokio::test]
+async fn test_default_compute_budget_sets_unit_limit_only() {
+    let tx = mock_builder()
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        program_ids(&tx),
+        vec![
+            solana_compute_budget_interface::id(),
+            w3b2_bridge_program::ID
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_compute_budget_override_adds_priority_fee() {
+    let budget = ComputeBudget {
+        unit_limit: Some(50_000),
+        unit_price: Some(1_000),
+    };
+    let tx = mock_builder()
+        .with_compute_budget(budget)
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        program_ids(&tx),
+        vec![
+            solana_compute_budget_interface::id(),
+            solana_compute_budget_interface::id(),
+            w3b2_bridge_program::ID
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_empty_compute_budget_adds_nothing() {
+    let budget = ComputeBudget {
+        unit_limit: None,
+        unit_price: None,
+    };
+    let tx = mock_builder()
+        .with_compute_budget(budget)
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(program_ids(&tx), vec![w3b2_bridge_program::ID]);
+}
+
+#[tokio::test]
+async fn test_prepare_versioned_transaction_uses_lookup_tables() {
+    let authority = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let (admin_pda, _) =
+        Pubkey::find_program_address(&[b"admin", authority.as_ref()], &w3b2_bridge_program::ID);
+    let ix = Instruction::new_with_bytes(
+        w3b2_bridge_program::ID,
+        &[],
+        vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(admin_pda, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+    let table_key = Pubkey::new_unique();
+    let table = AddressLookupTableAccount {
+        key: table_key,
+        addresses: vec![admin_pda, recipient],
+    };
+
+    let tx = mock_builder()
+        .prepare_versioned_transaction(authority, vec![ix], &[table])
+        .await
+        .unwrap();
+
+    let VersionedMessage::V0(message) = &tx.message else {
+        panic!("expected a v0 message");
+    };
+    assert_eq!(tx.signatures.len(), 1);
+    assert_eq!(message.address_table_lookups.len(), 1);
+    assert_eq!(message.address_table_lookups[0].account_key, table_key);
+    assert_eq!(message.address_table_lookups[0].writable_indexes.len(), 2);
+    assert!(!message.account_keys.contains(&admin_pda));
+    assert!(!message.account_keys.contains(&recipient));
+}
+
+#[tokio::test]
+async fn test_batch_combines_user_onboarding_into_one_transaction() {
+    let user = Pubkey::new_unique();
+    let admin_pda = instructions::admin_profile_pda(&Pubkey::new_unique());
+
+    let tx = mock_builder()
+        .with_compute_budget(ComputeBudget {
+            unit_limit: None,
+            unit_price: None,
+        })
+        .batch(user)
+        .user_create_profile(admin_pda, Pubkey::new_unique())
+        .user_deposit(admin_pda, 1_000_000)
+        .user_dispatch_command(admin_pda, 1, false, vec![])
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(tx.message.account_keys[0], user);
+    assert_eq!(tx.message.header.num_required_signatures, 1);
+    assert_eq!(program_ids(&tx), vec![w3b2_bridge_program::ID; 3]);
+}
+
+#[tokio::test]
+async fn test_simulate_prepared_transaction() {
+    let builder = mock_builder();
+    let tx = builder
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap();
+
+    let result = builder.simulate(&tx).await.unwrap();
+
+    assert!(result.is_ok());
+    assert!(result.bridge_error.is_none());
+}
+
+#[test]
+fn test_decode_bridge_error_maps_custom_codes() {
+    let err = TransactionError::InstructionError(1, InstructionError::Custom(6005));
+    assert!(matches!(
+        decode_bridge_error(&err),
+        Some(BridgeError::CommandNotFound)
+    ));
+    let err = TransactionError::InstructionError(0, InstructionError::Custom(6011));
+    assert!(matches!(
+        decode_bridge_error(&err),
+        Some(BridgeError::ProfileMigrated)
+    ));
+    let err = TransactionError::InstructionError(0, InstructionError::Custom(6012));
+    assert!(matches!(
+        decode_bridge_error(&err),
+        Some(BridgeError::V2SeedsDisabled)
+    ));
+
+    let system_err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+    assert!(decode_bridge_error(&system_err).is_none());
+    assert!(decode_bridge_error(&TransactionError::AccountNotFound).is_none());
+}
+
+#[tokio::test]
+async fn test_builder_uses_shared_blockhash_cache() {
+    let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+    let cache = Arc::new(BlockhashCache::new(
+        rpc_client.clone(),
+        Duration::from_secs(60),
+    ));
+    let cached = cache.get().await.unwrap();
+
+    let tx = TransactionBuilder::new(rpc_client)
+        .with_blockhash_cache(cache.clone())
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap();
+
+    assert_eq!(tx.message.recent_blockhash, cached);
+
+    cache.invalidate().await;
+    assert_eq!(cache.get().await.unwrap(), cached);
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_reports_confirmation() {
+    let builder = mock_builder();
+    let tx = builder
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap();
+
+    let outcome = builder
+        .submit_with_policy(&tx, &SubmitPolicy::default())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        outcome,
+        SubmitOutcome::Confirmed { attempts: 1, .. }
+    ));
+    assert_eq!(outcome.signature(), &tx.signatures[0]);
+}
+
+#[tokio::test]
+async fn test_submit_with_policy_reports_on_chain_failure() {
+    let builder = TransactionBuilder::new(Arc::new(RpcClient::new_mock(
+        "instruction_error".to_string(),
+    )));
+    let tx = builder
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap();
+
+    let outcome = builder
+        .submit_with_policy(&tx, &SubmitPolicy::default())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        outcome,
+        SubmitOutcome::Failed {
+            error: TransactionError::InstructionError(0, InstructionError::UninitializedAccount),
+            bridge_error: None,
+            ..
+        }
+    ));
+}
+
+fn fee(slot: u64, prioritization_fee: u64) -> RpcPrioritizationFee {
+    RpcPrioritizationFee {
+        slot,
+        prioritization_fee,
+    }
+}
+
+#[tokio::test]
+async fn test_auto_priority_fee_pays_the_requested_percentile() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    rpc.set_prioritization_fees(vec![
+        fee(1, 1_000),
+        fee(2, 50),
+        fee(3, 2_000),
+        fee(4, 500),
+        fee(5, 100),
+    ]);
+
+    let tx = TransactionBuilder::new(rpc.clone())
+        .with_auto_priority_fee(50)
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    let price_ix = ComputeBudgetInstruction::set_compute_unit_price(500);
+    assert!(tx
+        .message
+        .instructions
+        .iter()
+        .any(|ix| ix.data == price_ix.data));
+    assert_eq!(rpc.calls("get_recent_prioritization_fees"), 1);
+}
+
+#[tokio::test]
+async fn test_explicit_unit_price_skips_the_fee_estimate() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+
+    TransactionBuilder::new(rpc.clone())
+        .with_auto_priority_fee(75)
+        .with_compute_budget(ComputeBudget {
+            unit_limit: None,
+            unit_price: Some(10),
+        })
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(rpc.calls("get_recent_prioritization_fees"), 0);
+}
+
+#[tokio::test]
+async fn test_estimate_without_recent_fees_is_zero() {
+    let builder = TransactionBuilder::new(Arc::new(MockSolanaRpc::new()));
+
+    let price = builder
+        .estimate_compute_unit_price(&[Pubkey::new_unique()], 90)
+        .await
+        .unwrap();
+
+    assert_eq!(price, 0);
+}
+
+#[tokio::test]
+async fn test_to_versioned_transaction_keeps_instructions_and_blockhash() {
+    let user = Pubkey::new_unique();
+    let admin_pda = instructions::admin_profile_pda(&Pubkey::new_unique());
+    let legacy = mock_builder()
+        .prepare_user_deposit(user, admin_pda, 1_000)
+        .await
+        .unwrap();
+    let table_key = Pubkey::new_unique();
+    let table = AddressLookupTableAccount {
+        key: table_key,
+        addresses: vec![admin_pda],
+    };
+
+    let tx = to_versioned_transaction(&legacy, &[table]).unwrap();
+
+    let VersionedMessage::V0(message) = &tx.message else {
+        panic!("expected a v0 message");
+    };
+    assert_eq!(message.account_keys[0], user);
+    assert_eq!(message.recent_blockhash, legacy.message.recent_blockhash);
+    assert_eq!(
+        message.instructions.len(),
+        legacy.message.instructions.len()
+    );
+    assert_eq!(message.address_table_lookups[0].account_key, table_key);
+    assert!(!message.account_keys.contains(&admin_pda));
+    assert_eq!(tx.signatures.len(), 1);
+}
+
+#[tokio::test]
+async fn test_memo_is_appended_after_the_bridge_instruction() {
+    let tx = mock_builder()
+        .with_memo("invoice-4711")
+        .unwrap()
+        .prepare_admin_register_profile(Pubkey::new_unique(), Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        program_ids(&tx),
+        vec![
+            solana_compute_budget_interface::id(),
+            w3b2_bridge_program::ID,
+            spl_memo::id()
+        ]
+    );
+    let memo = tx.message.instructions.last().unwrap();
+    assert_eq!(memo.data, b"invoice-4711");
+    assert!(memo.accounts.is_empty());
+}
+
+#[test]
+fn test_overlong_memo_is_rejected() {
+    assert!(mock_builder().with_memo("x".repeat(MAX_MEMO_LEN)).is_ok());
+    assert!(mock_builder()
+        .with_memo("x".repeat(MAX_MEMO_LEN + 1))
+        .is_err());
+}
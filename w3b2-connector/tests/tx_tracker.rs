@@ -0,0 +1,101 @@
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, instruction::InstructionError, pubkey::Pubkey,
+    signature::Signature, transaction::TransactionError,
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::sync::Arc;
+use std::time::Duration;
+use w3b2_bridge_program::errors::BridgeError;
+use w3b2_connector::{
+    client::TransactionBuilder,
+    rpc::MockSolanaRpc,
+    tracker::{TxStatus, TxTracker},
+};
+
+fn status(slot: u64, confirmation_status: TransactionConfirmationStatus) -> TransactionStatus {
+    TransactionStatus {
+        slot,
+        confirmations: None,
+        status: Ok(()),
+        err: None,
+        confirmation_status: Some(confirmation_status),
+    }
+}
+
+fn tracker(rpc: &Arc<MockSolanaRpc>) -> TxTracker {
+    TxTracker::new(rpc.clone()).with_poll_interval(Duration::from_millis(1))
+}
+
+#[tokio::test]
+async fn test_watch_reports_each_transition_until_settled() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let builder = TransactionBuilder::new(rpc.clone());
+    let tx = builder
+        .prepare_log_action(Pubkey::new_unique(), 1, 2)
+        .await
+        .unwrap();
+    let signature = builder.broadcast_transaction(&tx).await.unwrap();
+    assert_eq!(rpc.sent_signatures(), vec![signature]);
+
+    let mut updates = tracker(&rpc)
+        .with_commitment(CommitmentConfig::finalized())
+        .watch(signature, tx.message.recent_blockhash);
+
+    let first = updates.recv().await.unwrap();
+    assert!(matches!(first.status, TxStatus::Pending));
+    assert!(!first.is_final);
+
+    rpc.set_signature_status(
+        signature,
+        Some(status(7, TransactionConfirmationStatus::Confirmed)),
+    );
+    let confirmed = updates.recv().await.unwrap();
+    assert!(matches!(confirmed.status, TxStatus::Confirmed { slot: 7 }));
+    assert!(!confirmed.is_final);
+
+    rpc.set_signature_status(
+        signature,
+        Some(status(7, TransactionConfirmationStatus::Finalized)),
+    );
+    let finalized = updates.recv().await.unwrap();
+    assert!(matches!(finalized.status, TxStatus::Finalized { slot: 7 }));
+    assert!(finalized.is_final);
+    assert_eq!(finalized.signature, signature);
+    assert!(updates.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_wait_reports_expired_blockhash() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    rpc.set_blockhash_valid(false);
+
+    let status = tracker(&rpc)
+        .wait(Signature::new_unique(), Hash::new_unique())
+        .await;
+
+    assert!(matches!(status, TxStatus::Expired));
+    assert!(status.is_error());
+}
+
+#[tokio::test]
+async fn test_wait_decodes_program_errors() {
+    let rpc = Arc::new(MockSolanaRpc::new());
+    let signature = Signature::new_unique();
+    let mut failed = status(9, TransactionConfirmationStatus::Processed);
+    failed.err = Some(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(6005),
+    ));
+    rpc.set_signature_status(signature, Some(failed));
+
+    let status = tracker(&rpc).wait(signature, Hash::new_unique()).await;
+
+    assert!(matches!(
+        status,
+        TxStatus::Failed {
+            slot: 9,
+            bridge_error: Some(BridgeError::CommandNotFound),
+            ..
+        }
+    ));
+}
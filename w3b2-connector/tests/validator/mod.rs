@@ -0,0 +1,134 @@
+// tests/validator/mod.rs
+
+//! A `solana-test-validator` process with the bridge program deployed, for
+//! end-to-end tests of the connector against a real cluster.
+//!
+//! The program must be built first (`anchor build`). Set `W3B2_PROGRAM_SO` to load
+//! it from somewhere other than `../target/deploy/w3b2_bridge_program.so`.
+
+// Each test binary uses only a subset of these shared helpers.
+#![allow(dead_code)]
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    signature::{Keypair, Signer},
+};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::time::{sleep, Duration, Instant};
+use w3b2_connector::config::{ConnectorConfig, Solana};
+
+/// The default path of the compiled on-chain program binary (`.so` file).
+const PATH_SBF: &str = "../target/deploy/w3b2_bridge_program.so";
+
+/// How long the validator may take to start serving RPC requests.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A running `solana-test-validator`, killed when dropped.
+pub struct TestValidator {
+    process: Child,
+    rpc_url: String,
+    ws_url: String,
+    /// Kept for its Drop implementation, which removes the ledger.
+    _ledger: TempDir,
+}
+
+impl TestValidator {
+    /// Starts a fresh validator with the bridge program deployed at its program ID
+    /// and waits until it is healthy.
+    ///
+    /// # Panics
+    /// If `solana-test-validator` is not on the `PATH` or does not come up in time.
+    pub async fn start() -> Self {
+        let ledger = tempfile::tempdir().expect("Failed to create ledger dir");
+        let program = std::env::var("W3B2_PROGRAM_SO").unwrap_or_else(|_| PATH_SBF.to_string());
+        // The validator serves the WebSocket API on the port after the RPC port.
+        let rpc_port = portpicker::pick_unused_port().expect("No free ports");
+        let faucet_port = portpicker::pick_unused_port().expect("No free ports");
+
+        let process = Command::new("solana-test-validator")
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(ledger.path())
+            .args(["--rpc-port", &rpc_port.to_string()])
+            .args(["--faucet-port", &faucet_port.to_string()])
+            .arg("--bpf-program")
+            .arg(w3b2_bridge_program::ID.to_string())
+            .arg(&program)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn solana-test-validator; is it on the PATH?");
+
+        let validator = Self {
+            process,
+            rpc_url: format!("http://127.0.0.1:{}", rpc_port),
+            ws_url: format!("ws://127.0.0.1:{}", rpc_port + 1),
+            _ledger: ledger,
+        };
+        validator.wait_until_healthy().await;
+        validator
+    }
+
+    async fn wait_until_healthy(&self) {
+        let rpc_client = self.rpc_client();
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while rpc_client.get_health().await.is_err() {
+            assert!(
+                Instant::now() < deadline,
+                "solana-test-validator did not become healthy within {:?}",
+                STARTUP_TIMEOUT
+            );
+            sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    /// Returns a client for the validator's RPC endpoint at `confirmed` commitment.
+    pub fn rpc_client(&self) -> Arc<RpcClient> {
+        Arc::new(RpcClient::new_with_commitment(
+            self.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+        ))
+    }
+
+    /// Returns a connector configuration that indexes this validator at `confirmed`
+    /// commitment, polling quickly so tests don't wait long for catch-up.
+    pub fn connector_config(&self) -> ConnectorConfig {
+        let mut config = ConnectorConfig {
+            solana: Solana {
+                rpc_url: self.rpc_url.clone(),
+                ws_url: self.ws_url.clone(),
+                commitment: CommitmentLevel::Confirmed,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        config.synchronizer.poll_interval_secs = 1;
+        config
+    }
+
+    /// Creates a new `Keypair` and funds it from the validator's faucet.
+    pub async fn funded_keypair(&self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        let rpc_client = self.rpc_client();
+        let signature = rpc_client
+            .request_airdrop(&keypair.pubkey(), lamports)
+            .await
+            .expect("Airdrop request failed");
+        rpc_client
+            .poll_for_signature(&signature)
+            .await
+            .expect("Airdrop did not land");
+        keypair
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,255 @@
+mod validator;
+
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::time::{timeout, Duration};
+use validator::TestValidator;
+use w3b2_bridge_program::protocols::{PayloadCodec, PayloadHeader};
+use w3b2_connector::{
+    client::TransactionBuilder,
+    events::{EventEnvelope, EventKind},
+    instructions::{admin_profile_pda, user_profile_pda},
+    storage::MemoryStorage,
+    workers::EventManager,
+    Accounts::PriceEntry,
+};
+
+/// How long the connector may take to publish the events of a confirmed transaction.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Submits transactions to the validator and checks what the connector publishes.
+struct Bridge {
+    builder: TransactionBuilder,
+    events: broadcast::Receiver<EventEnvelope>,
+}
+
+impl Bridge {
+    /// Signs and submits `tx`, then asserts that the next event the connector
+    /// publishes is of `kind` and belongs to that transaction.
+    async fn expect(
+        &mut self,
+        tx: Result<Transaction, ClientError>,
+        signer: &Keypair,
+        kind: EventKind,
+    ) -> EventEnvelope {
+        let mut tx = tx.expect("Failed to prepare transaction");
+        let blockhash = tx.message.recent_blockhash;
+        tx.sign(&[signer], blockhash);
+        let signature = self
+            .builder
+            .submit_transaction(&tx)
+            .await
+            .unwrap_or_else(|e| panic!("{:?} transaction failed: {}", kind, e));
+
+        let envelope = timeout(EVENT_TIMEOUT, self.events.recv())
+            .await
+            .unwrap_or_else(|_| panic!("No {:?} event within {:?}", kind, EVENT_TIMEOUT))
+            .expect("Event channel closed");
+        assert_eq!(envelope.event.kind(), kind);
+        assert_eq!(envelope.signature, signature.to_string());
+        envelope
+    }
+}
+
+fn payload(body: &[u8]) -> Vec<u8> {
+    PayloadHeader::new(PayloadCodec::Raw, false).wrap(body)
+}
+
+/// Runs every instruction of the program against a `solana-test-validator` and
+/// checks that the synchronizer delivers its event, end to end.
+///
+/// `gc_inactive_profile` is not covered: it needs whole epochs of inactivity.
+///
+/// Run with `cargo test --test validator_events -- --ignored` after `anchor build`,
+/// with `solana-test-validator` on the `PATH`.
+#[tokio::test]
+#[ignore] // This test spawns a local validator and is slow.
+async fn test_synchronizer_delivers_every_instruction_event() {
+    let validator = TestValidator::start().await;
+    let rpc_client = validator.rpc_client();
+    let (runner, handle) = EventManager::new(
+        Arc::new(validator.connector_config()),
+        rpc_client.clone(),
+        Arc::new(MemoryStorage::new()),
+        256,
+        16,
+    );
+    let mut bridge = Bridge {
+        builder: TransactionBuilder::new(rpc_client),
+        events: handle.subscribe_all(),
+    };
+    tokio::spawn(runner.run());
+
+    let admin = validator.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let user = validator.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let admin_pda = admin_profile_pda(&admin.pubkey());
+    let user_pda = user_profile_pda(&user.pubkey(), &admin_pda);
+    let price = 1_000;
+    let builder = bridge.builder.clone();
+
+    // --- Admin setup ---
+    bridge
+        .expect(
+            builder
+                .prepare_admin_register_profile(admin.pubkey(), Pubkey::new_unique())
+                .await,
+            &admin,
+            EventKind::AdminProfileRegistered,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_update_comm_key(admin.pubkey(), Pubkey::new_unique())
+                .await,
+            &admin,
+            EventKind::AdminCommKeyUpdated,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_update_prices(admin.pubkey(), vec![PriceEntry::new(1, price)])
+                .await,
+            &admin,
+            EventKind::AdminPricesUpdated,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_set_gc_policy(admin.pubkey(), 10)
+                .await,
+            &admin,
+            EventKind::AdminGcPolicyUpdated,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_set_priority_surcharge(admin.pubkey(), 500)
+                .await,
+            &admin,
+            EventKind::AdminPrioritySurchargeUpdated,
+        )
+        .await;
+
+    // --- User lifecycle ---
+    bridge
+        .expect(
+            builder
+                .prepare_user_create_profile(user.pubkey(), admin_pda, Pubkey::new_unique())
+                .await,
+            &user,
+            EventKind::UserProfileCreated,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_user_update_comm_key(user.pubkey(), admin_pda, Pubkey::new_unique())
+                .await,
+            &user,
+            EventKind::UserCommKeyUpdated,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_user_deposit(user.pubkey(), admin_pda, LAMPORTS_PER_SOL)
+                .await,
+            &user,
+            EventKind::UserFundsDeposited,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_user_dispatch_command(user.pubkey(), admin_pda, 1, false, payload(&[1, 2]))
+                .await,
+            &user,
+            EventKind::UserCommandDispatched,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_dispatch_command(admin.pubkey(), user_pda, 7, 0, payload(&[3]))
+                .await,
+            &admin,
+            EventKind::AdminCommandDispatched,
+        )
+        .await;
+    bridge
+        .expect(
+            builder.prepare_log_action(user.pubkey(), 1, 200).await,
+            &user,
+            EventKind::OffChainActionLogged,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_user_withdraw(user.pubkey(), admin_pda, price, user.pubkey())
+                .await,
+            &user,
+            EventKind::UserFundsWithdrawn,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_withdraw(admin.pubkey(), price, admin.pubkey())
+                .await,
+            &admin,
+            EventKind::AdminFundsWithdrawn,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_user_close_profile(user.pubkey(), admin_pda)
+                .await,
+            &user,
+            EventKind::UserProfileClosed,
+        )
+        .await;
+
+    // --- Admin teardown ---
+    bridge
+        .expect(
+            builder.prepare_admin_migrate_to_v2(admin.pubkey(), 0).await,
+            &admin,
+            EventKind::AdminProfileMigrated,
+        )
+        .await;
+
+    let closing_admin = validator.funded_keypair(LAMPORTS_PER_SOL).await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_register_profile(closing_admin.pubkey(), Pubkey::new_unique())
+                .await,
+            &closing_admin,
+            EventKind::AdminProfileRegistered,
+        )
+        .await;
+    bridge
+        .expect(
+            builder
+                .prepare_admin_close_profile(closing_admin.pubkey())
+                .await,
+            &closing_admin,
+            EventKind::AdminProfileClosed,
+        )
+        .await;
+
+    handle.stop().await;
+}
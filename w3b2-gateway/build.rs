@@ -1,3 +1,7 @@
+use std::path::Path;
+
+const GATEWAY_PROTO: &str = "../w3b2-bridge-program/proto/gateway.proto";
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure().build_server(true).compile(
         &[
@@ -6,5 +10,32 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ], // The file to compile
         &["../w3b2-bridge-program/proto"], // The directory to search in
     )?;
+    write_gateway_methods()?;
+    Ok(())
+}
+
+/// Writes the request path and name of every gateway method to `gateway_methods.rs`,
+/// so metrics can tell the service's methods from any other path.
+fn write_gateway_methods() -> Result<(), Box<dyn std::error::Error>> {
+    let proto = std::fs::read_to_string(GATEWAY_PROTO)?;
+    let mut package = "";
+    let mut service = "";
+    let mut methods = String::new();
+    for line in proto.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("package ") {
+            package = rest.trim_end_matches(';').trim();
+        } else if let Some(rest) = line.strip_prefix("service ") {
+            service = rest.trim_end_matches('{').trim();
+        } else if let Some(rest) = line.strip_prefix("rpc ") {
+            let name = rest.split('(').next().unwrap_or_default().trim();
+            methods.push_str(&format!(
+                "    (\"/{}.{}/{}\", \"{}\"),\n",
+                package, service, name, name
+            ));
+        }
+    }
+    let out = Path::new(&std::env::var("OUT_DIR")?).join("gateway_methods.rs");
+    std::fs::write(out, format!("&[\n{}]\n", methods))?;
+    println!("cargo:rerun-if-changed={}", GATEWAY_PROTO);
     Ok(())
 }
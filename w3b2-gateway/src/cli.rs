@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use solana_sdk::native_token::sol_str_to_lamports;
 
 /// The main CLI structure for the W3B2 Gateway.
 #[derive(Parser, Debug)]
@@ -10,13 +11,30 @@ pub struct Cli {
 
 /// Defines the available subcommands for the application.
 ///
-/// For now, we only have the `run` command to start the service.
-/// Later, we can add commands like `cards`, `health`, etc.
+/// Besides `run`, which starts the service, there are developer commands for
+/// testing the bridge by hand against a local or devnet cluster.
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run the W3B2 Gateway service.
     /// This starts the Solana event listener and the gRPC server.
     Run(RunCmd),
+    /// Request an airdrop to a ChainCard. Only works on localnet and devnet.
+    Airdrop(AirdropCmd),
+    /// Show the lamport balance of a ChainCard and, optionally, its deposit with a service.
+    Balance(BalanceCmd),
+    /// Print a decoded AdminProfile or UserProfile.
+    Profile(ProfileCmd),
+    /// Export or import the sync state, e.g. to seed a new gateway node.
+    /// Stop the gateway using the database first.
+    Snapshot(SnapshotCmd),
+    /// Write an annotated configuration file with the default settings.
+    InitConfig(InitConfigCmd),
+    /// Load and validate a configuration file, then print the effective settings,
+    /// including defaults and `W3B2__*` environment overrides.
+    CheckConfig(CheckConfigCmd),
+    /// Check RPC and WebSocket connectivity, the program deployment, database
+    /// access and port availability, and explain how to fix what fails.
+    Doctor(DoctorCmd),
 }
 
 /// Arguments for the `run` subcommand.
@@ -27,3 +45,116 @@ pub struct RunCmd {
     #[arg(short, long)]
     pub config: Option<String>,
 }
+
+/// Arguments for the `airdrop` subcommand.
+#[derive(Parser, Debug)]
+pub struct AirdropCmd {
+    /// Path to the gateway configuration TOML file, for the RPC endpoint.
+    #[arg(short, long)]
+    pub config: Option<String>,
+    /// The ChainCard public key to fund.
+    pub pubkey: String,
+    /// The amount to request, in SOL.
+    #[arg(long = "sol", default_value = "1", value_parser = parse_sol)]
+    pub lamports: u64,
+}
+
+/// Arguments for the `balance` subcommand.
+#[derive(Parser, Debug)]
+pub struct BalanceCmd {
+    /// Path to the gateway configuration TOML file, for the RPC endpoint.
+    #[arg(short, long)]
+    pub config: Option<String>,
+    /// The ChainCard public key to inspect.
+    pub pubkey: String,
+    /// The admin's ChainCard public key. Also shows the deposit with this service.
+    #[arg(long)]
+    pub admin: Option<String>,
+}
+
+/// Arguments for the `profile` subcommand.
+#[derive(Parser, Debug)]
+pub struct ProfileCmd {
+    /// Path to the gateway configuration TOML file, for the RPC endpoint.
+    #[arg(short, long)]
+    pub config: Option<String>,
+    #[command(subcommand)]
+    pub kind: ProfileKind,
+}
+
+/// The kind of profile to print.
+#[derive(Subcommand, Debug)]
+pub enum ProfileKind {
+    /// The AdminProfile registered by an admin.
+    Admin {
+        /// The admin's ChainCard public key.
+        authority: String,
+    },
+    /// The UserProfile a user holds with a service.
+    User {
+        /// The user's ChainCard public key.
+        authority: String,
+        /// The admin's ChainCard public key.
+        #[arg(long)]
+        admin: String,
+    },
+}
+
+/// Arguments for the `snapshot` subcommand.
+#[derive(Parser, Debug)]
+pub struct SnapshotCmd {
+    /// Path to the gateway configuration TOML file, for the database path.
+    #[arg(short, long)]
+    pub config: Option<String>,
+    #[command(subcommand)]
+    pub action: SnapshotAction,
+}
+
+/// What to do with a snapshot file.
+#[derive(Subcommand, Debug)]
+pub enum SnapshotAction {
+    /// Write the sync cursor and subscriber cursors to a file.
+    Export {
+        /// The file to write.
+        file: String,
+        /// Also include the stored event history.
+        #[arg(long)]
+        events: bool,
+    },
+    /// Load a snapshot file into the database.
+    Import {
+        /// The file to read.
+        file: String,
+    },
+}
+
+/// Arguments for the `init-config` subcommand.
+#[derive(Parser, Debug)]
+pub struct InitConfigCmd {
+    /// Where to write the configuration file.
+    #[arg(default_value = "config.toml")]
+    pub path: String,
+    /// Overwrite the file if it already exists.
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for the `check-config` subcommand.
+#[derive(Parser, Debug)]
+pub struct CheckConfigCmd {
+    /// Path to the gateway configuration TOML file to check.
+    #[arg(short, long)]
+    pub config: String,
+}
+
+/// Arguments for the `doctor` subcommand.
+#[derive(Parser, Debug)]
+pub struct DoctorCmd {
+    /// Path to the gateway configuration TOML file to check the environment for.
+    #[arg(short, long)]
+    pub config: Option<String>,
+}
+
+fn parse_sol(sol: &str) -> Result<u64, String> {
+    sol_str_to_lamports(sol).ok_or_else(|| format!("invalid SOL amount '{}'", sol))
+}
@@ -1,19 +1,103 @@
-use anyhow::{Context, Result};
-use serde::Deserialize;
-use w3b2_connector::config::ConnectorConfig;
+use crate::cli::{CheckConfigCmd, InitConfigCmd};
+use crate::grpc::logging::REDACTED;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use w3b2_connector::config::{BackpressurePolicy, ConnectorConfig};
+
+/// The annotated example configuration `init-config` writes.
+pub const EXAMPLE_CONFIG: &str = include_str!("../config.example.toml");
+
+/// The name of the cluster configured by the `[connector]` section.
+pub const DEFAULT_CLUSTER: &str = "default";
 
 /// The top-level configuration for the W3B2 Gateway application.
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct GatewayConfig {
+    /// The default cluster, serving calls that do not select another one.
     #[serde(default)]
     pub connector: ConnectorConfig,
+    /// Further clusters by name, e.g. `[clusters.devnet]`, each with its own
+    /// connector settings. Calls select one through request metadata.
+    #[serde(default)]
+    pub clusters: BTreeMap<String, ConnectorConfig>,
     #[serde(default)]
     pub gateway: GatewaySpecificConfig,
 }
 
+impl GatewayConfig {
+    /// Returns every cluster by name, `DEFAULT_CLUSTER` first.
+    pub fn all_clusters(&self) -> impl Iterator<Item = (&str, &ConnectorConfig)> {
+        std::iter::once((DEFAULT_CLUSTER, &self.connector)).chain(
+            self.clusters
+                .iter()
+                .map(|(name, connector)| (name.as_str(), connector)),
+        )
+    }
+
+    /// Returns the path of the database a cluster stores its synchronization
+    /// state in. The default cluster uses `db-path` itself, every other cluster
+    /// a sibling named after it.
+    pub fn cluster_db_path(&self, name: &str) -> String {
+        if name == DEFAULT_CLUSTER {
+            self.gateway.db_path.clone()
+        } else {
+            format!("{}-{}", self.gateway.db_path, name)
+        }
+    }
+
+    /// Checks the connector settings of every cluster, the cluster names and the
+    /// streaming capacities.
+    pub fn validate(&self) -> Result<()> {
+        for (name, connector) in self.all_clusters() {
+            connector.validate().with_context(|| {
+                format!("Invalid connector configuration of cluster '{}'", name)
+            })?;
+        }
+        for name in self.clusters.keys() {
+            if name == DEFAULT_CLUSTER {
+                bail!(
+                    "Cluster '{}' is reserved for the [connector] section",
+                    DEFAULT_CLUSTER
+                );
+            }
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "Cluster name '{}' may only contain ASCII letters, digits, '-' and '_'",
+                    name
+                );
+            }
+        }
+        // Channels cannot be created with a capacity of zero.
+        let streaming = &self.gateway.streaming;
+        for (field, capacity) in [
+            ("broadcast-capacity", streaming.broadcast_capacity),
+            ("command-capacity", streaming.command_capacity),
+            (
+                "listener-channel-capacity",
+                streaming.listener_channel_capacity,
+            ),
+            ("output-stream-capacity", streaming.output_stream_capacity),
+            (
+                "service-listener-capacity",
+                streaming.service_listener_capacity,
+            ),
+        ] {
+            if capacity == 0 {
+                bail!("gateway.streaming.{} must be greater than zero", field);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Contains settings that are unique to the gateway binary.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct GatewaySpecificConfig {
     pub db_path: String,
@@ -26,18 +110,93 @@ pub struct GatewaySpecificConfig {
     /// Logging configuration.
     #[serde(default)]
     pub log: LogConfig,
+    /// Requires JWT bearer tokens on every call when set.
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    /// Serves Prometheus metrics over HTTP when set.
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    /// Serves the `/healthz` and `/readyz` probes over HTTP when set.
+    #[serde(default)]
+    pub health: Option<HealthConfig>,
+    /// The priority fee of prepared transactions whose request sets none.
+    #[serde(default)]
+    pub priority_fee: PriorityFeeConfig,
 }
 
 /// gRPC server connection settings.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct GrpcConfig {
     pub host: String,
     pub port: u16,
+    /// Serves TLS instead of plaintext when set.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// The PEM-encoded certificate chain and private key the gRPC server presents.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// How the priority fee of a prepared transaction is chosen.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case", rename_all_fields = "kebab-case", tag = "mode")]
+pub enum PriorityFeeConfig {
+    /// No priority fee is paid.
+    #[default]
+    None,
+    /// Every transaction pays the same price per compute unit.
+    Fixed { micro_lamports: u64 },
+    /// Pays this percentile (0-100) of the fees recently paid by transactions
+    /// writing to the same accounts.
+    Auto { percentile: u8 },
+}
+
+/// Where the Prometheus `/metrics` endpoint listens.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct MetricsConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+/// Where the `/healthz` and `/readyz` probes listen and when the gateway is ready.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct HealthConfig {
+    pub host: String,
+    pub port: u16,
+    /// How many slots a cluster's synchronizer may be behind the chain before
+    /// `/readyz` reports the gateway as not ready.
+    #[serde(default = "default_max_slot_lag")]
+    pub max_slot_lag: u64,
+}
+
+fn default_max_slot_lag() -> u64 {
+    150
+}
+
+/// JWT authentication settings.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuthConfig {
+    /// The shared secret tokens are signed with (HS256).
+    pub jwt_secret: String,
+    /// If set, tokens must carry this `iss` claim.
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// If set, tokens must carry this `aud` claim.
+    #[serde(default)]
+    pub audience: Option<String>,
 }
 
 /// Defines capacities for various channels used in the gateway.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct StreamingConfig {
     /// The buffer capacity for the main event broadcast channel (from Synchronizer to Dispatcher).
@@ -50,10 +209,35 @@ pub struct StreamingConfig {
     pub output_stream_capacity: usize,
     /// The buffer capacity for a specific service listener channel.
     pub service_listener_capacity: usize,
+    /// What the dispatcher does when a client's listener channel is full.
+    #[serde(default)]
+    pub listener_backpressure: BackpressurePolicy,
+    /// How long an event waits for room in a client's output stream before it
+    /// is recorded as a dead letter instead.
+    #[serde(default = "default_slow_consumer_timeout_ms")]
+    pub slow_consumer_timeout_ms: u64,
+    /// How long a dead letter is kept if the client never acknowledges it.
+    #[serde(default = "default_dead_letter_max_age_secs")]
+    pub dead_letter_max_age_secs: u64,
+    /// How many dead letters are kept per client; the oldest go first.
+    #[serde(default = "default_max_dead_letters_per_subscriber")]
+    pub max_dead_letters_per_subscriber: usize,
+}
+
+fn default_slow_consumer_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_dead_letter_max_age_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_max_dead_letters_per_subscriber() -> usize {
+    10_000
 }
 
 /// Logging configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct LogConfig {
     /// Log level, e.g., "info", "debug", "trace".
@@ -64,10 +248,26 @@ pub struct LogConfig {
     pub output: LogOutput,
     /// Path to the log file, required if output is "file".
     pub file_path: Option<String>,
+    /// Request fields whose values are replaced by `<redacted>` in the request log.
+    #[serde(default = "default_redact_fields")]
+    pub redact_fields: Vec<String>,
+    /// The share of requests logged, from 0.0 (none) to 1.0 (all).
+    #[serde(default = "default_request_sample_rate")]
+    pub request_sample_rate: f64,
+}
+
+fn default_redact_fields() -> Vec<String> {
+    ["payload", "signed_tx", "tx", "communication_pubkey", "new_key"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_request_sample_rate() -> f64 {
+    1.0
 }
 
 /// Defines the format for log messages.
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum LogFormat {
     Plain,
@@ -75,7 +275,7 @@ pub enum LogFormat {
 }
 
 /// Defines the destination for log output.
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum LogOutput {
     Stdout,
@@ -89,6 +289,10 @@ impl Default for GatewaySpecificConfig {
             grpc: GrpcConfig::default(),
             streaming: StreamingConfig::default(),
             log: LogConfig::default(),
+            auth: None,
+            metrics: None,
+            health: None,
+            priority_fee: PriorityFeeConfig::default(),
         }
     }
 }
@@ -101,6 +305,10 @@ impl Default for StreamingConfig {
             listener_channel_capacity: 1024,
             output_stream_capacity: 1024,
             service_listener_capacity: 256,
+            listener_backpressure: BackpressurePolicy::default(),
+            slow_consumer_timeout_ms: default_slow_consumer_timeout_ms(),
+            dead_letter_max_age_secs: default_dead_letter_max_age_secs(),
+            max_dead_letters_per_subscriber: default_max_dead_letters_per_subscriber(),
         }
     }
 }
@@ -110,6 +318,7 @@ impl Default for GrpcConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 50051,
+            tls: None,
         }
     }
 }
@@ -121,6 +330,8 @@ impl Default for LogConfig {
             format: LogFormat::Plain,
             output: LogOutput::Stdout,
             file_path: None,
+            redact_fields: default_redact_fields(),
+            request_sample_rate: default_request_sample_rate(),
         }
     }
 }
@@ -139,6 +350,38 @@ pub fn load_config(path: &str) -> Result<GatewayConfig> {
         .context(format!("Failed to build configuration from '{}'", path))?
         .try_deserialize()
         .context("Failed to deserialize configuration")?;
+    settings.validate()?;
 
     Ok(settings)
 }
+
+/// Runs the `init-config` subcommand and returns the report to print.
+pub fn init_config(cmd: &InitConfigCmd) -> Result<String> {
+    if !cmd.force && std::path::Path::new(&cmd.path).exists() {
+        bail!(
+            "'{}' already exists; pass --force to overwrite it",
+            cmd.path
+        );
+    }
+    std::fs::write(&cmd.path, EXAMPLE_CONFIG)
+        .with_context(|| format!("Failed to write '{}'", cmd.path))?;
+    Ok(format!(
+        "Wrote the default configuration to '{}'. Adjust it, then run `check-config -c {}`.",
+        cmd.path, cmd.path
+    ))
+}
+
+/// Runs the `check-config` subcommand and returns the report to print: the
+/// effective configuration as TOML, with secrets redacted.
+pub fn check_config(cmd: &CheckConfigCmd) -> Result<String> {
+    let mut config = load_config(&cmd.config)?;
+    if let Some(auth) = &mut config.gateway.auth {
+        auth.jwt_secret = REDACTED.to_string();
+    }
+    let effective =
+        toml::to_string_pretty(&config).context("Failed to render the configuration")?;
+    Ok(format!(
+        "'{}' is valid. Effective configuration:\n\n{}",
+        cmd.config, effective
+    ))
+}
@@ -0,0 +1,100 @@
+//! Developer commands for testing the bridge by hand against a local or devnet
+//! cluster, without writing Rust. Each command returns the report to print.
+
+use crate::cli::{AirdropCmd, BalanceCmd, ProfileCmd, ProfileKind};
+use crate::config::GatewayConfig;
+use anyhow::{Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, native_token::Sol, pubkey::Pubkey};
+use std::str::FromStr;
+use std::sync::Arc;
+use w3b2_connector::{
+    instructions::{admin_profile_pda, user_profile_pda},
+    reader::AccountReader,
+};
+
+/// Creates a client for the configured RPC endpoint and commitment.
+pub fn rpc_client(config: &GatewayConfig) -> Arc<RpcClient> {
+    Arc::new(RpcClient::new_with_commitment(
+        config.connector.solana.rpc_url.clone(),
+        CommitmentConfig {
+            commitment: config.connector.solana.commitment,
+        },
+    ))
+}
+
+/// Requests an airdrop and waits for it to land.
+pub async fn airdrop(rpc_client: &RpcClient, cmd: &AirdropCmd) -> Result<String> {
+    let pubkey = parse_pubkey(&cmd.pubkey)?;
+    let signature = rpc_client
+        .request_airdrop(&pubkey, cmd.lamports)
+        .await
+        .context("Airdrop request failed (airdrops only work on localnet and devnet)")?;
+    rpc_client.poll_for_signature(&signature).await?;
+    let balance = rpc_client.get_balance(&pubkey).await?;
+
+    Ok(format!(
+        "Airdropped {} to {}\nSignature: {}\nBalance: {}",
+        Sol(cmd.lamports),
+        pubkey,
+        signature,
+        Sol(balance)
+    ))
+}
+
+/// Reports the lamport balance of a ChainCard and, if an admin is given, the
+/// spendable deposit of its `UserProfile` with that admin's service.
+pub async fn balance(rpc_client: Arc<RpcClient>, cmd: &BalanceCmd) -> Result<String> {
+    let pubkey = parse_pubkey(&cmd.pubkey)?;
+    let lamports = rpc_client.get_balance(&pubkey).await?;
+    let mut report = format!(
+        "Balance of {}: {} ({} lamports)",
+        pubkey,
+        Sol(lamports),
+        lamports
+    );
+
+    if let Some(admin) = &cmd.admin {
+        let admin_pda = admin_profile_pda(&parse_pubkey(admin)?);
+        let deposit = AccountReader::new(rpc_client)
+            .get_deposit_balance(&pubkey, &admin_pda)
+            .await?;
+        report.push_str(&format!(
+            "\nDeposit with service {}: {} ({} lamports)",
+            admin_pda,
+            Sol(deposit),
+            deposit
+        ));
+    }
+    Ok(report)
+}
+
+/// Fetches and pretty-prints an `AdminProfile` or `UserProfile`.
+pub async fn profile(rpc_client: Arc<RpcClient>, cmd: &ProfileCmd) -> Result<String> {
+    let reader = AccountReader::new(rpc_client);
+    match &cmd.kind {
+        ProfileKind::Admin { authority } => {
+            let authority = parse_pubkey(authority)?;
+            let admin_pda = admin_profile_pda(&authority);
+            Ok(match reader.fetch_admin_profile_at(&admin_pda).await? {
+                Some(profile) => format!("AdminProfile {}\n{:#?}", admin_pda, profile),
+                None => format!("No AdminProfile registered by {}", authority),
+            })
+        }
+        ProfileKind::User { authority, admin } => {
+            let authority = parse_pubkey(authority)?;
+            let admin_pda = admin_profile_pda(&parse_pubkey(admin)?);
+            let user_pda = user_profile_pda(&authority, &admin_pda);
+            Ok(
+                match reader.fetch_user_profile(&authority, &admin_pda).await? {
+                    Some(profile) => format!("UserProfile {}\n{:#?}", user_pda, profile),
+                    None => format!("No UserProfile of {} with service {}", authority, admin_pda),
+                },
+            )
+        }
+    }
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey> {
+    Pubkey::from_str(pubkey).with_context(|| format!("Invalid public key '{}'", pubkey))
+}
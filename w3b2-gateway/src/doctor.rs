@@ -0,0 +1,200 @@
+//! The `doctor` command: checks the environment the gateway would run in and
+//! explains how to fix what is wrong, before `run` fails on it.
+//!
+//! For every cluster it checks that each RPC and WebSocket endpoint answers,
+//! that the bridge program is deployed and that the sync database is writable.
+//! It then checks that the gRPC, metrics and health check ports are free.
+
+use crate::config::GatewayConfig;
+use solana_client::nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient};
+use std::fmt;
+use std::net::TcpListener;
+use std::time::Duration;
+use w3b2_connector::PROGRAM_ID;
+
+/// How long each network check may take.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The outcome of one check.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    /// What was found or, for a failed check, what is wrong and how to fix it.
+    pub detail: String,
+}
+
+/// The outcomes of every check `doctor` ran.
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    fn record(&mut self, name: impl Into<String>, outcome: Result<String, String>) {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.checks.push(Check {
+            name: name.into(),
+            passed,
+            detail,
+        });
+    }
+
+    /// Returns the number of failed checks.
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = if check.passed { " ok " } else { "FAIL" };
+            writeln!(f, "[{}] {}: {}", status, check.name, check.detail)?;
+        }
+        match self.failures() {
+            0 => write!(f, "All {} checks passed.", self.checks.len()),
+            failed => write!(f, "{} of {} checks failed.", failed, self.checks.len()),
+        }
+    }
+}
+
+/// Runs every check against `config`.
+pub async fn doctor(config: &GatewayConfig) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    for (name, connector) in config.all_clusters() {
+        let solana = &connector.solana;
+        for url in solana.rpc_urls() {
+            report.record(
+                format!("cluster '{}': RPC {}", name, url),
+                check_rpc(&url).await,
+            );
+        }
+        for url in solana.ws_urls() {
+            report.record(
+                format!("cluster '{}': WebSocket {}", name, url),
+                check_ws(&url).await,
+            );
+        }
+        report.record(
+            format!("cluster '{}': program {}", name, PROGRAM_ID),
+            check_program(&solana.rpc_url).await,
+        );
+        let db_path = config.cluster_db_path(name);
+        report.record(
+            format!("cluster '{}': database {}", name, db_path),
+            check_database(&db_path),
+        );
+    }
+
+    let grpc = &config.gateway.grpc;
+    report.record(
+        "gRPC port",
+        check_port(&format!("{}:{}", grpc.host, grpc.port)),
+    );
+    if let Some(metrics) = &config.gateway.metrics {
+        report.record(
+            "metrics port",
+            check_port(&format!("{}:{}", metrics.host, metrics.port)),
+        );
+    }
+    if let Some(health) = &config.gateway.health {
+        report.record(
+            "health check port",
+            check_port(&format!("{}:{}", health.host, health.port)),
+        );
+    }
+    report
+}
+
+async fn check_rpc(url: &str) -> Result<String, String> {
+    let rpc_client = RpcClient::new_with_timeout(url.to_string(), CHECK_TIMEOUT);
+    let version = rpc_client.get_version().await.map_err(|e| {
+        format!(
+            "no answer ({}). Check the URL and that the node is running, e.g. \
+             `solana-test-validator` for localnet.",
+            e
+        )
+    })?;
+    match rpc_client.get_health().await {
+        Ok(()) => Ok(format!("solana-core {}", version.solana_core)),
+        Err(e) => Err(format!(
+            "solana-core {} answers but is unhealthy ({}). It is probably still \
+             catching up; wait or use another node.",
+            version.solana_core, e
+        )),
+    }
+}
+
+async fn check_ws(url: &str) -> Result<String, String> {
+    match tokio::time::timeout(CHECK_TIMEOUT, PubsubClient::new(url)).await {
+        Ok(Ok(client)) => {
+            client.shutdown().await.ok();
+            Ok("connected".to_string())
+        }
+        Ok(Err(e)) => Err(format!(
+            "cannot connect ({}). Check the URL; RPC nodes usually serve WebSockets \
+             on the RPC port + 1.",
+            e
+        )),
+        Err(_) => Err(format!(
+            "timed out after {}s. Check that a firewall or proxy lets WebSockets through.",
+            CHECK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+async fn check_program(rpc_url: &str) -> Result<String, String> {
+    let rpc_client = RpcClient::new_with_timeout(rpc_url.to_string(), CHECK_TIMEOUT);
+    let account = rpc_client
+        .get_account_with_commitment(&PROGRAM_ID, rpc_client.commitment())
+        .await
+        .map_err(|e| format!("cannot look it up ({}). Fix the RPC check first.", e))?
+        .value
+        .ok_or_else(|| {
+            "not deployed on this cluster. Deploy it with `anchor deploy`, or point \
+             rpc-url at the cluster it is deployed on."
+                .to_string()
+        })?;
+    if account.executable {
+        Ok("deployed".to_string())
+    } else {
+        Err(
+            "the account exists but is not an executable program. The program id \
+             does not match the deployed program; rebuild with the right keypair."
+                .to_string(),
+        )
+    }
+}
+
+fn check_database(db_path: &str) -> Result<String, String> {
+    let probe = || -> sled::Result<()> {
+        let db = sled::open(db_path)?;
+        db.insert("doctor-probe", "ok")?;
+        db.remove("doctor-probe")?;
+        db.flush()?;
+        Ok(())
+    };
+    probe().map(|()| "writable".to_string()).map_err(|e| {
+        format!(
+            "not writable ({}). Check the directory's permissions and that no running \
+             gateway holds the database.",
+            e
+        )
+    })
+}
+
+fn check_port(addr: &str) -> Result<String, String> {
+    match TcpListener::bind(addr) {
+        Ok(_) => Ok(format!("{} is free", addr)),
+        Err(e) => Err(format!(
+            "cannot listen on {} ({}). Another process, maybe a running gateway, uses \
+             it; stop it or change the port.",
+            addr, e
+        )),
+    }
+}
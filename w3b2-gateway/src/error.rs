@@ -2,6 +2,7 @@ use solana_client::client_error::ClientError;
 use solana_sdk::pubkey::ParsePubkeyError;
 use thiserror::Error;
 use tonic::Status;
+use w3b2_connector::storage::StorageError;
 
 /// Defines the primary error types for the gRPC gateway.
 #[derive(Error, Debug)]
@@ -9,8 +10,23 @@ pub enum GatewayError {
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Failed precondition: {0}")]
+    FailedPrecondition(String),
+
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Internal connector error: {0}")]
-    Connector(#[from] ClientError),
+    Connector(Box<ClientError>),
+
+    #[error("Event storage error: {0}")]
+    Storage(#[from] StorageError),
 
     #[error("Serialization failed: {0}")]
     Serialization(#[from] bincode::error::EncodeError),
@@ -25,9 +41,14 @@ impl From<GatewayError> for Status {
     fn from(err: GatewayError) -> Self {
         match err {
             GatewayError::InvalidArgument(reason) => Status::invalid_argument(reason),
+            GatewayError::NotFound(what) => Status::not_found(what),
+            GatewayError::PermissionDenied(reason) => Status::permission_denied(reason),
+            GatewayError::FailedPrecondition(reason) => Status::failed_precondition(reason),
+            GatewayError::Unavailable(reason) => Status::unavailable(reason),
             GatewayError::Connector(e) => {
                 Status::internal(format!("Blockchain client error: {}", e))
             }
+            GatewayError::Storage(e) => Status::internal(format!("Event storage error: {}", e)),
             GatewayError::Serialization(e) => {
                 Status::internal(format!("Data serialization error: {}", e))
             }
@@ -44,3 +65,10 @@ impl From<ParsePubkeyError> for GatewayError {
         GatewayError::InvalidArgument(format!("Invalid public key format: {}", err))
     }
 }
+
+/// Boxes the (large) RPC client error so `GatewayError` stays cheap to return.
+impl From<ClientError> for GatewayError {
+    fn from(err: ClientError) -> Self {
+        GatewayError::Connector(Box::new(err))
+    }
+}
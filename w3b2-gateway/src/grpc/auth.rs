@@ -0,0 +1,140 @@
+//! JWT bearer authentication for the gRPC service.
+//!
+//! When `[gateway.auth]` is configured, every call must carry an
+//! `authorization: Bearer <token>` header with an HS256-signed JWT. The token's
+//! `pubkeys` claim lists the authority pubkeys the caller may prepare
+//! transactions for or listen to; `"*"` grants every pubkey. The interceptor
+//! only verifies the token, the handlers check the pubkey they act on against
+//! the resulting `AuthScope`.
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use tonic::{Request, Status, service::Interceptor};
+
+use crate::config::AuthConfig;
+use crate::error::GatewayError;
+
+/// The `pubkeys` entry granting access to every pubkey.
+pub const ANY_PUBKEY: &str = "*";
+
+/// The claims the gateway reads from a token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Who the token was issued to. Only used for logging.
+    pub sub: String,
+    /// Expiry as a Unix timestamp.
+    pub exp: u64,
+    /// The authority pubkeys the caller may act for.
+    #[serde(default)]
+    pub pubkeys: Vec<String>,
+}
+
+/// What an authenticated caller may act for.
+#[derive(Debug, Clone)]
+pub enum AuthScope {
+    /// Authentication is disabled or the token grants every pubkey.
+    Unrestricted,
+    /// The caller may only act for the listed pubkeys.
+    Pubkeys {
+        subject: String,
+        pubkeys: HashSet<Pubkey>,
+    },
+}
+
+impl AuthScope {
+    /// Returns the scope the interceptor attached to `request`.
+    pub fn of<T>(request: &Request<T>) -> Self {
+        request
+            .extensions()
+            .get::<AuthScope>()
+            .cloned()
+            .unwrap_or(AuthScope::Unrestricted)
+    }
+
+    /// Returns whether the caller may act for `pubkey`.
+    pub fn permits(&self, pubkey: &Pubkey) -> bool {
+        match self {
+            AuthScope::Unrestricted => true,
+            AuthScope::Pubkeys { pubkeys, .. } => pubkeys.contains(pubkey),
+        }
+    }
+
+    /// Fails with `PermissionDenied` unless the caller may act for `pubkey`.
+    pub fn check(&self, pubkey: &Pubkey) -> Result<(), GatewayError> {
+        match self {
+            AuthScope::Pubkeys { subject, .. } if !self.permits(pubkey) => Err(
+                GatewayError::PermissionDenied(format!("{} may not act for {}", subject, pubkey)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the scope granted by `claims`, or `None` if they list an invalid pubkey.
+    fn from_claims(claims: Claims) -> Option<Self> {
+        if claims.pubkeys.iter().any(|key| key == ANY_PUBKEY) {
+            return Some(AuthScope::Unrestricted);
+        }
+        let pubkeys = claims
+            .pubkeys
+            .iter()
+            .map(|key| Pubkey::from_str(key).ok())
+            .collect::<Option<HashSet<_>>>()?;
+        Some(AuthScope::Pubkeys {
+            subject: claims.sub,
+            pubkeys,
+        })
+    }
+}
+
+/// A tonic interceptor verifying bearer tokens and attaching their `AuthScope`.
+#[derive(Clone)]
+pub struct Authenticator {
+    verifier: Option<Arc<(DecodingKey, Validation)>>,
+}
+
+impl Authenticator {
+    /// Creates an authenticator for `config`, or one letting every call through
+    /// if authentication is not configured.
+    pub fn new(config: Option<&AuthConfig>) -> Self {
+        let verifier = config.map(|config| {
+            let mut validation = Validation::new(Algorithm::HS256);
+            if let Some(issuer) = &config.issuer {
+                validation.set_issuer(&[issuer]);
+            }
+            if let Some(audience) = &config.audience {
+                validation.set_audience(&[audience]);
+            }
+            let key = DecodingKey::from_secret(config.jwt_secret.as_bytes());
+            Arc::new((key, validation))
+        });
+        Self { verifier }
+    }
+}
+
+impl Interceptor for Authenticator {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(verifier) = &self.verifier else {
+            return Ok(request);
+        };
+        let (key, validation) = verifier.as_ref();
+
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let claims = decode::<Claims>(token, key, validation)
+            .map_err(|e| Status::unauthenticated(format!("invalid token: {}", e)))?
+            .claims;
+
+        let scope = AuthScope::from_claims(claims)
+            .ok_or_else(|| Status::unauthenticated("token lists an invalid pubkey"))?;
+        request.extensions_mut().insert(scope);
+        Ok(request)
+    }
+}
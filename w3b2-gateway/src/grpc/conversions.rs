@@ -1,5 +1,10 @@
 use crate::grpc::proto::w3b2::bridge::gateway;
+use crate::storage::{DeadLetter, DeadLetterReason};
+use solana_sdk::pubkey::Pubkey;
 use w3b2_connector::events as ConnectorEvents;
+use w3b2_connector::reader::{UserProfileEntry, UserProfilePage};
+use w3b2_connector::tracker::{TxStatus, TxStatusUpdate};
+use w3b2_connector::Accounts::{AdminProfile, PriceEntry};
 
 impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
     fn from(event: ConnectorEvents::BridgeEvent) -> Self {
@@ -34,6 +39,22 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                     ts: e.ts,
                 }),
             ),
+            ConnectorEvents::BridgeEvent::AdminGcPolicyUpdated(e) => Some(
+                gateway::bridge_event::Event::AdminGcPolicyUpdated(gateway::AdminGcPolicyUpdated {
+                    authority: e.authority.to_string(),
+                    inactivity_epochs: e.inactivity_epochs,
+                    ts: e.ts,
+                }),
+            ),
+            ConnectorEvents::BridgeEvent::AdminPrioritySurchargeUpdated(e) => {
+                Some(gateway::bridge_event::Event::AdminPrioritySurchargeUpdated(
+                    gateway::AdminPrioritySurchargeUpdated {
+                        authority: e.authority.to_string(),
+                        surcharge: e.surcharge,
+                        ts: e.ts,
+                    },
+                ))
+            }
             ConnectorEvents::BridgeEvent::AdminFundsWithdrawn(e) => Some(
                 gateway::bridge_event::Event::AdminFundsWithdrawn(gateway::AdminFundsWithdrawn {
                     authority: e.authority.to_string(),
@@ -48,6 +69,15 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                     ts: e.ts,
                 }),
             ),
+            ConnectorEvents::BridgeEvent::AdminProfileMigrated(e) => Some(
+                gateway::bridge_event::Event::AdminProfileMigrated(gateway::AdminProfileMigrated {
+                    authority: e.authority.to_string(),
+                    old_profile: e.old_profile.to_string(),
+                    new_profile: e.new_profile.to_string(),
+                    profile_index: e.profile_index as u32,
+                    ts: e.ts,
+                }),
+            ),
             ConnectorEvents::BridgeEvent::AdminCommandDispatched(e) => {
                 Some(gateway::bridge_event::Event::AdminCommandDispatched(
                     gateway::AdminCommandDispatched {
@@ -56,6 +86,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                         command_id: e.command_id as u32,
                         payload: e.payload,
                         ts: e.ts,
+                        rebate: e.rebate,
                     },
                 ))
             }
@@ -79,6 +110,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                     authority: e.authority.to_string(),
                     amount: e.amount,
                     new_deposit_balance: e.new_deposit_balance,
+                    rent_reserve: e.rent_reserve,
                     ts: e.ts,
                 }),
             ),
@@ -88,6 +120,7 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                     amount: e.amount,
                     destination: e.destination.to_string(),
                     new_deposit_balance: e.new_deposit_balance,
+                    rent_reserve: e.rent_reserve,
                     ts: e.ts,
                 }),
             ),
@@ -106,6 +139,8 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                         price_paid: e.price_paid,
                         payload: e.payload,
                         ts: e.ts,
+                        high_priority: e.high_priority,
+                        priority_fee: e.priority_fee,
                     },
                 ))
             }
@@ -117,9 +152,153 @@ impl From<ConnectorEvents::BridgeEvent> for gateway::BridgeEvent {
                     ts: e.ts,
                 }),
             ),
-            ConnectorEvents::BridgeEvent::Unknown => None,
+            ConnectorEvents::BridgeEvent::Unknown { .. } => None,
         };
 
         Self { event: event_oneof }
     }
 }
+
+impl From<PriceEntry> for gateway::PriceEntry {
+    fn from(entry: PriceEntry) -> Self {
+        Self {
+            command_id: entry.command_id as u32,
+            price: entry.price,
+        }
+    }
+}
+
+/// Converts an `AdminProfile` together with its PDA.
+impl From<(Pubkey, AdminProfile)> for gateway::AdminProfileResponse {
+    fn from((admin_pda, profile): (Pubkey, AdminProfile)) -> Self {
+        Self {
+            admin_profile_pda: admin_pda.to_string(),
+            authority_pubkey: profile.authority.to_string(),
+            communication_pubkey: profile.communication_pubkey.to_string(),
+            balance: profile.balance,
+            prices: profile.prices.into_iter().map(Into::into).collect(),
+            priority_surcharge: profile.priority_surcharge,
+            gc_inactivity_epochs: profile.gc_inactivity_epochs,
+        }
+    }
+}
+
+impl From<UserProfileEntry> for gateway::UserProfileResponse {
+    fn from(entry: UserProfileEntry) -> Self {
+        let profile = entry.profile;
+        Self {
+            user_profile_pda: entry.user_pda.to_string(),
+            authority_pubkey: profile.authority.to_string(),
+            communication_pubkey: profile.communication_pubkey.to_string(),
+            admin_profile_pda: profile.admin_authority_on_creation.to_string(),
+            deposit_balance: profile.deposit_balance,
+            rent_reserve: profile.rent_reserve,
+            last_active_epoch: profile.last_active_epoch,
+        }
+    }
+}
+
+impl From<UserProfileEntry> for gateway::DepositBalance {
+    fn from(entry: UserProfileEntry) -> Self {
+        Self {
+            admin_profile_pda: entry.profile.admin_authority_on_creation.to_string(),
+            user_profile_pda: entry.user_pda.to_string(),
+            deposit_balance: entry.profile.deposit_balance,
+            rent_reserve: entry.profile.rent_reserve,
+        }
+    }
+}
+
+impl From<UserProfilePage> for gateway::ListUserProfilesForAdminResponse {
+    fn from(page: UserProfilePage) -> Self {
+        Self {
+            profiles: page.profiles.into_iter().map(Into::into).collect(),
+            total: page.total as u64,
+            next_offset: page.next_offset.unwrap_or_default() as u64,
+            has_more: page.next_offset.is_some(),
+        }
+    }
+}
+
+impl From<&ConnectorEvents::EventEnvelope> for gateway::EventMetadata {
+    fn from(envelope: &ConnectorEvents::EventEnvelope) -> Self {
+        Self {
+            slot: envelope.slot,
+            signature: envelope.signature.clone(),
+            index: envelope.index,
+            block_time: envelope.block_time.unwrap_or_default(),
+            tx_index: envelope.tx_index,
+        }
+    }
+}
+
+impl From<TxStatusUpdate> for gateway::TransactionStatusUpdate {
+    fn from(update: TxStatusUpdate) -> Self {
+        use gateway::transaction_status_update::Status;
+
+        let slot = update.status.slot().unwrap_or_default();
+        let (status, error, bridge_error) = match update.status {
+            TxStatus::Pending => (Status::Pending, None, None),
+            TxStatus::Processed { .. } => (Status::Processed, None, None),
+            TxStatus::Confirmed { .. } => (Status::Confirmed, None, None),
+            TxStatus::Finalized { .. } => (Status::Finalized, None, None),
+            TxStatus::Failed {
+                error,
+                bridge_error,
+                ..
+            } => (Status::Failed, Some(error), bridge_error),
+            TxStatus::Expired => (Status::Expired, None, None),
+        };
+        Self {
+            signature: update.signature.to_string(),
+            status: status.into(),
+            slot,
+            error: error.map(|e| e.to_string()).unwrap_or_default(),
+            bridge_error: bridge_error.map(|e| e.to_string()).unwrap_or_default(),
+            is_final: update.is_final,
+        }
+    }
+}
+
+impl From<DeadLetter> for gateway::DeadLetter {
+    fn from(letter: DeadLetter) -> Self {
+        use gateway::dead_letter::Reason;
+
+        let reason = match letter.reason {
+            DeadLetterReason::SlowConsumer => Reason::SlowConsumer,
+            DeadLetterReason::Disconnected => Reason::Disconnected,
+        };
+        Self {
+            metadata: Some((&letter.envelope).into()),
+            event: Some(letter.envelope.event.into()),
+            reason: reason.into(),
+            recorded_at: letter.recorded_at,
+        }
+    }
+}
+
+impl From<gateway::event_filter::EventType> for ConnectorEvents::EventKind {
+    fn from(event_type: gateway::event_filter::EventType) -> Self {
+        use ConnectorEvents::EventKind;
+        use gateway::event_filter::EventType;
+
+        match event_type {
+            EventType::AdminProfileRegistered => EventKind::AdminProfileRegistered,
+            EventType::AdminCommKeyUpdated => EventKind::AdminCommKeyUpdated,
+            EventType::AdminPricesUpdated => EventKind::AdminPricesUpdated,
+            EventType::AdminFundsWithdrawn => EventKind::AdminFundsWithdrawn,
+            EventType::AdminProfileClosed => EventKind::AdminProfileClosed,
+            EventType::AdminCommandDispatched => EventKind::AdminCommandDispatched,
+            EventType::UserProfileCreated => EventKind::UserProfileCreated,
+            EventType::UserCommKeyUpdated => EventKind::UserCommKeyUpdated,
+            EventType::UserFundsDeposited => EventKind::UserFundsDeposited,
+            EventType::UserFundsWithdrawn => EventKind::UserFundsWithdrawn,
+            EventType::UserProfileClosed => EventKind::UserProfileClosed,
+            EventType::UserCommandDispatched => EventKind::UserCommandDispatched,
+            EventType::OffChainActionLogged => EventKind::OffChainActionLogged,
+            EventType::AdminGcPolicyUpdated => EventKind::AdminGcPolicyUpdated,
+            EventType::AdminPrioritySurchargeUpdated => EventKind::AdminPrioritySurchargeUpdated,
+            EventType::AdminProfileMigrated => EventKind::AdminProfileMigrated,
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! # Event Delivery
+//!
+//! The event streams send their messages through a `delivery::channel`, which
+//! makes sure an event that does not reach the client is recorded as a
+//! `DeadLetter` instead of being dropped:
+//!
+//! - a client that leaves its stream full for the slow-consumer timeout misses
+//!   the event on the stream, which then moves on to the next one;
+//! - while the stream waits on a slow client, further events queue up behind it;
+//!   once that queue is full too, new events are recorded right away;
+//! - when a client disconnects, the event being sent and every event still
+//!   queued for it are recorded.
+//!
+//! Waiting on the client happens in a task of its own, so the task routing events
+//! to the stream never blocks and never falls behind its listener.
+//!
+//! Clients read and acknowledge their dead letters with `GetDeadLetters` and
+//! `AckDeadLetters`. `prune_dead_letters` deletes the ones no client comes back
+//! for.
+
+use crate::storage::{DeadLetter, DeadLetterReason, SledStorage};
+use solana_sdk::pubkey::Pubkey;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{
+    self,
+    error::{SendTimeoutError, TrySendError},
+};
+use tokio_stream::Stream;
+use tonic::Status;
+use w3b2_connector::events::EventEnvelope;
+
+/// Where the dead letters of one subscriber go.
+pub struct DeadLetters {
+    storage: Arc<SledStorage>,
+    subscriber: Pubkey,
+}
+
+impl DeadLetters {
+    pub fn new(storage: Arc<SledStorage>, subscriber: Pubkey) -> Self {
+        Self {
+            storage,
+            subscriber,
+        }
+    }
+
+    /// Records `envelopes` in one batch. The write runs on a blocking thread, so
+    /// recording never stalls the task it is called from.
+    fn record(&self, envelopes: Vec<EventEnvelope>, reason: DeadLetterReason) {
+        if envelopes.is_empty() {
+            return;
+        }
+        let recorded_at = unix_now();
+        let letters = envelopes
+            .into_iter()
+            .map(|envelope| DeadLetter {
+                envelope,
+                reason,
+                recorded_at,
+            })
+            .collect::<Vec<_>>();
+        let storage = self.storage.clone();
+        let subscriber = self.subscriber;
+        let write = move || match storage.record_dead_letters(&subscriber, &letters) {
+            Ok(()) => {
+                for letter in &letters {
+                    tracing::warn!(
+                        "Event {}#{} was not delivered to {} ({:?}); recorded as a dead letter",
+                        letter.envelope.signature,
+                        letter.envelope.index,
+                        subscriber,
+                        reason
+                    );
+                }
+            }
+            Err(e) => tracing::error!(
+                "Failed to record {} undelivered events for {}: {}",
+                letters.len(),
+                subscriber,
+                e
+            ),
+        };
+        // A stream may be dropped outside the runtime, e.g. while it shuts down.
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(write)),
+            Err(_) => write(),
+        }
+    }
+
+    fn record_undelivered<T>(
+        &self,
+        deliveries: impl IntoIterator<Item = Delivery<T>>,
+        reason: DeadLetterReason,
+    ) {
+        let envelopes = deliveries
+            .into_iter()
+            .filter_map(|delivery| match delivery {
+                Delivery::Event(envelope, _) => Some(envelope),
+                Delivery::Error(_) => None,
+            })
+            .collect();
+        self.record(envelopes, reason);
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Every `interval`, deletes the dead letters older than `max_age` and the
+/// oldest ones of subscribers holding more than `max_per_subscriber`.
+pub async fn prune_dead_letters(
+    storage: Arc<SledStorage>,
+    interval: Duration,
+    max_age: Duration,
+    max_per_subscriber: usize,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let storage = storage.clone();
+        let recorded_before = unix_now().saturating_sub(max_age.as_secs() as i64);
+        let pruned = tokio::task::spawn_blocking(move || {
+            storage.prune_dead_letters(recorded_before, max_per_subscriber)
+        })
+        .await;
+        match pruned {
+            Ok(Ok(0)) => {}
+            Ok(Ok(removed)) => tracing::info!("Pruned {} dead letters.", removed),
+            Ok(Err(e)) => tracing::error!("Failed to prune dead letters: {}", e),
+            Err(e) => tracing::error!("Dead letter pruning panicked: {}", e),
+        }
+    }
+}
+
+enum Delivery<T> {
+    Event(EventEnvelope, T),
+    Error(Status),
+}
+
+/// Creates a stream for a client and the sender feeding it. The stream buffers
+/// up to `capacity` messages, and as many more wait for room in it, each for at
+/// most `timeout`.
+pub fn channel<T: Send + 'static>(
+    capacity: usize,
+    timeout: Duration,
+    dead_letters: DeadLetters,
+) -> (DeliverySender<T>, DeliveryStream<T>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let (pending_tx, pending_rx) = mpsc::channel(capacity);
+    let dead_letters = Arc::new(dead_letters);
+    tokio::spawn(pump(pending_rx, tx, timeout, dead_letters.clone()));
+    (
+        DeliverySender {
+            tx: pending_tx,
+            dead_letters: dead_letters.clone(),
+        },
+        DeliveryStream { rx, dead_letters },
+    )
+}
+
+/// Moves the pending messages into the client's stream, waiting at most
+/// `timeout` for room for each event, until the client disconnects or the
+/// sender is dropped.
+async fn pump<T>(
+    mut pending: mpsc::Receiver<Delivery<T>>,
+    tx: mpsc::Sender<Delivery<T>>,
+    timeout: Duration,
+    dead_letters: Arc<DeadLetters>,
+) {
+    let mut undelivered = Vec::new();
+    loop {
+        let delivery = tokio::select! {
+            delivery = pending.recv() => match delivery {
+                Some(delivery) => delivery,
+                None => return,
+            },
+            _ = tx.closed() => break,
+        };
+        let sent = match delivery {
+            Delivery::Event(..) => tx.send_timeout(delivery, timeout).await,
+            Delivery::Error(_) => tx
+                .send(delivery)
+                .await
+                .map_err(|e| SendTimeoutError::Closed(e.0)),
+        };
+        match sent {
+            Ok(()) => {}
+            Err(SendTimeoutError::Timeout(delivery)) => {
+                dead_letters.record_undelivered([delivery], DeadLetterReason::SlowConsumer);
+            }
+            Err(SendTimeoutError::Closed(delivery)) => {
+                undelivered.push(delivery);
+                break;
+            }
+        }
+    }
+    // The client disconnected: keep what was still waiting for it.
+    pending.close();
+    while let Ok(delivery) = pending.try_recv() {
+        undelivered.push(delivery);
+    }
+    dead_letters.record_undelivered(undelivered, DeadLetterReason::Disconnected);
+}
+
+/// Sends messages to a `DeliveryStream`, see `channel`.
+pub struct DeliverySender<T> {
+    tx: mpsc::Sender<Delivery<T>>,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl<T> DeliverySender<T> {
+    /// Queues `message`, built from `envelope`, for the client without waiting.
+    /// If too many messages are waiting for a slow client already, or the client
+    /// does not make room for it within the timeout, the event is recorded as a
+    /// dead letter instead.
+    ///
+    /// Returns `false` once the client has disconnected; the event is then
+    /// recorded too.
+    pub fn send(&self, envelope: EventEnvelope, message: T) -> bool {
+        match self.tx.try_send(Delivery::Event(envelope, message)) {
+            Ok(()) => true,
+            Err(TrySendError::Full(delivery)) => {
+                self.dead_letters
+                    .record_undelivered([delivery], DeadLetterReason::SlowConsumer);
+                true
+            }
+            Err(TrySendError::Closed(delivery)) => {
+                self.dead_letters
+                    .record_undelivered([delivery], DeadLetterReason::Disconnected);
+                false
+            }
+        }
+    }
+
+    /// Like `send`, but waits for room among the queued messages instead of
+    /// recording the event, e.g. to replay stored events to a client before its
+    /// live ones.
+    pub async fn send_waiting(&self, envelope: EventEnvelope, message: T) -> bool {
+        match self.tx.send(Delivery::Event(envelope, message)).await {
+            Ok(()) => true,
+            Err(e) => {
+                self.dead_letters
+                    .record_undelivered([e.0], DeadLetterReason::Disconnected);
+                false
+            }
+        }
+    }
+
+    /// Records an event that was still queued for the client when it disconnected.
+    pub fn undelivered(&self, envelope: EventEnvelope) {
+        self.dead_letters
+            .record(vec![envelope], DeadLetterReason::Disconnected);
+    }
+
+    /// Ends the stream with `status`.
+    pub async fn fail(&self, status: Status) {
+        let _ = self.tx.send(Delivery::Error(status)).await;
+    }
+}
+
+/// The stream returned to a client, see `channel`.
+///
+/// When the client disconnects, the events still buffered in it are recorded as
+/// dead letters.
+pub struct DeliveryStream<T> {
+    rx: mpsc::Receiver<Delivery<T>>,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl<T> Stream for DeliveryStream<T> {
+    type Item = Result<T, Status>;
+
+    // The item type is the one tonic expects of a response stream.
+    #[allow(clippy::result_large_err)]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|delivery| {
+            delivery.map(|delivery| match delivery {
+                Delivery::Event(_, message) => Ok(message),
+                Delivery::Error(status) => Err(status),
+            })
+        })
+    }
+}
+
+impl<T> Drop for DeliveryStream<T> {
+    fn drop(&mut self) {
+        self.rx.close();
+        let mut undelivered = Vec::new();
+        while let Ok(delivery) = self.rx.try_recv() {
+            undelivered.push(delivery);
+        }
+        self.dead_letters
+            .record_undelivered(undelivered, DeadLetterReason::Disconnected);
+    }
+}
+
+/// Returns the items `stream` has ready, without waiting for more.
+pub async fn ready_items<S: Stream + Unpin>(stream: &mut S) -> Vec<S::Item> {
+    std::future::poll_fn(|cx| {
+        let mut items = Vec::new();
+        while let Poll::Ready(Some(item)) = Pin::new(&mut *stream).poll_next(cx) {
+            items.push(item);
+        }
+        Poll::Ready(items)
+    })
+    .await
+}
@@ -0,0 +1,132 @@
+//! Structured logging of incoming gRPC requests.
+//!
+//! Every handler hands its request to `RequestLogger::log`, which emits one
+//! `info` record with the method and the request's `Debug` form. Fields named in
+//! `log.redact-fields` have their values replaced by `<redacted>`, so command
+//! payloads and signed transactions never reach the logs, and only a
+//! `log.request-sample-rate` share of the requests is logged at all.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::LogConfig;
+
+/// What a redacted value is replaced with.
+pub const REDACTED: &str = "<redacted>";
+
+/// Logs requests with their sensitive fields redacted, see the module docs.
+pub struct RequestLogger {
+    redact_fields: HashSet<String>,
+    sample_rate: f64,
+    seen: AtomicU64,
+}
+
+impl RequestLogger {
+    /// Creates a logger redacting `redact_fields` and logging a `sample_rate`
+    /// share (clamped to `0.0..=1.0`) of the requests.
+    pub fn new(redact_fields: impl IntoIterator<Item = String>, sample_rate: f64) -> Self {
+        Self {
+            redact_fields: redact_fields.into_iter().collect(),
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a logger from the gateway's logging configuration.
+    pub fn from_config(config: &LogConfig) -> Self {
+        Self::new(
+            config.redact_fields.iter().cloned(),
+            config.request_sample_rate,
+        )
+    }
+
+    /// Logs a request to `method`, if it is sampled.
+    pub fn log<T: Debug>(&self, method: &str, request: &T) {
+        if self.sampled() {
+            let request = self.redact(&format!("{:?}", request));
+            tracing::info!(method, %request, "Received gRPC request");
+        }
+    }
+
+    /// Returns `debug`, a `Debug`-formatted request, with the values of the
+    /// redacted fields replaced.
+    pub fn redact(&self, debug: &str) -> String {
+        let bytes = debug.as_bytes();
+        let mut out = String::with_capacity(debug.len());
+        let mut copied = 0;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if bytes[i] == b'"' {
+                i = skip_string(bytes, i);
+            } else if is_ident(bytes[i]) && (i == 0 || !is_ident(bytes[i - 1])) {
+                let start = i;
+                while i < bytes.len() && is_ident(bytes[i]) {
+                    i += 1;
+                }
+                if self.redact_fields.contains(&debug[start..i]) && debug[i..].starts_with(": ") {
+                    let value_start = i + 2;
+                    out.push_str(&debug[copied..value_start]);
+                    out.push_str(REDACTED);
+                    i = skip_value(bytes, value_start);
+                    // Keep the space before a closing bracket.
+                    copied = i;
+                    while copied > value_start && bytes[copied - 1] == b' ' {
+                        copied -= 1;
+                    }
+                }
+            } else {
+                i += 1;
+            }
+        }
+        out.push_str(&debug[copied..]);
+        out
+    }
+
+    /// Spreads the sampled requests evenly: with a rate of 0.25, every fourth
+    /// request is logged.
+    fn sampled(&self) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.sample_rate).floor() > (n * self.sample_rate).floor()
+    }
+}
+
+fn is_ident(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Returns the index just past the string literal starting at `start`.
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the index just past the field value starting at `start`, i.e. of the
+/// `,` or closing bracket that ends it.
+fn skip_value(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'[' | b'{' | b'(' => depth += 1,
+            b']' | b'}' | b')' if depth == 0 => return i,
+            b']' | b'}' | b')' => depth -= 1,
+            b',' if depth == 0 => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
@@ -1,40 +1,72 @@
+pub mod auth;
 mod conversions;
-use anyhow::Result;
+pub mod delivery;
+pub mod logging;
+use anyhow::{Context, Result};
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Request, Response, Status, transport::Server};
+use tonic::{
+    Request, Response, Status,
+    transport::{Identity, Server, ServerTlsConfig},
+};
 use w3b2_connector::{
     Accounts::PriceEntry,
-    client::TransactionBuilder,
-    listener::{self, AdminListener},
+    blockhash::BlockhashCache,
+    client::{ComputeBudget, MAX_COMPUTE_UNIT_LIMIT, TransactionBuilder, to_versioned_transaction},
+    cluster::Cluster,
+    config::ConnectorConfig,
+    dispatcher::{EventFilter, ListenerOptions},
+    events::{EventCursor, EventKind},
+    listener::{self, AdminEventKind, AdminListener, UserEventKind},
+    prices::PriceCache,
+    reader::{AccountReader, MAX_PAGE_SIZE},
+    rpc_pool::RpcPool,
+    tracker::TxTracker,
     workers::{EventManager, EventManagerHandle},
 };
-use std::collections::HashMap;
 
 use crate::grpc::proto::w3b2::bridge::gateway::bridge_gateway_service_server::{
     BridgeGatewayService, BridgeGatewayServiceServer,
 };
 use crate::{
-    config::GatewayConfig,
+    config::{DEFAULT_CLUSTER, GatewayConfig, PriorityFeeConfig, StreamingConfig, TlsConfig},
     error::GatewayError,
+    grpc::auth::{AuthScope, Authenticator},
+    grpc::delivery::{DeadLetters, DeliveryStream},
+    grpc::logging::RequestLogger,
     grpc::proto::w3b2::bridge::gateway::{
-        self, AdminEventStream,  ListenAsAdminRequest,
-        PrepareAdminCloseProfileRequest, PrepareAdminDispatchCommandRequest,
-        PrepareAdminRegisterProfileRequest, PrepareAdminUpdateCommKeyRequest,
-        PrepareAdminUpdatePricesRequest, PrepareAdminWithdrawRequest, PrepareLogActionRequest,
-        PrepareUserCloseProfileRequest, PrepareUserCreateProfileRequest, PrepareUserDepositRequest,
+        self, AckDeadLettersRequest, AckDeadLettersResponse, AdminEventStream,
+        AdminProfileResponse, AdminRef, GetAdminProfileRequest, GetBalancesRequest,
+        GetBalancesResponse, GetDeadLettersRequest, GetDeadLettersResponse, GetRecentEventsRequest,
+        GetRecentEventsResponse, ListUserProfilesForAdminRequest, ListUserProfilesForAdminResponse,
+        ListenAsAdminRequest, PrepareAdminCloseProfileRequest, PrepareAdminCloseProfileToRequest,
+        PrepareAdminDispatchCommandRequest, PrepareAdminMigrateToV2Request,
+        PrepareAdminRegisterProfileRequest, PrepareAdminSetGcPolicyRequest,
+        PrepareAdminSetPrioritySurchargeRequest, PrepareAdminUpdateCommKeyRequest,
+        PrepareAdminUpdatePricesRequest, PrepareAdminWithdrawRequest,
+        PrepareGcInactiveProfileRequest, PrepareLogActionRequest, PrepareUserCloseProfileRequest,
+        PrepareUserCreateProfileRequest, PrepareUserDepositRequest,
         PrepareUserDispatchCommandRequest, PrepareUserUpdateCommKeyRequest,
-        PrepareUserWithdrawRequest, StopListenerRequest, SubmitTransactionRequest,
-        SubscribeToService, TransactionResponse, UnsignedTransactionResponse,
-        UnsubscribeFromService, UserEventStream, UserStreamCommand,
-        admin_event_stream::EventCategory as AdminEventCategory,
+        PrepareUserWithdrawRequest, RecentEvent, RequestAirdropRequest, SimulateTransactionRequest,
+        SimulateTransactionResponse, StopListenerRequest, SubmitTransactionRequest,
+        SubscribeToService, TransactionOptions, TransactionResponse, TransactionStatusUpdate,
+        UnsignedTransactionResponse, UnsubscribeFromService, UserEventStream, UserStreamCommand,
+        admin_event_stream::EventCategory as AdminEventCategory, admin_ref,
         user_event_stream::EventCategory as UserEventCategory, user_stream_command,
     },
+    health::{self, ClusterProbe},
+    metrics::{self, GatewayMetrics, MetricsLayer, StreamKind},
     storage::SledStorage,
 };
 
@@ -48,12 +80,55 @@ pub mod proto {
     }
 }
 
-
+/// The request metadata key naming the cluster a call is served by. Calls
+/// without it are served by `DEFAULT_CLUSTER`.
+pub const CLUSTER_METADATA_KEY: &str = "x-w3b2-cluster";
+
+/// The number of events `GetRecentEvents` returns when the request sets no limit.
+pub const DEFAULT_RECENT_EVENTS: u32 = 100;
+/// The most events one `GetRecentEvents` call returns, and the most a stream
+/// replays before its live events.
+pub const MAX_RECENT_EVENTS: u32 = 1000;
+/// The number of dead letters `GetDeadLetters` returns when the request sets no limit.
+pub const DEFAULT_DEAD_LETTERS: u32 = 100;
+/// The most dead letters one `GetDeadLetters` call returns.
+pub const MAX_DEAD_LETTERS: u32 = 1000;
+
+/// The RPC client, caches and event manager of one configured cluster.
 #[derive(Clone)]
-pub struct AppState {
+pub struct ClusterState {
+    pub rpc_pool: RpcPool,
     pub rpc_client: Arc<RpcClient>,
+    pub blockhash_cache: Arc<BlockhashCache>,
+    /// Price lists of the admins looked up so far, kept current by the event stream.
+    pub price_cache: Arc<PriceCache>,
     pub event_manager: EventManagerHandle,
-    pub config: Arc<GatewayConfig>,
+    /// The cluster's database, also holding the dead letters of its streams.
+    pub storage: Arc<SledStorage>,
+}
+
+impl ClusterState {
+    /// Returns a `TransactionBuilder` backed by the shared blockhash and price caches.
+    fn transaction_builder(&self) -> TransactionBuilder {
+        TransactionBuilder::new(self.rpc_client.clone())
+            .with_blockhash_cache(self.blockhash_cache.clone())
+            .with_price_cache(self.price_cache.clone())
+    }
+
+    /// Returns an `AccountReader` for the query RPCs.
+    fn account_reader(&self) -> AccountReader {
+        AccountReader::new(self.rpc_client.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct AppState {
+    /// Every configured cluster by name, including `DEFAULT_CLUSTER`.
+    pub clusters: Arc<HashMap<String, ClusterState>>,
+    /// The running configuration, updated by the `ConfigReloader`.
+    pub config: watch::Receiver<Arc<GatewayConfig>>,
+    pub metrics: Arc<GatewayMetrics>,
+    pub request_log: Arc<RequestLogger>,
 }
 
 /// gRPC server implementation.
@@ -66,53 +141,223 @@ impl GatewayServer {
     pub fn new(state: AppState) -> Self {
         Self { state }
     }
-}
 
-    async fn forward_events(
-        service_rx: &mut mpsc::Receiver<listener::BridgeEvent>,
-        inner_tx: &mpsc::Sender<gateway::BridgeEvent>,
-    ) {
-        while let Some(event) = service_rx.recv().await {
-            // Convert the connector event into a gateway (proto) event before sending.
-            let proto_event: gateway::BridgeEvent = event.into();
+    /// Returns the running configuration.
+    fn config(&self) -> Arc<GatewayConfig> {
+        self.state.config.borrow().clone()
+    }
+
+    /// Returns the cluster `request` selected through its `CLUSTER_METADATA_KEY`.
+    fn cluster<T>(&self, request: &Request<T>) -> Result<&ClusterState, GatewayError> {
+        let name = match request.metadata().get(CLUSTER_METADATA_KEY) {
+            Some(value) => value.to_str().map_err(|_| {
+                GatewayError::InvalidArgument(format!(
+                    "{} is not valid ASCII",
+                    CLUSTER_METADATA_KEY
+                ))
+            })?,
+            None => DEFAULT_CLUSTER,
+        };
+        self.state
+            .clusters
+            .get(name)
+            .ok_or_else(|| GatewayError::InvalidArgument(format!("unknown cluster '{}'", name)))
+    }
 
-            if inner_tx.send(proto_event).await.is_err() {
-                break;
+    /// Returns a `TransactionBuilder` for a Prepare request, applying its `options`.
+    /// Without a priority fee in them, the configured one is paid.
+    fn prepare_builder(
+        &self,
+        cluster: &ClusterState,
+        options: Option<&TransactionOptions>,
+    ) -> Result<TransactionBuilder, GatewayError> {
+        let mut builder = cluster.transaction_builder();
+        let mut compute_budget = ComputeBudget::default();
+        match self.config().gateway.priority_fee {
+            PriorityFeeConfig::None => {}
+            PriorityFeeConfig::Fixed { micro_lamports } => {
+                compute_budget.unit_price = Some(micro_lamports);
+            }
+            PriorityFeeConfig::Auto { percentile } => {
+                builder = builder.with_auto_priority_fee(percentile);
+            }
+        }
+        if let Some(price) = options.and_then(|options| options.compute_unit_price_micro_lamports) {
+            compute_budget.unit_price = Some(price);
+        }
+        if let Some(limit) = options.and_then(|options| options.compute_unit_limit) {
+            if limit == 0 || limit > MAX_COMPUTE_UNIT_LIMIT {
+                return Err(GatewayError::InvalidArgument(format!(
+                    "compute_unit_limit must be between 1 and {}",
+                    MAX_COMPUTE_UNIT_LIMIT
+                )));
             }
+            compute_budget.unit_limit = Some(limit);
+        }
+        if let Some(memo) = options
+            .and_then(|options| options.memo.as_deref())
+            .filter(|memo| !memo.is_empty())
+        {
+            builder = builder
+                .with_memo(memo)
+                .map_err(|e| GatewayError::InvalidArgument(e.to_string()))?;
         }
+        Ok(builder.with_compute_budget(compute_budget))
     }
 
-/// The main entry point to start the gRPC server and all background services.
-pub async fn start(config: &GatewayConfig) -> Result<EventManagerHandle> {
-    // --- 1. Initialize dependencies ---
-    let db = sled::open(&config.gateway.db_path)?;
-    let storage = Arc::new(SledStorage::new(db));
-    let addr = format!("{}:{}", config.gateway.grpc.host, config.gateway.grpc.port).parse()?;
-    let rpc_client = Arc::new(RpcClient::new(config.connector.solana.rpc_url.clone()));
+    /// Serializes a prepared transaction for the client, recompiled as a v0
+    /// transaction if the request's `options` ask for one.
+    async fn unsigned_response(
+        &self,
+        cluster: &ClusterState,
+        transaction: Transaction,
+        options: Option<&TransactionOptions>,
+    ) -> Result<UnsignedTransactionResponse, GatewayError> {
+        let config = bincode::config::standard();
+        let Some(options) = options.filter(|options| options.use_v0) else {
+            return Ok(UnsignedTransactionResponse {
+                unsigned_tx: bincode::serde::encode_to_vec(&transaction, config)?,
+                versioned: false,
+            });
+        };
 
-    // --- 2. Create and spawn the EventManager service ---
+        let table_addresses = options
+            .lookup_table_addresses
+            .iter()
+            .map(|address| parse_pubkey(address))
+            .collect::<Result<Vec<_>, _>>()?;
+        let lookup_tables = cluster
+            .transaction_builder()
+            .resolve_lookup_tables(&table_addresses)
+            .await
+            .map_err(GatewayError::from)?;
+        let versioned =
+            to_versioned_transaction(&transaction, &lookup_tables).map_err(GatewayError::from)?;
+        Ok(UnsignedTransactionResponse {
+            unsigned_tx: bincode::serde::encode_to_vec(&versioned, config)?,
+            versioned: true,
+        })
+    }
+}
+
+async fn forward_events(
+    service_rx: &mut mpsc::Receiver<listener::EventEnvelope>,
+    inner_tx: &mpsc::Sender<listener::EventEnvelope>,
+) {
+    while let Some(envelope) = service_rx.recv().await {
+        if inner_tx.send(envelope).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Connects to one cluster and spawns its `EventManager`, which stores its
+/// synchronization state in the database at `db_path`.
+fn start_cluster(
+    connector: &ConnectorConfig,
+    db_path: &str,
+    streaming: &StreamingConfig,
+) -> Result<ClusterState> {
+    let db = sled::open(db_path)?;
+    let storage = Arc::new(SledStorage::new(db));
+    let rpc_pool = RpcPool::from_config(&connector.solana);
+    tokio::spawn(rpc_pool.clone().run_health_checks(Duration::from_secs(
+        connector.solana.health_check_interval_secs,
+    )));
+    let rpc_client = Arc::new(rpc_pool.rpc_client(CommitmentConfig::default()));
+    let blockhash_cache = Arc::new(BlockhashCache::new(
+        rpc_client.clone(),
+        Duration::from_millis(connector.solana.blockhash_refresh_interval_ms),
+    ));
+    let price_cache = Arc::new(PriceCache::new(AccountReader::new(rpc_client.clone())));
 
     // `EventManager::new` now returns the runner and its handle.
-    let (event_manager_runner, event_manager_handle) = EventManager::new(
-        Arc::new(config.connector.clone()),
+    let (event_manager_runner, event_manager) = EventManager::new(
+        Arc::new(connector.clone()),
         rpc_client.clone(),
-        storage,
-        config.gateway.streaming.broadcast_capacity,
-        config.gateway.streaming.command_capacity,
+        storage.clone(),
+        streaming.broadcast_capacity,
+        streaming.command_capacity,
     );
+    let event_manager_runner = event_manager_runner.with_price_cache(price_cache.clone());
 
     tokio::spawn(event_manager_runner.run());
+    tokio::spawn(delivery::prune_dead_letters(
+        storage.clone(),
+        Duration::from_secs(connector.retention.prune_interval_secs),
+        Duration::from_secs(streaming.dead_letter_max_age_secs),
+        streaming.max_dead_letters_per_subscriber,
+    ));
+
+    Ok(ClusterState {
+        rpc_pool,
+        rpc_client,
+        blockhash_cache,
+        price_cache,
+        event_manager,
+        storage,
+    })
+}
 
-    // --- 3. Set up the gRPC server state ---
+/// What `start` leaves running: the event managers to stop on shutdown and what
+/// a `ConfigReloader` needs to update the running configuration.
+pub struct GatewayHandle {
+    /// The event manager of every cluster, the default cluster's first.
+    pub event_managers: Vec<EventManagerHandle>,
+    /// Publishes a new configuration to the gRPC handlers.
+    pub config: watch::Sender<Arc<GatewayConfig>>,
+    /// The RPC pool of every cluster, by name.
+    pub rpc_pools: HashMap<String, RpcPool>,
+}
 
-    // Clone the handle for the gRPC server state. The original will be returned.
-    let handle_for_server = event_manager_handle.clone();
+impl GatewayHandle {
+    /// Stops the event manager of every cluster.
+    pub async fn stop(&self) {
+        for event_manager in &self.event_managers {
+            event_manager.stop().await;
+        }
+    }
+}
+
+/// The main entry point to start the gRPC server and all background services.
+pub async fn start(config: &GatewayConfig) -> Result<GatewayHandle> {
+    // --- 1. Connect to every cluster and spawn its EventManager ---
+    let addr = format!("{}:{}", config.gateway.grpc.host, config.gateway.grpc.port).parse()?;
+    let mut clusters = HashMap::new();
+    let mut event_manager_handles = Vec::new();
+    let mut rpc_pools = HashMap::new();
+    let mut health_probes = Vec::new();
+    for (name, connector) in config.all_clusters() {
+        let cluster = start_cluster(
+            connector,
+            &config.cluster_db_path(name),
+            &config.gateway.streaming,
+        )
+        .with_context(|| format!("Failed to start cluster '{}'", name))?;
+        tracing::info!(
+            "Serving cluster '{}' from {}",
+            name,
+            connector.solana.rpc_url
+        );
+        event_manager_handles.push(cluster.event_manager.clone());
+        rpc_pools.insert(name.to_string(), cluster.rpc_pool.clone());
+        health_probes.push(ClusterProbe {
+            name: name.to_string(),
+            event_manager: cluster.event_manager.clone(),
+            rpc_pool: cluster.rpc_pool.clone(),
+        });
+        clusters.insert(name.to_string(), cluster);
+    }
+
+    // --- 2. Set up the gRPC server state ---
+    let metrics = Arc::new(GatewayMetrics::new());
+    let (config_tx, config_rx) = watch::channel(Arc::new(config.clone()));
 
-    // Create the shared state, storing the lightweight `handle` for the RPCs to use.
     let app_state = AppState {
-        rpc_client,
-        event_manager: handle_for_server, // Store the cloned handle
-        config: Arc::new(config.clone()),
+        clusters: Arc::new(clusters),
+        config: config_rx,
+        metrics: metrics.clone(),
+        request_log: Arc::new(RequestLogger::from_config(&config.gateway.log)),
     };
 
     let gateway_server = GatewayServer::new(app_state);
@@ -122,9 +367,22 @@ pub async fn start(config: &GatewayConfig) -> Result<EventManagerHandle> {
         addr
     );
 
-    // --- 4. Start the gRPC server ---
-    let grpc_server =
-        Server::builder().add_service(BridgeGatewayServiceServer::new(gateway_server));
+    // --- 3. Start the gRPC server ---
+    let mut server_builder = Server::builder();
+    if let Some(tls) = &config.gateway.grpc.tls {
+        server_builder = server_builder.tls_config(server_tls_config(tls)?)?;
+        tracing::info!("gRPC server will serve TLS using {}", tls.cert_path);
+    }
+    if config.gateway.auth.is_some() {
+        tracing::info!("gRPC calls require a JWT bearer token");
+    }
+    let authenticator = Authenticator::new(config.gateway.auth.as_ref());
+    let grpc_server = server_builder
+        .layer(MetricsLayer::new(metrics.clone()))
+        .add_service(BridgeGatewayServiceServer::with_interceptor(
+            gateway_server,
+            authenticator,
+        ));
 
     tokio::spawn(async move {
         if let Err(e) = grpc_server.serve(addr).await {
@@ -132,7 +390,50 @@ pub async fn start(config: &GatewayConfig) -> Result<EventManagerHandle> {
         }
     });
 
-    Ok(event_manager_handle)
+    // --- 4. Start the metrics endpoint ---
+    if let Some(metrics_config) = &config.gateway.metrics {
+        let metrics_addr = format!("{}:{}", metrics_config.host, metrics_config.port).parse()?;
+        tracing::info!(
+            "Serving Prometheus metrics on http://{}/metrics",
+            metrics_addr
+        );
+        let probes = health_probes.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(metrics_addr, metrics, probes).await {
+                tracing::error!("Metrics server failed: {}", e);
+            }
+        });
+    }
+
+    // --- 5. Start the health check endpoint ---
+    if let Some(health_config) = &config.gateway.health {
+        let health_addr = format!("{}:{}", health_config.host, health_config.port).parse()?;
+        tracing::info!(
+            "Serving health checks on http://{}/healthz and /readyz",
+            health_addr
+        );
+        let max_slot_lag = health_config.max_slot_lag;
+        tokio::spawn(async move {
+            if let Err(e) = health::serve(health_addr, health_probes, max_slot_lag).await {
+                tracing::error!("Health check server failed: {}", e);
+            }
+        });
+    }
+
+    Ok(GatewayHandle {
+        event_managers: event_manager_handles,
+        config: config_tx,
+        rpc_pools,
+    })
+}
+
+// helper: load the server identity from the configured PEM files
+fn server_tls_config(tls: &TlsConfig) -> Result<ServerTlsConfig> {
+    let cert = std::fs::read(&tls.cert_path)
+        .with_context(|| format!("Failed to read TLS certificate '{}'", tls.cert_path))?;
+    let key = std::fs::read(&tls.key_path)
+        .with_context(|| format!("Failed to read TLS private key '{}'", tls.key_path))?;
+    Ok(ServerTlsConfig::new().identity(Identity::from_pem(cert, key)))
 }
 
 // helper: parse a Pubkey returning GatewayError
@@ -140,16 +441,129 @@ fn parse_pubkey(s: &str) -> Result<Pubkey, GatewayError> {
     Pubkey::from_str(s).map_err(GatewayError::from)
 }
 
+// helper: resolve an `AdminRef` to the `AdminProfile` PDA it names, following an
+// authority's migration to the v2 seeds
+async fn parse_admin_ref(
+    cluster: &ClusterState,
+    admin: Option<AdminRef>,
+) -> Result<Pubkey, GatewayError> {
+    match admin.and_then(|admin| admin.admin) {
+        Some(admin_ref::Admin::AuthorityPubkey(authority)) => cluster
+            .transaction_builder()
+            .resolve_admin_profile(&parse_pubkey(&authority)?)
+            .await
+            .map_err(GatewayError::from),
+        Some(admin_ref::Admin::AdminProfilePda(pda)) => parse_pubkey(&pda),
+        None => Err(GatewayError::InvalidArgument(
+            "either authority_pubkey or admin_profile_pda is required".to_string(),
+        )),
+    }
+}
+
+// helper: the error ending a stream whose listener was disconnected for lagging
+fn lagged_status(pubkey: Pubkey) -> Status {
+    Status::resource_exhausted(format!(
+        "event listener for {} fell behind and was disconnected",
+        pubkey
+    ))
+}
+
+// helper: parse a stream's filter into the one the dispatcher applies
+fn parse_event_filter(filter: Option<gateway::EventFilter>) -> Result<EventFilter, GatewayError> {
+    let Some(filter) = filter else {
+        return Ok(EventFilter::default());
+    };
+    let kinds = filter
+        .event_types
+        .into_iter()
+        .map(|value| {
+            gateway::event_filter::EventType::try_from(value)
+                .map(EventKind::from)
+                .map_err(|_| GatewayError::InvalidArgument(format!("unknown event type {}", value)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let command_ids = match (filter.min_command_id, filter.max_command_id) {
+        (None, None) => None,
+        (min, max) => {
+            let (min, max) = (min.unwrap_or(0), max.unwrap_or(u64::MAX));
+            if min > max {
+                return Err(GatewayError::InvalidArgument(
+                    "min_command_id must be at most max_command_id".to_string(),
+                ));
+            }
+            Some(min..=max)
+        }
+    };
+    Ok(EventFilter {
+        kinds,
+        command_ids,
+        min_price: filter.min_price,
+    })
+}
+
+// helper: check the number of stored events a stream replays before its live ones
+fn replay_limit(replay_last_n: u32) -> Result<usize, GatewayError> {
+    if replay_last_n > MAX_RECENT_EVENTS {
+        return Err(GatewayError::InvalidArgument(format!(
+            "replay_last_n must be at most {}",
+            MAX_RECENT_EVENTS
+        )));
+    }
+    Ok(replay_last_n as usize)
+}
+
+// helper: build the user stream message for an event of the given kind
+fn user_event_message(envelope: &listener::EventEnvelope, kind: UserEventKind) -> UserEventStream {
+    let event = envelope.event.clone().into();
+    UserEventStream {
+        metadata: Some(envelope.into()),
+        event_category: Some(match kind {
+            UserEventKind::Personal => UserEventCategory::PersonalEvent(event),
+            UserEventKind::ServiceInteraction => UserEventCategory::ServiceInteractionEvent(event),
+        }),
+    }
+}
+
+// helper: build the admin stream message for an event of the given kind
+fn admin_event_message(
+    envelope: &listener::EventEnvelope,
+    kind: AdminEventKind,
+) -> Option<AdminEventStream> {
+    // Convert the whole connector event to a proto event first
+    let proto_event: gateway::BridgeEvent = envelope.event.clone().into();
+    // Then extract the specific event type the category needs
+    let event_category = match kind {
+        AdminEventKind::Personal => AdminEventCategory::PersonalEvent(proto_event),
+        AdminEventKind::IncomingUserCommand => match proto_event.event {
+            Some(gateway::bridge_event::Event::UserCommandDispatched(specific_event)) => {
+                AdminEventCategory::IncomingUserCommand(specific_event)
+            }
+            _ => return None,
+        },
+        AdminEventKind::NewUserProfile => match proto_event.event {
+            Some(gateway::bridge_event::Event::UserProfileCreated(specific_event)) => {
+                AdminEventCategory::NewUserProfile(specific_event)
+            }
+            _ => return None,
+        },
+    };
+    Some(AdminEventStream {
+        metadata: Some(envelope.into()),
+        event_category: Some(event_category),
+    })
+}
+
 #[tonic::async_trait]
 impl BridgeGatewayService for GatewayServer {
-    type ListenAsUserStream = ReceiverStream<Result<UserEventStream, Status>>;
+    type ListenAsUserStream = DeliveryStream<UserEventStream>;
 
     async fn listen_as_user(
         &self,
         request: Request<tonic::Streaming<UserStreamCommand>>,
     ) -> Result<Response<Self::ListenAsUserStream>, Status> {
+        let scope = AuthScope::of(&request);
+        let cluster = self.cluster(&request).map_err(Status::from)?;
         let mut in_stream = request.into_inner();
-        let state = self.state.clone();
 
         // The first message MUST be an `Init` command.
         let initial_command = in_stream.next().await.ok_or_else(|| {
@@ -165,17 +579,33 @@ impl BridgeGatewayService for GatewayServer {
             }
         };
 
-        tracing::info!("Received ListenAsUser request: {:?}", init_req);
+        self.state.request_log.log("ListenAsUser", &init_req);
 
         let result: Result<Response<Self::ListenAsUserStream>, GatewayError> = (async move {
-            let listener_capacity = self.state.config.gateway.streaming.listener_channel_capacity;
-            let service_listener_capacity = self.state.config.gateway.streaming.service_listener_capacity;
-            let output_capacity = self.state.config.gateway.streaming.output_stream_capacity;
+            let config = self.config();
+            let listener_capacity = config.gateway.streaming.listener_channel_capacity;
+            let service_listener_capacity = config.gateway.streaming.service_listener_capacity;
+            let output_capacity = config.gateway.streaming.output_stream_capacity;
+            let slow_consumer_timeout =
+                Duration::from_millis(config.gateway.streaming.slow_consumer_timeout_ms);
 
             let pubkey = parse_pubkey(&init_req.user_pubkey)?;
+            scope.check(&pubkey)?;
+            let replay_last_n = replay_limit(init_req.replay_last_n)?;
+            let filter = parse_event_filter(init_req.filter)?;
 
             tracing::debug!("Creating user listener for pubkey: {}", pubkey);
-            let user_listener = Arc::new(state.event_manager.listen_as_user(pubkey, listener_capacity).await);
+            let listener_options = ListenerOptions {
+                backpressure: config.gateway.streaming.listener_backpressure,
+                filter: filter.clone(),
+            };
+            let user_listener = cluster
+                .event_manager
+                .listen_as_user_with(pubkey, listener_capacity, listener_options)
+                .await
+                .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+            let user_listener = Arc::new(user_listener);
+            let lag_status = user_listener.lag_status();
 
             // Channel for merging all specific service events into one stream.
             let (specific_tx, mut specific_rx_merged) = mpsc::channel(output_capacity);
@@ -203,43 +633,95 @@ impl BridgeGatewayService for GatewayServer {
             // Get clonable broadcast receivers for the select loop.
             let mut personal_rx = user_listener.personal_events();
             let mut interactions_rx = user_listener.all_service_interactions();
-            let (tx, rx) = mpsc::channel(output_capacity);
+            // Read the history only once the listener is registered, so no event
+            // falls between the two.
+            let mut history = if replay_last_n > 0 {
+                cluster
+                    .event_manager
+                    .recent_events(&pubkey, replay_last_n)
+                    .await?
+            } else {
+                Vec::new()
+            };
+            history.retain(|envelope| filter.matches(&envelope.event));
+            let (tx, rx) = delivery::channel(
+                output_capacity,
+                slow_consumer_timeout,
+                DeadLetters::new(cluster.storage.clone(), pubkey),
+            );
             let service_senders_clone = service_senders.clone();
+            let stream_guard = self.state.metrics.stream_opened(StreamKind::User);
 
             // The main task that multiplexes all events and commands.
             tokio::spawn(async move {
-                loop { tokio::select! {
-                    // --- Handle outgoing events to the client ---
-                    result = personal_rx.recv() => {
-                        match result {
-                            Ok(event) => {
-                                let msg = UserEventStream { event_category: Some(UserEventCategory::PersonalEvent(event.into())) };
-                                tracing::debug!("Forwarding personal event to user {}: {:?}", pubkey, msg);
-                                if tx.send(Ok(msg)).await.is_err() { break; }
-                            },
-                            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                                tracing::warn!("User {} event stream lagged by {} messages.", pubkey, n);
-                            },
-                            Err(_) => break, // Channel closed
-                        }
-                    },
-                    result = interactions_rx.recv() => {
-                        match result {
-                            Ok(event) => {
-                                let msg = UserEventStream { event_category: Some(UserEventCategory::ServiceInteractionEvent(event.into())) };
-                                tracing::debug!("Forwarding service interaction event to user {}: {:?}", pubkey, msg);
-                                if tx.send(Ok(msg)).await.is_err() { break; }
-                            },
-                            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                                tracing::warn!("User {} interaction stream lagged by {} messages.", pubkey, n);
-                            },
-                            Err(_) => break, // Channel closed,
-                        }
+                let _stream_guard = stream_guard;
+                let mut connected = true;
+                // Replay the history first. Events stored while it was read may also
+                // arrive live; those are skipped below.
+                let mut replayed = HashSet::new();
+                for envelope in history {
+                    let Some(kind) = UserEventKind::of(&pubkey, &envelope.event) else {
+                        continue;
+                    };
+                    replayed.insert(envelope.cursor());
+                    let msg = user_event_message(&envelope, kind);
+                    tracing::debug!("Replaying stored event to user {}: {:?}", pubkey, msg);
+                    if !tx.send_waiting(envelope, msg).await {
+                        connected = false;
+                        break;
+                    }
+                }
+                while connected {
+                    tokio::select! {
+                        // --- Handle outgoing events to the client ---
+                        result = personal_rx.recv() => {
+                            match result {
+                                Ok(envelope) => {
+                                    if !replayed.is_empty() && replayed.remove(&envelope.cursor()) {
+                                        continue;
+                                    }
+                                    let msg = user_event_message(&envelope, UserEventKind::Personal);
+                                    tracing::debug!("Forwarding personal event to user {}: {:?}", pubkey, msg);
+                                    if !tx.send(envelope, msg) {
+                                        connected = false;
+                                        break;
+                                    }
+                                },
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                                    tracing::warn!("User {} event stream lagged by {} messages.", pubkey, n);
+                                },
+                                Err(_) => break, // Channel closed
+                            }
+                        },
+                        result = interactions_rx.recv() => {
+                            match result {
+                                Ok(envelope) => {
+                                    if !replayed.is_empty() && replayed.remove(&envelope.cursor()) {
+                                        continue;
+                                    }
+                                    let msg = user_event_message(&envelope, UserEventKind::ServiceInteraction);
+                                    tracing::debug!("Forwarding service interaction event to user {}: {:?}", pubkey, msg);
+                                    if !tx.send(envelope, msg) {
+                                        connected = false;
+                                        break;
+                                    }
+                                },
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                                    tracing::warn!("User {} interaction stream lagged by {} messages.", pubkey, n);
+                                },
+                                Err(_) => break, // Channel closed,
+                            }
                         },
-                        Some(event) = specific_rx_merged.recv() => { // This now receives BridgeEvent directly
-                                let msg = UserEventStream { event_category: Some(UserEventCategory::ServiceSpecificEvent(event.into())) };
-                                tracing::debug!("Forwarding service-specific event to user {}: {:?}", pubkey, msg);
-                                if tx.send(Ok(msg)).await.is_err() { break; }
+                        Some(envelope) = specific_rx_merged.recv() => {
+                            let msg = UserEventStream {
+                                metadata: Some((&envelope).into()),
+                                event_category: Some(UserEventCategory::ServiceSpecificEvent(envelope.event.clone().into())),
+                            };
+                            tracing::debug!("Forwarding service-specific event to user {}: {:?}", pubkey, msg);
+                            if !tx.send(envelope, msg) {
+                                connected = false;
+                                break;
+                            }
                         },
 
                         // --- Handle incoming commands from the client ---
@@ -249,30 +731,30 @@ impl BridgeGatewayService for GatewayServer {
                                     match command.command {
                                         Some(user_stream_command::Command::Subscribe(SubscribeToService { service_pda })) => {
                                             if let Ok(pda) = parse_pubkey(&service_pda) {
-                                                 tracing::info!("Dynamically subscribing user {} to service {}", pubkey, pda);
-                                                 let mut service_rx = user_listener.listen_for_service(pda, service_listener_capacity);
-                                                 let inner_tx = specific_tx.clone();
-                                                 let (tx_close, mut rx_close) = mpsc::channel::<()>(1);
-                                                 service_senders_clone.lock().await.insert(pda, tx_close);
- 
-                                                 tokio::spawn(async move {
-                                                     tokio::select! {
-                                                         _ = rx_close.recv() => {}, // Task is cancelled
-                                                         _ = forward_events(&mut service_rx, &inner_tx) => {}
-                                                     };
-                                                 });
+                                                tracing::info!("Dynamically subscribing user {} to service {}", pubkey, pda);
+                                                let mut service_rx = user_listener.listen_for_service(pda, service_listener_capacity);
+                                                let inner_tx = specific_tx.clone();
+                                                let (tx_close, mut rx_close) = mpsc::channel::<()>(1);
+                                                service_senders_clone.lock().await.insert(pda, tx_close);
+
+                                                tokio::spawn(async move {
+                                                    tokio::select! {
+                                                        _ = rx_close.recv() => {}, // Task is cancelled
+                                                        _ = forward_events(&mut service_rx, &inner_tx) => {}
+                                                    };
+                                                });
                                             } else {
                                                 tracing::warn!("Failed to parse pubkey from subscribe command: {}", service_pda);
                                             }
                                         },
                                         Some(user_stream_command::Command::Unsubscribe(UnsubscribeFromService { service_pda })) => {
                                             if let Ok(pda) = parse_pubkey(&service_pda) {
-                                                 tracing::info!("Dynamically unsubscribing user {} from service {}", pubkey, pda);
-                                                 if let Some(tx_close) = service_senders_clone.lock().await.remove(&pda) {
-                                                     let _ = tx_close.send(()).await;
-                                                 }
-                                                 // This will drop the sender and cause the receiver loop to exit
-                                                 user_listener.stop_listening_for_service(pda);
+                                                tracing::info!("Dynamically unsubscribing user {} from service {}", pubkey, pda);
+                                                if let Some(tx_close) = service_senders_clone.lock().await.remove(&pda) {
+                                                    let _ = tx_close.send(()).await;
+                                                }
+                                                // This will drop the sender and cause the receiver loop to exit
+                                                user_listener.stop_listening_for_service(pda);
                                             } else {
                                                 tracing::warn!("Failed to parse pubkey from unsubscribe command: {}", service_pda);
                                             }
@@ -286,101 +768,170 @@ impl BridgeGatewayService for GatewayServer {
                         else => { break; }
                     }
                 }
-                tracing::info!("User stream for {} ended. Unsubscribing from event manager.", pubkey);
-                state.event_manager.unsubscribe(pubkey).await;
+                // Dropping the listener here ends only this client's subscription; other
+                // clients watching the same pubkey keep theirs.
+                tracing::info!("User stream for {} ended.", pubkey);
+                if !connected {
+                    // Keep what was already routed to this client as dead letters.
+                    while let Ok(envelope) = personal_rx.try_recv() {
+                        tx.undelivered(envelope);
+                    }
+                    while let Ok(envelope) = interactions_rx.try_recv() {
+                        tx.undelivered(envelope);
+                    }
+                    while let Ok(envelope) = specific_rx_merged.try_recv() {
+                        tx.undelivered(envelope);
+                    }
+                }
+                if lag_status.is_lagged() {
+                    tx.fail(lagged_status(pubkey)).await;
+                }
             });
 
-            Ok(Response::new(ReceiverStream::new(rx)))
+            Ok(Response::new(rx))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
-    type ListenAsAdminStream = ReceiverStream<Result<AdminEventStream, Status>>;
+    type ListenAsAdminStream = DeliveryStream<AdminEventStream>;
 
     async fn listen_as_admin(
         &self,
         request: Request<ListenAsAdminRequest>,
     ) -> Result<Response<Self::ListenAsAdminStream>, Status> {
         let result: Result<Response<Self::ListenAsAdminStream>, GatewayError> = (async {
-            tracing::info!(
-                "Received ListenAsAdmin request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("ListenAsAdmin", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
 
-            let listener_capacity = self.state.config.gateway.streaming.listener_channel_capacity;
-            let output_capacity = self.state.config.gateway.streaming.output_stream_capacity;
+            let config = self.config();
+            let listener_capacity = config.gateway.streaming.listener_channel_capacity;
+            let output_capacity = config.gateway.streaming.output_stream_capacity;
+            let slow_consumer_timeout =
+                Duration::from_millis(config.gateway.streaming.slow_consumer_timeout_ms);
 
             let pubkey = parse_pubkey(&req.admin_pubkey)?;
-            let admin_listener: AdminListener = self.state.event_manager.listen_as_admin(pubkey, listener_capacity).await;
+            scope.check(&pubkey)?;
+            let replay_last_n = replay_limit(req.replay_last_n)?;
+            let filter = parse_event_filter(req.filter)?;
+            let listener_options = ListenerOptions {
+                backpressure: config.gateway.streaming.listener_backpressure,
+                filter: filter.clone(),
+            };
+            let admin_listener: AdminListener = cluster
+                .event_manager
+                .listen_as_admin_with(pubkey, listener_capacity, listener_options)
+                .await
+                .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+            let lag_status = admin_listener.lag_status();
             tracing::debug!("Created admin listener for pubkey: {}", pubkey);
 
-            let (mut personal_rx, mut commands_rx, mut new_users_rx) = admin_listener.into_parts();
-            let (tx, rx) = tokio::sync::mpsc::channel(output_capacity);
-            let event_manager = self.state.event_manager.clone();
+            // Read the history only once the listener is registered, so no event
+            // falls between the two.
+            let mut history = if replay_last_n > 0 {
+                cluster
+                    .event_manager
+                    .recent_events(&pubkey, replay_last_n)
+                    .await?
+            } else {
+                Vec::new()
+            };
+            history.retain(|envelope| filter.matches(&envelope.event));
+
+            let (personal, commands, new_users) = admin_listener.into_streams();
+            let tag = |kind: AdminEventKind| {
+                move |envelope: listener::EventEnvelope| {
+                    admin_event_message(&envelope, kind).map(|msg| (envelope, msg))
+                }
+            };
+            let personal = personal.map(tag(AdminEventKind::Personal));
+            let commands = commands.map(tag(AdminEventKind::IncomingUserCommand));
+            let new_users = new_users.map(tag(AdminEventKind::NewUserProfile));
+            let mut messages = personal
+                .merge(commands)
+                .merge(new_users)
+                .filter_map(|msg| msg);
+            let (tx, rx) = delivery::channel(
+                output_capacity,
+                slow_consumer_timeout,
+                DeadLetters::new(cluster.storage.clone(), pubkey),
+            );
+            let stream_guard = self.state.metrics.stream_opened(StreamKind::Admin);
 
             tokio::spawn(async move {
-                loop {
-                    tokio::select! {
-                        Some(event) = personal_rx.recv() => {
-                            let stream_msg = AdminEventStream { event_category: Some(
-                                AdminEventCategory::PersonalEvent(event.into()),
-                            )};
-                            tracing::debug!("Forwarding personal event to admin {}: {:?}", pubkey, stream_msg);
-                            if tx.send(Ok(stream_msg)).await.is_err() { break; }
-                        },
-                        Some(event) = commands_rx.recv() => {
-                            // Convert the whole connector event to a proto event first
-                            let proto_event: gateway::BridgeEvent = event.into();
-                            // Then extract the specific event type we need
-                            if let Some(gateway::bridge_event::Event::UserCommandDispatched(specific_event)) = proto_event.event {
-                                 let stream_msg = AdminEventStream {
-                                     event_category: Some(AdminEventCategory::IncomingUserCommand(specific_event)),
-                                 };
-                                 tracing::debug!("Forwarding incoming user command to admin {}: {:?}", pubkey, stream_msg);
-                                 if tx.send(Ok(stream_msg)).await.is_err() { break; }
-                            }
-                        },
-                        Some(event) = new_users_rx.recv() => {
-                            let proto_event: gateway::BridgeEvent = event.into();
-                            if let Some(gateway::bridge_event::Event::UserProfileCreated(specific_event)) = proto_event.event {
-                                 let stream_msg = AdminEventStream {
-                                     event_category: Some(AdminEventCategory::NewUserProfile(specific_event)),
-                                 };
-                                 tracing::debug!("Forwarding new user profile event to admin {}: {:?}", pubkey, stream_msg);
-                                 if tx.send(Ok(stream_msg)).await.is_err() { break; }
-                            }
-                        },
-                        else => { break; }
+                let _stream_guard = stream_guard;
+                // Replay the history first. Events stored while it was read may also
+                // arrive live; those are skipped below.
+                let mut replayed = HashSet::new();
+                let mut connected = true;
+                for envelope in history {
+                    let Some(stream_msg) = AdminEventKind::of(&pubkey, &envelope.event)
+                        .and_then(|kind| admin_event_message(&envelope, kind))
+                    else {
+                        continue;
+                    };
+                    replayed.insert(envelope.cursor());
+                    tracing::debug!(
+                        "Replaying stored event to admin {}: {:?}",
+                        pubkey,
+                        stream_msg
+                    );
+                    if !tx.send_waiting(envelope, stream_msg).await {
+                        connected = false;
+                        break;
                     }
                 }
-                tracing::info!("Admin stream for {} ended. Unsubscribing from event manager.", pubkey);
-                event_manager.unsubscribe(pubkey).await;
+                while connected {
+                    let Some((envelope, stream_msg)) = messages.next().await else {
+                        break;
+                    };
+                    if !replayed.is_empty() && replayed.remove(&envelope.cursor()) {
+                        continue;
+                    }
+                    tracing::debug!("Forwarding event to admin {}: {:?}", pubkey, stream_msg);
+                    connected = tx.send(envelope, stream_msg);
+                }
+                if !connected {
+                    // Keep what was already routed to this client as dead letters.
+                    for (envelope, _) in delivery::ready_items(&mut messages).await {
+                        tx.undelivered(envelope);
+                    }
+                }
+                tracing::info!("Admin stream for {} ended.", pubkey);
+                if lag_status.is_lagged() {
+                    tx.fail(lagged_status(pubkey)).await;
+                }
             });
 
-            Ok(Response::new(ReceiverStream::new(rx)))
+            Ok(Response::new(rx))
         })
         .await;
 
         result.map_err(Status::from)
     }
 
-  
-
     async fn stop_listener(
         &self,
         request: Request<StopListenerRequest>,
     ) -> Result<Response<()>, Status> {
         let result: Result<Response<()>, GatewayError> = (async {
-            tracing::info!("Received StopListener request: {:?}", request.get_ref());
+            self.state
+                .request_log
+                .log("StopListener", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let pubkey = parse_pubkey(&req.pubkey_to_stop)?;
+            scope.check(&pubkey)?;
             tracing::info!("Received explicit unsubscribe request for {}", pubkey);
-            self.state.event_manager.unsubscribe(pubkey).await;
+            cluster.event_manager.unsubscribe(pubkey).await;
             Ok(Response::new(()))
         })
         .await;
@@ -393,30 +944,32 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminRegisterProfileRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminRegisterProfile request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminRegisterProfile", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let communication_pubkey = parse_pubkey(&req.communication_pubkey)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_register_profile(authority, communication_pubkey)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared admin_register_profile tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -428,30 +981,32 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminUpdateCommKeyRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminUpdateCommKey request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminUpdateCommKey", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let new_key = parse_pubkey(&req.new_key)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_update_comm_key(authority, new_key)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared admin_update_comm_key tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -463,13 +1018,15 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminUpdatePricesRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminUpdatePrices request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminUpdatePrices", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
 
             let new_prices = req
                 .new_prices
@@ -480,21 +1037,93 @@ impl BridgeGatewayService for GatewayServer {
                 })
                 .collect::<Vec<PriceEntry>>();
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_update_prices(authority, new_prices)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared admin_update_prices tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn prepare_admin_set_gc_policy(
+        &self,
+        request: Request<PrepareAdminSetGcPolicyRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("PrepareAdminSetGcPolicy", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
+
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
+            let transaction = builder
+                .prepare_admin_set_gc_policy(authority, req.inactivity_epochs)
+                .await
+                .map_err(GatewayError::from)?;
+
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
+            tracing::debug!(
+                "Prepared admin_set_gc_policy tx for authority {}",
+                authority
+            );
+
+            Ok(Response::new(response))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn prepare_admin_set_priority_surcharge(
+        &self,
+        request: Request<PrepareAdminSetPrioritySurchargeRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("PrepareAdminSetPrioritySurcharge", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
+
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
+            let transaction = builder
+                .prepare_admin_set_priority_surcharge(authority, req.surcharge)
+                .await
+                .map_err(GatewayError::from)?;
+
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
+            tracing::debug!(
+                "Prepared admin_set_priority_surcharge tx for authority {}",
+                authority
+            );
+
+            Ok(Response::new(response))
         })
         .await;
 
@@ -506,27 +1135,29 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminWithdrawRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminWithdraw request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminWithdraw", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let destination = parse_pubkey(&req.destination)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_withdraw(authority, req.amount, destination)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!("Prepared admin_withdraw tx for authority {}", authority);
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -538,29 +1169,110 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminCloseProfileRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminCloseProfile request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminCloseProfile", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_close_profile(authority)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared admin_close_profile tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn prepare_admin_close_profile_to(
+        &self,
+        request: Request<PrepareAdminCloseProfileToRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("PrepareAdminCloseProfileTo", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
+            let destination = parse_pubkey(&req.destination)?;
+
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
+            let transaction = builder
+                .prepare_admin_close_profile_to(authority, destination)
+                .await
+                .map_err(GatewayError::from)?;
+
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
+            tracing::debug!(
+                "Prepared admin_close_profile_to tx for authority {}",
+                authority
+            );
+
+            Ok(Response::new(response))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn prepare_admin_migrate_to_v2(
+        &self,
+        request: Request<PrepareAdminMigrateToV2Request>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("PrepareAdminMigrateToV2", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
+            let profile_index = u16::try_from(req.profile_index).map_err(|_| {
+                GatewayError::InvalidArgument(format!(
+                    "profile_index {} does not fit in u16",
+                    req.profile_index
+                ))
+            })?;
+
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
+            let transaction = builder
+                .prepare_admin_migrate_to_v2(authority, profile_index)
+                .await
+                .map_err(GatewayError::from)?;
+
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
+            tracing::debug!(
+                "Prepared admin_migrate_to_v2 tx for authority {}",
+                authority
+            );
+
+            Ok(Response::new(response))
         })
         .await;
 
@@ -572,35 +1284,38 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareAdminDispatchCommandRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareAdminDispatchCommand request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareAdminDispatchCommand", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let target_user_profile_pda = parse_pubkey(&req.target_user_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_admin_dispatch_command(
                     authority,
                     target_user_profile_pda,
                     req.command_id,
+                    req.rebate,
                     req.payload,
                 )
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared admin_dispatch_command tx for authority {}",
                 authority
             );
 
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -612,30 +1327,32 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserCreateProfileRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserCreateProfile request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserCreateProfile", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let target_admin_pda = parse_pubkey(&req.target_admin_pda)?;
             let communication_pubkey = parse_pubkey(&req.communication_pubkey)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_create_profile(authority, target_admin_pda, communication_pubkey)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared user_create_profile tx for authority {}",
                 authority
             );
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -647,30 +1364,32 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserUpdateCommKeyRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserUpdateCommKey request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserUpdateCommKey", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
             let new_key = parse_pubkey(&req.new_key)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_update_comm_key(authority, admin_profile_pda, new_key)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared user_update_comm_key tx for authority {}",
                 authority
             );
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -682,26 +1401,28 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserDepositRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserDeposit request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserDeposit", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_deposit(authority, admin_profile_pda, req.amount)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!("Prepared user_deposit tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -713,27 +1434,29 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserWithdrawRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserWithdraw request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserWithdraw", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
             let destination = parse_pubkey(&req.destination)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_withdraw(authority, admin_profile_pda, req.amount, destination)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!("Prepared user_withdraw tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -745,26 +1468,28 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserCloseProfileRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserCloseProfile request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserCloseProfile", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_close_profile(authority, admin_profile_pda)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!("Prepared user_close_profile tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -776,34 +1501,37 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareUserDispatchCommandRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received PrepareUserDispatchCommand request: {:?}",
-                request.get_ref()
-            );
+            self.state
+                .request_log
+                .log("PrepareUserDispatchCommand", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
             let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_user_dispatch_command(
                     authority,
                     admin_profile_pda,
                     req.command_id as u16,
+                    req.high_priority,
                     req.payload,
                 )
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!(
                 "Prepared user_dispatch_command tx for authority {}",
                 authority
             );
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
         })
         .await;
 
@@ -815,22 +1543,61 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<PrepareLogActionRequest>,
     ) -> Result<Response<UnsignedTransactionResponse>, Status> {
         let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
-            tracing::info!("Received PrepareLogAction request: {:?}", request.get_ref());
+            self.state
+                .request_log
+                .log("PrepareLogAction", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
             let req = request.into_inner();
             let authority = parse_pubkey(&req.authority_pubkey)?;
+            scope.check(&authority)?;
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
             let transaction = builder
                 .prepare_log_action(authority, req.session_id, req.action_code as u16)
                 .await
                 .map_err(GatewayError::from)?;
 
-            let unsigned_tx =
-                bincode::serde::encode_to_vec(&transaction, bincode::config::standard())
-                    .map_err(GatewayError::from)?;
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
             tracing::debug!("Prepared log_action tx for authority {}", authority);
-            Ok(Response::new(UnsignedTransactionResponse { unsigned_tx }))
+            Ok(Response::new(response))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn prepare_gc_inactive_profile(
+        &self,
+        request: Request<PrepareGcInactiveProfileRequest>,
+    ) -> Result<Response<UnsignedTransactionResponse>, Status> {
+        let result: Result<Response<UnsignedTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("PrepareGcInactiveProfile", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let caller = parse_pubkey(&req.caller_pubkey)?;
+            scope.check(&caller)?;
+            let user_authority = parse_pubkey(&req.user_authority_pubkey)?;
+            let admin_profile_pda = parse_pubkey(&req.admin_profile_pda)?;
+
+            let builder = self.prepare_builder(cluster, req.options.as_ref())?;
+            let transaction = builder
+                .prepare_gc_inactive_profile(caller, user_authority, admin_profile_pda)
+                .await
+                .map_err(GatewayError::from)?;
+
+            let response = self
+                .unsigned_response(cluster, transaction, req.options.as_ref())
+                .await?;
+            tracing::debug!("Prepared gc_inactive_profile tx for caller {}", caller);
+            Ok(Response::new(response))
         })
         .await;
 
@@ -842,28 +1609,417 @@ impl BridgeGatewayService for GatewayServer {
         request: Request<SubmitTransactionRequest>,
     ) -> Result<Response<TransactionResponse>, Status> {
         let result: Result<Response<TransactionResponse>, GatewayError> = (async {
-            tracing::info!(
-                "Received SubmitTransaction request with {} bytes",
-                request.get_ref().signed_tx.len()
-            );
+            self.state
+                .request_log
+                .log("SubmitTransaction", request.get_ref());
 
+            let cluster = self.cluster(&request)?;
             let req = request.into_inner();
             let tx_bytes = req.signed_tx;
+            let builder = cluster.transaction_builder();
+
+            let submitted = if req.versioned {
+                let (transaction, _len): (VersionedTransaction, usize) =
+                    bincode::serde::borrow_decode_from_slice(
+                        tx_bytes.as_slice(),
+                        bincode::config::standard(),
+                    )
+                    .map_err(GatewayError::from)?;
+                tracing::debug!("Deserialized versioned transaction: {:?}", transaction);
+                builder.submit_versioned_transaction(&transaction).await
+            } else {
+                let (transaction, _len): (Transaction, usize) =
+                    bincode::serde::borrow_decode_from_slice(
+                        tx_bytes.as_slice(),
+                        bincode::config::standard(),
+                    )
+                    .map_err(GatewayError::from)?;
+                tracing::debug!("Deserialized transaction: {:?}", transaction);
+                builder.submit_transaction(&transaction).await
+            };
+            self.state.metrics.record_submit(submitted.is_ok());
+            let signature = submitted.map_err(GatewayError::from)?;
+            tracing::info!("Submitted transaction, signature: {}", signature);
 
-            let (transaction, _len): (Transaction, usize) =
-                bincode::serde::borrow_decode_from_slice(
-                    tx_bytes.as_slice(),
-                    bincode::config::standard(),
-                )
-                .map_err(GatewayError::from)?;
-            tracing::debug!("Deserialized transaction: {:?}", transaction);
+            Ok(Response::new(TransactionResponse {
+                signature: signature.to_string(),
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    type SubmitAndWatchTransactionStream = ReceiverStream<Result<TransactionStatusUpdate, Status>>;
+
+    async fn submit_and_watch_transaction(
+        &self,
+        request: Request<SubmitTransactionRequest>,
+    ) -> Result<Response<Self::SubmitAndWatchTransactionStream>, Status> {
+        let result: Result<Response<Self::SubmitAndWatchTransactionStream>, GatewayError> =
+            (async {
+                self.state
+                    .request_log
+                    .log("SubmitAndWatchTransaction", request.get_ref());
+
+                let cluster = self.cluster(&request)?;
+                let req = request.into_inner();
+                let builder = cluster.transaction_builder();
+                let (broadcast, recent_blockhash) = if req.versioned {
+                    let (transaction, _len): (VersionedTransaction, usize) =
+                        bincode::serde::borrow_decode_from_slice(
+                            req.signed_tx.as_slice(),
+                            bincode::config::standard(),
+                        )
+                        .map_err(GatewayError::from)?;
+                    (
+                        builder.broadcast_versioned_transaction(&transaction).await,
+                        *transaction.message.recent_blockhash(),
+                    )
+                } else {
+                    let (transaction, _len): (Transaction, usize) =
+                        bincode::serde::borrow_decode_from_slice(
+                            req.signed_tx.as_slice(),
+                            bincode::config::standard(),
+                        )
+                        .map_err(GatewayError::from)?;
+                    (
+                        builder.broadcast_transaction(&transaction).await,
+                        transaction.message.recent_blockhash,
+                    )
+                };
+                self.state.metrics.record_submit(broadcast.is_ok());
+                let signature = broadcast.map_err(GatewayError::from)?;
+                tracing::info!("Broadcast transaction, signature: {}", signature);
+
+                let mut updates =
+                    TxTracker::new(cluster.rpc_client.clone()).watch(signature, recent_blockhash);
+                let (tx, rx) = tokio::sync::mpsc::channel(
+                    self.config().gateway.streaming.output_stream_capacity,
+                );
+                let stream_guard = self.state.metrics.stream_opened(StreamKind::TxStatus);
+                tokio::spawn(async move {
+                    let _stream_guard = stream_guard;
+                    while let Some(update) = updates.recv().await {
+                        tracing::debug!("Transaction {} status: {:?}", signature, update.status);
+                        if tx.send(Ok(update.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                Ok(Response::new(ReceiverStream::new(rx)))
+            })
+            .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn simulate_transaction(
+        &self,
+        request: Request<SimulateTransactionRequest>,
+    ) -> Result<Response<SimulateTransactionResponse>, Status> {
+        let result: Result<Response<SimulateTransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("SimulateTransaction", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let req = request.into_inner();
+            let builder = cluster.transaction_builder();
+            let simulation = if req.versioned {
+                let (transaction, _len): (VersionedTransaction, usize) =
+                    bincode::serde::borrow_decode_from_slice(
+                        req.tx.as_slice(),
+                        bincode::config::standard(),
+                    )
+                    .map_err(GatewayError::from)?;
+                builder.simulate_versioned(&transaction).await
+            } else {
+                let (transaction, _len): (Transaction, usize) =
+                    bincode::serde::borrow_decode_from_slice(
+                        req.tx.as_slice(),
+                        bincode::config::standard(),
+                    )
+                    .map_err(GatewayError::from)?;
+                builder.simulate(&transaction).await
+            }
+            .map_err(GatewayError::from)?;
+            tracing::debug!("Simulation result: {:?}", simulation);
+
+            Ok(Response::new(SimulateTransactionResponse {
+                success: simulation.is_ok(),
+                error: simulation.err.map(|e| e.to_string()).unwrap_or_default(),
+                bridge_error: simulation
+                    .bridge_error
+                    .map(|e| e.to_string())
+                    .unwrap_or_default(),
+                logs: simulation.logs,
+                units_consumed: simulation.units_consumed.unwrap_or_default(),
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
 
-            let builder = TransactionBuilder::new(self.state.rpc_client.clone());
-            let signature = builder
-                .submit_transaction(&transaction)
+    async fn get_admin_profile(
+        &self,
+        request: Request<GetAdminProfileRequest>,
+    ) -> Result<Response<AdminProfileResponse>, Status> {
+        let result: Result<Response<AdminProfileResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("GetAdminProfile", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let admin_pda = parse_admin_ref(cluster, request.into_inner().admin).await?;
+            let profile = cluster
+                .account_reader()
+                .fetch_admin_profile_at(&admin_pda)
+                .await
+                .map_err(GatewayError::from)?
+                .ok_or_else(|| {
+                    GatewayError::NotFound(format!("no AdminProfile at {}", admin_pda))
+                })?;
+            tracing::debug!("Fetched AdminProfile {}", admin_pda);
+
+            Ok(Response::new((admin_pda, profile).into()))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn list_user_profiles_for_admin(
+        &self,
+        request: Request<ListUserProfilesForAdminRequest>,
+    ) -> Result<Response<ListUserProfilesForAdminResponse>, Status> {
+        let result: Result<Response<ListUserProfilesForAdminResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("ListUserProfilesForAdmin", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let req = request.into_inner();
+            let admin_pda = parse_admin_ref(cluster, req.admin).await?;
+            let limit = match req.limit {
+                0 => MAX_PAGE_SIZE,
+                limit => limit as usize,
+            };
+
+            let page = cluster
+                .account_reader()
+                .list_user_profiles_for_admin(&admin_pda, req.offset as usize, limit)
                 .await
                 .map_err(GatewayError::from)?;
-            tracing::info!("Submitted transaction, signature: {}", signature);
+            tracing::debug!(
+                "Listed {} of {} UserProfiles for admin {}",
+                page.profiles.len(),
+                page.total,
+                admin_pda
+            );
+
+            Ok(Response::new(page.into()))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn get_balances(
+        &self,
+        request: Request<GetBalancesRequest>,
+    ) -> Result<Response<GetBalancesResponse>, Status> {
+        let result: Result<Response<GetBalancesResponse>, GatewayError> = (async {
+            self.state.request_log.log("GetBalances", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let pubkey = parse_pubkey(&request.into_inner().pubkey)?;
+            let reader = cluster.account_reader();
+            let (lamports, profiles) = tokio::try_join!(
+                cluster.rpc_client.get_balance(&pubkey),
+                reader.list_user_profiles_of(&pubkey),
+            )
+            .map_err(GatewayError::from)?;
+            tracing::debug!(
+                "Fetched balances of {} across {} services",
+                pubkey,
+                profiles.len()
+            );
+
+            let total_deposits = profiles
+                .iter()
+                .map(|entry| entry.profile.deposit_balance)
+                .fold(0u64, u64::saturating_add);
+            Ok(Response::new(GetBalancesResponse {
+                lamports,
+                deposits: profiles.into_iter().map(Into::into).collect(),
+                total_deposits,
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn get_recent_events(
+        &self,
+        request: Request<GetRecentEventsRequest>,
+    ) -> Result<Response<GetRecentEventsResponse>, Status> {
+        let result: Result<Response<GetRecentEventsResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("GetRecentEvents", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let pubkey = parse_pubkey(&req.pubkey)?;
+            scope.check(&pubkey)?;
+            let limit = match req.limit {
+                0 => DEFAULT_RECENT_EVENTS,
+                limit if limit > MAX_RECENT_EVENTS => {
+                    return Err(GatewayError::InvalidArgument(format!(
+                        "limit must be at most {}",
+                        MAX_RECENT_EVENTS
+                    )));
+                }
+                limit => limit,
+            };
+
+            let envelopes = cluster
+                .event_manager
+                .recent_events(&pubkey, limit as usize)
+                .await?;
+            tracing::debug!("Read {} recent events of {}", envelopes.len(), pubkey);
+            Ok(Response::new(GetRecentEventsResponse {
+                events: envelopes
+                    .into_iter()
+                    .map(|envelope| RecentEvent {
+                        metadata: Some((&envelope).into()),
+                        event: Some(envelope.event.into()),
+                    })
+                    .collect(),
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn get_dead_letters(
+        &self,
+        request: Request<GetDeadLettersRequest>,
+    ) -> Result<Response<GetDeadLettersResponse>, Status> {
+        let result: Result<Response<GetDeadLettersResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("GetDeadLetters", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let pubkey = parse_pubkey(&req.pubkey)?;
+            scope.check(&pubkey)?;
+            let limit = match req.limit {
+                0 => DEFAULT_DEAD_LETTERS,
+                limit if limit > MAX_DEAD_LETTERS => {
+                    return Err(GatewayError::InvalidArgument(format!(
+                        "limit must be at most {}",
+                        MAX_DEAD_LETTERS
+                    )));
+                }
+                limit => limit,
+            };
+
+            let letters = cluster.storage.dead_letters(&pubkey, limit as usize)?;
+            tracing::debug!("Read {} dead letters of {}", letters.len(), pubkey);
+            Ok(Response::new(GetDeadLettersResponse {
+                dead_letters: letters.into_iter().map(Into::into).collect(),
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn ack_dead_letters(
+        &self,
+        request: Request<AckDeadLettersRequest>,
+    ) -> Result<Response<AckDeadLettersResponse>, Status> {
+        let result: Result<Response<AckDeadLettersResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("AckDeadLetters", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let pubkey = parse_pubkey(&req.pubkey)?;
+            scope.check(&pubkey)?;
+            let through = req
+                .through
+                .ok_or_else(|| GatewayError::InvalidArgument("through is required".to_string()))?;
+
+            let removed = cluster.storage.ack_dead_letters(
+                &pubkey,
+                &EventCursor {
+                    slot: through.slot,
+                    signature: through.signature,
+                    index: through.index,
+                },
+            )?;
+            tracing::info!("Acknowledged {} dead letters of {}", removed, pubkey);
+            Ok(Response::new(AckDeadLettersResponse {
+                removed: removed as u32,
+            }))
+        })
+        .await;
+
+        result.map_err(Status::from)
+    }
+
+    async fn request_airdrop(
+        &self,
+        request: Request<RequestAirdropRequest>,
+    ) -> Result<Response<TransactionResponse>, Status> {
+        let result: Result<Response<TransactionResponse>, GatewayError> = (async {
+            self.state
+                .request_log
+                .log("RequestAirdrop", request.get_ref());
+
+            let cluster = self.cluster(&request)?;
+            let scope = AuthScope::of(&request);
+            let req = request.into_inner();
+            let pubkey = parse_pubkey(&req.pubkey)?;
+            scope.check(&pubkey)?;
+            if req.lamports == 0 {
+                return Err(GatewayError::InvalidArgument(
+                    "lamports must be greater than zero".to_string(),
+                ));
+            }
+
+            let genesis_hash = cluster.rpc_client.get_genesis_hash().await?;
+            let network = Cluster::from_genesis_hash(&genesis_hash);
+            if !network.is_development() {
+                return Err(GatewayError::FailedPrecondition(format!(
+                    "airdrops are only available on devnet and localnet, not on {}",
+                    network
+                )));
+            }
+
+            let signature = cluster
+                .rpc_client
+                .request_airdrop(&pubkey, req.lamports)
+                .await?;
+            tracing::debug!(
+                "Requested an airdrop of {} lamports to {} on {}: {}",
+                req.lamports,
+                pubkey,
+                network,
+                signature
+            );
 
             Ok(Response::new(TransactionResponse {
                 signature: signature.to_string(),
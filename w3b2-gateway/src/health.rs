@@ -0,0 +1,172 @@
+//! # Health Checks
+//!
+//! `serve` answers plain HTTP probes, so Docker or Kubernetes can check the
+//! gateway without gRPC tooling:
+//!
+//! - `GET /healthz` answers `200` while the gateway runs and can read the
+//!   synchronization state of every cluster (liveness);
+//! - `GET /readyz` answers `200` only while every cluster has a healthy RPC
+//!   endpoint and its synchronizer is at most `max-slot-lag` slots behind the
+//!   chain (readiness).
+//!
+//! Both answer `503` otherwise, with one line per cluster saying what was found.
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, StatusCode};
+use std::convert::Infallible;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use w3b2_connector::metrics::SyncStatus;
+use w3b2_connector::rpc_pool::RpcPool;
+use w3b2_connector::workers::EventManagerHandle;
+
+/// What the probes look at for one cluster.
+#[derive(Clone)]
+pub struct ClusterProbe {
+    pub name: String,
+    pub event_manager: EventManagerHandle,
+    pub rpc_pool: RpcPool,
+}
+
+/// The state of one cluster when a probe ran.
+#[derive(Debug, Clone)]
+pub struct ClusterHealth {
+    pub name: String,
+    /// The RPC endpoints that passed their last health check.
+    pub healthy_rpc_urls: Vec<String>,
+    /// The synchronizer's progress, or why it could not be read.
+    pub sync: Result<SyncStatus, String>,
+}
+
+impl ClusterHealth {
+    fn is_live(&self) -> bool {
+        self.sync.is_ok()
+    }
+
+    fn is_ready(&self, max_slot_lag: u64) -> bool {
+        match &self.sync {
+            Ok(status) => {
+                !self.healthy_rpc_urls.is_empty()
+                    && status.chain_slot > 0
+                    && status.slot_lag() <= max_slot_lag
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// The state of every cluster when a probe ran.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub clusters: Vec<ClusterHealth>,
+    pub max_slot_lag: u64,
+}
+
+impl HealthReport {
+    /// Returns whether the synchronization state of every cluster can be read.
+    pub fn is_live(&self) -> bool {
+        self.clusters.iter().all(ClusterHealth::is_live)
+    }
+
+    /// Returns whether every cluster can reach a healthy RPC endpoint and is
+    /// synchronized to within `max_slot_lag` slots of the chain.
+    pub fn is_ready(&self) -> bool {
+        self.clusters
+            .iter()
+            .all(|cluster| cluster.is_ready(self.max_slot_lag))
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for cluster in &self.clusters {
+            write!(
+                f,
+                "cluster '{}': {} healthy RPC endpoint(s), ",
+                cluster.name,
+                cluster.healthy_rpc_urls.len()
+            )?;
+            match &cluster.sync {
+                Ok(status) if status.chain_slot == 0 => {
+                    writeln!(f, "synchronizer has not seen the chain yet")?
+                }
+                Ok(status) => writeln!(
+                    f,
+                    "synchronizer at slot {} of {} ({} behind, {} allowed)",
+                    status.processed_slot,
+                    status.chain_slot,
+                    status.slot_lag(),
+                    self.max_slot_lag
+                )?,
+                Err(e) => writeln!(f, "synchronizer state unreadable: {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the current state of every cluster in `probes`.
+pub async fn check(probes: &[ClusterProbe], max_slot_lag: u64) -> HealthReport {
+    let mut clusters = Vec::with_capacity(probes.len());
+    for probe in probes {
+        clusters.push(ClusterHealth {
+            name: probe.name.clone(),
+            healthy_rpc_urls: probe.rpc_pool.healthy_urls(),
+            sync: probe
+                .event_manager
+                .sync_status()
+                .await
+                .map_err(|e| e.to_string()),
+        });
+    }
+    HealthReport {
+        clusters,
+        max_slot_lag,
+    }
+}
+
+/// Serves `/healthz` and `/readyz` on `addr` until the server fails.
+pub async fn serve(addr: SocketAddr, probes: Vec<ClusterProbe>, max_slot_lag: u64) -> Result<()> {
+    let probes = Arc::new(probes);
+    let make_service = make_service_fn(move |_| {
+        let probes = probes.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, probes.clone(), max_slot_lag)
+            }))
+        }
+    });
+    hyper::Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+async fn handle(
+    request: hyper::Request<Body>,
+    probes: Arc<Vec<ClusterProbe>>,
+    max_slot_lag: u64,
+) -> Result<hyper::Response<Body>, Infallible> {
+    let path = request.uri().path();
+    if request.method() != Method::GET || (path != "/healthz" && path != "/readyz") {
+        let mut response = hyper::Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(response);
+    }
+
+    let report = check(&probes, max_slot_lag).await;
+    let healthy = if path == "/healthz" {
+        report.is_live()
+    } else {
+        report.is_ready()
+    };
+    let mut response = hyper::Response::new(Body::from(report.to_string()));
+    if !healthy {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    Ok(response)
+}
@@ -1,21 +1,28 @@
 pub mod cli;
 pub mod config;
+pub mod dev;
+pub mod doctor;
 pub mod error;
 pub mod grpc;
+pub mod health;
+pub mod metrics;
+pub mod reload;
 pub mod storage;
 
 use anyhow::Result;
 use clap::Parser;
 use cli::{Cli, Commands};
 use config::{GatewayConfig, load_config};
-use std::{fs::File, str::FromStr};
+use reload::ConfigReloader;
+use std::{fs::File, str::FromStr, sync::Arc};
 use tokio::signal;
 use tracing::Level;
 use tracing_subscriber::{
     Registry,
     filter::LevelFilter,
-    fmt::{self, writer::MakeWriterExt},
+    fmt,
     prelude::*,
+    reload as log_reload,
 };
 
 /// The main entry point for running the gateway application logic.
@@ -27,18 +34,13 @@ pub async fn run() -> Result<()> {
     match cli.command {
         Commands::Run(run_cmd) => {
             // --- 2. Load configuration or use defaults ---
-            let config = if let Some(config_path) = run_cmd.config {
-                // We can't log yet, so we print directly.
-                println!("Loading configuration from '{}'", &config_path);
-                load_config(&config_path)?
-            } else {
-                println!("No config file provided, using default settings.");
-                GatewayConfig::default()
-            };
+            let config = load_config_or_default(run_cmd.config.clone())?;
 
             // --- 3. Initialize logging based on config ---
+            // The level sits behind a reload layer so a config reload can change it.
             let log_level = Level::from_str(&config.gateway.log.level).unwrap_or(Level::INFO);
-            let level_filter = LevelFilter::from_level(log_level);
+            let (level_filter, log_level_handle) =
+                log_reload::Layer::new(LevelFilter::from_level(log_level));
 
             let subscriber = Registry::default().with(level_filter);
 
@@ -49,8 +51,7 @@ pub async fn run() -> Result<()> {
                         "Log output is 'file' but 'file_path' is not specified in config"
                     )
                 })?;
-                let log_file = File::create(file_path)?;
-                let file_writer = log_file.with_max_level(log_level);
+                let file_writer = Arc::new(File::create(file_path)?);
 
                 match config.gateway.log.format {
                     config::LogFormat::Plain => subscriber
@@ -62,7 +63,7 @@ pub async fn run() -> Result<()> {
                 }
             } else {
                 // Default to stdout
-                let stdout_writer = std::io::stdout.with_max_level(log_level);
+                let stdout_writer = std::io::stdout;
                 match config.gateway.log.format {
                     config::LogFormat::Plain => {
                         let fmt_layer = fmt::layer().with_writer(stdout_writer).pretty();
@@ -76,13 +77,28 @@ pub async fn run() -> Result<()> {
             };
 
             // --- 4. Start the main application logic ---
-            let event_manager_handle = grpc::start(&config).await?;
+            let gateway_handle = grpc::start(&config).await?;
+
+            // --- 5. Apply changes to the config file while running ---
+            if let Some(config_path) = run_cmd.config {
+                let reloader = ConfigReloader::new(
+                    config_path,
+                    gateway_handle.config.clone(),
+                    move |level| {
+                        if let Err(e) = log_level_handle.reload(level) {
+                            tracing::warn!("Failed to change the log level: {}", e);
+                        }
+                    },
+                    gateway_handle.rpc_pools.clone(),
+                );
+                tokio::spawn(reloader.run(reload::POLL_INTERVAL));
+            }
 
-            // --- 5. Wait for a shutdown signal ---
+            // --- 6. Wait for a shutdown signal ---
             match signal::ctrl_c().await {
                 Ok(()) => {
                     tracing::info!("Received Ctrl+C, initiating graceful shutdown...");
-                    event_manager_handle.stop().await;
+                    gateway_handle.stop().await;
                     tracing::info!("Shutdown complete.");
                 }
                 Err(err) => {
@@ -90,7 +106,50 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
+        Commands::Airdrop(cmd) => {
+            let config = load_config_or_default(cmd.config.clone())?;
+            println!("{}", dev::airdrop(&dev::rpc_client(&config), &cmd).await?);
+        }
+        Commands::Balance(cmd) => {
+            let config = load_config_or_default(cmd.config.clone())?;
+            println!("{}", dev::balance(dev::rpc_client(&config), &cmd).await?);
+        }
+        Commands::Profile(cmd) => {
+            let config = load_config_or_default(cmd.config.clone())?;
+            println!("{}", dev::profile(dev::rpc_client(&config), &cmd).await?);
+        }
+        Commands::Snapshot(cmd) => {
+            let config = load_config_or_default(cmd.config.clone())?;
+            let storage = storage::SledStorage::new(sled::open(&config.gateway.db_path)?);
+            println!("{}", storage::snapshot(&storage, &cmd).await?);
+        }
+        Commands::InitConfig(cmd) => {
+            println!("{}", config::init_config(&cmd)?);
+        }
+        Commands::CheckConfig(cmd) => {
+            println!("{}", config::check_config(&cmd)?);
+        }
+        Commands::Doctor(cmd) => {
+            let config = load_config_or_default(cmd.config.clone())?;
+            let report = doctor::doctor(&config).await;
+            println!("{}", report);
+            if report.failures() > 0 {
+                anyhow::bail!("The environment is not ready to run the gateway");
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Loads the configuration file at `config_path`, or the defaults if none is given.
+fn load_config_or_default(config_path: Option<String>) -> Result<GatewayConfig> {
+    if let Some(config_path) = config_path {
+        // We can't log yet, so we print directly.
+        println!("Loading configuration from '{}'", &config_path);
+        load_config(&config_path)
+    } else {
+        println!("No config file provided, using default settings.");
+        Ok(GatewayConfig::default())
+    }
+}
@@ -0,0 +1,359 @@
+//! # Gateway Metrics
+//!
+//! `GatewayMetrics` counts gRPC calls, open event streams and transaction
+//! submissions. `serve` exposes them on an HTTP `/metrics` endpoint in the
+//! Prometheus text format, followed by the connector's `SyncStatus` of every
+//! cluster, labeled `cluster="<name>"`, so operators can see both how the gateway
+//! is used and how far each synchronizer is behind.
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, StatusCode};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::Code;
+use tonic::codegen::http;
+use tower::{Layer, Service};
+use w3b2_connector::metrics::SyncStatus;
+
+use crate::health::ClusterProbe;
+
+/// The upper bounds, in seconds, of the gRPC latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The request path and name of every `BridgeGatewayService` method.
+const GATEWAY_METHODS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/gateway_methods.rs"));
+
+/// The `method` label of requests to paths that are not a gateway method.
+pub const UNKNOWN_METHOD: &str = "unknown";
+
+/// Returns the `method` label of a request to `path`: the method name for
+/// `BridgeGatewayService` methods, `UNKNOWN_METHOD` for anything else.
+///
+/// Calls are counted before authentication, so the labels must not be taken from
+/// the path as is, or any client could create series without limit.
+pub fn method_label(path: &str) -> &'static str {
+    GATEWAY_METHODS
+        .iter()
+        .find(|(method_path, _)| *method_path == path)
+        .map_or(UNKNOWN_METHOD, |(_, name)| name)
+}
+
+/// The kinds of long-lived streams the gateway serves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    User,
+    Admin,
+    TxStatus,
+}
+
+impl StreamKind {
+    const ALL: [StreamKind; 3] = [StreamKind::User, StreamKind::Admin, StreamKind::TxStatus];
+
+    fn label(self) -> &'static str {
+        match self {
+            StreamKind::User => "user",
+            StreamKind::Admin => "admin",
+            StreamKind::TxStatus => "tx_status",
+        }
+    }
+}
+
+/// A cumulative latency histogram over `LATENCY_BUCKETS`.
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Counters shared by the gRPC handlers and the `MetricsLayer`.
+#[derive(Default)]
+pub struct GatewayMetrics {
+    /// Calls handled, keyed by method and status code.
+    calls: Mutex<BTreeMap<(String, String), u64>>,
+    latencies: Mutex<BTreeMap<String, Histogram>>,
+    open_streams: [AtomicUsize; StreamKind::ALL.len()],
+    submits_ok: AtomicU64,
+    submits_failed: AtomicU64,
+}
+
+impl GatewayMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a call to `method` that ended with `code` after `elapsed`.
+    pub fn record_call(&self, method: &str, code: Code, elapsed: Duration) {
+        *self
+            .calls
+            .lock()
+            .unwrap()
+            .entry((method.to_string(), format!("{:?}", code)))
+            .or_default() += 1;
+        self.latencies
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Counts a stream of `kind` as open until the returned guard is dropped.
+    pub fn stream_opened(self: &Arc<Self>, kind: StreamKind) -> StreamGuard {
+        self.open_streams[kind as usize].fetch_add(1, Ordering::Relaxed);
+        StreamGuard {
+            metrics: self.clone(),
+            kind,
+        }
+    }
+
+    /// Returns the number of open streams of `kind`.
+    pub fn open_streams(&self, kind: StreamKind) -> usize {
+        self.open_streams[kind as usize].load(Ordering::Relaxed)
+    }
+
+    /// Records whether a transaction submission was accepted by the RPC node.
+    pub fn record_submit(&self, ok: bool) {
+        let counter = if ok {
+            &self.submits_ok
+        } else {
+            &self.submits_failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP w3b2_gateway_grpc_requests_total gRPC calls handled, by method and status code."
+        );
+        let _ = writeln!(out, "# TYPE w3b2_gateway_grpc_requests_total counter");
+        for ((method, code), count) in self.calls.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "w3b2_gateway_grpc_requests_total{{method=\"{method}\",code=\"{code}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP w3b2_gateway_grpc_request_duration_seconds Time until the response headers of a gRPC call, by method."
+        );
+        let _ = writeln!(
+            out,
+            "# TYPE w3b2_gateway_grpc_request_duration_seconds histogram"
+        );
+        for (method, histogram) in self.latencies.lock().unwrap().iter() {
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "w3b2_gateway_grpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "w3b2_gateway_grpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "w3b2_gateway_grpc_request_duration_seconds_sum{{method=\"{method}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "w3b2_gateway_grpc_request_duration_seconds_count{{method=\"{method}\"}} {}",
+                histogram.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP w3b2_gateway_open_streams Event and transaction status streams currently open, by kind."
+        );
+        let _ = writeln!(out, "# TYPE w3b2_gateway_open_streams gauge");
+        for kind in StreamKind::ALL {
+            let _ = writeln!(
+                out,
+                "w3b2_gateway_open_streams{{kind=\"{}\"}} {}",
+                kind.label(),
+                self.open_streams(kind)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP w3b2_gateway_submits_total Transaction submissions, by whether the RPC node accepted them."
+        );
+        let _ = writeln!(out, "# TYPE w3b2_gateway_submits_total counter");
+        let _ = writeln!(
+            out,
+            "w3b2_gateway_submits_total{{result=\"ok\"}} {}",
+            self.submits_ok.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "w3b2_gateway_submits_total{{result=\"failed\"}} {}",
+            self.submits_failed.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+/// Keeps a stream counted as open, see `GatewayMetrics::stream_opened`.
+pub struct StreamGuard {
+    metrics: Arc<GatewayMetrics>,
+    kind: StreamKind,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.metrics.open_streams[self.kind as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A tower layer recording the method, status code and latency of every gRPC call.
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Arc<GatewayMetrics>,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Arc<GatewayMetrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// The service produced by `MetricsLayer`.
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<GatewayMetrics>,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        // Call the service that was driven to readiness, leaving a fresh clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let metrics = self.metrics.clone();
+        let method = method_label(request.uri().path());
+        let started = Instant::now();
+
+        Box::pin(async move {
+            let response = inner.call(request).await?;
+            // Failed calls answer with the status in the headers; successful ones
+            // send it in the trailers, after the body.
+            let code = response
+                .headers()
+                .get("grpc-status")
+                .map(|status| Code::from_bytes(status.as_bytes()))
+                .unwrap_or(Code::Ok);
+            metrics.record_call(method, code, started.elapsed());
+            Ok(response)
+        })
+    }
+}
+
+/// Serves `/metrics` on `addr` until the server fails, with the sync status of
+/// every cluster in `probes`.
+pub async fn serve(
+    addr: SocketAddr,
+    metrics: Arc<GatewayMetrics>,
+    probes: Vec<ClusterProbe>,
+) -> Result<()> {
+    let probes = Arc::new(probes);
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        let probes = probes.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, metrics.clone(), probes.clone())
+            }))
+        }
+    });
+    hyper::Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+async fn handle(
+    request: hyper::Request<Body>,
+    metrics: Arc<GatewayMetrics>,
+    probes: Arc<Vec<ClusterProbe>>,
+) -> Result<hyper::Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        let mut response = hyper::Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(response);
+    }
+
+    let mut statuses = Vec::with_capacity(probes.len());
+    for probe in probes.iter() {
+        match probe.event_manager.sync_status().await {
+            Ok(status) => statuses.push((probe.name.clone(), status)),
+            Err(e) => tracing::warn!(
+                "Failed to read the sync status of cluster '{}' for /metrics: {}",
+                probe.name,
+                e
+            ),
+        }
+    }
+    let mut body = metrics.to_prometheus();
+    body.push_str(&SyncStatus::to_prometheus_labeled("cluster", &statuses));
+    let mut response = hyper::Response::new(Body::from(body));
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("text/plain; version=0.0.4"),
+    );
+    Ok(response)
+}
@@ -0,0 +1,153 @@
+//! # Configuration Hot Reloading
+//!
+//! `ConfigReloader` polls the configuration file and, when it changes, applies
+//! the settings that are safe to change at runtime without touching open event
+//! streams:
+//!
+//! - `gateway.log.level`;
+//! - the per-listener `gateway.streaming` capacities, backpressure policy and
+//!   slow-consumer timeout, which apply to streams opened afterwards;
+//! - `gateway.priority-fee`;
+//! - the `solana.rate-limit` of every cluster.
+//!
+//! Every other change is logged and only takes effect after a restart. A file
+//! that fails to load or validate is ignored, keeping the running configuration.
+
+use crate::config::{GatewayConfig, load_config};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tracing::level_filters::LevelFilter;
+use w3b2_connector::rpc_pool::RpcPool;
+
+/// How often the configuration file is checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Returns `current` with the settings that can change at runtime taken from `new`.
+///
+/// Clusters only present in one of the two configurations are left as they are.
+pub fn merge_reloadable(current: &GatewayConfig, new: &GatewayConfig) -> GatewayConfig {
+    let mut merged = current.clone();
+
+    merged.gateway.log.level = new.gateway.log.level.clone();
+    merged.gateway.priority_fee = new.gateway.priority_fee;
+    let streaming = &mut merged.gateway.streaming;
+    streaming.listener_channel_capacity = new.gateway.streaming.listener_channel_capacity;
+    streaming.output_stream_capacity = new.gateway.streaming.output_stream_capacity;
+    streaming.service_listener_capacity = new.gateway.streaming.service_listener_capacity;
+    streaming.listener_backpressure = new.gateway.streaming.listener_backpressure;
+    streaming.slow_consumer_timeout_ms = new.gateway.streaming.slow_consumer_timeout_ms;
+
+    merged.connector.solana.rate_limit = new.connector.solana.rate_limit.clone();
+    for (name, connector) in merged.clusters.iter_mut() {
+        if let Some(new_connector) = new.clusters.get(name) {
+            connector.solana.rate_limit = new_connector.solana.rate_limit.clone();
+        }
+    }
+    merged
+}
+
+/// Watches the configuration file and applies its safe changes, see the module docs.
+pub struct ConfigReloader {
+    path: String,
+    /// The modification time of the file when it was last loaded.
+    modified: Option<SystemTime>,
+    config: watch::Sender<Arc<GatewayConfig>>,
+    set_log_level: Box<dyn Fn(LevelFilter) + Send + Sync>,
+    rpc_pools: HashMap<String, RpcPool>,
+}
+
+impl ConfigReloader {
+    /// Creates a reloader for the file at `path`, which the running `config` was
+    /// loaded from.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Publishes the applied configuration to the gRPC handlers.
+    /// * `set_log_level` - Changes the level of the installed log subscriber.
+    /// * `rpc_pools` - The RPC pool of every cluster, by name.
+    pub fn new(
+        path: impl Into<String>,
+        config: watch::Sender<Arc<GatewayConfig>>,
+        set_log_level: impl Fn(LevelFilter) + Send + Sync + 'static,
+        rpc_pools: HashMap<String, RpcPool>,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            modified: modified_at(&path),
+            path,
+            config,
+            set_log_level: Box::new(set_log_level),
+            rpc_pools,
+        }
+    }
+
+    /// Checks the file every `interval` and reloads it when it changed. Meant to
+    /// be spawned.
+    pub async fn run(mut self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let modified = modified_at(&self.path);
+            if modified == self.modified {
+                continue;
+            }
+            self.modified = modified;
+            if let Err(e) = self.reload() {
+                tracing::warn!("Keeping the running configuration: {:#}", e);
+            }
+        }
+    }
+
+    /// Loads the file and applies its safe changes.
+    pub fn reload(&self) -> Result<()> {
+        let new =
+            load_config(&self.path).with_context(|| format!("Failed to reload '{}'", self.path))?;
+        self.apply(&new);
+        Ok(())
+    }
+
+    /// Applies the safe changes of `new` and logs whether others were left out.
+    pub fn apply(&self, new: &GatewayConfig) {
+        let current = self.config.borrow().clone();
+        let merged = merge_reloadable(&current, new);
+        if merged != *new {
+            tracing::warn!(
+                "Some changed settings only take effect after a restart; applying the others"
+            );
+        }
+        if merged == *current {
+            return;
+        }
+
+        if merged.gateway.log.level != current.gateway.log.level {
+            match LevelFilter::from_str(&merged.gateway.log.level) {
+                Ok(level) => (self.set_log_level)(level),
+                Err(_) => tracing::warn!("Unknown log level '{}'", merged.gateway.log.level),
+            }
+        }
+        for (name, connector) in merged.all_clusters() {
+            let (Some(pool), Some((_, old))) = (
+                self.rpc_pools.get(name),
+                current
+                    .all_clusters()
+                    .find(|(old_name, _)| *old_name == name),
+            ) else {
+                continue;
+            };
+            if connector.solana.rate_limit != old.solana.rate_limit {
+                pool.set_rate_limit(connector.solana.rate_limit.as_ref());
+            }
+        }
+
+        self.config.send_replace(Arc::new(merged));
+        tracing::info!("Applied the reloaded configuration from '{}'", self.path);
+    }
+}
+
+fn modified_at(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
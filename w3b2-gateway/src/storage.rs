@@ -1,15 +1,97 @@
 /// Provides concrete `sled`-based implementations for the storage traits
 /// defined in the `w3b2-connector` library.
-use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use sled::{Db, transaction::TransactionalTree};
+use sled::{Db, Transactional, transaction::TransactionalTree};
+use solana_sdk::pubkey::Pubkey;
 
-use w3b2_connector::storage::Storage;
+use crate::cli::{SnapshotAction, SnapshotCmd};
+use w3b2_connector::{
+    events::{EventCursor, EventEnvelope},
+    storage::{Snapshot, Storage, StorageError},
+};
+
+/// Tree holding every stored event, keyed by `event_key`.
+const EVENTS_TREE: &str = "events";
+/// Index from `{signature}:{index}` to the event's key.
+const EVENTS_BY_SIG_TREE: &str = "events_by_sig";
+/// Index from `{pubkey}:{event_key}` to the event's key.
+const EVENTS_BY_PUBKEY_TREE: &str = "events_by_pubkey";
+/// Durable subscriber cursors, keyed by subscriber id.
+const CURSORS_TREE: &str = "cursors";
+/// Events a stream failed to deliver, keyed by `{subscriber}:{event_key}`.
+const DEAD_LETTERS_TREE: &str = "dead_letters";
+
+/// Maps a `sled` error onto the backend-agnostic `StorageError`.
+fn storage_error(e: sled::Error) -> StorageError {
+    match e {
+        sled::Error::Io(e) => StorageError::Io(e),
+        sled::Error::Corruption { .. } => StorageError::corrupted(e),
+        e => StorageError::Backend(e.to_string()),
+    }
+}
+
+/// Builds the primary key of an event. The zero-padded slot makes the
+/// lexicographic key order match chronological order.
+fn event_key(slot: u64, signature: &str, index: u32) -> String {
+    format!("{slot:020}:{signature}:{index:010}")
+}
+
+/// Why an event did not reach a subscriber's stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// The client did not read its stream within the slow-consumer timeout.
+    SlowConsumer,
+    /// The client disconnected before the event was sent.
+    Disconnected,
+}
+
+/// An event a subscriber's stream failed to deliver.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub envelope: EventEnvelope,
+    pub reason: DeadLetterReason,
+    /// When delivery failed, as a Unix timestamp.
+    pub recorded_at: i64,
+}
+
+impl DeadLetter {
+    fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.reason {
+            DeadLetterReason::SlowConsumer => 0u8,
+            DeadLetterReason::Disconnected => 1u8,
+        };
+        let mut bytes = vec![reason];
+        bytes.extend_from_slice(&self.recorded_at.to_le_bytes());
+        bytes.extend_from_slice(&self.envelope.to_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, StorageError> {
+        let corrupted = || StorageError::corrupted("truncated dead letter");
+        let (&reason, rest) = bytes.split_first().ok_or_else(corrupted)?;
+        let reason = match reason {
+            0 => DeadLetterReason::SlowConsumer,
+            1 => DeadLetterReason::Disconnected,
+            other => {
+                return Err(StorageError::corrupted(format!(
+                    "unknown dead letter reason {other}"
+                )));
+            }
+        };
+        let (recorded_at, envelope) = rest.split_first_chunk::<8>().ok_or_else(corrupted)?;
+        Ok(Self {
+            envelope: EventEnvelope::from_bytes(envelope).map_err(StorageError::corrupted)?,
+            reason,
+            recorded_at: i64::from_le_bytes(*recorded_at),
+        })
+    }
+}
 
 /// A `sled`-backed implementation of the `Storage` trait.
 ///
 /// It uses a single `sled` database to transactionally store the `last_slot`
-/// and `last_sig` processed by the synchronizer.
+/// and `last_sig` processed by the synchronizer, plus the event history with
+/// secondary indexes by signature and by pubkey.
 #[derive(Clone)]
 pub struct SledStorage {
     db: Db,
@@ -24,16 +106,181 @@ impl SledStorage {
     pub fn new(db: Db) -> Self {
         Self { db }
     }
+
+    /// Loads the events whose primary keys are the values yielded by `keys`.
+    fn load_events(
+        &self,
+        keys: impl Iterator<Item = sled::Result<(sled::IVec, sled::IVec)>>,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let mut envelopes = Vec::new();
+        for entry in keys {
+            let (_, key) = entry.map_err(storage_error)?;
+            if let Some(bytes) = events.get(key).map_err(storage_error)? {
+                envelopes.push(EventEnvelope::from_bytes(&bytes).map_err(StorageError::corrupted)?);
+            }
+        }
+        Ok(envelopes)
+    }
+
+    /// Records an event `subscriber`'s stream failed to deliver. Recording the
+    /// same event again replaces the earlier record.
+    pub fn record_dead_letter(
+        &self,
+        subscriber: &Pubkey,
+        letter: &DeadLetter,
+    ) -> Result<(), StorageError> {
+        self.record_dead_letters(subscriber, std::slice::from_ref(letter))
+    }
+
+    /// Records several of `subscriber`'s undelivered events in one batch.
+    pub fn record_dead_letters(
+        &self,
+        subscriber: &Pubkey,
+        letters: &[DeadLetter],
+    ) -> Result<(), StorageError> {
+        let mut batch = sled::Batch::default();
+        for letter in letters {
+            let envelope = &letter.envelope;
+            let key = event_key(envelope.slot, &envelope.signature, envelope.index);
+            batch.insert(format!("{subscriber}:{key}").as_bytes(), letter.to_bytes());
+        }
+        self.db
+            .open_tree(DEAD_LETTERS_TREE)
+            .and_then(|tree| tree.apply_batch(batch))
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` of `subscriber`'s dead letters, oldest first.
+    pub fn dead_letters(
+        &self,
+        subscriber: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, StorageError> {
+        let tree = self
+            .db
+            .open_tree(DEAD_LETTERS_TREE)
+            .map_err(storage_error)?;
+        tree.scan_prefix(format!("{subscriber}:"))
+            .values()
+            .take(limit)
+            .map(|bytes| DeadLetter::from_bytes(&bytes.map_err(storage_error)?))
+            .collect()
+    }
+
+    /// Deletes `subscriber`'s dead letters up to and including the event at
+    /// `through`. Returns the number of dead letters removed.
+    pub fn ack_dead_letters(
+        &self,
+        subscriber: &Pubkey,
+        through: &EventCursor,
+    ) -> Result<usize, StorageError> {
+        let tree = self
+            .db
+            .open_tree(DEAD_LETTERS_TREE)
+            .map_err(storage_error)?;
+        let start = format!("{subscriber}:");
+        let end = format!(
+            "{subscriber}:{}",
+            event_key(through.slot, &through.signature, through.index)
+        );
+        let keys = tree
+            .range(start.as_bytes()..=end.as_bytes())
+            .keys()
+            .collect::<sled::Result<Vec<_>>>()
+            .map_err(storage_error)?;
+        for key in &keys {
+            tree.remove(key).map_err(storage_error)?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Deletes the dead letters recorded before `recorded_before` (a Unix
+    /// timestamp), then the oldest ones of every subscriber holding more than
+    /// `max_per_subscriber`. Returns the number of dead letters removed.
+    pub fn prune_dead_letters(
+        &self,
+        recorded_before: i64,
+        max_per_subscriber: usize,
+    ) -> Result<usize, StorageError> {
+        let tree = self
+            .db
+            .open_tree(DEAD_LETTERS_TREE)
+            .map_err(storage_error)?;
+        // Keys are `{subscriber}:{event_key}`, so each subscriber's dead
+        // letters are contiguous and oldest first.
+        let mut expired = Vec::new();
+        let mut kept: Vec<(Vec<u8>, Vec<sled::IVec>)> = Vec::new();
+        for entry in tree.iter() {
+            let (key, bytes) = entry.map_err(storage_error)?;
+            if DeadLetter::from_bytes(&bytes)?.recorded_at < recorded_before {
+                expired.push(key);
+                continue;
+            }
+            let subscriber = key.split(|&b| b == b':').next().unwrap_or_default();
+            match kept.last_mut() {
+                Some((last, keys)) if last.as_slice() == subscriber => keys.push(key),
+                _ => kept.push((subscriber.to_vec(), vec![key])),
+            }
+        }
+        let excess = kept.into_iter().flat_map(|(_, keys)| {
+            let excess = keys.len().saturating_sub(max_per_subscriber);
+            keys.into_iter().take(excess)
+        });
+
+        let mut batch = sled::Batch::default();
+        let mut removed = 0;
+        for key in expired.into_iter().chain(excess) {
+            batch.remove(key);
+            removed += 1;
+        }
+        tree.apply_batch(batch).map_err(storage_error)?;
+        Ok(removed)
+    }
+
+    /// Removes the events at the given primary keys together with their index entries.
+    fn remove_events(&self, keys: Vec<sled::IVec>) -> Result<usize, StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let by_sig = self
+            .db
+            .open_tree(EVENTS_BY_SIG_TREE)
+            .map_err(storage_error)?;
+        let by_pubkey = self
+            .db
+            .open_tree(EVENTS_BY_PUBKEY_TREE)
+            .map_err(storage_error)?;
+
+        let mut removed = 0;
+        for key in keys {
+            let Some(bytes) = events.remove(&key).map_err(storage_error)? else {
+                continue;
+            };
+            let envelope = EventEnvelope::from_bytes(&bytes).map_err(StorageError::corrupted)?;
+            let key = String::from_utf8_lossy(&key);
+            by_sig
+                .remove(format!("{}:{:010}", envelope.signature, envelope.index).as_bytes())
+                .map_err(storage_error)?;
+            for pubkey in envelope.pubkeys() {
+                by_pubkey
+                    .remove(format!("{pubkey}:{key}").as_bytes())
+                    .map_err(storage_error)?;
+            }
+            removed += 1;
+        }
+        Ok(removed)
+    }
 }
 
 #[async_trait]
 impl Storage for SledStorage {
     /// Retrieves the last synchronized slot number from the database.
     /// Returns 0 if no slot has been stored yet.
-    async fn get_last_slot(&self) -> Result<u64> {
+    async fn get_last_slot(&self) -> Result<u64, StorageError> {
         let result = self
             .db
-            .get("sync::last_slot")?
+            .get("sync::last_slot")
+            .map_err(storage_error)?
             .and_then(|v| String::from_utf8(v.to_vec()).ok())
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(0);
@@ -42,27 +289,235 @@ impl Storage for SledStorage {
 
     /// Retrieves the last synchronized signature from the database.
     /// Returns `None` if no signature has been stored yet.
-    async fn get_last_sig(&self) -> Result<Option<String>> {
+    async fn get_last_sig(&self) -> Result<Option<String>, StorageError> {
         let result = self
             .db
-            .get("sync::last_sig")?
+            .get("sync::last_sig")
+            .map_err(storage_error)?
             .and_then(|v| String::from_utf8(v.to_vec()).ok());
         Ok(result)
     }
 
     /// Atomically sets the last synchronized slot and signature using a `sled` transaction.
     /// This ensures that the sync state is always consistent.
-    async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<()> {
-        self.db.transaction(
-            |tx: &TransactionalTree| -> Result<(), sled::transaction::ConflictableTransactionError<()>> {
-                tx.insert("sync::last_slot", slot.to_string().as_bytes())?;
-                tx.insert("sync::last_sig", sig.as_bytes())?;
-                Ok(())
-            },
-        ).map_err(|e| anyhow!("Sled transaction for sync state failed: {:?}", e))?;
+    async fn set_sync_state(&self, slot: u64, sig: &str) -> Result<(), StorageError> {
+        self.db
+            .transaction(
+                |tx: &TransactionalTree| -> Result<(), sled::transaction::ConflictableTransactionError<()>> {
+                    tx.insert("sync::last_slot", slot.to_string().as_bytes())?;
+                    tx.insert("sync::last_sig", sig.as_bytes())?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| {
+                StorageError::Backend(format!("Sled transaction for sync state failed: {:?}", e))
+            })?;
 
-        self.db.flush_async().await?;
+        self.db.flush_async().await.map_err(storage_error)?;
 
         Ok(())
     }
+
+    /// Stores the event and its index entries in a single transaction.
+    async fn append_event(&self, envelope: &EventEnvelope) -> Result<(), StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let by_sig = self
+            .db
+            .open_tree(EVENTS_BY_SIG_TREE)
+            .map_err(storage_error)?;
+        let by_pubkey = self
+            .db
+            .open_tree(EVENTS_BY_PUBKEY_TREE)
+            .map_err(storage_error)?;
+
+        let key = event_key(envelope.slot, &envelope.signature, envelope.index);
+        let value = envelope.to_bytes();
+        let pubkeys = envelope.pubkeys();
+
+        (&events, &by_sig, &by_pubkey)
+            .transaction(|(events, by_sig, by_pubkey)| {
+                events.insert(key.as_bytes(), value.as_slice())?;
+                by_sig.insert(
+                    format!("{}:{:010}", envelope.signature, envelope.index).as_bytes(),
+                    key.as_bytes(),
+                )?;
+                for pubkey in &pubkeys {
+                    by_pubkey.insert(format!("{pubkey}:{key}").as_bytes(), key.as_bytes())?;
+                }
+                Ok::<_, sled::transaction::ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| {
+                StorageError::Backend(format!("Sled transaction for event append failed: {:?}", e))
+            })?;
+
+        Ok(())
+    }
+
+    async fn events_by_slot_range(
+        &self,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let start = format!("{from_slot:020}:");
+        // ';' sorts right after ':', so this bound includes every event in `to_slot`.
+        let end = format!("{to_slot:020};");
+        let mut envelopes = Vec::new();
+        for entry in events.range(start.as_bytes()..end.as_bytes()) {
+            let (_, bytes) = entry.map_err(storage_error)?;
+            envelopes.push(EventEnvelope::from_bytes(&bytes).map_err(StorageError::corrupted)?);
+        }
+        Ok(envelopes)
+    }
+
+    async fn events_by_signature(
+        &self,
+        signature: &str,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let by_sig = self
+            .db
+            .open_tree(EVENTS_BY_SIG_TREE)
+            .map_err(storage_error)?;
+        self.load_events(by_sig.scan_prefix(format!("{signature}:").as_bytes()))
+    }
+
+    async fn events_by_pubkey(
+        &self,
+        pubkey: &Pubkey,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let by_pubkey = self
+            .db
+            .open_tree(EVENTS_BY_PUBKEY_TREE)
+            .map_err(storage_error)?;
+        let start = format!("{pubkey}:{from_slot:020}:");
+        let end = format!("{pubkey}:{to_slot:020};");
+        self.load_events(by_pubkey.range(start.as_bytes()..end.as_bytes()))
+    }
+
+    async fn recent_events_by_pubkey(
+        &self,
+        pubkey: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        let by_pubkey = self
+            .db
+            .open_tree(EVENTS_BY_PUBKEY_TREE)
+            .map_err(storage_error)?;
+        let prefix = format!("{pubkey}:");
+        let mut events = self.load_events(by_pubkey.scan_prefix(prefix).rev().take(limit))?;
+        events.reverse();
+        Ok(events)
+    }
+
+    async fn prune_events_before(&self, before_slot: u64) -> Result<usize, StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let end = format!("{before_slot:020}:");
+        let keys = events
+            .range(..end.as_bytes())
+            .keys()
+            .collect::<sled::Result<Vec<_>>>()
+            .map_err(storage_error)?;
+        let removed = self.remove_events(keys)?;
+        self.db.flush_async().await.map_err(storage_error)?;
+        Ok(removed)
+    }
+
+    async fn remove_events_by_signature(&self, signature: &str) -> Result<usize, StorageError> {
+        let by_sig = self
+            .db
+            .open_tree(EVENTS_BY_SIG_TREE)
+            .map_err(storage_error)?;
+        let keys = by_sig
+            .scan_prefix(format!("{signature}:").as_bytes())
+            .values()
+            .collect::<sled::Result<Vec<_>>>()
+            .map_err(storage_error)?;
+        let removed = self.remove_events(keys)?;
+        self.db.flush_async().await.map_err(storage_error)?;
+        Ok(removed)
+    }
+
+    async fn prune_events_to_count(&self, max_events: usize) -> Result<usize, StorageError> {
+        let events = self.db.open_tree(EVENTS_TREE).map_err(storage_error)?;
+        let excess = events.len().saturating_sub(max_events);
+        let keys = events
+            .iter()
+            .keys()
+            .take(excess)
+            .collect::<sled::Result<Vec<_>>>()
+            .map_err(storage_error)?;
+        let removed = self.remove_events(keys)?;
+        self.db.flush_async().await.map_err(storage_error)?;
+        Ok(removed)
+    }
+
+    async fn get_cursor(&self, subscriber_id: &str) -> Result<Option<EventCursor>, StorageError> {
+        let cursors = self.db.open_tree(CURSORS_TREE).map_err(storage_error)?;
+        cursors
+            .get(subscriber_id.as_bytes())
+            .map_err(storage_error)?
+            .map(|bytes| EventCursor::from_bytes(&bytes).map_err(StorageError::corrupted))
+            .transpose()
+    }
+
+    async fn set_cursor(
+        &self,
+        subscriber_id: &str,
+        cursor: &EventCursor,
+    ) -> Result<(), StorageError> {
+        let cursors = self.db.open_tree(CURSORS_TREE).map_err(storage_error)?;
+        cursors
+            .insert(subscriber_id.as_bytes(), cursor.to_bytes())
+            .map_err(storage_error)?;
+        cursors.flush_async().await.map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn cursors(&self) -> Result<Vec<(String, EventCursor)>, StorageError> {
+        let cursors = self.db.open_tree(CURSORS_TREE).map_err(storage_error)?;
+        let mut result = Vec::new();
+        for entry in cursors.iter() {
+            let (id, bytes) = entry.map_err(storage_error)?;
+            result.push((
+                String::from_utf8_lossy(&id).into_owned(),
+                EventCursor::from_bytes(&bytes).map_err(StorageError::corrupted)?,
+            ));
+        }
+        Ok(result)
+    }
+
+    async fn flush(&self) -> Result<(), StorageError> {
+        self.db.flush_async().await.map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+/// Runs the `snapshot` subcommand against `storage` and returns the report to print.
+pub async fn snapshot(storage: &dyn Storage, cmd: &SnapshotCmd) -> anyhow::Result<String> {
+    match &cmd.action {
+        SnapshotAction::Export { file, events } => {
+            let snapshot = storage.export_snapshot(*events).await?;
+            tokio::fs::write(file, snapshot.to_bytes()).await?;
+            Ok(format!(
+                "Exported slot {} with {} cursors and {} events to {}",
+                snapshot.last_slot,
+                snapshot.cursors.len(),
+                snapshot.events.len(),
+                file
+            ))
+        }
+        SnapshotAction::Import { file } => {
+            let snapshot = Snapshot::from_bytes(&tokio::fs::read(file).await?)?;
+            storage.import_snapshot(&snapshot).await?;
+            Ok(format!(
+                "Imported slot {} with {} cursors and {} events from {}",
+                snapshot.last_slot,
+                snapshot.cursors.len(),
+                snapshot.events.len(),
+                file
+            ))
+        }
+    }
 }
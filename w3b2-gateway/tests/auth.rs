@@ -0,0 +1,93 @@
+use jsonwebtoken::{EncodingKey, Header, encode};
+use solana_sdk::pubkey::Pubkey;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tonic::{Code, Request, service::Interceptor};
+use w3b2_gateway::config::AuthConfig;
+use w3b2_gateway::grpc::auth::{ANY_PUBKEY, AuthScope, Authenticator, Claims};
+
+const SECRET: &str = "test-secret";
+
+fn auth_config() -> AuthConfig {
+    AuthConfig {
+        jwt_secret: SECRET.to_string(),
+        issuer: None,
+        audience: None,
+    }
+}
+
+fn token(secret: &str, pubkeys: &[&str], expires_in: i64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let claims = Claims {
+        sub: "wallet-app".to_string(),
+        exp: (now + expires_in) as u64,
+        pubkeys: pubkeys.iter().map(|key| key.to_string()).collect(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn request_with(token: &str) -> Request<()> {
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    request
+}
+
+#[test]
+fn test_token_scopes_the_request_to_its_pubkeys() {
+    let allowed = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let mut authenticator = Authenticator::new(Some(&auth_config()));
+
+    let request = authenticator
+        .call(request_with(&token(SECRET, &[&allowed.to_string()], 60)))
+        .unwrap();
+    let scope = AuthScope::of(&request);
+
+    assert!(scope.check(&allowed).is_ok());
+    assert!(scope.check(&other).is_err());
+}
+
+#[test]
+fn test_wildcard_token_is_unrestricted() {
+    let mut authenticator = Authenticator::new(Some(&auth_config()));
+
+    let request = authenticator
+        .call(request_with(&token(SECRET, &[ANY_PUBKEY], 60)))
+        .unwrap();
+
+    assert!(AuthScope::of(&request).permits(&Pubkey::new_unique()));
+}
+
+#[test]
+fn test_rejects_missing_forged_and_expired_tokens() {
+    let pubkey = Pubkey::new_unique().to_string();
+    let mut authenticator = Authenticator::new(Some(&auth_config()));
+
+    let missing = authenticator.call(Request::new(())).unwrap_err();
+    assert_eq!(missing.code(), Code::Unauthenticated);
+
+    let forged = authenticator
+        .call(request_with(&token("other-secret", &[&pubkey], 60)))
+        .unwrap_err();
+    assert_eq!(forged.code(), Code::Unauthenticated);
+
+    let expired = authenticator
+        .call(request_with(&token(SECRET, &[&pubkey], -3600)))
+        .unwrap_err();
+    assert_eq!(expired.code(), Code::Unauthenticated);
+}
+
+#[test]
+fn test_disabled_authentication_lets_every_call_through() {
+    let mut authenticator = Authenticator::new(None);
+
+    let request = authenticator.call(Request::new(())).unwrap();
+
+    assert!(AuthScope::of(&request).permits(&Pubkey::new_unique()));
+}
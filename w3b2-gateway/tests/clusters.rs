@@ -0,0 +1,57 @@
+use std::io::Write;
+use w3b2_gateway::config::{DEFAULT_CLUSTER, GatewayConfig, load_config};
+
+fn load(toml: &str) -> anyhow::Result<GatewayConfig> {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    file.write_all(toml.as_bytes()).unwrap();
+    load_config(file.path().to_str().unwrap())
+}
+
+#[test]
+fn test_named_clusters_have_their_own_connector_settings() {
+    let config = load(
+        r#"
+        [connector.solana]
+        rpc-url = "http://127.0.0.1:8899"
+        ws-url = "ws://127.0.0.1:8900"
+        commitment = "Confirmed"
+
+        [clusters.devnet.solana]
+        rpc-url = "https://api.devnet.solana.com"
+        ws-url = "wss://api.devnet.solana.com"
+        commitment = "Finalized"
+
+        [gateway]
+        db-path = "./gateway.db"
+        "#,
+    )
+    .unwrap();
+
+    let clusters: Vec<_> = config.all_clusters().collect();
+    assert_eq!(clusters.len(), 2);
+    assert_eq!(clusters[0].0, DEFAULT_CLUSTER);
+    assert_eq!(clusters[0].1.solana.rpc_url, "http://127.0.0.1:8899");
+    assert_eq!(clusters[1].0, "devnet");
+    assert_eq!(clusters[1].1.solana.rpc_url, "https://api.devnet.solana.com");
+
+    assert_eq!(config.cluster_db_path(DEFAULT_CLUSTER), "./gateway.db");
+    assert_eq!(config.cluster_db_path("devnet"), "./gateway.db-devnet");
+}
+
+#[test]
+fn test_reserved_and_invalid_cluster_names_are_rejected() {
+    let cluster = |name: &str| {
+        format!(
+            r#"
+            [clusters."{name}".solana]
+            rpc-url = "https://api.devnet.solana.com"
+            ws-url = "wss://api.devnet.solana.com"
+            commitment = "Confirmed"
+            "#
+        )
+    };
+
+    assert!(load(&cluster(DEFAULT_CLUSTER)).is_err());
+    assert!(load(&cluster("dev net")).is_err());
+    assert!(load(&cluster("staging")).is_ok());
+}
@@ -0,0 +1,28 @@
+// tests/common/mod.rs
+
+//! Event fixtures shared by the gateway's tests.
+
+// Each test binary uses only a subset of these shared helpers.
+#![allow(dead_code)]
+
+use solana_sdk::pubkey::Pubkey;
+use w3b2_bridge_program::events::OffChainActionLogged;
+use w3b2_connector::events::{BridgeEvent, EventEnvelope};
+
+/// Returns an off-chain action by `actor` as event `index` of transaction
+/// `signature`, at `slot`.
+pub fn action(actor: Pubkey, slot: u64, signature: &str, index: u32) -> EventEnvelope {
+    EventEnvelope {
+        slot,
+        signature: signature.to_string(),
+        index,
+        tx_index: None,
+        block_time: None,
+        event: BridgeEvent::OffChainActionLogged(OffChainActionLogged {
+            actor,
+            session_id: slot,
+            action_code: 200,
+            ts: 0,
+        }),
+    }
+}
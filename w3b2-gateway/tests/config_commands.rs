@@ -0,0 +1,57 @@
+use w3b2_gateway::cli::{CheckConfigCmd, InitConfigCmd};
+use w3b2_gateway::config::{check_config, init_config};
+
+#[test]
+fn test_init_config_writes_a_valid_config_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir
+        .path()
+        .join("gateway.toml")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let cmd = InitConfigCmd {
+        path: path.clone(),
+        force: false,
+    };
+
+    init_config(&cmd).unwrap();
+    assert!(init_config(&cmd).is_err());
+    assert!(
+        init_config(&InitConfigCmd {
+            path: path.clone(),
+            force: true,
+        })
+        .is_ok()
+    );
+
+    let report = check_config(&CheckConfigCmd { config: path }).unwrap();
+    assert!(report.contains("rpc-url = \"http://127.0.0.1:8899\""));
+    assert!(report.contains("[gateway.grpc]"));
+}
+
+#[test]
+fn test_check_config_redacts_secrets_and_reports_invalid_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("gateway.toml");
+    std::fs::write(
+        &path,
+        "[gateway.auth]\njwt-secret = \"hunter2\"\n[gateway]\ndb-path = \"./gateway.db\"\n",
+    )
+    .unwrap();
+    let config = path.to_str().unwrap().to_string();
+
+    let report = check_config(&CheckConfigCmd {
+        config: config.clone(),
+    })
+    .unwrap();
+    assert!(report.contains("jwt-secret = \"<redacted>\""));
+    assert!(!report.contains("hunter2"));
+
+    std::fs::write(
+        &path,
+        "[connector.solana]\nrpc-url = \"ftp://node\"\nws-url = \"ws://node\"\ncommitment = \"Confirmed\"\n",
+    )
+    .unwrap();
+    assert!(check_config(&CheckConfigCmd { config }).is_err());
+}
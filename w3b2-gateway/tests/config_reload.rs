@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tracing::level_filters::LevelFilter;
+use w3b2_connector::config::RateLimit;
+use w3b2_gateway::config::{GatewayConfig, load_config};
+use w3b2_gateway::reload::{ConfigReloader, merge_reloadable};
+
+const CONFIG: &str = r#"
+[connector.solana]
+rpc-url = "http://127.0.0.1:8899"
+ws-url = "ws://127.0.0.1:8900"
+commitment = "Confirmed"
+
+[gateway]
+db-path = "./gateway.db"
+
+[gateway.log]
+level = "info"
+format = "plain"
+output = "stdout"
+"#;
+
+#[test]
+fn test_merge_takes_only_the_reloadable_settings() {
+    let current = GatewayConfig::default();
+    let mut new = current.clone();
+    new.gateway.log.level = "debug".to_string();
+    new.gateway.streaming.output_stream_capacity = 7;
+    new.gateway.streaming.broadcast_capacity = 7;
+    new.gateway.db_path = "./elsewhere.db".to_string();
+    new.connector.solana.rate_limit = Some(RateLimit {
+        requests_per_second: 5,
+        burst: None,
+    });
+
+    let merged = merge_reloadable(&current, &new);
+
+    assert_eq!(merged.gateway.log.level, "debug");
+    assert_eq!(merged.gateway.streaming.output_stream_capacity, 7);
+    assert_eq!(
+        merged.connector.solana.rate_limit,
+        new.connector.solana.rate_limit
+    );
+    // The broadcast channel and the database are set up once at startup.
+    assert_eq!(
+        merged.gateway.streaming.broadcast_capacity,
+        current.gateway.streaming.broadcast_capacity
+    );
+    assert_eq!(merged.gateway.db_path, current.gateway.db_path);
+}
+
+#[test]
+fn test_reload_publishes_the_new_config_and_log_level() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    file.write_all(CONFIG.as_bytes()).unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let (config_tx, config_rx) = watch::channel(Arc::new(load_config(&path).unwrap()));
+    let levels = Arc::new(Mutex::new(Vec::new()));
+    let reloader = {
+        let levels = levels.clone();
+        ConfigReloader::new(
+            path.clone(),
+            config_tx,
+            move |level| levels.lock().unwrap().push(level),
+            HashMap::new(),
+        )
+    };
+
+    std::fs::write(
+        &path,
+        CONFIG.replace("level = \"info\"", "level = \"debug\""),
+    )
+    .unwrap();
+    reloader.reload().unwrap();
+
+    assert_eq!(config_rx.borrow().gateway.log.level, "debug");
+    assert_eq!(*levels.lock().unwrap(), vec![LevelFilter::DEBUG]);
+
+    // A broken file keeps the running configuration.
+    std::fs::write(&path, "[gateway\n").unwrap();
+    assert!(reloader.reload().is_err());
+    assert_eq!(config_rx.borrow().gateway.log.level, "debug");
+}
+
+#[test]
+fn test_reload_refuses_a_zero_stream_capacity() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    file.write_all(CONFIG.as_bytes()).unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let (config_tx, config_rx) = watch::channel(Arc::new(load_config(&path).unwrap()));
+    let reloader = ConfigReloader::new(path.clone(), config_tx, |_| {}, HashMap::new());
+
+    let streaming = r#"
+[gateway.streaming]
+broadcast-capacity = 4096
+command-capacity = 256
+listener-channel-capacity = 1024
+output-stream-capacity = 0
+service-listener-capacity = 256
+"#;
+    std::fs::write(&path, format!("{}{}", CONFIG, streaming)).unwrap();
+    let error = reloader.reload().unwrap_err();
+
+    assert!(format!("{:#}", error).contains("output-stream-capacity must be greater than zero"));
+    assert_eq!(
+        config_rx.borrow().gateway.streaming.output_stream_capacity,
+        1024
+    );
+}
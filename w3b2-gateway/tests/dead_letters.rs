@@ -0,0 +1,191 @@
+mod common;
+
+use common::action;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_stream::StreamExt;
+use w3b2_connector::events::{EventCursor, EventEnvelope};
+use w3b2_gateway::grpc::delivery::{self, DeadLetters};
+use w3b2_gateway::storage::{DeadLetter, DeadLetterReason, SledStorage};
+
+fn storage() -> Arc<SledStorage> {
+    Arc::new(SledStorage::new(
+        sled::Config::new().temporary(true).open().unwrap(),
+    ))
+}
+
+fn recorded(storage: &SledStorage, subscriber: &Pubkey) -> Vec<(u64, DeadLetterReason)> {
+    storage
+        .dead_letters(subscriber, usize::MAX)
+        .unwrap()
+        .into_iter()
+        .map(|letter| (letter.envelope.slot, letter.reason))
+        .collect()
+}
+
+#[test]
+fn test_dead_letters_are_kept_per_subscriber_until_acknowledged() {
+    let storage = storage();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    for (subscriber, slot) in [(alice, 30), (alice, 10), (bob, 20), (alice, 20)] {
+        let letter = DeadLetter {
+            envelope: EventEnvelope {
+                block_time: Some(1_700_000_000),
+                ..action(subscriber, slot, "sig", 0)
+            },
+            reason: DeadLetterReason::SlowConsumer,
+            recorded_at: 42,
+        };
+        storage.record_dead_letter(&subscriber, &letter).unwrap();
+    }
+
+    let letters = storage.dead_letters(&alice, 2).unwrap();
+    assert_eq!(
+        letters.iter().map(|l| l.envelope.slot).collect::<Vec<_>>(),
+        vec![10, 20]
+    );
+    assert_eq!(letters[0].recorded_at, 42);
+    assert_eq!(letters[0].envelope.block_time, Some(1_700_000_000));
+
+    let through = letters[1].envelope.cursor();
+    assert_eq!(storage.ack_dead_letters(&alice, &through).unwrap(), 2);
+    assert_eq!(
+        recorded(&storage, &alice),
+        vec![(30, DeadLetterReason::SlowConsumer)]
+    );
+    assert_eq!(
+        recorded(&storage, &bob),
+        vec![(20, DeadLetterReason::SlowConsumer)]
+    );
+
+    let all = EventCursor {
+        slot: u64::MAX,
+        signature: String::new(),
+        index: 0,
+    };
+    assert_eq!(storage.ack_dead_letters(&alice, &all).unwrap(), 1);
+    assert!(recorded(&storage, &alice).is_empty());
+}
+
+#[test]
+fn test_pruning_drops_expired_dead_letters_and_caps_each_subscriber() {
+    let storage = storage();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let letters = |subscriber, recorded: &[(u64, i64)]| {
+        let letters = recorded
+            .iter()
+            .map(|&(slot, recorded_at)| DeadLetter {
+                envelope: action(subscriber, slot, "sig", 0),
+                reason: DeadLetterReason::Disconnected,
+                recorded_at,
+            })
+            .collect::<Vec<_>>();
+        storage.record_dead_letters(&subscriber, &letters).unwrap();
+    };
+    letters(alice, &[(1, 100), (2, 200), (3, 200), (4, 200), (5, 200)]);
+    letters(bob, &[(1, 100), (2, 200)]);
+
+    // Alice's first letter expired; of the rest, only her newest three are kept.
+    assert_eq!(storage.prune_dead_letters(150, 3).unwrap(), 3);
+    let slots = |subscriber| {
+        recorded(&storage, subscriber)
+            .into_iter()
+            .map(|(slot, _)| slot)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(slots(&alice), vec![3, 4, 5]);
+    assert_eq!(slots(&bob), vec![2]);
+    assert_eq!(storage.prune_dead_letters(150, 3).unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_undelivered_events_become_dead_letters() {
+    let storage = storage();
+    let alice = Pubkey::new_unique();
+    let (tx, mut rx) = delivery::channel(
+        1,
+        Duration::from_millis(20),
+        DeadLetters::new(storage.clone(), alice),
+    );
+
+    // The client reads nothing: the second event waits out the timeout.
+    assert!(tx.send(action(alice, 1, "a", 0), "first"));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(tx.send(action(alice, 2, "b", 0), "second"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        recorded(&storage, &alice),
+        vec![(2, DeadLetterReason::SlowConsumer)]
+    );
+    assert_eq!(rx.next().await.unwrap().unwrap(), "first");
+
+    // The client disconnects with an event still buffered.
+    assert!(tx.send(action(alice, 3, "c", 0), "third"));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(rx);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!tx.send(action(alice, 4, "d", 0), "fourth"));
+    tx.undelivered(action(alice, 5, "e", 0));
+    // Dead letters are written on a blocking thread.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(
+        recorded(&storage, &alice),
+        vec![
+            (2, DeadLetterReason::SlowConsumer),
+            (3, DeadLetterReason::Disconnected),
+            (4, DeadLetterReason::Disconnected),
+            (5, DeadLetterReason::Disconnected),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_a_stalled_client_does_not_make_the_listener_lag() {
+    let storage = storage();
+    let alice = Pubkey::new_unique();
+    let (tx, mut rx) = delivery::channel(
+        2,
+        Duration::from_millis(20),
+        DeadLetters::new(storage.clone(), alice),
+    );
+
+    // Forward a listener channel to the client, as the event streams do.
+    let (events_tx, mut events_rx) = broadcast::channel(4);
+    let forwarder = tokio::spawn(async move {
+        let mut lagged = 0;
+        loop {
+            match events_rx.recv().await {
+                Ok(envelope) => {
+                    tx.send(envelope, ());
+                }
+                Err(RecvError::Lagged(n)) => lagged += n,
+                Err(RecvError::Closed) => return lagged,
+            }
+        }
+    });
+
+    // The client stalls for many more events than the listener channel holds.
+    for slot in 1..=20 {
+        events_tx.send(action(alice, slot, "sig", 0)).unwrap();
+        tokio::task::yield_now().await;
+    }
+    drop(events_tx);
+    assert_eq!(forwarder.await.unwrap(), 0);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Every event either reached the stream or was recorded.
+    let delivered = delivery::ready_items(&mut rx).await.len() as u64;
+    let dead_letters = recorded(&storage, &alice);
+    assert_eq!(delivered + dead_letters.len() as u64, 20);
+    assert!(
+        dead_letters
+            .iter()
+            .all(|(_, reason)| *reason == DeadLetterReason::SlowConsumer)
+    );
+    assert_eq!(dead_letters.last().unwrap().0, 20);
+}
@@ -0,0 +1,50 @@
+use clap::Parser;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+use w3b2_gateway::cli::{Cli, Commands, ProfileKind};
+use w3b2_gateway::dev;
+
+fn parse(args: &[&str]) -> Commands {
+    Cli::try_parse_from(std::iter::once("w3b2-gateway").chain(args.iter().copied()))
+        .unwrap()
+        .command
+}
+
+#[test]
+fn test_dev_commands_parse() {
+    let pubkey = Pubkey::new_unique().to_string();
+
+    let Commands::Airdrop(cmd) = parse(&["airdrop", &pubkey, "--sol", "1.5"]) else {
+        panic!("expected an airdrop command");
+    };
+    assert_eq!(cmd.lamports, 1_500_000_000);
+    assert!(Cli::try_parse_from(["w3b2-gateway", "airdrop", &pubkey, "--sol", "x"]).is_err());
+
+    let Commands::Profile(cmd) = parse(&["profile", "user", &pubkey, "--admin", &pubkey]) else {
+        panic!("expected a profile command");
+    };
+    assert!(matches!(cmd.kind, ProfileKind::User { .. }));
+}
+
+#[tokio::test]
+async fn test_dev_airdrop_and_balance() {
+    let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+    let pubkey = Pubkey::new_unique().to_string();
+
+    let Commands::Airdrop(cmd) = parse(&["airdrop", &pubkey]) else {
+        panic!("expected an airdrop command");
+    };
+    let report = dev::airdrop(&rpc_client, &cmd).await.unwrap();
+    assert!(report.starts_with(&format!("Airdropped ◎1.000000000 to {}", pubkey)));
+
+    // The mock reports a balance of 50 lamports for every account.
+    let Commands::Balance(cmd) = parse(&["balance", &pubkey]) else {
+        panic!("expected a balance command");
+    };
+    let report = dev::balance(rpc_client, &cmd).await.unwrap();
+    assert_eq!(
+        report,
+        format!("Balance of {}: ◎0.000000050 (50 lamports)", pubkey)
+    );
+}
@@ -0,0 +1,68 @@
+use std::net::TcpListener;
+use w3b2_connector::config::ConnectorConfig;
+use w3b2_gateway::config::{GatewayConfig, MetricsConfig};
+use w3b2_gateway::doctor::doctor;
+
+fn config(db_path: &str) -> GatewayConfig {
+    let mut config = GatewayConfig {
+        connector: ConnectorConfig::builder()
+            .rpc_url("http://127.0.0.1:1")
+            .ws_url("ws://127.0.0.1:1")
+            .build()
+            .unwrap(),
+        ..Default::default()
+    };
+    config.gateway.db_path = db_path.to_string();
+    config.gateway.grpc.port = portpicker::pick_unused_port().unwrap();
+    config
+}
+
+#[tokio::test]
+async fn test_doctor_reports_unreachable_nodes_with_a_hint() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("db");
+    let report = doctor(&config(db_path.to_str().unwrap())).await;
+
+    let failed: Vec<_> = report
+        .checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| check.name.as_str())
+        .collect();
+    assert_eq!(
+        failed,
+        vec![
+            "cluster 'default': RPC http://127.0.0.1:1",
+            "cluster 'default': WebSocket ws://127.0.0.1:1",
+            &format!("cluster 'default': program {}", w3b2_connector::PROGRAM_ID),
+        ]
+    );
+    assert!(report.checks[0].detail.contains("solana-test-validator"));
+    assert!(report.to_string().ends_with("3 of 5 checks failed."));
+}
+
+#[tokio::test]
+async fn test_doctor_reports_taken_ports() {
+    let dir = tempfile::tempdir().unwrap();
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut config = config(dir.path().join("db").to_str().unwrap());
+    config.gateway.metrics = Some(MetricsConfig {
+        host: "127.0.0.1".to_string(),
+        port: taken.local_addr().unwrap().port(),
+    });
+
+    let report = doctor(&config).await;
+
+    let grpc = report
+        .checks
+        .iter()
+        .find(|c| c.name == "gRPC port")
+        .unwrap();
+    let metrics = report
+        .checks
+        .iter()
+        .find(|c| c.name == "metrics port")
+        .unwrap();
+    assert!(grpc.passed);
+    assert!(!metrics.passed);
+}
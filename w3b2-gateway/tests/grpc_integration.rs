@@ -10,7 +10,10 @@ use solana_sdk::{
 use tempfile::TempDir;
 use tokio::time::{sleep, Duration};
 use tokio_stream::StreamExt;
-use w3b2_bridge_program::state::{AdminProfile, UserProfile};
+use w3b2_bridge_program::{
+    protocols::{PayloadCodec, PayloadHeader},
+    state::{AdminProfile, UserProfile},
+};
 use w3b2_connector::config::ConnectorConfig;
 use w3b2_gateway::{
     config::{GatewayConfig, GatewaySpecificConfig, GrpcConfig, LogConfig, StreamingConfig},
@@ -50,14 +53,20 @@ async fn setup_test_environment() -> TestEnvironment {
     // Create a test-specific configuration.
     let config = GatewayConfig {
         connector: ConnectorConfig::default(),
+        clusters: Default::default(),
         gateway: GatewaySpecificConfig {
             db_path: temp_dir.path().to_str().unwrap().to_string(),
             grpc: GrpcConfig {
                 host: "127.0.0.1".to_string(),
                 port,
+                tls: None,
             },
             streaming: StreamingConfig::default(),
             log: LogConfig::default(),
+            auth: None,
+            metrics: None,
+            health: None,
+            priority_fee: Default::default(),
         },
     };
 
@@ -93,7 +102,7 @@ async fn airdrop_and_confirm(rpc_client: &RpcClient, pubkey: &Pubkey, amount: u6
                 if status.err.is_some() {
                     panic!("Airdrop failed with error: {:?}", status.err);
                 }
-                if status.confirmation_status.as_ref().map_or(false, |s| {
+                if status.confirmation_status.as_ref().is_some_and(|s| {
                     *s == solana_transaction_status::TransactionConfirmationStatus::Finalized
                 }) {
                     break;
@@ -127,6 +136,7 @@ async fn execute_prepared_tx(
     let signed_tx_bytes = bincode::serde::encode_to_vec(&tx, bincode::config::standard()).unwrap();
     let sub_req = SubmitTransactionRequest {
         signed_tx: signed_tx_bytes,
+        versioned: false,
     };
 
     let response = client.submit_transaction(sub_req).await.unwrap();
@@ -150,6 +160,7 @@ async fn test_prepare_and_submit_lifecycle() {
     let prep_req = PrepareAdminRegisterProfileRequest {
         authority_pubkey: admin_authority.pubkey().to_string(),
         communication_pubkey: Pubkey::new_unique().to_string(),
+        options: None,
     };
     let unsigned_tx_resp = client
         .prepare_admin_register_profile(prep_req)
@@ -183,6 +194,7 @@ async fn test_prepare_and_submit_lifecycle() {
             authority_pubkey: user_authority.pubkey().to_string(),
             target_admin_pda: admin_pda.to_string(),
             communication_pubkey: Pubkey::new_unique().to_string(),
+            options: None,
         })
         .await
         .unwrap()
@@ -210,12 +222,13 @@ async fn test_prepare_and_submit_lifecycle() {
     println!("✅ User profile created successfully.");
 
     // Deposit funds
-    let deposit_amount = 1 * LAMPORTS_PER_SOL;
+    let deposit_amount = LAMPORTS_PER_SOL;
     let unsigned_tx_resp = client
         .prepare_user_deposit(PrepareUserDepositRequest {
             authority_pubkey: user_authority.pubkey().to_string(),
             admin_profile_pda: admin_pda.to_string(),
             amount: deposit_amount,
+            options: None,
         })
         .await
         .unwrap()
@@ -255,6 +268,7 @@ async fn test_listen_as_admin_stream() {
     let prep_req = PrepareAdminRegisterProfileRequest {
         authority_pubkey: admin_authority.pubkey().to_string(),
         communication_pubkey: Pubkey::new_unique().to_string(),
+        options: None,
     };
     let unsigned_tx_resp = client
         .prepare_admin_register_profile(prep_req)
@@ -277,6 +291,7 @@ async fn test_listen_as_admin_stream() {
     // === 2. Act: Start listening ===
     let req = ListenAsAdminRequest {
         admin_pubkey: admin_authority.pubkey().to_string(),
+        ..Default::default()
     };
     let mut stream = client.listen_as_admin(req).await.unwrap().into_inner();
     println!("Listening for admin events...");
@@ -291,6 +306,7 @@ async fn test_listen_as_admin_stream() {
         authority_pubkey: user_authority.pubkey().to_string(),
         target_admin_pda: admin_pda.to_string(),
         communication_pubkey: Pubkey::new_unique().to_string(),
+        options: None,
     };
     let unsigned_tx_resp = client
         .prepare_user_create_profile(prep_user_req)
@@ -306,12 +322,14 @@ async fn test_listen_as_admin_stream() {
     println!("Triggered NewUserProfile event.");
 
     // Trigger an `IncomingUserCommand` event.
-    let command_payload = vec![1, 2, 3, 4, 5];
+    let command_payload = PayloadHeader::new(PayloadCodec::Raw, false).wrap(&[1, 2, 3, 4, 5]);
     let prep_dispatch_req = PrepareUserDispatchCommandRequest {
         authority_pubkey: user_authority.pubkey().to_string(),
         admin_profile_pda: admin_pda.to_string(),
         command_id: 123,
         payload: command_payload.clone(),
+        high_priority: false,
+        options: None,
     };
     let unsigned_tx_resp = client
         .prepare_user_dispatch_command(prep_dispatch_req)
@@ -380,6 +398,7 @@ async fn test_stop_listener() {
     // === 2. Act: Start listening ===
     let req = ListenAsAdminRequest {
         admin_pubkey: admin_pubkey.to_string(),
+        ..Default::default()
     };
     let mut stream = client.listen_as_admin(req).await.unwrap().into_inner();
     println!("Stream started for {}", admin_pubkey);
@@ -0,0 +1,70 @@
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use w3b2_connector::config::ConnectorConfig;
+use w3b2_connector::rpc_pool::RpcPool;
+use w3b2_connector::storage::{MemoryStorage, Storage};
+use w3b2_connector::workers::EventManager;
+use w3b2_gateway::health::{self, ClusterProbe};
+
+/// Sends `GET path` and returns the status line and the body.
+async fn get(port: u16, path: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[tokio::test]
+async fn test_readyz_follows_sync_progress_and_rpc_health() {
+    // The node is unreachable, but the pool counts it healthy until a check fails.
+    let rpc_pool = RpcPool::new(
+        vec!["http://127.0.0.1:1".to_string()],
+        Duration::from_secs(60),
+        None,
+    );
+    let storage = Arc::new(MemoryStorage::new());
+    let (_runner, event_manager) = EventManager::new(
+        Arc::new(ConnectorConfig::default()),
+        Arc::new(rpc_pool.rpc_client(CommitmentConfig::default())),
+        storage.clone(),
+        16,
+        16,
+    );
+    let probes = vec![ClusterProbe {
+        name: "default".to_string(),
+        event_manager,
+        rpc_pool: rpc_pool.clone(),
+    }];
+    let port = portpicker::pick_unused_port().unwrap();
+    tokio::spawn(health::serve(([127, 0, 0, 1], port).into(), probes, 10));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Nothing is synchronized yet: alive, but not ready.
+    assert_eq!(get(port, "/healthz").await.0, "HTTP/1.1 200 OK");
+    let (status, body) = get(port, "/readyz").await;
+    assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+    assert!(body.contains("synchronizer has not seen the chain yet"));
+
+    storage.set_sync_state(100, "sig").await.unwrap();
+    let (status, body) = get(port, "/readyz").await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(
+        body,
+        "cluster 'default': 1 healthy RPC endpoint(s), synchronizer at slot 100 of 100 \
+         (0 behind, 10 allowed)\n"
+    );
+
+    assert_eq!(rpc_pool.check_health().await, 0);
+    assert_eq!(
+        get(port, "/readyz").await.0,
+        "HTTP/1.1 503 Service Unavailable"
+    );
+    assert_eq!(get(port, "/healthz").await.0, "HTTP/1.1 200 OK");
+    assert_eq!(get(port, "/metrics").await.0, "HTTP/1.1 404 Not Found");
+}
@@ -0,0 +1,147 @@
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tonic::Code;
+use w3b2_connector::config::ConnectorConfig;
+use w3b2_connector::rpc_pool::RpcPool;
+use w3b2_connector::storage::{MemoryStorage, Storage};
+use w3b2_connector::workers::EventManager;
+use w3b2_gateway::health::ClusterProbe;
+use w3b2_gateway::metrics::{self, GatewayMetrics, StreamKind};
+
+#[test]
+fn test_calls_are_counted_by_method_and_code() {
+    let metrics = GatewayMetrics::new();
+
+    metrics.record_call("GetBalances", Code::Ok, Duration::from_millis(20));
+    metrics.record_call("GetBalances", Code::Ok, Duration::from_millis(300));
+    metrics.record_call("GetBalances", Code::NotFound, Duration::from_millis(3));
+    let text = metrics.to_prometheus();
+
+    assert!(
+        text.contains("w3b2_gateway_grpc_requests_total{method=\"GetBalances\",code=\"Ok\"} 2")
+    );
+    assert!(
+        text.contains(
+            "w3b2_gateway_grpc_requests_total{method=\"GetBalances\",code=\"NotFound\"} 1"
+        )
+    );
+    // Buckets are cumulative: 3ms and 20ms fall under 25ms, all three under 0.5s.
+    assert!(text.contains(
+        "w3b2_gateway_grpc_request_duration_seconds_bucket{method=\"GetBalances\",le=\"0.025\"} 2"
+    ));
+    assert!(text.contains(
+        "w3b2_gateway_grpc_request_duration_seconds_bucket{method=\"GetBalances\",le=\"0.5\"} 3"
+    ));
+    assert!(
+        text.contains("w3b2_gateway_grpc_request_duration_seconds_count{method=\"GetBalances\"} 3")
+    );
+}
+
+#[test]
+fn test_only_gateway_methods_get_their_own_label() {
+    let service = "/w3b2.bridge.gateway.BridgeGatewayService";
+
+    assert_eq!(
+        metrics::method_label(&format!("{service}/GetBalances")),
+        "GetBalances"
+    );
+    assert_eq!(
+        metrics::method_label(&format!("{service}/ListenAsUser")),
+        "ListenAsUser"
+    );
+    // Paths a client makes up all share one label.
+    assert_eq!(
+        metrics::method_label(&format!("{service}/NoSuchMethod")),
+        metrics::UNKNOWN_METHOD
+    );
+    assert_eq!(
+        metrics::method_label("/other.Service/GetBalances"),
+        metrics::UNKNOWN_METHOD
+    );
+}
+
+#[test]
+fn test_streams_stay_open_until_their_guard_drops() {
+    let metrics = Arc::new(GatewayMetrics::new());
+
+    let user = metrics.stream_opened(StreamKind::User);
+    let admin = metrics.stream_opened(StreamKind::Admin);
+    assert_eq!(metrics.open_streams(StreamKind::User), 1);
+    assert!(
+        metrics
+            .to_prometheus()
+            .contains("w3b2_gateway_open_streams{kind=\"admin\"} 1")
+    );
+
+    drop(user);
+    drop(admin);
+    assert_eq!(metrics.open_streams(StreamKind::User), 0);
+    assert_eq!(metrics.open_streams(StreamKind::Admin), 0);
+}
+
+#[test]
+fn test_submits_are_counted_by_result() {
+    let metrics = GatewayMetrics::new();
+
+    metrics.record_submit(true);
+    metrics.record_submit(true);
+    metrics.record_submit(false);
+    let text = metrics.to_prometheus();
+
+    assert!(text.contains("w3b2_gateway_submits_total{result=\"ok\"} 2"));
+    assert!(text.contains("w3b2_gateway_submits_total{result=\"failed\"} 1"));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_reports_every_cluster() {
+    let mut probes = Vec::new();
+    for (name, slot) in [("default", 100), ("devnet", 40)] {
+        let rpc_pool = RpcPool::new(
+            vec!["http://127.0.0.1:1".to_string()],
+            Duration::from_secs(60),
+            None,
+        );
+        let storage = Arc::new(MemoryStorage::new());
+        storage.set_sync_state(slot, "sig").await.unwrap();
+        let (_runner, event_manager) = EventManager::new(
+            Arc::new(ConnectorConfig::default()),
+            Arc::new(rpc_pool.rpc_client(CommitmentConfig::default())),
+            storage,
+            16,
+            16,
+        );
+        probes.push(ClusterProbe {
+            name: name.to_string(),
+            event_manager,
+            rpc_pool,
+        });
+    }
+    let port = portpicker::pick_unused_port().unwrap();
+    tokio::spawn(metrics::serve(
+        ([127, 0, 0, 1], port).into(),
+        Arc::new(GatewayMetrics::new()),
+        probes,
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("w3b2_sync_processed_slot{cluster=\"default\"} 100\n"));
+    assert!(response.contains("w3b2_sync_processed_slot{cluster=\"devnet\"} 40\n"));
+    assert_eq!(
+        response
+            .matches("# TYPE w3b2_sync_processed_slot gauge")
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,53 @@
+use w3b2_gateway::grpc::logging::RequestLogger;
+use w3b2_gateway::grpc::proto::w3b2::bridge::gateway::{
+    PrepareUserDispatchCommandRequest, SubmitTransactionRequest,
+};
+
+fn logger(fields: &[&str]) -> RequestLogger {
+    RequestLogger::new(fields.iter().map(|field| field.to_string()), 1.0)
+}
+
+#[test]
+fn test_redacts_only_the_named_fields() {
+    let request = PrepareUserDispatchCommandRequest {
+        authority_pubkey: "Auth1111".to_string(),
+        admin_profile_pda: "Admin111".to_string(),
+        command_id: 7,
+        payload: vec![1, 2, 3, 4],
+        ..Default::default()
+    };
+
+    let redacted = logger(&["payload"]).redact(&format!("{:?}", request));
+
+    assert!(redacted.contains("authority_pubkey: \"Auth1111\""));
+    assert!(redacted.contains("command_id: 7"));
+    assert!(redacted.contains("payload: <redacted>"));
+    assert!(!redacted.contains("[1, 2, 3, 4]"));
+}
+
+#[test]
+fn test_field_names_inside_strings_are_left_alone() {
+    let debug = r#"Request { note: "payload: [9, 9], tx: 1", tx: [5, 6], nested: Some(Inner { tx: "x" }) }"#;
+
+    let redacted = logger(&["tx"]).redact(debug);
+
+    assert_eq!(
+        redacted,
+        r#"Request { note: "payload: [9, 9], tx: 1", tx: <redacted>, nested: Some(Inner { tx: <redacted> }) }"#
+    );
+}
+
+#[test]
+fn test_redacts_transaction_bytes() {
+    let request = SubmitTransactionRequest {
+        signed_tx: vec![42; 8],
+        versioned: false,
+    };
+
+    let redacted = logger(&["signed_tx"]).redact(&format!("{:?}", request));
+
+    assert_eq!(
+        redacted,
+        "SubmitTransactionRequest { signed_tx: <redacted>, versioned: false }"
+    );
+}
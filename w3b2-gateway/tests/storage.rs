@@ -0,0 +1,223 @@
+mod common;
+
+use common::action;
+use solana_sdk::pubkey::Pubkey;
+use w3b2_connector::events::EventEnvelope;
+use w3b2_connector::storage::{MemoryStorage, Snapshot, Storage, StorageError};
+use w3b2_gateway::storage::SledStorage;
+
+fn positions(events: Vec<EventEnvelope>) -> Vec<(u64, u32)> {
+    events.iter().map(|e| (e.slot, e.index)).collect()
+}
+
+#[tokio::test]
+async fn test_sled_storage_event_history() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = SledStorage::new(db);
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+
+    storage
+        .append_event(&action(alice, 20, "b", 0))
+        .await
+        .unwrap();
+    storage.append_event(&action(bob, 9, "a", 0)).await.unwrap();
+    storage
+        .append_event(&action(alice, 9, "a", 1))
+        .await
+        .unwrap();
+    storage
+        .append_event(&action(alice, 9, "a", 1))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        positions(storage.events_by_slot_range(0, 100).await.unwrap()),
+        vec![(9, 0), (9, 1), (20, 0)]
+    );
+    assert_eq!(
+        positions(storage.events_by_slot_range(9, 9).await.unwrap()),
+        vec![(9, 0), (9, 1)]
+    );
+    assert_eq!(
+        positions(storage.events_by_signature("a").await.unwrap()),
+        vec![(9, 0), (9, 1)]
+    );
+    assert_eq!(
+        positions(storage.events_by_pubkey(&alice, 0, 100).await.unwrap()),
+        vec![(9, 1), (20, 0)]
+    );
+    assert_eq!(
+        positions(storage.events_by_pubkey(&bob, 10, 100).await.unwrap()),
+        vec![]
+    );
+}
+
+#[tokio::test]
+async fn test_recent_events_are_the_latest_of_a_pubkey() {
+    let sled = SledStorage::new(sled::Config::new().temporary(true).open().unwrap());
+    let memory = MemoryStorage::new();
+    let alice = Pubkey::new_unique();
+    let bob = Pubkey::new_unique();
+    let storages: [&dyn Storage; 2] = [&sled, &memory];
+
+    for storage in storages {
+        for (actor, slot, signature, index) in [
+            (alice, 5, "a", 0),
+            (alice, 7, "b", 0),
+            (bob, 8, "c", 0),
+            (alice, 7, "b", 1),
+            (alice, 9, "d", 0),
+        ] {
+            storage
+                .append_event(&action(actor, slot, signature, index))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            positions(storage.recent_events_by_pubkey(&alice, 3).await.unwrap()),
+            vec![(7, 0), (7, 1), (9, 0)]
+        );
+        assert_eq!(
+            positions(storage.recent_events_by_pubkey(&alice, 10).await.unwrap()),
+            vec![(5, 0), (7, 0), (7, 1), (9, 0)]
+        );
+        assert_eq!(
+            positions(storage.recent_events_by_pubkey(&bob, 0).await.unwrap()),
+            vec![]
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_sled_storage_pruning_removes_index_entries() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = SledStorage::new(db);
+    let actor = Pubkey::new_unique();
+    for slot in [10, 20, 30, 40] {
+        storage
+            .append_event(&action(actor, slot, &format!("sig-{slot}"), 0))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(storage.prune_events_before(20).await.unwrap(), 1);
+    assert_eq!(storage.prune_events_to_count(2).await.unwrap(), 1);
+
+    assert_eq!(
+        positions(storage.events_by_pubkey(&actor, 0, u64::MAX).await.unwrap()),
+        vec![(30, 0), (40, 0)]
+    );
+    assert!(storage
+        .events_by_signature("sig-10")
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(storage
+        .events_by_signature("sig-20")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_sled_storage_remove_events_by_signature() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = SledStorage::new(db);
+    let actor = Pubkey::new_unique();
+    storage
+        .append_event(&action(actor, 5, "dropped", 0))
+        .await
+        .unwrap();
+    storage
+        .append_event(&action(actor, 5, "dropped", 1))
+        .await
+        .unwrap();
+    storage
+        .append_event(&action(actor, 6, "kept", 0))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        storage.remove_events_by_signature("dropped").await.unwrap(),
+        2
+    );
+
+    assert_eq!(
+        positions(storage.events_by_pubkey(&actor, 0, u64::MAX).await.unwrap()),
+        vec![(6, 0)]
+    );
+    assert!(storage
+        .events_by_signature("dropped")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_sled_storage_cursors() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = SledStorage::new(db);
+    let cursor = action(Pubkey::new_unique(), 7, "sig", 3).cursor();
+
+    assert_eq!(storage.get_cursor("svc").await.unwrap(), None);
+    storage.set_cursor("svc", &cursor).await.unwrap();
+    assert_eq!(storage.get_cursor("svc").await.unwrap(), Some(cursor));
+    assert_eq!(storage.get_cursor("other").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_sled_storage_reports_corrupted_cursor() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    db.open_tree("cursors")
+        .unwrap()
+        .insert("svc", b"garbage".as_slice())
+        .unwrap();
+    let storage = SledStorage::new(db);
+
+    assert!(matches!(
+        storage.get_cursor("svc").await,
+        Err(StorageError::Corrupted(_))
+    ));
+    assert!(matches!(
+        storage.cursors().await,
+        Err(StorageError::Corrupted(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sled_storage_snapshot_to_memory() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let storage = SledStorage::new(db);
+    let actor = Pubkey::new_unique();
+    storage.set_sync_state(12, "sig").await.unwrap();
+    storage
+        .append_event(&action(actor, 12, "sig", 0))
+        .await
+        .unwrap();
+    storage
+        .set_cursor("b", &action(actor, 12, "sig", 0).cursor())
+        .await
+        .unwrap();
+    storage
+        .set_cursor("a", &action(actor, 12, "sig", 0).cursor())
+        .await
+        .unwrap();
+
+    let snapshot = storage.export_snapshot(true).await.unwrap();
+    let ids: Vec<_> = snapshot.cursors.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"]);
+
+    let memory = MemoryStorage::new();
+    memory
+        .import_snapshot(&Snapshot::from_bytes(&snapshot.to_bytes()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(memory.get_last_slot().await.unwrap(), 12);
+    assert_eq!(
+        positions(memory.events_by_pubkey(&actor, 0, u64::MAX).await.unwrap()),
+        vec![(12, 0)]
+    );
+    assert_eq!(memory.cursors().await.unwrap(), snapshot.cursors);
+}